@@ -0,0 +1,226 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use ghd::{
+    db::DB,
+    gh::{
+        filters::IssueFilter,
+        prs, search,
+        types::{Issue, PullRequest},
+    },
+    page::PageRequest,
+};
+
+/// Number of Pull Requests in the generated fixture, spread over
+/// `FIXTURE_USERS` authors and `FIXTURE_REPOS` repositories.
+///
+const FIXTURE_PRS: i64 = 5000;
+const FIXTURE_USERS: i64 = 50;
+const FIXTURE_REPOS: i64 = 20;
+
+/// Generate `n` Pull Requests shaped like those obtained from GitHub,
+/// deterministically, so runs are comparable.
+///
+/// # Arguments
+///
+/// * `n` - Number of Pull Requests to generate.
+/// * `generation` - Distinguishes repeated fixtures for the same Pull
+///   Requests, as if they had been updated since.
+///
+fn generate_prs(n: i64, generation: i64) -> Vec<PullRequest> {
+    let base = chrono::Utc::now() - chrono::Duration::days(365);
+
+    (0..n)
+        .map(|i| {
+            let author_id = 1 + i % FIXTURE_USERS;
+            let repo = i % FIXTURE_REPOS;
+            let created_at = base + chrono::Duration::minutes(i * 60);
+            PullRequest {
+                issue: Issue {
+                    id: 1_000_000 + i,
+                    number: 1 + i / FIXTURE_REPOS,
+                    title: format!(
+                        "component-{}: change #{} ({})",
+                        repo, i, generation
+                    ),
+                    author: format!("user-{}", author_id),
+                    author_id,
+                    author_is_bot: i % 17 == 0,
+                    url: format!(
+                        "https://github.com/org/repo-{}/pull/{}",
+                        repo, i
+                    ),
+                    repo_owner: String::from("org"),
+                    repo_name: format!("repo-{}", repo),
+                    state: String::from(if i % 5 == 0 {
+                        "merged"
+                    } else {
+                        "open"
+                    }),
+                    created_at,
+                    updated_at: created_at
+                        + chrono::Duration::minutes(generation * 10 + i % 30),
+                    closed_at: None,
+                    is_pull_request: true,
+                    last_viewed: None,
+                    labels: vec![format!("area/{}", i % 7)],
+                    body: String::new(),
+                    unknown_raw: None,
+                    fixes: vec![],
+                    first_response_at: None,
+                    references: vec![],
+                },
+                is_draft: i % 11 == 0,
+                review_decision: String::from(match i % 3 {
+                    0 => "approved",
+                    1 => "changes_requested",
+                    _ => "review_required",
+                }),
+                merged_at: None,
+                head_ref: format!("wip/change-{}", i),
+                author_association: String::from("member"),
+                review_requests: vec![1 + (i + 1) % FIXTURE_USERS],
+                latest_reviews: vec![],
+                head_committed_at: Some(created_at.timestamp()),
+                ci_state: Some(String::from("success")),
+            }
+        })
+        .collect()
+}
+
+/// Set up a scratch database at `path`, with the fixture's users and a
+/// first generation of its Pull Requests.
+///
+async fn setup_db(path: &std::path::PathBuf) -> DB {
+    let mut db = DB::new(&path);
+    db.setup().await;
+    db.connect().await;
+
+    for id in 1..=FIXTURE_USERS {
+        sqlx::query(
+            "INSERT INTO users (id, login, avatar_url, name) VALUES (?, ?, '', '')",
+        )
+        .bind(&id)
+        .bind(format!("user-{}", id))
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    upsert(&db, &generate_prs(FIXTURE_PRS, 0)).await;
+    db
+}
+
+/// Upsert `prs` as the sync would, in a single transaction.
+///
+async fn upsert(db: &DB, prs: &Vec<PullRequest>) {
+    let mut tx = db.pool().begin().await.unwrap();
+    prs::consume_issues(&mut tx, &1, &vec![], &prs)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+}
+
+/// Benchmark the data layer against a scratch database populated with
+/// generated fixtures. Criterion compares each run against the previous
+/// one, reporting regressions; `--save-baseline <name>` and
+/// `--baseline <name>` compare against a named run instead.
+///
+fn data_layer(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("ghd-bench-{}.db", chrono::Utc::now().timestamp()));
+    let db = rt.block_on(setup_db(&path));
+    let login = String::from("user-1");
+
+    let mut generation = 0;
+    c.bench_function("upsert pipeline", |b| {
+        b.to_async(&rt).iter_batched(
+            || {
+                generation += 1;
+                generate_prs(FIXTURE_PRS, generation)
+            },
+            |prs| {
+                let db = &db;
+                async move { upsert(&db, &prs).await }
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    c.bench_function("list by author", |b| {
+        b.to_async(&rt).iter(|| async {
+            let page = PageRequest::new(&None, &None).unwrap();
+            prs::get_prs_by_author(&db, &login, &page).await.unwrap();
+        })
+    });
+
+    c.bench_function("list involved", |b| {
+        b.to_async(&rt).iter(|| async {
+            let page = PageRequest::new(&None, &None).unwrap();
+            prs::get_involved_prs(&db, &login, &None, &page)
+                .await
+                .unwrap();
+        })
+    });
+
+    c.bench_function("list filtered", |b| {
+        b.to_async(&rt).iter(|| async {
+            let filter = IssueFilter {
+                repo_owner: Some(String::from("org")),
+                repo_name: Some(String::from("repo-3")),
+                author: None,
+                involves: None,
+                label: Some(String::from("area/3")),
+                state: Some(String::from("open")),
+                is_pull_request: Some(true),
+                updated_before: None,
+                unviewed_only: None,
+                my_turn: None,
+                tag: None,
+                language: None,
+                topic: None,
+                size: None,
+            };
+            let page = PageRequest::new(&None, &None).unwrap();
+            prs::get_filtered_prs(&db, &filter, &page).await.unwrap();
+        })
+    });
+
+    // exact matches fill the page; typos fall back to fuzzy matching.
+    for (name, query) in [
+        ("search", "component-3 change"),
+        ("search fuzzy", "compnent-3 chnage"),
+    ] {
+        let query = String::from(query);
+        c.bench_function(name, |b| {
+            b.to_async(&rt).iter(|| async {
+                let page = PageRequest::new(&None, &None).unwrap();
+                search::search(&db, &query, &page).await.unwrap();
+            })
+        });
+    }
+
+    rt.block_on(db.pool().close());
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = data_layer
+}
+criterion_main!(benches);
@@ -14,12 +14,14 @@
 
 use crate::{
     db::DB,
+    errors::GHDError,
     events,
-    gh::{self, Github},
-    ManagedState,
+    gh::{self, types::Repository, Github},
+    mail, notify, throttle, updates, vault, ManagedState,
 };
 use tauri::Manager;
 
+pub mod supervisor;
 mod types;
 
 pub struct BGTask {}
@@ -33,31 +35,80 @@ impl BGTask {
         let window = app.get_window("main").unwrap();
         let mstate = app.try_state::<ManagedState>().unwrap();
 
+        gh::limits::load(&mstate.state().await.db).await;
+        gh::gql::catalog::reconcile_versions(&mstate.state().await.db).await;
+
+        let interrupted =
+            gh::outbox::get_interrupted(&mstate.state().await.db).await;
+        if !interrupted.is_empty() {
+            events::emit_outbox_interrupted(&window, &interrupted);
+        }
+
         let mut n = 1;
+        let mut last_status = None;
+        let mut next_pass_at = 0;
         loop {
             let state = &mstate.state().await;
             let db = &state.db;
             let _cfg = &state.config;
             let gh = &state.gh;
 
+            // paused or slowed down on the user's or the system's say-so;
+            // e.g., while on a metered connection or on battery.
+            let status = throttle::get_status(&db).await;
+            if last_status.as_ref() != Some(&status) {
+                events::emit_sync_status(&window, &status);
+                last_status = Some(status.clone());
+            }
+            let now = chrono::Utc::now().timestamp();
+            if status.paused || now < next_pass_at {
+                self.sleep_for_a_bit().await;
+                continue;
+            }
+            next_pass_at = now + status.delay_secs;
+
             println!("background task iteration #{}", n);
             window.emit("iteration", n).unwrap();
             n += 1;
 
+            // notifications are sealed within the syncs' transactions, with
+            // keys obtained beforehand.
+            vault::load_keys(&db).await;
+
+            // without a token, only follow public users and repositories,
+            // as slowly as the unauthenticated rate limit requires.
             if !has_token(&gh, &db).await {
+                if let Some(target) = gh::anon::next_due(&db).await {
+                    match gh.refresh_anon_target(&db, &target).await {
+                        Ok(()) => events::emit_anon_update(&window, &target),
+                        Err(err) => {
+                            println!(
+                                "error refreshing {} '{}': {:?}",
+                                target.kind, target.name, err,
+                            );
+                        }
+                    }
+                    gh::ledger::flush(&db).await;
+                }
                 self.sleep_for_a_bit().await;
                 continue;
             }
 
             let to_refresh = gh::refresh::get_to_refresh_users(&db).await;
+            let mut changed = false;
             for user in &to_refresh {
                 println!("should refresh user '{}'", user.login);
                 match gh.refresh_user(&db, &user.login).await {
-                    Ok(true) => {
+                    Ok(diff) if !diff.is_empty() => {
                         println!("refreshed user '{}'", user.login);
-                        events::emit_user_data_update(&window, &user.login);
+                        changed = true;
+                        events::emit_user_data_update(
+                            &window,
+                            &user.login,
+                            &diff,
+                        );
                     }
-                    Ok(false) => {}
+                    Ok(_) => {}
                     Err(err) => {
                         println!(
                             "error refreshing user '{}': {:?}",
@@ -67,6 +118,119 @@ impl BGTask {
                 }
             }
 
+            let tracked = match gh::users::get_tracked_users(&db).await {
+                Ok(res) => res,
+                Err(err) => {
+                    panic!("Unable to obtain tracked users: {:?}", err);
+                }
+            };
+            for user in &tracked {
+                if !gh::reconcile::should_reconcile_user(&db, &user.id).await {
+                    continue;
+                }
+                match gh.reconcile_user(&db, &user.login).await {
+                    Ok(diff) if !diff.is_empty() => {
+                        println!("reconciled user '{}'", user.login);
+                        events::emit_user_data_update(
+                            &window,
+                            &user.login,
+                            &diff,
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        println!(
+                            "error reconciling user '{}': {:?}",
+                            user.login, err,
+                        );
+                    }
+                }
+            }
+
+            if changed {
+                gh::lint::run(&db).await;
+            }
+
+            for prid in &gh::reconcile::get_due(&db).await {
+                match gh.verify_pull(&db, &prid).await {
+                    Ok(Some(change)) => {
+                        events::emit_pr_correction(&window, &change);
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        println!("error verifying pr {}: {:?}", prid, err);
+                    }
+                }
+            }
+            gh::reviewtime::stop_idle(&db).await;
+
+            // while away, only keep the dashboard itself up to date.
+            if !gh::away::is_away(&db).await {
+                run_extra_syncs(&window, &db, &gh).await;
+                if gh::patches::is_enabled(&db).await {
+                    let dir = state.paths.data_dir.join("patches");
+                    match gh.refresh_patches(&db, &dir).await {
+                        Ok(0) => {}
+                        Ok(n) => println!("downloaded {} patches", n),
+                        Err(err) => {
+                            println!("error downloading patches: {:?}", err);
+                        }
+                    }
+                }
+                gh::reminders::run(&db).await;
+                gh::sla::run(&db).await;
+
+                let deliveries = notify::get_deliveries(&db).await;
+                for notification in &deliveries.immediate {
+                    events::emit_notification(&window, &notification);
+                    notify::mark_delivered(&db, &notification.id).await;
+                }
+                if !deliveries.digest.is_empty() {
+                    events::emit_notification_digest(
+                        &window,
+                        &deliveries.digest,
+                    );
+                    for notification in &deliveries.digest {
+                        notify::mark_delivered(&db, &notification.id).await;
+                    }
+                }
+
+                if mail::is_digest_due(&db).await {
+                    if let Err(err) = mail::send_digest(&db).await {
+                        println!("error mailing daily digest: {:?}", err);
+                    }
+                }
+            }
+
+            if updates::should_check(&db).await {
+                let was_available = updates::get_status(&db).await.is_available;
+                match updates::check(&db).await {
+                    Ok(status) if status.is_available && !was_available => {
+                        events::emit_update_available(&window, &status);
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        println!("error checking for updates: {:?}", err);
+                    }
+                }
+            }
+
+            gh::ledger::flush(&db).await;
+
+            // a runaway loop would get the token blocked; stop syncing for a
+            // while and let the user know.
+            let anomalies = gh::anomalies::check(&db).await;
+            for anomaly in &anomalies {
+                events::emit_usage_anomaly(&window, &anomaly);
+            }
+            if !anomalies.is_empty() {
+                throttle::pause_for_anomaly(
+                    &db,
+                    gh::anomalies::ANOMALY_PAUSE_MINUTES,
+                )
+                .await;
+            }
+
             let users = match gh::users::get_tracked_users(&db).await {
                 Ok(res) => res,
                 Err(err) => {
@@ -87,9 +251,418 @@ impl BGTask {
     }
 }
 
+/// Enriches Pull Requests with their files, commits, and blockers, one job at
+/// a time and most visible first, separately from the listing pass done by
+/// `BGTask`. The state is only held while running a job, so enrichment never
+/// holds up refreshing the dashboard for long.
+///
+pub struct EnrichTask {}
+
+impl EnrichTask {
+    pub fn new() -> Self {
+        EnrichTask {}
+    }
+
+    pub async fn run(self: &mut Self, app: tauri::AppHandle) {
+        let mstate = app.try_state::<ManagedState>().unwrap();
+
+        loop {
+            let slowed;
+            let idle = {
+                let state = &mstate.state().await;
+                let db = &state.db;
+                let gh = &state.gh;
+
+                let status = throttle::get_status(&db).await;
+                slowed = status.delay_secs > 0;
+                if !has_token(&gh, &db).await
+                    || gh::away::is_away(&db).await
+                    || status.paused
+                {
+                    true
+                } else {
+                    match gh::enrich::next_job(&db).await {
+                        Ok(Some(job))
+                            if !gh::enrich::has_budget(&db, &job).await =>
+                        {
+                            println!("rate limit low, holding off enrichment");
+                            true
+                        }
+                        Ok(Some(job)) => {
+                            let now = chrono::Utc::now();
+                            match gh.enrich_pull(&db, &job).await {
+                                Ok(()) => false,
+                                // try again once the rate limit resets.
+                                Err(GHDError::RateLimitedError) => true,
+                                Err(err) => {
+                                    println!(
+                                        "error enriching {:?} for pr {}/{}#{}: {:?}",
+                                        job.kind,
+                                        job.pr.repo_owner,
+                                        job.pr.repo_name,
+                                        job.pr.number,
+                                        err,
+                                    );
+                                    gh::enrich::mark_attempted(&db, &job, &now)
+                                        .await;
+                                    false
+                                }
+                            }
+                        }
+                        Ok(None) => true,
+                        Err(err) => {
+                            println!(
+                                "error obtaining enrichment job: {:?}",
+                                err
+                            );
+                            true
+                        }
+                    }
+                }
+            };
+
+            let secs = if idle || slowed {
+                ENRICH_IDLE_SECS
+            } else {
+                ENRICH_JOB_SECS
+            };
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs)).await;
+        }
+    }
+}
+
+/// Seconds to wait between enrichment jobs, leaving room for other tasks to
+/// get at the state.
+///
+const ENRICH_JOB_SECS: u64 = 1;
+
+/// Seconds to wait before looking for enrichment jobs again once there are
+/// none left.
+///
+const ENRICH_IDLE_SECS: u64 = 10;
+
 async fn has_token(gh: &Github, db: &DB) -> bool {
     match &gh.get_token(&db).await {
         Ok(_) => true,
         Err(_) => false,
     }
 }
+
+/// Run the syncs that aren't essential to keep the dashboard going, i.e.
+/// everything but refreshing tracked users and enriching their Pull Requests.
+/// These are paused while away.
+///
+async fn run_extra_syncs(window: &tauri::Window, db: &DB, gh: &Github) {
+    let accounts = match gh::accounts::get_accounts(&db).await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain accounts: {:?}", err);
+        }
+    };
+    for account in &accounts {
+        if !gh::orgs::should_refresh(&db, &account.id).await {
+            continue;
+        }
+        match gh.refresh_orgs(&db, &account.id).await {
+            Ok(()) => {
+                println!("refreshed orgs for '{}'", account.login);
+                events::emit_orgs_update(&window, &account.login);
+            }
+            Err(err) => {
+                println!(
+                    "error refreshing orgs for '{}': {:?}",
+                    account.login, err,
+                );
+            }
+        }
+    }
+
+    for scope in &gh.get_org_scopes(&db).await {
+        if !gh::orgscope::should_refresh(&db, &scope.org_id).await {
+            continue;
+        }
+        match gh.refresh_org_scope(&db, &scope).await {
+            Ok(()) => {
+                println!("refreshed org-wide prs for '{}'", scope.org_login);
+                events::emit_org_scope_update(&window, &scope.org_login);
+            }
+            Err(err) => {
+                println!(
+                    "error refreshing org-wide prs for '{}': {:?}",
+                    scope.org_login, err,
+                );
+            }
+        }
+    }
+
+    for account in &accounts {
+        if !gh::pending::should_refresh(&db, &account.id).await {
+            continue;
+        }
+        if let Err(err) = gh
+            .refresh_pending_reviews(&db, &account.id, &account.login)
+            .await
+        {
+            println!(
+                "error refreshing pending reviews for '{}': {:?}",
+                account.login, err,
+            );
+        }
+    }
+
+    for account in &accounts {
+        if !gh::followups::should_refresh(&db, &account.id).await {
+            continue;
+        }
+        if let Err(err) = gh
+            .refresh_follow_ups(&db, &account.id, &account.login)
+            .await
+        {
+            println!(
+                "error refreshing follow-ups for '{}': {:?}",
+                account.login, err,
+            );
+        }
+    }
+
+    // teams shared by several accounts are only refreshed once, by whoever
+    // gets to them first.
+    for account in &accounts {
+        let teams = match gh::orgs::get_teams(&db, &account.id).await {
+            Ok(res) => res,
+            Err(_) => continue,
+        };
+        for team in &teams {
+            if !gh::mentions::should_refresh(&db, &team.id).await {
+                continue;
+            }
+            if let Err(err) =
+                gh.refresh_team_mentions(&db, &account.id, &team).await
+            {
+                println!(
+                    "error refreshing mentions of '{}/{}': {:?}",
+                    team.org_login, team.slug, err,
+                );
+            }
+        }
+    }
+
+    for account in &accounts {
+        if !gh::profile::should_refresh(&db, &account.id).await {
+            continue;
+        }
+        if let Err(err) = gh.refresh_profile_stats(&db, &account.id).await {
+            println!(
+                "error refreshing profile stats for '{}': {:?}",
+                account.login, err,
+            );
+        }
+    }
+
+    for history in &gh::backfill::get_pending(&db).await {
+        match gh.backfill_user(&db, &history).await {
+            Ok(res) => events::emit_backfill_progress(&window, &res),
+            Err(err) => {
+                println!(
+                    "error backfilling user '{}': {:?}",
+                    history.login, err,
+                );
+            }
+        }
+    }
+
+    let repos = get_synced_repos(&db).await;
+    // check for renames along with branches, so as not to do it too often.
+    for repo in &repos {
+        if !gh::repos::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        match gh.track_repo_rename(&db, &repo.owner, &repo.name).await {
+            Ok(Some((owner, name))) => {
+                events::emit_repo_renamed(
+                    &window,
+                    &format!("{}/{}", repo.owner, repo.name),
+                    &format!("{}/{}", owner, name),
+                );
+            }
+            Ok(None) => {}
+            Err(err) => {
+                println!(
+                    "error checking rename of '{}/{}': {:?}",
+                    repo.owner, repo.name, err,
+                );
+            }
+        }
+    }
+
+    let repos = get_synced_repos(&db).await;
+    for repo in &repos {
+        if !gh::repos::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        match gh.refresh_repo_branches(&db, &repo.owner, &repo.name).await {
+            Ok(()) => {
+                gh::quarantine::record_success(&db, &repo.owner, &repo.name)
+                    .await;
+                events::emit_branches_update(&window, &repo.owner, &repo.name);
+            }
+            Err(err) => {
+                println!(
+                    "error refreshing branches for '{}/{}': {:?}",
+                    repo.owner, repo.name, err,
+                );
+                gh::quarantine::record_failure(
+                    &db,
+                    &repo.owner,
+                    &repo.name,
+                    &err,
+                )
+                .await;
+            }
+        }
+    }
+
+    for repo in &repos {
+        if !gh::advisories::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        match gh.refresh_advisories(&db, &repo.owner, &repo.name).await {
+            Ok(()) => {
+                events::emit_advisories_update(
+                    &window,
+                    &repo.owner,
+                    &repo.name,
+                );
+            }
+            Err(err) => {
+                println!(
+                    "error refreshing advisories for '{}/{}': {:?}",
+                    repo.owner, repo.name, err,
+                );
+                gh::quarantine::record_failure(
+                    &db,
+                    &repo.owner,
+                    &repo.name,
+                    &err,
+                )
+                .await;
+            }
+        }
+    }
+
+    for repo in &repos {
+        if !gh::access::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        if let Err(err) =
+            gh.refresh_repo_access(&db, &repo.owner, &repo.name).await
+        {
+            println!(
+                "error refreshing permission on '{}/{}': {:?}",
+                repo.owner, repo.name, err,
+            );
+            gh::quarantine::record_failure(&db, &repo.owner, &repo.name, &err)
+                .await;
+        }
+    }
+
+    for repo in &repos {
+        if !gh::policy::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        if let Err(err) =
+            gh.refresh_merge_policy(&db, &repo.owner, &repo.name).await
+        {
+            println!(
+                "error refreshing merge policy of '{}/{}': {:?}",
+                repo.owner, repo.name, err,
+            );
+            gh::quarantine::record_failure(&db, &repo.owner, &repo.name, &err)
+                .await;
+        }
+    }
+
+    for repo in &repos {
+        if !gh::labels::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        if let Err(err) = gh.refresh_labels(&db, &repo.owner, &repo.name).await
+        {
+            println!(
+                "error refreshing labels of '{}/{}': {:?}",
+                repo.owner, repo.name, err,
+            );
+            gh::quarantine::record_failure(&db, &repo.owner, &repo.name, &err)
+                .await;
+        }
+    }
+
+    for repo in &repos {
+        if !gh::depupdates::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        if let Err(err) = gh
+            .refresh_dependency_updates(&db, &repo.owner, &repo.name)
+            .await
+        {
+            println!(
+                "error refreshing dependency updates for '{}/{}': {:?}",
+                repo.owner, repo.name, err,
+            );
+            gh::quarantine::record_failure(&db, &repo.owner, &repo.name, &err)
+                .await;
+        }
+    }
+
+    for repo in &repos {
+        if !gh::releases::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        if let Err(err) =
+            gh.refresh_releases(&db, &repo.owner, &repo.name).await
+        {
+            println!(
+                "error refreshing releases for '{}/{}': {:?}",
+                repo.owner, repo.name, err,
+            );
+            gh::quarantine::record_failure(&db, &repo.owner, &repo.name, &err)
+                .await;
+        }
+    }
+
+    for repo in &repos {
+        if !gh::templates::should_refresh(&db, &repo.owner, &repo.name).await {
+            continue;
+        }
+        if let Err(err) =
+            gh.refresh_pr_template(&db, &repo.owner, &repo.name).await
+        {
+            println!(
+                "error refreshing template for '{}/{}': {:?}",
+                repo.owner, repo.name, err,
+            );
+            gh::quarantine::record_failure(&db, &repo.owner, &repo.name, &err)
+                .await;
+        }
+    }
+}
+
+/// Obtain the maintained repositories that should be synced, i.e. those
+/// that are neither paused nor quarantined.
+///
+async fn get_synced_repos(db: &DB) -> Vec<Repository> {
+    let repos = match gh::repos::get_repos(&db).await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain repos: {:?}", err);
+        }
+    };
+
+    let mut res = vec![];
+    for repo in repos {
+        if !gh::quarantine::is_held(&db, &repo.owner, &repo.name).await {
+            res.push(repo);
+        }
+    }
+    res
+}
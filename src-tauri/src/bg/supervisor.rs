@@ -0,0 +1,146 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, future::Future, sync::Mutex};
+
+/// Seconds to wait before restarting a task that just went down, doubled
+/// for each consecutive failure, up to `MAX_BACKOFF_SECS`.
+///
+const BASE_BACKOFF_SECS: u64 = 1;
+const MAX_BACKOFF_SECS: u64 = 5 * 60;
+
+/// Seconds a task must have been running for before going down for its
+/// failure not to count as consecutive to the previous one.
+///
+const HEALTHY_AFTER_SECS: i64 = 10 * 60;
+
+/// How a supervised task has been doing since start.
+///
+#[derive(Clone, serde::Serialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub started_at: i64,
+    pub restarts: i64,
+    pub panics: i64,
+    pub last_panic: Option<String>,
+    pub last_panic_at: Option<i64>,
+}
+
+/// Health of every supervised task, by name. Kept in memory, as it only
+/// concerns this run.
+///
+static HEALTH: Mutex<Option<HashMap<String, TaskHealth>>> = Mutex::new(None);
+
+/// Run the task produced by `make` until it ends, restarting it with backoff
+/// whenever it panics or returns, so one bad response can't silently stop
+/// it for good.
+///
+/// # Arguments
+///
+/// * `name` - The task's name, for logging and health reporting.
+/// * `make` - Produces a fresh instance of the task to run.
+///
+pub async fn supervise<F, Fut>(name: &str, make: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut failures: u32 = 0;
+    loop {
+        let started_at = chrono::Utc::now().timestamp();
+        update(name, |h| h.started_at = started_at);
+
+        let res = tokio::spawn(make()).await;
+        let ran_for = chrono::Utc::now().timestamp() - started_at;
+
+        match res {
+            Ok(()) => {
+                println!("task '{}' exited after {}s", name, ran_for);
+            }
+            Err(err) if err.is_panic() => {
+                let msg = panic_message(err.into_panic());
+                println!(
+                    "task '{}' panicked after {}s: {}",
+                    name, ran_for, msg
+                );
+                update(name, |h| {
+                    h.panics += 1;
+                    h.last_panic = Some(msg.clone());
+                    h.last_panic_at = Some(chrono::Utc::now().timestamp());
+                });
+            }
+            Err(err) => {
+                println!("task '{}' was cancelled: {}", name, err);
+                return;
+            }
+        };
+
+        failures = match ran_for >= HEALTHY_AFTER_SECS {
+            true => 0,
+            false => failures + 1,
+        };
+        let backoff = std::cmp::min(
+            MAX_BACKOFF_SECS,
+            BASE_BACKOFF_SECS << std::cmp::min(failures, 16),
+        );
+        println!("restarting task '{}' in {}s", name, backoff);
+        tokio::time::sleep(tokio::time::Duration::from_secs(backoff)).await;
+        update(name, |h| h.restarts += 1);
+    }
+}
+
+/// Obtain a panic's message, if it has one.
+///
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+/// Apply `f` to the health of task `name`, adding it if new.
+///
+fn update<F>(name: &str, f: F)
+where
+    F: FnOnce(&mut TaskHealth),
+{
+    let mut health = HEALTH.lock().unwrap();
+    let entry = health
+        .get_or_insert_with(HashMap::new)
+        .entry(name.to_string())
+        .or_insert_with(|| TaskHealth {
+            name: name.to_string(),
+            started_at: 0,
+            restarts: 0,
+            panics: 0,
+            last_panic: None,
+            last_panic_at: None,
+        });
+    f(entry);
+}
+
+/// Obtain the health of every supervised task, sorted by name.
+///
+pub fn get_health() -> Vec<TaskHealth> {
+    let health = HEALTH.lock().unwrap();
+    let mut res: Vec<TaskHealth> = match &*health {
+        Some(h) => h.values().cloned().collect(),
+        None => vec![],
+    };
+    res.sort_by(|a, b| a.name.cmp(&b.name));
+    res
+}
@@ -12,13 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::{
+    db::DB,
+    store::{ReadStore, Store},
+};
+
 #[derive(Debug)]
 pub enum ConfigError {
     SettingNotFoundError,
     TokenNotFoundError,
 }
 
-#[derive(sqlx::FromRow)]
+#[derive(sqlx::FromRow, serde::Serialize)]
 pub struct ConfigEntry {
     pub key: String,
     pub value: String,
@@ -31,3 +36,186 @@ impl Default for Config {
         Config {}
     }
 }
+
+/// Setting holding the IANA time zone dates are displayed in, e.g.
+/// `Europe/Lisbon`, or `local` for the system's.
+///
+pub const SETTING_TIMEZONE: &str = "display.timezone";
+
+/// Setting holding the BCP 47 locale dates are formatted for, e.g. `pt-PT`,
+/// or empty for the system's.
+///
+pub const SETTING_LOCALE: &str = "display.locale";
+
+/// Setting holding the hour cycle, `h12` or `h23`, or empty for the locale's.
+///
+pub const SETTING_HOUR_CYCLE: &str = "display.hour_cycle";
+
+/// Setting holding the date style, one of `short`, `medium`, or `long`.
+///
+pub const SETTING_DATE_STYLE: &str = "display.date_style";
+
+/// How dates are to be displayed. Times are always stored and passed around
+/// as UTC epoch timestamps; these only affect rendering.
+///
+#[derive(serde::Serialize)]
+pub struct DisplayPrefs {
+    pub timezone: String,
+    pub locale: String,
+    pub hour_cycle: String,
+    pub date_style: String,
+}
+
+/// Obtain how dates are to be displayed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_display_prefs(db: &DB) -> DisplayPrefs {
+    DisplayPrefs {
+        timezone: get_setting_or(&db, SETTING_TIMEZONE, "local").await,
+        locale: get_setting_or(&db, SETTING_LOCALE, "").await,
+        hour_cycle: get_setting_or(&db, SETTING_HOUR_CYCLE, "").await,
+        date_style: get_setting_or(&db, SETTING_DATE_STYLE, "medium").await,
+    }
+}
+
+/// Prefix for settings holding the UI state of a window, by window label;
+/// e.g. `ui.state.main`. The state itself is opaque JSON, up to the
+/// frontend.
+///
+pub const SETTING_UI_STATE_PREFIX: &str = "ui.state.";
+
+/// Obtain the value of setting `key`, or `ConfigError::SettingNotFoundError`
+/// if it has never been set.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+/// * `key` - The setting's key.
+///
+pub async fn get_setting<S: ReadStore>(
+    store: &S,
+    key: &str,
+) -> Result<String, ConfigError> {
+    match store.get_setting(&key).await {
+        Some(res) => Ok(res),
+        None => Err(ConfigError::SettingNotFoundError),
+    }
+}
+
+/// Obtain the value of setting `key`, or `default` if it has never been set.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+/// * `key` - The setting's key.
+/// * `default` - The value to return if the setting has never been set.
+///
+pub async fn get_setting_or<S: ReadStore>(
+    store: &S,
+    key: &str,
+    default: &str,
+) -> String {
+    match get_setting(store, &key).await {
+        Ok(res) => res,
+        Err(ConfigError::SettingNotFoundError) => String::from(default),
+        Err(err) => {
+            panic!("Unexpected error obtaining setting '{}': {:?}", key, err);
+        }
+    }
+}
+
+/// Set setting `key` to `value`.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+/// * `key` - The setting's key.
+/// * `value` - The setting's new value.
+///
+pub async fn set_setting<S: Store>(store: &S, key: &str, value: &str) {
+    store.set_setting(&key, &value).await;
+}
+
+/// Obtain all settings that have been set.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+///
+pub async fn get_settings<S: ReadStore>(store: &S) -> Vec<ConfigEntry> {
+    store
+        .get_settings("")
+        .await
+        .into_iter()
+        .map(|(key, value)| ConfigEntry { key, value })
+        .collect()
+}
+
+/// Save the UI state of window `window`, replacing whatever was saved.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+/// * `window` - The window's label.
+/// * `state` - The window's UI state.
+///
+pub async fn save_ui_state<S: Store>(
+    store: &S,
+    window: &str,
+    state: &serde_json::Value,
+) {
+    let key = format!("{}{}", SETTING_UI_STATE_PREFIX, window);
+    set_setting(store, &key, &state.to_string()).await;
+}
+
+/// Obtain the saved UI state of window `window`, if any.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+/// * `window` - The window's label.
+///
+pub async fn load_ui_state<S: ReadStore>(
+    store: &S,
+    window: &str,
+) -> Option<serde_json::Value> {
+    let key = format!("{}{}", SETTING_UI_STATE_PREFIX, window);
+    let value = get_setting(store, &key).await.ok()?;
+    match serde_json::from_str(&value) {
+        Ok(res) => Some(res),
+        Err(err) => {
+            println!("unable to parse ui state of '{}': {}", window, err);
+            None
+        }
+    }
+}
+
+/// Forget the UI state of window `window`, so it isn't restored.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+/// * `window` - The window's label.
+///
+pub async fn clear_ui_state<S: Store>(store: &S, window: &str) {
+    let key = format!("{}{}", SETTING_UI_STATE_PREFIX, window);
+    store.remove_setting(&key).await;
+}
+
+/// Obtain the labels of the windows with a saved UI state, to be restored.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+///
+pub async fn get_ui_windows<S: ReadStore>(store: &S) -> Vec<String> {
+    store
+        .get_settings(SETTING_UI_STATE_PREFIX)
+        .await
+        .into_iter()
+        .map(|(key, _)| key[SETTING_UI_STATE_PREFIX.len()..].to_string())
+        .collect()
+}
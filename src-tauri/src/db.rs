@@ -12,18 +12,224 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use sqlx::{migrate::MigrateDatabase, sqlite::SqliteQueryResult, SqlitePool};
+use std::str::FromStr;
+
+use sqlx::{
+    migrate::MigrateDatabase,
+    sqlite::{SqliteConnectOptions, SqliteQueryResult},
+    ConnectOptions, SqlitePool,
+};
+
+use crate::querylog;
+
+/// A data transformation applied when upgrading from an older schema
+/// version. Tables are added by the schema itself; migrations only cover
+/// what `CREATE TABLE IF NOT EXISTS` can't.
+///
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub query: &'static str,
+    /// A query returning whether the schema already made the change, e.g.
+    /// when the table altered was only now created with the new column, in
+    /// which case the migration is skipped.
+    pub applied: Option<&'static str>,
+}
+
+/// Data transformations, in version order. The last version listed is the
+/// current schema version.
+///
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Keep unparsable values from GitHub with their issues",
+        query: "ALTER TABLE issues ADD COLUMN unknown_raw TEXT",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('issues')
+            WHERE name = 'unknown_raw'
+            ",
+        ),
+    },
+    Migration {
+        version: 2,
+        description: "Index existing issues for local search",
+        query: "
+            INSERT OR REPLACE INTO issue_search (rowid, title, ref, author)
+            SELECT
+                id, title, repo_owner || '/' || repo_name || '#' || number,
+                author
+            FROM issues
+        ",
+        applied: None,
+    },
+    Migration {
+        version: 3,
+        description: "Keep commit messages, for co-author credit",
+        query: "
+            ALTER TABLE pr_commits ADD COLUMN message TEXT NOT NULL DEFAULT ''
+        ",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('pr_commits')
+            WHERE name = 'message'
+            ",
+        ),
+    },
+    Migration {
+        version: 4,
+        description: "Mark backfill chunks left incomplete",
+        query: "
+            ALTER TABLE user_history
+            ADD COLUMN backfill_truncated INTEGER NOT NULL DEFAULT 0
+        ",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('user_history')
+            WHERE name = 'backfill_truncated'
+            ",
+        ),
+    },
+    Migration {
+        version: 5,
+        description: "Keep repository permissions per account",
+        query: "
+            DROP TABLE repo_permissions;
+            CREATE TABLE repo_permissions (
+                account_id      INTEGER NOT NULL,
+                repo_owner      TEXT NOT NULL,
+                repo_name       TEXT NOT NULL,
+                permission      TEXT NOT NULL,
+                fetched_at      INTEGER NOT NULL,
+                PRIMARY KEY (account_id, repo_owner, repo_name)
+            );
+            DELETE FROM sync_refresh WHERE key LIKE 'access:%';
+        ",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('repo_permissions')
+            WHERE name = 'account_id'
+            ",
+        ),
+    },
+    Migration {
+        version: 6,
+        description: "Keep track of evicted patches",
+        query: "ALTER TABLE pr_patches ADD COLUMN evicted_at INTEGER",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('pr_patches')
+            WHERE name = 'evicted_at'
+            ",
+        ),
+    },
+    Migration {
+        version: 7,
+        description: "Keep when issues were first responded to",
+        query: "ALTER TABLE issues ADD COLUMN first_response_at INTEGER",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('issues')
+            WHERE name = 'first_response_at'
+            ",
+        ),
+    },
+    Migration {
+        version: 8,
+        description: "Size Pull Requests whose files are already known",
+        // classified by the default thresholds, as in `gh::sizes`.
+        query: "
+            INSERT OR IGNORE INTO pr_sizes (pr_id, changed_lines, size_class)
+            SELECT
+                pr_id, lines,
+                CASE
+                    WHEN lines <= 10 THEN 'XS'
+                    WHEN lines <= 100 THEN 'S'
+                    WHEN lines <= 500 THEN 'M'
+                    WHEN lines <= 1000 THEN 'L'
+                    ELSE 'XL'
+                END
+            FROM (
+                SELECT pr_id, SUM(additions + deletions) AS lines
+                FROM pr_files
+                GROUP BY pr_id
+            )
+        ",
+        applied: None,
+    },
+    Migration {
+        version: 9,
+        description: "Link users to identities by host and login",
+        query: "
+            CREATE TABLE identity_users_new (
+                host        TEXT NOT NULL,
+                login       TEXT NOT NULL,
+                identity_id INTEGER NOT NULL,
+                PRIMARY KEY (host, login),
+                FOREIGN KEY (identity_id) REFERENCES identities (id)
+            );
+            INSERT OR IGNORE INTO identity_users_new (host, login, identity_id)
+            SELECT
+                COALESCE(
+                    (
+                        SELECT accounts.host
+                        FROM user_accounts INNER JOIN accounts
+                            ON accounts.user_id = user_accounts.account_id
+                        WHERE user_accounts.user_id = users.id
+                        LIMIT 1
+                    ),
+                    'github.com'
+                ),
+                users.login, identity_users.identity_id
+            FROM identity_users INNER JOIN users
+                ON users.id = identity_users.user_id;
+            DROP TABLE identity_users;
+            ALTER TABLE identity_users_new RENAME TO identity_users;
+        ",
+        applied: Some(
+            "
+            SELECT COUNT(*) > 0 FROM pragma_table_info('identity_users')
+            WHERE name = 'host'
+            ",
+        ),
+    },
+];
+
+/// What upgrading an existing database will change, reported before the
+/// upgrade is applied, and again once `applied`.
+///
+#[derive(Clone, serde::Serialize)]
+pub struct MigrationReport {
+    pub from_version: i64,
+    pub to_version: i64,
+    pub new_tables: Vec<String>,
+    pub migrations: Vec<String>,
+    pub backup_path: Option<String>,
+    pub applied: bool,
+}
+
+/// How many backups taken before upgrading are kept; older ones are removed
+/// as new ones are taken.
+///
+const BACKUPS_KEPT: usize = 3;
 
 pub struct DB {
     pub uri: String,
+    pub path: std::path::PathBuf,
     pub pool: Option<SqlitePool>,
+    pub migration: Option<MigrationReport>,
 }
 
 impl DB {
     pub fn new(path: &std::path::PathBuf) -> DB {
         let uri = format!("sqlite://{}", path.display());
 
-        DB { uri, pool: None }
+        DB {
+            uri,
+            path: path.clone(),
+            pool: None,
+            migration: None,
+        }
     }
 
     pub async fn connect(self: &mut Self) {
@@ -31,25 +237,184 @@ impl DB {
             panic!("Attempting to connect to connected database!");
         }
 
-        self.pool =
-            Some(SqlitePool::connect(&self.uri).await.unwrap_or_else(|_| {
+        let slow_ms = get_slow_query_ms(&self.uri).await;
+        let mut options = SqliteConnectOptions::from_str(&self.uri)
+            .unwrap_or_else(|err| {
+                panic!("Invalid database uri '{}': {}", self.uri, err);
+            });
+        // statements are only logged when slow, to be captured by `querylog`.
+        options
+            .log_statements(log::LevelFilter::Off)
+            .log_slow_statements(
+                log::LevelFilter::Warn,
+                std::time::Duration::from_millis(slow_ms),
+            );
+
+        self.pool = Some(
+            SqlitePool::connect_with(options).await.unwrap_or_else(|_| {
                 panic!("Unable to open database!");
-            }));
+            }),
+        );
+    }
+
+    /// Work out whether the existing database, if any, needs upgrading,
+    /// backing it up if so. The report is kept in `migration`, so that what
+    /// is being upgraded can be shown while `setup()` upgrades it.
+    ///
+    pub async fn prepare(self: &mut Self) {
+        let exists = sqlx::Sqlite::database_exists(&self.uri)
+            .await
+            .unwrap_or(false);
+        if !exists {
+            return;
+        }
+
+        let mut report = self.preflight().await.unwrap_or_else(|err| {
+            panic!("Unable to inspect existing database: {}", err);
+        });
+        if let Some(r) = &mut report {
+            r.backup_path = Some(self.backup(r.from_version));
+            self.prune_backups();
+        }
+        self.migration = report;
     }
 
-    pub async fn setup(self: Self) -> Self {
-        if !sqlx::Sqlite::database_exists(&self.uri)
+    /// Create the database if it doesn't exist, or upgrade it otherwise.
+    ///
+    pub async fn setup(self: &mut Self) {
+        let exists = sqlx::Sqlite::database_exists(&self.uri)
             .await
-            .unwrap_or(false)
-        {
+            .unwrap_or(false);
+        if !exists {
             sqlx::Sqlite::create_database(&self.uri).await.unwrap();
-            match create_db_schema(&self.uri).await {
-                Ok(_) => println!("Database created successfully."),
-                Err(err) => panic!("{}", err),
-            };
         }
 
-        self
+        let from_version = match exists {
+            true => get_version(&self.uri).await,
+            false => schema_version(),
+        };
+
+        // all tables are created only if they don't exist, so that existing
+        // databases pick up tables added in newer versions.
+        match create_db_schema(&self.uri).await {
+            Ok(_) if !exists => println!("Database created successfully."),
+            Ok(_) => {}
+            Err(err) => panic!("{}", err),
+        };
+
+        if let Err(err) = apply_migrations(&self.uri, from_version).await {
+            panic!("Unable to migrate database: {}", err);
+        }
+        if let Some(r) = &mut self.migration {
+            r.applied = true;
+        }
+    }
+
+    /// Report what upgrading the existing database would change, without
+    /// changing anything. Returns `None` if the database is up to date.
+    ///
+    pub async fn preflight(
+        self: &Self,
+    ) -> Result<Option<MigrationReport>, sqlx::Error> {
+        let pool = SqlitePool::connect(&self.uri).await?;
+        let existing = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table'",
+        )
+        .fetch_all(&pool)
+        .await;
+        pool.close().await;
+
+        let existing = existing?;
+        let from_version = get_version(&self.uri).await;
+        let new_tables: Vec<String> = schema_tables()
+            .into_iter()
+            .filter(|t| !existing.contains(t))
+            .collect();
+        let migrations: Vec<String> = MIGRATIONS
+            .iter()
+            .filter(|m| m.version > from_version)
+            .map(|m| m.description.to_string())
+            .collect();
+
+        if new_tables.is_empty() && migrations.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(MigrationReport {
+            from_version,
+            to_version: schema_version(),
+            new_tables,
+            migrations,
+            backup_path: None,
+            applied: false,
+        }))
+    }
+
+    /// Copy the database file aside before upgrading it, returning the
+    /// backup's path.
+    ///
+    fn backup(self: &Self, version: i64) -> String {
+        let path = format!(
+            "{}.v{}-{}.bak",
+            self.path.display(),
+            version,
+            chrono::Utc::now().timestamp()
+        );
+        std::fs::copy(&self.path, &path).unwrap_or_else(|err| {
+            panic!("Unable to back up database to '{}': {}", path, err);
+        });
+        println!("Database backed up to '{}'.", path);
+
+        path
+    }
+
+    /// Remove all but the `BACKUPS_KEPT` most recent backups taken before
+    /// upgrading, going by when they were taken. Backups that can't be
+    /// removed are left behind.
+    ///
+    fn prune_backups(self: &Self) {
+        let (dir, file) = match (self.path.parent(), self.path.file_name()) {
+            (Some(dir), Some(file)) => (dir, file.to_string_lossy()),
+            _ => return,
+        };
+        let prefix = format!("{}.v", file);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(res) => res,
+            Err(err) => {
+                println!("Unable to list database backups: {}", err);
+                return;
+            }
+        };
+
+        // backups are named '<db>.v<version>-<timestamp>.bak'.
+        let mut backups: Vec<(i64, std::path::PathBuf)> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                let taken_at = name
+                    .strip_prefix(&prefix)?
+                    .strip_suffix(".bak")?
+                    .rsplit_once('-')?
+                    .1
+                    .parse::<i64>()
+                    .ok()?;
+                Some((taken_at, e.path()))
+            })
+            .collect();
+        backups.sort_by(|a, b| b.0.cmp(&a.0));
+
+        for (_, path) in backups.iter().skip(BACKUPS_KEPT) {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {
+                    println!("Removed database backup '{}'.", path.display())
+                }
+                Err(err) => println!(
+                    "Unable to remove database backup '{}': {}",
+                    path.display(),
+                    err
+                ),
+            };
+        }
     }
 
     pub fn pool(self: &Self) -> &SqlitePool {
@@ -60,11 +425,130 @@ impl DB {
             }
         }
     }
+
+    /// Begin a read snapshot, for commands issuing several queries to see the
+    /// database as of a single point in time, regardless of a sync writing
+    /// to it meanwhile.
+    ///
+    pub async fn snapshot(self: &Self) -> Snapshot {
+        match self.pool().begin().await {
+            Ok(tx) => Snapshot {
+                tx: tokio::sync::Mutex::new(tx),
+            },
+            Err(err) => {
+                panic!("Error starting read snapshot: {}", err);
+            }
+        }
+    }
 }
 
-async fn create_db_schema(uri: &str) -> Result<SqliteQueryResult, sqlx::Error> {
+/// A read transaction. Every query issued through it sees the database as of
+/// its first query: SQLite holds a shared lock until it ends, which writers
+/// wait on before committing. Snapshots are thus to be kept short. Nothing is
+/// to be written through one; it is rolled back when dropped.
+///
+pub struct Snapshot {
+    tx: tokio::sync::Mutex<sqlx::Transaction<'static, sqlx::Sqlite>>,
+}
+
+impl Snapshot {
+    /// Obtain the snapshot's transaction, to issue queries through.
+    ///
+    pub async fn conn(
+        self: &Self,
+    ) -> tokio::sync::MutexGuard<'_, sqlx::Transaction<'static, sqlx::Sqlite>>
+    {
+        self.tx.lock().await
+    }
+}
+
+fn schema_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Obtain the names of the tables created by the schema.
+///
+fn schema_tables() -> Vec<String> {
+    SCHEMA
+        .split("CREATE TABLE IF NOT EXISTS ")
+        .skip(1)
+        .filter_map(|s| s.split_whitespace().next())
+        .map(|s| s.trim_end_matches('(').to_string())
+        .collect()
+}
+
+/// Obtain the slow query threshold from the settings, before there's a pool
+/// to obtain it through.
+///
+async fn get_slow_query_ms(uri: &str) -> u64 {
+    let pool = SqlitePool::connect(uri).await.unwrap_or_else(|err| {
+        panic!("Unable to open database: {}", err);
+    });
+    let value = sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?",
+    )
+    .bind(querylog::SETTING_SLOW_QUERY_MS)
+    .fetch_optional(&pool)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain slow query threshold: {}", err);
+    });
+    pool.close().await;
+
+    value
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(querylog::DEFAULT_SLOW_QUERY_MS)
+}
+
+async fn get_version(uri: &str) -> i64 {
+    let pool = SqlitePool::connect(uri).await.unwrap_or_else(|err| {
+        panic!("Unable to open database: {}", err);
+    });
+    let version = sqlx::query_scalar::<_, i64>("PRAGMA user_version")
+        .fetch_one(&pool)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to obtain database version: {}", err);
+        });
+    pool.close().await;
+
+    version
+}
+
+/// Apply the data transformations newer than `from_version`, recording each
+/// version as it's applied.
+///
+async fn apply_migrations(
+    uri: &str,
+    from_version: i64,
+) -> Result<(), sqlx::Error> {
     let pool = SqlitePool::connect(uri).await?;
-    let query = "
+    let mut tx = pool.begin().await?;
+
+    for m in MIGRATIONS.iter().filter(|m| m.version > from_version) {
+        if let Some(applied) = m.applied {
+            if sqlx::query_scalar::<_, bool>(applied)
+                .fetch_one(&mut tx)
+                .await?
+            {
+                continue;
+            }
+        }
+        println!("Migrating database to v{}: {}", m.version, m.description);
+        sqlx::query(m.query).execute(&mut tx).await?;
+    }
+    // pragmas don't take bound parameters.
+    sqlx::query(&format!("PRAGMA user_version = {}", schema_version()))
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+    pool.close().await;
+
+    Ok(())
+}
+
+const SCHEMA: &str = "
     PRAGMA foreign_keys = ON;
     CREATE TABLE IF NOT EXISTS settings (
         key         TEXT PRIMARY KEY NOT NULL,
@@ -90,7 +574,45 @@ async fn create_db_schema(uri: &str) -> Result<SqliteQueryResult, sqlx::Error> {
         updated_at      INTEGER NOT NULL,
         closed_at       INTEGER,
         is_pull_request BOOL NOT NULL,
-        last_viewed     INTEGER
+        last_viewed     INTEGER,
+        unknown_raw     TEXT,
+        first_response_at   INTEGER
+    );
+    CREATE VIRTUAL TABLE IF NOT EXISTS issue_search USING fts5 (
+        title, ref, author, tokenize = 'trigram'
+    );
+    CREATE TRIGGER IF NOT EXISTS issue_search_insert
+    AFTER INSERT ON issues BEGIN
+        INSERT OR REPLACE INTO issue_search (rowid, title, ref, author)
+        VALUES (
+            new.id, new.title,
+            new.repo_owner || '/' || new.repo_name || '#' || new.number,
+            new.author
+        );
+    END;
+    CREATE TRIGGER IF NOT EXISTS issue_search_update
+    AFTER UPDATE OF title, repo_owner, repo_name, number, author ON issues
+    BEGIN
+        INSERT OR REPLACE INTO issue_search (rowid, title, ref, author)
+        VALUES (
+            new.id, new.title,
+            new.repo_owner || '/' || new.repo_name || '#' || new.number,
+            new.author
+        );
+    END;
+    CREATE TRIGGER IF NOT EXISTS issue_search_delete
+    AFTER DELETE ON issues BEGIN
+        DELETE FROM issue_search WHERE rowid = old.id;
+    END;
+    CREATE TABLE IF NOT EXISTS authors (
+        login       TEXT PRIMARY KEY NOT NULL,
+        is_bot      BOOLEAN NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS issue_labels (
+        issue_id    INTEGER NOT NULL,
+        name        TEXT NOT NULL,
+        PRIMARY KEY (issue_id, name),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
     );
     CREATE TABLE IF NOT EXISTS pull_requests (
         id              INTEGER PRIMARY KEY NOT NULL,
@@ -99,6 +621,372 @@ async fn create_db_schema(uri: &str) -> Result<SqliteQueryResult, sqlx::Error> {
         merged_at       INTEGER,
         FOREIGN KEY (id) REFERENCES issues (id)
     );
+    CREATE TABLE IF NOT EXISTS backports (
+        backport_id     INTEGER PRIMARY KEY NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        original_number INTEGER,
+        branch          TEXT NOT NULL,
+        FOREIGN KEY (backport_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS repos (
+        owner           TEXT NOT NULL,
+        name            TEXT NOT NULL,
+        default_branch  TEXT,
+        PRIMARY KEY (owner, name)
+    );
+    CREATE TABLE IF NOT EXISTS repo_metadata (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        language        TEXT,
+        PRIMARY KEY (repo_owner, repo_name)
+    );
+    CREATE TABLE IF NOT EXISTS repo_topics (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        topic           TEXT NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name, topic)
+    );
+    CREATE TABLE IF NOT EXISTS repo_sync (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        paused          BOOLEAN NOT NULL DEFAULT 0,
+        failures        INTEGER NOT NULL DEFAULT 0,
+        last_error      TEXT,
+        failed_at       INTEGER,
+        retry_at        INTEGER,
+        PRIMARY KEY (repo_owner, repo_name)
+    );
+    CREATE TABLE IF NOT EXISTS repo_permissions (
+        account_id      INTEGER NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        permission      TEXT NOT NULL,
+        fetched_at      INTEGER NOT NULL,
+        PRIMARY KEY (account_id, repo_owner, repo_name)
+    );
+    CREATE TABLE IF NOT EXISTS repo_labels (
+        repo_owner  TEXT NOT NULL,
+        repo_name   TEXT NOT NULL,
+        name        TEXT NOT NULL,
+        color       TEXT NOT NULL,
+        description TEXT,
+        PRIMARY KEY (repo_owner, repo_name, name)
+    );
+    CREATE TABLE IF NOT EXISTS merge_policies (
+        repo_owner              TEXT NOT NULL,
+        repo_name               TEXT NOT NULL,
+        default_branch          TEXT NOT NULL,
+        allow_merge_commit      BOOL NOT NULL,
+        allow_squash_merge      BOOL NOT NULL,
+        allow_rebase_merge      BOOL NOT NULL,
+        requires_linear_history BOOL NOT NULL,
+        fetched_at              INTEGER NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name)
+    );
+    CREATE TABLE IF NOT EXISTS branches (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        name            TEXT NOT NULL,
+        last_commit_at  INTEGER,
+        PRIMARY KEY (repo_owner, repo_name, name)
+    );
+    CREATE TABLE IF NOT EXISTS branch_prs (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        branch          TEXT NOT NULL,
+        number          INTEGER NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name, branch, number)
+    );
+    CREATE TABLE IF NOT EXISTS triage_rules (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        position        INTEGER NOT NULL,
+        title_pattern   TEXT,
+        label           TEXT,
+        author          TEXT,
+        bucket          TEXT NOT NULL,
+        tag             TEXT,
+        notify          BOOLEAN NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS triage (
+        issue_id        INTEGER PRIMARY KEY NOT NULL,
+        bucket          TEXT NOT NULL,
+        rule_id         INTEGER NOT NULL,
+        triaged_at      INTEGER NOT NULL,
+        dismissed       BOOLEAN NOT NULL DEFAULT 0,
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS issue_tags (
+        issue_id        INTEGER NOT NULL,
+        tag             TEXT NOT NULL,
+        PRIMARY KEY (issue_id, tag),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS issue_annotations (
+        issue_id        INTEGER PRIMARY KEY NOT NULL,
+        priority        TEXT,
+        note            TEXT,
+        updated_at      INTEGER NOT NULL,
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS notifications (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind            TEXT NOT NULL,
+        issue_id        INTEGER,
+        title           TEXT NOT NULL,
+        body            TEXT NOT NULL,
+        priority        INTEGER NOT NULL,
+        created_at      INTEGER NOT NULL,
+        delivered_at    INTEGER,
+        is_read         BOOLEAN NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS notification_rules (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        account_id      INTEGER NOT NULL,
+        repo_owner      TEXT,
+        repo_name       TEXT,
+        label           TEXT NOT NULL,
+        action          TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS pr_author_associations (
+        pr_id           INTEGER PRIMARY KEY NOT NULL,
+        association     TEXT NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_head_refs (
+        pr_id           INTEGER PRIMARY KEY NOT NULL,
+        head_ref        TEXT NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_lint_warnings (
+        pr_id           INTEGER NOT NULL,
+        rule            TEXT NOT NULL,
+        field           TEXT NOT NULL,
+        pattern         TEXT NOT NULL,
+        PRIMARY KEY (pr_id, rule),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS issue_dependencies (
+        issue_id        INTEGER NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        number          INTEGER NOT NULL,
+        PRIMARY KEY (issue_id, repo_owner, repo_name, number),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS path_views (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        name            TEXT NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS path_view_globs (
+        view_id         INTEGER NOT NULL,
+        glob            TEXT NOT NULL,
+        PRIMARY KEY (view_id, glob),
+        FOREIGN KEY (view_id) REFERENCES path_views (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_templates (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        template        TEXT NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name)
+    );
+    CREATE TABLE IF NOT EXISTS pr_template_checks (
+        pr_id           INTEGER PRIMARY KEY,
+        is_empty        BOOLEAN NOT NULL,
+        missing         TEXT NOT NULL,
+        checked_at      INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS advisory_findings (
+        repo_owner          TEXT NOT NULL,
+        repo_name           TEXT NOT NULL,
+        ghsa_id             TEXT NOT NULL,
+        ecosystem           TEXT NOT NULL,
+        package             TEXT NOT NULL,
+        version             TEXT NOT NULL,
+        severity            TEXT NOT NULL,
+        summary             TEXT NOT NULL,
+        url                 TEXT NOT NULL,
+        vulnerable_range    TEXT,
+        patched_version     TEXT,
+        found_at            INTEGER NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name, ghsa_id, ecosystem, package)
+    );
+    CREATE TABLE IF NOT EXISTS pr_heads (
+        pr_id               INTEGER PRIMARY KEY NOT NULL,
+        head_sha            TEXT NOT NULL,
+        requires_signatures BOOLEAN,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_commits (
+        pr_id           INTEGER NOT NULL,
+        sha             TEXT NOT NULL,
+        position        INTEGER NOT NULL,
+        verified        BOOLEAN NOT NULL,
+        signature_state TEXT NOT NULL,
+        message         TEXT NOT NULL DEFAULT '',
+        PRIMARY KEY (pr_id, sha),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS commit_co_authors (
+        pr_id       INTEGER NOT NULL,
+        sha         TEXT NOT NULL,
+        name        TEXT NOT NULL,
+        email       TEXT NOT NULL,
+        login       TEXT,
+        PRIMARY KEY (pr_id, sha, email),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_reviews (
+        pr_id           INTEGER NOT NULL,
+        user_id         INTEGER NOT NULL,
+        login           TEXT NOT NULL,
+        state           TEXT NOT NULL,
+        submitted_at    INTEGER NOT NULL,
+        PRIMARY KEY (pr_id, user_id),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_viewed_heads (
+        pr_id       INTEGER PRIMARY KEY NOT NULL,
+        head_sha    TEXT NOT NULL,
+        viewed_at   INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_deltas (
+        pr_id           INTEGER PRIMARY KEY NOT NULL,
+        base_sha        TEXT NOT NULL,
+        head_sha        TEXT NOT NULL,
+        force_pushed    BOOLEAN NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_delta_commits (
+        pr_id       INTEGER NOT NULL,
+        sha         TEXT NOT NULL,
+        PRIMARY KEY (pr_id, sha),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_delta_files (
+        pr_id       INTEGER NOT NULL,
+        path        TEXT NOT NULL,
+        status      TEXT NOT NULL,
+        additions   INTEGER NOT NULL,
+        deletions   INTEGER NOT NULL,
+        PRIMARY KEY (pr_id, path),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_blockers (
+        pr_id               INTEGER PRIMARY KEY NOT NULL,
+        protection_visible  BOOLEAN NOT NULL,
+        required_approvals  INTEGER NOT NULL,
+        approvals           INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_missing_checks (
+        pr_id           INTEGER NOT NULL,
+        context         TEXT NOT NULL,
+        PRIMARY KEY (pr_id, context),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_review_requests (
+        pr_id           INTEGER NOT NULL,
+        user_id         INTEGER NOT NULL,
+        requested_at    INTEGER NOT NULL,
+        PRIMARY KEY (pr_id, user_id),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS review_reminders (
+        pr_id           INTEGER NOT NULL,
+        user_id         INTEGER NOT NULL,
+        reminded_at     INTEGER NOT NULL,
+        PRIMARY KEY (pr_id, user_id),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS reminder_snoozes (
+        pr_id           INTEGER PRIMARY KEY NOT NULL,
+        snoozed_until   INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_turns (
+        pr_id           INTEGER PRIMARY KEY NOT NULL,
+        my_turn         BOOLEAN NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_ci_states (
+        pr_id       INTEGER PRIMARY KEY NOT NULL,
+        state       TEXT NOT NULL,
+        changed_at  INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS releases (
+        id              INTEGER PRIMARY KEY NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        tag_name        TEXT NOT NULL,
+        name            TEXT,
+        url             TEXT NOT NULL,
+        published_at    INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS away_periods (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        started_at  INTEGER NOT NULL,
+        ended_at    INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS api_calls (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        account_id  INTEGER,
+        host        TEXT NOT NULL,
+        feature     TEXT NOT NULL,
+        resource    TEXT,
+        cost        INTEGER NOT NULL,
+        remaining   INTEGER,
+        called_at   INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS api_usage_hours (
+        account_id  INTEGER NOT NULL,
+        host        TEXT NOT NULL,
+        hour        INTEGER NOT NULL,
+        calls       INTEGER NOT NULL,
+        cost        INTEGER NOT NULL,
+        PRIMARY KEY (account_id, host, hour)
+    );
+    CREATE TABLE IF NOT EXISTS usage_anomalies (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        account_id  INTEGER NOT NULL,
+        host        TEXT NOT NULL,
+        hour        INTEGER NOT NULL,
+        calls       INTEGER NOT NULL,
+        baseline    INTEGER NOT NULL,
+        detected_at INTEGER NOT NULL,
+        UNIQUE (account_id, host, hour)
+    );
+    CREATE TABLE IF NOT EXISTS profile_stats (
+        user_id         INTEGER PRIMARY KEY NOT NULL,
+        followers       INTEGER NOT NULL,
+        following       INTEGER NOT NULL,
+        public_repos    INTEGER NOT NULL,
+        stars           INTEGER NOT NULL,
+        updated_at      INTEGER NOT NULL,
+        FOREIGN KEY (user_id) REFERENCES users (id)
+    );
+    CREATE TABLE IF NOT EXISTS outbox (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        kind TEXT NOT NULL,
+        pr_id INTEGER NOT NULL,
+        payload TEXT NOT NULL,
+        state TEXT NOT NULL,
+        error TEXT,
+        created_at INTEGER NOT NULL,
+        completed_at INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS pr_verifications (
+        pr_id       INTEGER PRIMARY KEY NOT NULL,
+        due_at      INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
     CREATE TABLE IF NOT EXISTS user_issues (
         user_id     INTEGER NOT NULL,
         issue_id    INTEGER NOT NULL,
@@ -106,20 +994,346 @@ async fn create_db_schema(uri: &str) -> Result<SqliteQueryResult, sqlx::Error> {
         FOREIGN KEY (user_id) REFERENCES users (id),
         FOREIGN KEY (issue_id) REFERENCES issues (id)
     );
+    CREATE TABLE IF NOT EXISTS user_issue_relations (
+        user_id     INTEGER NOT NULL,
+        issue_id    INTEGER NOT NULL,
+        relation    TEXT NOT NULL,
+        PRIMARY KEY (user_id, issue_id, relation),
+        FOREIGN KEY (user_id) REFERENCES users (id),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_files (
+        pr_id       INTEGER NOT NULL,
+        path        TEXT NOT NULL,
+        status      TEXT NOT NULL,
+        additions   INTEGER NOT NULL,
+        deletions   INTEGER NOT NULL,
+        PRIMARY KEY (pr_id, path),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_sizes (
+        pr_id           INTEGER PRIMARY KEY NOT NULL,
+        changed_lines   INTEGER NOT NULL,
+        size_class      TEXT NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS truncations (
+        issue_id        INTEGER NOT NULL,
+        kind            TEXT NOT NULL,
+        kept            INTEGER NOT NULL,
+        truncated_at    INTEGER NOT NULL,
+        PRIMARY KEY (issue_id, kind),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS reactions (
+        issue_id        INTEGER NOT NULL,
+        subject         TEXT NOT NULL,
+        comment_id      INTEGER NOT NULL,
+        content         TEXT NOT NULL,
+        count           INTEGER NOT NULL,
+        my_reaction_id  INTEGER,
+        PRIMARY KEY (issue_id, subject, comment_id, content),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS pending_reviews (
+        review_id   INTEGER PRIMARY KEY NOT NULL,
+        user_id     INTEGER NOT NULL,
+        pr_id       INTEGER NOT NULL,
+        repo_owner  TEXT NOT NULL,
+        repo_name   TEXT NOT NULL,
+        number      INTEGER NOT NULL,
+        title       TEXT NOT NULL,
+        url         TEXT NOT NULL,
+        comments    INTEGER NOT NULL,
+        created_at  INTEGER NOT NULL,
+        updated_at  INTEGER NOT NULL,
+        FOREIGN KEY (user_id) REFERENCES users (id)
+    );
+    CREATE TABLE IF NOT EXISTS follow_ups (
+        comment_id      INTEGER PRIMARY KEY NOT NULL,
+        user_id         INTEGER NOT NULL,
+        issue_id        INTEGER NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        number          INTEGER NOT NULL,
+        title           TEXT NOT NULL,
+        url             TEXT NOT NULL,
+        comment_url     TEXT NOT NULL,
+        commented_at    INTEGER NOT NULL,
+        FOREIGN KEY (user_id) REFERENCES users (id)
+    );
+    CREATE TABLE IF NOT EXISTS follow_up_dismissals (
+        comment_id      INTEGER PRIMARY KEY NOT NULL,
+        dismissed_at    INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS reply_templates (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        name        TEXT NOT NULL,
+        body        TEXT NOT NULL,
+        created_at  INTEGER NOT NULL,
+        updated_at  INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS review_sessions (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        pr_id       INTEGER NOT NULL,
+        started_at  INTEGER NOT NULL,
+        stopped_at  INTEGER,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS draft_reviews (
+        pr_id       INTEGER PRIMARY KEY NOT NULL,
+        body        TEXT NOT NULL,
+        updated_at  INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS draft_review_comments (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        pr_id       INTEGER NOT NULL,
+        path        TEXT NOT NULL,
+        line        INTEGER NOT NULL,
+        side        TEXT NOT NULL,
+        body        TEXT NOT NULL,
+        created_at  INTEGER NOT NULL,
+        updated_at  INTEGER NOT NULL,
+        FOREIGN KEY (pr_id) REFERENCES draft_reviews (pr_id)
+    );
+    CREATE TABLE IF NOT EXISTS pr_patches (
+        pr_id       INTEGER PRIMARY KEY NOT NULL,
+        format      TEXT NOT NULL,
+        size        INTEGER NOT NULL,
+        stored_size INTEGER NOT NULL,
+        too_large   BOOL NOT NULL,
+        fetched_at  INTEGER NOT NULL,
+        evicted_at  INTEGER,
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
     CREATE TABLE IF NOT EXISTS user_refresh (
         id          INTEGER PRIMARY KEY NOT NULL,
         refresh_at  INTEGER,
         FOREIGN KEY(id) REFERENCES users(id)
     );
+    CREATE TABLE IF NOT EXISTS sync_journal (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        source          TEXT NOT NULL,
+        started_at      INTEGER NOT NULL,
+        duration_ms     INTEGER NOT NULL,
+        changes         TEXT NOT NULL,
+        error           TEXT
+    );
+    CREATE TABLE IF NOT EXISTS user_history (
+        user_id                 INTEGER PRIMARY KEY NOT NULL,
+        synced_from             INTEGER,
+        backfill_from           INTEGER,
+        backfill_to             INTEGER,
+        backfill_cursor         INTEGER,
+        backfill_items          INTEGER NOT NULL DEFAULT 0,
+        backfill_truncated      INTEGER NOT NULL DEFAULT 0,
+        backfill_finished_at    INTEGER,
+        FOREIGN KEY (user_id) REFERENCES users (id)
+    );
+    CREATE TABLE IF NOT EXISTS update_status (
+        id              INTEGER PRIMARY KEY CHECK (id = 1),
+        latest_version  TEXT,
+        url             TEXT,
+        published_at    INTEGER,
+        checked_at      INTEGER,
+        downloaded_path TEXT
+    );
+    CREATE TABLE IF NOT EXISTS gql_versions (
+        operation   TEXT PRIMARY KEY NOT NULL,
+        tag         TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS sync_refresh (
+        key         TEXT PRIMARY KEY NOT NULL,
+        refresh_at  INTEGER NOT NULL
+    );
     CREATE TABLE IF NOT EXISTS tokens (
         id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
         token       TEXT NOT NULL,
         user_id     INTEGER,
         UNIQUE(token, user_id)
     );
+    CREATE TABLE IF NOT EXISTS accounts (
+        user_id     INTEGER PRIMARY KEY NOT NULL,
+        host        TEXT NOT NULL,
+        FOREIGN KEY (user_id) REFERENCES users (id)
+    );
+    CREATE TABLE IF NOT EXISTS user_accounts (
+        user_id     INTEGER NOT NULL,
+        account_id  INTEGER NOT NULL,
+        PRIMARY KEY (user_id, account_id),
+        FOREIGN KEY (user_id) REFERENCES users (id),
+        FOREIGN KEY (account_id) REFERENCES accounts (user_id)
+    );
+    CREATE TABLE IF NOT EXISTS identities (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        name        TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS identity_users (
+        host        TEXT NOT NULL,
+        login       TEXT NOT NULL,
+        identity_id INTEGER NOT NULL,
+        PRIMARY KEY (host, login),
+        FOREIGN KEY (identity_id) REFERENCES identities (id)
+    );
+    CREATE TABLE IF NOT EXISTS orgs (
+        id          INTEGER PRIMARY KEY NOT NULL,
+        login       TEXT NOT NULL,
+        avatar_url  TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS user_orgs (
+        user_id     INTEGER NOT NULL,
+        org_id      INTEGER NOT NULL,
+        PRIMARY KEY (user_id, org_id),
+        FOREIGN KEY (user_id) REFERENCES users (id),
+        FOREIGN KEY (org_id) REFERENCES orgs (id)
+    );
+    CREATE TABLE IF NOT EXISTS teams (
+        id          INTEGER PRIMARY KEY NOT NULL,
+        org_id      INTEGER NOT NULL,
+        slug        TEXT NOT NULL,
+        name        TEXT NOT NULL,
+        FOREIGN KEY (org_id) REFERENCES orgs (id)
+    );
+    CREATE TABLE IF NOT EXISTS user_teams (
+        user_id     INTEGER NOT NULL,
+        team_id     INTEGER NOT NULL,
+        PRIMARY KEY (user_id, team_id),
+        FOREIGN KEY (user_id) REFERENCES users (id),
+        FOREIGN KEY (team_id) REFERENCES teams (id)
+    );
+    CREATE TABLE IF NOT EXISTS team_members (
+        team_id     INTEGER NOT NULL,
+        user_id     INTEGER NOT NULL,
+        login       TEXT NOT NULL,
+        PRIMARY KEY (team_id, user_id),
+        FOREIGN KEY (team_id) REFERENCES teams (id)
+    );
+    CREATE TABLE IF NOT EXISTS team_mentions (
+        team_id         INTEGER NOT NULL,
+        issue_id        INTEGER NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        number          INTEGER NOT NULL,
+        title           TEXT NOT NULL,
+        author          TEXT NOT NULL,
+        url             TEXT NOT NULL,
+        is_pull_request BOOLEAN NOT NULL,
+        state           TEXT NOT NULL,
+        updated_at      INTEGER NOT NULL,
+        PRIMARY KEY (team_id, issue_id),
+        FOREIGN KEY (team_id) REFERENCES teams (id)
+    );
+    CREATE TABLE IF NOT EXISTS label_alerts (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        issue_id        INTEGER NOT NULL,
+        label           TEXT NOT NULL,
+        on_change       TEXT NOT NULL,
+        created_at      INTEGER NOT NULL,
+        triggered_at    INTEGER,
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS issue_fixes (
+        issue_id    INTEGER NOT NULL,
+        pr_id       INTEGER NOT NULL,
+        number      INTEGER NOT NULL,
+        title       TEXT NOT NULL,
+        url         TEXT NOT NULL,
+        repo_owner  TEXT NOT NULL,
+        repo_name   TEXT NOT NULL,
+        state       TEXT NOT NULL,
+        linked_at   INTEGER NOT NULL,
+        PRIMARY KEY (issue_id, pr_id),
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS issue_references (
+        target_id       INTEGER NOT NULL,
+        source_id       INTEGER NOT NULL,
+        number          INTEGER NOT NULL,
+        title           TEXT NOT NULL,
+        url             TEXT NOT NULL,
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        is_pull_request BOOL NOT NULL,
+        state           TEXT NOT NULL,
+        will_close      BOOL NOT NULL,
+        referenced_at   INTEGER NOT NULL,
+        PRIMARY KEY (target_id, source_id),
+        FOREIGN KEY (target_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS sla_rules (
+        repo_owner      TEXT NOT NULL,
+        repo_name       TEXT NOT NULL,
+        label           TEXT NOT NULL,
+        response_hours  INTEGER NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name, label)
+    );
+    CREATE TABLE IF NOT EXISTS issue_slas (
+        issue_id            INTEGER PRIMARY KEY NOT NULL,
+        label               TEXT NOT NULL,
+        due_at              INTEGER NOT NULL,
+        first_response_at   INTEGER,
+        status              TEXT NOT NULL,
+        FOREIGN KEY (issue_id) REFERENCES issues (id)
+    );
+    CREATE TABLE IF NOT EXISTS workspaces (
+        repo_owner  TEXT NOT NULL,
+        repo_name   TEXT NOT NULL,
+        path        TEXT NOT NULL,
+        added_at    INTEGER NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name)
+    );
+    CREATE TABLE IF NOT EXISTS anon_targets (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        kind        TEXT NOT NULL,
+        name        TEXT NOT NULL,
+        added_at    INTEGER NOT NULL,
+        synced_at   INTEGER,
+        UNIQUE (kind, name)
+    );
+    CREATE TABLE IF NOT EXISTS anon_prs (
+        target_id   INTEGER NOT NULL,
+        repo_owner  TEXT NOT NULL,
+        repo_name   TEXT NOT NULL,
+        number      INTEGER NOT NULL,
+        title       TEXT NOT NULL,
+        author      TEXT NOT NULL,
+        state       TEXT NOT NULL,
+        is_draft    BOOLEAN NOT NULL,
+        url         TEXT NOT NULL,
+        updated_at  INTEGER NOT NULL,
+        PRIMARY KEY (target_id, repo_owner, repo_name, number),
+        FOREIGN KEY (target_id) REFERENCES anon_targets (id)
+    );
+    CREATE TABLE IF NOT EXISTS dependency_prs (
+        repo_owner  TEXT NOT NULL,
+        repo_name   TEXT NOT NULL,
+        pr_id       INTEGER NOT NULL,
+        PRIMARY KEY (repo_owner, repo_name, pr_id),
+        FOREIGN KEY (pr_id) REFERENCES pull_requests (id)
+    );
+    CREATE TABLE IF NOT EXISTS org_scopes (
+        org_id      INTEGER PRIMARY KEY NOT NULL,
+        user_id     INTEGER NOT NULL,
+        enabled_at  INTEGER NOT NULL,
+        synced_at   INTEGER,
+        capped      BOOLEAN NOT NULL,
+        FOREIGN KEY (org_id) REFERENCES orgs (id),
+        FOREIGN KEY (user_id) REFERENCES users (id)
+    );
+    CREATE TABLE IF NOT EXISTS org_scope_prs (
+        org_id      INTEGER NOT NULL,
+        pr_id       INTEGER NOT NULL,
+        PRIMARY KEY (org_id, pr_id),
+        FOREIGN KEY (org_id) REFERENCES orgs (id),
+        FOREIGN KEY (pr_id) REFERENCES issues (id)
+    );
     ";
 
-    let result = sqlx::query(&query).execute(&pool).await;
+async fn create_db_schema(uri: &str) -> Result<SqliteQueryResult, sqlx::Error> {
+    let pool = SqlitePool::connect(uri).await?;
+    let result = sqlx::query(SCHEMA).execute(&pool).await;
     pool.close().await;
 
     result
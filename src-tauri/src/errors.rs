@@ -22,4 +22,137 @@ pub enum GHDError {
     BadRequest,
     UnknownError,
     NotFoundError,
+    PermissionDeniedError,
+    ResponseTooLargeError,
+    MissingScopeError(String),
+    RateLimitedError,
+    KeyUnavailableError,
+}
+
+impl GHDError {
+    /// Machine-readable kind of error, stable across releases.
+    ///
+    pub fn kind(self: &Self) -> &'static str {
+        match self {
+            GHDError::TokenNotFoundError => "token_not_found",
+            GHDError::BadTokenError => "bad_token",
+            GHDError::UserNotSetError => "user_not_set",
+            GHDError::UserNotFoundError => "user_not_found",
+            GHDError::NeverRefreshedError => "never_refreshed",
+            GHDError::BadRequest => "bad_request",
+            GHDError::UnknownError => "unknown",
+            GHDError::NotFoundError => "not_found",
+            GHDError::PermissionDeniedError => "permission_denied",
+            GHDError::ResponseTooLargeError => "response_too_large",
+            GHDError::MissingScopeError(_) => "missing_scope",
+            GHDError::RateLimitedError => "rate_limited",
+            GHDError::KeyUnavailableError => "key_unavailable",
+        }
+    }
+
+    /// Human-readable description of what went wrong.
+    ///
+    pub fn message(self: &Self) -> String {
+        match self {
+            GHDError::TokenNotFoundError => {
+                "No GitHub token has been set.".into()
+            }
+            GHDError::BadTokenError => {
+                "GitHub refused the token; it may have expired or been \
+                revoked."
+                    .into()
+            }
+            GHDError::UserNotSetError => "No main user has been set.".into(),
+            GHDError::UserNotFoundError => "User not found.".into(),
+            GHDError::NeverRefreshedError => {
+                "This data hasn't been obtained yet.".into()
+            }
+            GHDError::BadRequest => "The request is not valid.".into(),
+            GHDError::UnknownError => "Something unexpected went wrong.".into(),
+            GHDError::NotFoundError => "Not found.".into(),
+            GHDError::PermissionDeniedError => {
+                "This operation is not allowed.".into()
+            }
+            GHDError::ResponseTooLargeError => {
+                "GitHub's response was larger than allowed.".into()
+            }
+            GHDError::MissingScopeError(scope) => {
+                format!("The token is missing the '{}' scope.", scope)
+            }
+            GHDError::RateLimitedError => {
+                "GitHub's rate limit has been exceeded.".into()
+            }
+            GHDError::KeyUnavailableError => {
+                "The encryption key could not be obtained from the keychain."
+                    .into()
+            }
+        }
+    }
+
+    /// Suggested way out of the error, if there's something the user can do
+    /// about it.
+    ///
+    pub fn remediation(self: &Self) -> Option<String> {
+        let hint = match self {
+            GHDError::TokenNotFoundError => {
+                "Set a GitHub personal access token in the settings."
+            }
+            GHDError::BadTokenError => {
+                "Generate a new token with the 'repo' and 'read:org' scopes, \
+                and set it in the settings."
+            }
+            GHDError::UserNotSetError => "Set the main user in the settings.",
+            GHDError::UserNotFoundError => {
+                "Check the login for typos; the user may have been renamed \
+                or deleted."
+            }
+            GHDError::NeverRefreshedError => {
+                "Wait for the next sync, or trigger a refresh."
+            }
+            GHDError::PermissionDeniedError => {
+                "Check the token's permissions, and whether read-only mode \
+                is enabled in the settings."
+            }
+            GHDError::ResponseTooLargeError => {
+                "Raise 'limits.max_response_kb' in the settings."
+            }
+            GHDError::RateLimitedError => {
+                "Wait for the rate limit to reset before trying again."
+            }
+            GHDError::KeyUnavailableError => {
+                "Unlock the OS keychain and try again, or disable \
+                'store.encrypt_bodies' in the settings."
+            }
+            GHDError::MissingScopeError(scope) => {
+                return Some(format!(
+                    "Regenerate the token with the '{}' scope, and set it in \
+                    the settings.",
+                    scope
+                ));
+            }
+            GHDError::BadRequest
+            | GHDError::UnknownError
+            | GHDError::NotFoundError => return None,
+        };
+        Some(hint.into())
+    }
+}
+
+/// An error as reported to the frontend by Tauri commands.
+///
+#[derive(Debug, serde::Serialize)]
+pub struct CommandError {
+    pub kind: String,
+    pub message: String,
+    pub remediation: Option<String>,
+}
+
+impl From<GHDError> for CommandError {
+    fn from(err: GHDError) -> Self {
+        CommandError {
+            kind: err.kind().into(),
+            message: err.message(),
+            remediation: err.remediation(),
+        }
+    }
 }
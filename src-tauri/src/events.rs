@@ -12,12 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::gh::types::GithubUser;
+use crate::{
+    db::MigrationReport,
+    gh::types::{
+        AnonTarget, GithubUser, IssueChange, IssuesDiff, OutboxEntry,
+        UsageAnomaly, UserHistory,
+    },
+    notify::Notification,
+    throttle::SyncStatus,
+    updates::UpdateStatus,
+};
 
 pub const EV_ITERATION: &str = "iteration";
 pub const EV_USER_DATA_UPDATE: &str = "user_data_update";
 pub const EV_USER_UPDATE: &str = "user_update";
 pub const EV_TOKEN_SET: &str = "token_set";
+pub const EV_ORGS_UPDATE: &str = "orgs_update";
+pub const EV_BRANCHES_UPDATE: &str = "branches_update";
+pub const EV_NOTIFICATION: &str = "notification";
+pub const EV_ADVISORIES_UPDATE: &str = "advisories_update";
+pub const EV_NOTIFICATION_DIGEST: &str = "notification_digest";
+pub const EV_DB_MIGRATION: &str = "db_migration";
+pub const EV_OUTBOX_INTERRUPTED: &str = "outbox_interrupted";
+pub const EV_PR_CORRECTION: &str = "pr_correction";
+pub const EV_REPO_RENAMED: &str = "repo_renamed";
+pub const EV_UPDATE_AVAILABLE: &str = "update_available";
+pub const EV_BACKFILL_PROGRESS: &str = "backfill_progress";
+pub const EV_SYNC_STATUS: &str = "sync_status";
+pub const EV_ORG_SCOPE_UPDATE: &str = "org_scope_update";
+pub const EV_ANON_UPDATE: &str = "anon_update";
+pub const EV_USAGE_ANOMALY: &str = "usage_anomaly";
 
 pub fn emit<S>(w: &tauri::Window, ev: &str, payload: S)
 where
@@ -35,7 +59,139 @@ pub fn emit_user_update(w: &tauri::Window, user: &GithubUser) {
     emit(w, EV_USER_UPDATE, user);
 }
 
-pub fn emit_user_data_update(w: &tauri::Window, login: &String) {
+/// Payload for user data updates, with the issues that changed.
+///
+#[derive(Clone, serde::Serialize)]
+pub struct UserDataUpdate {
+    pub login: String,
+    pub diff: IssuesDiff,
+}
+
+pub fn emit_user_data_update(
+    w: &tauri::Window,
+    login: &String,
+    diff: &IssuesDiff,
+) {
     println!("emite user data update for '{}'", login);
-    emit(w, EV_USER_DATA_UPDATE, login);
+    emit(
+        w,
+        EV_USER_DATA_UPDATE,
+        UserDataUpdate {
+            login: login.clone(),
+            diff: diff.clone(),
+        },
+    );
+}
+
+pub fn emit_orgs_update(w: &tauri::Window, login: &String) {
+    println!("emit orgs update for '{}'", login);
+    emit(w, EV_ORGS_UPDATE, login);
+}
+
+pub fn emit_org_scope_update(w: &tauri::Window, org: &String) {
+    println!("emit org scope update for '{}'", org);
+    emit(w, EV_ORG_SCOPE_UPDATE, org);
+}
+
+pub fn emit_anon_update(w: &tauri::Window, target: &AnonTarget) {
+    println!("emit anon update for {} '{}'", target.kind, target.name);
+    emit(w, EV_ANON_UPDATE, target.id);
+}
+
+pub fn emit_branches_update(w: &tauri::Window, owner: &String, name: &String) {
+    println!("emit branches update for '{}/{}'", owner, name);
+    emit(w, EV_BRANCHES_UPDATE, format!("{}/{}", owner, name));
+}
+
+pub fn emit_notification(w: &tauri::Window, notification: &Notification) {
+    println!("emit notification {}", notification.id);
+    emit(w, EV_NOTIFICATION, notification.clone());
+}
+
+pub fn emit_notification_digest(
+    w: &tauri::Window,
+    notifications: &Vec<Notification>,
+) {
+    println!("emit digest of {} notifications", notifications.len());
+    emit(w, EV_NOTIFICATION_DIGEST, notifications.clone());
+}
+
+pub fn emit_advisories_update(
+    w: &tauri::Window,
+    owner: &String,
+    name: &String,
+) {
+    println!("emit advisories update for '{}/{}'", owner, name);
+    emit(w, EV_ADVISORIES_UPDATE, format!("{}/{}", owner, name));
+}
+
+pub fn emit_db_migration(w: &tauri::Window, report: &MigrationReport) {
+    println!(
+        "emit db migration from v{} to v{}",
+        report.from_version, report.to_version
+    );
+    emit(w, EV_DB_MIGRATION, report.clone());
+}
+
+pub fn emit_outbox_interrupted(w: &tauri::Window, entries: &Vec<OutboxEntry>) {
+    println!("emit {} interrupted outbox entries", entries.len());
+    emit(w, EV_OUTBOX_INTERRUPTED, entries);
+}
+
+pub fn emit_pr_correction(w: &tauri::Window, change: &IssueChange) {
+    println!("emit correction for pr {}: {:?}", change.id, change.fields);
+    emit(w, EV_PR_CORRECTION, change.clone());
+}
+
+/// Payload for repository renames, with both the previous and current
+/// `owner/name`.
+///
+#[derive(Clone, serde::Serialize)]
+pub struct RepoRenamed {
+    pub from: String,
+    pub to: String,
+}
+
+pub fn emit_repo_renamed(w: &tauri::Window, from: &String, to: &String) {
+    println!("emit repo renamed from '{}' to '{}'", from, to);
+    emit(
+        w,
+        EV_REPO_RENAMED,
+        RepoRenamed {
+            from: from.clone(),
+            to: to.clone(),
+        },
+    );
+}
+
+pub fn emit_update_available(w: &tauri::Window, status: &UpdateStatus) {
+    println!(
+        "emit update available: {}",
+        status.latest_version.as_deref().unwrap_or("?")
+    );
+    emit(w, EV_UPDATE_AVAILABLE, status.clone());
+}
+
+pub fn emit_backfill_progress(w: &tauri::Window, history: &UserHistory) {
+    println!(
+        "emit backfill progress for '{}': {} items",
+        history.login, history.backfill_items
+    );
+    emit(w, EV_BACKFILL_PROGRESS, history.clone());
+}
+
+pub fn emit_sync_status(w: &tauri::Window, status: &SyncStatus) {
+    println!(
+        "emit sync status: paused {}, reason {:?}",
+        status.paused, status.reason
+    );
+    emit(w, EV_SYNC_STATUS, status.clone());
+}
+
+pub fn emit_usage_anomaly(w: &tauri::Window, anomaly: &UsageAnomaly) {
+    println!(
+        "emit usage anomaly on {}: {} calls, usually {}",
+        anomaly.host, anomaly.calls, anomaly.baseline
+    );
+    emit(w, EV_USAGE_ANOMALY, anomaly.clone());
 }
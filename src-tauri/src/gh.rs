@@ -12,19 +12,104 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::PathBuf;
+
 use sqlx::Row;
 
-use crate::{db::DB, errors::GHDError};
+use crate::{
+    common,
+    db::DB,
+    errors::GHDError,
+    journal,
+    page::{Page, PageRequest},
+    throttle, vault,
+};
 
-use self::types::{GithubUser, PullRequestTableEntry};
+use self::types::{
+    Account, AccountCounts, AccountPullRequestEntry, Action, ActivityEvent,
+    AdvisoryFinding, AnonPullRequest, AnonTarget, ApiUsageEntry, AuthReport,
+    AwayPeriod, BackportEntry, CatchUpSummary, ContributionCredit, Dashboard,
+    DependencyApproval, DependencyGraph, DependencyUpdateGroup, DraftReview,
+    FileDiff, FollowUp, GithubOrg, GithubTeam, GithubUser, HourlyUsage,
+    Identity, IssueChange, IssueFix, IssueReference, IssuesDiff, LabelAlert,
+    LintWarning, LocalTag, MergePolicy, OrgOverview, OrgRepository, OrgScope,
+    OutboxEntry, PathView, PendingReview, ProfileStats, PullRequestConflict,
+    PullRequestDetail, PullRequestFile, PullRequestPatch,
+    PullRequestTableEntry, RateLimitStatus, ReactionCount, ReleaseNotesDraft,
+    Reminder, ReplyTemplate, RepoBranch, RepoLabel, RepoPermission,
+    RepoSyncStatus, Repository, ReviewSession, ReviewTime, SearchResult,
+    SizeStats, SlaEntry, SlaRule, TeamMember, TeamMention, TriageEntry,
+    TriageRule, UsageAnomaly, UserHistory, Workspace, WorkspaceCandidate,
+    WorkspaceHead,
+};
 
+pub mod access;
+pub mod accounts;
+pub mod actions;
+pub mod activity;
+pub mod advisories;
+pub mod annotations;
+pub mod anomalies;
+pub mod anon;
 pub mod api;
+pub mod away;
+pub mod backfill;
+pub mod backports;
+pub mod blockers;
+pub mod bots;
+pub mod commits;
+pub mod conflicts;
+pub mod contributors;
+pub mod deltas;
+pub mod dependencies;
+pub mod depupdates;
+pub mod diagnose;
+pub mod diffs;
+pub mod drafts;
+pub mod enrich;
+pub mod files;
+pub mod filters;
+pub mod fixes;
+pub mod followups;
 pub mod gql;
+pub mod identities;
+pub mod labelalerts;
+pub mod labels;
+pub mod ledger;
+pub mod limits;
+pub mod lint;
+pub mod mentions;
+pub mod orgs;
+pub mod orgscope;
+pub mod outbox;
+pub mod patches;
+pub mod pending;
+pub mod policy;
+pub mod profile;
 pub mod prs;
+pub mod quarantine;
+pub mod reactions;
+pub mod reconcile;
+pub mod references;
 pub mod refresh;
+pub mod releases;
+pub mod reminders;
+pub mod replies;
+pub mod report;
+pub mod repos;
 pub mod rest;
+pub mod reviewtime;
+pub mod search;
+pub mod sizes;
+pub mod sla;
+pub mod tags;
+pub mod templates;
+pub mod triage;
+pub mod turns;
 pub mod types;
 pub mod users;
+pub mod views;
+pub mod workspaces;
 
 pub struct Github {}
 
@@ -67,26 +152,29 @@ impl Github {
 
     /// Set the API Token to be used by GHD. Expects a callback function as
     /// argument, which will be called once the token is properly persisted on
-    /// disk.
+    /// disk. Setting a token for a different user adds a new account, which
+    /// becomes the main account.
     ///
     /// # Arguments
     ///
     /// * `db` - The GHD Database handle.
     /// * `token` - String containing the API Token to persist.
+    /// * `host` - String containing the host the token is valid for.
     /// * `cb` - Callback function to be called once the Token is persisted.
     ///
     pub async fn set_token<F>(
         self: &Self,
         db: &DB,
         token: &String,
+        host: &String,
         cb: F,
     ) -> Result<(), GHDError>
     where
         F: FnOnce(&GithubUser),
     {
-        println!("setting token {}", token);
+        println!("setting token for host {}", host);
         println!("  obtaining user for token");
-        let user: GithubUser = match users::whoami(token).await {
+        let user: GithubUser = match users::whoami(token, host).await {
             Ok(res) => res,
             Err(err) => {
                 return match err {
@@ -106,7 +194,11 @@ impl Github {
             }
         };
 
-        users::add_user_to_db(&mut tx, &user).await;
+        let is_new_user =
+            users::get_user_by_login(&db, &user.login).await.is_err();
+        if is_new_user {
+            users::add_user_to_db(&mut tx, &user).await;
+        }
 
         sqlx::query(
             "INSERT OR REPLACE into tokens (token, user_id) VALUES (?, ?)",
@@ -119,12 +211,16 @@ impl Github {
             panic!("Error inserting token into database: {}", err);
         });
 
+        accounts::add_account(&mut tx, &user.id, &host).await;
+
         tx.commit().await.unwrap_or_else(|err| {
             panic!("Unable to commit transaction to set token: {}", err);
         });
         println!("  user and token have been set!");
 
-        self.populate_user(&db, &user.login).await.unwrap();
+        if is_new_user {
+            self.populate_user(&db, &user.login).await.unwrap();
+        }
 
         cb(&user);
         Ok(())
@@ -153,7 +249,7 @@ impl Github {
             Err(err) => return Err(err),
         };
 
-        let ghreq = rest::GithubRequest::new(&token);
+        let ghreq = rest::GithubRequest::new(&token).for_feature("users");
         let reqstr = format!("/users/{}", login);
         let req = ghreq.get(&reqstr);
         match ghreq.send::<rest::GithubUserReply>(req).await {
@@ -204,6 +300,9 @@ impl Github {
             Err(err) => return Err(err),
         };
 
+        // users are tracked through the main account.
+        let main = users::get_main_user(&db).await;
+
         let mut tx = match db.pool().begin().await {
             Ok(res) => res,
             Err(err) => {
@@ -213,6 +312,10 @@ impl Github {
 
         users::add_user_to_db(&mut tx, &user).await;
 
+        if let Ok(main) = &main {
+            accounts::add_user_to_account(&mut tx, &user.id, &main.id).await;
+        }
+
         tx.commit().await.unwrap_or_else(|err| {
             panic!("Unable to commit transaction to track new user: {}", err);
         });
@@ -223,6 +326,40 @@ impl Github {
         Ok(user)
     }
 
+    /// Stop tracking the specified user by their login, dropping what was
+    /// synced on their behalf. Users owning one of our accounts can't be
+    /// untracked; their account must be removed instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `login` - String containing the login of the user to untrack.
+    ///
+    pub async fn untrack_user(
+        self: &Self,
+        db: &DB,
+        login: &String,
+    ) -> Result<(), GHDError> {
+        let user = users::get_user_by_login(&db, &login).await?;
+        if users::is_account_owner(&db, &user.id).await {
+            return Err(GHDError::BadRequest);
+        }
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to untrack user: {}", err);
+            }
+        };
+
+        users::remove_user_from_db(&mut tx, &user.id).await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit transaction to untrack user: {}", err);
+        });
+        Ok(())
+    }
+
     /// Populate the database for a newly-added user.
     ///
     /// # Arguments
@@ -259,28 +396,60 @@ impl Github {
             }
         };
 
+        let mut journal = journal::Sync::start(format!("populate:{}", login));
+
         // obtain user information through GraphQL API
-        let token: String = match self.get_token(&db).await {
-            Ok(t) => t.clone(),
-            Err(_) => {
-                panic!("Token not set!");
-            }
-        };
+        let creds =
+            match accounts::get_credentials_for_user(&db, &user.id).await {
+                Ok(c) => c,
+                Err(_) => {
+                    panic!("Token not set!");
+                }
+            };
+
+        // every open issue is synced at first, but only recent history, the
+        // rest being left for a backfill, so that first runs don't take
+        // forever.
+        let since = backfill::get_initial_since(&db).await;
+        let res =
+            match gql::get_user_open_issues(&creds.token, &creds.host, &login)
+                .await
+            {
+                Ok(info) => info,
+                Err(err) => {
+                    panic!(
+                        "Unexpected error populating user from GQL: {:?}",
+                        err
+                    );
+                }
+            };
 
-        // let res = match gql::get_user_info(&token, &login).await {
-        let res = match gql::get_user_open_issues(&token, &login).await {
-            Ok(info) => info,
+        // files come in bulk along with a search, rather than a REST call
+        // per Pull Request; those left out are enriched later on.
+        let files = match gql::get_user_open_pull_files(
+            &creds.token,
+            &creds.host,
+            &login,
+            &since,
+        )
+        .await
+        {
+            Ok(res) => res,
             Err(err) => {
-                panic!("Unexpected error populating user from GQL: {:?}", err);
+                println!("error obtaining files for '{}': {:?}", login, err);
+                vec![]
             }
         };
 
+        let conventions = backports::BackportConventions::load(&db).await;
+
         let mut tx = match db.pool().begin().await {
             Ok(res) => res,
             Err(err) => {
                 panic!("Error starting transaction to populate user: {}", err);
             }
         };
+        journal.begin(&mut tx).await;
 
         if let Err(err) =
             prs::consume_issues(&mut tx, &user.id, &res.issues, &res.prs).await
@@ -290,9 +459,37 @@ impl Github {
                 err
             );
         };
+        backports::consume(&mut tx, &conventions, &res.prs).await;
+        triage::classify(&mut tx, &res.issues).await;
+        templates::consume(&mut tx, &res.prs).await;
+        dependencies::consume(
+            &mut tx,
+            &res.issues
+                .iter()
+                .chain(res.prs.iter().map(|pr| &pr.issue))
+                .collect(),
+        )
+        .await;
+        journal.count_changes(&mut tx, "issues").await;
 
+        for (prid, prfiles) in &files {
+            if !res.prs.iter().any(|pr| &pr.issue.id == prid) {
+                continue;
+            }
+            files::consume(&mut tx, &prid, &prfiles, false, &res.when).await;
+        }
+        journal.count_changes(&mut tx, "files").await;
+
+        backfill::record_synced_from(
+            &mut tx,
+            &user.id,
+            &since.map(|s| s.timestamp()),
+        )
+        .await;
         users::update_user_refresh(&mut tx, &user.id, &res.when).await;
 
+        journal.finish(&mut tx, None).await;
+
         tx.commit().await.unwrap_or_else(|err| {
             panic!(
                 "Unable to commit populate transaction for user '{}': {}",
@@ -303,7 +500,7 @@ impl Github {
         Ok(())
     }
 
-    /// Refreshes the specified user's data.
+    /// Refreshes the specified user's data, returning what changed.
     ///
     /// # Arguments
     ///
@@ -314,7 +511,7 @@ impl Github {
         self: &Self,
         db: &DB,
         login: &String,
-    ) -> Result<bool, GHDError> {
+    ) -> Result<IssuesDiff, GHDError> {
         let user = match users::get_user_by_login(&db, &login).await {
             Ok(u) => u,
             Err(GHDError::UserNotFoundError) => {
@@ -328,12 +525,14 @@ impl Github {
             }
         };
 
-        let token = match self.get_token(&db).await {
-            Ok(t) => t.clone(),
-            Err(_) => {
-                panic!("Token not set!");
-            }
-        };
+        let mut journal = journal::Sync::start(format!("user:{}", login));
+        let creds =
+            match accounts::get_credentials_for_user(&db, &user.id).await {
+                Ok(c) => c,
+                Err(_) => {
+                    panic!("Token not set!");
+                }
+            };
 
         let last_update = match refresh::get_user_refresh(&db, &user.id).await {
             Ok(v) => v,
@@ -342,16 +541,24 @@ impl Github {
             }
         };
 
-        let res =
-            match gql::get_user_updates(&token, &login, &last_update).await {
-                Ok(updates) => updates,
-                Err(err) => {
-                    panic!(
+        let res = match gql::get_user_updates(
+            &creds.token,
+            &creds.host,
+            &login,
+            &last_update,
+        )
+        .await
+        {
+            Ok(updates) => updates,
+            Err(err) => {
+                panic!(
                     "Unexpected error obtaining user updates from GQL: {:?}",
                     err
                 );
-                }
-            };
+            }
+        };
+
+        let conventions = backports::BackportConventions::load(&db).await;
 
         let mut tx = match db.pool().begin().await {
             Ok(res) => res,
@@ -359,23 +566,39 @@ impl Github {
                 panic!("Error starting transaction to update user: {}", err);
             }
         };
+        journal.begin(&mut tx).await;
 
-        let mut ret = true;
-        if res.prs.is_empty() && res.issues.is_empty() {
+        let diff =
+            match prs::consume_issues(&mut tx, &user.id, &res.issues, &res.prs)
+                .await
+            {
+                Ok(d) => d,
+                Err(err) => {
+                    panic!(
+                        "Error updating pull requests for user '{}': {:?}",
+                        login, err
+                    );
+                }
+            };
+        if diff.is_empty() {
             println!("nothing to update for user '{}'.", user.login);
-            ret = false;
-        }
-
-        if let Err(err) =
-            prs::consume_issues(&mut tx, &user.id, &res.issues, &res.prs).await
-        {
-            panic!(
-                "Error updating pull requests for user '{}': {:?}",
-                login, err
-            );
         }
+        backports::consume(&mut tx, &conventions, &res.prs).await;
+        triage::classify(&mut tx, &res.issues).await;
+        templates::consume(&mut tx, &res.prs).await;
+        dependencies::consume(
+            &mut tx,
+            &res.issues
+                .iter()
+                .chain(res.prs.iter().map(|pr| &pr.issue))
+                .collect(),
+        )
+        .await;
         users::update_user_refresh(&mut tx, &user.id, &res.when).await;
 
+        journal.count_changes(&mut tx, "issues").await;
+        journal.finish(&mut tx, None).await;
+
         tx.commit().await.unwrap_or_else(|err| {
             panic!(
                 "Unable to commit update transaction for user '{}': {}",
@@ -383,42 +606,2976 @@ impl Github {
             );
         });
 
-        Ok(ret)
+        Ok(diff)
     }
 
-    /// Obtain all Pull Requests from the provided author `login`.
+    /// Obtain a page of the Pull Requests from the provided author `login`.
     ///
     pub async fn get_pulls_by_author(
         self: &Self,
         db: &DB,
         login: &String,
-    ) -> Result<Vec<PullRequestTableEntry>, GHDError> {
-        prs::get_prs_by_author(&db, &login).await
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        prs::get_prs_by_author(&db, &login, &page).await
     }
 
-    /// Obtain all Pull Requests the provided `login` is involved with, except
-    /// those that have been authored by `login`.
+    /// Obtain a page of the Pull Requests the provided `login` is involved
+    /// with, except those that have been authored by `login`, optionally
+    /// only those it has a given `relation` to.
     ///
     pub async fn get_involved_pulls(
         self: &Self,
         db: &DB,
         login: &String,
-    ) -> Result<Vec<PullRequestTableEntry>, GHDError> {
-        prs::get_involved_prs(&db, &login).await
+        relation: &Option<String>,
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        prs::get_involved_prs(&db, &login, &relation, &page).await
+    }
+
+    /// Diagnose the health of the token for the account owned by `userid`,
+    /// or the main account if not specified.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `userid` - Optional database ID of the account's user.
+    ///
+    pub async fn diagnose_auth(
+        self: &Self,
+        db: &DB,
+        userid: &Option<i64>,
+    ) -> Result<AuthReport, GHDError> {
+        let userid = match userid {
+            Some(id) => *id,
+            None => users::get_main_user(&db).await?.id,
+        };
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        Ok(diagnose::diagnose(&db, &creds).await)
+    }
+
+    /// Obtain all configured accounts.
+    ///
+    pub async fn get_accounts(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<Account>, GHDError> {
+        accounts::get_accounts(&db).await
+    }
+
+    /// Obtain a page of the Pull Requests involving users tracked by all
+    /// accounts, tagged with the account they belong to.
+    ///
+    pub async fn get_aggregated_pulls(
+        self: &Self,
+        db: &DB,
+        page: &PageRequest,
+    ) -> Result<Page<AccountPullRequestEntry>, GHDError> {
+        accounts::get_aggregated_prs(&db.snapshot().await, &page).await
+    }
+
+    /// Obtain badge counts for each configured account.
+    ///
+    pub async fn get_aggregated_counts(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<AccountCounts>, GHDError> {
+        accounts::get_aggregated_counts(&db.snapshot().await).await
+    }
+
+    /// Obtain the first page of Pull Requests for each configured account,
+    /// their badge counts, and the sync status, all as of the same point in
+    /// time.
+    ///
+    pub async fn get_dashboard(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Dashboard, GHDError> {
+        let snapshot = db.snapshot().await;
+        let page = PageRequest::new(&None, &None)?;
+        Ok(Dashboard {
+            pulls: accounts::get_aggregated_prs(&snapshot, &page).await?,
+            counts: accounts::get_aggregated_counts(&snapshot).await?,
+            sync_status: throttle::get_status(&snapshot).await,
+            refreshed_at: refresh::get_users_refreshed_at(&snapshot).await,
+        })
     }
 
-    /// Marks a specified Pull Request as having been viewed.
+    /// Refreshes the organizations and teams the account owned by user
+    /// `userid` belongs to.
     ///
     /// # Arguments
     ///
     /// * `db` - A GHD Database handle.
-    /// * `prid` - The Pull Request's database ID.
+    /// * `userid` - The database ID of the account's user.
     ///
-    pub async fn mark_pull_request_viewed(
+    pub async fn refresh_orgs(
         self: &Self,
         db: &DB,
-        prid: &i64,
+        userid: &i64,
+    ) -> Result<(), GHDError> {
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        let res = orgs::fetch(&creds).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to update orgs: {}", err);
+            }
+        };
+
+        match &res {
+            Ok(update) => orgs::consume(&mut tx, &userid, &update).await,
+            Err(_) => {
+                // don't retry on every iteration if, e.g., the token lacks
+                // the required scopes.
+                let key = orgs::refresh_key(&userid);
+                let now = chrono::Utc::now();
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit orgs for user {}: {}", userid, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Refreshes the profile stats of the account owned by user `userid`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `userid` - The database ID of the account's user.
+    ///
+    pub async fn refresh_profile_stats(
+        self: &Self,
+        db: &DB,
+        userid: &i64,
     ) -> Result<(), GHDError> {
-        prs::mark_viewed(&db, &prid).await
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        let res = profile::fetch(&creds, &userid).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to update profile: {}", err);
+            }
+        };
+
+        match &res {
+            Ok(stats) => profile::consume(&mut tx, &stats).await,
+            Err(_) => {
+                let key = profile::refresh_key(&userid);
+                let now = chrono::Utc::now();
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit profile for user {}: {}", userid, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Refreshes the reviews user `userid`, logged in as `login`, started
+    /// on GitHub but never submitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `userid` - The database ID of the account's user.
+    /// * `login` - The account's login.
+    ///
+    pub async fn refresh_pending_reviews(
+        self: &Self,
+        db: &DB,
+        userid: &i64,
+        login: &String,
+    ) -> Result<usize, GHDError> {
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        let now = chrono::Utc::now();
+        let res = gql::get_pending_reviews(
+            &creds.token,
+            &creds.host,
+            &userid,
+            &login,
+        )
+        .await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update pending reviews: {}",
+                    err
+                );
+            }
+        };
+
+        match &res {
+            Ok(lst) => pending::consume(&mut tx, &userid, &lst, &now).await,
+            Err(_) => {
+                let key = pending::refresh_key(&userid);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit pending reviews for {}: {}", userid, err);
+        });
+
+        res.map(|lst| lst.len())
+    }
+
+    /// Obtain the reviews our users started on GitHub but never submitted.
+    ///
+    pub async fn get_pending_reviews(
+        self: &Self,
+        db: &DB,
+    ) -> Vec<PendingReview> {
+        pending::get_pending(&db).await
+    }
+
+    /// Refreshes the comments user `userid`, logged in as `login`, posted
+    /// on open issues and Pull Requests without getting a reply since.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `userid` - The database ID of the account's user.
+    /// * `login` - The account's login.
+    ///
+    pub async fn refresh_follow_ups(
+        self: &Self,
+        db: &DB,
+        userid: &i64,
+        login: &String,
+    ) -> Result<usize, GHDError> {
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        let now = chrono::Utc::now();
+        let res = gql::get_follow_ups(
+            &creds.token,
+            &creds.host,
+            &userid,
+            &login,
+            &followups::get_window_start(),
+        )
+        .await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update follow-ups: {}",
+                    err
+                );
+            }
+        };
+
+        match &res {
+            Ok(lst) => followups::consume(&mut tx, &userid, &lst, &now).await,
+            Err(_) => {
+                let key = followups::refresh_key(&userid);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit follow-ups for {}: {}", userid, err);
+        });
+
+        res.map(|lst| lst.len())
+    }
+
+    /// Obtain the comments our users posted that are awaiting a response.
+    ///
+    pub async fn get_follow_ups(self: &Self, db: &DB) -> Vec<FollowUp> {
+        followups::get_awaiting(&db).await
+    }
+
+    /// Dismiss the follow-up on comment `commentid`.
+    ///
+    pub async fn dismiss_follow_up(self: &Self, db: &DB, commentid: &i64) {
+        followups::dismiss(&db, &commentid).await;
+    }
+
+    /// Refreshes the issues and Pull Requests mentioning team `team`, as
+    /// seen by user `userid`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `userid` - The database ID of the account's user.
+    /// * `team` - The team to refresh mentions for.
+    ///
+    pub async fn refresh_team_mentions(
+        self: &Self,
+        db: &DB,
+        userid: &i64,
+        team: &GithubTeam,
+    ) -> Result<usize, GHDError> {
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        let now = chrono::Utc::now();
+        let res = mentions::fetch(&db, &creds, &team).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update mentions: {}",
+                    err
+                );
+            }
+        };
+
+        match &res {
+            Ok(lst) => mentions::consume(&mut tx, &team.id, &lst, &now).await,
+            Err(_) => {
+                let key = mentions::refresh_key(&team.id);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit mentions for team {}: {}", team.id, err);
+        });
+
+        res.map(|lst| lst.len())
+    }
+
+    /// Obtain the issues and Pull Requests mentioning our users' teams.
+    ///
+    pub async fn get_team_mentions(self: &Self, db: &DB) -> Vec<TeamMention> {
+        mentions::get_mentions(&db).await
+    }
+
+    /// Obtain the cached profile stats of the main user.
+    ///
+    pub async fn get_profile_stats(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Option<ProfileStats>, GHDError> {
+        let user = users::get_main_user(&db).await?;
+        profile::get_stats(&db, &user.id).await
+    }
+
+    /// Refreshes the organization-wide sync of the provided scope, as the user
+    /// it was enabled by.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `scope` - The organization to sync.
+    ///
+    pub async fn refresh_org_scope(
+        self: &Self,
+        db: &DB,
+        scope: &OrgScope,
+    ) -> Result<(), GHDError> {
+        let creds =
+            accounts::get_credentials_for_user(&db, &scope.user_id).await?;
+        let res =
+            orgscope::fetch(&creds, &scope.org_id, &scope.org_login).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to update org: {}", err);
+            }
+        };
+
+        match &res {
+            Ok(update) => orgscope::consume(&mut tx, &update).await,
+            Err(_) => {
+                // e.g., no longer an admin; don't retry on every iteration.
+                let key = orgscope::refresh_key(&scope.org_id);
+                let now = chrono::Utc::now();
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit org '{}': {}", scope.org_login, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Obtain the main user's organization with login `org`.
+    ///
+    async fn get_main_user_org(
+        self: &Self,
+        db: &DB,
+        org: &String,
+    ) -> Result<(i64, GithubOrg), GHDError> {
+        let user = users::get_main_user(&db).await?;
+        match orgs::get_orgs(&db, &user.id)
+            .await?
+            .into_iter()
+            .find(|o| &o.login == org)
+        {
+            Some(res) => Ok((user.id, res)),
+            None => Err(GHDError::NotFoundError),
+        }
+    }
+
+    /// Enable syncing all open Pull Requests of the main user's organization
+    /// `org`, provided they're one of its admins.
+    ///
+    pub async fn enable_org_scope(
+        self: &Self,
+        db: &DB,
+        org: &String,
+    ) -> Result<(), GHDError> {
+        let (userid, o) = self.get_main_user_org(&db, &org).await?;
+        let creds = accounts::get_credentials_for_user(&db, &userid).await?;
+        orgscope::enable(&db, &creds, &userid, &o.id, &o.login).await
+    }
+
+    /// Disable syncing all open Pull Requests of organization `org`.
+    ///
+    pub async fn disable_org_scope(
+        self: &Self,
+        db: &DB,
+        org: &String,
+    ) -> Result<(), GHDError> {
+        let (_, o) = self.get_main_user_org(&db, &org).await?;
+        orgscope::disable(&db, &o.id).await;
+        Ok(())
+    }
+
+    /// Obtain the organizations whose open Pull Requests are all synced.
+    ///
+    pub async fn get_org_scopes(self: &Self, db: &DB) -> Vec<OrgScope> {
+        orgscope::get_scopes(&db).await
+    }
+
+    /// Obtain an overview of organization `org`'s open Pull Requests, by
+    /// repository and by team.
+    ///
+    pub async fn get_org_overview(
+        self: &Self,
+        db: &DB,
+        org: &String,
+    ) -> Result<OrgOverview, GHDError> {
+        let (_, o) = self.get_main_user_org(&db, &org).await?;
+        orgscope::get_overview(&db, &o.id).await
+    }
+
+    /// Obtain the organizations the main user belongs to.
+    ///
+    pub async fn get_orgs(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<GithubOrg>, GHDError> {
+        let user = users::get_main_user(&db).await?;
+        orgs::get_orgs(&db, &user.id).await
+    }
+
+    /// Obtain the teams the main user is a member of.
+    ///
+    pub async fn get_teams(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<GithubTeam>, GHDError> {
+        let user = users::get_main_user(&db).await?;
+        orgs::get_teams(&db, &user.id).await
+    }
+
+    /// Obtain the roster for the team `teamid`.
+    ///
+    pub async fn get_team_members(
+        self: &Self,
+        db: &DB,
+        teamid: &i64,
+    ) -> Result<Vec<TeamMember>, GHDError> {
+        orgs::get_team_members(&db, &teamid).await
+    }
+
+    /// Refreshes the changed files of the provided Pull Request.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `pr` - The Pull Request to refresh the files for.
+    ///
+    pub async fn refresh_pull_files(
+        self: &Self,
+        db: &DB,
+        pr: &PullRequestTableEntry,
+    ) -> Result<(), GHDError> {
+        let creds = accounts::get_credentials_for_issue(&db, &pr.id).await?;
+        let now = chrono::Utc::now();
+        let res = files::fetch(&creds, &pr).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to update files: {}", err);
+            }
+        };
+
+        // on error, keep the files we have but still record the attempt so
+        // we don't retry on every iteration; we'll try again once the Pull
+        // Request is updated.
+        match &res {
+            Ok((f, truncated)) => {
+                files::consume(&mut tx, &pr.id, &f, *truncated, &now).await
+            }
+            Err(_) => {
+                let key = files::refresh_key(&pr.id);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit files for pr {}: {}", pr.id, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Refreshes the commits of the provided Pull Request, along with their
+    /// signature verification status.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `pr` - The Pull Request to refresh commits for.
+    ///
+    pub async fn refresh_pull_commits(
+        self: &Self,
+        db: &DB,
+        pr: &PullRequestTableEntry,
+    ) -> Result<(), GHDError> {
+        let creds = accounts::get_credentials_for_issue(&db, &pr.id).await?;
+        let now = chrono::Utc::now();
+        let res = gql::get_pull_request_commits(
+            &creds.token,
+            &creds.host,
+            &pr.id,
+            &pr.repo_owner,
+            &pr.repo_name,
+            &pr.number,
+        )
+        .await;
+
+        // compute what changed since the Pull Request was last viewed, if
+        // its head moved since.
+        let delta = match (&res, deltas::get_viewed_head(&db, &pr.id).await) {
+            (Ok(update), Some(viewed)) if viewed != update.head_sha => {
+                match deltas::fetch(&creds, &pr, &viewed, &update.head_sha)
+                    .await
+                {
+                    Ok(d) => Some(d),
+                    Err(err) => {
+                        println!(
+                            "error computing delta for pr {}: {:?}",
+                            pr.id, err
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to update commits: {}", err);
+            }
+        };
+
+        match &res {
+            Ok(update) => {
+                commits::consume(&mut tx, &pr.id, &update, &now).await;
+                if let Some(d) = &delta {
+                    deltas::consume(&mut tx, &pr.id, &d).await;
+                }
+            }
+            Err(_) => {
+                let key = commits::refresh_key(&pr.id);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit commits for pr {}: {}", pr.id, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Refreshes what is blocking the provided Pull Request from being merged,
+    /// in terms of required checks and approvals.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `pr` - The Pull Request to refresh blockers for.
+    ///
+    pub async fn refresh_pull_blockers(
+        self: &Self,
+        db: &DB,
+        pr: &PullRequestTableEntry,
+    ) -> Result<(), GHDError> {
+        let creds = accounts::get_credentials_for_issue(&db, &pr.id).await?;
+        let now = chrono::Utc::now();
+        let res = blockers::fetch(&creds, &pr).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update blockers: {}",
+                    err
+                );
+            }
+        };
+
+        match &res {
+            Ok(b) => blockers::consume(&mut tx, &pr.id, &b, &now).await,
+            Err(_) => {
+                let key = blockers::refresh_key(&pr.id);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit blockers for pr {}: {}", pr.id, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Runs the provided enrichment job.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `job` - The enrichment to run, and the Pull Request to run it on.
+    ///
+    pub async fn enrich_pull(
+        self: &Self,
+        db: &DB,
+        job: &enrich::EnrichJob,
+    ) -> Result<(), GHDError> {
+        match job.kind {
+            enrich::EnrichKind::Files => {
+                self.refresh_pull_files(&db, &job.pr).await
+            }
+            enrich::EnrichKind::Commits => {
+                self.refresh_pull_commits(&db, &job.pr).await
+            }
+            enrich::EnrichKind::Blockers => {
+                self.refresh_pull_blockers(&db, &job.pr).await
+            }
+        }
+    }
+
+    /// Obtain everything we know about Pull Request `prid`.
+    ///
+    pub async fn get_pull_detail(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<PullRequestDetail, GHDError> {
+        Ok(PullRequestDetail {
+            pr: prs::get_pr(&db, &prid).await?,
+            signatures: commits::get_signatures(&db, &prid).await?,
+            blockers: blockers::get_blockers(&db, &prid).await?,
+            since_last_view: deltas::get_delta(&db, &prid).await?,
+            truncated: limits::get_truncations(&db, &prid).await,
+        })
+    }
+
+    /// Obtain the files changed by Pull Request `prid`.
+    ///
+    pub async fn get_pull_files(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<Vec<PullRequestFile>, GHDError> {
+        files::get_files(&db, &prid).await
+    }
+
+    /// Obtain a page of the Pull Requests touching files under the provided
+    /// `path`.
+    ///
+    pub async fn get_pulls_by_path(
+        self: &Self,
+        db: &DB,
+        path: &String,
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        files::get_prs_by_path(&db, &path, &page).await
+    }
+
+    /// Save a new path view, returning its ID.
+    ///
+    pub async fn add_path_view(
+        self: &Self,
+        db: &DB,
+        view: &PathView,
+    ) -> Result<i64, GHDError> {
+        views::add_view(&db, &view).await
+    }
+
+    /// Remove path view `viewid`.
+    ///
+    pub async fn remove_path_view(self: &Self, db: &DB, viewid: &i64) {
+        views::remove_view(&db, &viewid).await
+    }
+
+    /// Obtain all saved path views.
+    ///
+    pub async fn get_path_views(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<PathView>, GHDError> {
+        views::get_views(&db).await
+    }
+
+    /// Obtain a page of the Pull Requests in path view `viewid`.
+    ///
+    pub async fn get_pulls_by_view(
+        self: &Self,
+        db: &DB,
+        viewid: &i64,
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        views::get_prs(&db, &viewid, &page).await
+    }
+
+    /// Obtain the open Pull Requests potentially conflicting with Pull Request
+    /// `prid`, based on the files they touch.
+    ///
+    pub async fn get_pull_conflicts(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<Vec<PullRequestConflict>, GHDError> {
+        conflicts::get_conflicts(&db, &prid).await
+    }
+
+    /// Draft release notes for a repository, from the Pull Requests merged
+    /// since its latest release's tag. If `tag` is provided, the notes are
+    /// also pushed to the repository as a draft release for said tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    /// * `tag` - Optional tag to create a draft release for.
+    ///
+    pub async fn draft_release_notes(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+        tag: &Option<String>,
+    ) -> Result<ReleaseNotesDraft, GHDError> {
+        let creds = accounts::get_main_credentials(&db).await?;
+        let last = releases::fetch_last_tag(&creds, &owner, &name).await?;
+        let since = last.as_ref().map(|(_, dt)| dt.timestamp());
+
+        let prs =
+            releases::get_merged_prs_since(&db, &owner, &name, &since).await?;
+        let markdown = releases::render_markdown(&prs);
+
+        let release_url = match tag {
+            Some(t) => Some(
+                releases::push_draft(&creds, &owner, &name, &t, &markdown)
+                    .await?
+                    .html_url,
+            ),
+            None => None,
+        };
+
+        Ok(ReleaseNotesDraft {
+            repo_owner: owner.clone(),
+            repo_name: name.clone(),
+            since_tag: last.map(|(t, _)| t),
+            since,
+            num_prs: prs.len(),
+            markdown,
+            release_url,
+        })
+    }
+
+    /// Obtain the known backports of Pull Request `prid`.
+    ///
+    pub async fn get_pull_backports(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<Vec<BackportEntry>, GHDError> {
+        backports::get_backports(&db, &prid).await
+    }
+
+    /// Obtain the merged Pull Requests of a repository still lacking
+    /// backports to its maintained branches.
+    ///
+    pub async fn get_missing_backports(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<Vec<BackportEntry>, GHDError> {
+        backports::get_missing_backports(&db, &owner, &name).await
+    }
+
+    /// Add repository `owner/name` to the set of maintained repositories.
+    ///
+    pub async fn add_repo(self: &Self, db: &DB, owner: &String, name: &String) {
+        repos::add_repo(&db, &owner, &name).await
+    }
+
+    /// Add the repositories `names` owned by `owner` to the set of
+    /// maintained repositories.
+    ///
+    pub async fn add_repos(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        names: &Vec<String>,
+    ) {
+        repos::add_repos(&db, &owner, &names).await
+    }
+
+    /// Obtain the repositories of organization `org`, using the main
+    /// account's credentials, so they can be added in bulk.
+    ///
+    pub async fn get_org_repos(
+        self: &Self,
+        db: &DB,
+        org: &String,
+    ) -> Result<Vec<OrgRepository>, GHDError> {
+        let creds = accounts::get_main_credentials(&db).await?;
+        repos::get_org_repos(&db, &creds, &org).await
+    }
+
+    /// Remove repository `owner/name` from the set of maintained
+    /// repositories.
+    ///
+    pub async fn remove_repo(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) {
+        repos::remove_repo(&db, &owner, &name).await
+    }
+
+    /// Obtain all maintained repositories.
+    ///
+    pub async fn get_repos(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<Repository>, GHDError> {
+        repos::get_repos(&db).await
+    }
+
+    /// Refreshes the branches of repository `owner/name`, using the main
+    /// account's credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_repo_branches(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let mut journal =
+            journal::Sync::start(format!("branches:{}/{}", owner, name));
+        let creds = accounts::get_main_credentials(&db).await?;
+        let res =
+            gql::get_repo_branches(&creds.token, &creds.host, &owner, &name)
+                .await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update branches: {}",
+                    err
+                );
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        match &res {
+            Ok(update) => repos::consume(&mut tx, &owner, &name, &update).await,
+            Err(_) => {
+                let key = repos::refresh_key(&owner, &name);
+                let now = chrono::Utc::now();
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        journal.count_changes(&mut tx, "branches").await;
+        journal
+            .finish(&mut tx, res.as_ref().err().map(|e| format!("{:?}", e)))
+            .await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit branches for '{}/{}': {}",
+                owner, name, err
+            );
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Checks whether repository `owner/name` has been renamed or
+    /// transferred, using the main account's credentials, and if so updates
+    /// everything known about it to its current name, which is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn track_repo_rename(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<Option<(String, String)>, GHDError> {
+        let creds = accounts::get_main_credentials(&db).await?;
+        let res = repos::get_renamed(&creds, &owner, &name).await?;
+        if let Some((new_owner, new_name)) = &res {
+            repos::rename_repo(&db, &owner, &name, &new_owner, &new_name).await;
+        }
+        Ok(res)
+    }
+
+    /// Obtain how syncing each maintained repository is going.
+    ///
+    pub async fn get_repo_sync_status(
+        self: &Self,
+        db: &DB,
+    ) -> Vec<RepoSyncStatus> {
+        quarantine::get_status(&db).await
+    }
+
+    /// Retry syncing quarantined repository `owner/name` right away.
+    ///
+    pub async fn retry_repo_sync(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) {
+        quarantine::retry(&db, &owner, &name).await
+    }
+
+    /// Pause or resume syncing repository `owner/name`.
+    ///
+    pub async fn set_repo_sync_paused(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+        paused: bool,
+    ) {
+        quarantine::set_paused(&db, &owner, &name, paused).await
+    }
+
+    /// Obtain the dependency graph of issue `issueid`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `issueid` - The issue's database ID.
+    ///
+    pub async fn get_dependency_graph(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+    ) -> Result<DependencyGraph, GHDError> {
+        dependencies::get_graph(&db, &issueid).await
+    }
+
+    /// Obtain the branches of repository `owner/name`.
+    ///
+    pub async fn get_repo_branches(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<Vec<RepoBranch>, GHDError> {
+        repos::get_branches(&db, &owner, &name).await
+    }
+
+    /// Obtain the stale branches of repository `owner/name`; i.e., those
+    /// without open Pull Requests that haven't been committed to recently.
+    ///
+    pub async fn get_stale_branches(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<Vec<RepoBranch>, GHDError> {
+        repos::get_stale_branches(&db, &owner, &name).await
+    }
+
+    /// Add a triage rule, returning its ID.
+    ///
+    pub async fn add_triage_rule(
+        self: &Self,
+        db: &DB,
+        rule: &TriageRule,
+    ) -> Result<i64, GHDError> {
+        triage::add_rule(&db, &rule).await
+    }
+
+    /// Remove triage rule `ruleid`.
+    ///
+    pub async fn remove_triage_rule(self: &Self, db: &DB, ruleid: &i64) {
+        triage::remove_rule(&db, &ruleid).await
+    }
+
+    /// Obtain the triage rules for repository `owner/name`.
+    ///
+    pub async fn get_triage_rules(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<Vec<TriageRule>, GHDError> {
+        triage::get_rules(&db, &owner, &name).await
+    }
+
+    /// Subscribe to a label transition on an issue, returning the alert's ID.
+    ///
+    pub async fn add_label_alert(
+        self: &Self,
+        db: &DB,
+        alert: &LabelAlert,
+    ) -> Result<i64, GHDError> {
+        labelalerts::add_alert(&db, &alert).await
+    }
+
+    /// Remove label alert `alertid`.
+    ///
+    pub async fn remove_label_alert(self: &Self, db: &DB, alertid: &i64) {
+        labelalerts::remove_alert(&db, &alertid).await
+    }
+
+    /// Obtain the label alerts on issue `issueid`, or on every issue.
+    ///
+    pub async fn get_label_alerts(
+        self: &Self,
+        db: &DB,
+        issueid: &Option<i64>,
+    ) -> Vec<LabelAlert> {
+        labelalerts::get_alerts(&db, &issueid).await
+    }
+
+    /// Obtain the Pull Requests known to close issue `issueid`.
+    ///
+    pub async fn get_issue_fixes(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+    ) -> Vec<IssueFix> {
+        fixes::get_fixes(&db, &issueid).await
+    }
+
+    /// Obtain the issues and Pull Requests linking to issue `issueid`, up to
+    /// `depth` references away.
+    ///
+    pub async fn get_issue_references(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        depth: &i64,
+    ) -> Vec<IssueReference> {
+        references::get_references(&db, &issueid, &depth).await
+    }
+
+    /// Set the service level for issues with a given label on a repository.
+    ///
+    pub async fn set_sla_rule(
+        self: &Self,
+        db: &DB,
+        rule: &SlaRule,
+    ) -> Result<(), GHDError> {
+        sla::set_rule(&db, &rule).await
+    }
+
+    /// Remove the service level for issues labeled `label` on repository
+    /// `owner/name`.
+    ///
+    pub async fn remove_sla_rule(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+        label: &String,
+    ) {
+        sla::remove_rule(&db, &owner, &name, &label).await
+    }
+
+    /// Obtain the service levels set, on repository `owner/name` if provided.
+    ///
+    pub async fn get_sla_rules(
+        self: &Self,
+        db: &DB,
+        owner: &Option<String>,
+        name: &Option<String>,
+    ) -> Vec<SlaRule> {
+        sla::get_rules(&db, &owner, &name).await
+    }
+
+    /// Obtain the open issues at risk of, or in, breach of their service
+    /// level, on repository `owner/name` if provided.
+    ///
+    pub async fn get_sla_breaches(
+        self: &Self,
+        db: &DB,
+        owner: &Option<String>,
+        name: &Option<String>,
+    ) -> Vec<SlaEntry> {
+        sla::get_breaches(&db, &owner, &name).await
+    }
+
+    /// Register the local clone at `path` as the workspace for `owner/name`.
+    ///
+    pub async fn set_workspace(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+        path: &String,
+    ) -> Result<(), GHDError> {
+        workspaces::set_workspace(&db, &owner, &name, &path).await
+    }
+
+    /// Remove the workspace registered for `owner/name`.
+    ///
+    pub async fn remove_workspace(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) {
+        workspaces::remove_workspace(&db, &owner, &name).await
+    }
+
+    /// Obtain all registered workspaces.
+    ///
+    pub async fn get_workspaces(self: &Self, db: &DB) -> Vec<Workspace> {
+        workspaces::get_workspaces(&db).await
+    }
+
+    /// Obtain the branch checked out in the workspace for `owner/name`, and
+    /// its open Pull Request.
+    ///
+    pub async fn get_workspace_head(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<WorkspaceHead, GHDError> {
+        workspaces::get_head(&db, &owner, &name).await
+    }
+
+    /// Scan the projects directory for clones of repositories to track.
+    ///
+    pub async fn scan_workspaces(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<WorkspaceCandidate>, GHDError> {
+        workspaces::scan(&db).await
+    }
+
+    /// Track the repositories of the given scanned clones, registering the
+    /// clones as their workspaces.
+    ///
+    pub async fn adopt_workspaces(
+        self: &Self,
+        db: &DB,
+        candidates: &Vec<WorkspaceCandidate>,
+    ) {
+        workspaces::adopt(&db, &candidates).await
+    }
+
+    /// Obtain the commands checking out Pull Request `prid` locally.
+    ///
+    pub async fn get_checkout_snippet(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<String, GHDError> {
+        workspaces::get_checkout_snippet(&db, &prid).await
+    }
+
+    /// Obtain a page of the triage inbox, optionally limited to a single
+    /// bucket.
+    ///
+    pub async fn get_triage_inbox(
+        self: &Self,
+        db: &DB,
+        bucket: &Option<String>,
+        page: &PageRequest,
+    ) -> Result<Page<TriageEntry>, GHDError> {
+        triage::get_inbox(&db, &bucket, &page).await
+    }
+
+    /// Dismiss issue `issueid` from the triage inbox.
+    ///
+    pub async fn dismiss_triage(self: &Self, db: &DB, issueid: &i64) {
+        triage::dismiss(&db, &issueid).await
+    }
+
+    /// Obtain a page of the open Pull Requests on maintained repositories
+    /// from first-time contributors.
+    ///
+    pub async fn get_first_time_pulls(
+        self: &Self,
+        db: &DB,
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        contributors::get_first_time_prs(&db, &page).await
+    }
+
+    /// Obtain contribution credit per contributor on maintained
+    /// repositories, counting co-authored commits and reviews along with
+    /// authored Pull Requests.
+    ///
+    pub async fn get_contribution_credits(
+        self: &Self,
+        db: &DB,
+        since: &Option<i64>,
+    ) -> Result<Vec<ContributionCredit>, GHDError> {
+        contributors::get_credits(&db, &since).await
+    }
+
+    /// Refreshes the security advisories affecting the dependencies of
+    /// repository `owner/name`, using the main account's credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_advisories(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let mut journal =
+            journal::Sync::start(format!("advisories:{}/{}", owner, name));
+        let creds = accounts::get_main_credentials(&db).await?;
+        let now = chrono::Utc::now();
+        let res = advisories::fetch(&creds, &owner, &name).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update advisories: {}",
+                    err
+                );
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        match &res {
+            Ok(findings) => {
+                advisories::consume(&mut tx, &owner, &name, &findings, &now)
+                    .await
+            }
+            Err(_) => {
+                // e.g., the dependency graph is disabled; try again tomorrow.
+                let key = advisories::refresh_key(&owner, &name);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        journal.count_changes(&mut tx, "advisory_findings").await;
+        journal
+            .finish(&mut tx, res.as_ref().err().map(|e| format!("{:?}", e)))
+            .await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit advisories for '{}/{}': {}",
+                owner, name, err
+            );
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Obtain the advisory feed, optionally limited to a single repository.
+    ///
+    pub async fn get_advisory_feed(
+        self: &Self,
+        db: &DB,
+        owner: &Option<String>,
+        name: &Option<String>,
+    ) -> Result<Vec<AdvisoryFinding>, GHDError> {
+        advisories::get_feed(&db, &owner, &name).await
+    }
+
+    /// Create a new identity named `name`, returning its ID.
+    ///
+    pub async fn create_identity(
+        self: &Self,
+        db: &DB,
+        name: &String,
+    ) -> Result<i64, GHDError> {
+        identities::create(&db, &name).await
+    }
+
+    /// Remove identity `identityid`.
+    ///
+    pub async fn remove_identity(self: &Self, db: &DB, identityid: &i64) {
+        identities::remove(&db, &identityid).await
+    }
+
+    /// Link user `login` on `host` to identity `identityid`.
+    ///
+    pub async fn link_identity(
+        self: &Self,
+        db: &DB,
+        identityid: &i64,
+        host: &String,
+        login: &String,
+    ) -> Result<(), GHDError> {
+        identities::link(&db, &identityid, &host, &login).await
+    }
+
+    /// Unlink user `login` on `host` from its identity.
+    ///
+    pub async fn unlink_identity(
+        self: &Self,
+        db: &DB,
+        host: &String,
+        login: &String,
+    ) {
+        identities::unlink(&db, &host, &login).await
+    }
+
+    /// Obtain all identities, along with their linked users.
+    ///
+    pub async fn get_identities(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<Identity>, GHDError> {
+        identities::get_identities(&db).await
+    }
+
+    /// Refreshes each account's permission level on repository
+    /// `owner/name`, forgetting those that can't be obtained. Fails only if
+    /// no account could obtain its permission.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_repo_access(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let mut res = Err(GHDError::TokenNotFoundError);
+        for creds in accounts::get_all_credentials(&db).await {
+            let now = chrono::Utc::now();
+            let level = access::fetch(&creds, &owner, &name).await;
+            let accountid = &creds.account_id;
+
+            let mut tx = match db.pool().begin().await {
+                Ok(res) => res,
+                Err(err) => {
+                    panic!(
+                        "Error starting transaction to update permission: {}",
+                        err
+                    );
+                }
+            };
+
+            match &level {
+                Ok(level) => {
+                    access::consume(
+                        &mut tx, &accountid, &owner, &name, &level, &now,
+                    )
+                    .await
+                }
+                Err(_) => {
+                    access::forget(&mut tx, &accountid, &owner, &name, &now)
+                        .await
+                }
+            };
+
+            tx.commit().await.unwrap_or_else(|err| {
+                panic!(
+                    "Unable to commit permission on '{}/{}': {}",
+                    owner, name, err
+                );
+            });
+
+            // one account able to see the repository is enough for it not
+            // to be considered failing.
+            if res.is_err() {
+                res = level.map(|_| ());
+            }
+        }
+
+        res
+    }
+
+    /// Obtain each account's permission level on every maintained
+    /// repository it's known for.
+    ///
+    pub async fn get_repo_permissions(
+        self: &Self,
+        db: &DB,
+    ) -> Vec<RepoPermission> {
+        access::get_permissions(&db).await
+    }
+
+    /// Refreshes the merge policy of repository `owner/name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_merge_policy(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let creds = accounts::get_main_credentials(&db).await?;
+        let now = chrono::Utc::now();
+        let res = policy::fetch(&creds, &owner, &name).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update merge policy: {}",
+                    err
+                );
+            }
+        };
+
+        match &res {
+            Ok(p) => policy::consume(&mut tx, &p, &now).await,
+            Err(_) => {
+                let key = policy::refresh_key(&owner, &name);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit merge policy of '{}/{}': {}",
+                owner, name, err
+            );
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Refreshes the labels defined on repository `owner/name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_labels(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let creds = accounts::get_main_credentials(&db).await?;
+        let now = chrono::Utc::now();
+        let res = labels::fetch(&creds, &owner, &name).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to update labels: {}", err);
+            }
+        };
+
+        match &res {
+            Ok(l) => labels::consume(&mut tx, &owner, &name, &l, &now).await,
+            Err(_) => {
+                let key = labels::refresh_key(&owner, &name);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit labels of '{}/{}': {}", owner, name, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Obtain the labels known for repository `owner/name` if provided, or
+    /// for every repository otherwise.
+    ///
+    pub async fn get_repo_labels(
+        self: &Self,
+        db: &DB,
+        owner: &Option<String>,
+        name: &Option<String>,
+    ) -> Vec<RepoLabel> {
+        labels::get_labels(&db, &owner, &name).await
+    }
+
+    /// Obtain the names of the known labels starting with `prefix`, on
+    /// repository `owner/name` if provided.
+    ///
+    pub async fn complete_label(
+        self: &Self,
+        db: &DB,
+        prefix: &String,
+        owner: &Option<String>,
+        name: &Option<String>,
+    ) -> Vec<String> {
+        labels::complete(&db, &prefix, &owner, &name).await
+    }
+
+    /// Obtain the methods Pull Request `prid` may be merged with, refreshing
+    /// its repository's merge policy first if due, and taking the rules of
+    /// its base branch into account. Should the policy be unknown, every
+    /// method is offered.
+    ///
+    pub async fn get_merge_methods(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<Vec<String>, GHDError> {
+        let pr = prs::get_pr(&db, &prid).await?;
+        if policy::should_refresh(&db, &pr.repo_owner, &pr.repo_name).await {
+            if let Err(err) = self
+                .refresh_merge_policy(&db, &pr.repo_owner, &pr.repo_name)
+                .await
+            {
+                println!(
+                    "error refreshing merge policy of '{}/{}': {:?}",
+                    pr.repo_owner, pr.repo_name, err
+                );
+            }
+        }
+
+        let creds = accounts::get_credentials_for_issue(&db, &prid).await?;
+        Ok(policy::get_pr_methods(
+            &db,
+            &creds,
+            &pr.repo_owner,
+            &pr.repo_name,
+            &pr.number,
+        )
+        .await)
+    }
+
+    /// Render the Markdown summary of the past week.
+    ///
+    pub async fn get_weekly_report(self: &Self, db: &DB) -> String {
+        report::render_weekly(&db).await
+    }
+
+    /// Obtain the merge policy of repository `owner/name`, if known.
+    ///
+    pub async fn get_merge_policy(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Option<MergePolicy> {
+        policy::get_policy(&db, &owner, &name).await
+    }
+
+    /// Refreshes the releases published by repository `owner/name`, using
+    /// the main account's credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_releases(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let mut journal =
+            journal::Sync::start(format!("releases:{}/{}", owner, name));
+        let creds = accounts::get_main_credentials(&db).await?;
+        let now = chrono::Utc::now();
+        let res = releases::fetch_releases(&creds, &owner, &name).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update releases: {}",
+                    err
+                );
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        match &res {
+            Ok(lst) => {
+                releases::consume_releases(&mut tx, &owner, &name, &lst, &now)
+                    .await
+            }
+            Err(_) => {
+                let key = releases::refresh_key(&owner, &name);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        journal.count_changes(&mut tx, "releases").await;
+        journal
+            .finish(&mut tx, res.as_ref().err().map(|e| format!("{:?}", e)))
+            .await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit releases for '{}/{}': {}",
+                owner, name, err
+            );
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Refreshes the open dependency update Pull Requests of repository
+    /// `owner/name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_dependency_updates(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let mut journal =
+            journal::Sync::start(depupdates::refresh_key(&owner, &name));
+        let creds = accounts::get_main_credentials(&db).await?;
+        let now = chrono::Utc::now();
+        let res = depupdates::fetch(&creds, &owner, &name).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update dependency prs: {}",
+                    err
+                );
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        match &res {
+            Ok(pulls) => {
+                depupdates::consume(&mut tx, &owner, &name, &pulls, &now).await;
+                journal.count_changes(&mut tx, "prs").await;
+            }
+            Err(_) => {
+                let key = depupdates::refresh_key(&owner, &name);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        journal
+            .finish(&mut tx, res.as_ref().err().map(|e| format!("{:?}", e)))
+            .await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit dependency prs for '{}/{}': {}",
+                owner, name, err
+            );
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Obtain the open dependency update Pull Requests across maintained
+    /// repositories, grouped by dependency and version.
+    ///
+    pub async fn get_dependency_updates(
+        self: &Self,
+        db: &DB,
+    ) -> Vec<DependencyUpdateGroup> {
+        depupdates::get_groups(&db).await
+    }
+
+    /// Approve, through the outbox, every open dependency update Pull
+    /// Request bumping a dependency by at most `bump`; e.g., `patch` only
+    /// approves patch bumps, `minor` patch and minor bumps. Updates whose
+    /// bump is unknown are never approved this way, nor are those already
+    /// approved. Failing to approve one doesn't stop the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `bump` - The largest bump to approve: `patch`, `minor` or `major`.
+    ///
+    pub async fn approve_dependency_updates(
+        self: &Self,
+        db: &DB,
+        bump: &String,
+    ) -> Result<DependencyApproval, GHDError> {
+        let max = depupdates::bump_rank(&bump);
+        if max > depupdates::bump_rank(depupdates::BUMP_MAJOR) {
+            return Err(GHDError::BadRequest);
+        }
+
+        let mut res = DependencyApproval {
+            approved: vec![],
+            failed: vec![],
+        };
+        for update in depupdates::get_updates(&db).await {
+            if depupdates::bump_rank(&update.bump) > max
+                || update.review_decision == "approved"
+            {
+                continue;
+            }
+            let action = outbox::PullRequestAction::Review {
+                event: String::from("APPROVE"),
+                body: None,
+                comments: vec![],
+            };
+            match self.run_pull_action(&db, &update.pr_id, &action).await {
+                Ok(()) => res.approved.push(update.pr_id),
+                Err(err) => {
+                    println!(
+                        "error approving {}/{}#{}: {:?}",
+                        update.repo_owner, update.repo_name, update.number, err
+                    );
+                    res.failed.push(update.pr_id);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Refreshes the Pull Request template of repository `owner/name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn refresh_pr_template(
+        self: &Self,
+        db: &DB,
+        owner: &String,
+        name: &String,
+    ) -> Result<(), GHDError> {
+        let mut journal =
+            journal::Sync::start(format!("templates:{}/{}", owner, name));
+        let creds = accounts::get_main_credentials(&db).await?;
+        let now = chrono::Utc::now();
+        let res = templates::fetch_template(&creds, &owner, &name).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update template: {}",
+                    err
+                );
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        match &res {
+            Ok(template) => {
+                templates::consume_template(
+                    &mut tx, &owner, &name, &template, &now,
+                )
+                .await
+            }
+            Err(_) => {
+                let key = templates::refresh_key(&owner, &name);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        journal.count_changes(&mut tx, "pr_templates").await;
+        journal
+            .finish(&mut tx, res.as_ref().err().map(|e| format!("{:?}", e)))
+            .await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit template for '{}/{}': {}",
+                owner, name, err
+            );
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Download the patches of small open Pull Requests that are missing or
+    /// outdated, a batch of up to the configured concurrency at once, and
+    /// evict the oldest patches beyond the cache's size. Returns how many
+    /// patches were downloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The GHD Database handle.
+    /// * `dir` - The directory patches are stored in.
+    ///
+    pub async fn refresh_patches(
+        self: &Self,
+        db: &DB,
+        dir: &PathBuf,
+    ) -> Result<usize, GHDError> {
+        let settings = patches::get_settings(&db).await;
+        let candidates = patches::get_candidates(&db, &settings).await;
+        if candidates.is_empty() {
+            return Ok(0);
+        }
+        let creds = accounts::get_main_credentials(&db).await?;
+
+        let mut set = tokio::task::JoinSet::new();
+        for candidate in candidates {
+            let creds = accounts::AccountCredentials {
+                account_id: creds.account_id,
+                token: creds.token.clone(),
+                host: creds.host.clone(),
+            };
+            let format = settings.format.clone();
+            let max_bytes = settings.max_bytes;
+            set.spawn(async move {
+                let res = patches::fetch_patch(
+                    &creds, &candidate, &format, max_bytes,
+                )
+                .await;
+                (candidate, res)
+            });
+        }
+
+        let now = chrono::Utc::now();
+        let mut fetched = 0;
+        let mut last_err = None;
+        while let Some(joined) = set.join_next().await {
+            let (candidate, res) = match joined {
+                Ok(res) => res,
+                Err(err) => {
+                    println!("patch download task failed: {}", err);
+                    continue;
+                }
+            };
+            let res = match res {
+                Ok(patch) => {
+                    patches::store(
+                        &db,
+                        &dir,
+                        &candidate.pr_id,
+                        &settings.format,
+                        &patch,
+                        &now,
+                    )
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(()) => fetched += 1,
+                Err(err) => {
+                    println!(
+                        "error downloading patch for {}/{}#{}: {:?}",
+                        candidate.repo_owner,
+                        candidate.repo_name,
+                        candidate.number,
+                        err
+                    );
+                    last_err = Some(err);
+                }
+            };
+        }
+
+        patches::evict(&db, &dir, settings.cache_bytes).await;
+
+        match (fetched, last_err) {
+            (0, Some(err)) => Err(err),
+            _ => Ok(fetched),
+        }
+    }
+
+    /// Follow the public user or repository `name` without a token, in
+    /// anonymous mode.
+    ///
+    pub async fn track_anon(
+        self: &Self,
+        db: &DB,
+        kind: &String,
+        name: &String,
+    ) -> Result<AnonTarget, GHDError> {
+        anon::track(&db, &kind, &name).await
+    }
+
+    /// Stop following target `targetid` in anonymous mode.
+    ///
+    pub async fn untrack_anon(self: &Self, db: &DB, targetid: &i64) {
+        anon::untrack(&db, &targetid).await
+    }
+
+    /// Obtain the users and repositories followed in anonymous mode.
+    ///
+    pub async fn get_anon_targets(self: &Self, db: &DB) -> Vec<AnonTarget> {
+        anon::get_targets(&db).await
+    }
+
+    /// Obtain the Pull Requests obtained for followed target `targetid`.
+    ///
+    pub async fn get_anon_pulls(
+        self: &Self,
+        db: &DB,
+        targetid: &i64,
+    ) -> Result<Vec<AnonPullRequest>, GHDError> {
+        anon::get_pulls(&db, &targetid).await
+    }
+
+    /// Refresh the Pull Requests of a followed target, without a token.
+    ///
+    pub async fn refresh_anon_target(
+        self: &Self,
+        db: &DB,
+        target: &AnonTarget,
+    ) -> Result<(), GHDError> {
+        let mut journal = journal::Sync::start(anon::refresh_key(&target.id));
+        let now = chrono::Utc::now();
+        let res = anon::fetch(&target).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to update target {}: {}",
+                    target.id, err
+                );
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        match &res {
+            Ok(prs) => {
+                anon::consume(&mut tx, &target.id, &prs, &now).await;
+                journal.count_changes(&mut tx, "prs").await;
+            }
+            Err(_) => {
+                let key = anon::refresh_key(&target.id);
+                refresh::update_sync_refresh(&mut tx, &key, &now).await;
+            }
+        };
+
+        journal
+            .finish(&mut tx, res.as_ref().err().map(|e| format!("{:?}", e)))
+            .await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!("Unable to commit target {}: {}", target.id, err);
+        });
+
+        res.map(|_| ())
+    }
+
+    /// Search issues and Pull Requests locally, falling back to fuzzy
+    /// matches if there aren't enough exact ones.
+    ///
+    pub async fn search(
+        self: &Self,
+        db: &DB,
+        query: &String,
+        page: &PageRequest,
+    ) -> Result<Page<SearchResult>, GHDError> {
+        search::search(&db, &query, &page).await
+    }
+
+    /// Obtain the cached patch of Pull Request `prid`.
+    ///
+    pub async fn get_pull_patch(
+        self: &Self,
+        db: &DB,
+        dir: &PathBuf,
+        prid: &i64,
+    ) -> Result<PullRequestPatch, GHDError> {
+        patches::get_patch(&db, &dir, &prid).await
+    }
+
+    /// Obtain the parsed diff of `path` in Pull Request `prid`, from its
+    /// cached patch.
+    ///
+    pub async fn get_pull_file_diff(
+        self: &Self,
+        db: &DB,
+        dir: &PathBuf,
+        prid: &i64,
+        path: &String,
+    ) -> Result<FileDiff, GHDError> {
+        diffs::get_file_diff(&db, &dir, &prid, &path).await
+    }
+
+    /// Obtain a page of the activity feed, optionally only with events that
+    /// happened after `since`.
+    ///
+    pub async fn get_activity_feed(
+        self: &Self,
+        db: &DB,
+        since: &Option<i64>,
+        page: &PageRequest,
+    ) -> Result<Page<ActivityEvent>, GHDError> {
+        activity::get_feed(&db, &since, &page).await
+    }
+
+    /// Obtain a page of the Pull Requests matching `filter`.
+    ///
+    pub async fn get_filtered_pulls(
+        self: &Self,
+        db: &DB,
+        filter: &filters::IssueFilter,
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        prs::get_filtered_prs(&db, &filter, &page).await
+    }
+
+    /// Obtain a page of the Pull Requests matching `filter`, largest first.
+    ///
+    pub async fn get_filtered_pulls_by_size(
+        self: &Self,
+        db: &DB,
+        filter: &filters::IssueFilter,
+        page: &PageRequest,
+    ) -> Result<Page<PullRequestTableEntry>, GHDError> {
+        prs::get_filtered_prs_by_size(&db, &filter, &page).await
+    }
+
+    /// Obtain the Pull Request size class thresholds.
+    ///
+    pub async fn get_size_thresholds(self: &Self, db: &DB) -> [i64; 4] {
+        sizes::get_thresholds(&db).await
+    }
+
+    /// Set the Pull Request size class thresholds, reclassifying Pull
+    /// Requests accordingly.
+    ///
+    pub async fn set_size_thresholds(
+        self: &Self,
+        db: &DB,
+        thresholds: &Vec<i64>,
+    ) -> Result<(), GHDError> {
+        sizes::set_thresholds(&db, &thresholds).await
+    }
+
+    /// Obtain how long Pull Requests of each size class took to be merged.
+    ///
+    pub async fn get_size_stats(
+        self: &Self,
+        db: &DB,
+        since: &Option<i64>,
+    ) -> Vec<SizeStats> {
+        sizes::get_stats(&db, &since).await
+    }
+
+    /// Marks every issue and Pull Request matching `filter` as having been
+    /// viewed, returning how many were marked.
+    ///
+    pub async fn mark_all_viewed(
+        self: &Self,
+        db: &DB,
+        filter: &filters::IssueFilter,
+    ) -> Result<u64, GHDError> {
+        filters::mark_all_viewed(&db, &filter).await
+    }
+
+    /// Dismisses every issue and Pull Request matching `filter` from the
+    /// triage inbox, returning how many were dismissed.
+    ///
+    pub async fn dismiss_all(
+        self: &Self,
+        db: &DB,
+        filter: &filters::IssueFilter,
+    ) -> Result<u64, GHDError> {
+        filters::dismiss_all(&db, &filter).await
+    }
+
+    /// Start being away, pausing non-essential syncs and notifications.
+    ///
+    pub async fn away_start(self: &Self, db: &DB) -> Result<i64, GHDError> {
+        Ok(away::start(&db).await)
+    }
+
+    /// Stop being away, returning the away period that just ended.
+    ///
+    pub async fn away_end(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Option<AwayPeriod>, GHDError> {
+        Ok(away::end(&db).await)
+    }
+
+    /// Obtain the current away period, if we're away.
+    ///
+    pub async fn get_away_status(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Option<AwayPeriod>, GHDError> {
+        Ok(away::get_current(&db).await)
+    }
+
+    /// Obtain the catch-up summary for an away period, defaulting to the most
+    /// recent one.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `periodid` - The away period's ID, if not the most recent one.
+    ///
+    pub async fn get_away_catch_up(
+        self: &Self,
+        db: &DB,
+        periodid: &Option<i64>,
+    ) -> Result<CatchUpSummary, GHDError> {
+        away::get_catch_up(&db, &periodid).await
+    }
+
+    /// Obtain API usage since `since`, aggregated per account and feature.
+    ///
+    pub async fn get_api_usage(
+        self: &Self,
+        db: &DB,
+        since: &i64,
+    ) -> Result<Vec<ApiUsageEntry>, GHDError> {
+        ledger::flush(&db).await;
+        ledger::get_usage(&db, &since).await
+    }
+
+    /// Obtain the state of each account's rate limits, per resource.
+    ///
+    pub async fn get_rate_limits(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<RateLimitStatus>, GHDError> {
+        ledger::flush(&db).await;
+        ledger::get_rate_limits(&db).await
+    }
+
+    /// Obtain the API calls made per account and hour since `since`.
+    ///
+    pub async fn get_hourly_usage(
+        self: &Self,
+        db: &DB,
+        since: &i64,
+    ) -> Vec<HourlyUsage> {
+        ledger::flush(&db).await;
+        ledger::get_hourly_usage(&db, &since).await
+    }
+
+    /// Obtain the API usage anomalies detected since `since`.
+    ///
+    pub async fn get_usage_anomalies(
+        self: &Self,
+        db: &DB,
+        since: &i64,
+    ) -> Vec<UsageAnomaly> {
+        anomalies::get_anomalies(&db, &since).await
+    }
+
+    /// Runs a write action on Pull Request `prid`, journaling it in the outbox
+    /// first so that, should we go down before it completes, it can be
+    /// reviewed and retried instead of being silently lost or run twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `prid` - The Pull Request's database ID.
+    /// * `action` - The action to run.
+    ///
+    pub async fn run_pull_action(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+        action: &outbox::PullRequestAction,
+    ) -> Result<(), GHDError> {
+        let pr = prs::get_pr(&db, &prid).await?;
+        let creds = accounts::get_credentials_for_issue(&db, &prid).await?;
+        let (owner, name) = (&pr.repo_owner, &pr.repo_name);
+        access::check(&db, &creds.account_id, &owner, &name, &action).await?;
+        policy::check(&db, &creds, &owner, &name, &pr.number, &action).await?;
+
+        let id = outbox::record(&db, &prid, &action).await?;
+        self.run_outbox_entry(&db, &id, &prid, &action).await
+    }
+
+    /// Compose a reply to Pull Request `prid` out of reply template
+    /// `templateid`, if any, and `body`.
+    ///
+    pub async fn compose_reply(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+        templateid: &Option<i64>,
+        body: &Option<String>,
+    ) -> Result<Option<String>, GHDError> {
+        let pr = prs::get_pr(&db, &prid).await?;
+        replies::compose(&db, &pr, &templateid, &body).await
+    }
+
+    /// Add a reply template, returning its ID.
+    ///
+    pub async fn add_reply_template(
+        self: &Self,
+        db: &DB,
+        template: &ReplyTemplate,
+    ) -> Result<i64, GHDError> {
+        replies::add_template(&db, &template).await
+    }
+
+    /// Update a reply template's name and body.
+    ///
+    pub async fn update_reply_template(
+        self: &Self,
+        db: &DB,
+        template: &ReplyTemplate,
+    ) -> Result<(), GHDError> {
+        replies::update_template(&db, &template).await
+    }
+
+    /// Remove reply template `templateid`.
+    ///
+    pub async fn remove_reply_template(self: &Self, db: &DB, templateid: &i64) {
+        replies::remove_template(&db, &templateid).await
+    }
+
+    /// Obtain all reply templates.
+    ///
+    pub async fn get_reply_templates(
+        self: &Self,
+        db: &DB,
+    ) -> Vec<ReplyTemplate> {
+        replies::get_templates(&db).await
+    }
+
+    /// Start reviewing Pull Request `prid`, stopping any other review.
+    ///
+    pub async fn start_review_session(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<ReviewSession, GHDError> {
+        reviewtime::start(&db, &prid).await
+    }
+
+    /// Stop reviewing Pull Request `prid`.
+    ///
+    pub async fn stop_review_session(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<ReviewSession, GHDError> {
+        reviewtime::stop(&db, &prid).await
+    }
+
+    /// Obtain the review sessions on Pull Request `prid`.
+    ///
+    pub async fn get_review_sessions(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Vec<ReviewSession> {
+        reviewtime::get_sessions(&db, &prid).await
+    }
+
+    /// Obtain the time spent reviewing each Pull Request per week, for the
+    /// time between `from` and `to`.
+    ///
+    pub async fn get_weekly_review_time(
+        self: &Self,
+        db: &DB,
+        from: &i64,
+        to: &i64,
+    ) -> Vec<ReviewTime> {
+        reviewtime::get_weekly(&db, &from, &to).await
+    }
+
+    /// Set the summary of Pull Request `prid`'s draft review.
+    ///
+    pub async fn set_draft_review_body(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+        body: &String,
+    ) -> Result<(), GHDError> {
+        drafts::set_body(&db, &prid, &body).await
+    }
+
+    /// Add an inline comment to Pull Request `prid`'s draft review,
+    /// returning its ID.
+    ///
+    pub async fn add_draft_review_comment(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+        path: &String,
+        line: &i64,
+        side: &String,
+        body: &String,
+    ) -> Result<i64, GHDError> {
+        drafts::add_comment(&db, &prid, &path, &line, &side, &body).await
+    }
+
+    /// Replace the body of draft review comment `id`.
+    ///
+    pub async fn update_draft_review_comment(
+        self: &Self,
+        db: &DB,
+        id: &i64,
+        body: &String,
+    ) -> Result<(), GHDError> {
+        drafts::update_comment(&db, &id, &body).await
+    }
+
+    /// Remove draft review comment `id`.
+    ///
+    pub async fn remove_draft_review_comment(
+        self: &Self,
+        db: &DB,
+        id: &i64,
+    ) -> Result<(), GHDError> {
+        drafts::remove_comment(&db, &id).await
+    }
+
+    /// Obtain Pull Request `prid`'s draft review.
+    ///
+    pub async fn get_draft_review(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<DraftReview, GHDError> {
+        drafts::get_draft(&db, &prid).await
+    }
+
+    /// Obtain the IDs of the Pull Requests with a draft review.
+    ///
+    pub async fn get_drafted_pulls(self: &Self, db: &DB) -> Vec<i64> {
+        drafts::get_drafted(&db).await
+    }
+
+    /// Drop Pull Request `prid`'s draft review.
+    ///
+    pub async fn discard_draft_review(self: &Self, db: &DB, prid: &i64) {
+        drafts::discard(&db, &prid).await
+    }
+
+    /// Submit Pull Request `prid`'s draft review as a single review with
+    /// `event`, one of `APPROVE`, `REQUEST_CHANGES`, or `COMMENT`, through
+    /// the outbox. The draft is only dropped once submitted, so it can be
+    /// submitted again if this fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `prid` - The Pull Request's ID.
+    /// * `event` - The review's event.
+    ///
+    pub async fn submit_draft_review(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+        event: &String,
+    ) -> Result<(), GHDError> {
+        let draft = drafts::get_draft(&db, &prid).await?;
+        let action = drafts::to_action(&draft, &event);
+        self.run_pull_action(&db, &prid, &action).await?;
+        drafts::discard(&db, &prid).await;
+        Ok(())
+    }
+
+    /// Obtain the cached reactions on `subject` of issue `issueid`.
+    ///
+    pub async fn get_reactions(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        subject: &reactions::ReactionSubject,
+    ) -> Vec<ReactionCount> {
+        reactions::get_reactions(&db, &issueid, &subject).await
+    }
+
+    /// Obtain the reactions on `subject` of issue `issueid` from the API,
+    /// replacing the cached ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `issueid` - The issue's ID.
+    /// * `subject` - What the reactions are left on.
+    ///
+    pub async fn refresh_reactions(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        subject: &reactions::ReactionSubject,
+    ) -> Result<Vec<ReactionCount>, GHDError> {
+        let target = reactions::get_target(&db, &issueid).await?;
+        let creds = accounts::get_credentials_for_issue(&db, &issueid).await?;
+        let counts = reactions::fetch(&creds, &target, &subject).await?;
+        reactions::consume(&db, &issueid, &subject, &counts).await;
+
+        Ok(reactions::get_reactions(&db, &issueid, &subject).await)
+    }
+
+    /// Leave a `content` reaction on `subject` of issue `issueid`, returning
+    /// the updated reactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `issueid` - The issue's ID.
+    /// * `subject` - What to react on.
+    /// * `content` - The reaction; e.g., `+1`.
+    ///
+    pub async fn add_reaction(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        subject: &reactions::ReactionSubject,
+        content: &String,
+    ) -> Result<Vec<ReactionCount>, GHDError> {
+        let target = reactions::get_target(&db, &issueid).await?;
+        let creds = accounts::get_credentials_for_issue(&db, &issueid).await?;
+        let (id, created) =
+            reactions::add(&creds, &target, &subject, &content).await?;
+        reactions::apply(&db, &issueid, &subject, &content, &Some(id), created)
+            .await;
+
+        Ok(reactions::get_reactions(&db, &issueid, &subject).await)
+    }
+
+    /// Remove our `content` reaction from `subject` of issue `issueid`,
+    /// returning the updated reactions. If we don't know our reaction's ID,
+    /// the reactions are refreshed first to find it.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `issueid` - The issue's ID.
+    /// * `subject` - What the reaction was left on.
+    /// * `content` - The reaction; e.g., `+1`.
+    ///
+    pub async fn remove_reaction(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        subject: &reactions::ReactionSubject,
+        content: &String,
+    ) -> Result<Vec<ReactionCount>, GHDError> {
+        let target = reactions::get_target(&db, &issueid).await?;
+        let creds = accounts::get_credentials_for_issue(&db, &issueid).await?;
+
+        let mut mine =
+            reactions::get_mine(&db, &issueid, &subject, &content).await;
+        if mine.is_none() {
+            let counts = reactions::fetch(&creds, &target, &subject).await?;
+            reactions::consume(&db, &issueid, &subject, &counts).await;
+            mine = reactions::get_mine(&db, &issueid, &subject, &content).await;
+        }
+
+        if let Some(id) = mine {
+            reactions::remove(&creds, &target, &subject, &id).await?;
+            reactions::apply(&db, &issueid, &subject, &content, &None, true)
+                .await;
+        }
+
+        Ok(reactions::get_reactions(&db, &issueid, &subject).await)
+    }
+
+    /// Runs interrupted outbox entry `id` again.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `id` - The outbox entry's ID.
+    ///
+    pub async fn retry_pull_action(
+        self: &Self,
+        db: &DB,
+        id: &i64,
+    ) -> Result<(), GHDError> {
+        let entry = outbox::get_entry(&db, &id).await?;
+        if entry.state != outbox::STATE_PENDING {
+            return Err(GHDError::BadRequest);
+        }
+        if entry.payload == vault::UNREADABLE {
+            return Err(GHDError::KeyUnavailableError);
+        }
+        let action: outbox::PullRequestAction =
+            match serde_json::from_str(&entry.payload) {
+                Ok(res) => res,
+                Err(err) => {
+                    log::error!("unable to parse outbox entry {}: {}", id, err);
+                    return Err(GHDError::BadRequest);
+                }
+            };
+        self.run_outbox_entry(&db, &id, &entry.pr_id, &action).await
+    }
+
+    async fn run_outbox_entry(
+        self: &Self,
+        db: &DB,
+        id: &i64,
+        prid: &i64,
+        action: &outbox::PullRequestAction,
+    ) -> Result<(), GHDError> {
+        // the entry is marked failed whatever goes wrong, so that it doesn't
+        // linger as pending.
+        let res = async {
+            let pr = prs::get_pr(&db, &prid).await?;
+            let creds = accounts::get_credentials_for_issue(&db, &prid).await?;
+            Ok(outbox::execute(&creds, &pr, &action).await)
+        }
+        .await;
+
+        let (err, reason) = match res {
+            Ok(Ok(())) => {
+                outbox::complete(&db, &id, outbox::STATE_DONE, &None).await;
+                reconcile::apply(&db, &prid, &action).await;
+                return Ok(());
+            }
+            Ok(Err(status)) => {
+                (rest::status_to_error(status), status.to_string())
+            }
+            Err(err) => {
+                let reason = format!("{:?}", err);
+                (err, reason)
+            }
+        };
+        outbox::complete(&db, &id, outbox::STATE_FAILED, &Some(reason)).await;
+        Err(err)
+    }
+
+    /// Verifies our copy of Pull Request `prid` against GitHub after a write
+    /// action, correcting it if GitHub disagrees. Returns what was corrected,
+    /// if anything. The verification is dropped on error, leaving it to the
+    /// next refresh.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `prid` - The Pull Request's database ID.
+    ///
+    pub async fn verify_pull(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<Option<IssueChange>, GHDError> {
+        let res = async {
+            let pr = prs::get_pr(&db, &prid).await?;
+            let creds = accounts::get_credentials_for_issue(&db, &prid).await?;
+            gql::get_pull_request_status(
+                &creds.token,
+                &creds.host,
+                &pr.repo_owner,
+                &pr.repo_name,
+                &pr.number,
+            )
+            .await
+        }
+        .await;
+
+        match res {
+            Ok(remote) => reconcile::reconcile(&db, &prid, &remote).await,
+            Err(err) => {
+                reconcile::unschedule(&db, &prid).await;
+                Err(err)
+            }
+        }
+    }
+
+    /// Fully reconciles the user `login` against GitHub, marking the open
+    /// issues and Pull Requests we hold for them that no longer exist
+    /// upstream as deleted. Returns what changed. Nothing is marked if the
+    /// user has more open issues than a search returns, since we can't tell
+    /// which are missing. The user is marked reconciled regardless, so as
+    /// not to retry on every iteration.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `login` - String containing the login of the user to reconcile.
+    ///
+    pub async fn reconcile_user(
+        self: &Self,
+        db: &DB,
+        login: &String,
+    ) -> Result<IssuesDiff, GHDError> {
+        let mut journal = journal::Sync::start(format!("reconcile:{}", login));
+        let user = users::get_user_by_login(&db, &login).await?;
+
+        let mut diff = IssuesDiff::default();
+        let res = async {
+            let creds =
+                accounts::get_credentials_for_user(&db, &user.id).await?;
+            let res =
+                gql::get_user_open_issues(&creds.token, &creds.host, &login)
+                    .await?;
+            if gql::is_capped(&res) {
+                println!("too many open issues to reconcile '{}'", login);
+                return Ok(());
+            }
+
+            let listed: Vec<i64> = res
+                .issues
+                .iter()
+                .map(|issue| issue.id)
+                .chain(res.prs.iter().map(|pr| pr.issue.id))
+                .collect();
+
+            for issue in &reconcile::get_missing(&db, &user.id, &listed).await {
+                // closed ones are picked up by the next refresh, and those
+                // we can't tell about are left alone.
+                match reconcile::exists_upstream(&creds, &issue).await {
+                    Ok(false) => diff
+                        .updated
+                        .push(reconcile::mark_deleted(&db, &issue.id).await),
+                    Ok(true) => {}
+                    Err(err) => println!(
+                        "unable to check {}/{}#{}: {:?}",
+                        issue.repo_owner, issue.repo_name, issue.number, err
+                    ),
+                };
+            }
+            Ok(())
+        }
+        .await;
+        reconcile::mark_reconciled(&db, &user.id).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!(
+                    "Error starting transaction to journal reconcile: {}",
+                    err
+                );
+            }
+        };
+        journal.count("issues", diff.updated.len());
+        let error = res.as_ref().err().map(|err| format!("{:?}", err));
+        journal.finish(&mut tx, error).await;
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit transaction to journal reconcile: {}",
+                err
+            );
+        });
+
+        res.map(|_| diff)
+    }
+
+    /// Pulls the next chunk of user `history.login`'s backfill, going back
+    /// from its cursor, and returns the backfill's progress. Chunks hitting
+    /// the search results cap are split, so as not to miss anything; those
+    /// still hitting it at their smallest are recorded as truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `history` - The user's history, with a backfill in progress.
+    ///
+    pub async fn backfill_user(
+        self: &Self,
+        db: &DB,
+        history: &UserHistory,
+    ) -> Result<UserHistory, GHDError> {
+        let (from, cursor) =
+            match (history.backfill_from, history.backfill_cursor) {
+                (Some(from), Some(cursor)) if cursor > from => (from, cursor),
+                _ => return Err(GHDError::BadRequest),
+            };
+        let mut journal =
+            journal::Sync::start(format!("backfill:{}", history.login));
+        let creds =
+            accounts::get_credentials_for_user(&db, &history.user_id).await?;
+        let to = common::ts_to_datetime(cursor).unwrap();
+
+        let mut secs = backfill::CHUNK_SECS;
+        let (chunk_from, res) = loop {
+            let chunk_from = std::cmp::max(from, cursor - secs);
+            let res = gql::get_user_history(
+                &creds.token,
+                &creds.host,
+                &history.login,
+                &common::ts_to_datetime(chunk_from).unwrap(),
+                &to,
+            )
+            .await?;
+            if !gql::is_capped(&res) || secs <= backfill::MIN_CHUNK_SECS {
+                break (chunk_from, res);
+            }
+            secs /= 2;
+        };
+
+        let conventions = backports::BackportConventions::load(&db).await;
+
+        let mut tx = match db.pool().begin().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Error starting transaction to backfill user: {}", err);
+            }
+        };
+        journal.begin(&mut tx).await;
+
+        if let Err(err) = prs::consume_issues(
+            &mut tx,
+            &history.user_id,
+            &res.issues,
+            &res.prs,
+        )
+        .await
+        {
+            panic!(
+                "Error consuming backfill for user '{}': {:?}",
+                history.login, err
+            );
+        };
+        backports::consume(&mut tx, &conventions, &res.prs).await;
+        templates::consume(&mut tx, &res.prs).await;
+        dependencies::consume(
+            &mut tx,
+            &res.issues
+                .iter()
+                .chain(res.prs.iter().map(|pr| &pr.issue))
+                .collect(),
+        )
+        .await;
+        backfill::advance(
+            &mut tx,
+            &history.user_id,
+            &chunk_from,
+            (res.issues.len() + res.prs.len()) as i64,
+            gql::is_capped(&res),
+        )
+        .await;
+
+        journal.count_changes(&mut tx, "issues").await;
+        journal.finish(&mut tx, None).await;
+
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit backfill for user '{}': {}",
+                history.login, err
+            );
+        });
+
+        match backfill::get_history(&db)
+            .await
+            .into_iter()
+            .find(|h| h.user_id == history.user_id)
+        {
+            Some(res) => Ok(res),
+            None => Err(GHDError::UserNotFoundError),
+        }
+    }
+
+    /// Requests user `login`'s history between `from` and `to` be
+    /// backfilled, replacing any backfill in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `login` - The user's login.
+    /// * `from` - Timestamp the range starts at.
+    /// * `to` - Timestamp the range ends at.
+    ///
+    pub async fn request_backfill(
+        self: &Self,
+        db: &DB,
+        login: &String,
+        from: &i64,
+        to: &i64,
+    ) -> Result<(), GHDError> {
+        let user = users::get_user_by_login(&db, &login).await?;
+        backfill::request(&db, &user.id, &from, &to).await
+    }
+
+    /// Obtain how far back each tracked user's history has been synced.
+    ///
+    pub async fn get_user_history(self: &Self, db: &DB) -> Vec<UserHistory> {
+        backfill::get_history(&db).await
+    }
+
+    /// Obtain the review requests pending on our users for longer than
+    /// their repository's reminder threshold.
+    ///
+    pub async fn get_reminders(self: &Self, db: &DB) -> Vec<Reminder> {
+        reminders::get_reminders(&db).await
+    }
+
+    /// Snooze reminders about Pull Request `prid` for `hours`, or lift the
+    /// snooze if `hours` is `0`.
+    ///
+    pub async fn snooze_reminders(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+        hours: i64,
+    ) {
+        reminders::snooze(&db, &prid, hours).await
+    }
+
+    /// Discards interrupted outbox entry `id`, e.g. because it did reach
+    /// GitHub before we went down.
+    ///
+    pub async fn discard_pull_action(
+        self: &Self,
+        db: &DB,
+        id: &i64,
+    ) -> Result<(), GHDError> {
+        outbox::discard(&db, &id).await
+    }
+
+    /// Obtain the outbox entries interrupted before completing.
+    ///
+    pub async fn get_interrupted_pull_actions(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<OutboxEntry>, GHDError> {
+        Ok(outbox::get_interrupted(&db).await)
+    }
+
+    /// Obtain the lint warnings for our open Pull Requests.
+    ///
+    pub async fn get_lint_warnings(
+        self: &Self,
+        db: &DB,
+    ) -> Result<Vec<LintWarning>, GHDError> {
+        lint::get_warnings(&db).await
+    }
+
+    /// Sets the local priority of issue `issueid`, one of
+    /// `annotations::PRIORITIES`, or clears it if `None`.
+    ///
+    pub async fn set_issue_priority(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        priority: &Option<String>,
+    ) -> Result<(), GHDError> {
+        annotations::set_priority(&db, &issueid, &priority).await
+    }
+
+    /// Sets the local note of issue `issueid`, or clears it if `None`.
+    ///
+    pub async fn set_issue_note(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        note: &Option<String>,
+    ) -> Result<(), GHDError> {
+        annotations::set_note(&db, &issueid, &note).await
+    }
+
+    /// Tags issue `issueid` with local tag `tag`.
+    ///
+    pub async fn add_issue_tag(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        tag: &String,
+    ) -> Result<(), GHDError> {
+        tags::add_tag(&db, &issueid, &tag).await
+    }
+
+    /// Removes local tag `tag` from issue `issueid`.
+    ///
+    pub async fn remove_issue_tag(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+        tag: &String,
+    ) {
+        tags::remove_tag(&db, &issueid, &tag).await
+    }
+
+    /// Obtain the local tags of issue `issueid`.
+    ///
+    pub async fn get_issue_tags(
+        self: &Self,
+        db: &DB,
+        issueid: &i64,
+    ) -> Vec<String> {
+        tags::get_issue_tags(&db, &issueid).await
+    }
+
+    /// Obtain every local tag in use.
+    ///
+    pub async fn get_tags(self: &Self, db: &DB) -> Vec<LocalTag> {
+        tags::get_tags(&db).await
+    }
+
+    /// Obtain the actions available in `context`.
+    ///
+    pub async fn list_actions(
+        self: &Self,
+        db: &DB,
+        context: &actions::ActionContext,
+    ) -> Result<Vec<Action>, GHDError> {
+        actions::list_actions(&db, &context).await
+    }
+
+    /// Marks a specified Pull Request as having been viewed, recording its
+    /// current head so we can later tell what changed since.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A GHD Database handle.
+    /// * `prid` - The Pull Request's database ID.
+    ///
+    pub async fn mark_pull_request_viewed(
+        self: &Self,
+        db: &DB,
+        prid: &i64,
+    ) -> Result<(), GHDError> {
+        prs::mark_viewed(&db, &prid).await?;
+        deltas::record_view(&db, &prid).await;
+        Ok(())
     }
 }
@@ -0,0 +1,314 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials, outbox::PullRequestAction, refresh, rest,
+    types::RepoPermission,
+};
+
+/// Permissions change rarely; refresh them every six hours.
+///
+pub const ACCESS_REFRESH_INTERVAL: i64 = 6 * 60 * 60;
+
+/// Permission levels on a repository, from least to most privileged.
+///
+pub const LEVELS: [&str; 5] = ["read", "triage", "write", "maintain", "admin"];
+
+/// Level required to comment on and review Pull Requests.
+///
+pub const REVIEW_LEVEL: &str = "read";
+
+/// Level required to merge Pull Requests.
+///
+pub const MERGE_LEVEL: &str = "write";
+
+/// Obtain the sync refresh key for our accounts' permissions on repository
+/// `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("access:{}/{}", owner, name)
+}
+
+/// Check whether our accounts' permissions on repository `owner/name` are
+/// due for a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        ACCESS_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Repository as described by the REST API, of which only the token owner's
+/// permissions are of interest.
+///
+#[derive(serde::Deserialize)]
+struct RepoInfo {
+    permissions: Option<RepoPermissions>,
+}
+
+#[derive(serde::Deserialize)]
+struct RepoPermissions {
+    admin: bool,
+    #[serde(default)]
+    maintain: bool,
+    push: bool,
+    #[serde(default)]
+    triage: bool,
+}
+
+/// Obtain the token owner's permission level on repository `owner/name`,
+/// one of `LEVELS`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<String, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("access");
+    let ep = format!("/repos/{}/{}", owner, name);
+    let info = match ghreq.send::<RepoInfo>(ghreq.get(&ep)).await {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    // permissions are only missing for anonymous requests, which can only
+    // read public repositories.
+    let level = match info.permissions {
+        Some(p) if p.admin => "admin",
+        Some(p) if p.maintain => "maintain",
+        Some(p) if p.push => "write",
+        Some(p) if p.triage => "triage",
+        _ => "read",
+    };
+    Ok(level.to_string())
+}
+
+/// Write account `accountid`'s permission level on repository `owner/name`
+/// to the database. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `accountid` - The database ID of the user owning the account.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `level` - The account's permission level, one of `LEVELS`.
+/// * `when` - When the permission level was obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    accountid: &i64,
+    owner: &String,
+    name: &String,
+    level: &String,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO repo_permissions (
+            account_id, repo_owner, repo_name, permission, fetched_at
+        ) VALUES (?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&accountid)
+    .bind(&owner)
+    .bind(&name)
+    .bind(&level)
+    .bind(&when.timestamp())
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error storing permission on '{}/{}': {}", owner, name, err);
+    });
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+/// Forget account `accountid`'s permission level on repository `owner/name`,
+/// e.g. when it could not be obtained, rather than keep acting on a stale
+/// one. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `accountid` - The database ID of the user owning the account.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `when` - When the permission level was attempted to be obtained.
+///
+pub async fn forget(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    accountid: &i64,
+    owner: &String,
+    name: &String,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "
+        DELETE FROM repo_permissions
+        WHERE account_id = ? AND repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&accountid)
+    .bind(&owner)
+    .bind(&name)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error removing permission on '{}/{}': {}", owner, name, err);
+    });
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+/// Obtain each account's permission level on every maintained repository
+/// it's known for.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_permissions(db: &DB) -> Vec<RepoPermission> {
+    match sqlx::query_as::<_, RepoPermission>(
+        "
+        SELECT repo_permissions.* FROM repo_permissions
+        INNER JOIN repos
+        ON repos.owner = repo_permissions.repo_owner
+            AND repos.name = repo_permissions.repo_name
+        ORDER BY repo_owner, repo_name, account_id
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain repo permissions: {}", err);
+        }
+    }
+}
+
+/// Obtain account `accountid`'s permission level on repository
+/// `owner/name`, if known.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `accountid` - The database ID of the user owning the account.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_permission(
+    db: &DB,
+    accountid: &i64,
+    owner: &String,
+    name: &String,
+) -> Option<String> {
+    match sqlx::query_scalar::<_, String>(
+        "
+        SELECT permission FROM repo_permissions
+        WHERE account_id = ? AND repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&accountid)
+    .bind(&owner)
+    .bind(&name)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Unable to obtain permission on '{}/{}': {}",
+                owner, name, err
+            );
+        }
+    }
+}
+
+/// Obtain the permission level `action` requires. Commenting and reviewing
+/// are open to anyone who can read the repository; merging requires being
+/// able to push to it.
+///
+pub fn required_level(action: &PullRequestAction) -> &'static str {
+    match action {
+        PullRequestAction::Merge { .. } => MERGE_LEVEL,
+        _ => REVIEW_LEVEL,
+    }
+}
+
+/// Check whether `level` grants at least `required`. Unknown levels grant
+/// nothing.
+///
+pub fn grants(level: &str, required: &str) -> bool {
+    let rank = |l: &str| LEVELS.iter().position(|v| *v == l);
+    match (rank(level), rank(required)) {
+        (Some(l), Some(r)) => l >= r,
+        _ => false,
+    }
+}
+
+/// Check whether a last known permission `level` allows what `required`
+/// does. Unknown permissions are given the benefit of the doubt, leaving it
+/// to the API to reject the action.
+///
+pub fn allows(level: &Option<String>, required: &str) -> bool {
+    match level {
+        Some(level) => grants(&level, &required),
+        None => true,
+    }
+}
+
+/// Check whether `action` may be run on repository `owner/name` by account
+/// `accountid`, going by its last known permission level on it.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `accountid` - The database ID of the user owning the account.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `action` - The action about to be run.
+///
+pub async fn check(
+    db: &DB,
+    accountid: &i64,
+    owner: &String,
+    name: &String,
+    action: &PullRequestAction,
+) -> Result<(), GHDError> {
+    let level = get_permission(&db, &accountid, &owner, &name).await;
+    match allows(&level, required_level(&action)) {
+        true => Ok(()),
+        false => Err(GHDError::PermissionDeniedError),
+    }
+}
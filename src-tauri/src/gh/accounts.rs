@@ -0,0 +1,442 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    db::{Snapshot, DB},
+    errors::GHDError,
+    page::{Page, PageRequest},
+};
+
+use super::{
+    bots, reconcile,
+    types::{Account, AccountCounts, AccountPullRequestEntry},
+};
+
+/// Host used for accounts that have not been explicitly associated with a
+/// GitHub Enterprise Server instance.
+///
+pub const DEFAULT_HOST: &str = "github.com";
+
+/// Credentials required to talk to the API on behalf of an account.
+///
+pub struct AccountCredentials {
+    /// The account's user ID; `0` for tokens not tied to a user.
+    pub account_id: i64,
+    pub token: String,
+    pub host: String,
+}
+
+/// Obtain the REST API base URL for the provided `host`.
+///
+pub fn rest_api_url(host: &str) -> String {
+    if host == DEFAULT_HOST {
+        String::from("https://api.github.com")
+    } else {
+        format!("https://{}/api/v3", host)
+    }
+}
+
+/// Obtain the GraphQL API endpoint for the provided `host`.
+///
+pub fn graphql_api_url(host: &str) -> String {
+    if host == DEFAULT_HOST {
+        String::from("https://api.github.com/graphql")
+    } else {
+        format!("https://{}/api/graphql", host)
+    }
+}
+
+/// Query listing, for each account, the users tracked through it. Accounts
+/// always track themselves, and users added before accounts were a thing are
+/// attributed to the main account.
+///
+const ACCOUNT_USERS_CTE: &str = "
+    accounts_all AS (
+        SELECT DISTINCT
+            tokens.user_id AS account_id,
+            users.login || '@' || COALESCE(accounts.host, 'github.com')
+                AS account
+        FROM
+            tokens
+            INNER JOIN users ON users.id = tokens.user_id
+            LEFT JOIN accounts ON accounts.user_id = tokens.user_id
+    ),
+    account_users AS (
+        SELECT account_id, user_id FROM user_accounts
+        UNION
+        SELECT user_id, user_id FROM tokens
+        UNION
+        SELECT (
+            SELECT user_id FROM tokens
+            WHERE id = (SELECT MAX(id) FROM tokens)
+        ), users.id
+        FROM users
+        WHERE users.id NOT IN (SELECT user_id FROM user_accounts)
+    )
+";
+
+/// Associate the user owning a token with the provided `host`. This function
+/// requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The database ID of the user owning the account.
+/// * `host` - The host the account belongs to; e.g., `github.com`.
+///
+pub async fn add_account(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    host: &String,
+) {
+    sqlx::query(
+        "INSERT OR REPLACE INTO accounts (user_id, host) VALUES (?, ?)",
+    )
+    .bind(&userid)
+    .bind(&host)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error inserting account into database: {}", err);
+    });
+
+    add_user_to_account(tx, userid, userid).await;
+}
+
+/// Track the user `userid` through the account owned by `accountid`. This
+/// function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The database ID of the user being tracked.
+/// * `accountid` - The database ID of the user owning the account.
+///
+pub async fn add_user_to_account(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    accountid: &i64,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO user_accounts (user_id, account_id)
+        VALUES (?, ?)
+        ",
+    )
+    .bind(&userid)
+    .bind(&accountid)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error associating user with account: {}", err);
+    });
+}
+
+/// Obtain all configured accounts.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_accounts(db: &DB) -> Result<Vec<Account>, GHDError> {
+    match sqlx::query_as::<_, Account>(
+        "
+        SELECT DISTINCT
+            users.id, users.login,
+            COALESCE(accounts.host, 'github.com') AS host
+        FROM
+            tokens
+            INNER JOIN users ON users.id = tokens.user_id
+            LEFT JOIN accounts ON accounts.user_id = tokens.user_id
+        ORDER BY users.login
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain accounts from db: {}", err);
+        }
+    }
+}
+
+/// Obtain the credentials to be used when talking to the API about the user
+/// `userid`. This will be the account through which the user is tracked, or
+/// the main account if the user is not associated with any account.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the user we are interested in.
+///
+pub async fn get_credentials_for_user(
+    db: &DB,
+    userid: &i64,
+) -> Result<AccountCredentials, GHDError> {
+    match sqlx::query_as::<_, (i64, String, String)>(
+        "
+        SELECT
+            COALESCE(tokens.user_id, 0), tokens.token,
+            COALESCE(accounts.host, 'github.com') AS host
+        FROM
+            tokens LEFT JOIN accounts ON accounts.user_id = tokens.user_id
+        WHERE
+            tokens.id = COALESCE(
+                (
+                    SELECT MAX(tokens.id) FROM tokens
+                    INNER JOIN user_accounts
+                    ON user_accounts.account_id = tokens.user_id
+                    WHERE user_accounts.user_id = ?
+                ),
+                (SELECT MAX(id) FROM tokens)
+            )
+        ",
+    )
+    .bind(&userid)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok((account_id, token, host)) => Ok(AccountCredentials {
+            account_id,
+            token,
+            host,
+        }),
+        Err(sqlx::Error::RowNotFound) => Err(GHDError::TokenNotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain credentials for user {}: {}", userid, err);
+        }
+    }
+}
+
+/// Obtain the credentials of every configured account, i.e. its latest
+/// token.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_all_credentials(db: &DB) -> Vec<AccountCredentials> {
+    match sqlx::query_as::<_, (i64, String, String)>(
+        "
+        SELECT
+            COALESCE(tokens.user_id, 0), tokens.token,
+            COALESCE(accounts.host, 'github.com') AS host
+        FROM
+            tokens LEFT JOIN accounts ON accounts.user_id = tokens.user_id
+        WHERE
+            tokens.id IN (
+                SELECT MAX(id) FROM tokens GROUP BY COALESCE(user_id, 0)
+            )
+        ORDER BY tokens.id DESC
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res
+            .into_iter()
+            .map(|(account_id, token, host)| AccountCredentials {
+                account_id,
+                token,
+                host,
+            })
+            .collect(),
+        Err(err) => {
+            panic!("Unable to obtain account credentials: {}", err);
+        }
+    }
+}
+
+/// Obtain the credentials for the main account.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_main_credentials(
+    db: &DB,
+) -> Result<AccountCredentials, GHDError> {
+    match sqlx::query_as::<_, (i64, String, String)>(
+        "
+        SELECT
+            COALESCE(tokens.user_id, 0), tokens.token,
+            COALESCE(accounts.host, 'github.com') AS host
+        FROM
+            tokens LEFT JOIN accounts ON accounts.user_id = tokens.user_id
+        WHERE
+            tokens.id = (SELECT MAX(id) FROM tokens)
+        ",
+    )
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok((account_id, token, host)) => Ok(AccountCredentials {
+            account_id,
+            token,
+            host,
+        }),
+        Err(sqlx::Error::RowNotFound) => Err(GHDError::TokenNotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain main credentials: {}", err);
+        }
+    }
+}
+
+/// Obtain the credentials to be used when talking to the API about the issue
+/// `issueid`, based on the tracked users it is associated with.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn get_credentials_for_issue(
+    db: &DB,
+    issueid: &i64,
+) -> Result<AccountCredentials, GHDError> {
+    let userid = match sqlx::query_scalar::<_, i64>(
+        "SELECT MIN(user_id) FROM user_issues WHERE issue_id = ?",
+    )
+    .bind(&issueid)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(_) => -1,
+    };
+
+    get_credentials_for_user(&db, &userid).await
+}
+
+/// Obtain a page of the Pull Requests involving users tracked by any of the
+/// configured accounts, most recently updated first. Each entry is tagged
+/// with the account it was obtained through; the same Pull Request may show
+/// up once per account, always within the same page.
+///
+/// # Arguments
+///
+/// * `snapshot` - The GHD Database read snapshot to read from.
+/// * `page` - The page to obtain.
+///
+pub async fn get_aggregated_prs(
+    snapshot: &Snapshot,
+    page: &PageRequest,
+) -> Result<Page<AccountPullRequestEntry>, GHDError> {
+    let query = format!(
+        "
+        WITH {},
+        entries AS (
+            SELECT DISTINCT
+                accounts_all.account,
+                issues.*, pull_requests.is_draft,
+                pull_requests.review_decision, pull_requests.merged_at
+            FROM
+                accounts_all
+                INNER JOIN account_users
+                    ON account_users.account_id = accounts_all.account_id
+                INNER JOIN user_issues
+                    ON user_issues.user_id = account_users.user_id
+                INNER JOIN issues ON issues.id = user_issues.issue_id
+                INNER JOIN pull_requests ON pull_requests.id = issues.id
+            WHERE {} AND {}
+        ),
+        page_prs AS (
+            SELECT DISTINCT updated_at, id FROM entries
+            WHERE {}
+            ORDER BY updated_at DESC, id DESC
+            LIMIT ?
+        )
+        SELECT entries.*
+        FROM entries INNER JOIN page_prs ON page_prs.id = entries.id
+        ORDER BY entries.updated_at DESC, entries.id DESC, entries.account
+        ",
+        ACCOUNT_USERS_CTE,
+        bots::filter(snapshot).await,
+        reconcile::NOT_DELETED,
+        PageRequest::condition("updated_at", "id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, AccountPullRequestEntry>(&query)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(&mut *snapshot.conn().await)
+        .await
+    {
+        Ok(res) => {
+            Ok(page.into_grouped_page(res, |e| (e.pr.updated_at, e.pr.id)))
+        }
+        Err(err) => {
+            panic!("Unable to obtain aggregated pull requests: {}", err);
+        }
+    }
+}
+
+/// Obtain badge counts for each configured account: open Pull Requests
+/// authored by the account's user, open Pull Requests involving any of the
+/// users tracked through the account, and how many of those have been
+/// updated since they were last viewed.
+///
+/// # Arguments
+///
+/// * `snapshot` - The GHD Database read snapshot to read from.
+///
+pub async fn get_aggregated_counts(
+    snapshot: &Snapshot,
+) -> Result<Vec<AccountCounts>, GHDError> {
+    let query = format!(
+        "
+        WITH {}
+        SELECT
+            accounts_all.account,
+            COUNT(DISTINCT CASE
+                WHEN issues.author_id = accounts_all.account_id
+                THEN issues.id END
+            ) AS authored,
+            COUNT(DISTINCT issues.id) AS involved,
+            COUNT(DISTINCT CASE
+                WHEN issues.last_viewed IS NULL
+                    OR issues.last_viewed < issues.updated_at
+                THEN issues.id END
+            ) AS unviewed
+        FROM
+            accounts_all
+            LEFT JOIN account_users
+                ON account_users.account_id = accounts_all.account_id
+            LEFT JOIN user_issues
+                ON user_issues.user_id = account_users.user_id
+            LEFT JOIN issues
+                ON issues.id = user_issues.issue_id
+                AND issues.is_pull_request = 1
+                AND issues.state = 'open'
+                AND {}
+        GROUP BY accounts_all.account
+        ORDER BY accounts_all.account
+        ",
+        ACCOUNT_USERS_CTE,
+        bots::filter(snapshot).await
+    );
+
+    match sqlx::query_as::<_, AccountCounts>(&query)
+        .fetch_all(&mut *snapshot.conn().await)
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain aggregated counts: {}", err);
+        }
+    }
+}
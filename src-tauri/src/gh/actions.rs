@@ -0,0 +1,278 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    access, accounts, annotations, away, policy,
+    types::{Action, ActionArg},
+};
+
+/// What to list actions for. Without an issue, only actions that don't
+/// apply to any particular item are listed.
+///
+#[derive(serde::Deserialize)]
+pub struct ActionContext {
+    pub issue_id: Option<i64>,
+}
+
+/// The item actions are being listed for, as far as they're concerned.
+///
+#[derive(sqlx::FromRow)]
+struct Item {
+    id: i64,
+    url: String,
+    repo_owner: String,
+    repo_name: String,
+    state: String,
+    is_pull_request: bool,
+    is_draft: Option<bool>,
+    head_ref: Option<String>,
+    is_ours: bool,
+    in_triage: bool,
+}
+
+impl Action {
+    fn new(id: &str, title: &str, command: Option<&str>) -> Self {
+        Action {
+            id: id.to_string(),
+            title: title.to_string(),
+            command: command.map(String::from),
+            args: vec![],
+        }
+    }
+
+    /// Add an argument to be filled in by the user.
+    ///
+    fn arg(mut self: Self, name: &str, kind: &str, required: bool) -> Self {
+        self.args.push(ActionArg {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            required,
+            choices: vec![],
+            value: None,
+        });
+        self
+    }
+
+    /// Add an argument limited to `choices`.
+    ///
+    fn choice(
+        mut self: Self,
+        name: &str,
+        choices: &[&str],
+        required: bool,
+    ) -> Self {
+        self.args.push(ActionArg {
+            name: name.to_string(),
+            kind: String::from("enum"),
+            required,
+            choices: choices.iter().map(|c| c.to_string()).collect(),
+            value: None,
+        });
+        self
+    }
+
+    /// Add an argument whose value is already known.
+    ///
+    fn value<T: serde::Serialize>(
+        mut self: Self,
+        name: &str,
+        kind: &str,
+        value: T,
+    ) -> Self {
+        self.args.push(ActionArg {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            required: true,
+            choices: vec![],
+            value: Some(serde_json::to_value(value).unwrap()),
+        });
+        self
+    }
+}
+
+/// Obtain the actions available in `context`. Actions with a `command` are
+/// run by invoking that command with the action's arguments; those without
+/// one are for the frontend to carry out with the provided values, e.g.
+/// opening a URL.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `context` - What to list actions for.
+///
+pub async fn list_actions(
+    db: &DB,
+    context: &ActionContext,
+) -> Result<Vec<Action>, GHDError> {
+    let issueid = match &context.issue_id {
+        Some(id) => id,
+        None => return Ok(get_global_actions(&db).await),
+    };
+
+    let item = match sqlx::query_as::<_, Item>(
+        "
+        SELECT
+            issues.id, issues.url, issues.repo_owner, issues.repo_name,
+            issues.state, issues.is_pull_request,
+            pull_requests.is_draft, pr_head_refs.head_ref,
+            issues.author_id IN (SELECT user_id FROM tokens) AS is_ours,
+            EXISTS (
+                SELECT 1 FROM triage
+                WHERE triage.issue_id = issues.id AND triage.dismissed = 0
+            ) AS in_triage
+        FROM
+            issues
+            LEFT JOIN pull_requests ON pull_requests.id = issues.id
+            LEFT JOIN pr_head_refs ON pr_head_refs.pr_id = issues.id
+        WHERE issues.id = ?
+        ",
+    )
+    .bind(&issueid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain issue {}: {}", issueid, err);
+        }
+    };
+
+    let mut actions = vec![Action::new("open", "Open on GitHub", None)
+        .value("url", "string", &item.url)];
+
+    if item.is_pull_request {
+        let is_open = item.state == "open";
+        let is_draft = item.is_draft.unwrap_or(false);
+
+        // leave out actions the acting account is known not to be allowed.
+        let level =
+            match accounts::get_credentials_for_issue(&db, &item.id).await {
+                Ok(creds) => {
+                    access::get_permission(
+                        &db,
+                        &creds.account_id,
+                        &item.repo_owner,
+                        &item.repo_name,
+                    )
+                    .await
+                }
+                Err(_) => None,
+            };
+        let can_review = access::allows(&level, access::REVIEW_LEVEL);
+        let can_merge = access::allows(&level, access::MERGE_LEVEL);
+
+        if let Some(head_ref) = &item.head_ref {
+            actions.push(
+                Action::new("copy_branch", "Copy branch name", None)
+                    .value("branch", "string", &head_ref),
+            );
+        }
+        actions.push(
+            Action::new(
+                "mark_viewed",
+                "Mark as viewed",
+                Some("pr_mark_viewed"),
+            )
+            .value("prid", "integer", &item.id),
+        );
+        if is_open && can_review {
+            actions.push(
+                Action::new("comment", "Comment", Some("pr_comment"))
+                    .value("prid", "integer", &item.id)
+                    .arg("body", "text", true),
+            );
+        }
+        if is_open && !is_draft && !item.is_ours && can_review {
+            actions.push(
+                Action::new("approve", "Approve", Some("pr_review"))
+                    .value("prid", "integer", &item.id)
+                    .value("event", "string", "APPROVE")
+                    .arg("body", "text", false),
+            );
+            actions.push(
+                Action::new(
+                    "request_changes",
+                    "Request changes",
+                    Some("pr_review"),
+                )
+                .value("prid", "integer", &item.id)
+                .value("event", "string", "REQUEST_CHANGES")
+                .arg("body", "text", true),
+            );
+        }
+        if is_open && !is_draft && can_merge {
+            let methods =
+                policy::get_methods(&db, &item.repo_owner, &item.repo_name)
+                    .await;
+            let methods: Vec<&str> =
+                methods.iter().map(|m| m.as_str()).collect();
+            actions.push(
+                Action::new("merge", "Merge", Some("pr_merge"))
+                    .value("prid", "integer", &item.id)
+                    .choice("method", &methods, true),
+            );
+        }
+        if is_open && !is_draft {
+            actions.push(
+                Action::new(
+                    "snooze",
+                    "Snooze reminders",
+                    Some("snooze_reminders"),
+                )
+                .value("prid", "integer", &item.id)
+                .arg("hours", "integer", true),
+            );
+        }
+    }
+
+    if item.in_triage {
+        actions.push(
+            Action::new(
+                "dismiss",
+                "Dismiss from triage",
+                Some("triage_dismiss"),
+            )
+            .value("issueid", "integer", &item.id),
+        );
+    }
+    actions.push(
+        Action::new("set_priority", "Set priority", Some("set_issue_priority"))
+            .value("issueid", "integer", &item.id)
+            .choice("priority", &annotations::PRIORITIES, false),
+    );
+    actions.push(
+        Action::new("set_note", "Set note", Some("set_issue_note"))
+            .value("issueid", "integer", &item.id)
+            .arg("note", "text", false),
+    );
+    actions.push(
+        Action::new("add_tag", "Add tag", Some("add_issue_tag"))
+            .value("issueid", "integer", &item.id)
+            .arg("tag", "string", true),
+    );
+
+    Ok(actions)
+}
+
+/// Obtain the actions that don't apply to any particular item.
+///
+async fn get_global_actions(db: &DB) -> Vec<Action> {
+    match away::is_away(&db).await {
+        true => vec![Action::new("away_end", "I'm back", Some("away_end"))],
+        false => vec![Action::new("away_start", "Go away", Some("away_start"))],
+    }
+}
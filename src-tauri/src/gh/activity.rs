@@ -0,0 +1,142 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    db::DB,
+    errors::GHDError,
+    page::{Page, PageRequest},
+};
+
+use super::types::ActivityEvent;
+
+/// Every kind of event in the activity feed, as a union of selects over the
+/// local caches. Each select must provide the same columns as
+/// `ActivityEvent`, with an `id` made unique across kinds by combining the
+/// row it's selected from with the kind's position below.
+///
+/// * `review_requested` - A review was requested from one of our users.
+/// * `merged` - A Pull Request we track was merged.
+/// * `ci_failed` - CI failed on the head of one of our Pull Requests.
+/// * `release_published` - A maintained repository published a release.
+/// * `mentioned` - One of our users was mentioned in an issue or Pull
+///   Request. Mentions are only spotted in the body, so they are dated by
+///   the item's creation.
+///
+const ACTIVITY_EVENTS: &str = "
+    SELECT
+        pr_review_requests.rowid * 8 AS id,
+        'review_requested' AS kind,
+        pr_review_requests.requested_at AS occurred_at,
+        issues.id AS issue_id, issues.repo_owner, issues.repo_name,
+        issues.number, issues.title, issues.url
+    FROM
+        pr_review_requests INNER JOIN issues
+            ON issues.id = pr_review_requests.pr_id
+    WHERE pr_review_requests.user_id IN (SELECT user_id FROM tokens)
+
+    UNION ALL
+
+    SELECT
+        pull_requests.id * 8 + 1 AS id,
+        'merged' AS kind,
+        pull_requests.merged_at AS occurred_at,
+        issues.id AS issue_id, issues.repo_owner, issues.repo_name,
+        issues.number, issues.title, issues.url
+    FROM
+        pull_requests INNER JOIN issues ON issues.id = pull_requests.id
+    WHERE pull_requests.merged_at IS NOT NULL
+
+    UNION ALL
+
+    SELECT
+        pr_ci_states.pr_id * 8 + 2 AS id,
+        'ci_failed' AS kind,
+        pr_ci_states.changed_at AS occurred_at,
+        issues.id AS issue_id, issues.repo_owner, issues.repo_name,
+        issues.number, issues.title, issues.url
+    FROM
+        pr_ci_states INNER JOIN issues ON issues.id = pr_ci_states.pr_id
+    WHERE
+        pr_ci_states.state IN ('failure', 'error')
+        AND issues.author_id IN (SELECT user_id FROM tokens)
+
+    UNION ALL
+
+    SELECT
+        releases.id * 8 + 3 AS id,
+        'release_published' AS kind,
+        releases.published_at AS occurred_at,
+        NULL AS issue_id, releases.repo_owner, releases.repo_name,
+        NULL AS number, COALESCE(releases.name, releases.tag_name) AS title,
+        releases.url
+    FROM releases
+
+    UNION ALL
+
+    SELECT DISTINCT
+        issues.id * 8 + 4 AS id,
+        'mentioned' AS kind,
+        issues.created_at AS occurred_at,
+        issues.id AS issue_id, issues.repo_owner, issues.repo_name,
+        issues.number, issues.title, issues.url
+    FROM
+        user_issue_relations INNER JOIN issues
+            ON issues.id = user_issue_relations.issue_id
+    WHERE
+        user_issue_relations.relation = 'mentions'
+        AND user_issue_relations.user_id IN (SELECT user_id FROM tokens)
+";
+
+/// Obtain a page of the activity feed, most recent first. If `since` is
+/// provided, only events that happened after it are returned, allowing the
+/// caller to poll for new events.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `since` - Only return events that happened after this timestamp.
+/// * `page` - The page to obtain.
+///
+pub async fn get_feed(
+    db: &DB,
+    since: &Option<i64>,
+    page: &PageRequest,
+) -> Result<Page<ActivityEvent>, GHDError> {
+    let query = format!(
+        "
+        SELECT * FROM ({})
+        WHERE (? IS NULL OR occurred_at > ?) AND {}
+        ORDER BY occurred_at DESC, id DESC
+        LIMIT ?
+        ",
+        ACTIVITY_EVENTS,
+        PageRequest::condition("occurred_at", "id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, ActivityEvent>(&query)
+        .bind(&since)
+        .bind(&since)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(page.into_page(res, |e| (e.occurred_at, e.id))),
+        Err(err) => {
+            panic!("Unable to obtain activity feed: {}", err);
+        }
+    }
+}
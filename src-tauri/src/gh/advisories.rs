@@ -0,0 +1,361 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{db::DB, errors::GHDError, notify};
+
+use super::{
+    accounts::AccountCredentials, refresh, rest, types::AdvisoryFinding,
+};
+
+/// Advisories are checked once a day.
+///
+pub const ADVISORIES_REFRESH_INTERVAL: i64 = 24 * 60 * 60;
+
+/// Maximum number of packages to ask about in a single advisories request,
+/// so we keep the query string within reasonable bounds.
+///
+const AFFECTS_CHUNK_SIZE: usize = 100;
+
+/// Package URL types and the advisory database ecosystem they map to.
+///
+const ECOSYSTEMS: [(&str, &str); 12] = [
+    ("cargo", "rust"),
+    ("npm", "npm"),
+    ("pypi", "pip"),
+    ("maven", "maven"),
+    ("golang", "go"),
+    ("gem", "rubygems"),
+    ("nuget", "nuget"),
+    ("composer", "composer"),
+    ("pub", "pub"),
+    ("swift", "swift"),
+    ("hex", "erlang"),
+    ("githubactions", "actions"),
+];
+
+/// A dependency, as understood by the advisory database.
+///
+struct Dependency {
+    ecosystem: String,
+    name: String,
+    version: String,
+}
+
+/// Obtain the sync refresh key for the advisories of repository `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("advisories:{}/{}", owner, name)
+}
+
+/// Check whether the advisories of repository `owner/name` are due for a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        ADVISORIES_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Parse a Package URL (e.g., `pkg:cargo/serde@1.0.152`) into a dependency.
+/// Returns `None` for package types the advisory database doesn't know about,
+/// or for packages without a version.
+///
+fn parse_purl(purl: &str) -> Option<Dependency> {
+    let purl = purl.strip_prefix("pkg:")?;
+    let purl = purl.split(|c| c == '?' || c == '#').next()?;
+    let (kind, rest) = purl.split_once('/')?;
+    let (path, version) = rest.rsplit_once('@')?;
+
+    let ecosystem = ECOSYSTEMS
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, e)| String::from(*e))?;
+
+    let path = path.replace("%40", "@");
+    let name = match kind {
+        // the advisory database names maven packages `group:artifact`.
+        "maven" => path.replacen('/', ":", 1),
+        _ => path,
+    };
+
+    Some(Dependency {
+        ecosystem,
+        name,
+        version: version.to_string(),
+    })
+}
+
+/// Obtain the dependencies of repository `owner/name` from its dependency
+/// graph. Requires the dependency graph to be enabled for the repository.
+///
+async fn fetch_dependencies(
+    ghreq: &rest::GithubRequest,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<Dependency>, GHDError> {
+    let ep = format!("/repos/{}/{}/dependency-graph/sbom", owner, name);
+    let req = ghreq.get(&ep);
+    let sbom = match ghreq.send::<rest::GithubSbomReply>(req).await {
+        Ok(res) => res.sbom,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(sbom
+        .packages
+        .iter()
+        .flat_map(|p| p.external_refs.iter())
+        .filter(|r| r.reference_type == "purl")
+        .filter_map(|r| parse_purl(&r.reference_locator))
+        .collect())
+}
+
+/// Obtain the advisories affecting the dependencies of repository
+/// `owner/name`, by cross-checking its dependency graph against the GitHub
+/// Advisory Database.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<AdvisoryFinding>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("advisories");
+    let deps = fetch_dependencies(&ghreq, &owner, &name).await?;
+
+    let mut by_ecosystem: HashMap<&String, Vec<&Dependency>> = HashMap::new();
+    for dep in &deps {
+        by_ecosystem.entry(&dep.ecosystem).or_default().push(dep);
+    }
+
+    let mut findings: Vec<AdvisoryFinding> = vec![];
+    for (ecosystem, deps) in by_ecosystem {
+        for chunk in deps.chunks(AFFECTS_CHUNK_SIZE) {
+            let affects = chunk
+                .iter()
+                .map(|d| format!("{}@{}", d.name, d.version))
+                .collect::<Vec<String>>()
+                .join(",");
+            let req = ghreq.get("/advisories").query(&[
+                ("ecosystem", ecosystem.as_str()),
+                ("affects", affects.as_str()),
+                ("per_page", "100"),
+            ]);
+            let advisories = match ghreq
+                .get_all_linked_pages::<rest::GithubAdvisoryReply>(req)
+                .await
+            {
+                Ok(res) => res,
+                Err(err) => return Err(rest::status_to_error(err)),
+            };
+
+            for adv in &advisories {
+                for vuln in &adv.vulnerabilities {
+                    let pkg = match &vuln.package {
+                        Some(p) if &p.ecosystem == ecosystem => p,
+                        _ => continue,
+                    };
+                    let dep = match chunk.iter().find(|d| d.name == pkg.name) {
+                        Some(d) => d,
+                        None => continue,
+                    };
+                    findings.push(AdvisoryFinding {
+                        repo_owner: owner.clone(),
+                        repo_name: name.clone(),
+                        ghsa_id: adv.ghsa_id.clone(),
+                        ecosystem: ecosystem.clone(),
+                        package: dep.name.clone(),
+                        version: dep.version.clone(),
+                        severity: adv.severity.clone(),
+                        summary: adv.summary.clone(),
+                        url: adv.html_url.clone(),
+                        vulnerable_range: vuln.vulnerable_version_range.clone(),
+                        patched_version: vuln.first_patched_version.clone(),
+                        found_at: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Write the provided findings to the database, replacing the findings
+/// previously known for repository `owner/name`. Findings seen for the first
+/// time get a notification. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `findings` - The advisories affecting the repository.
+/// * `when` - When the findings were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+    findings: &Vec<AdvisoryFinding>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    let known: HashMap<(String, String, String), i64> =
+        sqlx::query_as::<_, (String, String, String, i64)>(
+            "
+            SELECT ghsa_id, ecosystem, package, found_at
+            FROM advisory_findings
+            WHERE repo_owner = ? AND repo_name = ?
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to obtain known advisory findings: {}", err);
+        })
+        .into_iter()
+        .map(|(ghsa, eco, pkg, found_at)| ((ghsa, eco, pkg), found_at))
+        .collect();
+
+    sqlx::query(
+        "DELETE FROM advisory_findings WHERE repo_owner = ? AND repo_name = ?",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to clear advisory findings: {}", err);
+    });
+
+    let mut seen: HashSet<(String, String, String)> = HashSet::new();
+    for f in findings {
+        let key = (f.ghsa_id.clone(), f.ecosystem.clone(), f.package.clone());
+        // an advisory may list several vulnerable ranges for the same package.
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        let found_at = match known.get(&key) {
+            Some(ts) => *ts,
+            None => {
+                let title = format!(
+                    "{} advisory for {} in {}/{}",
+                    f.severity, f.package, owner, name
+                );
+                let priority = match f.severity.as_str() {
+                    "critical" | "high" => notify::PRIORITY_HIGH,
+                    _ => notify::classify("advisory"),
+                };
+                notify::push(
+                    tx, "advisory", &None, &title, &f.summary, priority,
+                )
+                .await;
+                when.timestamp()
+            }
+        };
+
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO advisory_findings (
+                repo_owner, repo_name, ghsa_id, ecosystem, package, version,
+                severity, summary, url, vulnerable_range, patched_version,
+                found_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .bind(&f.ghsa_id)
+        .bind(&f.ecosystem)
+        .bind(&f.package)
+        .bind(&f.version)
+        .bind(&f.severity)
+        .bind(&f.summary)
+        .bind(&f.url)
+        .bind(&f.vulnerable_range)
+        .bind(&f.patched_version)
+        .bind(&found_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to insert advisory {}: {}", f.ghsa_id, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+/// Obtain the advisory feed, most recently found and most severe first,
+/// optionally limited to repository `owner/name`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner, if limiting to one repository.
+/// * `name` - The repository's name, if limiting to one repository.
+///
+pub async fn get_feed(
+    db: &DB,
+    owner: &Option<String>,
+    name: &Option<String>,
+) -> Result<Vec<AdvisoryFinding>, GHDError> {
+    match sqlx::query_as::<_, AdvisoryFinding>(
+        "
+        SELECT * FROM advisory_findings
+        WHERE
+            (? IS NULL OR repo_owner = ?)
+            AND (? IS NULL OR repo_name = ?)
+        ORDER BY
+            found_at DESC,
+            CASE severity
+                WHEN 'critical' THEN 0
+                WHEN 'high' THEN 1
+                WHEN 'medium' THEN 2
+                WHEN 'low' THEN 3
+                ELSE 4
+            END,
+            repo_owner, repo_name, package
+        ",
+    )
+    .bind(&owner)
+    .bind(&owner)
+    .bind(&name)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain advisory feed: {}", err);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+/// Local priorities an issue or Pull Request may be given, lowest first.
+///
+pub const PRIORITIES: [&str; 4] = ["low", "normal", "high", "urgent"];
+
+/// Columns with the local annotations of the `issues` row being selected,
+/// if any.
+///
+pub const ANNOTATION_COLUMNS: &str = "
+    (
+        SELECT priority FROM issue_annotations
+        WHERE issue_annotations.issue_id = issues.id
+    ) AS local_priority,
+    (
+        SELECT note FROM issue_annotations
+        WHERE issue_annotations.issue_id = issues.id
+    ) AS local_note
+";
+
+/// Set the local priority of issue `issueid`, or clear it if `None`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+/// * `priority` - One of `PRIORITIES`, if any.
+///
+pub async fn set_priority(
+    db: &DB,
+    issueid: &i64,
+    priority: &Option<String>,
+) -> Result<(), GHDError> {
+    if let Some(p) = priority {
+        if !PRIORITIES.contains(&p.as_str()) {
+            return Err(GHDError::BadRequest);
+        }
+    }
+    set(db, issueid, "priority", priority).await
+}
+
+/// Set the local note of issue `issueid`, or clear it if `None` or empty.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+/// * `note` - Free-form text.
+///
+pub async fn set_note(
+    db: &DB,
+    issueid: &i64,
+    note: &Option<String>,
+) -> Result<(), GHDError> {
+    let note = note.as_ref().map(|n| n.trim()).filter(|n| !n.is_empty());
+    set(db, issueid, "note", &note.map(String::from)).await
+}
+
+/// Set annotation `column` of issue `issueid` to `value`, dropping the
+/// annotations altogether once none are left.
+///
+async fn set(
+    db: &DB,
+    issueid: &i64,
+    column: &str,
+    value: &Option<String>,
+) -> Result<(), GHDError> {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to annotate issue: {}", err);
+        }
+    };
+
+    match sqlx::query_scalar::<_, i64>("SELECT id FROM issues WHERE id = ?")
+        .bind(&issueid)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain issue {}: {}", issueid, err);
+        }
+    };
+
+    let query = format!(
+        "
+        INSERT INTO issue_annotations (issue_id, {0}, updated_at)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT (issue_id) DO UPDATE SET
+            {0} = excluded.{0}, updated_at = excluded.updated_at
+        ",
+        column
+    );
+    sqlx::query(&query)
+        .bind(&issueid)
+        .bind(&value)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to annotate issue {}: {}", issueid, err);
+        });
+
+    sqlx::query(
+        "
+        DELETE FROM issue_annotations
+        WHERE issue_id = ? AND priority IS NULL AND note IS NULL
+        ",
+    )
+    .bind(&issueid)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to clean up annotations of {}: {}", issueid, err);
+    });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit annotations of {}: {}", issueid, err);
+    });
+    Ok(())
+}
@@ -0,0 +1,205 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, db::DB};
+
+use super::types::UsageAnomaly;
+
+/// Setting holding how many times its usual hourly API calls an account must
+/// make in an hour for it to be anomalous; `0` disables detection.
+///
+pub const SETTING_ANOMALY_FACTOR: &str = "anomalies.factor";
+
+/// Setting holding the API calls in an hour below which usage is never
+/// anomalous, however quiet the account usually is.
+///
+pub const SETTING_ANOMALY_MIN_CALLS: &str = "anomalies.min_calls";
+
+const DEFAULT_ANOMALY_FACTOR: i64 = 5;
+const DEFAULT_ANOMALY_MIN_CALLS: i64 = 1000;
+
+/// Minutes sync is paused for once an anomaly is detected.
+///
+pub const ANOMALY_PAUSE_MINUTES: i64 = 30;
+
+/// Hours of usage the baseline is computed over, and how many of those must
+/// have seen calls for it to be trusted. Until then, only the minimum calls
+/// apply.
+///
+const BASELINE_HOURS: i64 = 7 * 24;
+const MIN_BASELINE_HOURS: i64 = 24;
+
+async fn get_int_setting(db: &DB, key: &str, default: i64) -> i64 {
+    config::get_setting_or(&db, key, &default.to_string())
+        .await
+        .parse::<i64>()
+        .unwrap_or(default)
+}
+
+/// Obtain the calls an account usually makes in an hour to `host`: the
+/// average over the hours it made any during the last week, not counting
+/// `hour`. Returns `None` if there's too little history to tell.
+///
+async fn get_baseline(
+    db: &DB,
+    accountid: &i64,
+    host: &String,
+    hour: &i64,
+) -> Option<i64> {
+    let (hours, average) = match sqlx::query_as::<_, (i64, f64)>(
+        "
+        SELECT COUNT(*), COALESCE(AVG(calls), 0.0)
+        FROM api_usage_hours
+        WHERE
+            account_id = ? AND host = ? AND hour < ? AND hour >= ?
+        ",
+    )
+    .bind(&accountid)
+    .bind(&host)
+    .bind(&hour)
+    .bind(hour - BASELINE_HOURS * 3600)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain usage baseline: {}", err);
+        }
+    };
+
+    match hours >= MIN_BASELINE_HOURS {
+        true => Some(average.round() as i64),
+        false => None,
+    }
+}
+
+/// Check the API calls made during the current hour against each account's
+/// baseline, recording and returning those newly found anomalous. An hour is
+/// flagged at most once per account. Should be called after flushing the
+/// ledger.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn check(db: &DB) -> Vec<UsageAnomaly> {
+    let factor =
+        get_int_setting(&db, SETTING_ANOMALY_FACTOR, DEFAULT_ANOMALY_FACTOR)
+            .await;
+    if factor <= 0 {
+        return vec![];
+    }
+    let min_calls = get_int_setting(
+        &db,
+        SETTING_ANOMALY_MIN_CALLS,
+        DEFAULT_ANOMALY_MIN_CALLS,
+    )
+    .await;
+
+    let now = chrono::Utc::now().timestamp();
+    let hour = now - now % 3600;
+    let current = match sqlx::query_as::<_, (i64, String, i64)>(
+        "SELECT account_id, host, calls FROM api_usage_hours WHERE hour = ?",
+    )
+    .bind(&hour)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain current api usage: {}", err);
+        }
+    };
+
+    let mut found = vec![];
+    for (accountid, host, calls) in current {
+        let baseline = get_baseline(&db, &accountid, &host, &hour).await;
+        let threshold = match baseline {
+            Some(b) => min_calls.max(b * factor),
+            None => min_calls,
+        };
+        if calls < threshold {
+            continue;
+        }
+
+        let res = sqlx::query(
+            "
+            INSERT OR IGNORE INTO usage_anomalies (
+                account_id, host, hour, calls, baseline, detected_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&accountid)
+        .bind(&host)
+        .bind(&hour)
+        .bind(&calls)
+        .bind(baseline.unwrap_or(0))
+        .bind(&now)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to record usage anomaly: {}", err);
+        });
+        if res.rows_affected() > 0 {
+            log::warn!(
+                "anomalous api usage for account {} on {}: {} calls",
+                accountid,
+                host,
+                calls
+            );
+            found.extend(get_anomalies(&db, &hour).await.into_iter().filter(
+                |a| a.account_id.unwrap_or(0) == accountid && a.host == host,
+            ));
+        }
+    }
+    found
+}
+
+/// Obtain the usage anomalies detected for hours since `since`, most recent
+/// first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `since` - Timestamp since which to obtain anomalies.
+///
+pub async fn get_anomalies(db: &DB, since: &i64) -> Vec<UsageAnomaly> {
+    match sqlx::query_as::<_, UsageAnomaly>(
+        "
+        SELECT
+            usage_anomalies.id,
+            NULLIF(usage_anomalies.account_id, 0) AS account_id,
+            users.login AS account, usage_anomalies.host,
+            usage_anomalies.hour, usage_anomalies.calls,
+            usage_anomalies.baseline, usage_anomalies.detected_at
+        FROM
+            usage_anomalies
+            LEFT JOIN users ON users.id = usage_anomalies.account_id
+        WHERE
+            usage_anomalies.hour >= ? - ? % 3600
+        ORDER BY
+            usage_anomalies.hour DESC, usage_anomalies.id DESC
+        ",
+    )
+    .bind(&since)
+    .bind(&since)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain usage anomalies: {}", err);
+        }
+    }
+}
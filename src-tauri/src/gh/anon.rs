@@ -0,0 +1,370 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    limits, refresh, rest,
+    types::{AnonPullRequest, AnonTarget},
+};
+
+pub const KIND_USER: &str = "user";
+pub const KIND_REPO: &str = "repo";
+
+/// Followed users and repositories are checked every 15 minutes at most.
+///
+pub const ANON_REFRESH_INTERVAL: i64 = 15 * 60;
+
+/// Unauthenticated searches are limited to 10 a minute. Calls are spaced so
+/// as to stay well clear of that, whatever the number of followed targets.
+///
+const ANON_CALL_SPACING_SECS: i64 = 10;
+
+/// Pull Requests obtained per followed user or repository, most recently
+/// updated first.
+///
+const ANON_MAX_PRS: i64 = 50;
+
+/// When the next anonymous call may be made, as a UTC epoch timestamp. Pushed
+/// back to when the rate limit resets if GitHub reports it exhausted.
+///
+static NEXT_CALL_AT: AtomicI64 = AtomicI64::new(0);
+
+/// Obtain the sync refresh key for the followed target `targetid`.
+///
+pub fn refresh_key(targetid: &i64) -> String {
+    format!("anon:{}", targetid)
+}
+
+/// Check whether an anonymous call may be made now, given the rate limit.
+///
+pub fn may_call() -> bool {
+    chrono::Utc::now().timestamp() >= NEXT_CALL_AT.load(Ordering::Relaxed)
+}
+
+/// Hold off anonymous calls until at least `when`.
+///
+fn hold_until(when: i64) {
+    NEXT_CALL_AT.fetch_max(when, Ordering::Relaxed);
+}
+
+/// Check whether `name` is well-formed for a target of `kind`: a login for
+/// users, `owner/name` for repositories.
+///
+fn is_valid_name(kind: &str, name: &str) -> bool {
+    let valid = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    };
+    match kind {
+        KIND_USER => valid(name),
+        KIND_REPO => match name.split_once('/') {
+            Some((owner, repo)) => valid(owner) && valid(repo),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Obtain the Pull Requests of a followed target without a token: those
+/// involving the user, or those open on the repository. Only public data can
+/// be obtained this way.
+///
+/// # Arguments
+///
+/// * `target` - The followed user or repository.
+///
+pub async fn fetch(
+    target: &AnonTarget,
+) -> Result<Vec<AnonPullRequest>, GHDError> {
+    let q = match target.kind.as_str() {
+        KIND_USER => format!("is:pr involves:{}", target.name),
+        _ => format!("is:pr is:open repo:{}", target.name),
+    };
+    let ghreq = rest::GithubRequest::new_anonymous().for_feature("anon");
+    let req = ghreq.get("/search/issues").query(&[
+        ("q", q),
+        ("sort", "updated".to_string()),
+        ("per_page", ANON_MAX_PRS.to_string()),
+    ]);
+
+    let now = chrono::Utc::now().timestamp();
+    hold_until(now + ANON_CALL_SPACING_SECS);
+    let res = match ghreq.execute(req).await {
+        Ok(res) => res,
+        Err(_) => return Err(GHDError::UnknownError),
+    };
+
+    let header = |name: &str| {
+        res.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<i64>().ok())
+    };
+    if !res.status().is_success() {
+        if header("x-ratelimit-remaining") == Some(0) {
+            hold_until(header("x-ratelimit-reset").unwrap_or(now + 60));
+            return Err(GHDError::UnknownError);
+        }
+        return Err(rest::status_to_error(res.status()));
+    }
+
+    let body = match limits::read_body(res).await {
+        Some(res) => res,
+        None => return Err(GHDError::ResponseTooLargeError),
+    };
+    let reply =
+        match serde_json::from_str::<rest::GithubSearchIssuesReply>(&body) {
+            Ok(res) => res,
+            Err(_) => return Err(GHDError::UnknownError),
+        };
+
+    Ok(reply
+        .items
+        .into_iter()
+        .filter_map(|i| {
+            let mut parts = i.repository_url.rsplitn(3, '/');
+            let repo_name = parts.next()?.to_string();
+            let repo_owner = parts.next()?.to_string();
+            Some(AnonPullRequest {
+                target_id: target.id,
+                repo_owner,
+                repo_name,
+                number: i.number,
+                title: i.title,
+                author: i.user.map_or(String::new(), |u| u.login),
+                state: i.state,
+                is_draft: i.draft,
+                url: i.html_url,
+                updated_at: i.updated_at.timestamp(),
+            })
+        })
+        .collect())
+}
+
+/// Replace the Pull Requests known for followed target `targetid`. This
+/// function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `targetid` - The followed target's database ID.
+/// * `prs` - The target's Pull Requests.
+/// * `when` - When the Pull Requests were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    targetid: &i64,
+    prs: &Vec<AnonPullRequest>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query("DELETE FROM anon_prs WHERE target_id = ?")
+        .bind(&targetid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to clear prs for target {}: {}", targetid, err);
+        });
+
+    for pr in prs {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO anon_prs (
+                target_id, repo_owner, repo_name, number, title, author,
+                state, is_draft, url, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&pr.target_id)
+        .bind(&pr.repo_owner)
+        .bind(&pr.repo_name)
+        .bind(&pr.number)
+        .bind(&pr.title)
+        .bind(&pr.author)
+        .bind(&pr.state)
+        .bind(&pr.is_draft)
+        .bind(&pr.url)
+        .bind(&pr.updated_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!(
+                "Unable to insert pr {}/{}#{}: {}",
+                pr.repo_owner, pr.repo_name, pr.number, err
+            );
+        });
+    }
+
+    sqlx::query("UPDATE anon_targets SET synced_at = ? WHERE id = ?")
+        .bind(when.timestamp())
+        .bind(&targetid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to update target {}: {}", targetid, err);
+        });
+
+    refresh::update_sync_refresh(tx, &refresh_key(&targetid), &when).await;
+}
+
+/// Follow the public user or repository `name` without a token.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `kind` - Either `user` or `repo`.
+/// * `name` - The user's login, or the repository's `owner/name`.
+///
+pub async fn track(
+    db: &DB,
+    kind: &String,
+    name: &String,
+) -> Result<AnonTarget, GHDError> {
+    if !is_valid_name(&kind, &name) {
+        return Err(GHDError::BadRequest);
+    }
+
+    sqlx::query(
+        "
+        INSERT OR IGNORE INTO anon_targets (kind, name, added_at)
+        VALUES (?, ?, ?)
+        ",
+    )
+    .bind(&kind)
+    .bind(&name)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to follow {} '{}': {}", kind, name, err);
+    });
+
+    match sqlx::query_as::<_, AnonTarget>(
+        "SELECT * FROM anon_targets WHERE kind = ? AND name = ?",
+    )
+    .bind(&kind)
+    .bind(&name)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain followed {} '{}': {}", kind, name, err);
+        }
+    }
+}
+
+/// Stop following target `targetid`, dropping its Pull Requests.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `targetid` - The followed target's database ID.
+///
+pub async fn untrack(db: &DB, targetid: &i64) {
+    for q in [
+        "DELETE FROM anon_prs WHERE target_id = ?",
+        "DELETE FROM anon_targets WHERE id = ?",
+        "DELETE FROM sync_refresh WHERE key = 'anon:' || ?",
+    ] {
+        sqlx::query(q)
+            .bind(&targetid)
+            .execute(db.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to unfollow target {}: {}", targetid, err);
+            });
+    }
+}
+
+/// Obtain the users and repositories followed without a token.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_targets(db: &DB) -> Vec<AnonTarget> {
+    match sqlx::query_as::<_, AnonTarget>(
+        "SELECT * FROM anon_targets ORDER BY kind, name",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain followed targets: {}", err);
+        }
+    }
+}
+
+/// Obtain the next followed target to refresh, if any is due and the rate
+/// limit allows for it. Targets never synced go first, then those synced the
+/// longest ago.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn next_due(db: &DB) -> Option<AnonTarget> {
+    if !may_call() {
+        return None;
+    }
+
+    let mut targets = get_targets(&db).await;
+    targets.sort_by_key(|t| t.synced_at.unwrap_or(0));
+    for target in targets {
+        if refresh::should_refresh_sync(
+            &db,
+            &refresh_key(&target.id),
+            ANON_REFRESH_INTERVAL,
+        )
+        .await
+        {
+            return Some(target);
+        }
+    }
+    None
+}
+
+/// Obtain the Pull Requests obtained for followed target `targetid`, most
+/// recently updated first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `targetid` - The followed target's database ID.
+///
+pub async fn get_pulls(
+    db: &DB,
+    targetid: &i64,
+) -> Result<Vec<AnonPullRequest>, GHDError> {
+    match sqlx::query_as::<_, AnonPullRequest>(
+        "
+        SELECT * FROM anon_prs WHERE target_id = ?
+        ORDER BY updated_at DESC
+        ",
+    )
+    .bind(&targetid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain prs for target {}: {}", targetid, err);
+        }
+    }
+}
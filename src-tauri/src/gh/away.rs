@@ -0,0 +1,235 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    conflicts, contributors,
+    types::{AwayPeriod, CatchUpSummary, PullRequestTableEntry},
+};
+
+/// Check whether we're currently away.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn is_away(db: &DB) -> bool {
+    get_current(&db).await.is_some()
+}
+
+/// Obtain the current away period, if we're away.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_current(db: &DB) -> Option<AwayPeriod> {
+    match sqlx::query_as::<_, AwayPeriod>(
+        "SELECT * FROM away_periods WHERE ended_at IS NULL",
+    )
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain current away period: {}", err);
+        }
+    }
+}
+
+/// Start an away period, unless we're already away. Returns the current away
+/// period's ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn start(db: &DB) -> i64 {
+    if let Some(period) = get_current(&db).await {
+        return period.id;
+    }
+
+    match sqlx::query("INSERT INTO away_periods (started_at) VALUES (?)")
+        .bind(chrono::Utc::now().timestamp())
+        .execute(db.pool())
+        .await
+    {
+        Ok(res) => res.last_insert_rowid(),
+        Err(err) => {
+            panic!("Unable to start away period: {}", err);
+        }
+    }
+}
+
+/// End the current away period, if any. Notifications queued while away
+/// are marked delivered without being shown; the catch-up summary covers
+/// them instead. Returns the ended away period.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn end(db: &DB) -> Option<AwayPeriod> {
+    let mut period = get_current(&db).await?;
+    let now = chrono::Utc::now().timestamp();
+
+    sqlx::query("UPDATE away_periods SET ended_at = ? WHERE id = ?")
+        .bind(&now)
+        .bind(&period.id)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error ending away period {}: {}", period.id, err);
+        });
+
+    sqlx::query(
+        "UPDATE notifications SET delivered_at = ? WHERE delivered_at IS NULL",
+    )
+    .bind(&now)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error suppressing away notifications: {}", err);
+    });
+
+    period.ended_at = Some(now);
+    Some(period)
+}
+
+/// Obtain the Pull Requests matching `condition` during the provided away
+/// period. The condition may refer to the period's start and end as `?1`
+/// and `?2`.
+///
+async fn get_prs_during(
+    db: &DB,
+    period: &AwayPeriod,
+    condition: &str,
+    order: &str,
+) -> Vec<PullRequestTableEntry> {
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at, {}, {}
+        FROM
+            pull_requests INNER JOIN issues
+        ON
+            pull_requests.id = issues.id
+        WHERE {}
+        ORDER BY {}
+        ",
+        conflicts::POTENTIAL_CONFLICTS_COLUMN,
+        contributors::FIRST_TIME_CONTRIBUTOR_COLUMN,
+        condition,
+        order
+    );
+
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&period.started_at)
+        .bind(period.ended_at.unwrap_or(chrono::Utc::now().timestamp()))
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain catch-up pull requests: {}", err);
+        }
+    }
+}
+
+/// Obtain the catch-up summary for away period `periodid`, or for the most
+/// recent away period if not provided: what was merged while away, what
+/// review requests came in, and which open Pull Requests, either ours or on
+/// maintained repositories, saw no activity at all.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `periodid` - The away period's ID, if not the most recent one.
+///
+pub async fn get_catch_up(
+    db: &DB,
+    periodid: &Option<i64>,
+) -> Result<CatchUpSummary, GHDError> {
+    let period = match sqlx::query_as::<_, AwayPeriod>(
+        "
+        SELECT * FROM away_periods
+        WHERE ? IS NULL OR id = ?
+        ORDER BY started_at DESC
+        LIMIT 1
+        ",
+    )
+    .bind(&periodid)
+    .bind(&periodid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain away period: {}", err);
+        }
+    };
+
+    let merged = get_prs_during(
+        &db,
+        &period,
+        "pull_requests.merged_at BETWEEN ?1 AND ?2",
+        "pull_requests.merged_at DESC",
+    )
+    .await;
+
+    let review_requested = get_prs_during(
+        &db,
+        &period,
+        "
+        issues.state = 'open' AND issues.id IN (
+            SELECT pr_id FROM pr_review_requests
+            WHERE
+                user_id IN (SELECT user_id FROM tokens)
+                AND requested_at BETWEEN ?1 AND ?2
+        )
+        ",
+        "issues.updated_at DESC",
+    )
+    .await;
+
+    let stale = get_prs_during(
+        &db,
+        &period,
+        "
+        issues.state = 'open'
+        AND issues.updated_at < ?1
+        AND (
+            issues.author_id IN (SELECT user_id FROM tokens)
+            OR EXISTS (
+                SELECT 1 FROM repos
+                WHERE
+                    repos.owner = issues.repo_owner
+                    AND repos.name = issues.repo_name
+            )
+        )
+        ",
+        "issues.updated_at ASC",
+    )
+    .await;
+
+    Ok(CatchUpSummary {
+        period,
+        merged,
+        review_requested,
+        stale,
+    })
+}
@@ -0,0 +1,231 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::types::UserHistory;
+
+/// Setting holding how many days back a new user's history is considered
+/// synced initially; older history is left for a backfill. Open issues are
+/// synced regardless of when they were last updated. `0` means no limit.
+///
+pub const SETTING_INITIAL_DAYS: &str = "sync.initial_days";
+
+const DEFAULT_INITIAL_DAYS: i64 = 30;
+
+/// Seconds of history pulled per backfill chunk, at most. Chunks hitting
+/// the search results cap are split, down to `MIN_CHUNK_SECS`.
+///
+pub const CHUNK_SECS: i64 = 7 * 24 * 60 * 60;
+pub const MIN_CHUNK_SECS: i64 = 60 * 60;
+
+/// Obtain the date a new user's initial sync should go back to, if limited.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_initial_since(
+    db: &DB,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let days = config::get_setting_or(
+        &db,
+        SETTING_INITIAL_DAYS,
+        &DEFAULT_INITIAL_DAYS.to_string(),
+    )
+    .await
+    .parse::<i64>()
+    .unwrap_or(DEFAULT_INITIAL_DAYS);
+
+    match days > 0 {
+        true => Some(chrono::Utc::now() - chrono::Duration::days(days)),
+        false => None,
+    }
+}
+
+/// Record that user `userid`'s history has been synced back to `from`, or
+/// entirely if not provided, unless it already went further back. This
+/// function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The user's ID.
+/// * `from` - How far back history has been synced, if limited.
+///
+pub async fn record_synced_from(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    from: &Option<i64>,
+) {
+    sqlx::query(
+        "
+        INSERT INTO user_history (user_id, synced_from) VALUES (?1, ?2)
+        ON CONFLICT (user_id) DO UPDATE SET synced_from = CASE
+            WHEN ?2 IS NULL OR synced_from IS NULL THEN NULL
+            ELSE MIN(synced_from, ?2)
+        END
+        ",
+    )
+    .bind(&userid)
+    .bind(&from)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to record history of user {}: {}", userid, err);
+    });
+}
+
+/// Request user `userid`'s history between `from` and `to` be backfilled,
+/// replacing any backfill in progress.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The user's ID.
+/// * `from` - Timestamp the range starts at.
+/// * `to` - Timestamp the range ends at.
+///
+pub async fn request(
+    db: &DB,
+    userid: &i64,
+    from: &i64,
+    to: &i64,
+) -> Result<(), GHDError> {
+    if *from <= 0 || from >= to {
+        println!("invalid backfill range {}..{}", from, to);
+        return Err(GHDError::BadRequest);
+    }
+
+    sqlx::query(
+        "
+        INSERT INTO user_history (
+            user_id, backfill_from, backfill_to, backfill_cursor
+        ) VALUES (?1, ?2, ?3, ?3)
+        ON CONFLICT (user_id) DO UPDATE SET
+            backfill_from = ?2, backfill_to = ?3, backfill_cursor = ?3,
+            backfill_items = 0, backfill_truncated = 0,
+            backfill_finished_at = NULL
+        ",
+    )
+    .bind(&userid)
+    .bind(&from)
+    .bind(&to)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to request backfill of user {}: {}", userid, err);
+    });
+    Ok(())
+}
+
+/// Record that user `userid`'s backfill has progressed down to `cursor`,
+/// having pulled `items` more issues, finishing it if `cursor` reached the
+/// start of its range. A chunk is `truncated` if it hit the search results
+/// cap, meaning some issues were left out. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The user's ID.
+/// * `cursor` - How far back the backfill now goes.
+/// * `items` - How many issues were pulled.
+/// * `truncated` - Whether more issues matched than were pulled.
+///
+pub async fn advance(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    cursor: &i64,
+    items: i64,
+    truncated: bool,
+) {
+    sqlx::query(
+        "
+        UPDATE user_history SET
+            backfill_cursor = ?2,
+            backfill_items = backfill_items + ?3,
+            backfill_truncated = backfill_truncated + ?5,
+            backfill_finished_at = CASE
+                WHEN ?2 <= backfill_from THEN ?4 ELSE NULL
+            END,
+            synced_from = CASE
+                WHEN ?2 > backfill_from OR synced_from IS NULL
+                    THEN synced_from
+                WHEN backfill_to < synced_from THEN synced_from
+                ELSE MIN(synced_from, backfill_from)
+            END
+        WHERE user_id = ?1
+        ",
+    )
+    .bind(&userid)
+    .bind(&cursor)
+    .bind(&items)
+    .bind(chrono::Utc::now().timestamp())
+    .bind(truncated as i64)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to advance backfill of user {}: {}", userid, err);
+    });
+}
+
+/// Obtain the history of every tracked user.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_history(db: &DB) -> Vec<UserHistory> {
+    match sqlx::query_as::<_, UserHistory>(
+        "
+        SELECT
+            users.id AS user_id, users.login,
+            user_history.synced_from, user_history.backfill_from,
+            user_history.backfill_to, user_history.backfill_cursor,
+            COALESCE(user_history.backfill_items, 0) AS backfill_items,
+            COALESCE(user_history.backfill_truncated, 0)
+                AS backfill_truncated,
+            user_history.backfill_finished_at
+        FROM
+            users LEFT JOIN user_history ON user_history.user_id = users.id
+        ORDER BY users.login
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain user history: {}", err);
+        }
+    }
+}
+
+/// Obtain the history of the users with a backfill in progress.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_pending(db: &DB) -> Vec<UserHistory> {
+    get_history(&db)
+        .await
+        .into_iter()
+        .filter(|h| match (h.backfill_from, h.backfill_cursor) {
+            (Some(from), Some(cursor)) => cursor > from,
+            _ => false,
+        })
+        .collect()
+}
@@ -0,0 +1,320 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::types::{BackportEntry, PullRequest};
+
+/// Setting holding the regular expression matching backport Pull Request
+/// titles. Its first capture group must be the target branch.
+///
+pub const SETTING_TITLE_PATTERN: &str = "backports.title_pattern";
+
+/// Setting holding the regular expression matching backport labels. Its first
+/// capture group must be the target branch.
+///
+pub const SETTING_LABEL_PATTERN: &str = "backports.label_pattern";
+
+/// Setting holding a comma-separated list of maintained branches. May be
+/// suffixed with `.<owner>/<name>` to apply to a single repository.
+///
+pub const SETTING_BRANCHES: &str = "backports.branches";
+
+const DEFAULT_TITLE_PATTERN: &str = r"(?i)^\s*\[backport ([^\]]+)\]";
+const DEFAULT_LABEL_PATTERN: &str = r"(?i)^backport[-/: ]+(.+)$";
+
+/// The conventions used to detect backport Pull Requests.
+///
+pub struct BackportConventions {
+    title: Regex,
+    label: Regex,
+}
+
+/// A detected backport, prior to being resolved against the database.
+///
+struct Backport {
+    branch: String,
+    original_number: Option<i64>,
+    original_title: String,
+}
+
+/// Compile the pattern stored in setting `key`, falling back to `default` if
+/// the setting is not set or is not a valid regular expression.
+///
+async fn load_pattern(db: &DB, key: &str, default: &str) -> Regex {
+    let pattern = config::get_setting_or(&db, &key, &default).await;
+    match Regex::new(&pattern) {
+        Ok(re) => re,
+        Err(err) => {
+            println!("invalid pattern for '{}', using default: {}", key, err);
+            Regex::new(&default).unwrap()
+        }
+    }
+}
+
+impl BackportConventions {
+    /// Load the backport conventions from the settings.
+    ///
+    pub async fn load(db: &DB) -> Self {
+        BackportConventions {
+            title: load_pattern(
+                &db,
+                SETTING_TITLE_PATTERN,
+                DEFAULT_TITLE_PATTERN,
+            )
+            .await,
+            label: load_pattern(
+                &db,
+                SETTING_LABEL_PATTERN,
+                DEFAULT_LABEL_PATTERN,
+            )
+            .await,
+        }
+    }
+
+    /// Check whether the provided Pull Request is a backport, based on its
+    /// title first, and its labels second. The original Pull Request is
+    /// obtained from a `#<number>` reference in the title, if any.
+    ///
+    fn detect(self: &Self, pr: &PullRequest) -> Option<Backport> {
+        let title = &pr.issue.title;
+        let (branch, remainder) = match self.title.captures(title) {
+            Some(caps) => match caps.get(1) {
+                Some(b) => (
+                    b.as_str().trim().to_string(),
+                    title[caps.get(0).unwrap().end()..].trim().to_string(),
+                ),
+                None => return None,
+            },
+            None => {
+                let branch = pr.issue.labels.iter().find_map(|l| {
+                    self.label
+                        .captures(l)
+                        .and_then(|c| c.get(1))
+                        .map(|b| b.as_str().trim().to_string())
+                })?;
+                (branch, title.trim().to_string())
+            }
+        };
+
+        let number_re = Regex::new(r"#(\d+)").unwrap();
+        let original_number = number_re
+            .captures(&remainder)
+            .and_then(|c| c.get(1))
+            .and_then(|n| n.as_str().parse::<i64>().ok());
+        let original_title = number_re
+            .replace_all(&remainder, "")
+            .trim()
+            .trim_end_matches("()")
+            .trim()
+            .to_string();
+
+        Some(Backport {
+            branch,
+            original_number,
+            original_title,
+        })
+    }
+}
+
+/// Detect backports among the provided Pull Requests, linking them to their
+/// original Pull Requests. If the original can't be found by number, it is
+/// looked for by title in the same repository. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `conventions` - The conventions used to detect backports.
+/// * `prs` - The Pull Requests to check.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    conventions: &BackportConventions,
+    prs: &Vec<PullRequest>,
+) {
+    for pr in prs {
+        let bp = match conventions.detect(&pr) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let original_number = match bp.original_number {
+            Some(n) => Some(n),
+            None => sqlx::query_scalar::<_, i64>(
+                "
+                SELECT number FROM issues
+                WHERE
+                    repo_owner = ? AND repo_name = ? AND title = ?
+                    AND is_pull_request = 1 AND id != ?
+                ORDER BY number DESC LIMIT 1
+                ",
+            )
+            .bind(&pr.issue.repo_owner)
+            .bind(&pr.issue.repo_name)
+            .bind(&bp.original_title)
+            .bind(&pr.issue.id)
+            .fetch_optional(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to look up original for backport: {}", err);
+            }),
+        };
+
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO backports (
+                backport_id, repo_owner, repo_name, original_number, branch
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&pr.issue.id)
+        .bind(&pr.issue.repo_owner)
+        .bind(&pr.issue.repo_name)
+        .bind(&original_number)
+        .bind(&bp.branch)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to consume backport {}: {}", pr.issue.id, err);
+        });
+    }
+}
+
+/// Obtain the maintained branches for a repository, from the settings.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_maintained_branches(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Vec<String> {
+    let key = format!("{}.{}/{}", SETTING_BRANCHES, owner, name);
+    let global = config::get_setting_or(&db, SETTING_BRANCHES, "").await;
+    let value = config::get_setting_or(&db, &key, &global).await;
+
+    value
+        .split(',')
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .collect()
+}
+
+/// Obtain the known backports of Pull Request `prid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The original Pull Request's database ID.
+///
+pub async fn get_backports(
+    db: &DB,
+    prid: &i64,
+) -> Result<Vec<BackportEntry>, GHDError> {
+    match sqlx::query_as::<_, BackportEntry>(
+        "
+        SELECT
+            backports.branch,
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at
+        FROM
+            issues AS original
+            INNER JOIN backports
+                ON backports.repo_owner = original.repo_owner
+                AND backports.repo_name = original.repo_name
+                AND backports.original_number = original.number
+            INNER JOIN issues ON issues.id = backports.backport_id
+            INNER JOIN pull_requests ON pull_requests.id = issues.id
+        WHERE original.id = ?
+        ORDER BY backports.branch
+        ",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain backports for pr {}: {}", prid, err);
+        }
+    }
+}
+
+/// Obtain, for each maintained branch of a repository, the merged Pull
+/// Requests that are not themselves backports and have not been backported
+/// to said branch.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_missing_backports(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<BackportEntry>, GHDError> {
+    let branches = get_maintained_branches(&db, &owner, &name).await;
+    if branches.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let values = vec!["(?)"; branches.len()].join(", ");
+    let query = format!(
+        "
+        WITH branches(branch) AS (VALUES {})
+        SELECT
+            branches.branch,
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at
+        FROM
+            issues
+            INNER JOIN pull_requests ON pull_requests.id = issues.id
+            CROSS JOIN branches
+        WHERE
+            issues.repo_owner = ? AND issues.repo_name = ?
+            AND pull_requests.merged_at IS NOT NULL
+            AND issues.id NOT IN (SELECT backport_id FROM backports)
+            AND NOT EXISTS (
+                SELECT 1 FROM backports
+                WHERE
+                    backports.repo_owner = issues.repo_owner
+                    AND backports.repo_name = issues.repo_name
+                    AND backports.original_number = issues.number
+                    AND backports.branch = branches.branch
+            )
+        ORDER BY pull_requests.merged_at DESC, branches.branch
+        ",
+        values
+    );
+
+    let mut q = sqlx::query_as::<_, BackportEntry>(&query);
+    for b in &branches {
+        q = q.bind(b);
+    }
+    match q.bind(&owner).bind(&name).fetch_all(db.pool()).await {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain missing backports: {}", err);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    gql, refresh, rest,
+    types::{PullRequestBlockers, PullRequestTableEntry},
+};
+
+/// Prefix of the per-PR sync job computing blockers.
+///
+pub const REFRESH_PREFIX: &str = "blockers";
+
+/// Obtain the sync refresh key for the blockers of Pull Request `prid`.
+///
+pub fn refresh_key(prid: &i64) -> String {
+    format!("{}:{}", REFRESH_PREFIX, prid)
+}
+
+/// Obtain the required checks and approvals from the repository rules
+/// applying to branch `branch`, if any. Rulesets are not available everywhere
+/// (e.g., older GHE instances), so failing to obtain them is not an error.
+///
+async fn fetch_branch_rules(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+    branch: &String,
+) -> (Vec<String>, i64) {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("blockers");
+    let ep = format!("/repos/{}/{}/rules/branches/{}", owner, name, branch);
+    let req = ghreq.get(&ep);
+    let rules = match ghreq.send::<Vec<rest::GithubBranchRuleReply>>(req).await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            println!(
+                "unable to obtain rules for {}/{}:{}: {:?}",
+                owner, name, branch, err
+            );
+            return (vec![], 0);
+        }
+    };
+
+    let mut contexts: Vec<String> = vec![];
+    let mut approvals = 0;
+    for rule in &rules {
+        match rule.kind.as_str() {
+            "required_status_checks" => {
+                if let Some(checks) =
+                    rule.parameters["required_status_checks"].as_array()
+                {
+                    contexts.extend(checks.iter().filter_map(|c| {
+                        c["context"].as_str().map(String::from)
+                    }));
+                }
+            }
+            "pull_request" => {
+                if let Some(n) =
+                    rule.parameters["required_approving_review_count"].as_i64()
+                {
+                    approvals = approvals.max(n);
+                }
+            }
+            _ => {}
+        };
+    }
+    (contexts, approvals)
+}
+
+/// Compute what is still blocking Pull Request `pr` from being merged, by
+/// comparing its base branch's protection and rulesets against its approvals
+/// and the checks reported for its head commit.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `pr` - The Pull Request.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    pr: &PullRequestTableEntry,
+) -> Result<PullRequestBlockers, GHDError> {
+    let reqs = gql::get_pull_request_merge_requirements(
+        &creds.token,
+        &creds.host,
+        &pr.repo_owner,
+        &pr.repo_name,
+        &pr.number,
+    )
+    .await?;
+    let (rule_contexts, rule_approvals) = fetch_branch_rules(
+        &creds,
+        &pr.repo_owner,
+        &pr.repo_name,
+        &reqs.base_ref,
+    )
+    .await;
+
+    let mut missing_checks: Vec<String> = reqs
+        .required_contexts
+        .into_iter()
+        .chain(rule_contexts)
+        .filter(|c| !reqs.reported_contexts.contains(c))
+        .collect();
+    missing_checks.sort();
+    missing_checks.dedup();
+
+    let required_approvals = reqs.required_approvals.max(rule_approvals);
+
+    Ok(PullRequestBlockers {
+        protection_visible: reqs.protection_visible,
+        required_approvals,
+        approvals: reqs.approvals,
+        approvals_remaining: (required_approvals - reqs.approvals).max(0),
+        missing_checks,
+    })
+}
+
+/// Write the provided blockers for Pull Request `prid` to the database,
+/// replacing any previously known blockers. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `blockers` - The blockers computed for the Pull Request.
+/// * `when` - When the blockers were computed.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    blockers: &PullRequestBlockers,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_blockers (
+            pr_id, protection_visible, required_approvals, approvals
+        ) VALUES (?, ?, ?, ?)
+        ",
+    )
+    .bind(&prid)
+    .bind(&blockers.protection_visible)
+    .bind(&blockers.required_approvals)
+    .bind(&blockers.approvals)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating blockers for pr {}: {}", prid, err);
+    });
+
+    sqlx::query("DELETE FROM pr_missing_checks WHERE pr_id = ?")
+        .bind(&prid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing missing checks for pr {}: {}", prid, err);
+        });
+
+    for context in &blockers.missing_checks {
+        sqlx::query(
+            "INSERT INTO pr_missing_checks (pr_id, context) VALUES (?, ?)",
+        )
+        .bind(&prid)
+        .bind(&context)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting missing check {}: {}", context, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&prid), &when).await;
+}
+
+/// Obtain what is blocking Pull Request `prid` from being merged, or `None`
+/// if its blockers have never been computed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_blockers(
+    db: &DB,
+    prid: &i64,
+) -> Result<Option<PullRequestBlockers>, GHDError> {
+    let (protection_visible, required_approvals, approvals) =
+        match sqlx::query_as::<_, (bool, i64, i64)>(
+            "
+            SELECT protection_visible, required_approvals, approvals
+            FROM pr_blockers WHERE pr_id = ?
+            ",
+        )
+        .bind(&prid)
+        .fetch_optional(db.pool())
+        .await
+        {
+            Ok(Some(res)) => res,
+            Ok(None) => return Ok(None),
+            Err(err) => {
+                panic!("Unable to obtain blockers for pr {}: {}", prid, err);
+            }
+        };
+
+    let missing_checks = match sqlx::query_scalar::<_, String>(
+        "SELECT context FROM pr_missing_checks WHERE pr_id = ? ORDER BY context",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain missing checks for pr {}: {}", prid, err);
+        }
+    };
+
+    Ok(Some(PullRequestBlockers {
+        protection_visible,
+        required_approvals,
+        approvals,
+        approvals_remaining: (required_approvals - approvals).max(0),
+        missing_checks,
+    }))
+}
@@ -0,0 +1,68 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, store::ReadStore};
+
+/// Setting hiding bot-authored issues and Pull Requests from listings,
+/// counts, and notifications when set to `true`.
+///
+pub const SETTING_HIDE_BOTS: &str = "filters.hide_bots";
+
+/// Condition matching the `issues` row being selected if it was authored by
+/// a bot.
+///
+pub const BOT_AUTHORED: &str = "
+    EXISTS (
+        SELECT 1 FROM authors
+        WHERE authors.login = issues.author AND authors.is_bot = 1
+    )
+";
+
+/// Check whether an author is a bot, either because GitHub says so or
+/// because its login carries the `[bot]` suffix used by GitHub Apps.
+///
+/// # Arguments
+///
+/// * `is_bot_type` - Whether the author's type is `Bot`.
+/// * `login` - The author's login.
+///
+pub fn is_bot_author(is_bot_type: bool, login: &String) -> bool {
+    is_bot_type || login.ends_with("[bot]")
+}
+
+/// Check whether bot-authored issues should be hidden.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+///
+pub async fn hide_bots<S: ReadStore>(store: &S) -> bool {
+    config::get_setting_or(store, SETTING_HIDE_BOTS, "false").await == "true"
+}
+
+/// Obtain a condition on the `issues` row being selected that filters out
+/// bot-authored issues if they should be hidden, or matches every row
+/// otherwise.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+///
+pub async fn filter<S: ReadStore>(store: &S) -> String {
+    if hide_bots(store).await {
+        format!("NOT {}", BOT_AUTHORED)
+    } else {
+        String::from("1")
+    }
+}
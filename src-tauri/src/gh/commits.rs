@@ -0,0 +1,149 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    contributors, refresh,
+    types::{
+        PullRequestCommit, PullRequestCommitsUpdate, PullRequestSignatures,
+    },
+};
+
+/// Prefix of the per-PR sync job fetching commits.
+///
+pub const REFRESH_PREFIX: &str = "commits";
+
+/// Obtain the sync refresh key for the commits of Pull Request `prid`.
+///
+pub fn refresh_key(prid: &i64) -> String {
+    format!("{}:{}", REFRESH_PREFIX, prid)
+}
+
+/// Write the provided commits for Pull Request `prid` to the database,
+/// replacing any previously known commits. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `update` - The commits obtained for the Pull Request.
+/// * `when` - When the commits were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    update: &PullRequestCommitsUpdate,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_heads (pr_id, head_sha, requires_signatures)
+        VALUES (?, ?, ?)
+        ",
+    )
+    .bind(&prid)
+    .bind(&update.head_sha)
+    .bind(&update.requires_signatures)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating head for pr {}: {}", prid, err);
+    });
+
+    sqlx::query("DELETE FROM pr_commits WHERE pr_id = ?")
+        .bind(&prid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing commits for pr {}: {}", prid, err);
+        });
+
+    for c in &update.commits {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_commits (
+                pr_id, sha, position, verified, signature_state, message
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&prid)
+        .bind(&c.sha)
+        .bind(&c.position)
+        .bind(&c.verified)
+        .bind(&c.signature_state)
+        .bind(&c.message)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting commit {}: {}", c.sha, err);
+        });
+    }
+
+    contributors::consume_co_authors(tx, &prid, &update.commits).await;
+    refresh::update_sync_refresh(tx, &refresh_key(&prid), &when).await;
+}
+
+/// Obtain the signature verification summary for Pull Request `prid`, or
+/// `None` if its commits have never been fetched.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_signatures(
+    db: &DB,
+    prid: &i64,
+) -> Result<Option<PullRequestSignatures>, GHDError> {
+    let requires_signatures = match sqlx::query_scalar::<_, Option<bool>>(
+        "SELECT requires_signatures FROM pr_heads WHERE pr_id = ?",
+    )
+    .bind(&prid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Ok(None),
+        Err(err) => {
+            panic!("Unable to obtain head for pr {}: {}", prid, err);
+        }
+    };
+
+    let commits = match sqlx::query_as::<_, PullRequestCommit>(
+        "SELECT * FROM pr_commits WHERE pr_id = ? ORDER BY position",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain commits for pr {}: {}", prid, err);
+        }
+    };
+
+    let total_commits = commits.len() as i64;
+    let unverified: Vec<PullRequestCommit> =
+        commits.into_iter().filter(|c| !c.verified).collect();
+
+    Ok(Some(PullRequestSignatures {
+        requires_signatures,
+        total_commits,
+        verified_commits: total_commits - unverified.len() as i64,
+        all_verified: unverified.is_empty(),
+        unverified,
+    }))
+}
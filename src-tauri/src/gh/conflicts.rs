@@ -0,0 +1,119 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::PullRequestConflict;
+
+/// Column computing, for the `issues` row being selected, how many other open
+/// Pull Requests in the same repository touch at least one of the same files,
+/// where at least one of the two has been authored by one of our accounts.
+/// Relies on the cached changed files, so it's only as accurate as those.
+///
+pub const POTENTIAL_CONFLICTS_COLUMN: &str = "
+    (
+        SELECT COUNT(DISTINCT other.pr_id)
+        FROM
+            pr_files AS ours
+            INNER JOIN pr_files AS other
+                ON other.path = ours.path AND other.pr_id != ours.pr_id
+            INNER JOIN issues AS other_issue
+                ON other_issue.id = other.pr_id
+        WHERE
+            ours.pr_id = issues.id
+            AND issues.state = 'open'
+            AND other_issue.state = 'open'
+            AND other_issue.repo_owner = issues.repo_owner
+            AND other_issue.repo_name = issues.repo_name
+            AND (
+                issues.author_id IN (SELECT user_id FROM tokens)
+                OR other_issue.author_id IN (SELECT user_id FROM tokens)
+            )
+    ) AS potential_conflicts
+";
+
+#[derive(sqlx::FromRow)]
+struct ConflictRow {
+    id: i64,
+    number: i64,
+    title: String,
+    author: String,
+    url: String,
+    path: String,
+}
+
+/// Obtain the open Pull Requests potentially conflicting with Pull Request
+/// `prid`, along with the paths both touch.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_conflicts(
+    db: &DB,
+    prid: &i64,
+) -> Result<Vec<PullRequestConflict>, GHDError> {
+    let rows = match sqlx::query_as::<_, ConflictRow>(
+        "
+        SELECT
+            other_issue.id, other_issue.number, other_issue.title,
+            other_issue.author, other_issue.url, ours.path
+        FROM
+            issues
+            INNER JOIN pr_files AS ours ON ours.pr_id = issues.id
+            INNER JOIN pr_files AS other
+                ON other.path = ours.path AND other.pr_id != ours.pr_id
+            INNER JOIN issues AS other_issue
+                ON other_issue.id = other.pr_id
+        WHERE
+            issues.id = ?
+            AND issues.state = 'open'
+            AND other_issue.state = 'open'
+            AND other_issue.repo_owner = issues.repo_owner
+            AND other_issue.repo_name = issues.repo_name
+            AND (
+                issues.author_id IN (SELECT user_id FROM tokens)
+                OR other_issue.author_id IN (SELECT user_id FROM tokens)
+            )
+        ORDER BY other_issue.number, ours.path
+        ",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain conflicts for pr {}: {}", prid, err);
+        }
+    };
+
+    let mut res: Vec<PullRequestConflict> = vec![];
+    for row in rows {
+        match res.last_mut() {
+            Some(last) if last.id == row.id => last.paths.push(row.path),
+            _ => res.push(PullRequestConflict {
+                id: row.id,
+                number: row.number,
+                title: row.title,
+                author: row.author,
+                url: row.url,
+                paths: vec![row.path],
+            }),
+        };
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,324 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::{
+    db::DB,
+    errors::GHDError,
+    page::{Page, PageRequest},
+};
+
+use super::{
+    bots, reconcile,
+    types::{
+        ContributionCredit, PullRequest, PullRequestCommit,
+        PullRequestTableEntry,
+    },
+};
+
+/// Column computing whether the author of the Pull Request in the `issues`
+/// row being selected is contributing to a maintained repository for the
+/// first time. Relies on the author association reported by GitHub, falling
+/// back to checking whether we have seen a merged Pull Request from the same
+/// author in the repository when the association is not known.
+///
+pub const FIRST_TIME_CONTRIBUTOR_COLUMN: &str = "
+    (
+        EXISTS (
+            SELECT 1 FROM repos
+            WHERE
+                repos.owner = issues.repo_owner
+                AND repos.name = issues.repo_name
+        )
+        AND CASE
+            WHEN EXISTS (
+                SELECT 1 FROM pr_author_associations
+                WHERE pr_author_associations.pr_id = issues.id
+            )
+            THEN EXISTS (
+                SELECT 1 FROM pr_author_associations
+                WHERE
+                    pr_author_associations.pr_id = issues.id
+                    AND pr_author_associations.association IN (
+                        'first_timer', 'first_time_contributor'
+                    )
+            )
+            ELSE NOT EXISTS (
+                SELECT 1
+                FROM
+                    issues AS prior
+                    INNER JOIN pull_requests AS prior_pr
+                        ON prior_pr.id = prior.id
+                WHERE
+                    prior.repo_owner = issues.repo_owner
+                    AND prior.repo_name = issues.repo_name
+                    AND prior.author_id = issues.author_id
+                    AND prior.id != issues.id
+                    AND prior_pr.merged_at IS NOT NULL
+            )
+        END
+    ) AS is_first_time_contributor
+";
+
+/// Obtain a page of the open Pull Requests on maintained repositories whose
+/// authors are contributing for the first time, oldest first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `page` - The page to obtain.
+///
+pub async fn get_first_time_prs(
+    db: &DB,
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    let query = format!(
+        "
+        SELECT * FROM (
+            SELECT
+                issues.*, pull_requests.is_draft,
+                pull_requests.review_decision, pull_requests.merged_at, {}
+            FROM
+                pull_requests INNER JOIN issues
+            ON
+                pull_requests.id = issues.id
+            WHERE
+                issues.state = 'open' AND {}
+        )
+        WHERE is_first_time_contributor = 1 AND {}
+        ORDER BY created_at ASC, id ASC
+        LIMIT ?
+        ",
+        FIRST_TIME_CONTRIBUTOR_COLUMN,
+        bots::filter(&db).await,
+        // oldest first, i.e. descending on the negated columns.
+        PageRequest::condition("-created_at", "-id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(page.into_page(res, |pr| (-pr.created_at, -pr.id))),
+        Err(err) => {
+            panic!("Unable to obtain first-time contributions: {}", err);
+        }
+    }
+}
+
+/// Parse the `Co-authored-by: Name <email>` trailers of a commit message
+/// into the name and email of each co-author.
+///
+/// # Arguments
+///
+/// * `message` - The commit's message.
+///
+pub fn parse_co_authors(message: &String) -> Vec<(String, String)> {
+    let re = Regex::new(r"(?im)^\s*co-authored-by:\s*(.+?)\s*<([^>]+)>\s*$")
+        .unwrap();
+    re.captures_iter(&message)
+        .map(|caps| (caps[1].to_string(), caps[2].trim().to_lowercase()))
+        .collect()
+}
+
+/// Obtain the login behind a GitHub `noreply` email address; e.g.,
+/// `123+octocat@users.noreply.github.com`. Other addresses don't say.
+///
+pub fn login_from_email(email: &String) -> Option<String> {
+    let local = email.strip_suffix("@users.noreply.github.com")?;
+    let login = match local.split_once('+') {
+        Some((_, login)) => login,
+        None => local,
+    };
+    match login.is_empty() {
+        true => None,
+        false => Some(login.to_string()),
+    }
+}
+
+/// Write the co-authors credited in Pull Request `prid`'s commits to the
+/// database, replacing those previously known. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `commits` - The Pull Request's commits.
+///
+pub async fn consume_co_authors(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    commits: &Vec<PullRequestCommit>,
+) {
+    sqlx::query("DELETE FROM commit_co_authors WHERE pr_id = ?")
+        .bind(&prid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing co-authors for pr {}: {}", prid, err);
+        });
+
+    for c in commits {
+        for (name, email) in parse_co_authors(&c.message) {
+            sqlx::query(
+                "
+                INSERT OR REPLACE INTO commit_co_authors (
+                    pr_id, sha, name, email, login
+                ) VALUES (?, ?, ?, ?, ?)
+                ",
+            )
+            .bind(&prid)
+            .bind(&c.sha)
+            .bind(&name)
+            .bind(&email)
+            .bind(login_from_email(&email))
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error inserting co-author of {}: {}", c.sha, err);
+            });
+        }
+    }
+}
+
+/// Write the latest review of each reviewer of Pull Request `pr` to the
+/// database, replacing those previously known. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `pr` - The Pull Request.
+///
+pub async fn consume_reviews(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    pr: &PullRequest,
+) {
+    sqlx::query("DELETE FROM pr_reviews WHERE pr_id = ?")
+        .bind(&pr.issue.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing reviews for pr {}: {}", pr.issue.id, err);
+        });
+
+    for r in &pr.latest_reviews {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_reviews (
+                pr_id, user_id, login, state, submitted_at
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&pr.issue.id)
+        .bind(&r.user_id)
+        .bind(&r.login)
+        .bind(&r.state)
+        .bind(&r.submitted_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting review by '{}': {}", r.login, err);
+        });
+    }
+}
+
+/// Obtain contribution credit per contributor on maintained repositories:
+/// Pull Requests authored and merged, merged Pull Requests co-authored
+/// through commit trailers, and Pull Requests reviewed and approved, other
+/// than their own. Co-authors whose login isn't known are credited by name.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `since` - Only credit merges and reviews from this timestamp on.
+///
+pub async fn get_credits(
+    db: &DB,
+    since: &Option<i64>,
+) -> Result<Vec<ContributionCredit>, GHDError> {
+    let since = since.unwrap_or(0);
+    let query = format!(
+        "
+        WITH maintained AS (
+            SELECT issues.id, issues.author, pull_requests.merged_at
+            FROM
+                issues
+                INNER JOIN pull_requests ON pull_requests.id = issues.id
+                INNER JOIN repos
+                    ON repos.owner = issues.repo_owner
+                    AND repos.name = issues.repo_name
+            WHERE {}
+        ),
+        credits AS (
+            SELECT author AS contributor, 'authored' AS kind, id AS pr_id
+            FROM maintained
+            WHERE merged_at >= ?1
+            UNION
+            SELECT
+                COALESCE(commit_co_authors.login, commit_co_authors.name),
+                'co_authored', maintained.id
+            FROM
+                commit_co_authors INNER JOIN maintained
+                    ON maintained.id = commit_co_authors.pr_id
+            WHERE
+                maintained.merged_at >= ?1
+                AND commit_co_authors.login IS NOT maintained.author
+            UNION
+            SELECT pr_reviews.login, 'reviewed', maintained.id
+            FROM pr_reviews INNER JOIN maintained
+                ON maintained.id = pr_reviews.pr_id
+            WHERE
+                pr_reviews.submitted_at >= ?1
+                AND pr_reviews.state != 'pending'
+                AND pr_reviews.login != maintained.author
+            UNION
+            SELECT pr_reviews.login, 'approved', maintained.id
+            FROM pr_reviews INNER JOIN maintained
+                ON maintained.id = pr_reviews.pr_id
+            WHERE
+                pr_reviews.submitted_at >= ?1
+                AND pr_reviews.state = 'approved'
+                AND pr_reviews.login != maintained.author
+        )
+        SELECT
+            contributor,
+            SUM(kind = 'authored') AS authored,
+            SUM(kind = 'co_authored') AS co_authored,
+            SUM(kind = 'reviewed') AS reviewed,
+            SUM(kind = 'approved') AS approved
+        FROM credits
+        GROUP BY contributor
+        ORDER BY authored + co_authored + reviewed DESC, contributor
+        ",
+        reconcile::NOT_DELETED
+    );
+
+    match sqlx::query_as::<_, ContributionCredit>(&query)
+        .bind(&since)
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain contribution credits: {}", err);
+        }
+    }
+}
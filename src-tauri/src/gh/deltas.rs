@@ -0,0 +1,319 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    rest,
+    types::{
+        PullRequestCommit, PullRequestDelta, PullRequestDeltaUpdate,
+        PullRequestFile, PullRequestTableEntry,
+    },
+};
+
+/// Record the head of Pull Request `prid` as the one last viewed, so later
+/// changes can be computed against it. Nothing is recorded if the Pull
+/// Request's commits have never been fetched.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn record_view(db: &DB, prid: &i64) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_viewed_heads (pr_id, head_sha, viewed_at)
+        SELECT pr_id, head_sha, ? FROM pr_heads WHERE pr_id = ?
+        ",
+    )
+    .bind(chrono::Utc::now().timestamp())
+    .bind(&prid)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to record viewed head for pr {}: {}", prid, err);
+    });
+}
+
+/// Obtain the head of Pull Request `prid` as of when it was last viewed, if
+/// known.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_viewed_head(db: &DB, prid: &i64) -> Option<String> {
+    match sqlx::query_scalar::<_, String>(
+        "SELECT head_sha FROM pr_viewed_heads WHERE pr_id = ?",
+    )
+    .bind(&prid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain viewed head for pr {}: {}", prid, err);
+        }
+    }
+}
+
+/// Obtain the commits and files changed between `base` and `head` on Pull
+/// Request `pr`. If `base` is no longer reachable from `head`, or no longer
+/// exists at all, the Pull Request has been force-pushed and only the
+/// commits and files we can still compare are returned.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `pr` - The Pull Request.
+/// * `base` - The head as of when the Pull Request was last viewed.
+/// * `head` - The Pull Request's current head.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    pr: &PullRequestTableEntry,
+    base: &String,
+    head: &String,
+) -> Result<PullRequestDeltaUpdate, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("deltas");
+    let ep = format!(
+        "/repos/{}/{}/compare/{}...{}",
+        pr.repo_owner, pr.repo_name, base, head
+    );
+    let req = ghreq.get(&ep);
+    let cmp = match ghreq.send::<rest::GithubCompareReply>(req).await {
+        Ok(res) => res,
+        Err(reqwest::StatusCode::NOT_FOUND) => {
+            return Ok(PullRequestDeltaUpdate {
+                base_sha: base.clone(),
+                head_sha: head.clone(),
+                force_pushed: true,
+                commits: vec![],
+                files: vec![],
+            });
+        }
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(PullRequestDeltaUpdate {
+        base_sha: base.clone(),
+        head_sha: head.clone(),
+        force_pushed: cmp.status == "diverged" || cmp.status == "behind",
+        commits: cmp.commits.into_iter().map(|c| c.sha).collect(),
+        files: cmp
+            .files
+            .into_iter()
+            .map(|f| PullRequestFile {
+                pr_id: pr.id,
+                path: f.filename,
+                status: f.status,
+                additions: f.additions,
+                deletions: f.deletions,
+            })
+            .collect(),
+    })
+}
+
+/// Write the provided delta for Pull Request `prid` to the database,
+/// replacing any previously computed delta. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `update` - The delta computed for the Pull Request.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    update: &PullRequestDeltaUpdate,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_deltas (
+            pr_id, base_sha, head_sha, force_pushed
+        ) VALUES (?, ?, ?, ?)
+        ",
+    )
+    .bind(&prid)
+    .bind(&update.base_sha)
+    .bind(&update.head_sha)
+    .bind(&update.force_pushed)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating delta for pr {}: {}", prid, err);
+    });
+
+    for q in [
+        "DELETE FROM pr_delta_commits WHERE pr_id = ?",
+        "DELETE FROM pr_delta_files WHERE pr_id = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&prid)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error clearing delta for pr {}: {}", prid, err);
+            });
+    }
+
+    for sha in &update.commits {
+        sqlx::query(
+            "INSERT OR IGNORE INTO pr_delta_commits (pr_id, sha) VALUES (?, ?)",
+        )
+        .bind(&prid)
+        .bind(&sha)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting delta commit {}: {}", sha, err);
+        });
+    }
+
+    for f in &update.files {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_delta_files (
+                pr_id, path, status, additions, deletions
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&prid)
+        .bind(&f.path)
+        .bind(&f.status)
+        .bind(&f.additions)
+        .bind(&f.deletions)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting delta file for pr {}: {}", prid, err);
+        });
+    }
+}
+
+/// Obtain what changed on Pull Request `prid` since it was last viewed.
+/// Returns `None` if it has never been viewed with a known head, or if the
+/// delta for its current head has not been computed yet.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_delta(
+    db: &DB,
+    prid: &i64,
+) -> Result<Option<PullRequestDelta>, GHDError> {
+    let row = match sqlx::query_as::<_, (String, i64, String)>(
+        "
+        SELECT
+            pr_viewed_heads.head_sha, pr_viewed_heads.viewed_at,
+            pr_heads.head_sha
+        FROM
+            pr_viewed_heads INNER JOIN pr_heads
+                ON pr_heads.pr_id = pr_viewed_heads.pr_id
+        WHERE pr_viewed_heads.pr_id = ?
+        ",
+    )
+    .bind(&prid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Ok(None),
+        Err(err) => {
+            panic!("Unable to obtain heads for pr {}: {}", prid, err);
+        }
+    };
+    let (viewed_sha, viewed_at, head_sha) = row;
+
+    if viewed_sha == head_sha {
+        return Ok(Some(PullRequestDelta {
+            viewed_sha,
+            viewed_at,
+            head_sha,
+            force_pushed: false,
+            new_commits: vec![],
+            changed_files: vec![],
+        }));
+    }
+
+    let force_pushed = match sqlx::query_scalar::<_, bool>(
+        "
+        SELECT force_pushed FROM pr_deltas
+        WHERE pr_id = ? AND base_sha = ? AND head_sha = ?
+        ",
+    )
+    .bind(&prid)
+    .bind(&viewed_sha)
+    .bind(&head_sha)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Ok(None),
+        Err(err) => {
+            panic!("Unable to obtain delta for pr {}: {}", prid, err);
+        }
+    };
+
+    let new_commits = match sqlx::query_as::<_, PullRequestCommit>(
+        "
+        SELECT pr_commits.*
+        FROM
+            pr_delta_commits INNER JOIN pr_commits
+                ON pr_commits.pr_id = pr_delta_commits.pr_id
+                AND pr_commits.sha = pr_delta_commits.sha
+        WHERE pr_delta_commits.pr_id = ?
+        ORDER BY pr_commits.position
+        ",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain delta commits for pr {}: {}", prid, err);
+        }
+    };
+
+    let changed_files = match sqlx::query_as::<_, PullRequestFile>(
+        "SELECT * FROM pr_delta_files WHERE pr_id = ? ORDER BY path",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain delta files for pr {}: {}", prid, err);
+        }
+    };
+
+    Ok(Some(PullRequestDelta {
+        viewed_sha,
+        viewed_at,
+        head_sha,
+        force_pushed,
+        new_commits,
+        changed_files,
+    }))
+}
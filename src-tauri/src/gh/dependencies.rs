@@ -0,0 +1,301 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, VecDeque};
+
+use regex::Regex;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::{DependencyEdge, DependencyGraph, DependencyNode, Issue};
+
+/// Setting holding the newline-separated regular expressions matching
+/// dependency references in issue and Pull Request bodies. Each must
+/// capture the referenced number as `number`, and may capture the
+/// referenced repository, as `owner/name`, as `repo`.
+///
+pub const SETTING_PATTERNS: &str = "dependencies.patterns";
+
+const DEFAULT_PATTERNS: &str = r"(?i)\b(?:blocked\s+by|depends\s+on)\s+(?:(?P<repo>[\w.-]+/[\w.-]+))?#(?P<number>\d+)";
+
+/// How many dependencies deep a graph goes, so long chains and cycles
+/// don't run away.
+///
+const MAX_DEPTH: usize = 10;
+
+/// A reference to an issue or Pull Request, by repository and number.
+///
+type Reference = (String, String, i64);
+
+/// Load the dependency patterns from the settings, skipping those that
+/// aren't valid regular expressions or don't capture a `number`. This
+/// function requires a transaction.
+///
+async fn load_patterns(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> Vec<Regex> {
+    let patterns = match sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?",
+    )
+    .bind(SETTING_PATTERNS)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(res) => res.unwrap_or(String::from(DEFAULT_PATTERNS)),
+        Err(err) => {
+            panic!("Unable to obtain dependency patterns: {}", err);
+        }
+    };
+
+    patterns
+        .lines()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .filter_map(|p| match Regex::new(&p) {
+            Ok(re) if re.capture_names().any(|n| n == Some("number")) => {
+                Some(re)
+            }
+            Ok(_) => {
+                println!("ignoring dependency pattern without number: {}", p);
+                None
+            }
+            Err(err) => {
+                println!(
+                    "ignoring invalid dependency pattern '{}': {}",
+                    p, err
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Obtain the references to other items in `issue`'s body. References
+/// without a repository are to `issue`'s own.
+///
+fn parse(patterns: &Vec<Regex>, issue: &Issue) -> Vec<Reference> {
+    let mut res: Vec<Reference> = vec![];
+    for re in patterns {
+        for caps in re.captures_iter(&issue.body) {
+            // the group may be optional, and so not part of the match.
+            let number = match caps.name("number").map(|n| n.as_str().parse()) {
+                Some(Ok(n)) => n,
+                _ => continue,
+            };
+            let (owner, name) = match caps
+                .name("repo")
+                .and_then(|r| r.as_str().split_once('/'))
+            {
+                Some((o, n)) => (o.to_string(), n.to_string()),
+                None => (issue.repo_owner.clone(), issue.repo_name.clone()),
+            };
+            let reference = (owner, name, number);
+            if !res.contains(&reference) {
+                res.push(reference);
+            }
+        }
+    }
+    res
+}
+
+/// Write the dependencies referenced in the bodies of the provided issues
+/// to the database, replacing those previously known. This function
+/// requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issues` - The newly synced issues and Pull Requests.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issues: &Vec<&Issue>,
+) {
+    let patterns = load_patterns(tx).await;
+
+    for issue in issues {
+        sqlx::query("DELETE FROM issue_dependencies WHERE issue_id = ?")
+            .bind(&issue.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to clear dependencies of {}: {}", issue.id, err);
+            });
+
+        for (owner, name, number) in parse(&patterns, &issue) {
+            sqlx::query(
+                "
+                INSERT OR IGNORE INTO issue_dependencies (
+                    issue_id, repo_owner, repo_name, number
+                ) VALUES (?, ?, ?, ?)
+                ",
+            )
+            .bind(&issue.id)
+            .bind(&owner)
+            .bind(&name)
+            .bind(&number)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to record dependency of {}: {}", issue.id, err);
+            });
+        }
+    }
+}
+
+/// Obtain the node for `reference`, with what we know about it if cached.
+///
+async fn get_node(db: &DB, reference: &Reference) -> DependencyNode {
+    let (owner, name, number) = reference;
+    match sqlx::query_as::<_, DependencyNode>(
+        "
+        SELECT repo_owner, repo_name, number, id, title, state, url
+        FROM issues
+        WHERE repo_owner = ? AND repo_name = ? AND number = ?
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(&number)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => DependencyNode {
+            repo_owner: owner.clone(),
+            repo_name: name.clone(),
+            number: *number,
+            id: None,
+            title: None,
+            state: None,
+            url: None,
+        },
+        Err(err) => {
+            panic!("Unable to obtain {}/{}#{}: {}", owner, name, number, err);
+        }
+    }
+}
+
+/// Obtain the dependency graph of issue `issueid`: everything it depends
+/// on, directly or not, and what directly depends on it.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn get_graph(
+    db: &DB,
+    issueid: &i64,
+) -> Result<DependencyGraph, GHDError> {
+    let root = match sqlx::query_as::<_, (String, String, i64)>(
+        "SELECT repo_owner, repo_name, number FROM issues WHERE id = ?",
+    )
+    .bind(&issueid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain issue {}: {}", issueid, err);
+        }
+    };
+
+    let mut nodes: Vec<DependencyNode> = vec![];
+    let mut index: HashMap<Reference, usize> = HashMap::new();
+    let mut edges: Vec<DependencyEdge> = vec![];
+
+    nodes.push(get_node(&db, &root).await);
+    index.insert(root.clone(), 0);
+
+    // walk what the root depends on, breadth first.
+    let mut queue: VecDeque<(usize, usize)> = VecDeque::from([(0, 0)]);
+    while let Some((from, depth)) = queue.pop_front() {
+        let id = match nodes[from].id {
+            Some(id) if depth < MAX_DEPTH => id,
+            _ => continue,
+        };
+        let deps = match sqlx::query_as::<_, (String, String, i64)>(
+            "
+            SELECT repo_owner, repo_name, number FROM issue_dependencies
+            WHERE issue_id = ?
+            ORDER BY repo_owner, repo_name, number
+            ",
+        )
+        .bind(&id)
+        .fetch_all(db.pool())
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Unable to obtain dependencies of {}: {}", id, err);
+            }
+        };
+
+        for dep in deps {
+            let to = match index.get(&dep) {
+                Some(to) => *to,
+                None => {
+                    nodes.push(get_node(&db, &dep).await);
+                    index.insert(dep, nodes.len() - 1);
+                    queue.push_back((nodes.len() - 1, depth + 1));
+                    nodes.len() - 1
+                }
+            };
+            edges.push(DependencyEdge { from, to });
+        }
+    }
+
+    let (owner, name, number) = &root;
+    let dependents = match sqlx::query_as::<_, (String, String, i64)>(
+        "
+        SELECT issues.repo_owner, issues.repo_name, issues.number
+        FROM issue_dependencies
+        JOIN issues ON issues.id = issue_dependencies.issue_id
+        WHERE
+            issue_dependencies.repo_owner = ?
+            AND issue_dependencies.repo_name = ?
+            AND issue_dependencies.number = ?
+        ORDER BY issues.repo_owner, issues.repo_name, issues.number
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(&number)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain dependents of {}: {}", issueid, err);
+        }
+    };
+
+    for dep in dependents {
+        let from = match index.get(&dep) {
+            Some(from) => *from,
+            None => {
+                nodes.push(get_node(&db, &dep).await);
+                index.insert(dep, nodes.len() - 1);
+                nodes.len() - 1
+            }
+        };
+        if !edges.iter().any(|e| e.from == from && e.to == 0) {
+            edges.push(DependencyEdge { from, to: 0 });
+        }
+    }
+
+    Ok(DependencyGraph { nodes, edges })
+}
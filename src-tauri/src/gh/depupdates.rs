@@ -0,0 +1,380 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    gql, prs, reconcile, refresh,
+    types::{DependencyUpdate, DependencyUpdateGroup, PullRequest},
+};
+
+/// Dependency update Pull Requests are checked once an hour.
+///
+pub const DEPENDENCY_REFRESH_INTERVAL: i64 = 60 * 60;
+
+/// Logins of the bots opening dependency update Pull Requests, without the
+/// `[bot]` suffix GitHub Apps carry on some APIs.
+///
+const BOT_LOGINS: [&str; 4] = [
+    "dependabot",
+    "dependabot-preview",
+    "renovate",
+    "renovate-bot",
+];
+
+pub const BUMP_MAJOR: &str = "major";
+pub const BUMP_MINOR: &str = "minor";
+pub const BUMP_PATCH: &str = "patch";
+pub const BUMP_UNKNOWN: &str = "unknown";
+
+/// Obtain the sync refresh key for the dependency updates of repository
+/// `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("dependency_prs:{}/{}", owner, name)
+}
+
+/// Check whether the dependency updates of repository `owner/name` are due
+/// for a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        DEPENDENCY_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Check whether `login` belongs to a dependency update bot.
+///
+pub fn is_update_bot(login: &String) -> bool {
+    let login = login.strip_suffix("[bot]").unwrap_or(login);
+    BOT_LOGINS.contains(&login)
+}
+
+/// Parse a dependency update Pull Request's title into the dependency being
+/// updated, and the versions it's updated from, if known, and to. Handles
+/// Dependabot's `Bump foo from 1.0.0 to 1.0.1` and Renovate's
+/// `Update dependency foo to v1.0.1`, with or without a conventional commit
+/// prefix. Grouped updates don't name a dependency, and yield `None`.
+///
+/// # Arguments
+///
+/// * `title` - The Pull Request's title.
+///
+pub fn parse_title(title: &str) -> Option<(String, Option<String>, String)> {
+    let prefix = r"(?i)^(?:[\w()!/-]+:\s*)?";
+    let dependabot = Regex::new(&format!(
+        r"{}bump (?P<dep>\S+) from (?P<from>\S+) to (?P<to>\S+)(?: in \S+)?$",
+        prefix
+    ))
+    .unwrap();
+    let renovate = Regex::new(&format!(
+        r"{}{}{}",
+        prefix,
+        r"update (?:(?:dependency|module|rust crate|docker tag|\S+ package)\s+)?",
+        r"(?P<dep>\S+)(?:\s+(?:action|digest|image))? to (?P<to>\S+)(?:\s+\(.*\))?$",
+    ))
+    .unwrap();
+
+    if let Some(caps) = dependabot.captures(title.trim()) {
+        return Some((
+            caps["dep"].to_string(),
+            Some(caps["from"].to_string()),
+            caps["to"].to_string(),
+        ));
+    }
+    renovate
+        .captures(title.trim())
+        .map(|caps| (caps["dep"].to_string(), None, caps["to"].to_string()))
+}
+
+/// Obtain the version a Renovate Pull Request updates from, out of the table
+/// of updates in its body; e.g., `` `1.0.0` -> `1.0.1` ``. Renovate's titles
+/// only carry the version updated to. Only a row updating to `to` is taken
+/// into account.
+///
+/// # Arguments
+///
+/// * `body` - The Pull Request's body.
+/// * `to` - The version being updated to, as per the title.
+///
+pub fn parse_body_from(body: &str, to: &str) -> Option<String> {
+    let re = Regex::new(r"`([^`\s]+)`\s*(?:->|→)\s*`([^`\s]+)`").unwrap();
+    let to = version_parts(&to);
+    let from = re
+        .captures_iter(&body)
+        .find(|caps| {
+            let row_to = version_parts(&caps[2]);
+            !to.is_empty() && row_to.starts_with(&to)
+        })
+        .map(|caps| caps[1].to_string());
+    from
+}
+
+/// Split a version into its numeric components; e.g., `v1.2.3-rc1` into
+/// `[1, 2, 3]`. Components past the first that isn't a number are dropped.
+///
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches(|c| c == 'v' || c == '^' || c == '~')
+        .split(|c| c == '.' || c == '-' || c == '+')
+        .map_while(|p| p.parse::<u64>().ok())
+        .collect()
+}
+
+/// Classify the bump from version `from` to version `to` as `major`,
+/// `minor`, or `patch`, as per the first component to differ. Returns
+/// `unknown` if either version can't be made sense of.
+///
+/// # Arguments
+///
+/// * `from` - The version being updated from, if known.
+/// * `to` - The version being updated to.
+///
+pub fn classify_bump(from: &Option<String>, to: &String) -> &'static str {
+    let from = match from {
+        Some(v) => version_parts(&v),
+        None => return BUMP_UNKNOWN,
+    };
+    let to = version_parts(&to);
+    if from.is_empty() || to.is_empty() {
+        return BUMP_UNKNOWN;
+    }
+
+    let at = |v: &Vec<u64>, i: usize| v.get(i).copied().unwrap_or(0);
+    if at(&from, 0) != at(&to, 0) {
+        BUMP_MAJOR
+    } else if at(&from, 1) != at(&to, 1) {
+        BUMP_MINOR
+    } else {
+        BUMP_PATCH
+    }
+}
+
+/// Rank bumps by how risky they are, unknown bumps being the riskiest.
+///
+pub fn bump_rank(bump: &str) -> i64 {
+    match bump {
+        BUMP_PATCH => 0,
+        BUMP_MINOR => 1,
+        BUMP_MAJOR => 2,
+        _ => 3,
+    }
+}
+
+/// Obtain the open dependency update Pull Requests of repository
+/// `owner/name`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<PullRequest>, GHDError> {
+    let update = gql::get_repo_dependency_pulls(
+        &creds.token,
+        &creds.host,
+        &owner,
+        &name,
+    )
+    .await?;
+    Ok(update.prs)
+}
+
+/// Write the open dependency update Pull Requests of repository
+/// `owner/name`, replacing those previously known. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `pulls` - The repository's open dependency update Pull Requests.
+/// * `when` - When the Pull Requests were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+    pulls: &Vec<PullRequest>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    let ids = prs::consume_pulls(tx, &pulls).await;
+
+    sqlx::query(
+        "DELETE FROM dependency_prs WHERE repo_owner = ? AND repo_name = ?",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error clearing '{}/{}' updates: {}", owner, name, err);
+    });
+    for id in &ids {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO dependency_prs (repo_owner, repo_name, pr_id)
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error marking pr {} as dependency update: {}", id, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+#[derive(sqlx::FromRow)]
+struct UpdateRow {
+    id: i64,
+    repo_owner: String,
+    repo_name: String,
+    number: i64,
+    title: String,
+    author: String,
+    url: String,
+    body: String,
+    review_decision: String,
+}
+
+/// Obtain the open dependency update Pull Requests across maintained
+/// repositories. Those whose title can't be parsed are left out.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_updates(db: &DB) -> Vec<DependencyUpdate> {
+    let query = format!(
+        "
+        SELECT
+            issues.id, issues.repo_owner, issues.repo_name, issues.number,
+            issues.title, issues.author, issues.url, issues.body,
+            pull_requests.review_decision
+        FROM
+            dependency_prs
+            INNER JOIN repos
+                ON repos.owner = dependency_prs.repo_owner
+                AND repos.name = dependency_prs.repo_name
+            INNER JOIN issues ON issues.id = dependency_prs.pr_id
+            INNER JOIN pull_requests ON pull_requests.id = issues.id
+        WHERE issues.state = 'open' AND {}
+        ORDER BY issues.repo_owner, issues.repo_name, issues.number
+        ",
+        reconcile::NOT_DELETED
+    );
+    let rows = match sqlx::query_as::<_, UpdateRow>(&query)
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain dependency updates: {}", err);
+        }
+    };
+
+    rows.into_iter()
+        .filter(|r| is_update_bot(&r.author))
+        .filter_map(|r| {
+            let (dependency, from_version, to_version) = parse_title(&r.title)?;
+            let from_version =
+                from_version.or_else(|| parse_body_from(&r.body, &to_version));
+            let bump = classify_bump(&from_version, &to_version).to_string();
+            Some(DependencyUpdate {
+                pr_id: r.id,
+                repo_owner: r.repo_owner,
+                repo_name: r.repo_name,
+                number: r.number,
+                title: r.title,
+                author: r.author,
+                url: r.url,
+                review_decision: r.review_decision,
+                dependency,
+                from_version,
+                to_version,
+                bump,
+            })
+        })
+        .collect()
+}
+
+/// Obtain the open dependency update Pull Requests across maintained
+/// repositories, grouped by dependency and the version it's updated to, so
+/// the same update can be dealt with everywhere at once. Groups with the
+/// most Pull Requests go first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_groups(db: &DB) -> Vec<DependencyUpdateGroup> {
+    let mut groups: Vec<DependencyUpdateGroup> = vec![];
+    for update in get_updates(&db).await {
+        let key = (
+            update.dependency.to_lowercase(),
+            update.to_version.trim_start_matches('v').to_string(),
+        );
+        match groups.iter_mut().find(|g| {
+            (
+                g.dependency.to_lowercase(),
+                g.to_version.trim_start_matches('v').to_string(),
+            ) == key
+        }) {
+            Some(group) => {
+                if bump_rank(&update.bump) > bump_rank(&group.bump) {
+                    group.bump = update.bump.clone();
+                }
+                group.updates.push(update);
+            }
+            None => groups.push(DependencyUpdateGroup {
+                dependency: update.dependency.clone(),
+                to_version: update.to_version.clone(),
+                bump: update.bump.clone(),
+                updates: vec![update],
+            }),
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.updates
+            .len()
+            .cmp(&a.updates.len())
+            .then_with(|| a.dependency.cmp(&b.dependency))
+    });
+    groups
+}
@@ -0,0 +1,228 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::db::DB;
+
+use super::{
+    accounts::AccountCredentials,
+    rest::{self, GithubRequest},
+    types::{AuthProbe, AuthReport},
+};
+
+/// Scopes GHD relies on. `repo` is required to see private repositories'
+/// issues, `read:org` to obtain organization and team memberships.
+///
+const REQUIRED_SCOPES: [&str; 2] = ["repo", "read:org"];
+
+#[derive(serde::Deserialize)]
+struct RateLimitResource {
+    remaining: i64,
+    reset: i64,
+}
+
+#[derive(serde::Deserialize)]
+struct RateLimitResources {
+    core: RateLimitResource,
+}
+
+#[derive(serde::Deserialize)]
+struct RateLimitReply {
+    resources: RateLimitResources,
+}
+
+/// Obtain a header's value as a String, if present.
+///
+fn header_str(res: &reqwest::Response, name: &str) -> Option<String> {
+    res.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// Record a probe that failed because the host could not be reached.
+///
+fn unreachable(name: &str, err: &reqwest::Error) -> AuthProbe {
+    AuthProbe {
+        name: name.to_string(),
+        ok: false,
+        status: None,
+        detail: format!("unable to reach host: {}", err),
+    }
+}
+
+/// Run a battery of probes against the API using the provided credentials,
+/// returning a report of what works and what's missing. Probes never fail;
+/// their outcome is reflected in the report instead.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle, used to find a repository to probe.
+/// * `creds` - The credentials of the account being diagnosed.
+///
+pub async fn diagnose(db: &DB, creds: &AccountCredentials) -> AuthReport {
+    let ghreq = GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("diagnose");
+    let mut report = AuthReport {
+        host: creds.host.clone(),
+        login: None,
+        scopes: None,
+        missing_scopes: vec![],
+        rate_limit_remaining: None,
+        rate_limit_reset: None,
+        probes: vec![],
+    };
+
+    // who are we, and what scopes do we have? Fine-grained tokens don't
+    // report scopes, in which case we can't tell what's missing. If we can't
+    // reach the host there's no point in probing any further.
+    let res = match ghreq.execute(ghreq.get("/user")).await {
+        Ok(res) => res,
+        Err(err) => {
+            report.probes.push(unreachable("user", &err));
+            return report;
+        }
+    };
+    let status = res.status();
+    let scopes = header_str(&res, "x-oauth-scopes").map(|v| {
+        v.split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<String>>()
+    });
+    if status.is_success() {
+        if let Ok(user) = res.json::<rest::GithubUserReply>().await {
+            report.login = Some(user.login);
+        }
+    }
+    report.probes.push(AuthProbe {
+        name: String::from("user"),
+        ok: status.is_success(),
+        status: Some(status.as_u16()),
+        detail: match &report.login {
+            Some(login) => format!("authenticated as '{}'", login),
+            None => String::from("token was not accepted"),
+        },
+    });
+    if let Some(scopes) = &scopes {
+        for required in REQUIRED_SCOPES {
+            // 'write:org' and 'admin:org' imply 'read:org'.
+            let satisfied = scopes.iter().any(|s| {
+                s == required
+                    || (required == "read:org"
+                        && (s == "write:org" || s == "admin:org"))
+            });
+            if !satisfied {
+                report.missing_scopes.push(required.to_string());
+            }
+        }
+    }
+    report.scopes = scopes;
+
+    // how much of the rate limit is left?
+    match ghreq.execute(ghreq.get("/rate_limit")).await {
+        Ok(res) => {
+            let status = res.status();
+            let mut detail = String::from("unable to obtain rate limit");
+            if status.is_success() {
+                if let Ok(rl) = res.json::<RateLimitReply>().await {
+                    let core = rl.resources.core;
+                    report.rate_limit_remaining = Some(core.remaining);
+                    report.rate_limit_reset = Some(core.reset);
+                    detail = format!("{} requests remaining", core.remaining);
+                }
+            }
+            report.probes.push(AuthProbe {
+                name: String::from("rate_limit"),
+                ok: report.rate_limit_remaining.map_or(false, |v| v > 0),
+                status: Some(status.as_u16()),
+                detail,
+            });
+        }
+        Err(err) => report.probes.push(unreachable("rate_limit", &err)),
+    };
+
+    // a repository we know the user should see, given we have issues from it.
+    let repo = match sqlx::query_as::<_, (String, String)>(
+        "
+        SELECT repo_owner, repo_name FROM issues
+        ORDER BY updated_at DESC LIMIT 1
+        ",
+    )
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain a repository to probe: {}", err);
+        }
+    };
+    match &repo {
+        Some((owner, name)) => {
+            let ep = format!("/repos/{}/{}", owner, name);
+            match ghreq.execute(ghreq.get(&ep)).await {
+                Ok(res) => {
+                    let status = res.status();
+                    let verb = match status.is_success() {
+                        true => "able",
+                        false => "unable",
+                    };
+                    report.probes.push(AuthProbe {
+                        name: String::from("repository"),
+                        ok: status.is_success(),
+                        status: Some(status.as_u16()),
+                        detail: format!(
+                            "{} to access '{}/{}'",
+                            verb, owner, name
+                        ),
+                    });
+                }
+                Err(err) => report.probes.push(unreachable("repository", &err)),
+            };
+        }
+        None => {
+            report.probes.push(AuthProbe {
+                name: String::from("repository"),
+                ok: true,
+                status: None,
+                detail: String::from("no known repositories to probe"),
+            });
+        }
+    };
+
+    // organizations requiring SAML SSO the token hasn't been authorized for
+    // are filtered out of listings, and flagged in the 'x-github-sso' header.
+    match ghreq.execute(ghreq.get("/user/orgs")).await {
+        Ok(res) => {
+            let status = res.status();
+            let sso = header_str(&res, "x-github-sso");
+            report.probes.push(AuthProbe {
+                name: String::from("sso"),
+                ok: status.is_success() && sso.is_none(),
+                status: Some(status.as_u16()),
+                detail: match &sso {
+                    Some(v) => {
+                        format!("token requires SSO authorization: {}", v)
+                    }
+                    None if status.is_success() => String::from(
+                        "no organizations require SSO authorization",
+                    ),
+                    None => String::from("unable to list organizations"),
+                },
+            });
+        }
+        Err(err) => report.probes.push(unreachable("sso", &err)),
+    };
+
+    report
+}
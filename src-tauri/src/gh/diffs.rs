@@ -0,0 +1,210 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use regex::Regex;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    patches,
+    types::{DiffHunk, DiffLine, FileDiff},
+};
+
+pub const LINE_CONTEXT: &str = "context";
+pub const LINE_ADDITION: &str = "addition";
+pub const LINE_DELETION: &str = "deletion";
+
+/// How many Pull Requests' parsed diffs are kept in memory at most. The
+/// cache is dropped as a whole once exceeded.
+///
+const MAX_CACHED_PRS: usize = 32;
+
+/// A Pull Request's parsed diff, by file, along with when its patch was
+/// downloaded.
+///
+struct ParsedDiff {
+    fetched_at: i64,
+    files: HashMap<String, FileDiff>,
+}
+
+/// Parsed diffs, by Pull Request ID. Kept in memory, as they're cheap to
+/// recompute from the cached patches.
+///
+static PARSED: Mutex<Option<HashMap<i64, ParsedDiff>>> = Mutex::new(None);
+
+/// Parse the hunks of every file in a diff, as returned by the API in
+/// either `diff` or `patch` format. For the latter, the hunks of every
+/// commit touching a file are kept, in order. Files are keyed by their new
+/// path, which deleted files keep.
+///
+/// # Arguments
+///
+/// * `prid` - The Pull Request's ID.
+/// * `text` - The diff to parse.
+///
+pub fn parse(prid: &i64, text: &str) -> HashMap<String, FileDiff> {
+    let hunk_re =
+        Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@ ?(.*)$")
+            .unwrap();
+    let git_re = Regex::new(r"^diff --git a/(.+) b/(.+)$").unwrap();
+
+    let mut files: HashMap<String, FileDiff> = HashMap::new();
+    let mut path: Option<String> = None;
+    let mut in_header = false;
+    let (mut old_no, mut new_no) = (0, 0);
+    let (mut old_left, mut new_left) = (0, 0);
+
+    for line in text.lines() {
+        if let Some(caps) = git_re.captures(line) {
+            let p = caps[2].to_string();
+            let old_path = match &caps[1] != p {
+                true => Some(caps[1].to_string()),
+                false => None,
+            };
+            files.entry(p.clone()).or_insert_with(|| FileDiff {
+                pr_id: *prid,
+                path: p.clone(),
+                old_path,
+                is_binary: false,
+                hunks: vec![],
+            });
+            path = Some(p);
+            in_header = true;
+            continue;
+        }
+        let file = match &path {
+            Some(p) => files.get_mut(p).unwrap(),
+            None => continue,
+        };
+
+        if in_header && line.starts_with("Binary files ") {
+            file.is_binary = true;
+        }
+
+        if let Some(caps) = hunk_re.captures(line) {
+            in_header = false;
+            let num = |i: usize| -> i64 {
+                caps.get(i)
+                    .map_or(1, |m| m.as_str().parse::<i64>().unwrap_or(1))
+            };
+            old_no = num(1);
+            new_no = num(3);
+            old_left = num(2);
+            new_left = num(4);
+            file.hunks.push(DiffHunk {
+                old_start: old_no,
+                old_lines: num(2),
+                new_start: new_no,
+                new_lines: num(4),
+                header: caps[5].to_string(),
+                lines: vec![],
+            });
+            continue;
+        }
+        // anything past the hunk's lines, e.g. the signature ending a
+        // commit in `patch` format, isn't part of it.
+        if in_header || (old_left <= 0 && new_left <= 0) {
+            continue;
+        }
+        let hunk = match file.hunks.last_mut() {
+            Some(h) => h,
+            None => continue,
+        };
+
+        let (kind, content) = match line.chars().next() {
+            Some('+') => (LINE_ADDITION, &line[1..]),
+            Some('-') => (LINE_DELETION, &line[1..]),
+            Some(' ') => (LINE_CONTEXT, &line[1..]),
+            None => (LINE_CONTEXT, ""),
+            // e.g. "\ No newline at end of file".
+            _ => continue,
+        };
+        let (old_line, new_line) = match kind {
+            LINE_ADDITION => (None, Some(new_no)),
+            LINE_DELETION => (Some(old_no), None),
+            _ => (Some(old_no), Some(new_no)),
+        };
+        if old_line.is_some() {
+            old_no += 1;
+            old_left -= 1;
+        }
+        if new_line.is_some() {
+            new_no += 1;
+            new_left -= 1;
+        }
+        hunk.lines.push(DiffLine {
+            kind: kind.to_string(),
+            content: content.to_string(),
+            old_line,
+            new_line,
+        });
+    }
+
+    files
+}
+
+/// Obtain the parsed diff of `path` in Pull Request `prid`, from its cached
+/// patch. Parsed diffs are cached until the patch is downloaded again.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `dir` - The directory patches are stored in.
+/// * `prid` - The Pull Request's ID.
+/// * `path` - The file's path.
+///
+pub async fn get_file_diff(
+    db: &DB,
+    dir: &PathBuf,
+    prid: &i64,
+    path: &String,
+) -> Result<FileDiff, GHDError> {
+    let fetched_at = patches::get_fetched_at(&db, &prid).await?;
+
+    {
+        let parsed = PARSED.lock().unwrap();
+        if let Some(diff) = parsed.as_ref().and_then(|p| p.get(&prid)) {
+            if diff.fetched_at == fetched_at {
+                return match diff.files.get(path) {
+                    Some(res) => Ok(res.clone()),
+                    None => Err(GHDError::NotFoundError),
+                };
+            }
+        }
+    }
+
+    let patch = patches::get_patch(&db, &dir, &prid).await?;
+    let files = parse(&prid, &patch.text);
+    let res = match files.get(path) {
+        Some(res) => Ok(res.clone()),
+        None => Err(GHDError::NotFoundError),
+    };
+
+    let mut parsed = PARSED.lock().unwrap();
+    let cache = parsed.get_or_insert_with(HashMap::new);
+    if cache.len() >= MAX_CACHED_PRS && !cache.contains_key(&prid) {
+        cache.clear();
+    }
+    cache.insert(
+        *prid,
+        ParsedDiff {
+            fetched_at: patch.fetched_at,
+            files,
+        },
+    );
+
+    res
+}
@@ -0,0 +1,384 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError, vault};
+
+use super::{
+    outbox::{PullRequestAction, ReviewComment},
+    types::{DraftReview, DraftReviewComment},
+};
+
+/// Sides of a diff an inline comment may be on: `LEFT` for deleted or
+/// unchanged lines as they were, `RIGHT` for added or unchanged lines as
+/// they are.
+///
+pub const SIDES: [&str; 2] = ["LEFT", "RIGHT"];
+
+/// Make sure Pull Request `prid` has a draft review, creating an empty one
+/// otherwise, and mark it as updated. This function requires a
+/// transaction.
+///
+async fn touch(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, prid: &i64) {
+    sqlx::query(
+        "
+        INSERT INTO draft_reviews (pr_id, body, updated_at) VALUES (?1, '', ?2)
+        ON CONFLICT (pr_id) DO UPDATE SET updated_at = ?2
+        ",
+    )
+    .bind(&prid)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to update draft review for pr {}: {}", prid, err);
+    });
+}
+
+/// Set the summary of Pull Request `prid`'s draft review, starting one if
+/// there's none. Returns `KeyUnavailableError` if the summary can't be
+/// encrypted as configured.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's ID.
+/// * `body` - The review's summary.
+///
+pub async fn set_body(
+    db: &DB,
+    prid: &i64,
+    body: &String,
+) -> Result<(), GHDError> {
+    let body = vault::sealer(&db, &Some(*prid)).await?.seal(&body);
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to set draft body: {}", err);
+        }
+    };
+    touch(&mut tx, &prid).await;
+
+    sqlx::query("UPDATE draft_reviews SET body = ? WHERE pr_id = ?")
+        .bind(&body)
+        .bind(&prid)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to set draft review for pr {}: {}", prid, err);
+        });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit transaction to set draft body: {}", err);
+    });
+    Ok(())
+}
+
+/// Add an inline comment to Pull Request `prid`'s draft review, starting
+/// one if there's none. Returns the comment's ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's ID.
+/// * `path` - The file the comment is on.
+/// * `line` - The line the comment is on.
+/// * `side` - The side of the diff `line` is on, `LEFT` or `RIGHT`.
+/// * `body` - The comment.
+///
+pub async fn add_comment(
+    db: &DB,
+    prid: &i64,
+    path: &String,
+    line: &i64,
+    side: &String,
+    body: &String,
+) -> Result<i64, GHDError> {
+    if !SIDES.contains(&side.as_str()) || *line < 1 || body.trim().is_empty() {
+        return Err(GHDError::BadRequest);
+    }
+
+    let body = vault::sealer(&db, &Some(*prid)).await?.seal(&body);
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to add draft comment: {}", err);
+        }
+    };
+    touch(&mut tx, &prid).await;
+
+    let now = chrono::Utc::now().timestamp();
+    let id = match sqlx::query(
+        "
+        INSERT INTO draft_review_comments (
+            pr_id, path, line, side, body, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&prid)
+    .bind(&path)
+    .bind(&line)
+    .bind(&side)
+    .bind(&body)
+    .bind(&now)
+    .bind(&now)
+    .execute(&mut tx)
+    .await
+    {
+        Ok(res) => res.last_insert_rowid(),
+        Err(err) => {
+            panic!("Unable to add draft comment for pr {}: {}", prid, err);
+        }
+    };
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit transaction to add draft comment: {}", err);
+    });
+    Ok(id)
+}
+
+/// Obtain the Pull Request draft comment `id` belongs to.
+///
+async fn get_comment_pr(
+    conn: &mut sqlx::SqliteConnection,
+    id: &i64,
+) -> Result<i64, GHDError> {
+    match sqlx::query_scalar::<_, i64>(
+        "SELECT pr_id FROM draft_review_comments WHERE id = ?",
+    )
+    .bind(&id)
+    .fetch_optional(&mut *conn)
+    .await
+    {
+        Ok(Some(res)) => Ok(res),
+        Ok(None) => Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain draft comment {}: {}", id, err);
+        }
+    }
+}
+
+/// Replace the body of draft comment `id`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The comment's ID.
+/// * `body` - The comment's new body.
+///
+pub async fn update_comment(
+    db: &DB,
+    id: &i64,
+    body: &String,
+) -> Result<(), GHDError> {
+    if body.trim().is_empty() {
+        return Err(GHDError::BadRequest);
+    }
+
+    let prid = {
+        let mut conn = match db.pool().acquire().await {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Unable to obtain database connection: {}", err);
+            }
+        };
+        get_comment_pr(&mut conn, &id).await?
+    };
+    let body = vault::sealer(&db, &Some(prid)).await?.seal(&body);
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Error starting transaction to update draft comment: {}",
+                err
+            );
+        }
+    };
+    touch(&mut tx, &prid).await;
+
+    sqlx::query(
+        "UPDATE draft_review_comments SET body = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(&body)
+    .bind(chrono::Utc::now().timestamp())
+    .bind(&id)
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to update draft comment {}: {}", id, err);
+    });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!(
+            "Unable to commit transaction to update draft comment: {}",
+            err
+        );
+    });
+    Ok(())
+}
+
+/// Remove draft comment `id`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The comment's ID.
+///
+pub async fn remove_comment(db: &DB, id: &i64) -> Result<(), GHDError> {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Error starting transaction to remove draft comment: {}",
+                err
+            );
+        }
+    };
+    let prid = get_comment_pr(&mut tx, &id).await?;
+    touch(&mut tx, &prid).await;
+
+    sqlx::query("DELETE FROM draft_review_comments WHERE id = ?")
+        .bind(&id)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to remove draft comment {}: {}", id, err);
+        });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!(
+            "Unable to commit transaction to remove draft comment: {}",
+            err
+        );
+    });
+    Ok(())
+}
+
+/// Obtain Pull Request `prid`'s draft review, with its comments by file and
+/// line.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's ID.
+///
+pub async fn get_draft(db: &DB, prid: &i64) -> Result<DraftReview, GHDError> {
+    let (body, updated_at) = match sqlx::query_as::<_, (String, i64)>(
+        "SELECT body, updated_at FROM draft_reviews WHERE pr_id = ?",
+    )
+    .bind(&prid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain draft review for pr {}: {}", prid, err);
+        }
+    };
+
+    let mut comments = match sqlx::query_as::<_, DraftReviewComment>(
+        "
+        SELECT * FROM draft_review_comments WHERE pr_id = ?
+        ORDER BY path, line, id
+        ",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain draft comments for pr {}: {}", prid, err);
+        }
+    };
+    for comment in &mut comments {
+        comment.body = vault::open(&comment.body).await;
+    }
+
+    Ok(DraftReview {
+        pr_id: *prid,
+        body: vault::open(&body).await,
+        updated_at,
+        comments,
+    })
+}
+
+/// Obtain the IDs of the Pull Requests with a draft review, most recently
+/// updated first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_drafted(db: &DB) -> Vec<i64> {
+    match sqlx::query_scalar::<_, i64>(
+        "SELECT pr_id FROM draft_reviews ORDER BY updated_at DESC",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain draft reviews: {}", err);
+        }
+    }
+}
+
+/// Drop Pull Request `prid`'s draft review, along with its comments.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's ID.
+///
+pub async fn discard(db: &DB, prid: &i64) {
+    for q in [
+        "DELETE FROM draft_review_comments WHERE pr_id = ?",
+        "DELETE FROM draft_reviews WHERE pr_id = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&prid)
+            .execute(db.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Unable to discard draft review for pr {}: {}",
+                    prid, err
+                );
+            });
+    }
+}
+
+/// Obtain the review action submitting `draft` with `event`, one of
+/// `APPROVE`, `REQUEST_CHANGES`, or `COMMENT`.
+///
+pub fn to_action(draft: &DraftReview, event: &String) -> PullRequestAction {
+    PullRequestAction::Review {
+        event: event.clone(),
+        body: match draft.body.trim().is_empty() {
+            true => None,
+            false => Some(draft.body.clone()),
+        },
+        comments: draft
+            .comments
+            .iter()
+            .map(|c| ReviewComment {
+                path: c.path.clone(),
+                line: c.line,
+                side: c.side.clone(),
+                body: c.body.clone(),
+            })
+            .collect(),
+    }
+}
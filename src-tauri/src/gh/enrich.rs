@@ -0,0 +1,182 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts, blockers, commits, files, ledger, refresh,
+    types::PullRequestTableEntry,
+};
+
+/// How visible a Pull Request is to us, and hence how urgently it should be
+/// enriched: review requests for one of our accounts come first, then Pull
+/// Requests we authored, then those we've looked at before, then the rest.
+///
+pub const ENRICHMENT_PRIORITY: &str = "
+    CASE
+        WHEN EXISTS (
+            SELECT 1 FROM pr_review_requests
+            WHERE
+                pr_review_requests.pr_id = issues.id
+                AND pr_review_requests.user_id IN (SELECT user_id FROM tokens)
+        ) THEN 3
+        WHEN issues.author_id IN (SELECT user_id FROM tokens) THEN 2
+        WHEN issues.last_viewed IS NOT NULL THEN 1
+        ELSE 0
+    END
+";
+
+/// Points of each rate limit resource left for everything else; enrichment
+/// stops once an account has fewer remaining.
+///
+pub const ENRICH_RATE_RESERVE: i64 = 500;
+
+/// The kinds of enrichment run on a Pull Request, each tracked as its own
+/// per-PR sync job.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnrichKind {
+    Files,
+    Commits,
+    Blockers,
+}
+
+impl EnrichKind {
+    /// Every kind of enrichment; earlier kinds go first when equally due.
+    ///
+    pub const ALL: [EnrichKind; 3] =
+        [EnrichKind::Files, EnrichKind::Commits, EnrichKind::Blockers];
+
+    /// Obtain the prefix of the per-PR sync job's key.
+    ///
+    pub fn prefix(self: &Self) -> &'static str {
+        match self {
+            EnrichKind::Files => files::REFRESH_PREFIX,
+            EnrichKind::Commits => commits::REFRESH_PREFIX,
+            EnrichKind::Blockers => blockers::REFRESH_PREFIX,
+        }
+    }
+}
+
+/// An enrichment to run on a Pull Request.
+///
+pub struct EnrichJob {
+    pub kind: EnrichKind,
+    pub pr: PullRequestTableEntry,
+}
+
+async fn get_priority(db: &DB, prid: &i64) -> i64 {
+    let query =
+        format!("SELECT {} FROM issues WHERE id = ?", ENRICHMENT_PRIORITY);
+    match sqlx::query_scalar::<_, i64>(&query)
+        .bind(&prid)
+        .fetch_one(db.pool())
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain priority of pr {}: {}", prid, err);
+        }
+    }
+}
+
+/// Obtain the next enrichment to run, if any: the most visible Pull
+/// Request, then the most recently updated, out of those due some kind of
+/// enrichment. Only Pull Requests we watch or have some visibility to are
+/// enriched. A job is due if it has never run, or has not run since the
+/// Pull Request was last updated.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn next_job(db: &DB) -> Result<Option<EnrichJob>, GHDError> {
+    let mut next: Option<(i64, EnrichJob)> = None;
+    for kind in EnrichKind::ALL {
+        let pr =
+            match refresh::get_stale_watched_prs(&db, kind.prefix(), 1).await {
+                Ok(mut res) => match res.pop() {
+                    Some(pr) => pr,
+                    None => continue,
+                },
+                Err(err) => return Err(err),
+            };
+        let priority = get_priority(&db, &pr.id).await;
+        let better = match &next {
+            Some((p, job)) => {
+                (priority, pr.updated_at) > (*p, job.pr.updated_at)
+            }
+            None => true,
+        };
+        if better {
+            next = Some((priority, EnrichJob { kind, pr }));
+        }
+    }
+
+    Ok(next.map(|(_, job)| job))
+}
+
+/// Check whether the account `job` would run as still has enough of its
+/// rate limits left, as last reported, to spend on enrichment.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `job` - The enrichment about to run.
+///
+pub async fn has_budget(db: &DB, job: &EnrichJob) -> bool {
+    let creds = match accounts::get_credentials_for_issue(&db, &job.pr.id).await
+    {
+        Ok(res) => res,
+        Err(_) => return false,
+    };
+
+    ledger::flush(&db).await;
+    let limits = ledger::get_rate_limits(&db).await.unwrap_or_default();
+    !limits.iter().any(|l| {
+        l.account_id == Some(creds.account_id)
+            && l.host == creds.host
+            && l.remaining.map_or(false, |r| r < ENRICH_RATE_RESERVE)
+    })
+}
+
+/// Record that `job` was attempted at `when`, even if it failed before
+/// getting that far, so that it isn't retried until the Pull Request is
+/// updated.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `job` - The enrichment attempted.
+/// * `when` - When the enrichment was attempted.
+///
+pub async fn mark_attempted(
+    db: &DB,
+    job: &EnrichJob,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    let key = format!("{}:{}", job.kind.prefix(), job.pr.id);
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to record enrichment: {}", err);
+        }
+    };
+
+    refresh::update_sync_refresh(&mut tx, &key, &when).await;
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit enrichment attempt '{}': {}", key, err);
+    });
+}
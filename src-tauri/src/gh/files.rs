@@ -0,0 +1,208 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    db::DB,
+    errors::GHDError,
+    page::{Page, PageRequest},
+};
+
+use super::{
+    accounts::AccountCredentials,
+    limits, refresh, rest, sizes,
+    types::{PullRequestFile, PullRequestTableEntry},
+};
+
+/// Prefix of the per-PR sync job fetching changed files.
+///
+pub const REFRESH_PREFIX: &str = "files";
+
+/// Obtain the sync refresh key for the files of Pull Request `prid`.
+///
+pub fn refresh_key(prid: &i64) -> String {
+    format!("{}:{}", REFRESH_PREFIX, prid)
+}
+
+/// Obtain the files changed by the provided Pull Request, along with whether
+/// there were more than allowed by `limits::max_list_items()`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `pr` - The Pull Request to obtain the files for.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    pr: &PullRequestTableEntry,
+) -> Result<(Vec<PullRequestFile>, bool), GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("files");
+    let ep = format!(
+        "/repos/{}/{}/pulls/{}/files",
+        pr.repo_owner, pr.repo_name, pr.number
+    );
+
+    match ghreq
+        .get_all_pages_capped::<rest::GithubPullFileReply>(&ep)
+        .await
+    {
+        Ok((res, truncated)) => Ok((
+            res.into_iter()
+                .map(|f| PullRequestFile {
+                    pr_id: pr.id,
+                    path: f.filename,
+                    status: f.status,
+                    additions: f.additions,
+                    deletions: f.deletions,
+                })
+                .collect(),
+            truncated,
+        )),
+        Err(err) => Err(rest::status_to_error(err)),
+    }
+}
+
+/// Replace the files known for Pull Request `prid`, recording whether they
+/// were truncated and the Pull Request's size. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `files` - The files changed by the Pull Request.
+/// * `truncated` - Whether there were more files than those provided.
+/// * `when` - When the files were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    files: &Vec<PullRequestFile>,
+    truncated: bool,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query("DELETE FROM pr_files WHERE pr_id = ?")
+        .bind(&prid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing files for pr {}: {}", prid, err);
+        });
+
+    for f in files {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_files (
+                pr_id, path, status, additions, deletions
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&f.pr_id)
+        .bind(&f.path)
+        .bind(&f.status)
+        .bind(&f.additions)
+        .bind(&f.deletions)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting file for pr {}: {}", prid, err);
+        });
+    }
+
+    limits::record(tx, &prid, limits::KIND_FILES, truncated, files.len()).await;
+    sizes::record(tx, &prid, &files).await;
+    refresh::update_sync_refresh(tx, &refresh_key(&prid), &when).await;
+}
+
+/// Obtain the files changed by Pull Request `prid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_files(
+    db: &DB,
+    prid: &i64,
+) -> Result<Vec<PullRequestFile>, GHDError> {
+    match sqlx::query_as::<_, PullRequestFile>(
+        "SELECT * FROM pr_files WHERE pr_id = ? ORDER BY path",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain files for pr {}: {}", prid, err);
+        }
+    }
+}
+
+/// Obtain a page of the Pull Requests touching files under the provided
+/// `path` prefix; e.g., `src-tauri/`, most recently updated first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `path` - The path prefix to filter on.
+/// * `page` - The page to obtain.
+///
+pub async fn get_prs_by_path(
+    db: &DB,
+    path: &String,
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    let pattern = format!(
+        "{}%",
+        path.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at
+        FROM
+            pull_requests INNER JOIN issues
+        ON
+            pull_requests.id = issues.id
+        WHERE
+            issues.id IN (
+                SELECT pr_id FROM pr_files WHERE path LIKE ? ESCAPE '\\'
+            )
+            AND {}
+        ORDER BY issues.updated_at DESC, issues.id DESC
+        LIMIT ?
+        ",
+        PageRequest::condition("issues.updated_at", "issues.id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&pattern)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(page.into_page(res, |pr| (pr.updated_at, pr.id))),
+        Err(err) => {
+            panic!("Unable to obtain pull requests by path: {}", err);
+        }
+    }
+}
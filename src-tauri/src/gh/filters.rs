@@ -0,0 +1,406 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sqlx::{
+    query::{Query, QueryAs},
+    sqlite::SqliteArguments,
+    Sqlite,
+};
+
+use crate::{db::DB, errors::GHDError};
+
+/// A filter on issues and Pull Requests. Every criterion is optional, and
+/// only the provided ones are applied. Issues deleted upstream are left out
+/// unless explicitly filtered on by `state`.
+///
+#[derive(serde::Deserialize)]
+pub struct IssueFilter {
+    pub repo_owner: Option<String>,
+    pub repo_name: Option<String>,
+    pub author: Option<String>,
+    pub involves: Option<String>,
+    pub label: Option<String>,
+    pub state: Option<String>,
+    pub is_pull_request: Option<bool>,
+    pub updated_before: Option<i64>,
+    pub unviewed_only: Option<bool>,
+    pub my_turn: Option<bool>,
+    pub tag: Option<String>,
+    pub language: Option<String>,
+    pub topic: Option<String>,
+    pub size: Option<String>,
+}
+
+/// Condition applying an `IssueFilter` to the `issues` row being selected.
+/// Uses numbered parameters `?1` through `?14`, which must be bound with
+/// `bind_query` or `bind_query_as`; additional parameters in the same query
+/// must be numbered from `?15` on. Languages and topics match regardless of
+/// case, as GitHub reports them.
+///
+pub const ISSUE_FILTER: &str = "
+    (?1 IS NULL OR issues.repo_owner = ?1)
+    AND (?2 IS NULL OR issues.repo_name = ?2)
+    AND (?3 IS NULL OR issues.author = ?3)
+    AND (
+        ?4 IS NULL OR issues.id IN (
+            SELECT user_issues.issue_id
+            FROM user_issues INNER JOIN users
+                ON users.id = user_issues.user_id
+            WHERE users.login = ?4
+        )
+    )
+    AND (
+        ?5 IS NULL OR issues.id IN (
+            SELECT issue_id FROM issue_labels WHERE name = ?5
+        )
+    )
+    AND (
+        (?6 IS NULL AND issues.state != 'deleted_upstream')
+        OR issues.state = ?6
+    )
+    AND (?7 IS NULL OR issues.is_pull_request = ?7)
+    AND (?8 IS NULL OR issues.updated_at < ?8)
+    AND (
+        ?9 IS NULL OR ?9 = 0
+        OR issues.last_viewed IS NULL
+        OR issues.last_viewed < issues.updated_at
+    )
+    AND (
+        ?10 IS NULL OR ?10 = COALESCE(
+            (SELECT my_turn FROM pr_turns WHERE pr_turns.pr_id = issues.id), 0
+        )
+    )
+    AND (
+        ?11 IS NULL OR issues.id IN (
+            SELECT issue_id FROM issue_tags WHERE tag = ?11
+        )
+    )
+    AND (
+        ?12 IS NULL OR EXISTS (
+            SELECT 1 FROM repo_metadata
+            WHERE repo_metadata.repo_owner = issues.repo_owner
+                AND repo_metadata.repo_name = issues.repo_name
+                AND repo_metadata.language = ?12 COLLATE NOCASE
+        )
+    )
+    AND (
+        ?13 IS NULL OR EXISTS (
+            SELECT 1 FROM repo_topics
+            WHERE repo_topics.repo_owner = issues.repo_owner
+                AND repo_topics.repo_name = issues.repo_name
+                AND repo_topics.topic = ?13 COLLATE NOCASE
+        )
+    )
+    AND (
+        ?14 IS NULL OR issues.id IN (
+            SELECT pr_id FROM pr_sizes WHERE size_class = ?14
+        )
+    )
+";
+
+/// Bind the parameters of `ISSUE_FILTER` on a query.
+///
+pub fn bind_query<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    filter: &'q IssueFilter,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    query
+        .bind(&filter.repo_owner)
+        .bind(&filter.repo_name)
+        .bind(&filter.author)
+        .bind(&filter.involves)
+        .bind(&filter.label)
+        .bind(&filter.state)
+        .bind(&filter.is_pull_request)
+        .bind(&filter.updated_before)
+        .bind(&filter.unviewed_only)
+        .bind(&filter.my_turn)
+        .bind(&filter.tag)
+        .bind(&filter.language)
+        .bind(&filter.topic)
+        .bind(&filter.size)
+}
+
+/// Bind the parameters of `ISSUE_FILTER` on a query mapping its rows to `O`.
+///
+pub fn bind_query_as<'q, O>(
+    query: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    filter: &'q IssueFilter,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    query
+        .bind(&filter.repo_owner)
+        .bind(&filter.repo_name)
+        .bind(&filter.author)
+        .bind(&filter.involves)
+        .bind(&filter.label)
+        .bind(&filter.state)
+        .bind(&filter.is_pull_request)
+        .bind(&filter.updated_before)
+        .bind(&filter.unviewed_only)
+        .bind(&filter.my_turn)
+        .bind(&filter.tag)
+        .bind(&filter.language)
+        .bind(&filter.topic)
+        .bind(&filter.size)
+}
+
+/// Mark every issue and Pull Request matching `filter` as viewed. Returns how
+/// many were marked.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `filter` - The filter selecting what to mark.
+///
+pub async fn mark_all_viewed(
+    db: &DB,
+    filter: &IssueFilter,
+) -> Result<u64, GHDError> {
+    let query =
+        format!("UPDATE issues SET last_viewed = ?15 WHERE {}", ISSUE_FILTER);
+
+    match bind_query(sqlx::query(&query), &filter)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(db.pool())
+        .await
+    {
+        Ok(res) => Ok(res.rows_affected()),
+        Err(err) => {
+            panic!("Unable to mark issues viewed: {}", err);
+        }
+    }
+}
+
+/// Dismiss every issue and Pull Request matching `filter` from the triage
+/// inbox. Returns how many were dismissed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `filter` - The filter selecting what to dismiss.
+///
+pub async fn dismiss_all(
+    db: &DB,
+    filter: &IssueFilter,
+) -> Result<u64, GHDError> {
+    let query = format!(
+        "
+        UPDATE triage SET dismissed = 1
+        WHERE dismissed = 0 AND issue_id IN (
+            SELECT issues.id FROM issues WHERE {}
+        )
+        ",
+        ISSUE_FILTER
+    );
+
+    match bind_query(sqlx::query(&query), &filter)
+        .execute(db.pool())
+        .await
+    {
+        Ok(res) => Ok(res.rows_affected()),
+        Err(err) => {
+            panic!("Unable to dismiss issues: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_filter() -> IssueFilter {
+        IssueFilter {
+            repo_owner: None,
+            repo_name: None,
+            author: None,
+            involves: None,
+            label: None,
+            state: None,
+            is_pull_request: None,
+            updated_before: None,
+            unviewed_only: None,
+            my_turn: None,
+            tag: None,
+            language: None,
+            topic: None,
+            size: None,
+        }
+    }
+
+    async fn setup_db(path: &std::path::PathBuf) -> DB {
+        let mut db = DB::new(&path);
+        db.setup().await;
+        db.connect().await;
+
+        // (id, repo_owner, repo_name, author, state, is_pr, updated_at,
+        //  last_viewed)
+        let issues = [
+            (1, "foo", "bar", "alice", "open", true, 100, None),
+            (2, "foo", "baz", "bob", "closed", false, 200, Some(300)),
+            (
+                3,
+                "qux",
+                "bar",
+                "alice",
+                "deleted_upstream",
+                true,
+                300,
+                None,
+            ),
+            (4, "qux", "bar", "bob", "open", false, 400, Some(350)),
+        ];
+        for (id, owner, name, author, state, is_pr, updated, viewed) in issues {
+            sqlx::query(
+                "
+                INSERT INTO issues (
+                    id, number, title, author, author_id, url, repo_owner,
+                    repo_name, state, created_at, updated_at, is_pull_request,
+                    last_viewed
+                ) VALUES (?1, ?1, '', ?2, 1, '', ?3, ?4, ?5, 0, ?6, ?7, ?8)
+                ",
+            )
+            .bind(&id)
+            .bind(&author)
+            .bind(&owner)
+            .bind(&name)
+            .bind(&state)
+            .bind(&updated)
+            .bind(&is_pr)
+            .bind(&viewed)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            "
+            INSERT INTO issue_labels (issue_id, name) VALUES (1, 'bug'), (4, 'bug');
+            INSERT INTO issue_tags (issue_id, tag) VALUES (2, 'later');
+            ",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        db
+    }
+
+    #[tokio::test]
+    async fn bind_cases() {
+        let path = std::env::temp_dir()
+            .join(format!("ghd-filters-test-{}.db", std::process::id()));
+        let db = setup_db(&path).await;
+
+        let cases = [
+            ("none", no_filter(), vec![1, 2, 4]),
+            (
+                "repo_owner",
+                IssueFilter {
+                    repo_owner: Some(String::from("qux")),
+                    ..no_filter()
+                },
+                vec![4],
+            ),
+            (
+                "repo_name",
+                IssueFilter {
+                    repo_name: Some(String::from("bar")),
+                    ..no_filter()
+                },
+                vec![1, 4],
+            ),
+            (
+                "author",
+                IssueFilter {
+                    author: Some(String::from("bob")),
+                    ..no_filter()
+                },
+                vec![2, 4],
+            ),
+            (
+                "label",
+                IssueFilter {
+                    label: Some(String::from("bug")),
+                    ..no_filter()
+                },
+                vec![1, 4],
+            ),
+            (
+                "state",
+                IssueFilter {
+                    state: Some(String::from("deleted_upstream")),
+                    ..no_filter()
+                },
+                vec![3],
+            ),
+            (
+                "is_pull_request",
+                IssueFilter {
+                    is_pull_request: Some(false),
+                    ..no_filter()
+                },
+                vec![2, 4],
+            ),
+            (
+                "updated_before",
+                IssueFilter {
+                    updated_before: Some(300),
+                    ..no_filter()
+                },
+                vec![1, 2],
+            ),
+            (
+                "unviewed_only",
+                IssueFilter {
+                    unviewed_only: Some(true),
+                    ..no_filter()
+                },
+                vec![1, 4],
+            ),
+            (
+                "tag",
+                IssueFilter {
+                    tag: Some(String::from("later")),
+                    ..no_filter()
+                },
+                vec![2],
+            ),
+            (
+                "combined",
+                IssueFilter {
+                    repo_owner: Some(String::from("foo")),
+                    label: Some(String::from("bug")),
+                    ..no_filter()
+                },
+                vec![1],
+            ),
+        ];
+
+        let query =
+            format!("SELECT id FROM issues WHERE {} ORDER BY id", ISSUE_FILTER);
+        for (name, filter, expected) in cases {
+            let ids: Vec<i64> =
+                bind_query_as(sqlx::query_as::<_, (i64,)>(&query), &filter)
+                    .fetch_all(db.pool())
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|(id,)| id)
+                    .collect();
+            assert_eq!(ids, expected, "{}", name);
+        }
+
+        db.pool().close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+}
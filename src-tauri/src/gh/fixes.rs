@@ -0,0 +1,173 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, notify};
+
+use super::types::{Issue, IssueFix};
+
+/// Column with whether an open Pull Request will close the `issues` row
+/// being selected.
+///
+pub const FIX_COLUMNS: &str = "
+    EXISTS (
+        SELECT 1 FROM issue_fixes
+        WHERE issue_fixes.issue_id = issues.id AND issue_fixes.state = 'open'
+    ) AS fix_in_progress
+";
+
+/// How recently a fix must have been linked to be notified of. Fixes linked
+/// earlier are recorded quietly, so issues first synced, or synced again
+/// after a while, don't flood notifications.
+///
+const NOTIFY_WINDOW_SECS: i64 = 24 * 3600;
+
+/// Check whether an open Pull Request is known to close issue `issueid`.
+/// This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn has_open_fix(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issueid: &i64,
+) -> bool {
+    sqlx::query_scalar::<_, i64>(
+        "
+        SELECT COUNT(*) FROM issue_fixes
+        WHERE issue_id = ? AND state = 'open'
+        ",
+    )
+    .bind(&issueid)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain fixes for issue {}: {}", issueid, err);
+    }) > 0
+}
+
+/// Replace the fixes known for `issue` with those just synced, notifying of
+/// open Pull Requests newly found to close it. Pull Requests carry no fixes,
+/// and are ignored. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issue` - The issue just synced.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issue: &Issue,
+) {
+    if issue.is_pull_request {
+        return;
+    }
+
+    let known = sqlx::query_scalar::<_, i64>(
+        "SELECT pr_id FROM issue_fixes WHERE issue_id = ? AND state = 'open'",
+    )
+    .bind(&issue.id)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain fixes for issue {}: {}", issue.id, err);
+    });
+
+    sqlx::query("DELETE FROM issue_fixes WHERE issue_id = ?")
+        .bind(&issue.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to clear fixes for issue {}: {}", issue.id, err);
+        });
+
+    let now = chrono::Utc::now().timestamp();
+    for fix in &issue.fixes {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO issue_fixes (
+                issue_id, pr_id, number, title, url,
+                repo_owner, repo_name, state, linked_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&issue.id)
+        .bind(&fix.pr_id)
+        .bind(&fix.number)
+        .bind(&fix.title)
+        .bind(&fix.url)
+        .bind(&fix.repo_owner)
+        .bind(&fix.repo_name)
+        .bind(&fix.state)
+        .bind(&fix.linked_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to consume fix for issue {}: {}", issue.id, err);
+        });
+
+        if fix.state != "open"
+            || known.contains(&fix.pr_id)
+            || fix.linked_at < now - NOTIFY_WINDOW_SECS
+        {
+            continue;
+        }
+
+        let title = format!(
+            "Fix in progress for {}/{}#{}",
+            issue.repo_owner, issue.repo_name, issue.number
+        );
+        let body = format!(
+            "{}/{}#{}: {}",
+            fix.repo_owner, fix.repo_name, fix.number, fix.title
+        );
+        notify::push(
+            tx,
+            "fix_in_progress",
+            &Some(issue.id),
+            &title,
+            &body,
+            notify::classify("fix_in_progress"),
+        )
+        .await;
+    }
+}
+
+/// Obtain the Pull Requests known to close issue `issueid` once merged, most
+/// recently linked first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn get_fixes(db: &DB, issueid: &i64) -> Vec<IssueFix> {
+    match sqlx::query_as::<_, IssueFix>(
+        "
+        SELECT * FROM issue_fixes
+        WHERE issue_id = ?
+        ORDER BY linked_at DESC
+        ",
+    )
+    .bind(&issueid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain fixes for issue {}: {}", issueid, err);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, db::DB};
+
+use super::{refresh, types::FollowUp};
+
+/// Setting holding the number of days after which a comment nobody replied
+/// to is awaiting a response; `0` disables follow-ups.
+///
+pub const SETTING_FOLLOW_UP_DAYS: &str = "followups.days";
+
+const DEFAULT_FOLLOW_UP_DAYS: i64 = 3;
+
+/// Comments are checked for replies every hour.
+///
+pub const FOLLOW_UP_REFRESH_INTERVAL: i64 = 60 * 60;
+
+/// How far back issues a user commented on are looked at; conversations
+/// left alone for longer are considered over.
+///
+const FOLLOW_UP_WINDOW_DAYS: i64 = 60;
+
+/// Obtain the sync refresh key for the follow-ups of user `userid`.
+///
+pub fn refresh_key(userid: &i64) -> String {
+    format!("follow_ups:{}", userid)
+}
+
+/// Check whether the follow-ups of user `userid` are due for a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn should_refresh(db: &DB, userid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&userid),
+        FOLLOW_UP_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Obtain the date since which issues commented on are checked for replies.
+///
+pub fn get_window_start() -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now() - chrono::Duration::days(FOLLOW_UP_WINDOW_DAYS)
+}
+
+/// Obtain the number of days after which an unanswered comment is awaiting a
+/// response, if follow-ups are enabled.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_days(db: &DB) -> Option<i64> {
+    let days = config::get_setting_or(
+        &db,
+        SETTING_FOLLOW_UP_DAYS,
+        &DEFAULT_FOLLOW_UP_DAYS.to_string(),
+    )
+    .await
+    .parse::<i64>()
+    .unwrap_or(DEFAULT_FOLLOW_UP_DAYS);
+
+    match days {
+        d if d > 0 => Some(d),
+        _ => None,
+    }
+}
+
+/// Write the unanswered comments obtained for user `userid` to the database,
+/// replacing those previously known, which have since been replied to. This
+/// function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The database ID of the account's user.
+/// * `followups` - The user's unanswered comments.
+/// * `when` - When the comments were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    followups: &Vec<FollowUp>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query("DELETE FROM follow_ups WHERE user_id = ?")
+        .bind(&userid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing follow-ups for {}: {}", userid, err);
+        });
+
+    for f in followups {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO follow_ups (
+                comment_id, user_id, issue_id, repo_owner, repo_name, number,
+                title, url, comment_url, commented_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&f.comment_id)
+        .bind(&f.user_id)
+        .bind(&f.issue_id)
+        .bind(&f.repo_owner)
+        .bind(&f.repo_name)
+        .bind(&f.number)
+        .bind(&f.title)
+        .bind(&f.url)
+        .bind(&f.comment_url)
+        .bind(&f.commented_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting follow-up {}: {}", f.comment_id, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&userid), &when).await;
+}
+
+/// Obtain the comments our users posted that have gone unanswered for longer
+/// than allowed, oldest first. Dismissed comments are left out; commenting
+/// again brings the conversation back.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_awaiting(db: &DB) -> Vec<FollowUp> {
+    let days = match get_days(&db).await {
+        Some(d) => d,
+        None => return vec![],
+    };
+    let before = chrono::Utc::now().timestamp() - days * 24 * 60 * 60;
+
+    match sqlx::query_as::<_, FollowUp>(
+        "
+        SELECT follow_ups.* FROM follow_ups
+        WHERE
+            commented_at < ?
+            AND comment_id NOT IN (SELECT comment_id FROM follow_up_dismissals)
+        ORDER BY commented_at, comment_id
+        ",
+    )
+    .bind(&before)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain follow-ups: {}", err);
+        }
+    }
+}
+
+/// Dismiss the follow-up on comment `commentid`, for when no reply is
+/// expected after all.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `commentid` - The comment's database ID.
+///
+pub async fn dismiss(db: &DB, commentid: &i64) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO follow_up_dismissals (comment_id, dismissed_at)
+        VALUES (?, ?)
+        ",
+    )
+    .bind(&commentid)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to dismiss follow-up {}: {}", commentid, err);
+    });
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod catalog;
+mod cost;
 mod custom_types;
 mod queries;
 
@@ -21,30 +23,109 @@ use queries::{user_info, UserInfo};
 use crate::errors::GHDError;
 
 use self::queries::{
+    follow_ups::{
+        self, FollowUpsSearchNodes,
+        FollowUpsSearchNodesOnIssueTimelineItemsNodes as IssueTimelineItem,
+        FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueCommentAuthorOn as IssueCommentAuthor,
+        FollowUpsSearchNodesOnPullRequestTimelineItemsNodes as PullRequestTimelineItem,
+        FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueCommentAuthorOn as PullRequestCommentAuthor,
+        FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReviewAuthorOn as PullRequestReviewAuthor,
+    },
+    org_repositories,
+    pending_reviews::{self, PendingReviewsSearchNodes},
+    pull_request_commits::{self, GitSignatureState},
+    pull_request_files_search::{
+        self, PatchStatus, PullRequestFilesSearchSearchNodes,
+    },
+    pull_request_merge_requirements::{
+        self,
+        PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodes as RollupContext,
+        PullRequestReviewState,
+    },
+    pull_request_status,
+    repo_branches::{self, RepoBranchesRepositoryRefsNodesTarget},
     search_issues::{
-        self, IssueState, PullRequestReviewDecision, PullRequestState,
-        SearchIssuesSearchNodes, SearchIssuesSearchNodesOnIssue,
-        SearchIssuesSearchNodesOnIssueAuthor,
+        self, CommentAuthorAssociation, CrossReferenceFragment,
+        CrossReferenceFragmentSource as CrossReferenceSource,
+        PullRequestReviewState as ReviewState,
+        ReviewFragmentAuthor as LatestReviewAuthor, SearchIssuesSearchNodes,
+        SearchIssuesSearchNodesOnIssue, SearchIssuesSearchNodesOnIssueAuthor,
+        SearchIssuesSearchNodesOnIssueCommentsNodesAuthor as CommentAuthor,
+        SearchIssuesSearchNodesOnIssueTimelineItemsNodes as TimelineItem,
         SearchIssuesSearchNodesOnPullRequest,
-        SearchIssuesSearchNodesOnPullRequestAuthor, UserFragment,
+        SearchIssuesSearchNodesOnPullRequestAuthor,
+        SearchIssuesSearchNodesOnPullRequestReviewRequestsNodesRequestedReviewer as RequestedReviewer,
+        SearchIssuesSearchNodesOnPullRequestTimelineItemsNodes as PullRequestReferenceItem,
+        StatusState, UserFragment,
     },
-    SearchIssues,
+    FollowUps, OrgRepositories, PendingReviews, PullRequestCommits,
+    PullRequestFilesSearch, PullRequestMergeRequirements, PullRequestStatus,
+    RepoBranches, SearchIssues,
 };
 
-use super::types::{Issue, PullRequest, UserUpdate};
+use super::{
+    accounts, bots, ledger, limits, rest,
+    types::{
+        parse, FollowUp, Issue, IssueFix, IssueReference, OrgRepository,
+        PendingReview, PullRequest, PullRequestCommit,
+        PullRequestCommitsUpdate, PullRequestFile,
+        PullRequestMergeRequirements as MergeRequirements, PullRequestReview,
+        PullRequestStatus as Status, RepoBranch, RepoBranchesUpdate,
+        UserUpdate,
+    },
+};
+
+/// The most results obtained for a search, as was the case before searches
+/// were split into pages.
+///
+const SEARCH_MAX_RESULTS: i64 = 100;
+
+/// The most results obtained for an organization-wide search, which is as
+/// many as the search API will return for a query.
+///
+pub const ORG_SEARCH_MAX_RESULTS: i64 = 1000;
+
+/// The most issues and Pull Requests a user commented on that are checked
+/// for replies.
+///
+const FOLLOW_UP_MAX_RESULTS: i64 = 200;
+
+/// The most dependency update Pull Requests obtained for a repository.
+///
+const DEPENDENCY_SEARCH_MAX_RESULTS: i64 = 200;
 
 #[derive(serde::Deserialize, Debug)]
 struct GQLResData<T> {
     pub data: T,
 }
 
+/// The cost GitHub charged for a query, which every query requests through
+/// `rateLimit { cost }`.
+///
+#[derive(serde::Deserialize, Debug)]
+struct GQLRateLimit {
+    cost: i64,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct GQLCostData {
+    #[serde(rename = "rateLimit")]
+    rate_limit: Option<GQLRateLimit>,
+}
+
 struct GithubGQLRequest {
     client: reqwest::Client,
+    token: String,
+    host: String,
+    url: String,
 }
 
 impl GithubGQLRequest {
-    pub fn new(token: &String) -> Self {
+    pub fn new(token: &String, host: &String) -> Self {
         GithubGQLRequest {
+            token: token.clone(),
+            host: host.clone(),
+            url: accounts::graphql_api_url(host),
             client: reqwest::Client::builder()
                 .user_agent("GHD")
                 .default_headers(
@@ -67,18 +148,29 @@ impl GithubGQLRequest {
         self: &Self,
         variables: T::Variables,
     ) -> Result<M, GHDError>
+    where
+        T: GraphQLQuery,
+        M: for<'de> serde::Deserialize<'de> + core::fmt::Debug,
+    {
+        self.execute_with_cost::<T, M>(variables, 1).await
+    }
+
+    /// Execute a query estimated to cost `cost` points. The ledger records the
+    /// cost reported by GitHub, falling back to the estimate should the
+    /// response not carry it.
+    ///
+    async fn execute_with_cost<'a, T, M>(
+        self: &Self,
+        variables: T::Variables,
+        cost: i64,
+    ) -> Result<M, GHDError>
     where
         T: GraphQLQuery,
         M: for<'de> serde::Deserialize<'de> + core::fmt::Debug,
     {
         let debug = std::env::var("GHD_GQL_DEBUG").is_ok();
         let req_body = T::build_query(variables);
-        let res = match self
-            .client
-            .post("https://api.github.com/graphql")
-            .json(&req_body)
-            .send()
-            .await
+        let res = match self.client.post(&self.url).json(&req_body).send().await
         {
             Ok(res) => res,
             Err(err) => {
@@ -86,12 +178,30 @@ impl GithubGQLRequest {
                 return Err(GHDError::UnknownError);
             }
         };
+        // queries are attributed to the feature by their operation name.
+        let headers = res.headers().clone();
+        let record = |cost: i64| {
+            ledger::record(
+                &self.token,
+                &self.host,
+                req_body.operation_name,
+                cost,
+                &headers,
+            );
+        };
 
-        match res.status() {
+        let status = rest::response_status(&res);
+        if status != reqwest::StatusCode::OK {
+            record(cost);
+        }
+        match status {
             reqwest::StatusCode::OK => {}
             reqwest::StatusCode::FORBIDDEN => {
                 return Err(GHDError::BadTokenError);
             }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                return Err(GHDError::RateLimitedError);
+            }
             reqwest::StatusCode::NOT_FOUND => {
                 return Err(GHDError::UserNotFoundError);
             }
@@ -104,13 +214,29 @@ impl GithubGQLRequest {
             }
         };
 
-        let res_body = res.text().await.unwrap_or_else(|err| {
-            panic!("Unable to unwrap graphql body result: {}", err);
-        });
+        let res_body = match limits::read_body(res).await {
+            Some(res) => res,
+            None => {
+                record(cost);
+                return Err(GHDError::ResponseTooLargeError);
+            }
+        };
         if debug {
             println!("res body:\n{}", res_body);
         }
 
+        record(
+            match serde_json::from_str::<GQLResData<GQLCostData>>(&res_body) {
+                Ok(GQLResData {
+                    data:
+                        GQLCostData {
+                            rate_limit: Some(limit),
+                        },
+                }) => limit.cost,
+                _ => cost,
+            },
+        );
+
         let res_data: GQLResData<M> = serde_json::from_str(&res_body)
             .unwrap_or_else(|err| {
                 panic!("Unable to decode graphql result: {}", err);
@@ -159,9 +285,30 @@ impl GithubGQLRequest {
     pub async fn get_user_open_issues(
         self: &Self,
         login: &String,
-    ) -> search_issues::ResponseData {
+    ) -> Vec<search_issues::ResponseData> {
         let q = format!("involves:{} is:open", login);
-        self.get_search_issues_data(&q).await
+        self.get_search_issues_data(&q, SEARCH_MAX_RESULTS).await
+    }
+
+    /// Obtain all issues involving the specified user `login`, whatever
+    /// their state, last updated within the specified dates.
+    ///
+    /// # Arguments
+    ///
+    /// * `login` - String containing the user's login handle.
+    /// * `from` - String containing the date and time the range starts at.
+    ///   This String must comply with RFC 3339.
+    /// * `to` - String containing the date and time the range ends at. This
+    ///   String must comply with RFC 3339.
+    ///
+    pub async fn get_user_history(
+        self: &Self,
+        login: &String,
+        from: &String,
+        to: &String,
+    ) -> Vec<search_issues::ResponseData> {
+        let q = format!("involves:{} updated:{}..{}", login, from, to);
+        self.get_search_issues_data(&q, SEARCH_MAX_RESULTS).await
     }
 
     /// Obtain all issues involving the specified user `login` that have been
@@ -179,35 +326,319 @@ impl GithubGQLRequest {
         self: &Self,
         login: &String,
         since: &String,
-    ) -> search_issues::ResponseData {
+    ) -> Vec<search_issues::ResponseData> {
         let q = format!("involves:{} updated:>{}", login, since);
-        self.get_search_issues_data(&q).await
+        self.get_search_issues_data(&q, SEARCH_MAX_RESULTS).await
+    }
+
+    /// Obtain all open Pull Requests in the repositories of organization
+    /// `org`, up to as many as the search API returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `org` - String containing the organization's login.
+    ///
+    pub async fn get_org_open_pulls(
+        self: &Self,
+        org: &String,
+    ) -> Vec<search_issues::ResponseData> {
+        let q = format!("org:{} is:pr is:open archived:false", org);
+        self.get_search_issues_data(&q, ORG_SEARCH_MAX_RESULTS)
+            .await
+    }
+
+    /// Obtain the issues and Pull Requests on repositories `repos` mentioning
+    /// team `team` that have been updated since the specified date, most
+    /// recently updated first.
+    ///
+    /// # Arguments
+    ///
+    /// * `team` - String containing the team, as `org/slug`.
+    /// * `repos` - The repositories to look in, as `owner/name`.
+    /// * `since` - String containing the date and time since which we should
+    ///   look for mentions. This String must comply with RFC 3339.
+    ///
+    pub async fn get_team_mentions(
+        self: &Self,
+        team: &String,
+        repos: &[String],
+        since: &String,
+    ) -> Vec<search_issues::ResponseData> {
+        let repos = repos
+            .iter()
+            .map(|r| format!("repo:{}", r))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let q = format!(
+            "team:{} updated:>{} {} sort:updated-desc",
+            team, since, repos
+        );
+        self.get_search_issues_data(&q, SEARCH_MAX_RESULTS).await
+    }
+
+    /// Obtain the open Pull Requests opened by dependency update bots, i.e.
+    /// Dependabot and Renovate, in repository `owner/name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    ///
+    pub async fn get_repo_dependency_pulls(
+        self: &Self,
+        owner: &String,
+        name: &String,
+    ) -> Vec<search_issues::ResponseData> {
+        let q = format!(
+            "repo:{}/{} is:pr is:open author:app/dependabot author:app/renovate",
+            owner, name
+        );
+        self.get_search_issues_data(&q, DEPENDENCY_SEARCH_MAX_RESULTS)
+            .await
     }
 
     /// Obtain issues matching the specified query. This function is auxiliary
     /// and implements the common functionality for the `get_user_open_issues()`
-    /// and `get_user_update()` functions.
+    /// and `get_user_update()` functions. Results are obtained over as many
+    /// pages as needed to keep each query within budget.
     ///
     /// # Arguments
     ///
     /// * `query` - String containing the query to be used for searching issues.
+    /// * `max` - The most results to obtain.
     ///
     async fn get_search_issues_data(
         self: &Self,
         query: &String,
-    ) -> search_issues::ResponseData {
-        let vars = search_issues::Variables { q: query.clone() };
-        let response_data: search_issues::ResponseData = match self
-            .execute::<SearchIssues, search_issues::ResponseData>(vars)
-            .await
-        {
-            Ok(res) => res,
-            Err(err) => {
-                panic!("error: {:?}", err);
+        max: i64,
+    ) -> Vec<search_issues::ResponseData> {
+        let page_size = cost::SEARCH_ISSUES.page_size(max);
+        let mut pages = vec![];
+        let mut after: Option<String> = None;
+        let mut fetched = 0;
+
+        loop {
+            let first = std::cmp::min(page_size, max - fetched);
+            let vars = search_issues::Variables {
+                q: query.clone(),
+                first,
+                after: after.clone(),
+            };
+            let res = match self
+                .execute_with_cost::<SearchIssues, search_issues::ResponseData>(
+                    vars,
+                    cost::SEARCH_ISSUES.points(first),
+                )
+                .await
+            {
+                Ok(res) => res,
+                Err(err) => {
+                    panic!("error: {:?}", err);
+                }
+            };
+            fetched += first;
+
+            let info = &res.search.page_info;
+            after = match info.has_next_page && fetched < max {
+                true => info.end_cursor.clone(),
+                false => None,
+            };
+            pages.push(res);
+            if after.is_none() {
+                break;
             }
+        }
+
+        pages
+    }
+
+    /// Obtain a page of branches for the specified repository.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    /// * `after` - Cursor after which the page starts, if any.
+    ///
+    async fn get_repo_branches_page(
+        self: &Self,
+        owner: &String,
+        name: &String,
+        after: &Option<String>,
+    ) -> Result<repo_branches::ResponseData, GHDError> {
+        let vars = repo_branches::Variables {
+            owner: owner.clone(),
+            name: name.clone(),
+            after: after.clone(),
+        };
+        self.execute::<RepoBranches, repo_branches::ResponseData>(vars)
+            .await
+    }
+
+    /// Obtain a page of the repositories belonging to organization `org`.
+    ///
+    /// # Arguments
+    ///
+    /// * `org` - The organization's login.
+    /// * `after` - Cursor to start the page after, if not the first page.
+    ///
+    async fn get_org_repositories_page(
+        self: &Self,
+        org: &String,
+        after: &Option<String>,
+    ) -> Result<org_repositories::ResponseData, GHDError> {
+        let vars = org_repositories::Variables {
+            org: org.clone(),
+            after: after.clone(),
         };
+        self.execute::<OrgRepositories, org_repositories::ResponseData>(vars)
+            .await
+    }
 
-        response_data
+    /// Obtain a page of the open Pull Requests matching the specified query,
+    /// along with the pending review by user `login` on each, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - String containing the query to search Pull Requests with.
+    /// * `login` - The login of the user whose pending reviews to obtain.
+    /// * `after` - Cursor after which the page starts, if any.
+    ///
+    async fn get_pending_reviews_page(
+        self: &Self,
+        query: &String,
+        login: &String,
+        after: &Option<String>,
+    ) -> Result<pending_reviews::ResponseData, GHDError> {
+        let vars = pending_reviews::Variables {
+            q: query.clone(),
+            login: login.clone(),
+            after: after.clone(),
+        };
+        self.execute::<PendingReviews, pending_reviews::ResponseData>(vars)
+            .await
+    }
+
+    /// Obtain a page of the Pull Requests matching the specified query, along
+    /// with the first files each of them changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - String containing the query to search Pull Requests with.
+    /// * `after` - Cursor after which the page starts, if any.
+    ///
+    async fn get_pull_files_search_page(
+        self: &Self,
+        query: &String,
+        after: &Option<String>,
+    ) -> Result<pull_request_files_search::ResponseData, GHDError> {
+        let vars = pull_request_files_search::Variables {
+            q: query.clone(),
+            after: after.clone(),
+        };
+        self.execute::<PullRequestFilesSearch, pull_request_files_search::ResponseData>(
+            vars,
+        )
+        .await
+    }
+
+    /// Obtain a page of the issues and Pull Requests matching the specified
+    /// query, along with the last comments and reviews on their timeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - String containing the query to search issues with.
+    /// * `after` - Cursor after which the page starts, if any.
+    ///
+    async fn get_follow_ups_page(
+        self: &Self,
+        query: &String,
+        after: &Option<String>,
+    ) -> Result<follow_ups::ResponseData, GHDError> {
+        let vars = follow_ups::Variables {
+            q: query.clone(),
+            after: after.clone(),
+        };
+        self.execute::<FollowUps, follow_ups::ResponseData>(vars)
+            .await
+    }
+
+    /// Obtain the last commits of the specified Pull Request, along with
+    /// whether its base branch requires signed commits.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    /// * `number` - The Pull Request's number.
+    ///
+    async fn get_pull_request_commits(
+        self: &Self,
+        owner: &String,
+        name: &String,
+        number: &i64,
+    ) -> Result<pull_request_commits::ResponseData, GHDError> {
+        let vars = pull_request_commits::Variables {
+            owner: owner.clone(),
+            name: name.clone(),
+            number: *number,
+        };
+        self.execute::<PullRequestCommits, pull_request_commits::ResponseData>(
+            vars,
+        )
+        .await
+    }
+
+    /// Obtain the merge requirements of the specified Pull Request's base
+    /// branch, along with its approvals and reported checks.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    /// * `number` - The Pull Request's number.
+    ///
+    async fn get_pull_request_merge_requirements(
+        self: &Self,
+        owner: &String,
+        name: &String,
+        number: &i64,
+    ) -> Result<pull_request_merge_requirements::ResponseData, GHDError> {
+        let vars = pull_request_merge_requirements::Variables {
+            owner: owner.clone(),
+            name: name.clone(),
+            number: *number,
+        };
+        self.execute::<
+            PullRequestMergeRequirements,
+            pull_request_merge_requirements::ResponseData,
+        >(vars)
+        .await
+    }
+
+    /// Obtain the current status of the specified Pull Request.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The repository's owner.
+    /// * `name` - The repository's name.
+    /// * `number` - The Pull Request's number.
+    ///
+    async fn get_pull_request_status(
+        self: &Self,
+        owner: &String,
+        name: &String,
+        number: &i64,
+    ) -> Result<pull_request_status::ResponseData, GHDError> {
+        let vars = pull_request_status::Variables {
+            owner: owner.clone(),
+            name: name.clone(),
+            number: *number,
+        };
+        self.execute::<PullRequestStatus, pull_request_status::ResponseData>(
+            vars,
+        )
+        .await
     }
 }
 
@@ -217,50 +648,847 @@ impl GithubGQLRequest {
 ///
 pub async fn get_user_open_issues(
     token: &String,
+    host: &String,
     login: &String,
 ) -> Result<UserUpdate, GHDError> {
-    let res = GithubGQLRequest::new(&token)
+    let res = GithubGQLRequest::new(&token, &host)
         .get_user_open_issues(&login)
         .await;
 
     process_user_search_results(&res)
 }
 
+/// Obtain the issues for the provided `login`, whatever their state, last
+/// updated between `from` and `to`.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `login` - String containing the user to obtain issues for.
+/// * `from` - Date the range starts at.
+/// * `to` - Date the range ends at.
+///
+pub async fn get_user_history(
+    token: &String,
+    host: &String,
+    login: &String,
+    from: &chrono::DateTime<chrono::Utc>,
+    to: &chrono::DateTime<chrono::Utc>,
+) -> Result<UserUpdate, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_user_history(&login, &from.to_rfc3339(), &to.to_rfc3339())
+        .await;
+
+    process_user_search_results(&res)
+}
+
+/// Check whether a search hit the maximum number of results, meaning there
+/// may be more matching issues than were obtained.
+///
+pub fn is_capped(update: &UserUpdate) -> bool {
+    (update.issues.len() + update.prs.len()) as i64 >= SEARCH_MAX_RESULTS
+}
+
 /// Obtain Pull Request and Issue updates for provided `login` since the
 /// provided date `since`.
 ///
 /// # Arguments
 ///
 /// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
 /// * `login` - String containing the user to obtain an update for.
 /// * `since` - Date since which updates should be looked for.
 ///
 pub async fn get_user_updates(
     token: &String,
+    host: &String,
     login: &String,
     since: &chrono::DateTime<chrono::Utc>,
 ) -> Result<UserUpdate, GHDError> {
     let since_str = since.to_rfc3339();
-    let res = GithubGQLRequest::new(&token)
+    let res = GithubGQLRequest::new(&token, &host)
         .get_user_update(&login, &since_str)
         .await;
 
     process_user_search_results(&res)
 }
 
-/// Process the resulting data from the GraphQL call into something that the
+/// Obtain all open Pull Requests in the repositories of organization `org`,
+/// regardless of who's involved with them.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `org` - String containing the organization's login.
+///
+pub async fn get_org_open_pulls(
+    token: &String,
+    host: &String,
+    org: &String,
+) -> Result<UserUpdate, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_org_open_pulls(&org)
+        .await;
+
+    process_user_search_results(&res)
+}
+
+/// Obtain the issues and Pull Requests on repositories `repos` mentioning
+/// team `org/slug`, updated since `since`.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `org` - String containing the team's organization login.
+/// * `slug` - String containing the team's slug.
+/// * `repos` - The repositories to look in, as `owner/name`.
+/// * `since` - Date since which mentions should be looked for.
+///
+pub async fn get_team_mentions(
+    token: &String,
+    host: &String,
+    org: &String,
+    slug: &String,
+    repos: &[String],
+    since: &chrono::DateTime<chrono::Utc>,
+) -> Result<UserUpdate, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_team_mentions(
+            &format!("{}/{}", org, slug),
+            &repos,
+            &since.to_rfc3339(),
+        )
+        .await;
+
+    process_user_search_results(&res)
+}
+
+/// Obtain the open Pull Requests opened by dependency update bots in
+/// repository `owner/name`.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_repo_dependency_pulls(
+    token: &String,
+    host: &String,
+    owner: &String,
+    name: &String,
+) -> Result<UserUpdate, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_repo_dependency_pulls(&owner, &name)
+        .await;
+
+    process_user_search_results(&res)
+}
+
+/// Obtain all branches for the repository `owner/name`, along with the date of
+/// their last commit and the open Pull Requests using them as head. The
+/// repository's primary language and topics come along for the ride.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_repo_branches(
+    token: &String,
+    host: &String,
+    owner: &String,
+    name: &String,
+) -> Result<RepoBranchesUpdate, GHDError> {
+    let req = GithubGQLRequest::new(&token, &host);
+    let mut default_branch: Option<String> = None;
+    let mut language: Option<String> = None;
+    let mut topics: Vec<String> = vec![];
+    let mut branches: Vec<RepoBranch> = vec![];
+    let mut after: Option<String> = None;
+
+    loop {
+        let res = req.get_repo_branches_page(&owner, &name, &after).await?;
+        let repo = match res.repository {
+            None => return Err(GHDError::NotFoundError),
+            Some(r) => r,
+        };
+        if let Some(r) = repo.default_branch_ref {
+            default_branch = Some(r.name);
+        }
+        if let Some(l) = repo.primary_language {
+            language = Some(l.name);
+        }
+        if after.is_none() {
+            topics = repo
+                .repository_topics
+                .nodes
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .map(|n| n.topic.name)
+                .collect();
+        }
+        let refs = match repo.refs {
+            None => break,
+            Some(r) => r,
+        };
+
+        for node in refs.nodes.unwrap_or_default().into_iter().flatten() {
+            let last_commit_at = match &node.target {
+                Some(RepoBranchesRepositoryRefsNodesTarget::Commit(c)) => {
+                    c.committed_date.value.map(|d| d.timestamp())
+                }
+                _ => None,
+            };
+            let pull_requests = match node.associated_pull_requests.nodes {
+                None => vec![],
+                Some(v) => v.iter().flatten().map(|pr| pr.number).collect(),
+            };
+            branches.push(RepoBranch {
+                name: node.name,
+                last_commit_at,
+                pull_requests,
+            });
+        }
+
+        if !refs.page_info.has_next_page {
+            break;
+        }
+        after = refs.page_info.end_cursor;
+    }
+
+    Ok(RepoBranchesUpdate {
+        when: chrono::Utc::now(),
+        default_branch,
+        language,
+        topics,
+        branches,
+    })
+}
+
+/// Obtain the repositories belonging to organization `org`, most recently
+/// pushed to first, along with how many open Pull Requests each has.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `org` - The organization's login.
+///
+pub async fn get_org_repositories(
+    token: &String,
+    host: &String,
+    org: &String,
+) -> Result<Vec<OrgRepository>, GHDError> {
+    let req = GithubGQLRequest::new(&token, &host);
+    let mut repos: Vec<OrgRepository> = vec![];
+    let mut after: Option<String> = None;
+
+    loop {
+        let res = req.get_org_repositories_page(&org, &after).await?;
+        let page = match res.organization {
+            None => return Err(GHDError::NotFoundError),
+            Some(o) => o.repositories,
+        };
+
+        for node in page.nodes.unwrap_or_default().into_iter().flatten() {
+            repos.push(OrgRepository {
+                owner: org.clone(),
+                name: node.name,
+                is_archived: node.is_archived,
+                pushed_at: node
+                    .pushed_at
+                    .and_then(|d| d.value)
+                    .map(|d| d.timestamp()),
+                open_prs: node.pull_requests.total_count,
+                is_tracked: false,
+            });
+        }
+
+        if !page.page_info.has_next_page {
+            break;
+        }
+        after = page.page_info.end_cursor;
+    }
+
+    Ok(repos)
+}
+
+/// Obtain the reviews user `login` started but never submitted, on open Pull
+/// Requests they're involved with, have been asked to review, or have
+/// reviewed before. Pull Requests on which they've never shown up can't be
+/// searched for, so drafts on those go unnoticed.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `userid` - The user's database ID.
+/// * `login` - The user's login.
+///
+pub async fn get_pending_reviews(
+    token: &String,
+    host: &String,
+    userid: &i64,
+    login: &String,
+) -> Result<Vec<PendingReview>, GHDError> {
+    let req = GithubGQLRequest::new(&token, &host);
+    let mut res: Vec<PendingReview> = vec![];
+
+    for qualifier in ["involves", "review-requested", "reviewed-by"] {
+        let query = format!("is:pr is:open {}:{}", qualifier, login);
+        let mut after: Option<String> = None;
+        loop {
+            let page = req
+                .get_pending_reviews_page(&query, &login, &after)
+                .await?
+                .search;
+
+            for node in page.nodes.unwrap_or_default().into_iter().flatten() {
+                let pr = match node {
+                    PendingReviewsSearchNodes::PullRequest(pr) => pr,
+                    _ => continue,
+                };
+                let review = match pr
+                    .reviews
+                    .and_then(|r| r.nodes)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .flatten()
+                    .next()
+                {
+                    Some(r) => r,
+                    None => continue,
+                };
+                let review_id = get_id(review.database_id);
+                if res.iter().any(|r| r.review_id == review_id) {
+                    continue;
+                }
+                let summary = pr.pull_request_summary_fragment;
+                res.push(PendingReview {
+                    review_id,
+                    user_id: *userid,
+                    pr_id: get_id(summary.database_id),
+                    repo_owner: summary.repository.owner.login,
+                    repo_name: summary.repository.name,
+                    number: summary.number,
+                    title: summary.title,
+                    url: summary.url,
+                    comments: review.comments.total_count,
+                    created_at: review
+                        .created_at
+                        .value
+                        .map_or(0, |d| d.timestamp()),
+                    updated_at: review
+                        .updated_at
+                        .value
+                        .map_or(0, |d| d.timestamp()),
+                });
+            }
+
+            if !page.page_info.has_next_page {
+                break;
+            }
+            after = page.page_info.end_cursor;
+        }
+    }
+
+    Ok(res)
+}
+
+/// Obtain the files changed by the open Pull Requests involving `login`,
+/// updated since `since` if provided, for as many Pull Requests as an initial
+/// import obtains. Files come nested in the search results, so a whole import
+/// takes a couple of queries rather than a REST call per Pull Request. Pull
+/// Requests changing more files than fit in a page are left out, to be
+/// obtained in full through `files::fetch()`.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `login` - String containing the user to obtain files for.
+/// * `since` - Only consider Pull Requests updated after this date, if any.
+///
+pub async fn get_user_open_pull_files(
+    token: &String,
+    host: &String,
+    login: &String,
+    since: &Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<(i64, Vec<PullRequestFile>)>, GHDError> {
+    let req = GithubGQLRequest::new(&token, &host);
+    let query = match since {
+        Some(since) => format!(
+            "involves:{} is:pr is:open updated:>{}",
+            login,
+            since.to_rfc3339()
+        ),
+        None => format!("involves:{} is:pr is:open", login),
+    };
+    let mut res: Vec<(i64, Vec<PullRequestFile>)> = vec![];
+    let mut after: Option<String> = None;
+    let mut fetched = 0;
+
+    loop {
+        let page = req.get_pull_files_search_page(&query, &after).await?.search;
+
+        for node in page.nodes.unwrap_or_default().into_iter().flatten() {
+            fetched += 1;
+            let pr = match node {
+                PullRequestFilesSearchSearchNodes::PullRequest(pr) => pr,
+                _ => continue,
+            };
+            let files = match pr.files {
+                Some(f) => f,
+                None => continue,
+            };
+            let nodes: Vec<_> = files
+                .nodes
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+                .collect();
+            if nodes.len() as i64 != files.total_count {
+                continue;
+            }
+
+            let prid = get_id(pr.database_id);
+            res.push((
+                prid,
+                nodes
+                    .into_iter()
+                    .map(|f| PullRequestFile {
+                        pr_id: prid,
+                        path: f.path,
+                        status: get_patch_status(&f.change_type),
+                        additions: f.additions,
+                        deletions: f.deletions,
+                    })
+                    .collect(),
+            ));
+        }
+
+        if !page.page_info.has_next_page || fetched >= SEARCH_MAX_RESULTS {
+            break;
+        }
+        after = page.page_info.end_cursor;
+    }
+
+    Ok(res)
+}
+
+/// Obtain the comments user `login` posted on open issues and Pull Requests
+/// updated since `since` that are still the last word on them, as per their
+/// timeline. Comments and reviews by bots aren't considered replies.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `userid` - The user's database ID.
+/// * `login` - The user's login.
+/// * `since` - Only consider issues updated after this date.
+///
+pub async fn get_follow_ups(
+    token: &String,
+    host: &String,
+    userid: &i64,
+    login: &String,
+    since: &chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<FollowUp>, GHDError> {
+    let req = GithubGQLRequest::new(&token, &host);
+    let query = format!(
+        "is:open commenter:{} updated:>{}",
+        login,
+        since.to_rfc3339()
+    );
+    let mut res: Vec<FollowUp> = vec![];
+    let mut after: Option<String> = None;
+    let mut fetched = 0;
+
+    loop {
+        let page = req.get_follow_ups_page(&query, &after).await?.search;
+
+        for node in page.nodes.unwrap_or_default().into_iter().flatten() {
+            fetched += 1;
+            // the timeline boiled down to who said something and when, along
+            // with the comment's ID and URL for comments.
+            let (issue_id, repo_owner, repo_name, number, title, url, items) =
+                match node {
+                    FollowUpsSearchNodes::Issue(i) => (
+                        i.database_id,
+                        i.repository.owner.login,
+                        i.repository.name,
+                        i.number,
+                        i.title,
+                        i.url,
+                        i.timeline_items
+                            .nodes
+                            .unwrap_or_default()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|n| match n {
+                                IssueTimelineItem::IssueComment(c) => {
+                                    let author = c.author?;
+                                    Some((
+                                        author.login,
+                                        matches!(
+                                            author.on,
+                                            IssueCommentAuthor::Bot
+                                        ),
+                                        c.database_id.map(|id| (id, c.url)),
+                                        c.created_at.value,
+                                    ))
+                                }
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                    FollowUpsSearchNodes::PullRequest(pr) => (
+                        pr.pull_request_summary_fragment.database_id,
+                        pr.pull_request_summary_fragment.repository.owner.login,
+                        pr.pull_request_summary_fragment.repository.name,
+                        pr.pull_request_summary_fragment.number,
+                        pr.pull_request_summary_fragment.title,
+                        pr.pull_request_summary_fragment.url,
+                        pr.timeline_items
+                            .nodes
+                            .unwrap_or_default()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|n| match n {
+                                PullRequestTimelineItem::IssueComment(c) => {
+                                    let author = c.author?;
+                                    Some((
+                                        author.login,
+                                        matches!(
+                                            author.on,
+                                            PullRequestCommentAuthor::Bot
+                                        ),
+                                        c.database_id.map(|id| (id, c.url)),
+                                        c.created_at.value,
+                                    ))
+                                }
+                                PullRequestTimelineItem::PullRequestReview(
+                                    r,
+                                ) => {
+                                    let author = r.author?;
+                                    Some((
+                                        author.login,
+                                        matches!(
+                                            author.on,
+                                            PullRequestReviewAuthor::Bot
+                                        ),
+                                        None,
+                                        r.created_at.value,
+                                    ))
+                                }
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                    _ => continue,
+                };
+
+            let last =
+                items.into_iter().filter(|(_, is_bot, _, _)| !is_bot).last();
+            let (comment_id, comment_url, commented_at) = match last {
+                Some((author, _, Some((id, url)), Some(when)))
+                    if author.eq_ignore_ascii_case(&login) =>
+                {
+                    (id, url, when.timestamp())
+                }
+                _ => continue,
+            };
+            res.push(FollowUp {
+                comment_id,
+                user_id: *userid,
+                issue_id: get_id(issue_id),
+                repo_owner,
+                repo_name,
+                number,
+                title,
+                url,
+                comment_url,
+                commented_at,
+            });
+        }
+
+        if !page.page_info.has_next_page || fetched >= FOLLOW_UP_MAX_RESULTS {
+            break;
+        }
+        after = page.page_info.end_cursor;
+    }
+
+    Ok(res)
+}
+
+/// Obtain the commits of Pull Request `prid`, along with their signature
+/// verification status. Only the last 100 commits are considered.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `prid` - The Pull Request's database ID.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `number` - The Pull Request's number.
+///
+pub async fn get_pull_request_commits(
+    token: &String,
+    host: &String,
+    prid: &i64,
+    owner: &String,
+    name: &String,
+    number: &i64,
+) -> Result<PullRequestCommitsUpdate, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_pull_request_commits(&owner, &name, &number)
+        .await?;
+    let pr = match res.repository.and_then(|r| r.pull_request) {
+        None => return Err(GHDError::NotFoundError),
+        Some(pr) => pr,
+    };
+
+    // the protection rule is only visible to those allowed to see it.
+    let requires_signatures = pr
+        .base_ref
+        .and_then(|r| r.branch_protection_rule)
+        .map(|r| r.requires_commit_signatures);
+
+    let commits = pr
+        .commits
+        .nodes
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(position, n)| {
+            let (verified, signature_state) = match &n.commit.signature {
+                None => (false, String::from("unsigned")),
+                Some(sig) => (
+                    sig.is_valid
+                        && matches!(sig.state, GitSignatureState::VALID),
+                    get_signature_state(&sig.state),
+                ),
+            };
+            PullRequestCommit {
+                pr_id: *prid,
+                sha: n.commit.oid,
+                position: position as i64,
+                verified,
+                signature_state,
+                message: n.commit.message,
+            }
+        })
+        .collect();
+
+    Ok(PullRequestCommitsUpdate {
+        head_sha: pr.head_ref_oid,
+        requires_signatures,
+        commits,
+    })
+}
+
+/// Obtain the merge requirements for the specified Pull Request from its base
+/// branch's protection rule, along with its approvals and the checks reported
+/// for its head commit. The protection rule may not be visible to the token's
+/// owner, in which case nothing is known to be required.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `number` - The Pull Request's number.
+///
+pub async fn get_pull_request_merge_requirements(
+    token: &String,
+    host: &String,
+    owner: &String,
+    name: &String,
+    number: &i64,
+) -> Result<MergeRequirements, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_pull_request_merge_requirements(&owner, &name, &number)
+        .await?;
+    let pr = match res.repository.and_then(|r| r.pull_request) {
+        None => return Err(GHDError::NotFoundError),
+        Some(pr) => pr,
+    };
+
+    let rule = pr.base_ref.and_then(|r| r.branch_protection_rule);
+    let (required_contexts, required_approvals) = match &rule {
+        None => (vec![], 0),
+        Some(r) => (
+            match &r.required_status_check_contexts {
+                Some(v) if r.requires_status_checks => {
+                    v.iter().flatten().cloned().collect()
+                }
+                _ => vec![],
+            },
+            match r.required_approving_review_count {
+                Some(n) if r.requires_approving_reviews => n,
+                _ => 0,
+            },
+        ),
+    };
+
+    let approvals = match &pr.latest_opinionated_reviews {
+        None => 0,
+        Some(r) => r
+            .nodes
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|n| matches!(n.state, PullRequestReviewState::APPROVED))
+            .count() as i64,
+    };
+
+    let reported_contexts = pr
+        .commits
+        .nodes
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .filter_map(|n| n.commit.status_check_rollup)
+        .flat_map(|r| r.contexts.nodes.unwrap_or_default())
+        .flatten()
+        .map(|c| match c {
+            RollupContext::CheckRun(run) => run.name,
+            RollupContext::StatusContext(ctx) => ctx.context,
+        })
+        .collect();
+
+    Ok(MergeRequirements {
+        base_ref: pr.base_ref_name,
+        protection_visible: rule.is_some(),
+        required_contexts,
+        required_approvals,
+        approvals,
+        reported_contexts,
+    })
+}
+
+/// Obtain the current status of the specified Pull Request, as represented in
+/// the database.
+///
+/// # Arguments
+///
+/// * `token` - String containing the Github API Token.
+/// * `host` - String containing the host the token is valid for.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `number` - The Pull Request's number.
+///
+pub async fn get_pull_request_status(
+    token: &String,
+    host: &String,
+    owner: &String,
+    name: &String,
+    number: &i64,
+) -> Result<Status, GHDError> {
+    let res = GithubGQLRequest::new(&token, &host)
+        .get_pull_request_status(&owner, &name, &number)
+        .await?;
+    let pr = match res.repository.and_then(|r| r.pull_request) {
+        None => return Err(GHDError::NotFoundError),
+        Some(pr) => pr,
+    };
+
+    // unknown values are only kept when consuming whole Pull Requests.
+    let mut unknowns = parse::Unknowns::default();
+    Ok(Status {
+        state: parse::state(&pr.state, &mut unknowns),
+        review_decision: parse::review_decision(
+            &pr.review_decision,
+            &mut unknowns,
+        ),
+        closed_at: pr.closed_at.and_then(|d| d.value).map(|d| d.timestamp()),
+        merged_at: pr.merged_at.and_then(|d| d.value).map(|d| d.timestamp()),
+    })
+}
+
+/// Obtain a lowercase representation of a GraphQL review state.
+///
+fn get_review_state(state: &ReviewState) -> String {
+    match state {
+        ReviewState::Other(v) => v.to_lowercase(),
+        ReviewState::APPROVED => String::from("approved"),
+        ReviewState::CHANGES_REQUESTED => String::from("changes_requested"),
+        ReviewState::COMMENTED => String::from("commented"),
+        ReviewState::DISMISSED => String::from("dismissed"),
+        ReviewState::PENDING => String::from("pending"),
+    }
+}
+
+/// Obtain the REST API's representation of a GraphQL patch status, which is
+/// how file statuses are kept.
+///
+fn get_patch_status(status: &PatchStatus) -> String {
+    match status {
+        PatchStatus::Other(v) => v.to_lowercase(),
+        PatchStatus::ADDED => String::from("added"),
+        PatchStatus::CHANGED => String::from("changed"),
+        PatchStatus::COPIED => String::from("copied"),
+        PatchStatus::DELETED => String::from("removed"),
+        PatchStatus::MODIFIED => String::from("modified"),
+        PatchStatus::RENAMED => String::from("renamed"),
+    }
+}
+
+/// Obtain a lowercase representation of a GraphQL signature state.
+///
+fn get_signature_state(state: &GitSignatureState) -> String {
+    match state {
+        GitSignatureState::Other(v) => v.to_lowercase(),
+        GitSignatureState::BAD_CERT => String::from("bad_cert"),
+        GitSignatureState::BAD_EMAIL => String::from("bad_email"),
+        GitSignatureState::EXPIRED_KEY => String::from("expired_key"),
+        GitSignatureState::GPGVERIFY_ERROR => String::from("gpgverify_error"),
+        GitSignatureState::GPGVERIFY_UNAVAILABLE => {
+            String::from("gpgverify_unavailable")
+        }
+        GitSignatureState::INVALID => String::from("invalid"),
+        GitSignatureState::MALFORMED_SIG => String::from("malformed_sig"),
+        GitSignatureState::NOT_SIGNING_KEY => String::from("not_signing_key"),
+        GitSignatureState::NO_USER => String::from("no_user"),
+        GitSignatureState::OCSP_ERROR => String::from("ocsp_error"),
+        GitSignatureState::OCSP_PENDING => String::from("ocsp_pending"),
+        GitSignatureState::OCSP_REVOKED => String::from("ocsp_revoked"),
+        GitSignatureState::UNKNOWN_KEY => String::from("unknown_key"),
+        GitSignatureState::UNKNOWN_SIG_TYPE => String::from("unknown_sig_type"),
+        GitSignatureState::UNSIGNED => String::from("unsigned"),
+        GitSignatureState::UNVERIFIED_EMAIL => String::from("unverified_email"),
+        GitSignatureState::VALID => String::from("valid"),
+    }
+}
+
+/// Process the resulting pages from the GraphQL calls into something that the
 /// calling layer may understand a bit better, returning a struct containing
 /// both issues and pull requests resulting from the original query.
 ///
 fn process_user_search_results(
-    res: &search_issues::ResponseData,
+    pages: &Vec<search_issues::ResponseData>,
 ) -> Result<UserUpdate, GHDError> {
-    let nodes = match &res.search.nodes {
-        None => {
-            panic!("Unexpected null nodes for user update!");
-        }
-        Some(v) => v,
-    };
+    let mut nodes = vec![];
+    for res in pages {
+        match &res.search.nodes {
+            None => {
+                panic!("Unexpected null nodes for user update!");
+            }
+            Some(v) => nodes.extend(v.iter()),
+        };
+    }
 
     let mut pr_lst: Vec<PullRequest> = vec![];
     let mut issue_lst: Vec<Issue> = vec![];
@@ -282,23 +1510,105 @@ fn process_user_search_results(
         };
 
         if let Some(SearchIssuesSearchNodes::PullRequest(entry)) = &n {
+            let mut issue = issue;
+            let mut unknowns = parse::Unknowns::from_column(&issue.unknown_raw);
+            let review_decision =
+                parse::review_decision(&entry.review_decision, &mut unknowns);
+            let merged_at = parse::optional_timestamp(
+                &entry.merged_at,
+                "merged_at",
+                &mut unknowns,
+            );
+            issue.unknown_raw = unknowns.to_column();
+
             pr_lst.push(PullRequest {
                 issue,
                 is_draft: entry.is_draft,
-                review_decision: match &entry.review_decision {
-                    None => String::from("none"),
-                    Some(PullRequestReviewDecision::APPROVED) => {
-                        String::from("approved")
+                review_decision,
+                merged_at,
+                head_ref: entry.head_ref_name.clone(),
+                author_association: match &entry.author_association {
+                    CommentAuthorAssociation::COLLABORATOR => {
+                        String::from("collaborator")
                     }
-                    Some(PullRequestReviewDecision::CHANGES_REQUESTED) => {
-                        String::from("changes_requested")
+                    CommentAuthorAssociation::CONTRIBUTOR => {
+                        String::from("contributor")
                     }
-                    Some(PullRequestReviewDecision::REVIEW_REQUIRED) => {
-                        String::from("review_required")
+                    CommentAuthorAssociation::FIRST_TIMER => {
+                        String::from("first_timer")
                     }
-                    Some(PullRequestReviewDecision::Other(v)) => v.clone(),
+                    CommentAuthorAssociation::FIRST_TIME_CONTRIBUTOR => {
+                        String::from("first_time_contributor")
+                    }
+                    CommentAuthorAssociation::MANNEQUIN => {
+                        String::from("mannequin")
+                    }
+                    CommentAuthorAssociation::MEMBER => String::from("member"),
+                    CommentAuthorAssociation::NONE => String::from("none"),
+                    CommentAuthorAssociation::OWNER => String::from("owner"),
+                    CommentAuthorAssociation::Other(v) => v.clone(),
+                },
+                review_requests: match &entry.review_requests {
+                    None => vec![],
+                    Some(r) => r
+                        .nodes
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .filter_map(|n| match &n.requested_reviewer {
+                            Some(RequestedReviewer::User(u)) => u.database_id,
+                            _ => None,
+                        })
+                        .collect(),
+                },
+                latest_reviews: match &entry.latest_reviews {
+                    None => vec![],
+                    Some(r) => r
+                        .nodes
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .filter_map(|n| {
+                            let at = n.submitted_at.as_ref()?.value?;
+                            match &n.author {
+                                Some(LatestReviewAuthor::User(u)) => {
+                                    Some(PullRequestReview {
+                                        user_id: u.database_id?,
+                                        login: u.login.clone(),
+                                        state: get_review_state(&n.state),
+                                        submitted_at: at.timestamp(),
+                                    })
+                                }
+                                _ => None,
+                            }
+                        })
+                        .collect(),
                 },
-                merged_at: entry.merged_at,
+                head_committed_at: entry
+                    .commits
+                    .nodes
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .last()
+                    .and_then(|n| n.commit.committed_date.value)
+                    .map(|d| d.timestamp()),
+                ci_state: entry
+                    .commits
+                    .nodes
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .last()
+                    .and_then(|n| n.commit.status_check_rollup.as_ref())
+                    .map(|r| match &r.state {
+                        StatusState::ERROR => String::from("error"),
+                        StatusState::EXPECTED => String::from("expected"),
+                        StatusState::FAILURE => String::from("failure"),
+                        StatusState::PENDING => String::from("pending"),
+                        StatusState::SUCCESS => String::from("success"),
+                        StatusState::Other(v) => v.to_lowercase(),
+                    }),
             });
         } else if let Some(SearchIssuesSearchNodes::Issue(_)) = &n {
             issue_lst.push(issue);
@@ -324,31 +1634,77 @@ fn get_issue_from_issue(node: &SearchIssuesSearchNodesOnIssue) -> Issue {
         Some(SearchIssuesSearchNodesOnIssueAuthor::User(user)) => {
             get_username_and_id(user)
         }
+        Some(SearchIssuesSearchNodesOnIssueAuthor::Bot(bot)) => {
+            (bot.login.clone(), get_id(bot.database_id))
+        }
         Some(_) => {
             panic!("unexpected author user type!");
         }
     };
+    let author_is_bot = bots::is_bot_author(
+        matches!(
+            &node.author,
+            Some(SearchIssuesSearchNodesOnIssueAuthor::Bot(_))
+        ),
+        &username,
+    );
 
-    Issue {
+    let mut unknowns = parse::Unknowns::default();
+    let mut issue = Issue {
         id: get_id(node.database_id),
         title: node.title.clone(),
         number: node.number,
         author: username.clone(),
         author_id: userid,
+        author_is_bot,
         url: node.url.clone(),
         repo_name: node.repository.name.clone(),
         repo_owner: node.repository.owner.login.clone(),
-        state: match &node.state {
-            IssueState::OPEN => String::from("open"),
-            IssueState::CLOSED => String::from("closed"),
-            IssueState::Other(v) => v.clone(),
-        },
-        created_at: node.created_at,
-        updated_at: node.updated_at,
-        closed_at: node.closed_at,
+        state: parse::state(&node.state, &mut unknowns),
+        created_at: parse::required_timestamp(
+            &node.created_at,
+            "created_at",
+            &mut unknowns,
+        ),
+        updated_at: parse::required_timestamp(
+            &node.updated_at,
+            "updated_at",
+            &mut unknowns,
+        ),
+        closed_at: parse::optional_timestamp(
+            &node.closed_at,
+            "closed_at",
+            &mut unknowns,
+        ),
+        unknown_raw: None,
         is_pull_request: false,
         last_viewed: None,
-    }
+        labels: match &node.labels {
+            None => vec![],
+            Some(l) => get_label_names(&l.nodes),
+        },
+        body: node.body.clone(),
+        fixes: vec![],
+        first_response_at: get_first_response(node, &username),
+        references: vec![],
+    };
+    issue.fixes = get_issue_fixes(node, &mut unknowns);
+    issue.references = get_references(
+        issue.id,
+        node.timeline_items
+            .nodes
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|item| match item {
+                TimelineItem::CrossReferencedEvent(e) => Some(e),
+                _ => None,
+            }),
+        &mut unknowns,
+    );
+    issue.unknown_raw = unknowns.to_column();
+
+    issue
 }
 
 /// Obtain the `Issue` associated with the provided GraphQL pull request node.
@@ -363,31 +1719,232 @@ fn get_issue_from_pull_request(
         Some(SearchIssuesSearchNodesOnPullRequestAuthor::User(user)) => {
             get_username_and_id(user)
         }
+        Some(SearchIssuesSearchNodesOnPullRequestAuthor::Bot(bot)) => {
+            (bot.login.clone(), get_id(bot.database_id))
+        }
         Some(_) => {
             panic!("unexpected author user type!");
         }
     };
+    let author_is_bot = bots::is_bot_author(
+        matches!(
+            &node.author,
+            Some(SearchIssuesSearchNodesOnPullRequestAuthor::Bot(_))
+        ),
+        &username,
+    );
 
-    Issue {
-        id: get_id(node.database_id),
-        title: node.title.clone(),
-        number: node.number,
+    let summary = &node.pull_request_summary_fragment;
+    let mut unknowns = parse::Unknowns::default();
+    let mut issue = Issue {
+        id: get_id(summary.database_id),
+        title: summary.title.clone(),
+        number: summary.number,
         author: username.clone(),
         author_id: userid,
-        url: node.url.clone(),
-        repo_name: node.repository.name.clone(),
-        repo_owner: node.repository.owner.login.clone(),
-        state: match &node.state {
-            PullRequestState::OPEN => String::from("open"),
-            PullRequestState::CLOSED => String::from("closed"),
-            PullRequestState::MERGED => String::from("merged"),
-            PullRequestState::Other(v) => v.clone(),
-        },
-        created_at: node.created_at,
-        updated_at: node.updated_at,
-        closed_at: node.closed_at,
+        author_is_bot,
+        url: summary.url.clone(),
+        repo_name: summary.repository.name.clone(),
+        repo_owner: summary.repository.owner.login.clone(),
+        state: parse::state(&node.state, &mut unknowns),
+        created_at: parse::required_timestamp(
+            &node.created_at,
+            "created_at",
+            &mut unknowns,
+        ),
+        updated_at: parse::required_timestamp(
+            &node.updated_at,
+            "updated_at",
+            &mut unknowns,
+        ),
+        closed_at: parse::optional_timestamp(
+            &node.closed_at,
+            "closed_at",
+            &mut unknowns,
+        ),
+        unknown_raw: None,
         is_pull_request: true,
         last_viewed: None,
+        labels: match &node.labels {
+            None => vec![],
+            Some(l) => get_label_names(&l.nodes),
+        },
+        body: node.body.clone(),
+        fixes: vec![],
+        first_response_at: None,
+        references: vec![],
+    };
+    issue.references = get_references(
+        issue.id,
+        node.timeline_items
+            .nodes
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|item| match item {
+                PullRequestReferenceItem::CrossReferencedEvent(e) => Some(e),
+                _ => None,
+            }),
+        &mut unknowns,
+    );
+    issue.unknown_raw = unknowns.to_column();
+
+    issue
+}
+
+/// Obtain the Pull Requests that will close the provided GraphQL issue node
+/// once merged, as cross-referenced from its timeline. A Pull Request may be
+/// referenced more than once; only its latest reference is kept.
+///
+fn get_issue_fixes(
+    node: &SearchIssuesSearchNodesOnIssue,
+    unknowns: &mut parse::Unknowns,
+) -> Vec<IssueFix> {
+    let mut fixes: Vec<IssueFix> = vec![];
+    for item in node.timeline_items.nodes.iter().flatten().flatten() {
+        let event = match item {
+            TimelineItem::CrossReferencedEvent(e) if e.will_close_target => e,
+            _ => continue,
+        };
+        let pr = match &event.source {
+            CrossReferenceSource::PullRequest(pr) => pr,
+            _ => continue,
+        };
+
+        let summary = &pr.pull_request_summary_fragment;
+        let prid = get_id(summary.database_id);
+        fixes.retain(|f| f.pr_id != prid);
+        fixes.push(IssueFix {
+            issue_id: get_id(node.database_id),
+            pr_id: prid,
+            number: summary.number,
+            title: summary.title.clone(),
+            url: summary.url.clone(),
+            repo_owner: summary.repository.owner.login.clone(),
+            repo_name: summary.repository.name.clone(),
+            state: parse::state(&pr.state, unknowns),
+            linked_at: parse::required_timestamp(
+                &event.referenced_at,
+                "linked_at",
+                unknowns,
+            )
+            .timestamp(),
+        });
+    }
+    fixes
+}
+
+/// Obtain the issues and Pull Requests cross-referencing issue `target`
+/// from the provided GraphQL timeline events. A source may reference the
+/// target more than once; only its latest reference is kept.
+///
+fn get_references<'a>(
+    target: i64,
+    events: impl Iterator<Item = &'a CrossReferenceFragment>,
+    unknowns: &mut parse::Unknowns,
+) -> Vec<IssueReference> {
+    let mut refs: Vec<IssueReference> = vec![];
+    for event in events {
+        let referenced_at = parse::required_timestamp(
+            &event.referenced_at,
+            "referenced_at",
+            unknowns,
+        )
+        .timestamp();
+        let reference = match &event.source {
+            CrossReferenceSource::PullRequest(pr) => {
+                let summary = &pr.pull_request_summary_fragment;
+                IssueReference {
+                    target_id: target,
+                    source_id: get_id(summary.database_id),
+                    number: summary.number,
+                    title: summary.title.clone(),
+                    url: summary.url.clone(),
+                    repo_owner: summary.repository.owner.login.clone(),
+                    repo_name: summary.repository.name.clone(),
+                    is_pull_request: true,
+                    state: parse::state(&pr.state, unknowns),
+                    will_close: event.will_close_target,
+                    referenced_at,
+                    depth: 1,
+                }
+            }
+            CrossReferenceSource::Issue(issue) => IssueReference {
+                target_id: target,
+                source_id: get_id(issue.database_id),
+                number: issue.number,
+                title: issue.title.clone(),
+                url: issue.url.clone(),
+                repo_owner: issue.repository.owner.login.clone(),
+                repo_name: issue.repository.name.clone(),
+                is_pull_request: false,
+                state: parse::state(&issue.issue_state, unknowns),
+                will_close: event.will_close_target,
+                referenced_at,
+                depth: 1,
+            },
+        };
+
+        refs.retain(|r| r.source_id != reference.source_id);
+        refs.push(reference);
+    }
+    refs
+}
+
+/// Obtain when someone other than `author` first commented on the provided
+/// GraphQL issue node, if anyone has. Comments by bots aren't a response.
+///
+fn get_first_response(
+    node: &SearchIssuesSearchNodesOnIssue,
+    author: &String,
+) -> Option<i64> {
+    node.comments
+        .nodes
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|c| match &c.author {
+            Some(CommentAuthor::User(user)) => {
+                user.login != *author
+                    && !bots::is_bot_author(false, &user.login)
+            }
+            _ => false,
+        })
+        .filter_map(|c| c.created_at.value)
+        .map(|ts| ts.timestamp())
+        .min()
+}
+
+/// Obtain the names of the labels in the provided GraphQL label nodes.
+///
+fn get_label_names<T>(nodes: &Option<Vec<Option<T>>>) -> Vec<String>
+where
+    T: LabelNode,
+{
+    match nodes {
+        None => vec![],
+        Some(v) => v.iter().flatten().map(|l| l.name().clone()).collect(),
+    }
+}
+
+/// Label nodes are generated as distinct types for issues and pull requests,
+/// even though they share the same shape.
+///
+trait LabelNode {
+    fn name(&self) -> &String;
+}
+
+impl LabelNode for search_issues::SearchIssuesSearchNodesOnIssueLabelsNodes {
+    fn name(&self) -> &String {
+        &self.name
+    }
+}
+
+impl LabelNode
+    for search_issues::SearchIssuesSearchNodesOnPullRequestLabelsNodes
+{
+    fn name(&self) -> &String {
+        &self.name
     }
 }
 
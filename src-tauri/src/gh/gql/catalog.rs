@@ -0,0 +1,355 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+
+use crate::{db::DB, store::Store};
+
+use super::super::backfill;
+use super::queries::{
+    follow_ups, org_repositories, pending_reviews, pull_request_commits,
+    pull_request_files_search, pull_request_merge_requirements,
+    pull_request_status, repo_branches, search_issues, user_info,
+};
+
+/// The whole GraphQL document, which every generated operation carries.
+///
+const DOCUMENT: &str = search_issues::QUERY;
+
+/// A fragment shared across operations.
+///
+pub struct Fragment {
+    pub name: &'static str,
+    pub version: i64,
+}
+
+pub const USER: Fragment = Fragment {
+    name: "UserFragment",
+    version: 1,
+};
+pub const BOT: Fragment = Fragment {
+    name: "BotFragment",
+    version: 1,
+};
+pub const REPOSITORY: Fragment = Fragment {
+    name: "RepositoryFragment",
+    version: 1,
+};
+/// What identifies a Pull Request wherever it's listed.
+pub const PULL_REQUEST_SUMMARY: Fragment = Fragment {
+    name: "PullRequestSummaryFragment",
+    version: 1,
+};
+pub const REVIEW: Fragment = Fragment {
+    name: "ReviewFragment",
+    version: 1,
+};
+pub const CROSS_REFERENCE: Fragment = Fragment {
+    name: "CrossReferenceFragment",
+    version: 1,
+};
+
+/// What must be synced again once an operation's version changes.
+///
+pub enum Resync {
+    /// Every user's history, through a backfill.
+    UserHistory,
+    /// The sync jobs whose refresh keys start with the prefix.
+    Sync(&'static str),
+}
+
+/// A GraphQL operation, along with the fragments it spreads, directly or
+/// through other fragments.
+///
+pub struct Operation {
+    pub name: &'static str,
+    pub version: i64,
+    pub fragments: &'static [&'static Fragment],
+    pub resync: &'static [Resync],
+}
+
+impl Operation {
+    /// Obtain the operation's version tag, covering its fragments' versions;
+    /// e.g., `SearchIssues@1+UserFragment@1`.
+    ///
+    pub fn tag(self: &Self) -> String {
+        std::iter::once(format!("{}@{}", self.name, self.version))
+            .chain(
+                self.fragments
+                    .iter()
+                    .map(|f| format!("{}@{}", f.name, f.version)),
+            )
+            .collect::<Vec<String>>()
+            .join("+")
+    }
+}
+
+/// Every operation in `queries.graphql`. Operations and fragments carry a
+/// version, to be bumped whenever the fields they select change; what was
+/// synced with an older version is then synced again, so new fields aren't
+/// left unset on what's already cached.
+///
+pub const OPERATIONS: &[Operation] = &[
+    Operation {
+        name: user_info::OPERATION_NAME,
+        version: 1,
+        fragments: &[&REPOSITORY],
+        resync: &[],
+    },
+    Operation {
+        name: search_issues::OPERATION_NAME,
+        version: 4,
+        fragments: &[
+            &USER,
+            &BOT,
+            &REPOSITORY,
+            &PULL_REQUEST_SUMMARY,
+            &REVIEW,
+            &CROSS_REFERENCE,
+        ],
+        resync: &[
+            Resync::UserHistory,
+            Resync::Sync("org_scope:"),
+            Resync::Sync("team_mentions:"),
+            Resync::Sync("anon:"),
+        ],
+    },
+    Operation {
+        name: repo_branches::OPERATION_NAME,
+        version: 1,
+        fragments: &[],
+        resync: &[Resync::Sync("branches:")],
+    },
+    Operation {
+        name: pull_request_commits::OPERATION_NAME,
+        version: 1,
+        fragments: &[],
+        resync: &[Resync::Sync("commits:")],
+    },
+    Operation {
+        name: pull_request_merge_requirements::OPERATION_NAME,
+        version: 1,
+        fragments: &[],
+        resync: &[Resync::Sync("blockers:")],
+    },
+    Operation {
+        name: pull_request_status::OPERATION_NAME,
+        version: 1,
+        fragments: &[],
+        resync: &[],
+    },
+    Operation {
+        name: org_repositories::OPERATION_NAME,
+        version: 1,
+        fragments: &[],
+        resync: &[],
+    },
+    Operation {
+        name: pull_request_files_search::OPERATION_NAME,
+        version: 1,
+        fragments: &[],
+        resync: &[Resync::Sync("files:")],
+    },
+    Operation {
+        name: follow_ups::OPERATION_NAME,
+        version: 1,
+        fragments: &[&REPOSITORY, &PULL_REQUEST_SUMMARY],
+        resync: &[Resync::Sync("follow_ups:")],
+    },
+    Operation {
+        name: pending_reviews::OPERATION_NAME,
+        version: 1,
+        fragments: &[&REPOSITORY, &PULL_REQUEST_SUMMARY],
+        resync: &[Resync::Sync("pending_reviews:")],
+    },
+];
+
+/// Obtain the text of the definition starting with `header` in the GraphQL
+/// document, up to the next definition.
+///
+fn get_definition(header: &str) -> Option<&'static str> {
+    let start = DOCUMENT.find(header)?;
+    let rest = &DOCUMENT[start..];
+    let end = ["\nquery ", "\nfragment ", "\nmutation "]
+        .iter()
+        .filter_map(|d| rest[1..].find(d).map(|i| i + 1))
+        .min()
+        .unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// Obtain the fragments spread by `text`, and by the fragments it spreads.
+///
+fn get_spreads(text: &str, found: &mut BTreeSet<String>) -> Result<(), String> {
+    let re = regex::Regex::new(r"\.\.\.([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    for cap in re.captures_iter(text) {
+        let name = cap[1].to_string();
+        if found.contains(&name) {
+            continue;
+        }
+        let def = match get_definition(&format!("fragment {} on ", name)) {
+            Some(d) => d,
+            None => return Err(format!("fragment {} not defined", name)),
+        };
+        found.insert(name);
+        get_spreads(def, found)?;
+    }
+    Ok(())
+}
+
+/// Check the catalog against the GraphQL document: every operation must be
+/// catalogued, and declare exactly the fragments it spreads. Returns the
+/// problems found, if any.
+///
+pub fn validate() -> Vec<String> {
+    let mut problems = vec![];
+
+    let re =
+        regex::Regex::new(r"(?m)^(query|mutation) ([A-Za-z0-9_]+)").unwrap();
+    for cap in re.captures_iter(DOCUMENT) {
+        if !OPERATIONS.iter().any(|op| op.name == &cap[2]) {
+            problems.push(format!("operation {} not catalogued", &cap[2]));
+        }
+    }
+
+    for op in OPERATIONS {
+        let def = match get_definition(&format!("query {}", op.name)) {
+            Some(d) => d,
+            None => {
+                problems.push(format!("operation {} not defined", op.name));
+                continue;
+            }
+        };
+
+        let mut spread = BTreeSet::new();
+        if let Err(err) = get_spreads(def, &mut spread) {
+            problems.push(format!("operation {}: {}", op.name, err));
+            continue;
+        }
+        let declared = op
+            .fragments
+            .iter()
+            .map(|f| f.name.to_string())
+            .collect::<BTreeSet<String>>();
+        if spread != declared {
+            problems.push(format!(
+                "operation {} spreads {:?}, declares {:?}",
+                op.name, spread, declared
+            ));
+        }
+    }
+    problems
+}
+
+/// Sync again whatever was synced with the previous version of `op`.
+///
+async fn resync(db: &DB, op: &Operation) {
+    let now = chrono::Utc::now().timestamp();
+    for what in op.resync {
+        match what {
+            Resync::Sync(prefix) => {
+                db.clear_sync_refresh(prefix).await;
+            }
+            Resync::UserHistory => {
+                let users = match sqlx::query_as::<_, (i64, Option<i64>)>(
+                    "
+                    SELECT
+                        user_refresh.id,
+                        COALESCE(
+                            user_history.synced_from,
+                            (
+                                SELECT MIN(issues.created_at)
+                                FROM issues INNER JOIN user_issues
+                                    ON user_issues.issue_id = issues.id
+                                WHERE user_issues.user_id = user_refresh.id
+                            )
+                        )
+                    FROM user_refresh LEFT JOIN user_history
+                        ON user_history.user_id = user_refresh.id
+                    WHERE user_refresh.refresh_at > 0
+                    ",
+                )
+                .fetch_all(db.pool())
+                .await
+                {
+                    Ok(res) => res,
+                    Err(err) => {
+                        panic!("Unable to obtain users to resync: {}", err);
+                    }
+                };
+
+                for (userid, from) in users {
+                    if let Some(from) = from {
+                        let _ =
+                            backfill::request(&db, &userid, &from, &now).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Record the version tags of the catalogued operations, syncing again what
+/// was synced with a different version. On first run, versions are recorded
+/// without syncing anything again. Should be called on start up.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn reconcile_versions(db: &DB) {
+    for problem in validate() {
+        log::error!("graphql catalog: {}", problem);
+    }
+
+    let known = match sqlx::query_as::<_, (String, String)>(
+        "SELECT operation, tag FROM gql_versions",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain graphql versions: {}", err);
+        }
+    };
+
+    for op in OPERATIONS {
+        let tag = op.tag();
+        match known.iter().find(|(name, _)| name == op.name) {
+            Some((_, t)) if *t == tag => continue,
+            Some((_, t)) => {
+                log::info!("graphql operation changed from {} to {}", t, tag);
+                resync(&db, &op).await;
+            }
+            None if known.is_empty() => {}
+            None => resync(&db, &op).await,
+        };
+
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO gql_versions (operation, tag, recorded_at)
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&op.name)
+        .bind(&tag)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to record graphql version: {}", err);
+        });
+    }
+}
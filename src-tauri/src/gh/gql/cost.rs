@@ -0,0 +1,71 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// GitHub's limit on the number of nodes a single GraphQL query may request.
+///
+pub const MAX_NODES: i64 = 500_000;
+
+/// The most points we let a single GraphQL query cost. Queries estimated to
+/// cost more are split into several smaller ones, which GitHub is less likely
+/// to time out on, and which spread their cost across refreshes should one
+/// fail midway. A full page of `SearchIssues` costs about 9 points.
+///
+pub const MAX_QUERY_POINTS: i64 = 10;
+
+/// The connections requested for each node of a paginated GraphQL query, by
+/// their page size.
+///
+pub struct QueryShape {
+    pub children: &'static [i64],
+}
+
+/// The connections requested for each `SearchIssues` node, for either Pull
+/// Requests or Issues: review requests, reviews, latest reviews, labels, last
+/// commit, and comments, along with the cross-references on Pull Requests and
+/// on Issues.
+///
+pub const SEARCH_ISSUES: QueryShape = QueryShape {
+    children: &[30, 1, 30, 20, 1, 100, 20, 20],
+};
+
+impl QueryShape {
+    /// Estimate how many nodes a page of `first` entries may return, at most.
+    ///
+    pub fn nodes(self: &Self, first: i64) -> i64 {
+        first * (1 + self.children.iter().sum::<i64>())
+    }
+
+    /// Estimate how many points a page of `first` entries costs, following
+    /// GitHub's documented calculation: one request per connection to
+    /// fulfill, per hundred, rounded up, at least one point.
+    ///
+    pub fn points(self: &Self, first: i64) -> i64 {
+        let requests = 1 + first * self.children.len() as i64;
+        std::cmp::max(1, (requests + 99) / 100)
+    }
+
+    /// Obtain the largest page size, up to `wanted`, whose query stays within
+    /// both GitHub's node limit and our point budget.
+    ///
+    pub fn page_size(self: &Self, wanted: i64) -> i64 {
+        let mut first = wanted;
+        while first > 1
+            && (self.nodes(first) > MAX_NODES
+                || self.points(first) > MAX_QUERY_POINTS)
+        {
+            first -= 1;
+        }
+        first
+    }
+}
@@ -1,3 +1,4 @@
 #[allow(clippy::upper_case_acronyms)]
 pub type URI = String;
-pub type DateTime = chrono::DateTime<chrono::Utc>;
+pub type DateTime = crate::gh::types::parse::Timestamp;
+pub type GitObjectID = String;
@@ -4,7 +4,7 @@ pub mod user_info {
     #![allow(dead_code)]
     use std::result::Result;
     pub const OPERATION_NAME: &str = "UserInfo";
-    pub const QUERY : & str = "query UserInfo($login: String!) {\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          owner {\n            __typename\n            login\n          }\n          name\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nquery SearchIssues($q: String!) {\n  search(first: 100, query: $q, type: ISSUE) {\n    nodes {\n      __typename\n      ... on PullRequest {\n        title\n        number\n        databaseId\n        author {\n          __typename\n          ...UserFragment\n        }\n        repository {\n          owner {\n            __typename\n            login\n          }\n          name\n        }\n        url\n        state\n        isDraft\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n        }\n        repository {\n          owner {\n            __typename\n            login\n          }\n          name\n        }\n        state\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
     use super::*;
     use serde::{Deserialize, Serialize};
     #[allow(dead_code)]
@@ -62,6 +62,23 @@ pub mod user_info {
     }
     impl Variables {}
     #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragment {
+        pub owner: RepositoryFragmentOwner,
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragmentOwner {
+        pub login: String,
+        #[serde(flatten)]
+        pub on: RepositoryFragmentOwnerOn,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum RepositoryFragmentOwnerOn {
+        Organization,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
     pub struct ResponseData {
         pub user: Option<UserInfoUser>,
     }
@@ -105,23 +122,7 @@ pub mod user_info {
     pub struct UserInfoUserPullRequestsNodesMilestone {
         pub id: ID,
     }
-    #[derive(Deserialize, Debug)]
-    pub struct UserInfoUserPullRequestsNodesRepository {
-        pub owner: UserInfoUserPullRequestsNodesRepositoryOwner,
-        pub name: String,
-    }
-    #[derive(Deserialize, Debug)]
-    pub struct UserInfoUserPullRequestsNodesRepositoryOwner {
-        pub login: String,
-        #[serde(flatten)]
-        pub on: UserInfoUserPullRequestsNodesRepositoryOwnerOn,
-    }
-    #[derive(Deserialize, Debug)]
-    #[serde(tag = "__typename")]
-    pub enum UserInfoUserPullRequestsNodesRepositoryOwnerOn {
-        Organization,
-        User,
-    }
+    pub type UserInfoUserPullRequestsNodesRepository = RepositoryFragment;
     #[derive(Deserialize, Debug)]
     pub struct UserInfoUserPullRequestsNodesReviews {
         #[serde(rename = "totalCount")]
@@ -190,7 +191,7 @@ pub mod search_issues {
     #![allow(dead_code)]
     use std::result::Result;
     pub const OPERATION_NAME: &str = "SearchIssues";
-    pub const QUERY : & str = "query UserInfo($login: String!) {\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          owner {\n            __typename\n            login\n          }\n          name\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nquery SearchIssues($q: String!) {\n  search(first: 100, query: $q, type: ISSUE) {\n    nodes {\n      __typename\n      ... on PullRequest {\n        title\n        number\n        databaseId\n        author {\n          __typename\n          ...UserFragment\n        }\n        repository {\n          owner {\n            __typename\n            login\n          }\n          name\n        }\n        url\n        state\n        isDraft\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n        }\n        repository {\n          owner {\n            __typename\n            login\n          }\n          name\n        }\n        state\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
     use super::*;
     use serde::{Deserialize, Serialize};
     #[allow(dead_code)]
@@ -204,6 +205,58 @@ pub mod search_issues {
     type DateTime = crate::gh::gql::custom_types::DateTime;
     type URI = crate::gh::gql::custom_types::URI;
     #[derive(Debug)]
+    pub enum CommentAuthorAssociation {
+        COLLABORATOR,
+        CONTRIBUTOR,
+        FIRST_TIMER,
+        FIRST_TIME_CONTRIBUTOR,
+        MANNEQUIN,
+        MEMBER,
+        NONE,
+        OWNER,
+        Other(String),
+    }
+    impl ::serde::Serialize for CommentAuthorAssociation {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                CommentAuthorAssociation::COLLABORATOR => "COLLABORATOR",
+                CommentAuthorAssociation::CONTRIBUTOR => "CONTRIBUTOR",
+                CommentAuthorAssociation::FIRST_TIMER => "FIRST_TIMER",
+                CommentAuthorAssociation::FIRST_TIME_CONTRIBUTOR => {
+                    "FIRST_TIME_CONTRIBUTOR"
+                }
+                CommentAuthorAssociation::MANNEQUIN => "MANNEQUIN",
+                CommentAuthorAssociation::MEMBER => "MEMBER",
+                CommentAuthorAssociation::NONE => "NONE",
+                CommentAuthorAssociation::OWNER => "OWNER",
+                CommentAuthorAssociation::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for CommentAuthorAssociation {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "COLLABORATOR" => Ok(CommentAuthorAssociation::COLLABORATOR),
+                "CONTRIBUTOR" => Ok(CommentAuthorAssociation::CONTRIBUTOR),
+                "FIRST_TIMER" => Ok(CommentAuthorAssociation::FIRST_TIMER),
+                "FIRST_TIME_CONTRIBUTOR" => {
+                    Ok(CommentAuthorAssociation::FIRST_TIME_CONTRIBUTOR)
+                }
+                "MANNEQUIN" => Ok(CommentAuthorAssociation::MANNEQUIN),
+                "MEMBER" => Ok(CommentAuthorAssociation::MEMBER),
+                "NONE" => Ok(CommentAuthorAssociation::NONE),
+                "OWNER" => Ok(CommentAuthorAssociation::OWNER),
+                _ => Ok(CommentAuthorAssociation::Other(s)),
+            }
+        }
+    }
+    #[derive(Debug)]
     pub enum IssueState {
         CLOSED,
         OPEN,
@@ -273,6 +326,49 @@ pub mod search_issues {
         }
     }
     #[derive(Debug)]
+    pub enum PullRequestReviewState {
+        APPROVED,
+        CHANGES_REQUESTED,
+        COMMENTED,
+        DISMISSED,
+        PENDING,
+        Other(String),
+    }
+    impl ::serde::Serialize for PullRequestReviewState {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                PullRequestReviewState::APPROVED => "APPROVED",
+                PullRequestReviewState::CHANGES_REQUESTED => {
+                    "CHANGES_REQUESTED"
+                }
+                PullRequestReviewState::COMMENTED => "COMMENTED",
+                PullRequestReviewState::DISMISSED => "DISMISSED",
+                PullRequestReviewState::PENDING => "PENDING",
+                PullRequestReviewState::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for PullRequestReviewState {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "APPROVED" => Ok(PullRequestReviewState::APPROVED),
+                "CHANGES_REQUESTED" => {
+                    Ok(PullRequestReviewState::CHANGES_REQUESTED)
+                }
+                "COMMENTED" => Ok(PullRequestReviewState::COMMENTED),
+                "DISMISSED" => Ok(PullRequestReviewState::DISMISSED),
+                "PENDING" => Ok(PullRequestReviewState::PENDING),
+                _ => Ok(PullRequestReviewState::Other(s)),
+            }
+        }
+    }
+    #[derive(Debug)]
     pub enum PullRequestState {
         CLOSED,
         MERGED,
@@ -305,9 +401,50 @@ pub mod search_issues {
             }
         }
     }
+    #[derive(Debug)]
+    pub enum StatusState {
+        ERROR,
+        EXPECTED,
+        FAILURE,
+        PENDING,
+        SUCCESS,
+        Other(String),
+    }
+    impl ::serde::Serialize for StatusState {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                StatusState::ERROR => "ERROR",
+                StatusState::EXPECTED => "EXPECTED",
+                StatusState::FAILURE => "FAILURE",
+                StatusState::PENDING => "PENDING",
+                StatusState::SUCCESS => "SUCCESS",
+                StatusState::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for StatusState {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "ERROR" => Ok(StatusState::ERROR),
+                "EXPECTED" => Ok(StatusState::EXPECTED),
+                "FAILURE" => Ok(StatusState::FAILURE),
+                "PENDING" => Ok(StatusState::PENDING),
+                "SUCCESS" => Ok(StatusState::SUCCESS),
+                _ => Ok(StatusState::Other(s)),
+            }
+        }
+    }
     #[derive(Serialize)]
     pub struct Variables {
         pub q: String,
+        pub first: Int,
+        pub after: Option<String>,
     }
     impl Variables {}
     #[derive(Deserialize, Debug)]
@@ -317,14 +454,105 @@ pub mod search_issues {
         pub database_id: Option<Int>,
     }
     #[derive(Deserialize, Debug)]
+    pub struct BotFragment {
+        pub login: String,
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragment {
+        pub owner: RepositoryFragmentOwner,
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragmentOwner {
+        pub login: String,
+        #[serde(flatten)]
+        pub on: RepositoryFragmentOwnerOn,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum RepositoryFragmentOwnerOn {
+        Organization,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestSummaryFragment {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub number: Int,
+        pub title: String,
+        pub url: URI,
+        pub repository: PullRequestSummaryFragmentRepository,
+    }
+    pub type PullRequestSummaryFragmentRepository = RepositoryFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct ReviewFragment {
+        pub author: Option<ReviewFragmentAuthor>,
+        pub state: PullRequestReviewState,
+        #[serde(rename = "submittedAt")]
+        pub submitted_at: Option<DateTime>,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum ReviewFragmentAuthor {
+        Bot,
+        EnterpriseUserAccount,
+        Mannequin,
+        Organization,
+        User(ReviewFragmentAuthorOnUser),
+    }
+    pub type ReviewFragmentAuthorOnUser = UserFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct CrossReferenceFragment {
+        #[serde(rename = "willCloseTarget")]
+        pub will_close_target: Boolean,
+        #[serde(rename = "referencedAt")]
+        pub referenced_at: DateTime,
+        pub source: CrossReferenceFragmentSource,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum CrossReferenceFragmentSource {
+        Issue(CrossReferenceFragmentSourceOnIssue),
+        PullRequest(CrossReferenceFragmentSourceOnPullRequest),
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct CrossReferenceFragmentSourceOnIssue {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub number: Int,
+        pub title: String,
+        pub url: URI,
+        pub repository: CrossReferenceFragmentSourceOnIssueRepository,
+        #[serde(rename = "issueState")]
+        pub issue_state: IssueState,
+    }
+    pub type CrossReferenceFragmentSourceOnIssueRepository = RepositoryFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct CrossReferenceFragmentSourceOnPullRequest {
+        #[serde(flatten)]
+        pub pull_request_summary_fragment: PullRequestSummaryFragment,
+        pub state: PullRequestState,
+    }
+    #[derive(Deserialize, Debug)]
     pub struct ResponseData {
         pub search: SearchIssuesSearch,
     }
     #[derive(Deserialize, Debug)]
     pub struct SearchIssuesSearch {
+        #[serde(rename = "pageInfo")]
+        pub page_info: SearchIssuesSearchPageInfo,
         pub nodes: Option<Vec<Option<SearchIssuesSearchNodes>>>,
     }
     #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Debug)]
     #[serde(tag = "__typename")]
     pub enum SearchIssuesSearchNodes {
         App,
@@ -345,7 +573,9 @@ pub mod search_issues {
         pub url: URI,
         pub author: Option<SearchIssuesSearchNodesOnIssueAuthor>,
         pub repository: SearchIssuesSearchNodesOnIssueRepository,
+        pub body: String,
         pub state: IssueState,
+        pub labels: Option<SearchIssuesSearchNodesOnIssueLabels>,
         #[serde(rename = "createdAt")]
         pub created_at: DateTime,
         #[serde(rename = "updatedAt")]
@@ -353,33 +583,29 @@ pub mod search_issues {
         #[serde(rename = "closedAt")]
         pub closed_at: Option<DateTime>,
         pub comments: SearchIssuesSearchNodesOnIssueComments,
+        #[serde(rename = "timelineItems")]
+        pub timeline_items: SearchIssuesSearchNodesOnIssueTimelineItems,
     }
     #[derive(Deserialize, Debug)]
     #[serde(tag = "__typename")]
     pub enum SearchIssuesSearchNodesOnIssueAuthor {
-        Bot,
+        Bot(SearchIssuesSearchNodesOnIssueAuthorOnBot),
         EnterpriseUserAccount,
         Mannequin,
         Organization,
         User(SearchIssuesSearchNodesOnIssueAuthorOnUser),
     }
+    pub type SearchIssuesSearchNodesOnIssueAuthorOnBot = BotFragment;
     pub type SearchIssuesSearchNodesOnIssueAuthorOnUser = UserFragment;
+    pub type SearchIssuesSearchNodesOnIssueRepository = RepositoryFragment;
     #[derive(Deserialize, Debug)]
-    pub struct SearchIssuesSearchNodesOnIssueRepository {
-        pub owner: SearchIssuesSearchNodesOnIssueRepositoryOwner,
-        pub name: String,
-    }
-    #[derive(Deserialize, Debug)]
-    pub struct SearchIssuesSearchNodesOnIssueRepositoryOwner {
-        pub login: String,
-        #[serde(flatten)]
-        pub on: SearchIssuesSearchNodesOnIssueRepositoryOwnerOn,
+    pub struct SearchIssuesSearchNodesOnIssueLabels {
+        pub nodes:
+            Option<Vec<Option<SearchIssuesSearchNodesOnIssueLabelsNodes>>>,
     }
     #[derive(Deserialize, Debug)]
-    #[serde(tag = "__typename")]
-    pub enum SearchIssuesSearchNodesOnIssueRepositoryOwnerOn {
-        Organization,
-        User,
+    pub struct SearchIssuesSearchNodesOnIssueLabelsNodes {
+        pub name: String,
     }
     #[derive(Deserialize, Debug)]
     pub struct SearchIssuesSearchNodesOnIssueComments {
@@ -389,6 +615,8 @@ pub mod search_issues {
     #[derive(Deserialize, Debug)]
     pub struct SearchIssuesSearchNodesOnIssueCommentsNodes {
         pub author: Option<SearchIssuesSearchNodesOnIssueCommentsNodesAuthor>,
+        #[serde(rename = "createdAt")]
+        pub created_at: DateTime,
     }
     #[derive(Deserialize, Debug)]
     #[serde(tag = "__typename")]
@@ -402,17 +630,30 @@ pub mod search_issues {
     pub type SearchIssuesSearchNodesOnIssueCommentsNodesAuthorOnUser =
         UserFragment;
     #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnIssueTimelineItems {
+        pub nodes: Option<
+            Vec<Option<SearchIssuesSearchNodesOnIssueTimelineItemsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum SearchIssuesSearchNodesOnIssueTimelineItemsNodes {
+        AddedToProjectEvent , AssignedEvent , ClosedEvent , CommentDeletedEvent , ConnectedEvent , ConvertedNoteToIssueEvent , ConvertedToDiscussionEvent , CrossReferencedEvent (SearchIssuesSearchNodesOnIssueTimelineItemsNodesOnCrossReferencedEvent) , DemilestonedEvent , DisconnectedEvent , IssueComment , LabeledEvent , LockedEvent , MarkedAsDuplicateEvent , MentionedEvent , MilestonedEvent , MovedColumnsInProjectEvent , PinnedEvent , ReferencedEvent , RemovedFromProjectEvent , RenamedTitleEvent , ReopenedEvent , SubscribedEvent , TransferredEvent , UnassignedEvent , UnlabeledEvent , UnlockedEvent , UnmarkedAsDuplicateEvent , UnpinnedEvent , UnsubscribedEvent , UserBlockedEvent }
+    pub type SearchIssuesSearchNodesOnIssueTimelineItemsNodesOnCrossReferencedEvent =
+        CrossReferenceFragment;
+    #[derive(Deserialize, Debug)]
     pub struct SearchIssuesSearchNodesOnPullRequest {
-        pub title: String,
-        pub number: Int,
-        #[serde(rename = "databaseId")]
-        pub database_id: Option<Int>,
+        #[serde(flatten)]
+        pub pull_request_summary_fragment: PullRequestSummaryFragment,
         pub author: Option<SearchIssuesSearchNodesOnPullRequestAuthor>,
-        pub repository: SearchIssuesSearchNodesOnPullRequestRepository,
-        pub url: URI,
         pub state: PullRequestState,
         #[serde(rename = "isDraft")]
         pub is_draft: Boolean,
+        #[serde(rename = "headRefName")]
+        pub head_ref_name: String,
+        pub body: String,
+        #[serde(rename = "authorAssociation")]
+        pub author_association: CommentAuthorAssociation,
         #[serde(rename = "totalCommentsCount")]
         pub total_comments_count: Option<Int>,
         #[serde(rename = "reviewRequests")]
@@ -421,6 +662,10 @@ pub mod search_issues {
         #[serde(rename = "reviewDecision")]
         pub review_decision: Option<PullRequestReviewDecision>,
         pub reviews: Option<SearchIssuesSearchNodesOnPullRequestReviews>,
+        #[serde(rename = "latestReviews")]
+        pub latest_reviews:
+            Option<SearchIssuesSearchNodesOnPullRequestLatestReviews>,
+        pub labels: Option<SearchIssuesSearchNodesOnPullRequestLabels>,
         #[serde(rename = "createdAt")]
         pub created_at: DateTime,
         #[serde(rename = "updatedAt")]
@@ -429,35 +674,22 @@ pub mod search_issues {
         pub closed_at: Option<DateTime>,
         #[serde(rename = "mergedAt")]
         pub merged_at: Option<DateTime>,
+        pub commits: SearchIssuesSearchNodesOnPullRequestCommits,
+        #[serde(rename = "timelineItems")]
+        pub timeline_items: SearchIssuesSearchNodesOnPullRequestTimelineItems,
     }
     #[derive(Deserialize, Debug)]
     #[serde(tag = "__typename")]
     pub enum SearchIssuesSearchNodesOnPullRequestAuthor {
-        Bot,
+        Bot(SearchIssuesSearchNodesOnPullRequestAuthorOnBot),
         EnterpriseUserAccount,
         Mannequin,
         Organization,
         User(SearchIssuesSearchNodesOnPullRequestAuthorOnUser),
     }
+    pub type SearchIssuesSearchNodesOnPullRequestAuthorOnBot = BotFragment;
     pub type SearchIssuesSearchNodesOnPullRequestAuthorOnUser = UserFragment;
     #[derive(Deserialize, Debug)]
-    pub struct SearchIssuesSearchNodesOnPullRequestRepository {
-        pub owner: SearchIssuesSearchNodesOnPullRequestRepositoryOwner,
-        pub name: String,
-    }
-    #[derive(Deserialize, Debug)]
-    pub struct SearchIssuesSearchNodesOnPullRequestRepositoryOwner {
-        pub login: String,
-        #[serde(flatten)]
-        pub on: SearchIssuesSearchNodesOnPullRequestRepositoryOwnerOn,
-    }
-    #[derive(Deserialize, Debug)]
-    #[serde(tag = "__typename")]
-    pub enum SearchIssuesSearchNodesOnPullRequestRepositoryOwnerOn {
-        Organization,
-        User,
-    }
-    #[derive(Deserialize, Debug)]
     pub struct SearchIssuesSearchNodesOnPullRequestReviewRequests {
         pub nodes: Option<
             Vec<
@@ -479,6 +711,53 @@ pub mod search_issues {
         #[serde(rename = "totalCount")]
         pub total_count: Int,
     }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestLatestReviews {
+        pub nodes: Option<
+            Vec<Option<SearchIssuesSearchNodesOnPullRequestLatestReviewsNodes>>,
+        >,
+    }
+    pub type SearchIssuesSearchNodesOnPullRequestLatestReviewsNodes =
+        ReviewFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestLabels {
+        pub nodes: Option<
+            Vec<Option<SearchIssuesSearchNodesOnPullRequestLabelsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestLabelsNodes {
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestCommits {
+        pub nodes: Option<
+            Vec<Option<SearchIssuesSearchNodesOnPullRequestCommitsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestCommitsNodes {
+        pub commit: SearchIssuesSearchNodesOnPullRequestCommitsNodesCommit,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestCommitsNodesCommit { # [serde (rename = "committedDate")] pub committed_date : DateTime , # [serde (rename = "statusCheckRollup")] pub status_check_rollup : Option < SearchIssuesSearchNodesOnPullRequestCommitsNodesCommitStatusCheckRollup > , }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestCommitsNodesCommitStatusCheckRollup
+    {
+        pub state: StatusState,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct SearchIssuesSearchNodesOnPullRequestTimelineItems {
+        pub nodes: Option<
+            Vec<Option<SearchIssuesSearchNodesOnPullRequestTimelineItemsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum SearchIssuesSearchNodesOnPullRequestTimelineItemsNodes {
+        AddedToProjectEvent , AssignedEvent , AutoMergeDisabledEvent , AutoMergeEnabledEvent , AutoRebaseEnabledEvent , AutoSquashEnabledEvent , AutomaticBaseChangeFailedEvent , AutomaticBaseChangeSucceededEvent , BaseRefChangedEvent , BaseRefDeletedEvent , BaseRefForcePushedEvent , ClosedEvent , CommentDeletedEvent , ConnectedEvent , ConvertToDraftEvent , ConvertedNoteToIssueEvent , ConvertedToDiscussionEvent , CrossReferencedEvent (SearchIssuesSearchNodesOnPullRequestTimelineItemsNodesOnCrossReferencedEvent) , DemilestonedEvent , DeployedEvent , DeploymentEnvironmentChangedEvent , DisconnectedEvent , HeadRefDeletedEvent , HeadRefForcePushedEvent , HeadRefRestoredEvent , IssueComment , LabeledEvent , LockedEvent , MarkedAsDuplicateEvent , MentionedEvent , MergedEvent , MilestonedEvent , MovedColumnsInProjectEvent , PinnedEvent , PullRequestCommit , PullRequestCommitCommentThread , PullRequestReview , PullRequestReviewThread , PullRequestRevisionMarker , ReadyForReviewEvent , ReferencedEvent , RemovedFromProjectEvent , RenamedTitleEvent , ReopenedEvent , ReviewDismissedEvent , ReviewRequestRemovedEvent , ReviewRequestedEvent , SubscribedEvent , TransferredEvent , UnassignedEvent , UnlabeledEvent , UnlockedEvent , UnmarkedAsDuplicateEvent , UnpinnedEvent , UnsubscribedEvent , UserBlockedEvent }
+    pub type SearchIssuesSearchNodesOnPullRequestTimelineItemsNodesOnCrossReferencedEvent =
+        CrossReferenceFragment;
 }
 impl graphql_client::GraphQLQuery for SearchIssues {
     type Variables = search_issues::Variables;
@@ -493,3 +772,1119 @@ impl graphql_client::GraphQLQuery for SearchIssues {
         }
     }
 }
+pub struct RepoBranches;
+pub mod repo_branches {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "RepoBranches";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::gh::gql::custom_types::DateTime;
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub owner: String,
+        pub name: String,
+        pub after: Option<String>,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub repository: Option<RepoBranchesRepository>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepository {
+        #[serde(rename = "defaultBranchRef")]
+        pub default_branch_ref: Option<RepoBranchesRepositoryDefaultBranchRef>,
+        #[serde(rename = "primaryLanguage")]
+        pub primary_language: Option<RepoBranchesRepositoryPrimaryLanguage>,
+        #[serde(rename = "repositoryTopics")]
+        pub repository_topics: RepoBranchesRepositoryRepositoryTopics,
+        pub refs: Option<RepoBranchesRepositoryRefs>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryDefaultBranchRef {
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryPrimaryLanguage {
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRepositoryTopics {
+        pub nodes:
+            Option<Vec<Option<RepoBranchesRepositoryRepositoryTopicsNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRepositoryTopicsNodes {
+        pub topic: RepoBranchesRepositoryRepositoryTopicsNodesTopic,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRepositoryTopicsNodesTopic {
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRefs {
+        #[serde(rename = "pageInfo")]
+        pub page_info: RepoBranchesRepositoryRefsPageInfo,
+        pub nodes: Option<Vec<Option<RepoBranchesRepositoryRefsNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRefsPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRefsNodes {
+        pub name: String,
+        pub target: Option<RepoBranchesRepositoryRefsNodesTarget>,
+        #[serde(rename = "associatedPullRequests")]
+        pub associated_pull_requests:
+            RepoBranchesRepositoryRefsNodesAssociatedPullRequests,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum RepoBranchesRepositoryRefsNodesTarget {
+        Blob,
+        Commit(RepoBranchesRepositoryRefsNodesTargetOnCommit),
+        Tag,
+        Tree,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRefsNodesTargetOnCommit {
+        #[serde(rename = "committedDate")]
+        pub committed_date: DateTime,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRefsNodesAssociatedPullRequests {
+        pub nodes: Option<
+            Vec<
+                Option<
+                    RepoBranchesRepositoryRefsNodesAssociatedPullRequestsNodes,
+                >,
+            >,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepoBranchesRepositoryRefsNodesAssociatedPullRequestsNodes {
+        pub number: Int,
+    }
+}
+impl graphql_client::GraphQLQuery for RepoBranches {
+    type Variables = repo_branches::Variables;
+    type ResponseData = repo_branches::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: repo_branches::QUERY,
+            operation_name: repo_branches::OPERATION_NAME,
+        }
+    }
+}
+pub struct PullRequestCommits;
+pub mod pull_request_commits {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "PullRequestCommits";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type GitObjectID = crate::gh::gql::custom_types::GitObjectID;
+    #[derive(Debug)]
+    pub enum GitSignatureState {
+        BAD_CERT,
+        BAD_EMAIL,
+        EXPIRED_KEY,
+        GPGVERIFY_ERROR,
+        GPGVERIFY_UNAVAILABLE,
+        INVALID,
+        MALFORMED_SIG,
+        NOT_SIGNING_KEY,
+        NO_USER,
+        OCSP_ERROR,
+        OCSP_PENDING,
+        OCSP_REVOKED,
+        UNKNOWN_KEY,
+        UNKNOWN_SIG_TYPE,
+        UNSIGNED,
+        UNVERIFIED_EMAIL,
+        VALID,
+        Other(String),
+    }
+    impl ::serde::Serialize for GitSignatureState {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                GitSignatureState::BAD_CERT => "BAD_CERT",
+                GitSignatureState::BAD_EMAIL => "BAD_EMAIL",
+                GitSignatureState::EXPIRED_KEY => "EXPIRED_KEY",
+                GitSignatureState::GPGVERIFY_ERROR => "GPGVERIFY_ERROR",
+                GitSignatureState::GPGVERIFY_UNAVAILABLE => {
+                    "GPGVERIFY_UNAVAILABLE"
+                }
+                GitSignatureState::INVALID => "INVALID",
+                GitSignatureState::MALFORMED_SIG => "MALFORMED_SIG",
+                GitSignatureState::NOT_SIGNING_KEY => "NOT_SIGNING_KEY",
+                GitSignatureState::NO_USER => "NO_USER",
+                GitSignatureState::OCSP_ERROR => "OCSP_ERROR",
+                GitSignatureState::OCSP_PENDING => "OCSP_PENDING",
+                GitSignatureState::OCSP_REVOKED => "OCSP_REVOKED",
+                GitSignatureState::UNKNOWN_KEY => "UNKNOWN_KEY",
+                GitSignatureState::UNKNOWN_SIG_TYPE => "UNKNOWN_SIG_TYPE",
+                GitSignatureState::UNSIGNED => "UNSIGNED",
+                GitSignatureState::UNVERIFIED_EMAIL => "UNVERIFIED_EMAIL",
+                GitSignatureState::VALID => "VALID",
+                GitSignatureState::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for GitSignatureState {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "BAD_CERT" => Ok(GitSignatureState::BAD_CERT),
+                "BAD_EMAIL" => Ok(GitSignatureState::BAD_EMAIL),
+                "EXPIRED_KEY" => Ok(GitSignatureState::EXPIRED_KEY),
+                "GPGVERIFY_ERROR" => Ok(GitSignatureState::GPGVERIFY_ERROR),
+                "GPGVERIFY_UNAVAILABLE" => {
+                    Ok(GitSignatureState::GPGVERIFY_UNAVAILABLE)
+                }
+                "INVALID" => Ok(GitSignatureState::INVALID),
+                "MALFORMED_SIG" => Ok(GitSignatureState::MALFORMED_SIG),
+                "NOT_SIGNING_KEY" => Ok(GitSignatureState::NOT_SIGNING_KEY),
+                "NO_USER" => Ok(GitSignatureState::NO_USER),
+                "OCSP_ERROR" => Ok(GitSignatureState::OCSP_ERROR),
+                "OCSP_PENDING" => Ok(GitSignatureState::OCSP_PENDING),
+                "OCSP_REVOKED" => Ok(GitSignatureState::OCSP_REVOKED),
+                "UNKNOWN_KEY" => Ok(GitSignatureState::UNKNOWN_KEY),
+                "UNKNOWN_SIG_TYPE" => Ok(GitSignatureState::UNKNOWN_SIG_TYPE),
+                "UNSIGNED" => Ok(GitSignatureState::UNSIGNED),
+                "UNVERIFIED_EMAIL" => Ok(GitSignatureState::UNVERIFIED_EMAIL),
+                "VALID" => Ok(GitSignatureState::VALID),
+                _ => Ok(GitSignatureState::Other(s)),
+            }
+        }
+    }
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub owner: String,
+        pub name: String,
+        pub number: Int,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub repository: Option<PullRequestCommitsRepository>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepository {
+        #[serde(rename = "pullRequest")]
+        pub pull_request: Option<PullRequestCommitsRepositoryPullRequest>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequest {
+        #[serde(rename = "headRefOid")]
+        pub head_ref_oid: GitObjectID,
+        #[serde(rename = "baseRef")]
+        pub base_ref: Option<PullRequestCommitsRepositoryPullRequestBaseRef>,
+        pub commits: PullRequestCommitsRepositoryPullRequestCommits,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequestBaseRef {
+        #[serde(rename = "branchProtectionRule")]
+        pub branch_protection_rule: Option<
+            PullRequestCommitsRepositoryPullRequestBaseRefBranchProtectionRule,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequestBaseRefBranchProtectionRule {
+        #[serde(rename = "requiresCommitSignatures")]
+        pub requires_commit_signatures: Boolean,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequestCommits {
+        pub nodes: Option<
+            Vec<Option<PullRequestCommitsRepositoryPullRequestCommitsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequestCommitsNodes {
+        pub commit: PullRequestCommitsRepositoryPullRequestCommitsNodesCommit,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequestCommitsNodesCommit {
+        pub oid: GitObjectID,
+        pub message: String,
+        pub signature: Option<
+            PullRequestCommitsRepositoryPullRequestCommitsNodesCommitSignature,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestCommitsRepositoryPullRequestCommitsNodesCommitSignature { # [serde (rename = "isValid")] pub is_valid : Boolean , pub state : GitSignatureState , # [serde (flatten)] pub on : PullRequestCommitsRepositoryPullRequestCommitsNodesCommitSignatureOn }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum PullRequestCommitsRepositoryPullRequestCommitsNodesCommitSignatureOn {
+        GpgSignature,
+        SmimeSignature,
+        SshSignature,
+        UnknownSignature,
+    }
+}
+impl graphql_client::GraphQLQuery for PullRequestCommits {
+    type Variables = pull_request_commits::Variables;
+    type ResponseData = pull_request_commits::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: pull_request_commits::QUERY,
+            operation_name: pull_request_commits::OPERATION_NAME,
+        }
+    }
+}
+pub struct PullRequestMergeRequirements;
+pub mod pull_request_merge_requirements {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "PullRequestMergeRequirements";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    #[derive(Debug)]
+    pub enum PullRequestReviewState {
+        APPROVED,
+        CHANGES_REQUESTED,
+        COMMENTED,
+        DISMISSED,
+        PENDING,
+        Other(String),
+    }
+    impl ::serde::Serialize for PullRequestReviewState {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                PullRequestReviewState::APPROVED => "APPROVED",
+                PullRequestReviewState::CHANGES_REQUESTED => {
+                    "CHANGES_REQUESTED"
+                }
+                PullRequestReviewState::COMMENTED => "COMMENTED",
+                PullRequestReviewState::DISMISSED => "DISMISSED",
+                PullRequestReviewState::PENDING => "PENDING",
+                PullRequestReviewState::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for PullRequestReviewState {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "APPROVED" => Ok(PullRequestReviewState::APPROVED),
+                "CHANGES_REQUESTED" => {
+                    Ok(PullRequestReviewState::CHANGES_REQUESTED)
+                }
+                "COMMENTED" => Ok(PullRequestReviewState::COMMENTED),
+                "DISMISSED" => Ok(PullRequestReviewState::DISMISSED),
+                "PENDING" => Ok(PullRequestReviewState::PENDING),
+                _ => Ok(PullRequestReviewState::Other(s)),
+            }
+        }
+    }
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub owner: String,
+        pub name: String,
+        pub number: Int,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub repository: Option<PullRequestMergeRequirementsRepository>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepository {
+        #[serde(rename = "pullRequest")]
+        pub pull_request:
+            Option<PullRequestMergeRequirementsRepositoryPullRequest>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequest { # [serde (rename = "baseRefName")] pub base_ref_name : String , # [serde (rename = "baseRef")] pub base_ref : Option < PullRequestMergeRequirementsRepositoryPullRequestBaseRef > , # [serde (rename = "latestOpinionatedReviews")] pub latest_opinionated_reviews : Option < PullRequestMergeRequirementsRepositoryPullRequestLatestOpinionatedReviews > , pub commits : PullRequestMergeRequirementsRepositoryPullRequestCommits , }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestBaseRef { # [serde (rename = "branchProtectionRule")] pub branch_protection_rule : Option < PullRequestMergeRequirementsRepositoryPullRequestBaseRefBranchProtectionRule > , }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestBaseRefBranchProtectionRule
+    {
+        #[serde(rename = "requiresStatusChecks")]
+        pub requires_status_checks: Boolean,
+        #[serde(rename = "requiredStatusCheckContexts")]
+        pub required_status_check_contexts: Option<Vec<Option<String>>>,
+        #[serde(rename = "requiresApprovingReviews")]
+        pub requires_approving_reviews: Boolean,
+        #[serde(rename = "requiredApprovingReviewCount")]
+        pub required_approving_review_count: Option<Int>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestLatestOpinionatedReviews { pub nodes : Option < Vec < Option < PullRequestMergeRequirementsRepositoryPullRequestLatestOpinionatedReviewsNodes >> > , }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestLatestOpinionatedReviewsNodes
+    {
+        pub state: PullRequestReviewState,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommits { pub nodes : Option < Vec < Option < PullRequestMergeRequirementsRepositoryPullRequestCommitsNodes >> > , }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommitsNodes {
+        pub commit:
+            PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommit,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommit { # [serde (rename = "statusCheckRollup")] pub status_check_rollup : Option < PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollup > , }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollup { pub contexts : PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContexts , }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContexts { pub nodes : Option < Vec < Option < PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodes >> > , }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodes
+    {
+        CheckRun (PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodesOnCheckRun) , StatusContext (PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodesOnStatusContext) }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodesOnCheckRun
+    {
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestMergeRequirementsRepositoryPullRequestCommitsNodesCommitStatusCheckRollupContextsNodesOnStatusContext
+    {
+        pub context: String,
+    }
+}
+impl graphql_client::GraphQLQuery for PullRequestMergeRequirements {
+    type Variables = pull_request_merge_requirements::Variables;
+    type ResponseData = pull_request_merge_requirements::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: pull_request_merge_requirements::QUERY,
+            operation_name: pull_request_merge_requirements::OPERATION_NAME,
+        }
+    }
+}
+pub struct PullRequestStatus;
+pub mod pull_request_status {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "PullRequestStatus";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::gh::gql::custom_types::DateTime;
+    #[derive(Debug)]
+    pub enum PullRequestReviewDecision {
+        APPROVED,
+        CHANGES_REQUESTED,
+        REVIEW_REQUIRED,
+        Other(String),
+    }
+    impl ::serde::Serialize for PullRequestReviewDecision {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                PullRequestReviewDecision::APPROVED => "APPROVED",
+                PullRequestReviewDecision::CHANGES_REQUESTED => {
+                    "CHANGES_REQUESTED"
+                }
+                PullRequestReviewDecision::REVIEW_REQUIRED => "REVIEW_REQUIRED",
+                PullRequestReviewDecision::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for PullRequestReviewDecision {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "APPROVED" => Ok(PullRequestReviewDecision::APPROVED),
+                "CHANGES_REQUESTED" => {
+                    Ok(PullRequestReviewDecision::CHANGES_REQUESTED)
+                }
+                "REVIEW_REQUIRED" => {
+                    Ok(PullRequestReviewDecision::REVIEW_REQUIRED)
+                }
+                _ => Ok(PullRequestReviewDecision::Other(s)),
+            }
+        }
+    }
+    #[derive(Debug)]
+    pub enum PullRequestState {
+        CLOSED,
+        MERGED,
+        OPEN,
+        Other(String),
+    }
+    impl ::serde::Serialize for PullRequestState {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                PullRequestState::CLOSED => "CLOSED",
+                PullRequestState::MERGED => "MERGED",
+                PullRequestState::OPEN => "OPEN",
+                PullRequestState::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for PullRequestState {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "CLOSED" => Ok(PullRequestState::CLOSED),
+                "MERGED" => Ok(PullRequestState::MERGED),
+                "OPEN" => Ok(PullRequestState::OPEN),
+                _ => Ok(PullRequestState::Other(s)),
+            }
+        }
+    }
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub owner: String,
+        pub name: String,
+        pub number: Int,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub repository: Option<PullRequestStatusRepository>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestStatusRepository {
+        #[serde(rename = "pullRequest")]
+        pub pull_request: Option<PullRequestStatusRepositoryPullRequest>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestStatusRepositoryPullRequest {
+        pub state: PullRequestState,
+        #[serde(rename = "reviewDecision")]
+        pub review_decision: Option<PullRequestReviewDecision>,
+        #[serde(rename = "closedAt")]
+        pub closed_at: Option<DateTime>,
+        #[serde(rename = "mergedAt")]
+        pub merged_at: Option<DateTime>,
+    }
+}
+impl graphql_client::GraphQLQuery for PullRequestStatus {
+    type Variables = pull_request_status::Variables;
+    type ResponseData = pull_request_status::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: pull_request_status::QUERY,
+            operation_name: pull_request_status::OPERATION_NAME,
+        }
+    }
+}
+pub struct OrgRepositories;
+pub mod org_repositories {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "OrgRepositories";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::gh::gql::custom_types::DateTime;
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub org: String,
+        pub after: Option<String>,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub organization: Option<OrgRepositoriesOrganization>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct OrgRepositoriesOrganization {
+        pub repositories: OrgRepositoriesOrganizationRepositories,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct OrgRepositoriesOrganizationRepositories {
+        #[serde(rename = "pageInfo")]
+        pub page_info: OrgRepositoriesOrganizationRepositoriesPageInfo,
+        pub nodes:
+            Option<Vec<Option<OrgRepositoriesOrganizationRepositoriesNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct OrgRepositoriesOrganizationRepositoriesPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct OrgRepositoriesOrganizationRepositoriesNodes {
+        pub name: String,
+        #[serde(rename = "isArchived")]
+        pub is_archived: Boolean,
+        #[serde(rename = "pushedAt")]
+        pub pushed_at: Option<DateTime>,
+        #[serde(rename = "pullRequests")]
+        pub pull_requests:
+            OrgRepositoriesOrganizationRepositoriesNodesPullRequests,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct OrgRepositoriesOrganizationRepositoriesNodesPullRequests {
+        #[serde(rename = "totalCount")]
+        pub total_count: Int,
+    }
+}
+impl graphql_client::GraphQLQuery for OrgRepositories {
+    type Variables = org_repositories::Variables;
+    type ResponseData = org_repositories::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: org_repositories::QUERY,
+            operation_name: org_repositories::OPERATION_NAME,
+        }
+    }
+}
+pub struct PullRequestFilesSearch;
+pub mod pull_request_files_search {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "PullRequestFilesSearch";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    #[derive(Debug)]
+    pub enum PatchStatus {
+        ADDED,
+        CHANGED,
+        COPIED,
+        DELETED,
+        MODIFIED,
+        RENAMED,
+        Other(String),
+    }
+    impl ::serde::Serialize for PatchStatus {
+        fn serialize<S: serde::Serializer>(
+            &self,
+            ser: S,
+        ) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(match *self {
+                PatchStatus::ADDED => "ADDED",
+                PatchStatus::CHANGED => "CHANGED",
+                PatchStatus::COPIED => "COPIED",
+                PatchStatus::DELETED => "DELETED",
+                PatchStatus::MODIFIED => "MODIFIED",
+                PatchStatus::RENAMED => "RENAMED",
+                PatchStatus::Other(ref s) => &s,
+            })
+        }
+    }
+    impl<'de> ::serde::Deserialize<'de> for PatchStatus {
+        fn deserialize<D: ::serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Self, D::Error> {
+            let s: String = ::serde::Deserialize::deserialize(deserializer)?;
+            match s.as_str() {
+                "ADDED" => Ok(PatchStatus::ADDED),
+                "CHANGED" => Ok(PatchStatus::CHANGED),
+                "COPIED" => Ok(PatchStatus::COPIED),
+                "DELETED" => Ok(PatchStatus::DELETED),
+                "MODIFIED" => Ok(PatchStatus::MODIFIED),
+                "RENAMED" => Ok(PatchStatus::RENAMED),
+                _ => Ok(PatchStatus::Other(s)),
+            }
+        }
+    }
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub q: String,
+        pub after: Option<String>,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub search: PullRequestFilesSearchSearch,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestFilesSearchSearch {
+        #[serde(rename = "pageInfo")]
+        pub page_info: PullRequestFilesSearchSearchPageInfo,
+        pub nodes: Option<Vec<Option<PullRequestFilesSearchSearchNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestFilesSearchSearchPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum PullRequestFilesSearchSearchNodes {
+        App,
+        Discussion,
+        Issue,
+        MarketplaceListing,
+        Organization,
+        PullRequest(PullRequestFilesSearchSearchNodesOnPullRequest),
+        Repository,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestFilesSearchSearchNodesOnPullRequest {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub files: Option<PullRequestFilesSearchSearchNodesOnPullRequestFiles>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestFilesSearchSearchNodesOnPullRequestFiles {
+        #[serde(rename = "totalCount")]
+        pub total_count: Int,
+        pub nodes: Option<
+            Vec<
+                Option<
+                    PullRequestFilesSearchSearchNodesOnPullRequestFilesNodes,
+                >,
+            >,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestFilesSearchSearchNodesOnPullRequestFilesNodes {
+        pub path: String,
+        pub additions: Int,
+        pub deletions: Int,
+        #[serde(rename = "changeType")]
+        pub change_type: PatchStatus,
+    }
+}
+impl graphql_client::GraphQLQuery for PullRequestFilesSearch {
+    type Variables = pull_request_files_search::Variables;
+    type ResponseData = pull_request_files_search::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: pull_request_files_search::QUERY,
+            operation_name: pull_request_files_search::OPERATION_NAME,
+        }
+    }
+}
+pub struct FollowUps;
+pub mod follow_ups {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "FollowUps";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::gh::gql::custom_types::DateTime;
+    type URI = crate::gh::gql::custom_types::URI;
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub q: String,
+        pub after: Option<String>,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragment {
+        pub owner: RepositoryFragmentOwner,
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragmentOwner {
+        pub login: String,
+        #[serde(flatten)]
+        pub on: RepositoryFragmentOwnerOn,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum RepositoryFragmentOwnerOn {
+        Organization,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestSummaryFragment {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub number: Int,
+        pub title: String,
+        pub url: URI,
+        pub repository: PullRequestSummaryFragmentRepository,
+    }
+    pub type PullRequestSummaryFragmentRepository = RepositoryFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub search: FollowUpsSearch,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearch {
+        #[serde(rename = "pageInfo")]
+        pub page_info: FollowUpsSearchPageInfo,
+        pub nodes: Option<Vec<Option<FollowUpsSearchNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum FollowUpsSearchNodes {
+        App,
+        Discussion,
+        Issue(FollowUpsSearchNodesOnIssue),
+        MarketplaceListing,
+        Organization,
+        PullRequest(FollowUpsSearchNodesOnPullRequest),
+        Repository,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnIssue {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub number: Int,
+        pub title: String,
+        pub url: URI,
+        pub repository: FollowUpsSearchNodesOnIssueRepository,
+        #[serde(rename = "timelineItems")]
+        pub timeline_items: FollowUpsSearchNodesOnIssueTimelineItems,
+    }
+    pub type FollowUpsSearchNodesOnIssueRepository = RepositoryFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnIssueTimelineItems {
+        pub nodes:
+            Option<Vec<Option<FollowUpsSearchNodesOnIssueTimelineItemsNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum FollowUpsSearchNodesOnIssueTimelineItemsNodes {
+        AddedToProjectEvent,
+        AssignedEvent,
+        ClosedEvent,
+        CommentDeletedEvent,
+        ConnectedEvent,
+        ConvertedNoteToIssueEvent,
+        ConvertedToDiscussionEvent,
+        CrossReferencedEvent,
+        DemilestonedEvent,
+        DisconnectedEvent,
+        IssueComment(
+            FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueComment,
+        ),
+        LabeledEvent,
+        LockedEvent,
+        MarkedAsDuplicateEvent,
+        MentionedEvent,
+        MilestonedEvent,
+        MovedColumnsInProjectEvent,
+        PinnedEvent,
+        ReferencedEvent,
+        RemovedFromProjectEvent,
+        RenamedTitleEvent,
+        ReopenedEvent,
+        SubscribedEvent,
+        TransferredEvent,
+        UnassignedEvent,
+        UnlabeledEvent,
+        UnlockedEvent,
+        UnmarkedAsDuplicateEvent,
+        UnpinnedEvent,
+        UnsubscribedEvent,
+        UserBlockedEvent,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueComment {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub url: URI,
+        #[serde(rename = "createdAt")]
+        pub created_at: DateTime,
+        pub author: Option<
+            FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueCommentAuthor,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueCommentAuthor {
+        pub login: String,
+        #[serde(flatten)]
+        pub on:
+            FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueCommentAuthorOn,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum FollowUpsSearchNodesOnIssueTimelineItemsNodesOnIssueCommentAuthorOn {
+        Bot,
+        EnterpriseUserAccount,
+        Mannequin,
+        Organization,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnPullRequest {
+        #[serde(flatten)]
+        pub pull_request_summary_fragment: PullRequestSummaryFragment,
+        #[serde(rename = "timelineItems")]
+        pub timeline_items: FollowUpsSearchNodesOnPullRequestTimelineItems,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnPullRequestTimelineItems {
+        pub nodes: Option<
+            Vec<Option<FollowUpsSearchNodesOnPullRequestTimelineItemsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum FollowUpsSearchNodesOnPullRequestTimelineItemsNodes {
+        AddedToProjectEvent , AssignedEvent , AutoMergeDisabledEvent , AutoMergeEnabledEvent , AutoRebaseEnabledEvent , AutoSquashEnabledEvent , AutomaticBaseChangeFailedEvent , AutomaticBaseChangeSucceededEvent , BaseRefChangedEvent , BaseRefDeletedEvent , BaseRefForcePushedEvent , ClosedEvent , CommentDeletedEvent , ConnectedEvent , ConvertToDraftEvent , ConvertedNoteToIssueEvent , ConvertedToDiscussionEvent , CrossReferencedEvent , DemilestonedEvent , DeployedEvent , DeploymentEnvironmentChangedEvent , DisconnectedEvent , HeadRefDeletedEvent , HeadRefForcePushedEvent , HeadRefRestoredEvent , IssueComment (FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueComment) , LabeledEvent , LockedEvent , MarkedAsDuplicateEvent , MentionedEvent , MergedEvent , MilestonedEvent , MovedColumnsInProjectEvent , PinnedEvent , PullRequestCommit , PullRequestCommitCommentThread , PullRequestReview (FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReview) , PullRequestReviewThread , PullRequestRevisionMarker , ReadyForReviewEvent , ReferencedEvent , RemovedFromProjectEvent , RenamedTitleEvent , ReopenedEvent , ReviewDismissedEvent , ReviewRequestRemovedEvent , ReviewRequestedEvent , SubscribedEvent , TransferredEvent , UnassignedEvent , UnlabeledEvent , UnlockedEvent , UnmarkedAsDuplicateEvent , UnpinnedEvent , UnsubscribedEvent , UserBlockedEvent }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueComment { # [serde (rename = "databaseId")] pub database_id : Option < Int > , pub url : URI , # [serde (rename = "createdAt")] pub created_at : DateTime , pub author : Option < FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueCommentAuthor > , }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueCommentAuthor { pub login : String , # [serde (flatten)] pub on : FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueCommentAuthorOn }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnIssueCommentAuthorOn
+    {
+        Bot,
+        EnterpriseUserAccount,
+        Mannequin,
+        Organization,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReview { # [serde (rename = "createdAt")] pub created_at : DateTime , pub author : Option < FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReviewAuthor > , }
+    #[derive(Deserialize, Debug)]
+    pub struct FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReviewAuthor { pub login : String , # [serde (flatten)] pub on : FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReviewAuthorOn }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum FollowUpsSearchNodesOnPullRequestTimelineItemsNodesOnPullRequestReviewAuthorOn
+    {
+        Bot,
+        EnterpriseUserAccount,
+        Mannequin,
+        Organization,
+        User,
+    }
+}
+impl graphql_client::GraphQLQuery for FollowUps {
+    type Variables = follow_ups::Variables;
+    type ResponseData = follow_ups::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: follow_ups::QUERY,
+            operation_name: follow_ups::OPERATION_NAME,
+        }
+    }
+}
+pub struct PendingReviews;
+pub mod pending_reviews {
+    #![allow(dead_code)]
+    use std::result::Result;
+    pub const OPERATION_NAME: &str = "PendingReviews";
+    pub const QUERY : & str = "query UserInfo($login: String!) {\n  rateLimit {\n    cost\n  }\n  user(login: $login) {\n    name\n    login\n    avatarUrl\n    databaseId\n    pullRequests(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        totalCommentsCount\n        createdAt\n        updatedAt\n        isDraft\n        milestone {\n          id\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n      }\n    }\n    issues(first: 30, states: OPEN) {\n      nodes {\n        title\n        number\n        databaseId\n        updatedAt\n        author {\n          __typename\n          login\n        }\n        participants(first: 1) {\n          totalCount\n        }\n        assignees(first: 10) {\n          nodes {\n            login\n          }\n        }\n      }\n    }\n  }\n}\n\nfragment UserFragment on User {\n  login\n  databaseId\n}\n\nfragment BotFragment on Bot {\n  login\n  databaseId\n}\n\nfragment RepositoryFragment on Repository {\n  owner {\n    __typename\n    login\n  }\n  name\n}\n\nfragment PullRequestSummaryFragment on PullRequest {\n  databaseId\n  number\n  title\n  url\n  repository {\n    ...RepositoryFragment\n  }\n}\n\nfragment ReviewFragment on PullRequestReview {\n  author {\n    __typename\n    ...UserFragment\n  }\n  state\n  submittedAt\n}\n\nfragment CrossReferenceFragment on CrossReferencedEvent {\n  willCloseTarget\n  referencedAt\n  source {\n    __typename\n    ... on PullRequest {\n      ...PullRequestSummaryFragment\n      state\n    }\n    ... on Issue {\n      databaseId\n      number\n      title\n      url\n      repository {\n        ...RepositoryFragment\n      }\n      issueState: state\n    }\n  }\n}\n\nquery SearchIssues($q: String!, $first: Int!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: $first, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        state\n        isDraft\n        headRefName\n        body\n        authorAssociation\n        totalCommentsCount\n        reviewRequests(first: 30) {\n          nodes {\n            requestedReviewer {\n              __typename\n              ...UserFragment\n            }\n          }\n        }\n        reviewDecision\n        reviews(first: 1) {\n          totalCount\n        }\n        latestReviews(first: 30) {\n          nodes {\n            ...ReviewFragment\n          }\n        }\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        mergedAt\n        commits(last: 1) {\n          nodes {\n            commit {\n              committedDate\n              statusCheckRollup {\n                state\n              }\n            }\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n      ... on Issue {\n        title\n        number\n        databaseId\n        url\n        author {\n          __typename\n          ...UserFragment\n          ...BotFragment\n        }\n        repository {\n          ...RepositoryFragment\n        }\n        body\n        state\n        labels(first: 20) {\n          nodes {\n            name\n          }\n        }\n        createdAt\n        updatedAt\n        closedAt\n        comments(first: 100) {\n          nodes {\n            author {\n              __typename\n              ...UserFragment\n            }\n            createdAt\n          }\n        }\n        timelineItems(last: 20, itemTypes: [CROSS_REFERENCED_EVENT]) {\n          nodes {\n            __typename\n            ...CrossReferenceFragment\n          }\n        }\n      }\n    }\n  }\n}\n\nquery RepoBranches($owner: String!, $name: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    defaultBranchRef {\n      name\n    }\n    primaryLanguage {\n      name\n    }\n    repositoryTopics(first: 20) {\n      nodes {\n        topic {\n          name\n        }\n      }\n    }\n    refs(first: 100, refPrefix: \"refs/heads/\", after: $after) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        target {\n          __typename\n          ... on Commit {\n            committedDate\n          }\n        }\n        associatedPullRequests(first: 10, states: OPEN) {\n          nodes {\n            number\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestCommits($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      headRefOid\n      baseRef {\n        branchProtectionRule {\n          requiresCommitSignatures\n        }\n      }\n      commits(last: 100) {\n        nodes {\n          commit {\n            oid\n            message\n            signature {\n              __typename\n              isValid\n              state\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestMergeRequirements(\n  $owner: String!\n  $name: String!\n  $number: Int!\n) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      baseRefName\n      baseRef {\n        branchProtectionRule {\n          requiresStatusChecks\n          requiredStatusCheckContexts\n          requiresApprovingReviews\n          requiredApprovingReviewCount\n        }\n      }\n      latestOpinionatedReviews(first: 100, writersOnly: true) {\n        nodes {\n          state\n        }\n      }\n      commits(last: 1) {\n        nodes {\n          commit {\n            statusCheckRollup {\n              contexts(first: 100) {\n                nodes {\n                  __typename\n                  ... on CheckRun {\n                    name\n                  }\n                  ... on StatusContext {\n                    context\n                  }\n                }\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestStatus($owner: String!, $name: String!, $number: Int!) {\n  rateLimit {\n    cost\n  }\n  repository(owner: $owner, name: $name) {\n    pullRequest(number: $number) {\n      state\n      reviewDecision\n      closedAt\n      mergedAt\n    }\n  }\n}\n\nquery OrgRepositories($org: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  organization(login: $org) {\n    repositories(\n      first: 100\n      after: $after\n      orderBy: { field: PUSHED_AT, direction: DESC }\n    ) {\n      pageInfo {\n        hasNextPage\n        endCursor\n      }\n      nodes {\n        name\n        isArchived\n        pushedAt\n        pullRequests(states: OPEN) {\n          totalCount\n        }\n      }\n    }\n  }\n}\n\nquery PullRequestFilesSearch($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        databaseId\n        files(first: 100) {\n          totalCount\n          nodes {\n            path\n            additions\n            deletions\n            changeType\n          }\n        }\n      }\n    }\n  }\n}\n\nquery FollowUps($q: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on Issue {\n        databaseId\n        number\n        title\n        url\n        repository {\n          ...RepositoryFragment\n        }\n        timelineItems(last: 10, itemTypes: [ISSUE_COMMENT]) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        timelineItems(\n          last: 10\n          itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW]\n        ) {\n          nodes {\n            __typename\n            ... on IssueComment {\n              databaseId\n              url\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n            ... on PullRequestReview {\n              createdAt\n              author {\n                __typename\n                login\n              }\n            }\n          }\n        }\n      }\n    }\n  }\n}\n\nquery PendingReviews($q: String!, $login: String!, $after: String) {\n  rateLimit {\n    cost\n  }\n  search(first: 50, after: $after, query: $q, type: ISSUE) {\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    nodes {\n      __typename\n      ... on PullRequest {\n        ...PullRequestSummaryFragment\n        reviews(first: 1, author: $login, states: [PENDING]) {\n          nodes {\n            databaseId\n            createdAt\n            updatedAt\n            comments {\n              totalCount\n            }\n          }\n        }\n      }\n    }\n  }\n}\n" ;
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    #[allow(dead_code)]
+    type Boolean = bool;
+    #[allow(dead_code)]
+    type Float = f64;
+    #[allow(dead_code)]
+    type Int = i64;
+    #[allow(dead_code)]
+    type ID = String;
+    type DateTime = crate::gh::gql::custom_types::DateTime;
+    type URI = crate::gh::gql::custom_types::URI;
+    #[derive(Serialize)]
+    pub struct Variables {
+        pub q: String,
+        pub login: String,
+        pub after: Option<String>,
+    }
+    impl Variables {}
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragment {
+        pub owner: RepositoryFragmentOwner,
+        pub name: String,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct RepositoryFragmentOwner {
+        pub login: String,
+        #[serde(flatten)]
+        pub on: RepositoryFragmentOwnerOn,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum RepositoryFragmentOwnerOn {
+        Organization,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PullRequestSummaryFragment {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        pub number: Int,
+        pub title: String,
+        pub url: URI,
+        pub repository: PullRequestSummaryFragmentRepository,
+    }
+    pub type PullRequestSummaryFragmentRepository = RepositoryFragment;
+    #[derive(Deserialize, Debug)]
+    pub struct ResponseData {
+        pub search: PendingReviewsSearch,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PendingReviewsSearch {
+        #[serde(rename = "pageInfo")]
+        pub page_info: PendingReviewsSearchPageInfo,
+        pub nodes: Option<Vec<Option<PendingReviewsSearchNodes>>>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PendingReviewsSearchPageInfo {
+        #[serde(rename = "hasNextPage")]
+        pub has_next_page: Boolean,
+        #[serde(rename = "endCursor")]
+        pub end_cursor: Option<String>,
+    }
+    #[derive(Deserialize, Debug)]
+    #[serde(tag = "__typename")]
+    pub enum PendingReviewsSearchNodes {
+        App,
+        Discussion,
+        Issue,
+        MarketplaceListing,
+        Organization,
+        PullRequest(PendingReviewsSearchNodesOnPullRequest),
+        Repository,
+        User,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PendingReviewsSearchNodesOnPullRequest {
+        #[serde(flatten)]
+        pub pull_request_summary_fragment: PullRequestSummaryFragment,
+        pub reviews: Option<PendingReviewsSearchNodesOnPullRequestReviews>,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PendingReviewsSearchNodesOnPullRequestReviews {
+        pub nodes: Option<
+            Vec<Option<PendingReviewsSearchNodesOnPullRequestReviewsNodes>>,
+        >,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PendingReviewsSearchNodesOnPullRequestReviewsNodes {
+        #[serde(rename = "databaseId")]
+        pub database_id: Option<Int>,
+        #[serde(rename = "createdAt")]
+        pub created_at: DateTime,
+        #[serde(rename = "updatedAt")]
+        pub updated_at: DateTime,
+        pub comments:
+            PendingReviewsSearchNodesOnPullRequestReviewsNodesComments,
+    }
+    #[derive(Deserialize, Debug)]
+    pub struct PendingReviewsSearchNodesOnPullRequestReviewsNodesComments {
+        #[serde(rename = "totalCount")]
+        pub total_count: Int,
+    }
+}
+impl graphql_client::GraphQLQuery for PendingReviews {
+    type Variables = pending_reviews::Variables;
+    type ResponseData = pending_reviews::ResponseData;
+    fn build_query(
+        variables: Self::Variables,
+    ) -> ::graphql_client::QueryBody<Self::Variables> {
+        graphql_client::QueryBody {
+            variables,
+            query: pending_reviews::QUERY,
+            operation_name: pending_reviews::OPERATION_NAME,
+        }
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::{Identity, IdentityUser};
+
+/// Subquery selecting every login linked to the same identity as the login
+/// bound to its placeholder, on any host, along with that login itself.
+/// Unlinked logins thus expand to just themselves. Requires binding the
+/// login twice.
+///
+pub const LINKED_LOGINS: &str = "
+    SELECT linked.login
+    FROM
+        identity_users AS own
+        INNER JOIN identity_users AS linked
+            ON linked.identity_id = own.identity_id
+    WHERE own.login = ?
+    UNION SELECT ?
+";
+
+/// Expression obtaining the host of the user in `users`: that of the account
+/// it is tracked through, or `github.com` otherwise.
+///
+const USER_HOST: &str = "
+    COALESCE(
+        (
+            SELECT accounts.host
+            FROM user_accounts INNER JOIN accounts
+                ON accounts.user_id = user_accounts.account_id
+            WHERE user_accounts.user_id = users.id
+            LIMIT 1
+        ),
+        'github.com'
+    )
+";
+
+/// Create a new, empty identity. Returns the new identity's ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `name` - A name for the human behind the identity.
+///
+pub async fn create(db: &DB, name: &String) -> Result<i64, GHDError> {
+    match sqlx::query("INSERT INTO identities (name) VALUES (?)")
+        .bind(&name)
+        .execute(db.pool())
+        .await
+    {
+        Ok(res) => Ok(res.last_insert_rowid()),
+        Err(err) => {
+            panic!("Unable to create identity: {}", err);
+        }
+    }
+}
+
+/// Remove identity `identityid`, unlinking all its users.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `identityid` - The identity's ID.
+///
+pub async fn remove(db: &DB, identityid: &i64) {
+    for q in [
+        "DELETE FROM identity_users WHERE identity_id = ?",
+        "DELETE FROM identities WHERE id = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&identityid)
+            .execute(db.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error removing identity {}: {}", identityid, err);
+            });
+    }
+}
+
+/// Link user `login` on `host` to identity `identityid`. A user belongs to at
+/// most one identity, so any previous link is replaced. The same login on
+/// different hosts may belong to different humans, and thus identities.
+/// Returns `NotFoundError` if either the identity or the user are not known.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `identityid` - The identity's ID.
+/// * `host` - The host the user is on; e.g., `github.com`.
+/// * `login` - The user's login.
+///
+pub async fn link(
+    db: &DB,
+    identityid: &i64,
+    host: &String,
+    login: &String,
+) -> Result<(), GHDError> {
+    let exists = match sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM identities WHERE id = ?",
+    )
+    .bind(&identityid)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => res > 0,
+        Err(err) => {
+            panic!("Unable to check identity {}: {}", identityid, err);
+        }
+    };
+    if !exists {
+        return Err(GHDError::NotFoundError);
+    }
+
+    let known = match sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM users WHERE login = ? AND {} = ?",
+        USER_HOST
+    ))
+    .bind(&login)
+    .bind(&host)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => res > 0,
+        Err(err) => {
+            panic!("Unable to obtain user {}@{}: {}", login, host, err);
+        }
+    };
+    if !known {
+        return Err(GHDError::NotFoundError);
+    }
+
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO identity_users (host, login, identity_id)
+        VALUES (?, ?, ?)
+        ",
+    )
+    .bind(&host)
+    .bind(&login)
+    .bind(&identityid)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to link user {}@{}: {}", login, host, err);
+    });
+
+    Ok(())
+}
+
+/// Unlink user `login` on `host` from whatever identity it belongs to.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `host` - The host the user is on; e.g., `github.com`.
+/// * `login` - The user's login.
+///
+pub async fn unlink(db: &DB, host: &String, login: &String) {
+    sqlx::query("DELETE FROM identity_users WHERE host = ? AND login = ?")
+        .bind(&host)
+        .bind(&login)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to unlink user {}@{}: {}", login, host, err);
+        });
+}
+
+/// Obtain all identities, along with their linked users.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_identities(db: &DB) -> Result<Vec<Identity>, GHDError> {
+    let identities = match sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, name FROM identities ORDER BY name, id",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain identities: {}", err);
+        }
+    };
+
+    let mut res: Vec<Identity> = vec![];
+    for (id, name) in identities {
+        let users = match sqlx::query_as::<_, IdentityUser>(&format!(
+            "
+            SELECT
+                identity_users.host, identity_users.login,
+                users.name, users.avatar_url
+            FROM
+                identity_users LEFT JOIN users
+                ON users.login = identity_users.login
+                AND {} = identity_users.host
+            WHERE identity_users.identity_id = ?
+            ORDER BY identity_users.host, identity_users.login
+            ",
+            USER_HOST
+        ))
+        .bind(&id)
+        .fetch_all(db.pool())
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Unable to obtain users for identity {}: {}", id, err);
+            }
+        };
+        res.push(Identity { id, name, users });
+    }
+
+    Ok(res)
+}
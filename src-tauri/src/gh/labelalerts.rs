@@ -0,0 +1,212 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError, notify};
+
+use super::types::{Issue, LabelAlert};
+
+/// Transitions a label alert may be subscribed to: the label being added,
+/// removed, or either.
+///
+pub const ON_ADDED: &str = "added";
+pub const ON_REMOVED: &str = "removed";
+pub const ON_ANY: &str = "any";
+
+const TRANSITIONS: [&str; 3] = [ON_ADDED, ON_REMOVED, ON_ANY];
+
+/// The label alerts subscribed to on an issue, along with the labels the
+/// issue had before being synced. Taken before an issue's labels are
+/// replaced, so transitions can be told apart afterwards.
+///
+pub struct Snapshot {
+    alerts: Vec<LabelAlert>,
+    labels: Vec<String>,
+}
+
+/// Obtain the label alerts subscribed to on issue `issueid` and its current
+/// labels, if there are any alerts. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn snapshot(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issueid: &i64,
+) -> Option<Snapshot> {
+    let alerts = sqlx::query_as::<_, LabelAlert>(
+        "SELECT * FROM label_alerts WHERE issue_id = ? ORDER BY id",
+    )
+    .bind(&issueid)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain label alerts for {}: {}", issueid, err);
+    });
+    if alerts.is_empty() {
+        return None;
+    }
+
+    let labels = sqlx::query_scalar::<_, String>(
+        "SELECT name FROM issue_labels WHERE issue_id = ?",
+    )
+    .bind(&issueid)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain labels for issue {}: {}", issueid, err);
+    });
+
+    Some(Snapshot { alerts, labels })
+}
+
+/// Notify of the label transitions subscribed to on `issue`, comparing its
+/// synced labels against those in `snapshot`. Labels are compared regardless
+/// of case. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issue` - The issue just synced.
+/// * `snapshot` - The issue's alerts and labels from before the sync.
+///
+pub async fn evaluate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issue: &Issue,
+    snapshot: &Snapshot,
+) {
+    let has = |labels: &Vec<String>, label: &String| {
+        labels.iter().any(|l| l.eq_ignore_ascii_case(&label))
+    };
+
+    for alert in &snapshot.alerts {
+        let before = has(&snapshot.labels, &alert.label);
+        let after = has(&issue.labels, &alert.label);
+        let transition = match (before, after) {
+            (false, true) => ON_ADDED,
+            (true, false) => ON_REMOVED,
+            _ => continue,
+        };
+        if alert.on_change != ON_ANY && alert.on_change != transition {
+            continue;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        sqlx::query("UPDATE label_alerts SET triggered_at = ? WHERE id = ?")
+            .bind(&now)
+            .bind(&alert.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to update label alert {}: {}", alert.id, err);
+            });
+
+        let title = format!(
+            "'{}' {} on {}/{}#{}",
+            alert.label,
+            transition,
+            issue.repo_owner,
+            issue.repo_name,
+            issue.number
+        );
+        notify::push(
+            tx,
+            "label_change",
+            &Some(issue.id),
+            &title,
+            &issue.title,
+            notify::classify("label_change"),
+        )
+        .await;
+    }
+}
+
+/// Subscribe to a label transition on an issue. Returns the new alert's ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `alert` - The alert to add. Its `id` is ignored.
+///
+pub async fn add_alert(db: &DB, alert: &LabelAlert) -> Result<i64, GHDError> {
+    if alert.label.trim().is_empty()
+        || !TRANSITIONS.contains(&alert.on_change.as_str())
+    {
+        return Err(GHDError::BadRequest);
+    }
+
+    match sqlx::query(
+        "
+        INSERT INTO label_alerts (issue_id, label, on_change, created_at)
+        VALUES (?, ?, ?, ?)
+        ",
+    )
+    .bind(&alert.issue_id)
+    .bind(alert.label.trim())
+    .bind(&alert.on_change)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res.last_insert_rowid()),
+        Err(err) => {
+            panic!("Unable to add label alert: {}", err);
+        }
+    }
+}
+
+/// Remove label alert `alertid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `alertid` - The alert's ID.
+///
+pub async fn remove_alert(db: &DB, alertid: &i64) {
+    sqlx::query("DELETE FROM label_alerts WHERE id = ?")
+        .bind(&alertid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to remove label alert {}: {}", alertid, err);
+        });
+}
+
+/// Obtain the label alerts subscribed to, on issue `issueid` if provided, or
+/// on every issue otherwise.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue to obtain alerts for, if any.
+///
+pub async fn get_alerts(db: &DB, issueid: &Option<i64>) -> Vec<LabelAlert> {
+    match sqlx::query_as::<_, LabelAlert>(
+        "
+        SELECT * FROM label_alerts
+        WHERE ?1 IS NULL OR issue_id = ?1
+        ORDER BY issue_id, id
+        ",
+    )
+    .bind(&issueid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain label alerts: {}", err);
+        }
+    }
+}
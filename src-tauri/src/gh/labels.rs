@@ -0,0 +1,224 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{accounts::AccountCredentials, refresh, rest, types::RepoLabel};
+
+/// Labels are seldom added or recolored; refresh them once a day.
+///
+pub const LABELS_REFRESH_INTERVAL: i64 = 24 * 60 * 60;
+
+/// The most label names offered when completing a prefix.
+///
+const MAX_COMPLETIONS: i64 = 20;
+
+/// Obtain the sync refresh key for the labels of repository `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("labels:{}/{}", owner, name)
+}
+
+/// Check whether the labels of repository `owner/name` are due for a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        LABELS_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Label as described by the REST API.
+///
+#[derive(serde::Deserialize)]
+struct LabelReply {
+    name: String,
+    color: String,
+    description: Option<String>,
+}
+
+/// Obtain the labels defined on repository `owner/name`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<RepoLabel>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("labels");
+    let ep = format!("/repos/{}/{}/labels", owner, name);
+    let labels = match ghreq.get_all_pages::<LabelReply>(&ep).await {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(labels
+        .into_iter()
+        .map(|l| RepoLabel {
+            repo_owner: owner.clone(),
+            repo_name: name.clone(),
+            name: l.name,
+            color: l.color.to_lowercase(),
+            description: l.description.filter(|d| !d.is_empty()),
+        })
+        .collect())
+}
+
+/// Replace the labels known for repository `owner/name` with `labels`. This
+/// function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `labels` - The repository's labels.
+/// * `when` - When the labels were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+    labels: &Vec<RepoLabel>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "DELETE FROM repo_labels WHERE repo_owner = ? AND repo_name = ?",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error clearing labels of '{}/{}': {}", owner, name, err);
+    });
+
+    for label in labels {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO repo_labels (
+                repo_owner, repo_name, name, color, description
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .bind(&label.name)
+        .bind(&label.color)
+        .bind(&label.description)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!(
+                "Error storing label '{}' of '{}/{}': {}",
+                label.name, owner, name, err
+            );
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+/// Obtain the labels known for repository `owner/name` if provided, or for
+/// every repository otherwise, by repository and name.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner, if any.
+/// * `name` - The repository's name, if any.
+///
+pub async fn get_labels(
+    db: &DB,
+    owner: &Option<String>,
+    name: &Option<String>,
+) -> Vec<RepoLabel> {
+    match sqlx::query_as::<_, RepoLabel>(
+        "
+        SELECT * FROM repo_labels
+        WHERE
+            (?1 IS NULL OR repo_owner = ?1)
+            AND (?2 IS NULL OR repo_name = ?2)
+        ORDER BY repo_owner, repo_name, name COLLATE NOCASE
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain labels: {}", err);
+        }
+    }
+}
+
+/// Obtain the names of the known labels starting with `prefix`, regardless
+/// of case, on repository `owner/name` if provided, or on any otherwise.
+/// Labels sharing a name across repositories are offered once.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prefix` - What the label's name starts with.
+/// * `owner` - The repository's owner, if any.
+/// * `name` - The repository's name, if any.
+///
+pub async fn complete(
+    db: &DB,
+    prefix: &String,
+    owner: &Option<String>,
+    name: &Option<String>,
+) -> Vec<String> {
+    match sqlx::query_scalar::<_, String>(
+        "
+        SELECT name FROM repo_labels
+        WHERE
+            SUBSTR(name, 1, LENGTH(?1)) = ?1 COLLATE NOCASE
+            AND (?2 IS NULL OR repo_owner = ?2)
+            AND (?3 IS NULL OR repo_name = ?3)
+        GROUP BY name COLLATE NOCASE
+        ORDER BY name COLLATE NOCASE
+        LIMIT ?4
+        ",
+    )
+    .bind(&prefix)
+    .bind(&owner)
+    .bind(&name)
+    .bind(MAX_COMPLETIONS)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to complete label '{}': {}", prefix, err);
+        }
+    }
+}
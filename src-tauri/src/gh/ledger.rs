@@ -0,0 +1,310 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Mutex;
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::types::{ApiUsageEntry, HourlyUsage, RateLimitStatus};
+
+/// Setting holding the maximum number of API calls kept in the ledger. Older
+/// calls are dropped as new ones are recorded.
+///
+pub const SETTING_LEDGER_SIZE: &str = "ledger.size";
+
+const DEFAULT_LEDGER_SIZE: i64 = 10000;
+
+/// Hours of per-account API usage kept; a week, plus a day to spare.
+///
+pub const USAGE_HOURS_KEPT: i64 = 8 * 24;
+
+/// An API call that has yet to be written to the ledger.
+///
+struct PendingCall {
+    token: String,
+    host: String,
+    feature: String,
+    resource: Option<String>,
+    cost: i64,
+    remaining: Option<i64>,
+    called_at: i64,
+}
+
+/// API calls are made without access to the database, so they're buffered
+/// here until the next `flush()`.
+///
+static PENDING: Mutex<Vec<PendingCall>> = Mutex::new(vec![]);
+
+/// Record an API call made on behalf of `feature`, along with the rate limit
+/// resource it was charged to and the budget remaining, if reported.
+///
+/// # Arguments
+///
+/// * `token` - The token the call was made with.
+/// * `host` - The host the call was made to.
+/// * `feature` - The feature the call was made for; e.g., `files`.
+/// * `cost` - The call's cost against its rate limit, as reported by GitHub
+///   or, failing that, as estimated by us.
+/// * `headers` - The headers of the response obtained for the call.
+///
+pub fn record(
+    token: &String,
+    host: &String,
+    feature: &str,
+    cost: i64,
+    headers: &reqwest::header::HeaderMap,
+) {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+
+    PENDING.lock().unwrap().push(PendingCall {
+        token: token.clone(),
+        host: host.clone(),
+        feature: feature.to_string(),
+        resource: header("x-ratelimit-resource"),
+        cost,
+        remaining: header("x-ratelimit-remaining")
+            .and_then(|v| v.parse::<i64>().ok()),
+        called_at: chrono::Utc::now().timestamp(),
+    });
+}
+
+/// Write the API calls recorded since the last flush to the ledger, and tally
+/// them per account and hour, dropping the oldest calls beyond the ledger's
+/// size and hours beyond `USAGE_HOURS_KEPT`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn flush(db: &DB) {
+    let pending: Vec<PendingCall> =
+        std::mem::take(&mut *PENDING.lock().unwrap());
+    if pending.is_empty() {
+        return;
+    }
+
+    let size = config::get_setting_or(&db, SETTING_LEDGER_SIZE, "")
+        .await
+        .parse::<i64>()
+        .unwrap_or(DEFAULT_LEDGER_SIZE);
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to flush ledger: {}", err);
+        }
+    };
+
+    for call in &pending {
+        // calls are attributed to the account owning the token, not the
+        // token itself, which we don't want lying around.
+        sqlx::query(
+            "
+            INSERT INTO api_calls (
+                account_id, host, feature, resource, cost, remaining,
+                called_at
+            ) VALUES (
+                (SELECT user_id FROM tokens WHERE token = ?), ?, ?, ?, ?, ?, ?
+            )
+            ",
+        )
+        .bind(&call.token)
+        .bind(&call.host)
+        .bind(&call.feature)
+        .bind(&call.resource)
+        .bind(&call.cost)
+        .bind(&call.remaining)
+        .bind(&call.called_at)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error recording api call: {}", err);
+        });
+
+        sqlx::query(
+            "
+            INSERT INTO api_usage_hours (account_id, host, hour, calls, cost)
+            VALUES (
+                COALESCE((SELECT user_id FROM tokens WHERE token = ?), 0),
+                ?, ?, 1, ?
+            )
+            ON CONFLICT (account_id, host, hour) DO UPDATE SET
+                calls = calls + 1, cost = cost + excluded.cost
+            ",
+        )
+        .bind(&call.token)
+        .bind(&call.host)
+        .bind(call.called_at - call.called_at % 3600)
+        .bind(&call.cost)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error recording hourly api usage: {}", err);
+        });
+    }
+
+    sqlx::query(
+        "DELETE FROM api_calls WHERE id <= (SELECT MAX(id) FROM api_calls) - ?",
+    )
+    .bind(&size)
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error trimming api call ledger: {}", err);
+    });
+
+    sqlx::query("DELETE FROM api_usage_hours WHERE hour < ?")
+        .bind(chrono::Utc::now().timestamp() - USAGE_HOURS_KEPT * 3600)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error trimming hourly api usage: {}", err);
+        });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit api call ledger: {}", err);
+    });
+}
+
+/// Obtain API usage since `since`, aggregated per account and feature, most
+/// expensive first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `since` - Timestamp since which to aggregate calls.
+///
+pub async fn get_usage(
+    db: &DB,
+    since: &i64,
+) -> Result<Vec<ApiUsageEntry>, GHDError> {
+    match sqlx::query_as::<_, ApiUsageEntry>(
+        "
+        SELECT
+            api_calls.account_id, users.login AS account, api_calls.host,
+            api_calls.feature, api_calls.resource,
+            COUNT(*) AS calls, SUM(api_calls.cost) AS cost,
+            MIN(api_calls.remaining) AS min_remaining,
+            MAX(api_calls.called_at) AS last_called_at
+        FROM
+            api_calls LEFT JOIN users ON users.id = api_calls.account_id
+        WHERE
+            api_calls.called_at >= ?
+        GROUP BY
+            api_calls.account_id, api_calls.host, api_calls.feature,
+            api_calls.resource
+        ORDER BY
+            cost DESC, api_calls.feature
+        ",
+    )
+    .bind(&since)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain api usage: {}", err);
+        }
+    }
+}
+
+/// Obtain the state of each rate limit resource per account, e.g. `core` for
+/// REST and `graphql` for GraphQL, which GitHub budgets separately: the
+/// points we've consumed over the last hour and the points remaining, as last
+/// reported.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_rate_limits(
+    db: &DB,
+) -> Result<Vec<RateLimitStatus>, GHDError> {
+    let since = chrono::Utc::now().timestamp() - 3600;
+
+    match sqlx::query_as::<_, RateLimitStatus>(
+        "
+        SELECT
+            api_calls.account_id, users.login AS account, api_calls.host,
+            api_calls.resource, SUM(api_calls.cost) AS consumed,
+            (
+                SELECT latest.remaining FROM api_calls AS latest
+                WHERE
+                    latest.account_id IS api_calls.account_id
+                    AND latest.host = api_calls.host
+                    AND latest.resource IS api_calls.resource
+                ORDER BY latest.id DESC LIMIT 1
+            ) AS remaining,
+            MAX(api_calls.called_at) AS last_called_at
+        FROM
+            api_calls LEFT JOIN users ON users.id = api_calls.account_id
+        WHERE
+            api_calls.called_at >= ?
+        GROUP BY
+            api_calls.account_id, api_calls.host, api_calls.resource
+        ORDER BY
+            account, api_calls.resource
+        ",
+    )
+    .bind(&since)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain rate limits: {}", err);
+        }
+    }
+}
+
+/// Obtain the API calls made per account and hour since `since`, most recent
+/// first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `since` - Timestamp since which to obtain hours.
+///
+pub async fn get_hourly_usage(db: &DB, since: &i64) -> Vec<HourlyUsage> {
+    match sqlx::query_as::<_, HourlyUsage>(
+        "
+        SELECT
+            NULLIF(api_usage_hours.account_id, 0) AS account_id,
+            users.login AS account, api_usage_hours.host,
+            api_usage_hours.hour, api_usage_hours.calls, api_usage_hours.cost
+        FROM
+            api_usage_hours
+            LEFT JOIN users ON users.id = api_usage_hours.account_id
+        WHERE
+            api_usage_hours.hour >= ? - ? % 3600
+        ORDER BY
+            api_usage_hours.hour DESC, account
+        ",
+    )
+    .bind(&since)
+    .bind(&since)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain hourly api usage: {}", err);
+        }
+    }
+}
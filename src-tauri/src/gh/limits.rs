@@ -0,0 +1,195 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{config, db::DB};
+
+use super::types::Truncation;
+
+/// Setting holding the largest API response body we'll read, in KiB.
+/// Larger responses are dropped as errors.
+///
+pub const SETTING_MAX_RESPONSE_KB: &str = "limits.max_response_kb";
+
+/// Setting holding the most files changed by a Pull Request we'll obtain.
+/// Longer lists are truncated.
+///
+pub const SETTING_MAX_LIST_ITEMS: &str = "limits.max_list_items";
+
+const DEFAULT_MAX_RESPONSE_KB: usize = 10 * 1024;
+const DEFAULT_MAX_LIST_ITEMS: usize = 3000;
+
+pub const KIND_FILES: &str = "files";
+
+/// The limits in effect, as loaded from the settings. Kept in memory, as
+/// they're checked on every API call.
+///
+static MAX_RESPONSE_BYTES: AtomicUsize =
+    AtomicUsize::new(DEFAULT_MAX_RESPONSE_KB * 1024);
+static MAX_LIST_ITEMS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_LIST_ITEMS);
+
+/// Check whether `key` is one of the limit settings.
+///
+pub fn is_setting(key: &str) -> bool {
+    key == SETTING_MAX_RESPONSE_KB || key == SETTING_MAX_LIST_ITEMS
+}
+
+async fn get_usize_setting(db: &DB, key: &str, default: usize) -> usize {
+    match config::get_setting_or(&db, key, &default.to_string())
+        .await
+        .parse::<usize>()
+    {
+        Ok(v) if v > 0 => v,
+        _ => default,
+    }
+}
+
+/// Load the limits from the settings, to be applied from then on.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn load(db: &DB) {
+    let kb = get_usize_setting(
+        &db,
+        SETTING_MAX_RESPONSE_KB,
+        DEFAULT_MAX_RESPONSE_KB,
+    )
+    .await;
+    let items =
+        get_usize_setting(&db, SETTING_MAX_LIST_ITEMS, DEFAULT_MAX_LIST_ITEMS)
+            .await;
+    MAX_RESPONSE_BYTES.store(kb.saturating_mul(1024), Ordering::Relaxed);
+    MAX_LIST_ITEMS.store(items, Ordering::Relaxed);
+}
+
+/// Obtain the largest API response body we'll read, in bytes.
+///
+pub fn max_response_bytes() -> usize {
+    MAX_RESPONSE_BYTES.load(Ordering::Relaxed)
+}
+
+/// Obtain the most entries we'll obtain for a paginated list.
+///
+pub fn max_list_items() -> usize {
+    MAX_LIST_ITEMS.load(Ordering::Relaxed)
+}
+
+/// Read the body of `res`, giving up as soon as it grows past
+/// `max_response_bytes()`, rather than reading all of it first. Returns
+/// `None` if it did.
+///
+pub async fn read_body(mut res: reqwest::Response) -> Option<String> {
+    let max = max_response_bytes();
+    if res.content_length().map_or(false, |l| l as usize > max) {
+        return None;
+    }
+
+    let mut body: Vec<u8> = vec![];
+    loop {
+        match res.chunk().await {
+            Ok(Some(chunk)) => {
+                if body.len() + chunk.len() > max {
+                    return None;
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(err) => {
+                panic!("Unable to read response body: {}", err);
+            }
+        }
+    }
+
+    Some(String::from_utf8_lossy(&body).to_string())
+}
+
+/// Record whether the list of `kind` obtained for issue `issueid` was
+/// truncated, keeping `kept` entries. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issueid` - The issue's database ID.
+/// * `kind` - What was listed; e.g., `files`.
+/// * `truncated` - Whether the list was truncated.
+/// * `kept` - How many entries were kept.
+///
+pub async fn record(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issueid: &i64,
+    kind: &str,
+    truncated: bool,
+    kept: usize,
+) {
+    let res = match truncated {
+        true => {
+            sqlx::query(
+                "
+            INSERT OR REPLACE INTO truncations (
+                issue_id, kind, kept, truncated_at
+            ) VALUES (?, ?, ?, ?)
+            ",
+            )
+            .bind(&issueid)
+            .bind(&kind)
+            .bind(kept as i64)
+            .bind(chrono::Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await
+        }
+        false => {
+            sqlx::query(
+                "DELETE FROM truncations WHERE issue_id = ? AND kind = ?",
+            )
+            .bind(&issueid)
+            .bind(&kind)
+            .execute(&mut *tx)
+            .await
+        }
+    };
+    res.unwrap_or_else(|err| {
+        panic!(
+            "Unable to record truncation of {} for issue {}: {}",
+            kind, issueid, err
+        );
+    });
+}
+
+/// Obtain the lists obtained for issue `issueid` that were truncated.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn get_truncations(db: &DB, issueid: &i64) -> Vec<Truncation> {
+    match sqlx::query_as::<_, Truncation>(
+        "SELECT * FROM truncations WHERE issue_id = ? ORDER BY kind",
+    )
+    .bind(&issueid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Unable to obtain truncations for issue {}: {}",
+                issueid, err
+            );
+        }
+    }
+}
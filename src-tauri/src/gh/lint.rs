@@ -0,0 +1,177 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::types::LintWarning;
+
+/// Prefix for settings holding a regular expression our Pull Request titles
+/// must match, e.g. `lint.title.ticket` = `[A-Z]+-[0-9]+`.
+///
+pub const SETTING_TITLE_PREFIX: &str = "lint.title.";
+
+/// Prefix for settings holding a regular expression our Pull Request head
+/// branch names must match.
+///
+pub const SETTING_BRANCH_PREFIX: &str = "lint.branch.";
+
+/// A lint rule, as loaded from the settings.
+///
+struct LintRule {
+    key: String,
+    field: &'static str,
+    pattern: String,
+    re: Regex,
+}
+
+/// Load the lint rules from the settings, skipping those that aren't valid
+/// regular expressions.
+///
+async fn load_rules(db: &DB) -> Vec<LintRule> {
+    config::get_settings(&db)
+        .await
+        .into_iter()
+        .filter_map(|entry| {
+            let field = if entry.key.starts_with(SETTING_TITLE_PREFIX) {
+                "title"
+            } else if entry.key.starts_with(SETTING_BRANCH_PREFIX) {
+                "branch"
+            } else {
+                return None;
+            };
+            match Regex::new(&entry.value) {
+                Ok(re) => Some(LintRule {
+                    key: entry.key,
+                    field,
+                    pattern: entry.value,
+                    re,
+                }),
+                Err(err) => {
+                    println!(
+                        "ignoring invalid lint rule '{}': {}",
+                        entry.key, err
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Whether setting `key` holds a lint rule.
+///
+pub fn is_rule(key: &str) -> bool {
+    key.starts_with(SETTING_TITLE_PREFIX)
+        || key.starts_with(SETTING_BRANCH_PREFIX)
+}
+
+/// Lint the open Pull Requests authored by our accounts against the rules in
+/// the settings, replacing the stored warnings.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn run(db: &DB) {
+    let rules = load_rules(&db).await;
+
+    let prs = match sqlx::query_as::<_, (i64, String, String)>(
+        "
+        SELECT issues.id, issues.title, pr_head_refs.head_ref
+        FROM issues
+        JOIN pr_head_refs ON pr_head_refs.pr_id = issues.id
+        WHERE
+            issues.is_pull_request = 1
+            AND issues.state = 'open'
+            AND issues.author_id IN (SELECT user_id FROM tokens)
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain pull requests to lint: {}", err);
+        }
+    };
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to lint pull requests: {}", err);
+        }
+    };
+    sqlx::query("DELETE FROM pr_lint_warnings")
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to clear lint warnings: {}", err);
+        });
+
+    for (prid, title, head_ref) in &prs {
+        for rule in &rules {
+            let value = match rule.field {
+                "title" => title,
+                _ => head_ref,
+            };
+            if rule.re.is_match(&value) {
+                continue;
+            }
+            sqlx::query(
+                "
+                INSERT INTO pr_lint_warnings (pr_id, rule, field, pattern)
+                VALUES (?, ?, ?, ?)
+                ",
+            )
+            .bind(&prid)
+            .bind(&rule.key)
+            .bind(&rule.field)
+            .bind(&rule.pattern)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to record lint warning for {}: {}", prid, err);
+            });
+        }
+    }
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!(
+            "Unable to commit transaction to lint pull requests: {}",
+            err
+        );
+    });
+}
+
+/// Obtain the lint warnings for our open Pull Requests.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_warnings(db: &DB) -> Result<Vec<LintWarning>, GHDError> {
+    match sqlx::query_as::<_, LintWarning>(
+        "SELECT * FROM pr_lint_warnings ORDER BY pr_id, rule",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain lint warnings: {}", err);
+        }
+    }
+}
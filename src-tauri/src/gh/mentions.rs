@@ -0,0 +1,185 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    gql, refresh, repos,
+    types::{GithubTeam, Issue, TeamMention},
+};
+
+/// Team mentions are checked every half hour.
+///
+pub const MENTIONS_REFRESH_INTERVAL: i64 = 30 * 60;
+
+/// How far back mentions of a team are looked for.
+///
+const MENTIONS_WINDOW_DAYS: i64 = 30;
+
+/// How many repositories are looked in per search, keeping each search's
+/// qualifiers within what GitHub accepts.
+///
+const MENTIONS_REPOS_PER_SEARCH: usize = 20;
+
+/// Obtain the sync refresh key for the mentions of team `teamid`.
+///
+pub fn refresh_key(teamid: &i64) -> String {
+    format!("team_mentions:{}", teamid)
+}
+
+/// Check whether the mentions of team `teamid` are due for a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `teamid` - The team's database ID.
+///
+pub async fn should_refresh(db: &DB, teamid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&teamid),
+        MENTIONS_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Obtain the issues and Pull Requests recently mentioning team `team`, on
+/// maintained repositories only; mentions elsewhere are someone else's
+/// business. Repositories are searched a chunk at a time, so that mentions
+/// on them aren't crowded out by those elsewhere.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `creds` - The credentials to use when talking to the API.
+/// * `team` - The team to obtain mentions for.
+///
+pub async fn fetch(
+    db: &DB,
+    creds: &AccountCredentials,
+    team: &GithubTeam,
+) -> Result<Vec<Issue>, GHDError> {
+    let since =
+        chrono::Utc::now() - chrono::Duration::days(MENTIONS_WINDOW_DAYS);
+    let repos = repos::get_repos(&db)
+        .await?
+        .into_iter()
+        .map(|r| format!("{}/{}", r.owner, r.name))
+        .collect::<Vec<String>>();
+
+    let mut issues = vec![];
+    for chunk in repos.chunks(MENTIONS_REPOS_PER_SEARCH) {
+        let update = gql::get_team_mentions(
+            &creds.token,
+            &creds.host,
+            &team.org_login,
+            &team.slug,
+            &chunk,
+            &since,
+        )
+        .await?;
+        issues.extend(
+            update
+                .issues
+                .into_iter()
+                .chain(update.prs.into_iter().map(|pr| pr.issue)),
+        );
+    }
+    Ok(issues)
+}
+
+/// Write the issues and Pull Requests mentioning team `teamid`, replacing
+/// those previously known. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `teamid` - The team's database ID.
+/// * `issues` - The issues and Pull Requests mentioning the team.
+/// * `when` - When the mentions were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    teamid: &i64,
+    issues: &Vec<Issue>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query("DELETE FROM team_mentions WHERE team_id = ?")
+        .bind(&teamid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing mentions for team {}: {}", teamid, err);
+        });
+
+    for issue in issues {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO team_mentions (
+                team_id, issue_id, repo_owner, repo_name, number, title,
+                author, url, is_pull_request, state, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&teamid)
+        .bind(&issue.id)
+        .bind(&issue.repo_owner)
+        .bind(&issue.repo_name)
+        .bind(&issue.number)
+        .bind(&issue.title)
+        .bind(&issue.author)
+        .bind(&issue.url)
+        .bind(&issue.is_pull_request)
+        .bind(&issue.state)
+        .bind(&issue.updated_at.timestamp())
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting mention of team {}: {}", teamid, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&teamid), &when).await;
+}
+
+/// Obtain the issues and Pull Requests mentioning our users' teams, most
+/// recently updated first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_mentions(db: &DB) -> Vec<TeamMention> {
+    match sqlx::query_as::<_, TeamMention>(
+        "
+        SELECT
+            team_mentions.*, orgs.login AS org_login, teams.slug AS team_slug
+        FROM
+            team_mentions
+            INNER JOIN teams ON teams.id = team_mentions.team_id
+            INNER JOIN orgs ON orgs.id = teams.org_id
+        WHERE team_mentions.team_id IN (SELECT team_id FROM user_teams)
+        ORDER BY team_mentions.updated_at DESC, team_mentions.issue_id
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain team mentions: {}", err);
+        }
+    }
+}
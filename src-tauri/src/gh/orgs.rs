@@ -0,0 +1,332 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    refresh, rest,
+    types::{GithubOrg, GithubTeam, OrgUpdate, TeamMember},
+};
+
+/// Organizations and teams change rarely; refresh them every six hours.
+///
+pub const ORG_REFRESH_INTERVAL: i64 = 6 * 60 * 60;
+
+/// Obtain the sync refresh key for the organizations of user `userid`.
+///
+pub fn refresh_key(userid: &i64) -> String {
+    format!("orgs:{}", userid)
+}
+
+/// Check whether the organizations and teams for user `userid` are due for
+/// a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn should_refresh(db: &DB, userid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&userid),
+        ORG_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Translate a failure to list organization data into a `GHDError`. Lacking
+/// the `read:org` scope, GitHub forbids access rather than refusing the
+/// token altogether.
+///
+fn status_to_error(status: reqwest::StatusCode) -> GHDError {
+    match status {
+        reqwest::StatusCode::FORBIDDEN => {
+            GHDError::MissingScopeError("read:org".into())
+        }
+        _ => rest::status_to_error(status),
+    }
+}
+
+/// Obtain the organizations and teams the token's owner belongs to, along
+/// with the roster of each team. Requires the token to have `read:org` scope.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials of the account to obtain data for.
+///
+pub async fn fetch(creds: &AccountCredentials) -> Result<OrgUpdate, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("orgs");
+
+    let orgs = match ghreq
+        .get_all_pages::<rest::GithubOrgReply>("/user/orgs")
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(status_to_error(err)),
+    };
+
+    let teams = match ghreq
+        .get_all_pages::<rest::GithubTeamReply>("/user/teams")
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(status_to_error(err)),
+    };
+
+    let mut members: Vec<TeamMember> = vec![];
+    for team in &teams {
+        let ep = format!(
+            "/orgs/{}/teams/{}/members",
+            team.organization.login, team.slug
+        );
+        let roster = match ghreq
+            .get_all_pages::<rest::GithubTeamMemberReply>(&ep)
+            .await
+        {
+            Ok(res) => res,
+            Err(err) => return Err(status_to_error(err)),
+        };
+        for m in roster {
+            members.push(TeamMember {
+                team_id: team.id,
+                user_id: m.id,
+                login: m.login,
+            });
+        }
+    }
+
+    Ok(OrgUpdate {
+        when: chrono::Utc::now(),
+        orgs: orgs
+            .into_iter()
+            .map(|o| GithubOrg {
+                id: o.id,
+                login: o.login,
+                avatar_url: o.avatar_url,
+            })
+            .collect(),
+        teams: teams
+            .into_iter()
+            .map(|t| GithubTeam {
+                id: t.id,
+                org_id: t.organization.id,
+                org_login: t.organization.login,
+                slug: t.slug,
+                name: t.name,
+            })
+            .collect(),
+        members,
+    })
+}
+
+/// Write the provided organization update to the database, replacing any
+/// previously known memberships for user `userid`. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The database ID of the account's user.
+/// * `update` - The organizations, teams, and rosters obtained for the user.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    update: &OrgUpdate,
+) {
+    for q in [
+        "DELETE FROM user_orgs WHERE user_id = ?",
+        "DELETE FROM user_teams WHERE user_id = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&userid)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error clearing memberships for {}: {}", userid, err);
+            });
+    }
+
+    for org in &update.orgs {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO orgs (id, login, avatar_url)
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&org.id)
+        .bind(&org.login)
+        .bind(&org.avatar_url)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting org '{}': {}", org.login, err);
+        });
+
+        sqlx::query("INSERT INTO user_orgs (user_id, org_id) VALUES (?, ?)")
+            .bind(&userid)
+            .bind(&org.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error inserting org membership: {}", err);
+            });
+    }
+
+    for team in &update.teams {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO teams (id, org_id, slug, name)
+            VALUES (?, ?, ?, ?)
+            ",
+        )
+        .bind(&team.id)
+        .bind(&team.org_id)
+        .bind(&team.slug)
+        .bind(&team.name)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting team '{}': {}", team.slug, err);
+        });
+
+        sqlx::query("INSERT INTO user_teams (user_id, team_id) VALUES (?, ?)")
+            .bind(&userid)
+            .bind(&team.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error inserting team membership: {}", err);
+            });
+
+        sqlx::query("DELETE FROM team_members WHERE team_id = ?")
+            .bind(&team.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error clearing roster for team {}: {}", team.id, err);
+            });
+    }
+
+    for m in &update.members {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO team_members (team_id, user_id, login)
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&m.team_id)
+        .bind(&m.user_id)
+        .bind(&m.login)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting team member '{}': {}", m.login, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&userid), &update.when).await;
+}
+
+/// Obtain the organizations user `userid` belongs to.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn get_orgs(
+    db: &DB,
+    userid: &i64,
+) -> Result<Vec<GithubOrg>, GHDError> {
+    match sqlx::query_as::<_, GithubOrg>(
+        "
+        SELECT orgs.* FROM orgs INNER JOIN user_orgs
+        ON orgs.id = user_orgs.org_id
+        WHERE user_orgs.user_id = ?
+        ORDER BY orgs.login
+        ",
+    )
+    .bind(&userid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain orgs from db: {}", err);
+        }
+    }
+}
+
+/// Obtain the teams user `userid` is a member of.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn get_teams(
+    db: &DB,
+    userid: &i64,
+) -> Result<Vec<GithubTeam>, GHDError> {
+    match sqlx::query_as::<_, GithubTeam>(
+        "
+        SELECT
+            teams.*, orgs.login AS org_login
+        FROM
+            teams
+            INNER JOIN user_teams ON teams.id = user_teams.team_id
+            INNER JOIN orgs ON teams.org_id = orgs.id
+        WHERE user_teams.user_id = ?
+        ORDER BY orgs.login, teams.slug
+        ",
+    )
+    .bind(&userid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain teams from db: {}", err);
+        }
+    }
+}
+
+/// Obtain the roster of team `teamid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `teamid` - The team's database ID.
+///
+pub async fn get_team_members(
+    db: &DB,
+    teamid: &i64,
+) -> Result<Vec<TeamMember>, GHDError> {
+    match sqlx::query_as::<_, TeamMember>(
+        "SELECT * FROM team_members WHERE team_id = ? ORDER BY login",
+    )
+    .bind(&teamid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain team members from db: {}", err);
+        }
+    }
+}
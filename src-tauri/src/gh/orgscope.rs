@@ -0,0 +1,433 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    gql, prs, refresh, rest,
+    types::{
+        GithubTeam, OrgOverview, OrgRepoCounts, OrgScope, OrgScopeUpdate,
+        OrgTeamCounts, TeamMember,
+    },
+};
+
+/// An organization's open Pull Requests are many, and searching for them is
+/// costly; refresh them every hour.
+///
+pub const ORG_SCOPE_REFRESH_INTERVAL: i64 = 60 * 60;
+
+/// Obtain the sync refresh key for the organization-wide sync of `orgid`.
+///
+pub fn refresh_key(orgid: &i64) -> String {
+    format!("org_scope:{}", orgid)
+}
+
+/// Check whether the organization-wide sync of `orgid` is due.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `orgid` - The organization's database ID.
+///
+pub async fn should_refresh(db: &DB, orgid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&orgid),
+        ORG_SCOPE_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Check whether the token's owner is an admin of organization `org`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials of the account to check.
+/// * `org` - The organization's login.
+///
+pub async fn is_admin(
+    creds: &AccountCredentials,
+    org: &String,
+) -> Result<bool, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("org_scope");
+    let ep = format!("/user/memberships/orgs/{}", org);
+    match ghreq
+        .send::<rest::GithubOrgMembershipReply>(ghreq.get(&ep))
+        .await
+    {
+        Ok(res) => Ok(res.role == "admin"),
+        Err(reqwest::StatusCode::NOT_FOUND) => Ok(false),
+        Err(err) => Err(rest::status_to_error(err)),
+    }
+}
+
+/// Obtain all open Pull Requests of organization `org`, along with all its
+/// teams and their rosters, provided the token's owner is still one of its
+/// admins. Requires the token to have `read:org` scope.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials of the account to obtain data for.
+/// * `orgid` - The organization's database ID.
+/// * `org` - The organization's login.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    orgid: &i64,
+    org: &String,
+) -> Result<OrgScopeUpdate, GHDError> {
+    if !is_admin(&creds, &org).await? {
+        return Err(GHDError::PermissionDeniedError);
+    }
+
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("org_scope");
+    let teams = match ghreq
+        .get_all_pages::<rest::GithubTeamReply>(&format!("/orgs/{}/teams", org))
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    let mut members: Vec<TeamMember> = vec![];
+    for team in &teams {
+        let ep = format!("/orgs/{}/teams/{}/members", org, team.slug);
+        let roster = match ghreq
+            .get_all_pages::<rest::GithubTeamMemberReply>(&ep)
+            .await
+        {
+            Ok(res) => res,
+            Err(err) => return Err(rest::status_to_error(err)),
+        };
+        for m in roster {
+            members.push(TeamMember {
+                team_id: team.id,
+                user_id: m.id,
+                login: m.login,
+            });
+        }
+    }
+
+    let update =
+        gql::get_org_open_pulls(&creds.token, &creds.host, &org).await?;
+
+    Ok(OrgScopeUpdate {
+        when: update.when,
+        org_id: *orgid,
+        capped: update.prs.len() as i64 >= gql::ORG_SEARCH_MAX_RESULTS,
+        prs: update.prs,
+        teams: teams
+            .into_iter()
+            .map(|t| GithubTeam {
+                id: t.id,
+                org_id: *orgid,
+                org_login: org.clone(),
+                slug: t.slug,
+                name: t.name,
+            })
+            .collect(),
+        members,
+    })
+}
+
+/// Write the provided organization-wide update to the database, replacing
+/// the organization's previously synced Pull Requests and team rosters. This
+/// function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `update` - The open Pull Requests and teams obtained for the
+///   organization.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    update: &OrgScopeUpdate,
+) {
+    let ids = prs::consume_pulls(tx, &update.prs).await;
+
+    sqlx::query("DELETE FROM org_scope_prs WHERE org_id = ?")
+        .bind(&update.org_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing org {} prs: {}", update.org_id, err);
+        });
+    for id in &ids {
+        sqlx::query(
+            "INSERT OR REPLACE INTO org_scope_prs (org_id, pr_id) VALUES (?, ?)",
+        )
+        .bind(&update.org_id)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error marking pr {} as org-wide: {}", id, err);
+        });
+    }
+
+    for team in &update.teams {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO teams (id, org_id, slug, name)
+            VALUES (?, ?, ?, ?)
+            ",
+        )
+        .bind(&team.id)
+        .bind(&team.org_id)
+        .bind(&team.slug)
+        .bind(&team.name)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting team '{}': {}", team.slug, err);
+        });
+
+        sqlx::query("DELETE FROM team_members WHERE team_id = ?")
+            .bind(&team.id)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error clearing roster for team {}: {}", team.id, err);
+            });
+    }
+
+    for m in &update.members {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO team_members (team_id, user_id, login)
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&m.team_id)
+        .bind(&m.user_id)
+        .bind(&m.login)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting team member '{}': {}", m.login, err);
+        });
+    }
+
+    sqlx::query(
+        "UPDATE org_scopes SET synced_at = ?, capped = ? WHERE org_id = ?",
+    )
+    .bind(&update.when.timestamp())
+    .bind(&update.capped)
+    .bind(&update.org_id)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating org scope {}: {}", update.org_id, err);
+    });
+
+    refresh::update_sync_refresh(
+        tx,
+        &refresh_key(&update.org_id),
+        &update.when,
+    )
+    .await;
+}
+
+/// Enable the organization-wide sync of `orgid`, as user `userid`. Only
+/// admins of the organization may do so.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `creds` - The credentials of the user's account.
+/// * `userid` - The database ID of the account's user.
+/// * `orgid` - The organization's database ID.
+/// * `org` - The organization's login.
+///
+pub async fn enable(
+    db: &DB,
+    creds: &AccountCredentials,
+    userid: &i64,
+    orgid: &i64,
+    org: &String,
+) -> Result<(), GHDError> {
+    if !is_admin(&creds, &org).await? {
+        return Err(GHDError::PermissionDeniedError);
+    }
+
+    sqlx::query(
+        "
+        INSERT INTO org_scopes (org_id, user_id, enabled_at, capped)
+        VALUES (?1, ?2, ?3, FALSE)
+        ON CONFLICT (org_id) DO UPDATE SET user_id = ?2
+        ",
+    )
+    .bind(&orgid)
+    .bind(&userid)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to enable org scope for '{}': {}", org, err);
+    });
+
+    Ok(())
+}
+
+/// Disable the organization-wide sync of `orgid`, forgetting which Pull
+/// Requests were synced for it. The Pull Requests themselves are kept.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `orgid` - The organization's database ID.
+///
+pub async fn disable(db: &DB, orgid: &i64) {
+    for q in [
+        "DELETE FROM org_scope_prs WHERE org_id = ?",
+        "DELETE FROM org_scopes WHERE org_id = ?",
+        "DELETE FROM sync_refresh WHERE key = 'org_scope:' || ?",
+    ] {
+        sqlx::query(q)
+            .bind(&orgid)
+            .execute(db.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to disable org scope {}: {}", orgid, err);
+            });
+    }
+}
+
+/// Obtain the organizations being synced organization-wide.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_scopes(db: &DB) -> Vec<OrgScope> {
+    match sqlx::query_as::<_, OrgScope>(
+        "
+        SELECT org_scopes.*, orgs.login AS org_login
+        FROM org_scopes INNER JOIN orgs ON org_scopes.org_id = orgs.id
+        ORDER BY orgs.login
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain org scopes: {}", err);
+        }
+    }
+}
+
+/// Obtain an overview of organization `orgid`'s open Pull Requests, as of
+/// its last organization-wide sync, with counts by repository and by team.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `orgid` - The organization's database ID.
+///
+pub async fn get_overview(
+    db: &DB,
+    orgid: &i64,
+) -> Result<OrgOverview, GHDError> {
+    let scope = match get_scopes(&db)
+        .await
+        .into_iter()
+        .find(|s| s.org_id == *orgid)
+    {
+        Some(res) => res,
+        None => return Err(GHDError::NotFoundError),
+    };
+
+    let repos = match sqlx::query_as::<_, OrgRepoCounts>(
+        "
+        SELECT
+            issues.repo_owner, issues.repo_name,
+            COUNT(*) AS open,
+            SUM(pull_requests.is_draft) AS drafts,
+            SUM(pull_requests.review_decision = 'approved') AS approved,
+            SUM(pull_requests.review_decision = 'changes_requested')
+                AS changes_requested,
+            SUM(pull_requests.review_decision = 'review_required')
+                AS review_required,
+            MIN(issues.created_at) AS oldest_created_at
+        FROM
+            org_scope_prs
+            INNER JOIN issues ON org_scope_prs.pr_id = issues.id
+            INNER JOIN pull_requests ON org_scope_prs.pr_id = pull_requests.id
+        WHERE org_scope_prs.org_id = ? AND issues.state = 'open'
+        GROUP BY issues.repo_owner, issues.repo_name
+        ORDER BY open DESC, issues.repo_name
+        ",
+    )
+    .bind(&orgid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain org {} repo counts: {}", orgid, err);
+        }
+    };
+
+    let teams = match sqlx::query_as::<_, OrgTeamCounts>(
+        "
+        SELECT
+            teams.id AS team_id, teams.slug, teams.name,
+            (
+                SELECT COUNT(*) FROM team_members
+                WHERE team_members.team_id = teams.id
+            ) AS members,
+            COUNT(issues.id) AS open,
+            COALESCE(SUM(pull_requests.is_draft), 0) AS drafts,
+            COALESCE(SUM(pull_requests.review_decision = 'approved'), 0)
+                AS approved,
+            COALESCE(
+                SUM(pull_requests.review_decision = 'changes_requested'), 0
+            ) AS changes_requested
+        FROM
+            teams
+            LEFT JOIN team_members ON team_members.team_id = teams.id
+            LEFT JOIN issues
+                ON issues.author_id = team_members.user_id
+                AND issues.state = 'open'
+                AND issues.id IN (
+                    SELECT pr_id FROM org_scope_prs WHERE org_id = ?1
+                )
+            LEFT JOIN pull_requests ON pull_requests.id = issues.id
+        WHERE teams.org_id = ?1
+        GROUP BY teams.id
+        ORDER BY open DESC, teams.slug
+        ",
+    )
+    .bind(&orgid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain org {} team counts: {}", orgid, err);
+        }
+    };
+
+    Ok(OrgOverview {
+        open: repos.iter().map(|r| r.open).sum(),
+        scope,
+        repos,
+        teams,
+    })
+}
@@ -0,0 +1,248 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError, vault};
+
+use super::{
+    accounts::AccountCredentials,
+    rest,
+    types::{OutboxEntry, PullRequestTableEntry},
+};
+
+pub const STATE_PENDING: &str = "pending";
+pub const STATE_DONE: &str = "done";
+pub const STATE_FAILED: &str = "failed";
+pub const STATE_DISCARDED: &str = "discarded";
+
+/// A write action on a Pull Request.
+///
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PullRequestAction {
+    Comment {
+        body: String,
+    },
+    /// `event` is one of `APPROVE`, `REQUEST_CHANGES`, or `COMMENT`.
+    Review {
+        event: String,
+        body: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        comments: Vec<ReviewComment>,
+    },
+    /// `method` is one of `merge`, `squash`, or `rebase`.
+    Merge {
+        method: String,
+    },
+}
+
+/// An inline comment submitted along with a review, on `line` of `path` on
+/// `side` of the diff, `LEFT` or `RIGHT`.
+///
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct ReviewComment {
+    pub path: String,
+    pub line: i64,
+    pub side: String,
+    pub body: String,
+}
+
+impl PullRequestAction {
+    pub fn kind(self: &Self) -> &'static str {
+        match self {
+            PullRequestAction::Comment { .. } => "comment",
+            PullRequestAction::Review { .. } => "review",
+            PullRequestAction::Merge { .. } => "merge",
+        }
+    }
+}
+
+/// Record the intent to run `action` on Pull Request `prid`, before running
+/// it. Returns the outbox entry's ID, or `KeyUnavailableError` if the action
+/// can't be encrypted as configured.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+/// * `action` - The action about to be run.
+///
+pub async fn record(
+    db: &DB,
+    prid: &i64,
+    action: &PullRequestAction,
+) -> Result<i64, GHDError> {
+    let payload = serde_json::to_string(&action).unwrap();
+    let payload = vault::sealer(&db, &Some(*prid)).await?.seal(&payload);
+
+    match sqlx::query(
+        "
+        INSERT INTO outbox (kind, pr_id, payload, state, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(action.kind())
+    .bind(&prid)
+    .bind(&payload)
+    .bind(STATE_PENDING)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res.last_insert_rowid()),
+        Err(err) => {
+            panic!(
+                "Unable to record {} for pr {}: {}",
+                action.kind(),
+                prid,
+                err
+            );
+        }
+    }
+}
+
+/// Mark outbox entry `id` as no longer pending, with the provided `state` and
+/// error, if any.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The outbox entry's ID.
+/// * `state` - The entry's final state.
+/// * `error` - Why the action failed, if it did.
+///
+pub async fn complete(db: &DB, id: &i64, state: &str, error: &Option<String>) {
+    sqlx::query(
+        "
+        UPDATE outbox SET state = ?, error = ?, completed_at = ?
+        WHERE id = ?
+        ",
+    )
+    .bind(&state)
+    .bind(&error)
+    .bind(chrono::Utc::now().timestamp())
+    .bind(&id)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error completing outbox entry {}: {}", id, err);
+    });
+}
+
+/// Run `action` on the provided Pull Request through the API.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `pr` - The Pull Request to run the action on.
+/// * `action` - The action to run.
+///
+pub async fn execute(
+    creds: &AccountCredentials,
+    pr: &PullRequestTableEntry,
+    action: &PullRequestAction,
+) -> Result<(), reqwest::StatusCode> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("actions");
+    let repo = format!("/repos/{}/{}", pr.repo_owner, pr.repo_name);
+
+    let req = match action {
+        PullRequestAction::Comment { body } => ghreq
+            .post(&format!("{}/issues/{}/comments", repo, pr.number))
+            .json(&serde_json::json!({ "body": body })),
+        PullRequestAction::Review {
+            event,
+            body,
+            comments,
+        } => ghreq
+            .post(&format!("{}/pulls/{}/reviews", repo, pr.number))
+            .json(&serde_json::json!({
+                "event": event,
+                "body": body,
+                "comments": comments,
+            })),
+        PullRequestAction::Merge { method } => ghreq
+            .put(&format!("{}/pulls/{}/merge", repo, pr.number))
+            .json(&serde_json::json!({ "merge_method": method })),
+    };
+
+    ghreq.send::<serde_json::Value>(req).await.map(|_| ())
+}
+
+/// Obtain outbox entry `id`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The outbox entry's ID.
+///
+pub async fn get_entry(db: &DB, id: &i64) -> Result<OutboxEntry, GHDError> {
+    match sqlx::query_as::<_, OutboxEntry>("SELECT * FROM outbox WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(db.pool())
+        .await
+    {
+        Ok(Some(mut res)) => {
+            res.payload = vault::open(&res.payload).await;
+            Ok(res)
+        }
+        Ok(None) => Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain outbox entry {}: {}", id, err);
+        }
+    }
+}
+
+/// Obtain the outbox entries that were never completed, oldest first. Actions
+/// are completed as soon as they return, so these were interrupted by a
+/// crash; whether they reached GitHub is unknown.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_interrupted(db: &DB) -> Vec<OutboxEntry> {
+    match sqlx::query_as::<_, OutboxEntry>(
+        "SELECT * FROM outbox WHERE state = ? ORDER BY created_at, id",
+    )
+    .bind(STATE_PENDING)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(mut res) => {
+            for entry in &mut res {
+                entry.payload = vault::open(&entry.payload).await;
+            }
+            res
+        }
+        Err(err) => {
+            panic!("Unable to obtain interrupted outbox entries: {}", err);
+        }
+    }
+}
+
+/// Discard interrupted outbox entry `id`, without running it again.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The outbox entry's ID.
+///
+pub async fn discard(db: &DB, id: &i64) -> Result<(), GHDError> {
+    let entry = get_entry(&db, &id).await?;
+    if entry.state != STATE_PENDING {
+        return Err(GHDError::BadRequest);
+    }
+    complete(&db, &id, STATE_DISCARDED, &None).await;
+    Ok(())
+}
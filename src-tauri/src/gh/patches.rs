@@ -0,0 +1,455 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::{accounts::AccountCredentials, rest, types::PullRequestPatch};
+
+/// Setting enabling downloading the patches of small open Pull Requests,
+/// `true` or `false`. Disabled by default.
+///
+pub const SETTING_ENABLED: &str = "patches.enabled";
+
+/// Setting holding the format patches are downloaded in, `diff` or `patch`.
+///
+pub const SETTING_FORMAT: &str = "patches.format";
+
+/// Setting holding how many changed lines, at most, a Pull Request may have
+/// for its patch to be downloaded.
+///
+pub const SETTING_MAX_LINES: &str = "patches.max_lines";
+
+/// Setting holding the largest patch kept, in KiB, before compression.
+///
+pub const SETTING_MAX_KB: &str = "patches.max_kb";
+
+/// Setting holding how much disk the compressed patches may take, in MiB.
+/// The oldest patches are evicted first.
+///
+pub const SETTING_CACHE_MB: &str = "patches.cache_mb";
+
+/// Setting holding how many patches are downloaded concurrently.
+///
+pub const SETTING_CONCURRENCY: &str = "patches.concurrency";
+
+pub const FORMAT_DIFF: &str = "diff";
+pub const FORMAT_PATCH: &str = "patch";
+
+const DEFAULT_MAX_LINES: i64 = 500;
+const DEFAULT_MAX_KB: i64 = 256;
+const DEFAULT_CACHE_MB: i64 = 64;
+const DEFAULT_CONCURRENCY: i64 = 4;
+const MAX_CONCURRENCY: i64 = 16;
+
+/// Patch download settings.
+///
+pub struct PatchSettings {
+    pub format: String,
+    pub max_lines: i64,
+    pub max_bytes: i64,
+    pub cache_bytes: i64,
+    pub concurrency: i64,
+}
+
+/// A Pull Request whose patch should be downloaded.
+///
+#[derive(sqlx::FromRow)]
+pub struct PatchCandidate {
+    pub pr_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+}
+
+/// A downloaded patch: its compressed contents and size before compression.
+///
+pub struct FetchedPatch {
+    pub compressed: Vec<u8>,
+    pub size: i64,
+}
+
+async fn get_int_setting(db: &DB, key: &str, default: i64) -> i64 {
+    config::get_setting_or(&db, key, &default.to_string())
+        .await
+        .parse::<i64>()
+        .unwrap_or(default)
+}
+
+/// Check whether downloading patches is enabled.
+///
+pub async fn is_enabled(db: &DB) -> bool {
+    config::get_setting_or(&db, SETTING_ENABLED, "false").await == "true"
+}
+
+/// Obtain the patch download settings, with defaults for those not set.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_settings(db: &DB) -> PatchSettings {
+    let format =
+        match config::get_setting_or(&db, SETTING_FORMAT, FORMAT_DIFF).await {
+            f if f == FORMAT_PATCH => f,
+            _ => String::from(FORMAT_DIFF),
+        };
+
+    PatchSettings {
+        format,
+        max_lines: get_int_setting(&db, SETTING_MAX_LINES, DEFAULT_MAX_LINES)
+            .await,
+        max_bytes: get_int_setting(&db, SETTING_MAX_KB, DEFAULT_MAX_KB).await
+            * 1024,
+        cache_bytes: get_int_setting(&db, SETTING_CACHE_MB, DEFAULT_CACHE_MB)
+            .await
+            * 1024
+            * 1024,
+        concurrency: get_int_setting(
+            &db,
+            SETTING_CONCURRENCY,
+            DEFAULT_CONCURRENCY,
+        )
+        .await
+        .clamp(1, MAX_CONCURRENCY),
+    }
+}
+
+/// Obtain the open Pull Requests whose patch is missing or outdated, small
+/// enough by their changed files to be downloaded, up to the concurrency
+/// limit. Pull Requests whose files haven't been obtained yet are left for
+/// later, and those whose patch was evicted are only downloaded again once
+/// updated.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `settings` - The patch download settings.
+///
+pub async fn get_candidates(
+    db: &DB,
+    settings: &PatchSettings,
+) -> Vec<PatchCandidate> {
+    match sqlx::query_as::<_, PatchCandidate>(
+        "
+        SELECT
+            issues.id AS pr_id, issues.repo_owner, issues.repo_name,
+            issues.number
+        FROM issues
+        INNER JOIN pr_files ON pr_files.pr_id = issues.id
+        LEFT JOIN pr_patches ON pr_patches.pr_id = issues.id
+        WHERE
+            issues.is_pull_request = 1
+            AND issues.state = 'open'
+            AND (
+                pr_patches.pr_id IS NULL
+                OR (
+                    pr_patches.evicted_at IS NULL
+                    AND pr_patches.format != ?1
+                )
+                OR pr_patches.fetched_at < issues.updated_at
+            )
+        GROUP BY issues.id
+        HAVING SUM(pr_files.additions + pr_files.deletions) <= ?2
+        ORDER BY issues.updated_at DESC
+        LIMIT ?3
+        ",
+    )
+    .bind(&settings.format)
+    .bind(&settings.max_lines)
+    .bind(&settings.concurrency)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain patch candidates: {}", err);
+        }
+    }
+}
+
+/// Download the patch of Pull Request `candidate`, compressed. Returns
+/// `None` if the patch exceeds `max_bytes`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `candidate` - The Pull Request to download the patch of.
+/// * `format` - The format to download, `diff` or `patch`.
+/// * `max_bytes` - The largest patch to keep.
+///
+pub async fn fetch_patch(
+    creds: &AccountCredentials,
+    candidate: &PatchCandidate,
+    format: &str,
+    max_bytes: i64,
+) -> Result<Option<FetchedPatch>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("patches");
+    let ep = format!(
+        "/repos/{}/{}/pulls/{}",
+        candidate.repo_owner, candidate.repo_name, candidate.number
+    );
+    let accept = format!("application/vnd.github.{}", format);
+
+    let res = match ghreq.execute_as(ghreq.get(&ep), &accept).await {
+        Ok(res) if res.status().is_success() => res,
+        Ok(res) => return Err(rest::status_to_error(res.status())),
+        Err(_) => return Err(GHDError::UnknownError),
+    };
+    if let Some(len) = res.content_length() {
+        if len as i64 > max_bytes {
+            return Ok(None);
+        }
+    }
+    let bytes = match res.bytes().await {
+        Ok(res) => res,
+        Err(_) => return Err(GHDError::UnknownError),
+    };
+    if bytes.len() as i64 > max_bytes {
+        return Ok(None);
+    }
+
+    let mut encoder = flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    );
+    let compressed = match encoder.write_all(&bytes) {
+        Ok(()) => encoder.finish(),
+        Err(err) => Err(err),
+    };
+    match compressed {
+        Ok(res) => Ok(Some(FetchedPatch {
+            compressed: res,
+            size: bytes.len() as i64,
+        })),
+        Err(err) => {
+            println!("unable to compress patch: {}", err);
+            Err(GHDError::UnknownError)
+        }
+    }
+}
+
+fn patch_path(dir: &PathBuf, prid: &i64, format: &str) -> PathBuf {
+    dir.join(format!("{}.{}.gz", prid, format))
+}
+
+/// Store the patch of Pull Request `prid` under `dir`, or record it was
+/// too large to keep, so it isn't downloaded again until updated. Fails if
+/// the patch can't be written to disk.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `dir` - The directory patches are stored in.
+/// * `prid` - The Pull Request's ID.
+/// * `format` - The format the patch was downloaded in.
+/// * `patch` - The downloaded patch, if kept.
+/// * `when` - When the patch was downloaded.
+///
+pub async fn store(
+    db: &DB,
+    dir: &PathBuf,
+    prid: &i64,
+    format: &str,
+    patch: &Option<FetchedPatch>,
+    when: &chrono::DateTime<chrono::Utc>,
+) -> Result<(), GHDError> {
+    remove_files(&dir, &prid).await;
+
+    let (stored, size) = match patch {
+        Some(p) => {
+            let path = patch_path(&dir, &prid, &format);
+            if let Err(err) = tokio::fs::create_dir_all(&dir).await {
+                println!("unable to create '{}': {}", dir.display(), err);
+                return Err(GHDError::UnknownError);
+            }
+            if let Err(err) = tokio::fs::write(&path, &p.compressed).await {
+                println!("unable to write '{}': {}", path.display(), err);
+                return Err(GHDError::UnknownError);
+            }
+            (p.compressed.len() as i64, p.size)
+        }
+        None => (0, 0),
+    };
+
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_patches (
+            pr_id, format, size, stored_size, too_large, fetched_at
+        ) VALUES (?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&prid)
+    .bind(&format)
+    .bind(&size)
+    .bind(&stored)
+    .bind(patch.is_none())
+    .bind(when.timestamp())
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to record patch for pr {}: {}", prid, err);
+    });
+
+    Ok(())
+}
+
+async fn remove_files(dir: &PathBuf, prid: &i64) {
+    for format in [FORMAT_DIFF, FORMAT_PATCH] {
+        let path = patch_path(&dir, &prid, format);
+        if path.exists() {
+            if let Err(err) = tokio::fs::remove_file(&path).await {
+                println!("unable to remove '{}': {}", path.display(), err);
+            }
+        }
+    }
+}
+
+/// Evict the oldest patches until the compressed ones kept take at most
+/// `cache_bytes`. Returns how many were evicted. Evicted patches are kept
+/// track of, so they aren't downloaded again until their Pull Request is
+/// updated.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `dir` - The directory patches are stored in.
+/// * `cache_bytes` - How much disk patches may take.
+///
+pub async fn evict(db: &DB, dir: &PathBuf, cache_bytes: i64) -> usize {
+    let now = chrono::Utc::now();
+    let kept = match sqlx::query_as::<_, (i64, i64)>(
+        "
+        SELECT pr_id, stored_size FROM pr_patches
+        WHERE too_large = 0 AND evicted_at IS NULL
+        ORDER BY fetched_at DESC
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain stored patches: {}", err);
+        }
+    };
+
+    let mut total = 0;
+    let mut evicted = 0;
+    for (prid, stored) in &kept {
+        total += stored;
+        if total <= cache_bytes {
+            continue;
+        }
+        remove_files(&dir, &prid).await;
+        sqlx::query(
+            "
+            UPDATE pr_patches SET stored_size = 0, evicted_at = ?
+            WHERE pr_id = ?
+            ",
+        )
+        .bind(&now.timestamp())
+        .bind(&prid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to evict patch for pr {}: {}", prid, err);
+        });
+        evicted += 1;
+    }
+    evicted
+}
+
+/// Obtain when the cached patch of Pull Request `prid` was downloaded.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's ID.
+///
+pub async fn get_fetched_at(db: &DB, prid: &i64) -> Result<i64, GHDError> {
+    match sqlx::query_scalar::<_, i64>(
+        "
+        SELECT fetched_at FROM pr_patches
+        WHERE pr_id = ? AND too_large = 0 AND evicted_at IS NULL
+        ",
+    )
+    .bind(&prid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => Ok(res),
+        Ok(None) => Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain patch for pr {}: {}", prid, err);
+        }
+    }
+}
+
+/// Obtain the cached patch of Pull Request `prid`, decompressed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `dir` - The directory patches are stored in.
+/// * `prid` - The Pull Request's ID.
+///
+pub async fn get_patch(
+    db: &DB,
+    dir: &PathBuf,
+    prid: &i64,
+) -> Result<PullRequestPatch, GHDError> {
+    let (format, fetched_at) = match sqlx::query_as::<_, (String, i64)>(
+        "
+        SELECT format, fetched_at FROM pr_patches
+        WHERE pr_id = ? AND too_large = 0 AND evicted_at IS NULL
+        ",
+    )
+    .bind(&prid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain patch for pr {}: {}", prid, err);
+        }
+    };
+
+    let path = patch_path(&dir, &prid, &format);
+    let compressed = match tokio::fs::read(&path).await {
+        Ok(res) => res,
+        Err(err) => {
+            println!("unable to read '{}': {}", path.display(), err);
+            return Err(GHDError::NotFoundError);
+        }
+    };
+    let mut text = String::new();
+    if let Err(err) =
+        flate2::read::GzDecoder::new(&compressed[..]).read_to_string(&mut text)
+    {
+        println!("unable to decompress '{}': {}", path.display(), err);
+        return Err(GHDError::UnknownError);
+    }
+
+    Ok(PullRequestPatch {
+        pr_id: *prid,
+        format,
+        text,
+        fetched_at,
+    })
+}
@@ -0,0 +1,120 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::db::DB;
+
+use super::{refresh, types::PendingReview};
+
+/// Refresh pending reviews every half hour; they're there to be reminded
+/// of, not to be tracked closely.
+///
+pub const PENDING_REFRESH_INTERVAL: i64 = 30 * 60;
+
+/// Obtain the sync refresh key for the pending reviews of user `userid`.
+///
+pub fn refresh_key(userid: &i64) -> String {
+    format!("pending_reviews:{}", userid)
+}
+
+/// Check whether the pending reviews of user `userid` are due for a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn should_refresh(db: &DB, userid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&userid),
+        PENDING_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Write the pending reviews obtained for user `userid` to the database,
+/// replacing those previously known, which have since been submitted or
+/// dropped. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The database ID of the account's user.
+/// * `reviews` - The user's pending reviews.
+/// * `when` - When the pending reviews were obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+    reviews: &Vec<PendingReview>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query("DELETE FROM pending_reviews WHERE user_id = ?")
+        .bind(&userid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error clearing pending reviews for {}: {}", userid, err);
+        });
+
+    for r in reviews {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pending_reviews (
+                review_id, user_id, pr_id, repo_owner, repo_name, number,
+                title, url, comments, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&r.review_id)
+        .bind(&r.user_id)
+        .bind(&r.pr_id)
+        .bind(&r.repo_owner)
+        .bind(&r.repo_name)
+        .bind(&r.number)
+        .bind(&r.title)
+        .bind(&r.url)
+        .bind(&r.comments)
+        .bind(&r.created_at)
+        .bind(&r.updated_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting pending review {}: {}", r.review_id, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&userid), &when).await;
+}
+
+/// Obtain the reviews our users started on GitHub but never submitted, least
+/// recently touched first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_pending(db: &DB) -> Vec<PendingReview> {
+    match sqlx::query_as::<_, PendingReview>(
+        "SELECT * FROM pending_reviews ORDER BY updated_at, review_id",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain pending reviews: {}", err);
+        }
+    }
+}
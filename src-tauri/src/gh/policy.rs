@@ -0,0 +1,378 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials, outbox::PullRequestAction, refresh, rest,
+    types::MergePolicy,
+};
+
+/// Merge settings change rarely; refresh them every six hours.
+///
+pub const POLICY_REFRESH_INTERVAL: i64 = 6 * 60 * 60;
+
+/// Methods a Pull Request may be merged with.
+///
+pub const MERGE_METHODS: [&str; 3] = ["merge", "squash", "rebase"];
+
+/// Obtain the sync refresh key for the merge policy of repository
+/// `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("merge_policy:{}/{}", owner, name)
+}
+
+/// Check whether the merge policy of repository `owner/name` is due for a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        POLICY_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Repository as described by the REST API. The merge settings are only
+/// provided to those who can push to it; otherwise, every method is assumed
+/// allowed.
+///
+#[derive(serde::Deserialize)]
+struct RepoInfo {
+    default_branch: String,
+    allow_merge_commit: Option<bool>,
+    allow_squash_merge: Option<bool>,
+    allow_rebase_merge: Option<bool>,
+}
+
+/// A rule applying to a branch, through a repository or organization
+/// ruleset.
+///
+#[derive(serde::Deserialize)]
+struct BranchRule {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// A branch's classic protection, of which only linear history is of
+/// interest.
+///
+#[derive(serde::Deserialize)]
+struct BranchProtection {
+    required_linear_history: Option<Enabled>,
+}
+
+#[derive(serde::Deserialize)]
+struct Enabled {
+    enabled: bool,
+}
+
+/// Check whether the default branch of repository `owner/name` requires a
+/// linear history, either through a ruleset or its classic protection.
+/// Classic protection is only visible to administrators, and rulesets may not
+/// be supported by the host; what can't be obtained is assumed not to
+/// require it.
+///
+async fn requires_linear_history(
+    ghreq: &rest::GithubRequest,
+    owner: &String,
+    name: &String,
+    branch: &String,
+) -> bool {
+    let branch = rest::encode_segment(&branch);
+    let ep = format!("/repos/{}/{}/rules/branches/{}", owner, name, branch);
+    if let Ok(rules) = ghreq.send::<Vec<BranchRule>>(ghreq.get(&ep)).await {
+        if rules.iter().any(|r| r.kind == "required_linear_history") {
+            return true;
+        }
+    }
+
+    let ep =
+        format!("/repos/{}/{}/branches/{}/protection", owner, name, branch);
+    match ghreq.send::<BranchProtection>(ghreq.get(&ep)).await {
+        Ok(BranchProtection {
+            required_linear_history: Some(Enabled { enabled }),
+        }) => enabled,
+        _ => false,
+    }
+}
+
+/// Obtain the merge policy of repository `owner/name`.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<MergePolicy, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("policy");
+    let ep = format!("/repos/{}/{}", owner, name);
+    let info = match ghreq.send::<RepoInfo>(ghreq.get(&ep)).await {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(MergePolicy {
+        repo_owner: owner.clone(),
+        repo_name: name.clone(),
+        default_branch: info.default_branch.clone(),
+        allow_merge_commit: info.allow_merge_commit.unwrap_or(true),
+        allow_squash_merge: info.allow_squash_merge.unwrap_or(true),
+        allow_rebase_merge: info.allow_rebase_merge.unwrap_or(true),
+        requires_linear_history: requires_linear_history(
+            &ghreq,
+            &owner,
+            &name,
+            &info.default_branch,
+        )
+        .await,
+        fetched_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Write the merge policy of a repository to the database. This function
+/// requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `policy` - The repository's merge policy.
+/// * `when` - When the merge policy was obtained.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    policy: &MergePolicy,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO merge_policies (
+            repo_owner, repo_name, default_branch,
+            allow_merge_commit, allow_squash_merge, allow_rebase_merge,
+            requires_linear_history, fetched_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&policy.repo_owner)
+    .bind(&policy.repo_name)
+    .bind(&policy.default_branch)
+    .bind(&policy.allow_merge_commit)
+    .bind(&policy.allow_squash_merge)
+    .bind(&policy.allow_rebase_merge)
+    .bind(&policy.requires_linear_history)
+    .bind(&when.timestamp())
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!(
+            "Error storing merge policy of '{}/{}': {}",
+            policy.repo_owner, policy.repo_name, err
+        );
+    });
+
+    let key = refresh_key(&policy.repo_owner, &policy.repo_name);
+    refresh::update_sync_refresh(tx, &key, &when).await;
+}
+
+/// Obtain the merge policy of repository `owner/name`, if known.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_policy(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Option<MergePolicy> {
+    match sqlx::query_as::<_, MergePolicy>(
+        "
+        SELECT * FROM merge_policies
+        WHERE repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Unable to obtain merge policy of '{}/{}': {}",
+                owner, name, err
+            );
+        }
+    }
+}
+
+/// Obtain the methods `policy` allows merging with, out of `MERGE_METHODS`.
+/// A linear history rules out merge commits, whatever the repository allows.
+///
+pub fn allowed_methods(policy: &MergePolicy) -> Vec<String> {
+    MERGE_METHODS
+        .iter()
+        .filter(|m| match **m {
+            "merge" => {
+                policy.allow_merge_commit && !policy.requires_linear_history
+            }
+            "squash" => policy.allow_squash_merge,
+            "rebase" => policy.allow_rebase_merge,
+            _ => false,
+        })
+        .map(|m| m.to_string())
+        .collect()
+}
+
+/// Obtain the methods Pull Requests on repository `owner/name` may be merged
+/// with, as its merge policy last obtained allows. Should the policy be
+/// unknown, every method is offered.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_methods(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Vec<String> {
+    match get_policy(&db, &owner, &name).await {
+        Some(policy) => allowed_methods(&policy),
+        None => MERGE_METHODS.iter().map(|m| m.to_string()).collect(),
+    }
+}
+
+/// A Pull Request as described by the REST API, of which only its base
+/// branch is of interest.
+///
+#[derive(serde::Deserialize)]
+struct PullInfo {
+    base: PullBase,
+}
+
+#[derive(serde::Deserialize)]
+struct PullBase {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+/// Obtain the methods Pull Request `owner/name#number` may be merged with.
+/// Beyond what its repository allows, the rules of the branch it targets are
+/// looked up, should it not target the default branch, for those may require
+/// a linear history where the default branch does not. Should the policy be
+/// unknown, every method is offered.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `number` - The Pull Request's number.
+///
+pub async fn get_pr_methods(
+    db: &DB,
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+    number: &i64,
+) -> Vec<String> {
+    let policy = match get_policy(&db, &owner, &name).await {
+        Some(res) => res,
+        None => return get_methods(&db, &owner, &name).await,
+    };
+
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("policy");
+    let ep = format!("/repos/{}/{}/pulls/{}", owner, name, number);
+    let base = match ghreq.send::<PullInfo>(ghreq.get(&ep)).await {
+        Ok(res) => res.base.name,
+        Err(err) => {
+            log::error!(
+                "Unable to obtain base branch of '{}/{}#{}': {}",
+                owner,
+                name,
+                number,
+                err
+            );
+            policy.default_branch.clone()
+        }
+    };
+    if base == policy.default_branch {
+        return allowed_methods(&policy);
+    }
+
+    let linear = requires_linear_history(&ghreq, &owner, &name, &base).await;
+    allowed_methods(&MergePolicy {
+        requires_linear_history: linear,
+        ..policy
+    })
+}
+
+/// Check whether `action` complies with the merge policy of Pull Request
+/// `owner/name#number`, as its repository's policy last obtained and its
+/// base branch's rules allow. Only merges are subject to it; those with an
+/// unknown method are rejected regardless. Repositories whose policy isn't
+/// known leave it to the API to reject the action.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `number` - The Pull Request's number.
+/// * `action` - The action about to be run.
+///
+pub async fn check(
+    db: &DB,
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+    number: &i64,
+    action: &PullRequestAction,
+) -> Result<(), GHDError> {
+    let method = match action {
+        PullRequestAction::Merge { method } => method,
+        _ => return Ok(()),
+    };
+    if !MERGE_METHODS.contains(&method.as_str()) {
+        return Err(GHDError::BadRequest);
+    }
+
+    let methods = get_pr_methods(&db, &creds, &owner, &name, &number).await;
+    match methods.contains(&method) {
+        true => Ok(()),
+        false => Err(GHDError::BadRequest),
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{accounts::AccountCredentials, refresh, rest, types::ProfileStats};
+
+/// Profile stats are only shown in passing; refresh them once a day.
+///
+pub const PROFILE_REFRESH_INTERVAL: i64 = 24 * 60 * 60;
+
+/// Obtain the sync refresh key for the profile stats of user `userid`.
+///
+pub fn refresh_key(userid: &i64) -> String {
+    format!("profile:{}", userid)
+}
+
+/// Check whether the profile stats for user `userid` are due for a refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn should_refresh(db: &DB, userid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&userid),
+        PROFILE_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Obtain the profile stats of the token's owner. Stars received are summed
+/// across the repositories the user owns.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials of the account to obtain stats for.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    userid: &i64,
+) -> Result<ProfileStats, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("profile");
+
+    let profile = match ghreq
+        .send::<rest::GithubProfileReply>(ghreq.get("/user"))
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    let repos = match ghreq
+        .get_all_pages::<rest::GithubRepoStarsReply>(
+            "/user/repos?affiliation=owner",
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(ProfileStats {
+        user_id: *userid,
+        followers: profile.followers,
+        following: profile.following,
+        public_repos: profile.public_repos,
+        stars: repos.iter().map(|r| r.stargazers_count).sum(),
+        updated_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Write the provided profile stats to the database. This function requires
+/// a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `stats` - The profile stats obtained for the user.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    stats: &ProfileStats,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO profile_stats (
+            user_id, followers, following, public_repos, stars, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&stats.user_id)
+    .bind(&stats.followers)
+    .bind(&stats.following)
+    .bind(&stats.public_repos)
+    .bind(&stats.stars)
+    .bind(&stats.updated_at)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!(
+            "Error updating profile stats for user {}: {}",
+            stats.user_id, err
+        );
+    });
+
+    let when = chrono::Utc::now();
+    refresh::update_sync_refresh(tx, &refresh_key(&stats.user_id), &when).await;
+}
+
+/// Obtain the cached profile stats for user `userid`, if ever fetched.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The database ID of the account's user.
+///
+pub async fn get_stats(
+    db: &DB,
+    userid: &i64,
+) -> Result<Option<ProfileStats>, GHDError> {
+    match sqlx::query_as::<_, ProfileStats>(
+        "SELECT * FROM profile_stats WHERE user_id = ?",
+    )
+    .bind(&userid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain profile stats for {}: {}", userid, err);
+        }
+    }
+}
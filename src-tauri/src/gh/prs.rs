@@ -12,9 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{common, db::DB, errors::GHDError};
+use std::collections::HashMap;
 
-use super::types::{Issue, PullRequest, PullRequestTableEntry};
+use crate::{
+    common,
+    db::DB,
+    errors::GHDError,
+    notify,
+    page::{Page, PageRequest},
+};
+
+use super::{
+    annotations, bots, conflicts, contributors,
+    filters::{self, IssueFilter},
+    fixes, identities, labelalerts, reconcile, references, sizes, sla, turns,
+    types::{
+        Issue, IssueChange, IssuesDiff, PullRequest, PullRequestTableEntry,
+    },
+};
 
 /// Obtain all Pull Requests from the database.
 ///
@@ -30,6 +45,8 @@ pub async fn get_all_prs_from_db(
             pull_requests LEFT JOIN issues
         ON
             pull_requests.id = issues.id
+        WHERE
+            issues.state != 'deleted_upstream'
         ",
     )
     .fetch_all(db.pool())
@@ -42,86 +59,411 @@ pub async fn get_all_prs_from_db(
     }
 }
 
-/// Obtain all Pull Requests from the provided author `login`.
+/// Obtain a page of the Pull Requests from the provided author `login`, or
+/// from any user linked to the same identity, most recently updated first.
 ///
 pub async fn get_prs_by_author(
     db: &DB,
     login: &String,
-) -> Result<Vec<PullRequestTableEntry>, GHDError> {
-    match sqlx::query_as::<_, PullRequestTableEntry>(
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    let query = format!(
         "
         SELECT
             issues.*, pull_requests.is_draft, pull_requests.review_decision,
-            pull_requests.merged_at
+            pull_requests.merged_at, {}, {}, {}, {}, {}
         FROM
             pull_requests LEFT JOIN issues
         ON
             pull_requests.id = issues.id
         WHERE
-            issues.author = ?
-        ORDER BY issues.updated_at DESC
+            issues.author IN ({}) AND {} AND {}
+        ORDER BY issues.updated_at DESC, issues.id DESC
+        LIMIT ?
         ",
-    )
-    .bind(&login)
-    .fetch_all(db.pool())
-    .await
+        conflicts::POTENTIAL_CONFLICTS_COLUMN,
+        contributors::FIRST_TIME_CONTRIBUTOR_COLUMN,
+        turns::MY_TURN_COLUMN,
+        annotations::ANNOTATION_COLUMNS,
+        sizes::SIZE_COLUMNS,
+        identities::LINKED_LOGINS,
+        reconcile::NOT_DELETED,
+        PageRequest::condition("issues.updated_at", "issues.id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&login)
+        .bind(&login)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
     {
-        Ok(res) => Ok(res),
+        Ok(res) => Ok(page.into_page(res, |pr| (pr.updated_at, pr.id))),
         Err(err) => {
             panic!("Unable to obtain pull requests from db: {}", err);
         }
     }
 }
 
-/// Obtain all Pull Requests the provided user `login` is involved with. This
-/// means mentions, review requests, authored, or where the user may have
-/// commented.
+/// Obtain a page of the Pull Requests the provided user `login`, or any user
+/// linked to the same identity, is involved with, most recently updated
+/// first. This means mentions, review requests, or where the user may have
+/// commented; Pull Requests authored by the identity are excluded, as are
+/// bot-authored ones if bots are hidden. Should `relation` be provided, only
+/// those the user is so related to are obtained; e.g., `review_requested`.
 ///
 pub async fn get_involved_prs(
     db: &DB,
     login: &String,
-) -> Result<Vec<PullRequestTableEntry>, GHDError> {
-    match sqlx::query_as::<_, PullRequestTableEntry>(
+    relation: &Option<String>,
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    if let Some(r) = relation {
+        if !RELATIONS.contains(&r.as_str()) {
+            return Err(GHDError::BadRequest);
+        }
+    }
+
+    let query = format!(
         "
         SELECT
             issues.*, pull_requests.is_draft, pull_requests.merged_at,
-            pull_requests.review_decision
+            pull_requests.review_decision, {}, {}, {}, {}, {}
         FROM pull_requests INNER JOIN (
-            SELECT
+            SELECT DISTINCT
                 issues.*
             FROM
                 issues LEFT JOIN user_issues
             ON
                 issues.id = user_issues.issue_id
             WHERE
-                user_issues.user_id = (
-                    SELECT id FROM users WHERE login = ?
+                user_issues.user_id IN (
+                    SELECT id FROM users WHERE login IN ({})
+                )
+                AND (
+                    ? IS NULL OR EXISTS (
+                        SELECT 1 FROM user_issue_relations
+                        WHERE
+                            user_issue_relations.user_id = user_issues.user_id
+                            AND user_issue_relations.issue_id = issues.id
+                            AND user_issue_relations.relation = ?
+                    )
                 )
         ) AS
             issues
         ON
-            pull_requests.id = issues.id AND issues.author != ?
-        ORDER BY issues.updated_at DESC
+            pull_requests.id = issues.id AND issues.author NOT IN ({})
+        WHERE {} AND {} AND {}
+        ORDER BY issues.updated_at DESC, issues.id DESC
+        LIMIT ?
+        ",
+        conflicts::POTENTIAL_CONFLICTS_COLUMN,
+        contributors::FIRST_TIME_CONTRIBUTOR_COLUMN,
+        turns::MY_TURN_COLUMN,
+        annotations::ANNOTATION_COLUMNS,
+        sizes::SIZE_COLUMNS,
+        identities::LINKED_LOGINS,
+        identities::LINKED_LOGINS,
+        bots::filter(&db).await,
+        reconcile::NOT_DELETED,
+        PageRequest::condition("issues.updated_at", "issues.id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&login)
+        .bind(&login)
+        .bind(&relation)
+        .bind(&relation)
+        .bind(&login)
+        .bind(&login)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(page.into_page(res, |pr| (pr.updated_at, pr.id))),
+        Err(err) => {
+            panic!("Unable to obtain data from database: {}", err);
+        }
+    }
+}
+
+/// Obtain a page of the Pull Requests matching `filter`, most recently
+/// updated first. Bot-authored Pull Requests are left out if bots are hidden.
+///
+pub async fn get_filtered_prs(
+    db: &DB,
+    filter: &IssueFilter,
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at, {}, {}, {}, {}, {}
+        FROM
+            pull_requests INNER JOIN issues
+        ON
+            pull_requests.id = issues.id
+        WHERE
+            {} AND {} AND {}
+        ORDER BY issues.updated_at DESC, issues.id DESC
+        LIMIT ?17
         ",
+        conflicts::POTENTIAL_CONFLICTS_COLUMN,
+        contributors::FIRST_TIME_CONTRIBUTOR_COLUMN,
+        turns::MY_TURN_COLUMN,
+        annotations::ANNOTATION_COLUMNS,
+        sizes::SIZE_COLUMNS,
+        filters::ISSUE_FILTER,
+        bots::filter(&db).await,
+        PageRequest::numbered_condition("issues.updated_at", "issues.id", 15)
+    );
+
+    let (key, id) = page.bind_values();
+    match filters::bind_query_as(
+        sqlx::query_as::<_, PullRequestTableEntry>(&query),
+        &filter,
+    )
+    .bind(key)
+    .bind(id)
+    .bind(page.fetch_limit())
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(page.into_page(res, |pr| (pr.updated_at, pr.id))),
+        Err(err) => {
+            panic!("Unable to obtain filtered pull requests: {}", err);
+        }
+    }
+}
+
+/// Obtain a page of the Pull Requests matching `filter`, largest first, by
+/// lines changed. Pull Requests whose files aren't known are left out, as
+/// are bot-authored ones if bots are hidden.
+///
+pub async fn get_filtered_prs_by_size(
+    db: &DB,
+    filter: &IssueFilter,
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at, {}, {}, {}, {}, {}
+        FROM
+            pull_requests
+            INNER JOIN issues ON pull_requests.id = issues.id
+            INNER JOIN pr_sizes ON pr_sizes.pr_id = issues.id
+        WHERE
+            {} AND {} AND {}
+        ORDER BY pr_sizes.changed_lines DESC, issues.id DESC
+        LIMIT ?17
+        ",
+        conflicts::POTENTIAL_CONFLICTS_COLUMN,
+        contributors::FIRST_TIME_CONTRIBUTOR_COLUMN,
+        turns::MY_TURN_COLUMN,
+        annotations::ANNOTATION_COLUMNS,
+        sizes::SIZE_COLUMNS,
+        filters::ISSUE_FILTER,
+        bots::filter(&db).await,
+        PageRequest::numbered_condition(
+            "pr_sizes.changed_lines",
+            "issues.id",
+            15
+        )
+    );
+
+    let (key, id) = page.bind_values();
+    match filters::bind_query_as(
+        sqlx::query_as::<_, PullRequestTableEntry>(&query),
+        &filter,
     )
-    .bind(&login)
-    .bind(&login)
+    .bind(key)
+    .bind(id)
+    .bind(page.fetch_limit())
     .fetch_all(db.pool())
     .await
+    {
+        Ok(res) => Ok(page.into_page(res, |pr| {
+            (pr.changed_lines.unwrap_or_default(), pr.id)
+        })),
+        Err(err) => {
+            panic!("Unable to obtain pull requests by size: {}", err);
+        }
+    }
+}
+
+/// Obtain Pull Request `prid` from the database, along with the columns
+/// otherwise only computed by listings.
+///
+pub async fn get_pr(
+    db: &DB,
+    prid: &i64,
+) -> Result<PullRequestTableEntry, GHDError> {
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at, {}, {}, {}, {}, {}
+        FROM
+            pull_requests INNER JOIN issues
+        ON
+            pull_requests.id = issues.id
+        WHERE
+            issues.id = ?
+        ",
+        conflicts::POTENTIAL_CONFLICTS_COLUMN,
+        contributors::FIRST_TIME_CONTRIBUTOR_COLUMN,
+        turns::MY_TURN_COLUMN,
+        annotations::ANNOTATION_COLUMNS,
+        sizes::SIZE_COLUMNS
+    );
+
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&prid)
+        .fetch_one(db.pool())
+        .await
     {
         Ok(res) => Ok(res),
+        Err(sqlx::Error::RowNotFound) => Err(GHDError::NotFoundError),
         Err(err) => {
-            panic!("Unable to obtain data from database: {}", err);
+            panic!("Unable to obtain pull request {}: {}", prid, err);
+        }
+    }
+}
+
+/// Compare the given issue against what's in the database, returning the
+/// names of the fields that changed, or `None` if the issue is new. This
+/// function requires a transaction.
+///
+async fn diff_issue(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issue: &Issue,
+) -> Option<Vec<String>> {
+    let stored =
+        sqlx::query_as::<_, (String, String, String, i64, Option<i64>)>(
+            "
+        SELECT title, author, state, updated_at, closed_at
+        FROM issues WHERE id = ?
+        ",
+        )
+        .bind(&issue.id)
+        .fetch_optional(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to obtain issue {}: {}", issue.id, err);
+        })?;
+
+    let labels = sqlx::query_scalar::<_, String>(
+        "SELECT name FROM issue_labels WHERE issue_id = ? ORDER BY name",
+    )
+    .bind(&issue.id)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain labels for issue {}: {}", issue.id, err);
+    });
+    let mut new_labels = issue.labels.clone();
+    new_labels.sort();
+
+    let fix_in_progress = fixes::has_open_fix(tx, &issue.id).await;
+
+    let (title, author, state, updated_at, closed_at) = stored;
+    let mut fields: Vec<String> = vec![];
+    for (name, changed) in [
+        ("title", title != issue.title),
+        ("author", author != issue.author),
+        ("state", state != issue.state),
+        ("updated_at", updated_at != issue.updated_at.timestamp()),
+        (
+            "closed_at",
+            closed_at != common::dt_opt_to_ts(&issue.closed_at),
+        ),
+        ("labels", labels != new_labels),
+        (
+            "fix_in_progress",
+            fix_in_progress != issue.fixes.iter().any(|f| f.state == "open"),
+        ),
+    ] {
+        if changed {
+            fields.push(name.to_string());
+        }
+    }
+
+    Some(fields)
+}
+
+/// Compare the Pull Request specific fields of the given Pull Request against
+/// what's in the database, returning the names of the fields that changed.
+/// This function requires a transaction.
+///
+async fn diff_pull_request(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    pr: &PullRequest,
+) -> Vec<String> {
+    let stored = sqlx::query_as::<_, (bool, String, Option<i64>)>(
+        "
+        SELECT is_draft, review_decision, merged_at
+        FROM pull_requests WHERE id = ?
+        ",
+    )
+    .bind(&pr.issue.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain pull request {}: {}", pr.issue.id, err);
+    });
+
+    let (is_draft, review_decision, merged_at) = match stored {
+        Some(v) => v,
+        None => return vec![],
+    };
+    let mut fields: Vec<String> = vec![];
+    for (name, changed) in [
+        ("is_draft", is_draft != pr.is_draft),
+        ("review_decision", review_decision != pr.review_decision),
+        (
+            "merged_at",
+            merged_at != common::dt_opt_to_ts(&pr.merged_at),
+        ),
+    ] {
+        if changed {
+            fields.push(name.to_string());
         }
     }
+
+    fields
 }
 
-/// Insert the given issue into the database.
+/// Record the outcome of comparing issue `id` against the database in
+/// `diff`.
+///
+fn add_to_diff(diff: &mut IssuesDiff, id: i64, fields: Option<Vec<String>>) {
+    match fields {
+        None => diff.created.push(id),
+        Some(f) if !f.is_empty() => {
+            diff.updated.push(IssueChange { id, fields: f })
+        }
+        Some(_) => {}
+    }
+}
+
+/// Insert the given issue into the database, notifying of label transitions
+/// subscribed to on it and of Pull Requests newly found to fix it.
 ///
 async fn consume_issue(
     tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
     issue: &Issue,
 ) -> Result<(), GHDError> {
+    let snapshot = labelalerts::snapshot(tx, &issue.id).await;
+
     match sqlx::query(
         "
         INSERT OR REPLACE INTO issues (
@@ -129,13 +471,13 @@ async fn consume_issue(
             url, repo_owner, repo_name, state,
             created_at, updated_at, closed_at,
             is_pull_request,
-            last_viewed
+            last_viewed, unknown_raw, first_response_at
         ) VALUES (
             ?, ?, ?, ?, ?,
             ?, ?, ?, ?,
             ?, ?, ?,
             ?,
-            ?
+            ?, ?, ?
         )
         ",
     )
@@ -153,6 +495,8 @@ async fn consume_issue(
     .bind(common::dt_opt_to_ts(&issue.closed_at))
     .bind(&issue.is_pull_request)
     .bind(common::dt_opt_to_ts(&issue.last_viewed))
+    .bind(&issue.unknown_raw)
+    .bind(&issue.first_response_at)
     .execute(&mut *tx)
     .await
     {
@@ -161,11 +505,308 @@ async fn consume_issue(
             panic!("Unable to consume issue: {}", err);
         }
     };
+
+    sqlx::query("INSERT OR REPLACE INTO authors (login, is_bot) VALUES (?, ?)")
+        .bind(&issue.author)
+        .bind(&issue.author_is_bot)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to consume author '{}': {}", issue.author, err);
+        });
+
+    sqlx::query("DELETE FROM issue_labels WHERE issue_id = ?")
+        .bind(&issue.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to clear labels for issue {}: {}", issue.id, err);
+        });
+    for label in &issue.labels {
+        sqlx::query(
+            "INSERT OR REPLACE INTO issue_labels (issue_id, name) VALUES (?, ?)",
+        )
+        .bind(&issue.id)
+        .bind(&label)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to consume label for issue {}: {}", issue.id, err);
+        });
+    }
+
+    if let Some(snapshot) = &snapshot {
+        labelalerts::evaluate(tx, &issue, &snapshot).await;
+    }
+    fixes::consume(tx, &issue).await;
+    references::consume(tx, &issue).await;
+    sla::consume(tx, &issue).await;
+
     Ok(())
 }
 
+/// Check whether user `userid` is one of our accounts. This function
+/// requires a transaction.
+///
+async fn is_ours(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+) -> bool {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM tokens WHERE user_id = ?",
+    )
+    .bind(&userid)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to check tokens for user {}: {}", userid, err);
+    }) > 0
+}
+
+/// Write the pending review requests of the provided Pull Request to the
+/// database. We can't tell when a review was requested, so requests keep the
+/// time we first saw them. New requests for one of our accounts get a
+/// notification. This function requires a transaction.
+///
+async fn consume_review_requests(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    pr: &PullRequest,
+) {
+    let known: HashMap<i64, i64> = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT user_id, requested_at FROM pr_review_requests WHERE pr_id = ?",
+    )
+    .bind(&pr.issue.id)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain review requests: {}", err);
+    })
+    .into_iter()
+    .collect();
+
+    sqlx::query("DELETE FROM pr_review_requests WHERE pr_id = ?")
+        .bind(&pr.issue.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to clear review requests: {}", err);
+        });
+
+    let now = chrono::Utc::now().timestamp();
+    for userid in &pr.review_requests {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_review_requests (
+                pr_id, user_id, requested_at
+            ) VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&pr.issue.id)
+        .bind(&userid)
+        .bind(known.get(&userid).unwrap_or(&now))
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to consume review request: {}", err);
+        });
+
+        if !known.contains_key(&userid) && is_ours(tx, &userid).await {
+            let title = format!(
+                "Review requested on {}/{}#{}",
+                pr.issue.repo_owner, pr.issue.repo_name, pr.issue.number
+            );
+            notify::push(
+                tx,
+                "review_requested",
+                &Some(pr.issue.id),
+                &title,
+                &pr.issue.title,
+                notify::classify("review_requested"),
+            )
+            .await;
+        }
+    }
+}
+
+/// Write the CI state of the provided Pull Request's head commit to the
+/// database, keeping track of when it last changed. CI starting to fail on one
+/// of our Pull Requests gets a notification. This function requires a
+/// transaction.
+///
+async fn consume_ci_state(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    pr: &PullRequest,
+) {
+    let state = match &pr.ci_state {
+        Some(s) => s,
+        None => {
+            sqlx::query("DELETE FROM pr_ci_states WHERE pr_id = ?")
+                .bind(&pr.issue.id)
+                .execute(&mut *tx)
+                .await
+                .unwrap_or_else(|err| {
+                    panic!("Unable to clear ci state: {}", err);
+                });
+            return;
+        }
+    };
+
+    let previous = sqlx::query_scalar::<_, String>(
+        "SELECT state FROM pr_ci_states WHERE pr_id = ?",
+    )
+    .bind(&pr.issue.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain ci state: {}", err);
+    });
+
+    sqlx::query(
+        "
+        INSERT INTO pr_ci_states (pr_id, state, changed_at) VALUES (?, ?, ?)
+        ON CONFLICT (pr_id) DO UPDATE SET
+            state = excluded.state, changed_at = excluded.changed_at
+        WHERE state != excluded.state
+        ",
+    )
+    .bind(&pr.issue.id)
+    .bind(&state)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to consume ci state: {}", err);
+    });
+
+    let failed = |s: &String| s == "failure" || s == "error";
+    if failed(state)
+        && !previous.as_ref().map_or(false, failed)
+        && is_ours(tx, &pr.issue.author_id).await
+    {
+        let title = format!(
+            "CI failed on {}/{}#{}",
+            pr.issue.repo_owner, pr.issue.repo_name, pr.issue.number
+        );
+        notify::push(
+            tx,
+            "ci_failed",
+            &Some(pr.issue.id),
+            &title,
+            &pr.issue.title,
+            notify::classify("ci_failed"),
+        )
+        .await;
+    }
+}
+
+/// Write the provided Pull Request to the database, along with its review
+/// requests, latest reviews, CI state and whose turn it is. This function
+/// requires a transaction.
+///
+async fn consume_pull_request(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ours: &Vec<i64>,
+    pr: &PullRequest,
+) {
+    match consume_issue(tx, &pr.issue).await {
+        Ok(_) => {}
+        Err(err) => {
+            panic!("unexpected error: {:?}", err);
+        }
+    };
+
+    // consume pull request
+    match sqlx::query(
+        "
+        INSERT OR REPLACE INTO pull_requests (
+            id, is_draft, review_decision, merged_at
+        ) VALUES (
+            ?, ?, ?, ?
+        )
+        ",
+    )
+    .bind(&pr.issue.id)
+    .bind(&pr.is_draft)
+    .bind(&pr.review_decision)
+    .bind(common::dt_opt_to_ts(&pr.merged_at))
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(_) => {}
+        Err(err) => {
+            panic!("unable to consume pull request: {}", err);
+        }
+    };
+
+    match sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_author_associations (
+            pr_id, association
+        ) VALUES (?, ?)
+        ",
+    )
+    .bind(&pr.issue.id)
+    .bind(&pr.author_association)
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(_) => {}
+        Err(err) => {
+            panic!("unable to consume author association: {}", err);
+        }
+    };
+
+    match sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_head_refs (pr_id, head_ref)
+        VALUES (?, ?)
+        ",
+    )
+    .bind(&pr.issue.id)
+    .bind(&pr.head_ref)
+    .execute(&mut *tx)
+    .await
+    {
+        Ok(_) => {}
+        Err(err) => {
+            panic!("unable to consume head ref: {}", err);
+        }
+    };
+
+    consume_review_requests(tx, &pr).await;
+    contributors::consume_reviews(tx, &pr).await;
+    consume_ci_state(tx, &pr).await;
+    turns::consume(tx, &pr.issue.id, turns::is_my_turn(&ours, &pr)).await;
+}
+
+/// Write the provided Pull Requests to the database without associating them
+/// with any user, e.g. when obtained for a whole organization. Returns the
+/// IDs of the Pull Requests written.
+///
+/// # Arguments
+///
+/// * `tx` - A transaction handle.
+/// * `prs` - A Vector of Pull Requests.
+///
+pub async fn consume_pulls(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prs: &Vec<PullRequest>,
+) -> Vec<i64> {
+    let ours = turns::get_our_users(&mut *tx).await;
+    let mut ids: Vec<i64> = vec![];
+    for pr in prs {
+        if ids.contains(&pr.issue.id) {
+            continue;
+        }
+        consume_pull_request(tx, &ours, &pr).await;
+        ids.push(pr.issue.id);
+    }
+    ids
+}
+
 /// Consume all issues and Pull Requests provided as arguments, writing them to
-/// the database, associating them with the provided `userid`.
+/// the database, associating them with the provided `userid`. Returns what
+/// changed in the database, issue by issue.
 ///
 /// # Arguments
 ///
@@ -179,12 +820,37 @@ pub async fn consume_issues(
     userid: &i64,
     issues: &Vec<Issue>,
     prs: &Vec<PullRequest>,
-) -> Result<(), GHDError> {
+) -> Result<IssuesDiff, GHDError> {
     println!("consuming {} issues, {} prs", issues.len(), prs.len());
 
+    let ours = turns::get_our_users(&mut *tx).await;
+    let login = match sqlx::query_scalar::<_, String>(
+        "SELECT login FROM users WHERE id = ?",
+    )
+    .bind(&userid)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain login of user {}: {}", userid, err);
+        }
+    };
     let mut issue_ids: Vec<i64> = vec![];
+    let mut relations: Vec<(i64, Vec<&str>)> = vec![];
+    let mut diff = IssuesDiff::default();
 
     for entry in issues {
+        // the same item may show up more than once, e.g. across pages.
+        if issue_ids.contains(&entry.id) {
+            continue;
+        }
+        relations
+            .push((entry.id, get_relations(&userid, &login, &entry, &None)));
+
+        let fields = diff_issue(tx, &entry).await;
+        add_to_diff(&mut diff, entry.id, fields);
+
         match consume_issue(tx, &entry).await {
             Ok(_) => {}
             Err(err) => {
@@ -195,36 +861,59 @@ pub async fn consume_issues(
     }
 
     for entry in prs {
-        match consume_issue(tx, &entry.issue).await {
-            Ok(_) => {}
-            Err(err) => {
-                panic!("unexpected error: {:?}", err);
-            }
-        };
+        if issue_ids.contains(&entry.issue.id) {
+            continue;
+        }
+        relations.push((
+            entry.issue.id,
+            get_relations(&userid, &login, &entry.issue, &Some(entry)),
+        ));
 
-        // consume pull request
-        match sqlx::query(
+        let mut fields = diff_issue(tx, &entry.issue).await;
+        if let Some(f) = &mut fields {
+            f.append(&mut diff_pull_request(tx, &entry).await);
+        }
+        add_to_diff(&mut diff, entry.issue.id, fields);
+
+        consume_pull_request(tx, &ours, &entry).await;
+        issue_ids.push(entry.issue.id);
+    }
+
+    for (id, lst) in &relations {
+        sqlx::query(
             "
-            INSERT OR REPLACE INTO pull_requests (
-                id, is_draft, review_decision, merged_at
-            ) VALUES (
-                ?, ?, ?, ?
-            )
+            DELETE FROM user_issue_relations
+            WHERE user_id = ? AND issue_id = ?
             ",
         )
-        .bind(&entry.issue.id)
-        .bind(&entry.is_draft)
-        .bind(&entry.review_decision)
-        .bind(common::dt_opt_to_ts(&entry.merged_at))
+        .bind(&userid)
+        .bind(id)
         .execute(&mut *tx)
         .await
-        {
-            Ok(_) => {}
-            Err(err) => {
-                panic!("unable to consume pull request: {}", err);
-            }
-        };
-        issue_ids.push(entry.issue.id);
+        .unwrap_or_else(|err| {
+            panic!("unable to clear relations for issue {}: {}", id, err);
+        });
+
+        for relation in lst {
+            sqlx::query(
+                "
+                INSERT INTO user_issue_relations (
+                    user_id, issue_id, relation
+                ) VALUES (?, ?, ?)
+                ",
+            )
+            .bind(&userid)
+            .bind(id)
+            .bind(relation)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "unable to relate issue {} to user {}: {}",
+                    id, userid, err
+                );
+            });
+        }
     }
 
     for id in &issue_ids {
@@ -252,7 +941,53 @@ pub async fn consume_issues(
         };
     }
 
-    Ok(())
+    Ok(diff)
+}
+
+/// Ways a user may be related to an issue or Pull Request.
+///
+pub const RELATIONS: [&str; 4] =
+    ["involved", "author", "review_requested", "mentions"];
+
+/// Check whether `body` mentions `login`, i.e. holds `@login` not followed by
+/// anything that would make it a longer login.
+///
+fn is_mentioned(body: &str, login: &str) -> bool {
+    let body = body.to_lowercase();
+    let mention = format!("@{}", login.to_lowercase());
+    body.match_indices(&mention).any(|(idx, _)| {
+        match body[idx + mention.len()..].chars().next() {
+            Some(c) => !(c.is_ascii_alphanumeric() || c == '-'),
+            None => true,
+        }
+    })
+}
+
+/// Obtain every way user `userid`, known as `login`, is related to `issue`,
+/// which always includes being `involved`, having been returned by the
+/// search, plus being its `author`, a requested reviewer of the Pull Request
+/// `pr`, if any, and being mentioned in its body.
+///
+fn get_relations(
+    userid: &i64,
+    login: &Option<String>,
+    issue: &Issue,
+    pr: &Option<&PullRequest>,
+) -> Vec<&'static str> {
+    let mut lst = vec![RELATIONS[0]];
+    if issue.author_id == *userid {
+        lst.push(RELATIONS[1]);
+    }
+    if pr.map_or(false, |p| p.review_requests.contains(userid)) {
+        lst.push(RELATIONS[2]);
+    }
+    if login
+        .as_ref()
+        .map_or(false, |l| is_mentioned(&issue.body, &l))
+    {
+        lst.push(RELATIONS[3]);
+    }
+    lst
 }
 
 /// Marks a specified Pull Request as having been viewed.
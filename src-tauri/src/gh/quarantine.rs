@@ -0,0 +1,231 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::RepoSyncStatus;
+
+/// Consecutive failures after which a repository is quarantined.
+///
+pub const QUARANTINE_AFTER_FAILURES: i64 = 3;
+
+/// Quarantined repositories are only retried once a day.
+///
+pub const QUARANTINE_BACKOFF: i64 = 24 * 60 * 60;
+
+/// Check whether errors of this kind point at the repository itself, e.g.
+/// access having been lost or the repository being gone, rather than at
+/// something transient, like the network or rate limiting, or at the token
+/// as a whole.
+///
+pub fn is_repo_error(err: &GHDError) -> bool {
+    match err {
+        GHDError::NotFoundError | GHDError::PermissionDeniedError => true,
+        _ => false,
+    }
+}
+
+/// Check whether syncing repository `owner/name` is on hold, either because
+/// it has been paused or because it's quarantined and not yet due a retry.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn is_held(db: &DB, owner: &String, name: &String) -> bool {
+    match sqlx::query_scalar::<_, bool>(
+        "
+        SELECT paused OR COALESCE(retry_at > ?, 0) FROM repo_sync
+        WHERE repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(chrono::Utc::now().timestamp())
+    .bind(&owner)
+    .bind(&name)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res.unwrap_or(false),
+        Err(err) => {
+            panic!(
+                "Unable to obtain sync status for '{}/{}': {}",
+                owner, name, err
+            );
+        }
+    }
+}
+
+/// Record a failure to sync repository `owner/name`, quarantining it if it
+/// has failed too many times in a row. While quarantined, each failed retry
+/// pushes the next retry back again.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `err` - The error syncing the repository.
+///
+pub async fn record_failure(
+    db: &DB,
+    owner: &String,
+    name: &String,
+    err: &GHDError,
+) {
+    if !is_repo_error(&err) {
+        return;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query(
+        "
+        INSERT INTO repo_sync (
+            repo_owner, repo_name, paused, failures, last_error, failed_at
+        ) VALUES (?1, ?2, 0, 1, ?3, ?4)
+        ON CONFLICT (repo_owner, repo_name) DO UPDATE SET
+            failures = failures + 1,
+            last_error = excluded.last_error,
+            failed_at = excluded.failed_at,
+            retry_at = CASE
+                WHEN failures + 1 >= ?5 THEN ?4 + ?6
+                ELSE NULL
+            END
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(format!("{:?}", err))
+    .bind(&now)
+    .bind(QUARANTINE_AFTER_FAILURES)
+    .bind(QUARANTINE_BACKOFF)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to record failure for '{}/{}': {}", owner, name, err);
+    });
+}
+
+/// Record a successful sync of repository `owner/name`, lifting any
+/// quarantine.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn record_success(db: &DB, owner: &String, name: &String) {
+    sqlx::query(
+        "
+        UPDATE repo_sync
+        SET failures = 0, last_error = NULL, failed_at = NULL, retry_at = NULL
+        WHERE repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to record success for '{}/{}': {}", owner, name, err);
+    });
+}
+
+/// Retry a quarantined repository `owner/name` on the next sync, rather than
+/// waiting out its backoff. Its failures are kept, so it goes straight back
+/// into quarantine should it fail again.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn retry(db: &DB, owner: &String, name: &String) {
+    sqlx::query(
+        "
+        UPDATE repo_sync SET retry_at = NULL
+        WHERE repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to retry '{}/{}': {}", owner, name, err);
+    });
+}
+
+/// Pause or resume syncing repository `owner/name`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `paused` - Whether syncing should be paused.
+///
+pub async fn set_paused(db: &DB, owner: &String, name: &String, paused: bool) {
+    sqlx::query(
+        "
+        INSERT INTO repo_sync (repo_owner, repo_name, paused, failures)
+        VALUES (?, ?, ?, 0)
+        ON CONFLICT (repo_owner, repo_name) DO UPDATE SET
+            paused = excluded.paused
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(&paused)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to pause '{}/{}': {}", owner, name, err);
+    });
+}
+
+/// Obtain the sync status of every maintained repository.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_status(db: &DB) -> Vec<RepoSyncStatus> {
+    match sqlx::query_as::<_, RepoSyncStatus>(
+        "
+        SELECT
+            repos.owner, repos.name,
+            COALESCE(repo_sync.paused, 0) AS paused,
+            COALESCE(repo_sync.failures, 0) AS failures,
+            repo_sync.last_error, repo_sync.failed_at, repo_sync.retry_at
+        FROM
+            repos LEFT JOIN repo_sync
+        ON
+            repo_sync.repo_owner = repos.owner
+            AND repo_sync.repo_name = repos.name
+        ORDER BY repos.owner, repos.name
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain repo sync status: {}", err);
+        }
+    }
+}
@@ -0,0 +1,414 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{accounts::AccountCredentials, rest, types::ReactionCount, users};
+
+/// Reactions supported by the API.
+///
+pub const CONTENTS: [&str; 8] = [
+    "+1", "-1", "laugh", "confused", "heart", "hooray", "rocket", "eyes",
+];
+
+/// What a reaction is left on: an issue or Pull Request itself, or one of
+/// its comments, either in the conversation or on the diff.
+///
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReactionSubject {
+    Issue,
+    IssueComment { comment_id: i64 },
+    ReviewComment { comment_id: i64 },
+}
+
+impl ReactionSubject {
+    pub fn kind(self: &Self) -> &'static str {
+        match self {
+            ReactionSubject::Issue => "issue",
+            ReactionSubject::IssueComment { .. } => "issue_comment",
+            ReactionSubject::ReviewComment { .. } => "review_comment",
+        }
+    }
+
+    /// The comment's ID, or `0` for the issue itself, as stored.
+    ///
+    fn comment_id(self: &Self) -> i64 {
+        match self {
+            ReactionSubject::Issue => 0,
+            ReactionSubject::IssueComment { comment_id }
+            | ReactionSubject::ReviewComment { comment_id } => *comment_id,
+        }
+    }
+}
+
+/// The issue a reaction's subject belongs to, as needed to reach the API.
+///
+#[derive(sqlx::FromRow)]
+pub struct ReactionTarget {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+}
+
+/// Obtain the issue or Pull Request `issueid`, to react on.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's ID.
+///
+pub async fn get_target(
+    db: &DB,
+    issueid: &i64,
+) -> Result<ReactionTarget, GHDError> {
+    match sqlx::query_as::<_, ReactionTarget>(
+        "SELECT repo_owner, repo_name, number FROM issues WHERE id = ?",
+    )
+    .bind(&issueid)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(Some(res)) => Ok(res),
+        Ok(None) => Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain issue {}: {}", issueid, err);
+        }
+    }
+}
+
+/// Obtain the API endpoint listing the reactions on `subject`.
+///
+fn endpoint(target: &ReactionTarget, subject: &ReactionSubject) -> String {
+    let repo = format!("/repos/{}/{}", target.repo_owner, target.repo_name);
+    match subject {
+        ReactionSubject::Issue => {
+            format!("{}/issues/{}/reactions", repo, target.number)
+        }
+        ReactionSubject::IssueComment { comment_id } => {
+            format!("{}/issues/comments/{}/reactions", repo, comment_id)
+        }
+        ReactionSubject::ReviewComment { comment_id } => {
+            format!("{}/pulls/comments/{}/reactions", repo, comment_id)
+        }
+    }
+}
+
+/// Obtain the reactions on `subject`, by content, along with the ID of the
+/// reaction left by the credentials' account, if any.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `target` - The issue the subject belongs to.
+/// * `subject` - What the reactions are left on.
+///
+pub async fn fetch(
+    creds: &AccountCredentials,
+    target: &ReactionTarget,
+    subject: &ReactionSubject,
+) -> Result<HashMap<String, (i64, Option<i64>)>, GHDError> {
+    let me = match users::whoami(&creds.token, &creds.host).await {
+        Ok(res) => res.login,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("reactions");
+    let reactions = match ghreq
+        .get_all_pages::<rest::GithubReactionReply>(&endpoint(
+            &target, &subject,
+        ))
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    let mut res: HashMap<String, (i64, Option<i64>)> = HashMap::new();
+    for reaction in &reactions {
+        let entry = res.entry(reaction.content.clone()).or_insert((0, None));
+        entry.0 += 1;
+        if reaction.user.as_ref().map(|u| &u.login) == Some(&me) {
+            entry.1 = Some(reaction.id);
+        }
+    }
+    Ok(res)
+}
+
+/// Replace the cached reactions on `subject` of issue `issueid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's ID.
+/// * `subject` - What the reactions are left on.
+/// * `counts` - The reactions, as returned by `fetch()`.
+///
+pub async fn consume(
+    db: &DB,
+    issueid: &i64,
+    subject: &ReactionSubject,
+    counts: &HashMap<String, (i64, Option<i64>)>,
+) {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to update reactions: {}", err);
+        }
+    };
+    sqlx::query(
+        "
+        DELETE FROM reactions
+        WHERE issue_id = ? AND subject = ? AND comment_id = ?
+        ",
+    )
+    .bind(&issueid)
+    .bind(subject.kind())
+    .bind(subject.comment_id())
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to clear reactions for issue {}: {}", issueid, err);
+    });
+
+    for (content, (count, mine)) in counts {
+        sqlx::query(
+            "
+            INSERT INTO reactions (
+                issue_id, subject, comment_id, content, count, my_reaction_id
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&issueid)
+        .bind(subject.kind())
+        .bind(subject.comment_id())
+        .bind(&content)
+        .bind(&count)
+        .bind(&mine)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to add reactions for issue {}: {}", issueid, err);
+        });
+    }
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit transaction to update reactions: {}", err);
+    });
+}
+
+/// Leave a `content` reaction on `subject` through the API. Returns the
+/// reaction's ID, and whether it's new rather than already there.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `target` - The issue the subject belongs to.
+/// * `subject` - What to react on.
+/// * `content` - The reaction; e.g., `+1`.
+///
+pub async fn add(
+    creds: &AccountCredentials,
+    target: &ReactionTarget,
+    subject: &ReactionSubject,
+    content: &String,
+) -> Result<(i64, bool), GHDError> {
+    if !CONTENTS.contains(&content.as_str()) {
+        return Err(GHDError::BadRequest);
+    }
+
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("reactions");
+    let req = ghreq
+        .post(&endpoint(&target, &subject))
+        .json(&serde_json::json!({ "content": content }));
+    let res = match ghreq.execute(req).await {
+        Ok(res) if res.status().is_success() => res,
+        Ok(res) => return Err(rest::status_to_error(res.status())),
+        Err(_) => return Err(GHDError::UnknownError),
+    };
+    let created = res.status() == reqwest::StatusCode::CREATED;
+    match res.json::<rest::GithubReactionReply>().await {
+        Ok(reaction) => Ok((reaction.id, created)),
+        Err(_) => Err(GHDError::UnknownError),
+    }
+}
+
+/// Remove reaction `reactionid` from `subject` through the API.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `target` - The issue the subject belongs to.
+/// * `subject` - What the reaction was left on.
+/// * `reactionid` - The reaction's ID.
+///
+pub async fn remove(
+    creds: &AccountCredentials,
+    target: &ReactionTarget,
+    subject: &ReactionSubject,
+    reactionid: &i64,
+) -> Result<(), GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("reactions");
+    let ep = format!("{}/{}", endpoint(&target, &subject), reactionid);
+
+    match ghreq.execute(ghreq.delete(&ep)).await {
+        Ok(res) if res.status().is_success() => Ok(()),
+        // already gone, which is what we wanted.
+        Ok(res) if res.status() == reqwest::StatusCode::NOT_FOUND => Ok(()),
+        Ok(res) => Err(rest::status_to_error(res.status())),
+        Err(_) => Err(GHDError::UnknownError),
+    }
+}
+
+/// Obtain our cached `content` reaction's ID on `subject` of issue
+/// `issueid`, if known.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's ID.
+/// * `subject` - What the reaction was left on.
+/// * `content` - The reaction; e.g., `+1`.
+///
+pub async fn get_mine(
+    db: &DB,
+    issueid: &i64,
+    subject: &ReactionSubject,
+    content: &String,
+) -> Option<i64> {
+    match sqlx::query_scalar::<_, Option<i64>>(
+        "
+        SELECT my_reaction_id FROM reactions
+        WHERE issue_id = ? AND subject = ? AND comment_id = ? AND content = ?
+        ",
+    )
+    .bind(&issueid)
+    .bind(subject.kind())
+    .bind(subject.comment_id())
+    .bind(&content)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res.flatten(),
+        Err(err) => {
+            panic!("Unable to obtain reaction for issue {}: {}", issueid, err);
+        }
+    }
+}
+
+/// Reflect our `content` reaction being added, with ID `reactionid`, or
+/// removed, if not provided, in the cached counts. Counts only change if
+/// `changed` is set; i.e., the reaction wasn't already there, or gone.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's ID.
+/// * `subject` - What the reaction was left on.
+/// * `content` - The reaction; e.g., `+1`.
+/// * `reactionid` - Our reaction's ID, if added.
+/// * `changed` - Whether the count changed.
+///
+pub async fn apply(
+    db: &DB,
+    issueid: &i64,
+    subject: &ReactionSubject,
+    content: &String,
+    reactionid: &Option<i64>,
+    changed: bool,
+) {
+    let delta = match (reactionid, changed) {
+        (_, false) => 0,
+        (Some(_), true) => 1,
+        (None, true) => -1,
+    };
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to apply reaction: {}", err);
+        }
+    };
+    for q in [
+        "
+        INSERT INTO reactions (
+            issue_id, subject, comment_id, content, count, my_reaction_id
+        ) VALUES (?1, ?2, ?3, ?4, MAX(?5, 0), ?6)
+        ON CONFLICT (issue_id, subject, comment_id, content) DO UPDATE SET
+            count = MAX(count + ?5, 0),
+            my_reaction_id = ?6
+        ",
+        "DELETE FROM reactions WHERE count = 0",
+    ] {
+        sqlx::query(q)
+            .bind(&issueid)
+            .bind(subject.kind())
+            .bind(subject.comment_id())
+            .bind(&content)
+            .bind(&delta)
+            .bind(&reactionid)
+            .execute(&mut tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Unable to update reactions for issue {}: {}",
+                    issueid, err
+                );
+            });
+    }
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit transaction to apply reaction: {}", err);
+    });
+}
+
+/// Obtain the cached reactions on `subject` of issue `issueid`, in the order
+/// GitHub shows them.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's ID.
+/// * `subject` - What the reactions are left on.
+///
+pub async fn get_reactions(
+    db: &DB,
+    issueid: &i64,
+    subject: &ReactionSubject,
+) -> Vec<ReactionCount> {
+    let mut res = match sqlx::query_as::<_, ReactionCount>(
+        "
+        SELECT
+            content, count, my_reaction_id IS NOT NULL AS reacted
+        FROM reactions
+        WHERE issue_id = ? AND subject = ? AND comment_id = ?
+        ",
+    )
+    .bind(&issueid)
+    .bind(subject.kind())
+    .bind(subject.comment_id())
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain reactions for issue {}: {}", issueid, err);
+        }
+    };
+    res.sort_by_key(|r| CONTENTS.iter().position(|c| *c == r.content));
+    res
+}
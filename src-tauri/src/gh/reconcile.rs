@@ -0,0 +1,431 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{
+    accounts::AccountCredentials,
+    outbox::PullRequestAction,
+    refresh, rest,
+    types::{IssueChange, PullRequestStatus},
+};
+
+/// Seconds to wait after a write action before checking its outcome against
+/// GitHub, which may take a moment to reflect it.
+///
+const VERIFY_DELAY_SECS: i64 = 30;
+
+/// Apply the expected outcome of a successful `action` to our copy of Pull
+/// Request `prid`, so it shows right away, and schedule verifying it against
+/// GitHub.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+/// * `action` - The action that succeeded.
+///
+pub async fn apply(db: &DB, prid: &i64, action: &PullRequestAction) {
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to apply action: {}", err);
+        }
+    };
+
+    match action {
+        PullRequestAction::Comment { .. } => {}
+        PullRequestAction::Review { event, .. } => {
+            let decision = match event.as_str() {
+                "APPROVE" => Some("approved"),
+                "REQUEST_CHANGES" => Some("changes_requested"),
+                _ => None,
+            };
+            if let Some(decision) = decision {
+                sqlx::query(
+                    "UPDATE pull_requests SET review_decision = ? WHERE id = ?",
+                )
+                .bind(&decision)
+                .bind(&prid)
+                .execute(&mut *tx)
+                .await
+                .unwrap_or_else(|err| {
+                    panic!("Unable to apply review on pr {}: {}", prid, err);
+                });
+            }
+        }
+        PullRequestAction::Merge { .. } => {
+            sqlx::query(
+                "UPDATE issues SET state = 'merged', closed_at = ? WHERE id = ?",
+            )
+            .bind(&now)
+            .bind(&prid)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to apply merge on pr {}: {}", prid, err);
+            });
+            sqlx::query("UPDATE pull_requests SET merged_at = ? WHERE id = ?")
+                .bind(&now)
+                .bind(&prid)
+                .execute(&mut *tx)
+                .await
+                .unwrap_or_else(|err| {
+                    panic!("Unable to apply merge on pr {}: {}", prid, err);
+                });
+        }
+    };
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO pr_verifications (pr_id, due_at) VALUES (?, ?)",
+    )
+    .bind(&prid)
+    .bind(now + VERIFY_DELAY_SECS)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to schedule verifying pr {}: {}", prid, err);
+    });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit transaction to apply action: {}", err);
+    });
+}
+
+/// Obtain the IDs of the Pull Requests due for verification.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_due(db: &DB) -> Vec<i64> {
+    match sqlx::query_scalar::<_, i64>(
+        "SELECT pr_id FROM pr_verifications WHERE due_at <= ? ORDER BY due_at",
+    )
+    .bind(chrono::Utc::now().timestamp())
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain due verifications: {}", err);
+        }
+    }
+}
+
+/// Drop the scheduled verification of Pull Request `prid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn unschedule(db: &DB, prid: &i64) {
+    sqlx::query("DELETE FROM pr_verifications WHERE pr_id = ?")
+        .bind(&prid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to unschedule verifying pr {}: {}", prid, err);
+        });
+}
+
+/// Reconcile our copy of Pull Request `prid` with its `remote` status, as
+/// obtained from GitHub. Returns the fields that had to be corrected, if any.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+/// * `remote` - The Pull Request's status on GitHub.
+///
+pub async fn reconcile(
+    db: &DB,
+    prid: &i64,
+    remote: &PullRequestStatus,
+) -> Result<Option<IssueChange>, GHDError> {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Error starting transaction to reconcile pull request: {}",
+                err
+            );
+        }
+    };
+
+    let local = match sqlx::query_as::<_, PullRequestStatus>(
+        "
+        SELECT issues.state, pull_requests.review_decision,
+            issues.closed_at, pull_requests.merged_at
+        FROM issues JOIN pull_requests ON issues.id = pull_requests.id
+        WHERE issues.id = ?
+        ",
+    )
+    .bind(&prid)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some(res)) => res,
+        Ok(None) => return Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain status for pr {}: {}", prid, err);
+        }
+    };
+
+    sqlx::query("DELETE FROM pr_verifications WHERE pr_id = ?")
+        .bind(&prid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to unschedule verifying pr {}: {}", prid, err);
+        });
+
+    if local == *remote {
+        tx.commit().await.unwrap_or_else(|err| {
+            panic!(
+                "Unable to commit transaction to reconcile pull request: {}",
+                err
+            );
+        });
+        return Ok(None);
+    }
+
+    let mut fields = vec![];
+    if local.state != remote.state {
+        fields.push(String::from("state"));
+    }
+    if local.review_decision != remote.review_decision {
+        fields.push(String::from("review_decision"));
+    }
+    if local.closed_at != remote.closed_at {
+        fields.push(String::from("closed_at"));
+    }
+    if local.merged_at != remote.merged_at {
+        fields.push(String::from("merged_at"));
+    }
+
+    sqlx::query("UPDATE issues SET state = ?, closed_at = ? WHERE id = ?")
+        .bind(&remote.state)
+        .bind(&remote.closed_at)
+        .bind(&prid)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to reconcile pr {}: {}", prid, err);
+        });
+    sqlx::query(
+        "UPDATE pull_requests SET review_decision = ?, merged_at = ? WHERE id = ?",
+    )
+    .bind(&remote.review_decision)
+    .bind(&remote.merged_at)
+    .bind(&prid)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to reconcile pr {}: {}", prid, err);
+    });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!(
+            "Unable to commit transaction to reconcile pull request: {}",
+            err
+        );
+    });
+    Ok(Some(IssueChange { id: *prid, fields }))
+}
+
+/// State given to issues and Pull Requests that no longer exist on GitHub.
+/// These are kept, but left out of listings unless explicitly asked for.
+///
+pub const STATE_DELETED_UPSTREAM: &str = "deleted_upstream";
+
+/// Condition excluding issues deleted upstream from the `issues` row being
+/// selected.
+///
+pub const NOT_DELETED: &str = "issues.state != 'deleted_upstream'";
+
+/// Full reconciliations are expensive; run them once a day.
+///
+pub const FULL_RECONCILE_INTERVAL: i64 = 24 * 60 * 60;
+
+/// Obtain the sync refresh key for the full reconciliation of user `userid`.
+///
+pub fn refresh_key(userid: &i64) -> String {
+    format!("reconcile:{}", userid)
+}
+
+/// Check whether user `userid` is due for a full reconciliation.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The user's database ID.
+///
+pub async fn should_reconcile_user(db: &DB, userid: &i64) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&userid),
+        FULL_RECONCILE_INTERVAL,
+    )
+    .await
+}
+
+/// An issue or Pull Request we hold as open, but which wasn't listed by
+/// GitHub.
+///
+#[derive(sqlx::FromRow)]
+pub struct MissingIssue {
+    pub id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+}
+
+/// Obtain the open issues and Pull Requests associated with user `userid`
+/// that aren't in `listed`, i.e. the IDs GitHub currently lists as open for
+/// the user.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The user's database ID.
+/// * `listed` - The IDs listed by GitHub.
+///
+pub async fn get_missing(
+    db: &DB,
+    userid: &i64,
+    listed: &Vec<i64>,
+) -> Vec<MissingIssue> {
+    let open = match sqlx::query_as::<_, MissingIssue>(
+        "
+        SELECT issues.id, issues.repo_owner, issues.repo_name, issues.number
+        FROM issues INNER JOIN user_issues
+            ON user_issues.issue_id = issues.id
+        WHERE user_issues.user_id = ? AND issues.state = 'open'
+        ",
+    )
+    .bind(&userid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain open issues for {}: {}", userid, err);
+        }
+    };
+
+    open.into_iter()
+        .filter(|issue| !listed.contains(&issue.id))
+        .collect()
+}
+
+/// Check whether `issue` still exists on GitHub. Only a definitive answer
+/// from GitHub counts as gone; any other error is returned, so the issue is
+/// left alone. GitHub also answers `404` for issues on private repositories
+/// we lost access to, so those only count as gone if the repository itself
+/// is still there.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `issue` - The issue to check.
+///
+pub async fn exists_upstream(
+    creds: &AccountCredentials,
+    issue: &MissingIssue,
+) -> Result<bool, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("reconcile");
+    let ep = format!(
+        "/repos/{}/{}/issues/{}",
+        issue.repo_owner, issue.repo_name, issue.number
+    );
+    let res = match ghreq.execute(ghreq.get(&ep)).await {
+        Ok(res) => res,
+        Err(err) => {
+            println!("unable to reach github for {}: {}", ep, err);
+            return Err(GHDError::UnknownError);
+        }
+    };
+
+    match res.status() {
+        reqwest::StatusCode::GONE => return Ok(false),
+        reqwest::StatusCode::NOT_FOUND => {}
+        status if status.is_success() => return Ok(true),
+        status => return Err(rest::status_to_error(status)),
+    };
+
+    let ep = format!("/repos/{}/{}", issue.repo_owner, issue.repo_name);
+    match ghreq.execute(ghreq.get(&ep)).await {
+        Ok(res) if res.status().is_success() => Ok(false),
+        Ok(res) => Err(rest::status_to_error(res.status())),
+        Err(err) => {
+            println!("unable to reach github for {}: {}", ep, err);
+            Err(GHDError::UnknownError)
+        }
+    }
+}
+
+/// Mark issue `id` as deleted upstream, returning the change for it.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The issue's database ID.
+///
+pub async fn mark_deleted(db: &DB, id: &i64) -> IssueChange {
+    sqlx::query("UPDATE issues SET state = ? WHERE id = ?")
+        .bind(STATE_DELETED_UPSTREAM)
+        .bind(&id)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to mark issue {} as deleted: {}", id, err);
+        });
+
+    IssueChange {
+        id: *id,
+        fields: vec![String::from("state")],
+    }
+}
+
+/// Record that user `userid` has just been fully reconciled.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The user's database ID.
+///
+pub async fn mark_reconciled(db: &DB, userid: &i64) {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Error starting transaction to mark user reconciled: {}",
+                err
+            );
+        }
+    };
+    refresh::update_sync_refresh(
+        &mut tx,
+        &refresh_key(&userid),
+        &chrono::Utc::now(),
+    )
+    .await;
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit reconciliation of {}: {}", userid, err);
+    });
+}
@@ -0,0 +1,121 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::db::DB;
+
+use super::types::{Issue, IssueReference};
+
+/// The furthest, in references, looked for what links to an issue.
+///
+pub const MAX_DEPTH: i64 = 3;
+
+/// Replace the cross-references known to `issue` with those just synced.
+/// This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issue` - The issue or Pull Request just synced.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issue: &Issue,
+) {
+    sqlx::query("DELETE FROM issue_references WHERE target_id = ?")
+        .bind(&issue.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to clear references to {}: {}", issue.id, err);
+        });
+
+    for reference in &issue.references {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO issue_references (
+                target_id, source_id, number, title, url, repo_owner,
+                repo_name, is_pull_request, state, will_close, referenced_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&issue.id)
+        .bind(&reference.source_id)
+        .bind(&reference.number)
+        .bind(&reference.title)
+        .bind(&reference.url)
+        .bind(&reference.repo_owner)
+        .bind(&reference.repo_name)
+        .bind(&reference.is_pull_request)
+        .bind(&reference.state)
+        .bind(&reference.will_close)
+        .bind(&reference.referenced_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to consume reference to {}: {}", issue.id, err);
+        });
+    }
+}
+
+/// Obtain everything linking to issue or Pull Request `issueid`, directly or
+/// through up to `depth` references, capped at `MAX_DEPTH`. Each source is
+/// listed once, by its closest reference; closest first, then most recent.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The database ID of the issue or Pull Request linked to.
+/// * `depth` - How many references away to look.
+///
+pub async fn get_references(
+    db: &DB,
+    issueid: &i64,
+    depth: &i64,
+) -> Vec<IssueReference> {
+    match sqlx::query_as::<_, IssueReference>(
+        "
+        WITH RECURSIVE graph (target_id, source_id, depth) AS (
+            SELECT target_id, source_id, 1 FROM issue_references
+            WHERE target_id = ?1
+            UNION
+            SELECT issue_references.target_id, issue_references.source_id,
+                graph.depth + 1
+            FROM issue_references
+            INNER JOIN graph
+                ON issue_references.target_id = graph.source_id
+            WHERE graph.depth < ?2
+        ),
+        closest (target_id, source_id, depth) AS (
+            SELECT target_id, source_id, MIN(depth) FROM graph
+            WHERE source_id != ?1
+            GROUP BY source_id
+        )
+        SELECT issue_references.*, closest.depth FROM issue_references
+        INNER JOIN closest
+            ON issue_references.target_id = closest.target_id
+            AND issue_references.source_id = closest.source_id
+        ORDER BY closest.depth, issue_references.referenced_at DESC
+        ",
+    )
+    .bind(&issueid)
+    .bind(depth.clamp(&1, &MAX_DEPTH))
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain references to {}: {}", issueid, err);
+        }
+    }
+}
@@ -12,9 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{common, db::DB, errors::GHDError};
+use crate::{
+    common,
+    db::{Snapshot, DB},
+    errors::GHDError,
+    store::ReadStore,
+};
 
-use super::{types::GithubUser, users};
+use super::{
+    enrich,
+    types::{GithubUser, PullRequestTableEntry},
+    users,
+};
 
 const USER_REFRESH_INTERVAL: i64 = 60;
 
@@ -47,6 +56,31 @@ pub async fn get_user_refresh(
     }
 }
 
+/// Obtain when every tracked user was last refreshed by, i.e., how stale
+/// what's cached for them may be at most. `None` if any of them has never
+/// been refreshed, or if there's none.
+///
+/// # Arguments
+///
+/// * `snapshot` - The database snapshot to read from.
+///
+pub async fn get_users_refreshed_at(snapshot: &Snapshot) -> Option<i64> {
+    match sqlx::query_scalar::<_, Option<i64>>(
+        "
+        SELECT CASE WHEN MIN(refresh_at) > 0 THEN MIN(refresh_at) END
+        FROM user_refresh
+        ",
+    )
+    .fetch_one(&mut *snapshot.conn().await)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain users refresh: {}", err);
+        }
+    }
+}
+
 /// Check whether we should refresh a given user, by login.
 ///
 /// # Arguments
@@ -115,3 +149,137 @@ pub async fn get_to_refresh_users(db: &DB) -> Vec<GithubUser> {
         }
     }
 }
+
+/// Obtain `chrono::DateTime` from when the sync job identified by `key` last
+/// ran. Sync jobs are those not tied to a specific user's issues, running on
+/// their own cadence; e.g., organization membership.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the sync refreshes.
+/// * `key` - String identifying the sync job.
+///
+pub async fn get_sync_refresh<S: ReadStore>(
+    store: &S,
+    key: &String,
+) -> Result<chrono::DateTime<chrono::Utc>, GHDError> {
+    match store.get_sync_refresh(&key).await {
+        Some(res) => Ok(common::ts_to_datetime(res).unwrap()),
+        None => Err(GHDError::NeverRefreshedError),
+    }
+}
+
+/// Check whether the sync job identified by `key` should run, given it is
+/// expected to run every `interval` seconds.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the sync refreshes.
+/// * `key` - String identifying the sync job.
+/// * `interval` - Number of seconds between runs.
+///
+pub async fn should_refresh_sync<S: ReadStore>(
+    store: &S,
+    key: &String,
+    interval: i64,
+) -> bool {
+    match get_sync_refresh(store, &key).await {
+        Ok(val) => common::has_expired(&val, interval),
+        Err(GHDError::NeverRefreshedError) => true,
+        Err(err) => {
+            panic!("Unknown error while checking sync refresh: {:?}", err);
+        }
+    }
+}
+
+/// Record that the sync job identified by `key` ran at `when`. This function
+/// requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `key` - String identifying the sync job.
+/// * `when` - When the sync job ran.
+///
+pub async fn update_sync_refresh(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    key: &String,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    sqlx::query(
+        "INSERT OR REPLACE INTO sync_refresh (key, refresh_at) VALUES (?, ?)",
+    )
+    .bind(&key)
+    .bind(when.timestamp())
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating sync refresh for '{}': {}", key, err);
+    });
+}
+
+/// Obtain the open Pull Requests, authored by one of our accounts, on a
+/// maintained repository, or otherwise visible to us, for which the per-PR
+/// sync job `<prefix>:<id>` has never run, or has not run since the Pull
+/// Request was last updated. The most visible come first, as ranked by
+/// `enrich::ENRICHMENT_PRIORITY`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prefix` - The prefix of the per-PR sync job's key; e.g., `commits`.
+/// * `limit` - Maximum number of Pull Requests to return.
+///
+pub async fn get_stale_watched_prs(
+    db: &DB,
+    prefix: &str,
+    limit: i64,
+) -> Result<Vec<PullRequestTableEntry>, GHDError> {
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at
+        FROM
+            pull_requests
+            INNER JOIN issues ON pull_requests.id = issues.id
+            LEFT JOIN sync_refresh
+                ON sync_refresh.key = ? || ':' || issues.id
+        WHERE
+            issues.state = 'open'
+            AND (
+                issues.author_id IN (SELECT user_id FROM tokens)
+                OR EXISTS (
+                    SELECT 1 FROM repos
+                    WHERE
+                        repos.owner = issues.repo_owner
+                        AND repos.name = issues.repo_name
+                )
+                OR ({}) > 0
+            )
+            AND (
+                sync_refresh.refresh_at IS NULL
+                OR sync_refresh.refresh_at < issues.updated_at
+            )
+        ORDER BY ({}) DESC, issues.updated_at DESC
+        LIMIT ?
+        ",
+        enrich::ENRICHMENT_PRIORITY,
+        enrich::ENRICHMENT_PRIORITY
+    );
+
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&prefix)
+        .bind(&limit)
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!(
+                "Unable to obtain stale '{}' pull requests: {}",
+                prefix, err
+            );
+        }
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError, updates};
+
+use super::{
+    accounts::AccountCredentials,
+    refresh, rest,
+    types::{PullRequestTableEntry, Release},
+};
+
+/// Releases are checked once an hour.
+///
+pub const RELEASES_REFRESH_INTERVAL: i64 = 60 * 60;
+
+/// Changelog sections, keyed by conventional commit type, in the order they
+/// should show up in the draft.
+///
+const SECTIONS: [(&str, &str); 8] = [
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Maintenance"),
+    ("ci", "Maintenance"),
+];
+
+const OTHER_SECTION: &str = "Other Changes";
+
+/// Obtain the sync refresh key for the releases of repository `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("releases:{}/{}", owner, name)
+}
+
+/// Check whether the releases of repository `owner/name` are due for a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        RELEASES_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Obtain the most recently published releases of a repository. Draft
+/// releases are ignored.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch_releases(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<Release>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("releases");
+    let ep = format!("/repos/{}/{}/releases", owner, name);
+    let req = ghreq.get(&ep).query(&[("per_page", 30)]);
+    let releases = match ghreq
+        .send::<Vec<rest::GithubReleaseListEntryReply>>(req)
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(releases
+        .into_iter()
+        .filter(|r| !r.draft)
+        .filter_map(|r| {
+            Some(Release {
+                id: r.id,
+                repo_owner: owner.clone(),
+                repo_name: name.clone(),
+                tag_name: r.tag_name,
+                name: r.name,
+                url: r.html_url,
+                published_at: r.published_at?.timestamp(),
+            })
+        })
+        .collect())
+}
+
+/// Write the provided releases of repository `owner/name` to the database.
+/// This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `releases` - The repository's most recent releases.
+/// * `when` - When the releases were obtained.
+///
+pub async fn consume_releases(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+    releases: &Vec<Release>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    for r in releases {
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO releases (
+                id, repo_owner, repo_name, tag_name, name, url, published_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&r.id)
+        .bind(&r.repo_owner)
+        .bind(&r.repo_name)
+        .bind(&r.tag_name)
+        .bind(&r.name)
+        .bind(&r.url)
+        .bind(&r.published_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to insert release {}: {}", r.tag_name, err);
+        });
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+/// Obtain the tag of the highest version amongst a repository's tags, if
+/// any is named after a version; e.g., `v1.2.3`. Tags are not listed in
+/// order of date, so versions are the only way to tell which is the latest.
+///
+async fn fetch_last_version_tag(
+    ghreq: &rest::GithubRequest,
+    owner: &String,
+    name: &String,
+) -> Result<Option<String>, GHDError> {
+    let ep = format!("/repos/{}/{}/tags", owner, name);
+    let tags = match ghreq
+        .get_all_pages_capped::<rest::GithubTagReply>(&ep)
+        .await
+    {
+        Ok((res, _)) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    Ok(tags
+        .into_iter()
+        .filter_map(|t| updates::parse_version(&t.name).map(|v| (v, t.name)))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, tag)| tag))
+}
+
+/// Obtain the tag of a repository's latest release, along with the date of
+/// the commit it points to. Repositories without releases fall back to
+/// their highest version tag. Returns `None` if there's neither.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch_last_tag(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Option<(String, chrono::DateTime<chrono::Utc>)>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("release_notes");
+    let ep = format!("/repos/{}/{}/releases/latest", owner, name);
+    let req = ghreq.get(&ep);
+    let tag = match ghreq.send::<rest::GithubReleaseListEntryReply>(req).await {
+        Ok(res) => res.tag_name,
+        Err(reqwest::StatusCode::NOT_FOUND) => {
+            match fetch_last_version_tag(&ghreq, &owner, &name).await? {
+                Some(res) => res,
+                None => return Ok(None),
+            }
+        }
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    let ep = format!("/repos/{}/{}/commits/{}", owner, name, tag);
+    let req = ghreq.get(&ep);
+    match ghreq.send::<rest::GithubCommitReply>(req).await {
+        Ok(res) => Ok(Some((tag, res.commit.committer.date))),
+        Err(err) => Err(rest::status_to_error(err)),
+    }
+}
+
+/// Obtain all cached Pull Requests for a repository merged after `since`, or
+/// all merged Pull Requests if `since` is not provided. Only Pull Requests
+/// involving tracked users are cached, so this may not be exhaustive.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `since` - Optional timestamp after which Pull Requests were merged.
+///
+pub async fn get_merged_prs_since(
+    db: &DB,
+    owner: &String,
+    name: &String,
+    since: &Option<i64>,
+) -> Result<Vec<PullRequestTableEntry>, GHDError> {
+    match sqlx::query_as::<_, PullRequestTableEntry>(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at
+        FROM
+            pull_requests INNER JOIN issues
+        ON
+            pull_requests.id = issues.id
+        WHERE
+            issues.repo_owner = ? AND issues.repo_name = ?
+            AND pull_requests.merged_at IS NOT NULL
+            AND pull_requests.merged_at > ?
+        ORDER BY pull_requests.merged_at ASC
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(since.unwrap_or(0))
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain merged pull requests: {}", err);
+        }
+    }
+}
+
+/// Obtain the changelog section for a Pull Request title, based on its
+/// conventional commit prefix (e.g., `fix(gh): ...`), along with the title
+/// stripped of said prefix.
+///
+fn classify_title(title: &String) -> (&'static str, String) {
+    if let Some((prefix, rest)) = title.split_once(':') {
+        let kind = prefix
+            .split('(')
+            .next()
+            .unwrap_or("")
+            .trim_end_matches('!')
+            .trim()
+            .to_lowercase();
+        for (k, section) in SECTIONS {
+            if kind == k {
+                return (section, rest.trim().to_string());
+            }
+        }
+    }
+
+    (OTHER_SECTION, title.clone())
+}
+
+/// Render the provided Pull Requests as a Markdown changelog, grouped by
+/// section.
+///
+/// # Arguments
+///
+/// * `prs` - The Pull Requests to include in the changelog.
+///
+pub fn render_markdown(prs: &Vec<PullRequestTableEntry>) -> String {
+    let mut sections: Vec<&str> = SECTIONS.iter().map(|(_, s)| *s).collect();
+    sections.dedup();
+    sections.push(OTHER_SECTION);
+
+    let entries: Vec<(&str, String)> = prs
+        .iter()
+        .map(|pr| {
+            let (section, title) = classify_title(&pr.title);
+            let line =
+                format!("* {} (#{}) by @{}", title, pr.number, pr.author);
+            (section, line)
+        })
+        .collect();
+
+    let mut md = String::from("## What's Changed\n");
+    for section in sections {
+        let lines: Vec<&String> = entries
+            .iter()
+            .filter(|(s, _)| *s == section)
+            .map(|(_, l)| l)
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+
+        md.push_str(&format!("\n### {}\n\n", section));
+        for line in lines {
+            md.push_str(line);
+            md.push('\n');
+        }
+    }
+
+    md
+}
+
+/// Create a draft release for a repository.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `tag` - The tag the release will be created for, once published.
+/// * `body` - The release notes.
+///
+pub async fn push_draft(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+    tag: &String,
+    body: &String,
+) -> Result<rest::GithubReleaseReply, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("release_notes");
+    let ep = format!("/repos/{}/{}/releases", owner, name);
+    let req = ghreq.post(&ep).json(&serde_json::json!({
+        "tag_name": tag,
+        "name": tag,
+        "body": body,
+        "draft": true,
+    }));
+
+    match ghreq.send::<rest::GithubReleaseReply>(req).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(rest::status_to_error(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_title_cases() {
+        let cases = [
+            ("feat: add a thing", "Features", "add a thing"),
+            ("fix(gh): handle errors", "Bug Fixes", "handle errors"),
+            ("feat!: break things", "Features", "break things"),
+            ("Perf(db)!: faster", "Performance", "faster"),
+            ("ci: bump runners", "Maintenance", "bump runners"),
+            ("chore: tidy up", OTHER_SECTION, "chore: tidy up"),
+            ("Update README", OTHER_SECTION, "Update README"),
+            ("fixes: nothing", OTHER_SECTION, "fixes: nothing"),
+        ];
+
+        for (title, section, stripped) in cases {
+            assert_eq!(
+                classify_title(&title.to_string()),
+                (section, stripped.to_string()),
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, db::DB, notify};
+
+use super::types::Reminder;
+
+/// Setting holding the number of hours after which a pending review request
+/// is reminded of; `0` disables reminders. May be suffixed with
+/// `.<owner>/<name>` to apply to a single repository.
+///
+pub const SETTING_REVIEW_HOURS: &str = "reminders.review_hours";
+
+/// Setting holding the number of hours between reminders of the same
+/// pending review request.
+///
+pub const SETTING_REPEAT_HOURS: &str = "reminders.repeat_hours";
+
+const DEFAULT_REVIEW_HOURS: i64 = 24;
+const DEFAULT_REPEAT_HOURS: i64 = 24;
+
+/// Obtain the number of hours after which review requests on repository
+/// `owner/name` are reminded of, if reminders are enabled for it.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_review_hours(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Option<i64> {
+    let key = format!("{}.{}/{}", SETTING_REVIEW_HOURS, owner, name);
+    let global = config::get_setting_or(&db, SETTING_REVIEW_HOURS, "").await;
+    let hours = config::get_setting_or(&db, &key, &global)
+        .await
+        .parse::<i64>()
+        .unwrap_or(DEFAULT_REVIEW_HOURS);
+
+    match hours {
+        h if h > 0 => Some(h),
+        _ => None,
+    }
+}
+
+/// Obtain the review requests pending on our users that are older than
+/// their repository's reminder threshold, oldest request first, whether
+/// snoozed or not.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_reminders(db: &DB) -> Vec<Reminder> {
+    let pending = match sqlx::query_as::<_, Reminder>(
+        "
+        SELECT
+            pr_review_requests.pr_id, pr_review_requests.user_id,
+            issues.repo_owner, issues.repo_name, issues.number, issues.title,
+            issues.url, pr_review_requests.requested_at,
+            review_reminders.reminded_at, reminder_snoozes.snoozed_until
+        FROM
+            pr_review_requests
+            INNER JOIN issues ON issues.id = pr_review_requests.pr_id
+            LEFT JOIN review_reminders
+                ON review_reminders.pr_id = pr_review_requests.pr_id
+                AND review_reminders.user_id = pr_review_requests.user_id
+            LEFT JOIN reminder_snoozes
+                ON reminder_snoozes.pr_id = pr_review_requests.pr_id
+        WHERE
+            pr_review_requests.user_id IN (SELECT user_id FROM tokens)
+            AND issues.state = 'open'
+        ORDER BY pr_review_requests.requested_at ASC
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain pending review requests: {}", err);
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let mut reminders = vec![];
+    for r in pending {
+        if let Some(hours) =
+            get_review_hours(&db, &r.repo_owner, &r.repo_name).await
+        {
+            if r.requested_at + hours * 60 * 60 <= now {
+                reminders.push(r);
+            }
+        }
+    }
+    reminders
+}
+
+/// Remind of the review requests that are due a reminder, i.e. that aren't
+/// snoozed and haven't been reminded of recently, queueing a notification
+/// for each.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn run(db: &DB) {
+    let repeat_hours = config::get_setting_or(&db, SETTING_REPEAT_HOURS, "")
+        .await
+        .parse::<i64>()
+        .unwrap_or(DEFAULT_REPEAT_HOURS);
+    let now = chrono::Utc::now().timestamp();
+
+    let due: Vec<Reminder> = get_reminders(&db)
+        .await
+        .into_iter()
+        .filter(|r| r.snoozed_until.map_or(true, |ts| ts <= now))
+        .filter(|r| {
+            r.reminded_at
+                .map_or(true, |ts| ts + repeat_hours * 60 * 60 <= now)
+        })
+        .collect();
+    if due.is_empty() {
+        return;
+    }
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to run reminders: {}", err);
+        }
+    };
+    for r in &due {
+        let hours = (now - r.requested_at) / (60 * 60);
+        notify::push(
+            &mut tx,
+            "review_reminder",
+            &Some(r.pr_id),
+            &format!(
+                "Review pending on {}/{}#{}",
+                r.repo_owner, r.repo_name, r.number
+            ),
+            &format!(
+                "'{}' has been waiting on a review for {}h",
+                r.title, hours
+            ),
+            notify::classify("review_reminder"),
+        )
+        .await;
+
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO review_reminders (
+                pr_id, user_id, reminded_at
+            )
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&r.pr_id)
+        .bind(&r.user_id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to record reminder for pr {}: {}", r.pr_id, err);
+        });
+    }
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit reminders: {}", err);
+    });
+}
+
+/// Snooze reminders about Pull Request `prid` for `hours`, or lift an
+/// existing snooze if `hours` is `0`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+/// * `hours` - For how many hours to snooze reminders.
+///
+pub async fn snooze(db: &DB, prid: &i64, hours: i64) {
+    let res = match hours {
+        h if h > 0 => {
+            sqlx::query(
+                "
+                INSERT OR REPLACE INTO reminder_snoozes (pr_id, snoozed_until)
+                VALUES (?, ?)
+                ",
+            )
+            .bind(&prid)
+            .bind(chrono::Utc::now().timestamp() + h * 60 * 60)
+            .execute(db.pool())
+            .await
+        }
+        _ => {
+            sqlx::query("DELETE FROM reminder_snoozes WHERE pr_id = ?")
+                .bind(&prid)
+                .execute(db.pool())
+                .await
+        }
+    };
+
+    res.unwrap_or_else(|err| {
+        panic!("Unable to snooze reminders for pr {}: {}", prid, err);
+    });
+}
@@ -0,0 +1,207 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::{PullRequestTableEntry, ReplyTemplate};
+
+/// Check whether `template` may be stored: it must be named, and say
+/// something.
+///
+fn is_valid(template: &ReplyTemplate) -> bool {
+    !template.name.trim().is_empty() && !template.body.trim().is_empty()
+}
+
+/// Add a reply template. Returns the new template's ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `template` - The template to add. Its `id` and timestamps are ignored.
+///
+pub async fn add_template(
+    db: &DB,
+    template: &ReplyTemplate,
+) -> Result<i64, GHDError> {
+    if !is_valid(&template) {
+        return Err(GHDError::BadRequest);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    match sqlx::query(
+        "
+        INSERT INTO reply_templates (name, body, created_at, updated_at)
+        VALUES (?, ?, ?, ?)
+        ",
+    )
+    .bind(template.name.trim())
+    .bind(&template.body)
+    .bind(&now)
+    .bind(&now)
+    .execute(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res.last_insert_rowid()),
+        Err(err) => {
+            panic!("Unable to add reply template: {}", err);
+        }
+    }
+}
+
+/// Update the name and body of reply template `template.id`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `template` - The template's new contents.
+///
+pub async fn update_template(
+    db: &DB,
+    template: &ReplyTemplate,
+) -> Result<(), GHDError> {
+    if !is_valid(&template) {
+        return Err(GHDError::BadRequest);
+    }
+
+    let res = sqlx::query(
+        "
+        UPDATE reply_templates SET name = ?, body = ?, updated_at = ?
+        WHERE id = ?
+        ",
+    )
+    .bind(template.name.trim())
+    .bind(&template.body)
+    .bind(chrono::Utc::now().timestamp())
+    .bind(&template.id)
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to update reply template {}: {}", template.id, err);
+    });
+    match res.rows_affected() {
+        0 => Err(GHDError::NotFoundError),
+        _ => Ok(()),
+    }
+}
+
+/// Remove reply template `templateid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `templateid` - The template's ID.
+///
+pub async fn remove_template(db: &DB, templateid: &i64) {
+    sqlx::query("DELETE FROM reply_templates WHERE id = ?")
+        .bind(&templateid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to remove reply template {}: {}", templateid, err);
+        });
+}
+
+/// Obtain all reply templates, by name.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_templates(db: &DB) -> Vec<ReplyTemplate> {
+    match sqlx::query_as::<_, ReplyTemplate>(
+        "SELECT * FROM reply_templates ORDER BY name COLLATE NOCASE, id",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain reply templates: {}", err);
+        }
+    }
+}
+
+/// Obtain reply template `templateid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `templateid` - The template's ID.
+///
+pub async fn get_template(
+    db: &DB,
+    templateid: &i64,
+) -> Result<ReplyTemplate, GHDError> {
+    match sqlx::query_as::<_, ReplyTemplate>(
+        "SELECT * FROM reply_templates WHERE id = ?",
+    )
+    .bind(&templateid)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(sqlx::Error::RowNotFound) => Err(GHDError::NotFoundError),
+        Err(err) => {
+            panic!("Unable to obtain reply template {}: {}", templateid, err);
+        }
+    }
+}
+
+/// Fill in the placeholders in `body` for Pull Request `pr`: `{number}`,
+/// `{author}`, `{title}`, `{repo}`, and `{url}`. Anything else in braces is
+/// left as is.
+///
+pub fn render(body: &String, pr: &PullRequestTableEntry) -> String {
+    let re = Regex::new(r"\{(number|author|title|repo|url)\}").unwrap();
+    re.replace_all(&body, |caps: &regex::Captures| match &caps[1] {
+        "number" => pr.number.to_string(),
+        "author" => pr.author.clone(),
+        "title" => pr.title.clone(),
+        "repo" => format!("{}/{}", pr.repo_owner, pr.repo_name),
+        _ => pr.url.clone(),
+    })
+    .to_string()
+}
+
+/// Compose a reply to Pull Request `pr` out of reply template `templateid`,
+/// if any, followed by `body`, if it says anything, a blank line apart.
+/// Placeholders are only filled in in the template.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `pr` - The Pull Request being replied to.
+/// * `templateid` - The template to reply with, if any.
+/// * `body` - What to add to the template, if anything.
+///
+pub async fn compose(
+    db: &DB,
+    pr: &PullRequestTableEntry,
+    templateid: &Option<i64>,
+    body: &Option<String>,
+) -> Result<Option<String>, GHDError> {
+    let template = match templateid {
+        Some(id) => Some(render(&get_template(&db, &id).await?.body, &pr)),
+        None => None,
+    };
+    Ok(match (template, body) {
+        (Some(t), Some(b)) if !b.trim().is_empty() => {
+            Some(format!("{}\n\n{}", t, b))
+        }
+        (Some(t), _) => Some(t),
+        (None, b) => b.clone(),
+    })
+}
@@ -0,0 +1,253 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, vault};
+
+use super::bots;
+
+/// Users we hold a token for, as an SQL sub-select.
+///
+const OUR_USERS: &str = "SELECT user_id FROM tokens WHERE user_id IS NOT NULL";
+
+/// An entry in a report section: an issue or Pull Request, along with what
+/// the section has to say about it.
+///
+#[derive(sqlx::FromRow)]
+struct ReportItem {
+    repo_owner: String,
+    repo_name: String,
+    number: i64,
+    title: String,
+    url: String,
+    detail: Option<String>,
+}
+
+/// Obtain the report items selected by `query`, binding `values` in order.
+///
+async fn get_items(db: &DB, query: &String, values: &[i64]) -> Vec<ReportItem> {
+    let mut q = sqlx::query_as::<_, ReportItem>(&query);
+    for value in values {
+        q = q.bind(value);
+    }
+    match q.fetch_all(db.pool()).await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain report items: {}", err);
+        }
+    }
+}
+
+/// Render a report section titled `title`, listing `items`.
+///
+fn render_section(out: &mut String, title: &str, items: &Vec<ReportItem>) {
+    out.push_str(&format!("## {} ({})\n\n", title, items.len()));
+    if items.is_empty() {
+        out.push_str("Nothing.\n\n");
+        return;
+    }
+    for item in items {
+        out.push_str(&format!(
+            "- [{}/{}#{}]({}) {}",
+            item.repo_owner, item.repo_name, item.number, item.url, item.title
+        ));
+        if let Some(detail) = &item.detail {
+            out.push_str(&format!(" — {}", detail));
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+/// Render a Markdown summary of what's on our plate, and of what happened
+/// between `since` and `until`: Pull Requests awaiting our review, our open
+/// Pull Requests, those merged, our issues with a fix in progress, and the
+/// notifications raised. Bot-authored issues are left out if bots are
+/// hidden.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `title` - The report's title.
+/// * `since` - Timestamp the report's period starts at.
+/// * `until` - Timestamp the report's period ends at.
+///
+pub async fn render(db: &DB, title: &str, since: &i64, until: &i64) -> String {
+    let bots = bots::filter(&db).await;
+    let fmt = |ts: &i64| match chrono::NaiveDateTime::from_timestamp_opt(*ts, 0)
+    {
+        Some(dt) => chrono::DateTime::<chrono::Utc>::from_utc(dt, chrono::Utc)
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+            .to_string(),
+        None => ts.to_string(),
+    };
+
+    let mut out =
+        format!("# {}\n\n_{} to {}_\n\n", title, fmt(since), fmt(until));
+
+    let awaiting = get_items(
+        &db,
+        &format!(
+            "
+            SELECT DISTINCT
+                issues.repo_owner, issues.repo_name, issues.number,
+                issues.title, issues.url,
+                'by ' || issues.author AS detail
+            FROM
+                pr_review_requests
+                INNER JOIN issues ON issues.id = pr_review_requests.pr_id
+            WHERE
+                pr_review_requests.user_id IN ({})
+                AND issues.state = 'open' AND {}
+            ORDER BY issues.updated_at DESC
+            ",
+            OUR_USERS, bots
+        ),
+        &[],
+    )
+    .await;
+    render_section(&mut out, "Awaiting your review", &awaiting);
+
+    let open = get_items(
+        &db,
+        &format!(
+            "
+            SELECT
+                issues.repo_owner, issues.repo_name, issues.number,
+                issues.title, issues.url,
+                CASE
+                    WHEN pull_requests.is_draft THEN 'draft'
+                    ELSE REPLACE(pull_requests.review_decision, '_', ' ')
+                END AS detail
+            FROM
+                issues
+                INNER JOIN pull_requests ON pull_requests.id = issues.id
+            WHERE
+                issues.author_id IN ({})
+                AND issues.state = 'open'
+            ORDER BY issues.updated_at DESC
+            ",
+            OUR_USERS
+        ),
+        &[],
+    )
+    .await;
+    render_section(&mut out, "Your open pull requests", &open);
+
+    let merged = get_items(
+        &db,
+        &format!(
+            "
+            SELECT
+                issues.repo_owner, issues.repo_name, issues.number,
+                issues.title, issues.url, NULL AS detail
+            FROM
+                issues
+                INNER JOIN pull_requests ON pull_requests.id = issues.id
+            WHERE
+                issues.author_id IN ({})
+                AND pull_requests.merged_at >= ?
+                AND pull_requests.merged_at < ?
+            ORDER BY pull_requests.merged_at DESC
+            ",
+            OUR_USERS
+        ),
+        &[*since, *until],
+    )
+    .await;
+    render_section(&mut out, "Merged", &merged);
+
+    let fixing = get_items(
+        &db,
+        &format!(
+            "
+            SELECT
+                issues.repo_owner, issues.repo_name, issues.number,
+                issues.title, issues.url,
+                (
+                    SELECT
+                        'fixed by ' || issue_fixes.repo_owner || '/'
+                        || issue_fixes.repo_name || '#' || issue_fixes.number
+                    FROM issue_fixes
+                    WHERE
+                        issue_fixes.issue_id = issues.id
+                        AND issue_fixes.state = 'open'
+                    ORDER BY issue_fixes.linked_at DESC
+                    LIMIT 1
+                ) AS detail
+            FROM issues
+            WHERE
+                issues.author_id IN ({})
+                AND issues.state = 'open'
+                AND issues.is_pull_request = 0
+                AND detail IS NOT NULL
+            ORDER BY issues.updated_at DESC
+            ",
+            OUR_USERS
+        ),
+        &[],
+    )
+    .await;
+    render_section(&mut out, "Your issues with a fix in progress", &fixing);
+
+    let notifications = match sqlx::query_as::<_, (String, String)>(&format!(
+        "
+        SELECT title, body FROM notifications
+        WHERE
+            created_at >= ? AND created_at < ?
+            AND (
+                issue_id IS NULL
+                OR issue_id IN (SELECT issues.id FROM issues WHERE {})
+            )
+        ORDER BY created_at DESC, id DESC
+        ",
+        bots
+    ))
+    .bind(&since)
+    .bind(&until)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain notifications to report: {}", err);
+        }
+    };
+    out.push_str(&format!("## Notifications ({})\n\n", notifications.len()));
+    if notifications.is_empty() {
+        out.push_str("Nothing.\n");
+    }
+    for (title, body) in &notifications {
+        let body = vault::open(&body).await;
+        match body.lines().next() {
+            Some(line) if !line.trim().is_empty() => {
+                out.push_str(&format!("- {} — {}\n", title, line.trim()))
+            }
+            _ => out.push_str(&format!("- {}\n", title)),
+        };
+    }
+
+    out
+}
+
+/// Render the Markdown summary of the past week.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn render_weekly(db: &DB) -> String {
+    let now = chrono::Utc::now().timestamp();
+    render(&db, "Weekly summary", &(now - 7 * 24 * 3600), &now).await
+}
@@ -0,0 +1,686 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::{
+    access,
+    accounts::AccountCredentials,
+    advisories, backports, depupdates, gql, refresh, releases, rest, templates,
+    types::{OrgRepository, RepoBranch, RepoBranchesUpdate, Repository},
+};
+
+/// Branches don't need to be kept very fresh; refresh them every hour.
+///
+pub const BRANCHES_REFRESH_INTERVAL: i64 = 60 * 60;
+
+/// Setting holding the number of days after which a branch without Pull
+/// Requests is considered stale.
+///
+pub const SETTING_STALE_DAYS: &str = "branches.stale_days";
+
+const DEFAULT_STALE_DAYS: i64 = 90;
+
+/// Obtain the sync refresh key for the branches of repository `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("branches:{}/{}", owner, name)
+}
+
+/// Check whether the branches of repository `owner/name` are due for a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        BRANCHES_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Add a repository to the set of maintained repositories.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn add_repo(db: &DB, owner: &String, name: &String) {
+    sqlx::query("INSERT OR IGNORE INTO repos (owner, name) VALUES (?, ?)")
+        .bind(&owner)
+        .bind(&name)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error adding repo '{}/{}': {}", owner, name, err);
+        });
+}
+
+/// Add several repositories owned by `owner` to the set of maintained
+/// repositories, all at once.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repositories' owner.
+/// * `names` - The repositories' names.
+///
+pub async fn add_repos(db: &DB, owner: &String, names: &Vec<String>) {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to add repos: {}", err);
+        }
+    };
+
+    for name in names {
+        sqlx::query("INSERT OR IGNORE INTO repos (owner, name) VALUES (?, ?)")
+            .bind(&owner)
+            .bind(&name)
+            .execute(&mut tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error adding repo '{}/{}': {}", owner, name, err);
+            });
+    }
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit repos for '{}': {}", owner, err);
+    });
+}
+
+/// Remove a repository from the set of maintained repositories, along with
+/// its cached branches, releases, labels, template and metadata, its sync
+/// status, our permission on it, its dependency update Pull Requests, the
+/// team mentions on it, and the Pull Requests on it fixing issues.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn remove_repo(db: &DB, owner: &String, name: &String) {
+    for q in [
+        "DELETE FROM repos WHERE owner = ? AND name = ?",
+        "DELETE FROM branches WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM branch_prs WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM releases WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM pr_templates WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM repo_sync WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM repo_permissions WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM merge_policies WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM repo_labels WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM dependency_prs WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM repo_metadata WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM repo_topics WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM team_mentions WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM workspaces WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM issue_fixes WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM sla_rules WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM notification_rules WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM pending_reviews WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM anon_prs WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM follow_ups WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM issue_references WHERE repo_owner = ? AND repo_name = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&owner)
+            .bind(&name)
+            .execute(db.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error removing repo '{}/{}': {}", owner, name, err);
+            });
+    }
+}
+
+/// Obtain all maintained repositories, with their language and topics.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_repos(db: &DB) -> Result<Vec<Repository>, GHDError> {
+    let repos = match sqlx::query_as::<
+        _,
+        (String, String, Option<String>, Option<String>),
+    >(
+        "
+        SELECT
+            repos.owner, repos.name, repos.default_branch,
+            repo_metadata.language
+        FROM repos LEFT JOIN repo_metadata
+            ON repo_metadata.repo_owner = repos.owner
+            AND repo_metadata.repo_name = repos.name
+        ORDER BY owner, name
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain repos from db: {}", err);
+        }
+    };
+
+    let topics = match sqlx::query_as::<_, (String, String, String)>(
+        "
+        SELECT repo_owner, repo_name, topic FROM repo_topics
+        ORDER BY repo_owner, repo_name, topic
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain repo topics from db: {}", err);
+        }
+    };
+    Ok(repos
+        .into_iter()
+        .map(|(owner, name, default_branch, language)| {
+            let topics = topics
+                .iter()
+                .filter(|(o, n, _)| o == &owner && n == &name)
+                .map(|(_, _, t)| t.clone())
+                .collect();
+            Repository {
+                owner,
+                name,
+                default_branch,
+                language,
+                topics,
+            }
+        })
+        .collect())
+}
+
+/// Repository as described by the REST API, of which only the canonical
+/// name is of interest.
+///
+#[derive(serde::Deserialize)]
+struct RepoInfo {
+    full_name: String,
+}
+
+/// Check whether repository `owner/name` has since been renamed or
+/// transferred, returning its current owner and name if so. GitHub redirects
+/// requests for the old name to the new one, so the canonical name in the
+/// response is what tells them apart.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's cached owner.
+/// * `name` - The repository's cached name.
+///
+pub async fn get_renamed(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Option<(String, String)>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("repos");
+    let ep = format!("/repos/{}/{}", owner, name);
+    let info = match ghreq.send::<RepoInfo>(ghreq.get(&ep)).await {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    match info.full_name.split_once('/') {
+        Some((new_owner, new_name))
+            if new_owner != owner || new_name != name =>
+        {
+            Ok(Some((new_owner.to_string(), new_name.to_string())))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Move everything known about repository `owner/name` over to its new
+/// `new_owner/new_name`, all at once. Should the new name already be
+/// maintained, the old entry is dropped rather than duplicated.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's previous owner.
+/// * `name` - The repository's previous name.
+/// * `new_owner` - The repository's current owner.
+/// * `new_name` - The repository's current name.
+///
+pub async fn rename_repo(
+    db: &DB,
+    owner: &String,
+    name: &String,
+    new_owner: &String,
+    new_name: &String,
+) {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to rename repo: {}", err);
+        }
+    };
+
+    for q in [
+        "UPDATE OR REPLACE repos SET owner = ?, name = ?
+         WHERE owner = ? AND name = ?",
+        "UPDATE issues SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE backports SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE issue_fixes SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE branches SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE branch_prs SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE triage_rules SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE sla_rules SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE advisory_findings SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE releases SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE pr_templates SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE issue_dependencies SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE path_views SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE repo_sync SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE repo_permissions SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE merge_policies SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE repo_labels SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE dependency_prs SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE repo_metadata SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE repo_topics SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE team_mentions SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE workspaces SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE notification_rules SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE pending_reviews SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE OR REPLACE anon_prs SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE follow_ups SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+        "UPDATE issue_references SET repo_owner = ?, repo_name = ?
+         WHERE repo_owner = ? AND repo_name = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&new_owner)
+            .bind(&new_name)
+            .bind(&owner)
+            .bind(&name)
+            .execute(&mut tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Error renaming repo '{}/{}' to '{}/{}': {}",
+                    owner, name, new_owner, new_name, err
+                );
+            });
+    }
+
+    let keys = [
+        (
+            refresh_key(&owner, &name),
+            refresh_key(&new_owner, &new_name),
+        ),
+        (
+            advisories::refresh_key(&owner, &name),
+            advisories::refresh_key(&new_owner, &new_name),
+        ),
+        (
+            releases::refresh_key(&owner, &name),
+            releases::refresh_key(&new_owner, &new_name),
+        ),
+        (
+            templates::refresh_key(&owner, &name),
+            templates::refresh_key(&new_owner, &new_name),
+        ),
+        (
+            access::refresh_key(&owner, &name),
+            access::refresh_key(&new_owner, &new_name),
+        ),
+        (
+            depupdates::refresh_key(&owner, &name),
+            depupdates::refresh_key(&new_owner, &new_name),
+        ),
+    ];
+    for (old_key, new_key) in &keys {
+        sqlx::query("UPDATE OR REPLACE sync_refresh SET key = ? WHERE key = ?")
+            .bind(&new_key)
+            .bind(&old_key)
+            .execute(&mut tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error renaming refresh key '{}': {}", old_key, err);
+            });
+    }
+
+    // per-repository settings are keyed by a `.owner/name` suffix.
+    let old_suffix = format!(".{}/{}", owner, name);
+    let new_suffix = format!(".{}/{}", new_owner, new_name);
+    sqlx::query(
+        "
+        UPDATE OR REPLACE settings
+        SET key = substr(key, 1, length(key) - length(?1)) || ?2
+        WHERE substr(key, -length(?1)) = ?1
+        ",
+    )
+    .bind(&old_suffix)
+    .bind(&new_suffix)
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error renaming settings for '{}/{}': {}", owner, name, err);
+    });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!(
+            "Unable to commit rename of '{}/{}' to '{}/{}': {}",
+            owner, name, new_owner, new_name, err
+        );
+    });
+}
+
+/// Obtain the repositories of organization `org`, flagging those already
+/// maintained, so they can be picked from in bulk.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `creds` - The credentials to use when talking to the API.
+/// * `org` - The organization's login.
+///
+pub async fn get_org_repos(
+    db: &DB,
+    creds: &AccountCredentials,
+    org: &String,
+) -> Result<Vec<OrgRepository>, GHDError> {
+    let mut repos =
+        gql::get_org_repositories(&creds.token, &creds.host, &org).await?;
+    let tracked: Vec<String> = get_repos(&db)
+        .await?
+        .into_iter()
+        .filter(|r| r.owner.eq_ignore_ascii_case(&org))
+        .map(|r| r.name)
+        .collect();
+
+    for repo in &mut repos {
+        repo.is_tracked = tracked.contains(&repo.name);
+    }
+
+    Ok(repos)
+}
+
+/// Write the provided branches update to the database, replacing any
+/// previously known branches, language and topics for repository
+/// `owner/name`. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `update` - The branches obtained for the repository.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+    update: &RepoBranchesUpdate,
+) {
+    sqlx::query(
+        "UPDATE repos SET default_branch = ? WHERE owner = ? AND name = ?",
+    )
+    .bind(&update.default_branch)
+    .bind(&owner)
+    .bind(&name)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating repo '{}/{}': {}", owner, name, err);
+    });
+
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO repo_metadata (repo_owner, repo_name, language)
+        VALUES (?, ?, ?)
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(&update.language)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error updating repo '{}/{}' language: {}", owner, name, err);
+    });
+
+    for q in [
+        "DELETE FROM branches WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM branch_prs WHERE repo_owner = ? AND repo_name = ?",
+        "DELETE FROM repo_topics WHERE repo_owner = ? AND repo_name = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&owner)
+            .bind(&name)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Error clearing branches for '{}/{}': {}",
+                    owner, name, err
+                );
+            });
+    }
+
+    for topic in &update.topics {
+        sqlx::query(
+            "
+            INSERT OR IGNORE INTO repo_topics (repo_owner, repo_name, topic)
+            VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .bind(&topic)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting topic '{}': {}", topic, err);
+        });
+    }
+
+    for branch in &update.branches {
+        sqlx::query(
+            "
+            INSERT INTO branches (repo_owner, repo_name, name, last_commit_at)
+            VALUES (?, ?, ?, ?)
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .bind(&branch.name)
+        .bind(&branch.last_commit_at)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Error inserting branch '{}': {}", branch.name, err);
+        });
+
+        for number in &branch.pull_requests {
+            sqlx::query(
+                "
+                INSERT OR IGNORE INTO branch_prs (
+                    repo_owner, repo_name, branch, number
+                ) VALUES (?, ?, ?, ?)
+                ",
+            )
+            .bind(&owner)
+            .bind(&name)
+            .bind(&branch.name)
+            .bind(&number)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Error inserting branch pr #{}: {}", number, err);
+            });
+        }
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &update.when)
+        .await;
+}
+
+/// Obtain the cached branches of repository `owner/name`, most recently
+/// committed to first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_branches(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<RepoBranch>, GHDError> {
+    let branches = match sqlx::query_as::<_, (String, Option<i64>)>(
+        "
+        SELECT name, last_commit_at FROM branches
+        WHERE repo_owner = ? AND repo_name = ?
+        ORDER BY last_commit_at DESC, name
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain branches from db: {}", err);
+        }
+    };
+
+    let prs = match sqlx::query_as::<_, (String, i64)>(
+        "
+        SELECT branch, number FROM branch_prs
+        WHERE repo_owner = ? AND repo_name = ?
+        ORDER BY number
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain branch prs from db: {}", err);
+        }
+    };
+
+    let mut prs_by_branch: HashMap<String, Vec<i64>> = HashMap::new();
+    for (branch, number) in prs {
+        prs_by_branch.entry(branch).or_default().push(number);
+    }
+
+    Ok(branches
+        .into_iter()
+        .map(|(branch, last_commit_at)| RepoBranch {
+            pull_requests: prs_by_branch.remove(&branch).unwrap_or_default(),
+            name: branch,
+            last_commit_at,
+        })
+        .collect())
+}
+
+/// Obtain the branches of repository `owner/name` that have no open Pull
+/// Requests and haven't been committed to in a while. The default branch and
+/// maintained backport branches are never considered stale.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_stale_branches(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<RepoBranch>, GHDError> {
+    let days = config::get_setting_or(&db, SETTING_STALE_DAYS, "")
+        .await
+        .parse::<i64>()
+        .unwrap_or(DEFAULT_STALE_DAYS);
+    let cutoff = chrono::Utc::now().timestamp() - days * 24 * 60 * 60;
+
+    let default_branch = match sqlx::query_scalar::<_, Option<String>>(
+        "SELECT default_branch FROM repos WHERE owner = ? AND name = ?",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res.flatten(),
+        Err(err) => {
+            panic!("Unable to obtain default branch from db: {}", err);
+        }
+    };
+    let maintained =
+        backports::get_maintained_branches(&db, &owner, &name).await;
+
+    let branches = get_branches(&db, &owner, &name).await?;
+    Ok(branches
+        .into_iter()
+        .filter(|b| {
+            b.pull_requests.is_empty()
+                && b.last_commit_at.map_or(false, |ts| ts < cutoff)
+                && Some(&b.name) != default_branch.as_ref()
+                && !maintained.contains(&b.name)
+        })
+        .collect())
+}
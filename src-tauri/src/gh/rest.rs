@@ -12,12 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::errors::GHDError;
+
+use super::{accounts, ledger, limits};
+
 /// Abstracts REST requests. May be used as one GithubRequest per REST
 /// operation, or may be reused.
 ///
 pub struct GithubRequest {
     client: reqwest::Client,
     token: String,
+    host: String,
+    api_url: String,
+    feature: String,
 }
 
 impl GithubRequest {
@@ -28,12 +35,49 @@ impl GithubRequest {
     /// * `token` - String containing the API Token to use.
     ///
     pub fn new(token: &String) -> Self {
+        GithubRequest::new_for_host(
+            token,
+            &String::from(accounts::DEFAULT_HOST),
+        )
+    }
+
+    /// Obtain a new GithubRequest instance talking to the API on `host`.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - String containing the API Token to use.
+    /// * `host` - String containing the host; e.g., `github.com`.
+    ///
+    pub fn new_for_host(token: &String, host: &String) -> Self {
         GithubRequest {
             client: reqwest::Client::new(),
             token: token.clone(),
+            host: host.clone(),
+            api_url: accounts::rest_api_url(host),
+            feature: String::from("rest"),
         }
     }
 
+    /// Obtain a new GithubRequest instance making unauthenticated requests
+    /// to `github.com`, subject to a much lower rate limit, and to public
+    /// data only.
+    ///
+    pub fn new_anonymous() -> Self {
+        GithubRequest::new(&String::new())
+    }
+
+    /// Attribute the requests made through this instance to `feature` in
+    /// the API usage ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The feature making the requests; e.g., `files`.
+    ///
+    pub fn for_feature(mut self: Self, feature: &str) -> Self {
+        self.feature = feature.to_string();
+        self
+    }
+
     /// Obtain a `reqwest::RequestBuilder` for a `GET` operation, targeting the
     /// provided `endpoint`.
     ///
@@ -47,7 +91,104 @@ impl GithubRequest {
             None => endpoint,
         };
 
-        self.client.get(format!("https://api.github.com/{}", ep))
+        self.client.get(format!("{}/{}", self.api_url, ep))
+    }
+
+    /// Obtain a `reqwest::RequestBuilder` for a `POST` operation, targeting the
+    /// provided `endpoint`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String containing the target endpoint; e.g., `/user`.
+    ///
+    pub fn post(self: &Self, endpoint: &str) -> reqwest::RequestBuilder {
+        let ep = match endpoint.strip_prefix("/") {
+            Some(res) => res,
+            None => endpoint,
+        };
+
+        self.client.post(format!("{}/{}", self.api_url, ep))
+    }
+
+    /// Obtain a `reqwest::RequestBuilder` for a `PUT` operation, targeting the
+    /// provided `endpoint`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String containing the target endpoint; e.g., `/user`.
+    ///
+    pub fn put(self: &Self, endpoint: &str) -> reqwest::RequestBuilder {
+        let ep = match endpoint.strip_prefix("/") {
+            Some(res) => res,
+            None => endpoint,
+        };
+
+        self.client.put(format!("{}/{}", self.api_url, ep))
+    }
+
+    /// Obtain a `reqwest::RequestBuilder` for a `DELETE` operation, targeting
+    /// the provided `endpoint`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String containing the target endpoint; e.g., `/user`.
+    ///
+    pub fn delete(self: &Self, endpoint: &str) -> reqwest::RequestBuilder {
+        let ep = match endpoint.strip_prefix("/") {
+            Some(res) => res,
+            None => endpoint,
+        };
+
+        self.client.delete(format!("{}/{}", self.api_url, ep))
+    }
+
+    /// Send the request and return the raw `reqwest::Response`, regardless of
+    /// its status, or a `reqwest::Error` if the server could not be reached.
+    /// This function handles setting headers and the token, and records the
+    /// call in the API usage ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `rb` - The pre-built `reqwest::RequestBuilder` to send to the server.
+    ///
+    pub async fn execute(
+        self: &Self,
+        rb: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        self.execute_as(rb, "application/vnd.github+json").await
+    }
+
+    /// Like `execute`, but asking for the provided media type rather than
+    /// JSON; e.g., `application/vnd.github.diff`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rb` - The pre-built `reqwest::RequestBuilder` to send to the server.
+    /// * `accept` - The media type to ask for.
+    ///
+    pub async fn execute_as(
+        self: &Self,
+        rb: reqwest::RequestBuilder,
+        accept: &str,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let rb = match self.token.is_empty() {
+            true => rb,
+            false => rb.bearer_auth(&self.token),
+        };
+        let res = rb
+            .header("User-Agent", "GHD")
+            .header("Accept", accept)
+            .send()
+            .await?;
+        ledger::record(
+            &self.token,
+            &self.host,
+            &self.feature,
+            1,
+            res.headers(),
+        );
+
+        Ok(res)
     }
 
     /// Send the request and return a result containing either the specified
@@ -66,19 +207,16 @@ impl GithubRequest {
     where
         T: for<'de> serde::Deserialize<'de>,
     {
-        let req = rb
-            .bearer_auth(&self.token)
-            .header("User-Agent", "GHD")
-            .header("Accept", "application/vnd.github+json")
-            .send()
-            .await
-            .unwrap();
+        let req = self.execute(rb).await.unwrap();
 
-        if req.status() != reqwest::StatusCode::OK {
-            return Err(req.status());
+        if !req.status().is_success() {
+            return Err(response_status(&req));
         }
 
-        let txt = req.text().await.unwrap();
+        let txt = match limits::read_body(req).await {
+            Some(res) => res,
+            None => return Err(reqwest::StatusCode::PAYLOAD_TOO_LARGE),
+        };
 
         if std::env::var("GHD_REST_DEBUG").is_ok() {
             println!("REST(send result): {}", txt);
@@ -87,8 +225,239 @@ impl GithubRequest {
         let res: T = serde_json::from_str(&txt).unwrap();
         Ok(res)
     }
+
+    /// Obtain page `page` of a `GET` operation on the provided `endpoint`,
+    /// along with whether GitHub reports there being a next page in the
+    /// response's `Link` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String containing the target endpoint; e.g., `/user`.
+    /// * `page` - The page to obtain, starting at 1.
+    ///
+    async fn get_page<T>(
+        self: &Self,
+        endpoint: &str,
+        page: usize,
+    ) -> Result<(Vec<T>, bool), reqwest::StatusCode>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let req = self
+            .get(endpoint)
+            .query(&[("per_page", PAGE_SIZE), ("page", page)]);
+        let res = self.execute(req).await.unwrap();
+        if !res.status().is_success() {
+            return Err(response_status(&res));
+        }
+
+        let has_next = next_link(&res).is_some();
+        let txt = match limits::read_body(res).await {
+            Some(res) => res,
+            None => return Err(reqwest::StatusCode::PAYLOAD_TOO_LARGE),
+        };
+
+        if std::env::var("GHD_REST_DEBUG").is_ok() {
+            println!("REST(page result): {}", txt);
+        }
+
+        let entries: Vec<T> = serde_json::from_str(&txt).unwrap();
+        Ok((entries, has_next))
+    }
+
+    /// Obtain all pages for a `GET` operation on the provided `endpoint`,
+    /// returning the concatenated results. Pages are requested for as long
+    /// as GitHub reports there being a next one.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String containing the target endpoint; e.g., `/user`.
+    ///
+    pub async fn get_all_pages<T>(
+        self: &Self,
+        endpoint: &str,
+    ) -> Result<Vec<T>, reqwest::StatusCode>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let mut res: Vec<T> = vec![];
+        let mut page = 1;
+        loop {
+            let (mut entries, has_next) =
+                self.get_page::<T>(endpoint, page).await?;
+            let empty = entries.is_empty();
+            res.append(&mut entries);
+            if !has_next || empty {
+                return Ok(res);
+            }
+            page += 1;
+        }
+    }
+
+    /// Like `get_all_pages`, but stopping once we have as many entries as
+    /// `limits::max_list_items()`, dropping the rest. Also returns whether
+    /// entries were dropped, i.e. whether there were more than that many.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String containing the target endpoint; e.g., `/user`.
+    ///
+    pub async fn get_all_pages_capped<T>(
+        self: &Self,
+        endpoint: &str,
+    ) -> Result<(Vec<T>, bool), reqwest::StatusCode>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let max = limits::max_list_items();
+        let mut res: Vec<T> = vec![];
+        let mut page = 1;
+        loop {
+            let (mut entries, has_next) =
+                self.get_page::<T>(endpoint, page).await?;
+            let empty = entries.is_empty();
+            res.append(&mut entries);
+
+            if res.len() >= max {
+                let truncated = res.len() > max || has_next;
+                res.truncate(max);
+                return Ok((res, truncated));
+            }
+            if !has_next || empty {
+                return Ok((res, false));
+            }
+            page += 1;
+        }
+    }
+
+    /// Obtain all pages for the `GET` request `rb`, following the next page's
+    /// URL from the `Link` header of each response, and returning the
+    /// concatenated results. Meant for endpoints paginated by cursor rather
+    /// than by page number; e.g., `/advisories`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rb` - The pre-built `reqwest::RequestBuilder` for the first page.
+    ///
+    pub async fn get_all_linked_pages<T>(
+        self: &Self,
+        rb: reqwest::RequestBuilder,
+    ) -> Result<Vec<T>, reqwest::StatusCode>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let mut res: Vec<T> = vec![];
+        let mut rb = rb;
+        loop {
+            let reply = self.execute(rb).await.unwrap();
+            if !reply.status().is_success() {
+                return Err(response_status(&reply));
+            }
+
+            let next = next_link(&reply);
+            let txt = match limits::read_body(reply).await {
+                Some(res) => res,
+                None => return Err(reqwest::StatusCode::PAYLOAD_TOO_LARGE),
+            };
+
+            if std::env::var("GHD_REST_DEBUG").is_ok() {
+                println!("REST(page result): {}", txt);
+            }
+
+            let mut entries: Vec<T> = serde_json::from_str(&txt).unwrap();
+            let empty = entries.is_empty();
+            res.append(&mut entries);
+            match next {
+                Some(url) if !empty => rb = self.client.get(url),
+                _ => return Ok(res),
+            };
+        }
+    }
+}
+
+/// Obtain the URL of the next page from the `Link` header of response `res`,
+/// if GitHub reports there being one.
+///
+fn next_link(res: &reqwest::Response) -> Option<String> {
+    let link = res.headers().get(reqwest::header::LINK)?.to_str().ok()?;
+    parse_next_link(link)
+}
+
+/// Obtain the URL of the next page from the value of a `Link` header, if
+/// there is one; e.g., from
+/// `<https://api.github.com/advisories?after=Y3Vy>; rel="next"`.
+///
+fn parse_next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|entry| {
+        let (url, rel) = entry.split_once(';')?;
+        if !rel.contains("rel=\"next\"") {
+            return None;
+        }
+        Some(
+            url.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .into(),
+        )
+    })
+}
+
+/// Obtain the status of response `res`, telling rate limiting apart from
+/// access being denied: GitHub answers both with `403 Forbidden`, but only
+/// the former comes with no requests remaining or a `retry-after` header.
+/// Rate limiting is reported as `429 Too Many Requests`.
+///
+pub fn response_status(res: &reqwest::Response) -> reqwest::StatusCode {
+    if res.status() != reqwest::StatusCode::FORBIDDEN {
+        return res.status();
+    }
+    let headers = res.headers();
+    let exhausted = headers
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| v == "0");
+    match exhausted || headers.contains_key("retry-after") {
+        true => reqwest::StatusCode::TOO_MANY_REQUESTS,
+        false => res.status(),
+    }
 }
 
+/// Percent-encode `segment` to be used as a single segment of an endpoint's
+/// path; e.g., a branch name, which may contain `/`, `#` or `?`.
+///
+pub fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => (b as char).to_string(),
+            b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Translate an unexpected `reqwest::StatusCode` into a `GHDError`.
+///
+pub fn status_to_error(status: reqwest::StatusCode) -> GHDError {
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED => GHDError::BadTokenError,
+        reqwest::StatusCode::FORBIDDEN => GHDError::PermissionDeniedError,
+        reqwest::StatusCode::TOO_MANY_REQUESTS => GHDError::RateLimitedError,
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE => {
+            GHDError::NotFoundError
+        }
+        reqwest::StatusCode::BAD_REQUEST => GHDError::BadRequest,
+        reqwest::StatusCode::PAYLOAD_TOO_LARGE => {
+            GHDError::ResponseTooLargeError
+        }
+        _ => GHDError::UnknownError,
+    }
+}
+
+/// Number of entries requested per page on paginated endpoints.
+///
+const PAGE_SIZE: usize = 100;
+
 /// REST API User Reply
 ///
 #[derive(serde::Deserialize)]
@@ -99,3 +468,277 @@ pub struct GithubUserReply {
     pub avatar_url: String,
     pub name: String,
 }
+
+/// REST API Authenticated User Profile Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubProfileReply {
+    pub followers: i64,
+    pub following: i64,
+    pub public_repos: i64,
+}
+
+/// REST API Repository Reply, as far as stars are concerned
+///
+#[derive(serde::Deserialize)]
+pub struct GithubRepoStarsReply {
+    pub stargazers_count: i64,
+}
+
+/// REST API Organization Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubOrgReply {
+    pub login: String,
+    pub id: i64,
+    pub avatar_url: String,
+}
+
+/// REST API Team Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubTeamReply {
+    pub id: i64,
+    pub slug: String,
+    pub name: String,
+    pub organization: GithubOrgReply,
+}
+
+/// REST API Organization Membership Reply, trimmed down to what we need.
+/// `role` is either `admin` or `member`.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubOrgMembershipReply {
+    pub role: String,
+}
+
+/// REST API Team Member Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubTeamMemberReply {
+    pub login: String,
+    pub id: i64,
+}
+
+/// REST API Pull Request File Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubPullFileReply {
+    pub filename: String,
+    pub status: String,
+    pub additions: i64,
+    pub deletions: i64,
+}
+
+/// REST API Compare Reply, trimmed down to what we need.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubCompareReply {
+    pub status: String,
+    pub commits: Vec<GithubCompareCommitReply>,
+    #[serde(default)]
+    pub files: Vec<GithubPullFileReply>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubCompareCommitReply {
+    pub sha: String,
+}
+
+/// REST API Commit Reply, trimmed down to what we need.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubCommitReply {
+    pub commit: GithubCommitDetailReply,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubCommitDetailReply {
+    pub committer: GithubCommitAuthorReply,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubCommitAuthorReply {
+    pub date: chrono::DateTime<chrono::Utc>,
+}
+
+/// REST API Release Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubReleaseReply {
+    pub html_url: String,
+}
+
+/// REST API Release Reply, as returned when listing or fetching a
+/// repository's releases.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubReleaseListEntryReply {
+    pub id: i64,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub html_url: String,
+    pub draft: bool,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// REST API Release Reply, trimmed down to the release's assets.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubReleaseAssetsReply {
+    pub assets: Vec<GithubReleaseAssetReply>,
+}
+
+/// REST API Release Asset Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubReleaseAssetReply {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// REST API Tag Reply, trimmed down to what we need.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubTagReply {
+    pub name: String,
+}
+
+/// REST API Dependency Graph SBOM Reply, trimmed down to what we need.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubSbomReply {
+    pub sbom: GithubSbomDocumentReply,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubSbomDocumentReply {
+    #[serde(default)]
+    pub packages: Vec<GithubSbomPackageReply>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubSbomPackageReply {
+    #[serde(rename = "externalRefs", default)]
+    pub external_refs: Vec<GithubSbomExternalRefReply>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubSbomExternalRefReply {
+    #[serde(rename = "referenceType")]
+    pub reference_type: String,
+    #[serde(rename = "referenceLocator")]
+    pub reference_locator: String,
+}
+
+/// REST API Global Security Advisory Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubAdvisoryReply {
+    pub ghsa_id: String,
+    pub summary: String,
+    pub severity: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub vulnerabilities: Vec<GithubAdvisoryVulnerabilityReply>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubAdvisoryVulnerabilityReply {
+    pub package: Option<GithubAdvisoryPackageReply>,
+    pub vulnerable_version_range: Option<String>,
+    pub first_patched_version: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubAdvisoryPackageReply {
+    pub ecosystem: String,
+    pub name: String,
+}
+
+/// REST API Branch Rule Reply, as returned when listing the rules applying
+/// to a branch. Parameters depend on the rule's type.
+///
+#[derive(serde::Deserialize)]
+pub struct GithubBranchRuleReply {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+}
+
+/// REST API Reaction Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubReactionReply {
+    pub id: i64,
+    pub content: String,
+    pub user: Option<GithubReactionUserReply>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubReactionUserReply {
+    pub login: String,
+}
+
+/// REST API Issue Search Reply
+///
+#[derive(serde::Deserialize)]
+pub struct GithubSearchIssuesReply {
+    pub items: Vec<GithubSearchIssueReply>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GithubSearchIssueReply {
+    pub number: i64,
+    pub title: String,
+    pub state: String,
+    #[serde(default)]
+    pub draft: bool,
+    pub html_url: String,
+    /// API URL of the repository; e.g., `https://api.github.com/repos/o/n`.
+    pub repository_url: String,
+    pub user: Option<GithubReactionUserReply>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_next_link_cases() {
+        let cases = [
+            (
+                r#"<https://api.github.com/advisories?after=Y3Vy>; rel="next""#,
+                Some("https://api.github.com/advisories?after=Y3Vy"),
+            ),
+            (
+                concat!(
+                    r#"<https://api.github.com/x?page=1>; rel="prev", "#,
+                    r#"<https://api.github.com/x?page=3>; rel="next", "#,
+                    r#"<https://api.github.com/x?page=9>; rel="last""#,
+                ),
+                Some("https://api.github.com/x?page=3"),
+            ),
+            (
+                concat!(
+                    r#"<https://api.github.com/x?page=1>; rel="first", "#,
+                    r#"<https://api.github.com/x?page=8>; rel="prev""#,
+                ),
+                None,
+            ),
+            (r#"<https://api.github.com/x?page=2>"#, None),
+            ("", None),
+        ];
+
+        for (link, expected) in cases {
+            assert_eq!(
+                parse_next_link(link),
+                expected.map(String::from),
+                "{}",
+                link
+            );
+        }
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::{ReviewSession, ReviewTime};
+
+/// Longest a session is accounted for. Sessions are often left running,
+/// e.g. when moving on to something else or quitting, so past this long
+/// they are taken as having gone idle, and stopped.
+///
+pub const MAX_SESSION_SECS: i64 = 60 * 60;
+
+const WEEK_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Start of the first week, on Monday, before the epoch; the epoch was on a
+/// Thursday.
+///
+const EPOCH_WEEK_START: i64 = -3 * 24 * 60 * 60;
+
+/// Obtain the SQL expression for when a session stopped, given `now` for
+/// running sessions, capped at `MAX_SESSION_SECS` after it started.
+///
+fn stopped_at(now: &str) -> String {
+    format!(
+        "MIN(COALESCE(review_sessions.stopped_at, {}), \
+            review_sessions.started_at + {})",
+        now, MAX_SESSION_SECS
+    )
+}
+
+/// Obtain the start of the week, on Monday in UTC, timestamp `ts` falls in.
+///
+fn week_start(ts: i64) -> i64 {
+    EPOCH_WEEK_START + (ts - EPOCH_WEEK_START).div_euclid(WEEK_SECS) * WEEK_SECS
+}
+
+/// Obtain the session running on Pull Request `prid`, if any. This function
+/// requires a transaction.
+///
+async fn get_running(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+) -> Option<ReviewSession> {
+    sqlx::query_as::<_, ReviewSession>(
+        "
+        SELECT * FROM review_sessions
+        WHERE pr_id = ? AND stopped_at IS NULL
+        ",
+    )
+    .bind(&prid)
+    .fetch_optional(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain review session on pr {}: {}", prid, err);
+    })
+}
+
+/// Start reviewing Pull Request `prid`. One reviews a Pull Request at a
+/// time, so the sessions running on others are stopped. Returns the
+/// session, which is the running one if already reviewing `prid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn start(db: &DB, prid: &i64) -> Result<ReviewSession, GHDError> {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Error starting transaction to start review session: {}",
+                err
+            );
+        }
+    };
+
+    let known = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM pull_requests WHERE id = ?",
+    )
+    .bind(&prid)
+    .fetch_one(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain pr {}: {}", prid, err);
+    });
+    if known == 0 {
+        return Err(GHDError::NotFoundError);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query(&format!(
+        "
+        UPDATE review_sessions SET stopped_at = {}
+        WHERE stopped_at IS NULL AND pr_id != ?
+        ",
+        stopped_at("?")
+    ))
+    .bind(&now)
+    .bind(&prid)
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to stop running review sessions: {}", err);
+    });
+
+    let session = match get_running(&mut tx, &prid).await {
+        Some(res) => res,
+        None => {
+            let res = sqlx::query(
+                "
+                INSERT INTO review_sessions (pr_id, started_at)
+                VALUES (?, ?)
+                ",
+            )
+            .bind(&prid)
+            .bind(&now)
+            .execute(&mut tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to start review session on {}: {}", prid, err);
+            });
+            ReviewSession {
+                id: res.last_insert_rowid(),
+                pr_id: *prid,
+                started_at: now,
+                stopped_at: None,
+            }
+        }
+    };
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit review session on {}: {}", prid, err);
+    });
+    Ok(session)
+}
+
+/// Stop reviewing Pull Request `prid`. Returns the session stopped.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn stop(db: &DB, prid: &i64) -> Result<ReviewSession, GHDError> {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!(
+                "Error starting transaction to stop review session: {}",
+                err
+            );
+        }
+    };
+
+    let mut session = match get_running(&mut tx, &prid).await {
+        Some(res) => res,
+        None => return Err(GHDError::NotFoundError),
+    };
+    let now = std::cmp::min(
+        chrono::Utc::now().timestamp(),
+        session.started_at + MAX_SESSION_SECS,
+    );
+    sqlx::query("UPDATE review_sessions SET stopped_at = ? WHERE id = ?")
+        .bind(&now)
+        .bind(&session.id)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to stop review session {}: {}", session.id, err);
+        });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit review session {}: {}", session.id, err);
+    });
+    session.stopped_at = Some(now);
+    Ok(session)
+}
+
+/// Stop the sessions that have been running for longer than
+/// `MAX_SESSION_SECS`, as of when they reached it; e.g., those left running
+/// when we last quit.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn stop_idle(db: &DB) {
+    sqlx::query(
+        "
+        UPDATE review_sessions SET stopped_at = started_at + ?
+        WHERE stopped_at IS NULL AND started_at + ? <= ?
+        ",
+    )
+    .bind(MAX_SESSION_SECS)
+    .bind(MAX_SESSION_SECS)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to stop idle review sessions: {}", err);
+    });
+}
+
+/// Obtain the review sessions on Pull Request `prid`, most recent first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `prid` - The Pull Request's database ID.
+///
+pub async fn get_sessions(db: &DB, prid: &i64) -> Vec<ReviewSession> {
+    match sqlx::query_as::<_, ReviewSession>(
+        "
+        SELECT * FROM review_sessions
+        WHERE pr_id = ?
+        ORDER BY started_at DESC
+        ",
+    )
+    .bind(&prid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain review sessions on {}: {}", prid, err);
+        }
+    }
+}
+
+/// Obtain the time spent reviewing each Pull Request per week, for the
+/// time between `from` and `to`; weeks start on Monday, in UTC. Sessions
+/// crossing into another week count towards each week for the time spent
+/// in it. Most recent week first, then the Pull Requests reviewed longest.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `from` - Start of the time to account for.
+/// * `to` - End of the time to account for.
+///
+pub async fn get_weekly(db: &DB, from: &i64, to: &i64) -> Vec<ReviewTime> {
+    let query = format!(
+        "
+        SELECT
+            review_sessions.pr_id,
+            issues.repo_owner, issues.repo_name, issues.number, issues.title,
+            review_sessions.started_at,
+            {0}
+        FROM review_sessions
+        INNER JOIN issues ON issues.id = review_sessions.pr_id
+        WHERE review_sessions.started_at <= ?2 AND {0} >= ?1
+        ",
+        stopped_at("?3")
+    );
+    let sessions = match sqlx::query_as::<
+        _,
+        (i64, String, String, i64, String, i64, i64),
+    >(&query)
+    .bind(&from)
+    .bind(&to)
+    .bind(chrono::Utc::now().timestamp())
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain weekly review time: {}", err);
+        }
+    };
+
+    let mut weekly: HashMap<(i64, i64), ReviewTime> = HashMap::new();
+    for (prid, owner, name, number, title, started_at, stopped_at) in sessions {
+        let mut start = std::cmp::max(started_at, *from);
+        let end = std::cmp::min(stopped_at, *to);
+        while start < end {
+            let week = week_start(start);
+            let until = std::cmp::min(week + WEEK_SECS, end);
+            let entry =
+                weekly.entry((week, prid)).or_insert_with(|| ReviewTime {
+                    week_start: week,
+                    pr_id: prid,
+                    repo_owner: owner.clone(),
+                    repo_name: name.clone(),
+                    number,
+                    title: title.clone(),
+                    sessions: 0,
+                    seconds: 0,
+                });
+            entry.sessions += 1;
+            entry.seconds += until - start;
+            start = until;
+        }
+    }
+
+    let mut res: Vec<ReviewTime> = weekly.into_values().collect();
+    res.sort_by(|a, b| {
+        b.week_start
+            .cmp(&a.week_start)
+            .then(b.seconds.cmp(&a.seconds))
+    });
+    res
+}
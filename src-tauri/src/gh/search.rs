@@ -0,0 +1,301 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use crate::{
+    db::DB,
+    errors::GHDError,
+    page::{Page, PageRequest},
+};
+
+use super::types::SearchResult;
+
+/// Results per page when no limit is provided.
+///
+pub const DEFAULT_LIMIT: i64 = 20;
+
+/// Share of a term's trigrams an issue must have for it to be a fuzzy match
+/// of the term.
+///
+const FUZZY_THRESHOLD: f64 = 0.34;
+
+/// How many candidates, per result asked for, are scored for fuzzy matches.
+///
+const FUZZY_CANDIDATES_FACTOR: i64 = 10;
+
+/// Columns of the `issues` row being selected, matched through the
+/// `issue_search` index.
+///
+const RESULT_COLUMNS: &str = "
+    issues.id, issues.number, issues.title, issues.author,
+    issues.repo_owner, issues.repo_name, issues.state,
+    issues.is_pull_request, issues.updated_at,
+    issue_search.title AS search_title, issue_search.ref AS search_ref
+";
+
+/// Normalize text for matching: lowercased as per Unicode, which covers
+/// non-ASCII scripts the way `LIKE` doesn't, with punctuation other than
+/// what shows up in references (`/`, `#`, `-`, `_`, `.`) dropped.
+///
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || "/#-_.".contains(c) {
+            true => c,
+            false => ' ',
+        })
+        .collect()
+}
+
+/// Obtain the trigrams of `term`, by character rather than byte.
+///
+fn trigrams(term: &str) -> HashSet<String> {
+    let chars: Vec<char> = term.chars().collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+/// Quote `text` as an FTS5 string, so it's matched as is.
+///
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Score how well `haystack` fuzzily matches `terms`: the share of each
+/// term's trigrams found in it, on average. Terms too short to have
+/// trigrams must be found as is.
+///
+fn score(terms: &Vec<String>, haystack: &str) -> f64 {
+    let hay = trigrams(haystack);
+    let total: f64 = terms
+        .iter()
+        .map(|term| {
+            let tris = trigrams(term);
+            if tris.is_empty() {
+                return match haystack.contains(term.as_str()) {
+                    true => 1.0,
+                    false => 0.0,
+                };
+            }
+            tris.intersection(&hay).count() as f64 / tris.len() as f64
+        })
+        .sum();
+
+    total / terms.len() as f64
+}
+
+#[derive(sqlx::FromRow)]
+struct Candidate {
+    #[sqlx(flatten)]
+    result: SearchResult,
+    search_title: String,
+    search_ref: String,
+}
+
+async fn get_candidates(db: &DB, expr: &str, limit: i64) -> Vec<Candidate> {
+    let query = format!(
+        "
+        SELECT {}
+        FROM issue_search INNER JOIN issues
+            ON issues.id = issue_search.rowid
+        WHERE
+            issue_search MATCH ?
+            AND issues.state != 'deleted_upstream'
+        ORDER BY issue_search.rank, issues.updated_at DESC
+        LIMIT ?
+        ",
+        RESULT_COLUMNS
+    );
+
+    match sqlx::query_as::<_, Candidate>(&query)
+        .bind(&expr)
+        .bind(&limit)
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to search issues: {}", err);
+        }
+    }
+}
+
+async fn get_recent(db: &DB, limit: i64) -> Vec<Candidate> {
+    let query = format!(
+        "
+        SELECT {}
+        FROM issue_search INNER JOIN issues
+            ON issues.id = issue_search.rowid
+        WHERE issues.state != 'deleted_upstream'
+        ORDER BY issues.updated_at DESC
+        LIMIT ?
+        ",
+        RESULT_COLUMNS
+    );
+
+    match sqlx::query_as::<_, Candidate>(&query)
+        .bind(&limit)
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain recent issues: {}", err);
+        }
+    }
+}
+
+/// Search issues and Pull Requests by title, reference (`owner/name#123`),
+/// and author. Every term must be found, anywhere and regardless of case;
+/// if that doesn't fill the results up to and including the requested page,
+/// issues fuzzily matching the terms follow, best first, so typos and
+/// partial names still find something. Fuzzy results are flagged as such.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `query` - What to search for.
+/// * `page` - The page of results to obtain.
+///
+pub async fn search(
+    db: &DB,
+    query: &String,
+    page: &PageRequest,
+) -> Result<Page<SearchResult>, GHDError> {
+    // results are ranked, not sorted by a column, so everything up to the
+    // requested page is searched for.
+    let limit = page.position().saturating_add(page.fetch_limit());
+    let candidates = limit.saturating_mul(FUZZY_CANDIDATES_FACTOR);
+    let terms: Vec<String> = normalize(&query)
+        .split_whitespace()
+        .map(|t| t.to_string())
+        .collect();
+    if terms.is_empty() {
+        return Err(GHDError::BadRequest);
+    }
+
+    // the trigram index can only match terms of three characters or more;
+    // shorter ones are checked on the candidates.
+    let (long, short): (Vec<&String>, Vec<&String>) =
+        terms.iter().partition(|t| t.chars().count() >= 3);
+    let haystack = |c: &Candidate| {
+        normalize(&format!(
+            "{} {} {}",
+            c.search_title, c.search_ref, c.result.author
+        ))
+    };
+
+    let exact = match long.is_empty() {
+        true => get_recent(&db, candidates).await,
+        false => {
+            let expr = long
+                .iter()
+                .map(|t| quote(t))
+                .collect::<Vec<String>>()
+                .join(" AND ");
+            get_candidates(&db, &expr, candidates).await
+        }
+    };
+    let mut res: Vec<SearchResult> = exact
+        .into_iter()
+        .filter(|c| {
+            let hay = haystack(c);
+            short.iter().all(|t| hay.contains(t.as_str()))
+        })
+        .take(limit as usize)
+        .map(|c| c.result)
+        .collect();
+
+    if res.len() as i64 >= limit || long.is_empty() {
+        return Ok(page.into_ranked_page(res));
+    }
+
+    let tris: HashSet<String> = long.iter().flat_map(|t| trigrams(t)).collect();
+    let expr = tris
+        .iter()
+        .map(|t| quote(t))
+        .collect::<Vec<String>>()
+        .join(" OR ");
+    let found: HashSet<i64> = res.iter().map(|r| r.id).collect();
+    let mut fuzzy: Vec<(f64, SearchResult)> =
+        get_candidates(&db, &expr, candidates)
+            .await
+            .into_iter()
+            .filter(|c| !found.contains(&c.result.id))
+            .filter_map(|mut c| {
+                let s = score(&terms, &haystack(&c));
+                if s < FUZZY_THRESHOLD {
+                    return None;
+                }
+                c.result.fuzzy = true;
+                Some((s, c.result))
+            })
+            .collect();
+    fuzzy.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    res.extend(
+        fuzzy
+            .into_iter()
+            .take(limit as usize - res.len())
+            .map(|(_, r)| r),
+    );
+    Ok(page.into_ranked_page(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_cases() {
+        let cases = [
+            ("Fix Crash", "fix crash"),
+            ("owner/name#123", "owner/name#123"),
+            ("fix(gh): don't panic!", "fix gh   don t panic "),
+            ("ÜBER Straße", "über straße"),
+            ("snake_case.rs-v2", "snake_case.rs-v2"),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(normalize(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn score_cases() {
+        let terms = |t: &[&str]| t.iter().map(|s| s.to_string()).collect();
+        let cases: [(Vec<String>, &str, f64); 6] = [
+            (terms(&["crash"]), "fix crash on start", 1.0),
+            (terms(&["crash"]), "nothing alike", 0.0),
+            // "crsh" has trigrams "crs" and "rsh", neither in "crash".
+            (terms(&["crsh"]), "crash", 0.0),
+            // "crashes": "cra", "ras", "ash" of 5 trigrams found.
+            (terms(&["crashes"]), "crash", 0.6),
+            (terms(&["crash", "ui"]), "crash in ui", 1.0),
+            (terms(&["crash", "ui"]), "crash in db", 0.5),
+        ];
+
+        for (terms, haystack, expected) in cases {
+            let res = score(&terms, haystack);
+            assert!(
+                (res - expected).abs() < 1e-9,
+                "{:?} in '{}': {} != {}",
+                terms,
+                haystack,
+                res,
+                expected
+            );
+        }
+    }
+}
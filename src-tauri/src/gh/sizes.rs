@@ -0,0 +1,266 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, db::DB, errors::GHDError};
+
+use super::types::{PullRequestFile, SizeStats};
+
+/// Setting holding the largest number of changed lines for each size class
+/// but the last, comma-separated and ascending; e.g., `10,100,500,1000`.
+///
+pub const SETTING_SIZE_THRESHOLDS: &str = "prs.size_thresholds";
+
+const DEFAULT_SIZE_THRESHOLDS: [i64; 4] = [10, 100, 500, 1000];
+
+/// Size classes, smallest first.
+///
+pub const SIZE_CLASSES: [&str; 5] = ["XS", "S", "M", "L", "XL"];
+
+/// Columns selecting a Pull Request's size class and changed lines, if its
+/// files are known.
+///
+pub const SIZE_COLUMNS: &str = "
+    (
+        SELECT size_class FROM pr_sizes WHERE pr_sizes.pr_id = issues.id
+    ) AS size_class,
+    (
+        SELECT changed_lines FROM pr_sizes WHERE pr_sizes.pr_id = issues.id
+    ) AS changed_lines
+";
+
+fn is_valid(thresholds: &[i64; 4]) -> bool {
+    thresholds[0] >= 0 && thresholds.windows(2).all(|w| w[0] < w[1])
+}
+
+fn parse_thresholds(value: &str) -> Option<[i64; 4]> {
+    let values = value
+        .split(',')
+        .map(|v| v.trim().parse::<i64>().ok())
+        .collect::<Option<Vec<i64>>>()?;
+    let thresholds: [i64; 4] = values.try_into().ok()?;
+    is_valid(&thresholds).then_some(thresholds)
+}
+
+fn format_thresholds(thresholds: &[i64; 4]) -> String {
+    thresholds
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Obtain the size class thresholds, falling back to the defaults if the
+/// setting is malformed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_thresholds(db: &DB) -> [i64; 4] {
+    let value = config::get_setting_or(
+        &db,
+        SETTING_SIZE_THRESHOLDS,
+        &format_thresholds(&DEFAULT_SIZE_THRESHOLDS),
+    )
+    .await;
+    parse_thresholds(&value).unwrap_or(DEFAULT_SIZE_THRESHOLDS)
+}
+
+/// Obtain the size class of a Pull Request changing `lines` lines.
+///
+/// # Arguments
+///
+/// * `lines` - Lines added plus lines deleted.
+/// * `thresholds` - The size class thresholds.
+///
+pub fn classify(lines: i64, thresholds: &[i64; 4]) -> &'static str {
+    let idx = thresholds
+        .iter()
+        .position(|t| lines <= *t)
+        .unwrap_or(thresholds.len());
+    SIZE_CLASSES[idx]
+}
+
+/// Classify Pull Request `prid` by the lines changed by `files`, reading the
+/// thresholds through the transaction. Pull Requests whose files were
+/// truncated are classified by those kept, and thus may be larger than their
+/// class says. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `files` - The files changed by the Pull Request.
+///
+pub async fn record(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    files: &Vec<PullRequestFile>,
+) {
+    let thresholds = match sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?",
+    )
+    .bind(SETTING_SIZE_THRESHOLDS)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(res) => res
+            .and_then(|v| parse_thresholds(&v))
+            .unwrap_or(DEFAULT_SIZE_THRESHOLDS),
+        Err(err) => {
+            panic!("Unable to obtain size thresholds: {}", err);
+        }
+    };
+
+    let lines: i64 = files.iter().map(|f| f.additions + f.deletions).sum();
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO pr_sizes (pr_id, changed_lines, size_class)
+        VALUES (?, ?, ?)
+        ",
+    )
+    .bind(&prid)
+    .bind(&lines)
+    .bind(classify(lines, &thresholds))
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Error recording size of pr {}: {}", prid, err);
+    });
+}
+
+/// Set the size class thresholds, reclassifying every Pull Request already
+/// sized.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `thresholds` - Four ascending thresholds, one per class but `XL`.
+///
+pub async fn set_thresholds(
+    db: &DB,
+    thresholds: &Vec<i64>,
+) -> Result<(), GHDError> {
+    let thresholds: [i64; 4] = match thresholds.clone().try_into() {
+        Ok(t) if is_valid(&t) => t,
+        _ => return Err(GHDError::BadRequest),
+    };
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to set thresholds: {}", err);
+        }
+    };
+
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+        .bind(SETTING_SIZE_THRESHOLDS)
+        .bind(format_thresholds(&thresholds))
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to set size thresholds: {}", err);
+        });
+
+    sqlx::query(
+        "
+        UPDATE pr_sizes SET size_class = CASE
+            WHEN changed_lines <= ? THEN ?
+            WHEN changed_lines <= ? THEN ?
+            WHEN changed_lines <= ? THEN ?
+            WHEN changed_lines <= ? THEN ?
+            ELSE ?
+        END
+        ",
+    )
+    .bind(&thresholds[0])
+    .bind(SIZE_CLASSES[0])
+    .bind(&thresholds[1])
+    .bind(SIZE_CLASSES[1])
+    .bind(&thresholds[2])
+    .bind(SIZE_CLASSES[2])
+    .bind(&thresholds[3])
+    .bind(SIZE_CLASSES[3])
+    .bind(SIZE_CLASSES[4])
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to reclassify pull requests: {}", err);
+    });
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit size thresholds: {}", err);
+    });
+    Ok(())
+}
+
+/// Obtain how long Pull Requests of each size class took to be merged, for
+/// those merged since `since`, if provided. Classes without merged Pull
+/// Requests are reported with no times.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `since` - Only consider Pull Requests merged since this timestamp.
+///
+pub async fn get_stats(db: &DB, since: &Option<i64>) -> Vec<SizeStats> {
+    let rows = match sqlx::query_as::<_, (String, i64)>(
+        "
+        SELECT
+            pr_sizes.size_class,
+            pull_requests.merged_at - issues.created_at
+        FROM
+            pr_sizes
+            INNER JOIN pull_requests ON pull_requests.id = pr_sizes.pr_id
+            INNER JOIN issues ON issues.id = pr_sizes.pr_id
+        WHERE
+            pull_requests.merged_at IS NOT NULL
+            AND (?1 IS NULL OR pull_requests.merged_at >= ?1)
+        ORDER BY 2
+        ",
+    )
+    .bind(&since)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain merged pull request sizes: {}", err);
+        }
+    };
+
+    SIZE_CLASSES
+        .iter()
+        .map(|class| {
+            let times = rows
+                .iter()
+                .filter(|(c, _)| c == class)
+                .map(|(_, t)| *t)
+                .collect::<Vec<i64>>();
+            let merged = times.len() as i64;
+            SizeStats {
+                size_class: class.to_string(),
+                merged,
+                median_time_to_merge: match merged {
+                    0 => None,
+                    _ => Some(times[times.len() / 2]),
+                },
+                mean_time_to_merge: match merged {
+                    0 => None,
+                    _ => Some(times.iter().sum::<i64>() / merged),
+                },
+            }
+        })
+        .collect()
+}
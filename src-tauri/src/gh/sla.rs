@@ -0,0 +1,591 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError, notify};
+
+use super::types::{Issue, SlaEntry, SlaRule};
+
+/// Where an issue may stand against its service level: awaiting a response
+/// in time, awaiting one with little time left, having gone without one for
+/// too long, or having been responded to in time.
+///
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_AT_RISK: &str = "at_risk";
+pub const STATUS_BREACHED: &str = "breached";
+pub const STATUS_MET: &str = "met";
+
+/// Share of the response window, in percent, after which an issue still
+/// awaiting a response is at risk.
+///
+const AT_RISK_PERCENT: i64 = 75;
+
+/// Obtain where an issue stands at `now`, given when it was created, its
+/// response window, and when it was first responded to.
+///
+fn status_of(
+    created_at: i64,
+    response_hours: i64,
+    first_response_at: &Option<i64>,
+    now: i64,
+) -> &'static str {
+    let window = response_hours * 60 * 60;
+    let due_at = created_at + window;
+    match first_response_at {
+        Some(ts) if *ts <= due_at => STATUS_MET,
+        Some(_) => STATUS_BREACHED,
+        None if now > due_at => STATUS_BREACHED,
+        None if now > created_at + window * AT_RISK_PERCENT / 100 => {
+            STATUS_AT_RISK
+        }
+        None => STATUS_PENDING,
+    }
+}
+
+/// How recently an issue must have come at risk or in breach to be notified
+/// of. Older transitions, such as those of issues first synced or of a
+/// service level just set, are recorded quietly so they don't flood
+/// notifications.
+///
+const NOTIFY_WINDOW_SECS: i64 = 24 * 3600;
+
+/// Obtain when `entry` came to stand where it does, if at risk or in breach.
+///
+fn transitioned_at(entry: &SlaEntry) -> Option<i64> {
+    match entry.status.as_str() {
+        STATUS_AT_RISK => Some(
+            entry.created_at
+                + (entry.due_at - entry.created_at) * AT_RISK_PERCENT / 100,
+        ),
+        STATUS_BREACHED => Some(match entry.first_response_at {
+            Some(ts) => ts,
+            None => entry.due_at,
+        }),
+        _ => None,
+    }
+}
+
+/// Notify of an issue now being at risk or in breach, if it wasn't already
+/// and it came to be recently. This function requires a transaction.
+///
+async fn notify_transition(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    entry: &SlaEntry,
+    previous: &Option<String>,
+    now: i64,
+) {
+    if previous.as_deref() == Some(&entry.status) {
+        return;
+    }
+    match transitioned_at(&entry) {
+        Some(ts) if ts >= now - NOTIFY_WINDOW_SECS => {}
+        _ => return,
+    };
+    let (kind, what) = match entry.status.as_str() {
+        STATUS_AT_RISK => ("sla_at_risk", "at risk"),
+        STATUS_BREACHED => ("sla_breached", "breached"),
+        _ => return,
+    };
+
+    let title = format!(
+        "Response time {} on {}/{}#{}",
+        what, entry.repo_owner, entry.repo_name, entry.number
+    );
+    let body = format!("'{}' ({})", entry.title, entry.label);
+    notify::push(
+        tx,
+        kind,
+        &Some(entry.issue_id),
+        &title,
+        &body,
+        notify::classify(kind),
+    )
+    .await;
+}
+
+/// Write where `entry` stands, notifying of a transition from `previous`.
+/// This function requires a transaction.
+///
+async fn record(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    entry: &SlaEntry,
+    previous: &Option<String>,
+    now: i64,
+) {
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO issue_slas (
+            issue_id, label, due_at, first_response_at, status
+        ) VALUES (?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&entry.issue_id)
+    .bind(&entry.label)
+    .bind(&entry.due_at)
+    .bind(&entry.first_response_at)
+    .bind(&entry.status)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to record sla of issue {}: {}", entry.issue_id, err);
+    });
+
+    notify_transition(tx, &entry, &previous, now).await;
+}
+
+/// An issue, as far as working out its service level is concerned.
+///
+struct Subject {
+    issue_id: i64,
+    repo_owner: String,
+    repo_name: String,
+    number: i64,
+    title: String,
+    url: String,
+    author: String,
+    created_at: i64,
+    state: String,
+    first_response_at: Option<i64>,
+    labels: Vec<String>,
+}
+
+/// Work out where `subject` stands against the strictest service level its
+/// labels subject it to, notifying of it coming at risk or in breach. Issues
+/// no longer subject to one, and those closed without a response, are
+/// dropped. This function requires a transaction.
+///
+async fn evaluate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    subject: Subject,
+) {
+    let rules = sqlx::query_as::<_, (String, i64)>(
+        "
+        SELECT label, response_hours FROM sla_rules
+        WHERE repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&subject.repo_owner)
+    .bind(&subject.repo_name)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!(
+            "Unable to obtain slas for issue {}: {}",
+            subject.issue_id, err
+        );
+    });
+    let rule = rules
+        .into_iter()
+        .filter(|(label, _)| {
+            subject
+                .labels
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(&label))
+        })
+        .min_by_key(|(_, hours)| *hours);
+
+    let (label, hours) = match rule {
+        Some(r)
+            if subject.state == "open"
+                || subject.first_response_at.is_some() =>
+        {
+            r
+        }
+        _ => {
+            sqlx::query("DELETE FROM issue_slas WHERE issue_id = ?")
+                .bind(&subject.issue_id)
+                .execute(&mut *tx)
+                .await
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "Unable to clear sla of issue {}: {}",
+                        subject.issue_id, err
+                    );
+                });
+            return;
+        }
+    };
+
+    let previous = sqlx::query_scalar::<_, String>(
+        "SELECT status FROM issue_slas WHERE issue_id = ?",
+    )
+    .bind(&subject.issue_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!(
+            "Unable to obtain sla of issue {}: {}",
+            subject.issue_id, err
+        );
+    });
+
+    let now = chrono::Utc::now().timestamp();
+    let created_at = subject.created_at;
+    let status = status_of(created_at, hours, &subject.first_response_at, now);
+    let entry = SlaEntry {
+        issue_id: subject.issue_id,
+        repo_owner: subject.repo_owner,
+        repo_name: subject.repo_name,
+        number: subject.number,
+        title: subject.title,
+        url: subject.url,
+        author: subject.author,
+        created_at,
+        label,
+        due_at: created_at + hours * 60 * 60,
+        first_response_at: subject.first_response_at,
+        status: status.to_string(),
+    };
+    record(tx, &entry, &previous, now).await;
+}
+
+/// Work out where `issue` stands against the strictest service level its
+/// labels subject it to, notifying of it coming at risk or in breach. Issues
+/// no longer subject to one, and those closed without a response, are
+/// dropped. Pull Requests are ignored. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issue` - The issue just synced.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issue: &Issue,
+) {
+    if issue.is_pull_request {
+        return;
+    }
+
+    let subject = Subject {
+        issue_id: issue.id,
+        repo_owner: issue.repo_owner.clone(),
+        repo_name: issue.repo_name.clone(),
+        number: issue.number,
+        title: issue.title.clone(),
+        url: issue.url.clone(),
+        author: issue.author.clone(),
+        created_at: issue.created_at.timestamp(),
+        state: issue.state.clone(),
+        first_response_at: issue.first_response_at,
+        labels: issue.labels.clone(),
+    };
+    evaluate(tx, subject).await;
+}
+
+/// Work out anew where the issues on repository `owner/name` stand, after its
+/// service levels changed. Only open issues, and those already held to a
+/// service level, are looked at. This function requires a transaction.
+///
+async fn reevaluate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+) {
+    let issues = sqlx::query_as::<
+        _,
+        (i64, i64, String, String, String, i64, String, Option<i64>),
+    >(
+        "
+        SELECT
+            issues.id, issues.number, issues.title, issues.url,
+            issues.author, issues.created_at, issues.state,
+            COALESCE(
+                issues.first_response_at, issue_slas.first_response_at
+            )
+        FROM issues
+        LEFT JOIN issue_slas ON issue_slas.issue_id = issues.id
+        WHERE
+            issues.repo_owner = ? AND issues.repo_name = ?
+            AND issues.is_pull_request = 0
+            AND (issues.state = 'open' OR issue_slas.issue_id IS NOT NULL)
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!(
+            "Unable to obtain issues for sla on '{}/{}': {}",
+            owner, name, err
+        );
+    });
+
+    for (
+        id,
+        number,
+        title,
+        url,
+        author,
+        created_at,
+        state,
+        first_response_at,
+    ) in issues
+    {
+        let labels = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM issue_labels WHERE issue_id = ?",
+        )
+        .bind(&id)
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to obtain labels for issue {}: {}", id, err);
+        });
+
+        let subject = Subject {
+            issue_id: id,
+            repo_owner: owner.clone(),
+            repo_name: name.clone(),
+            number,
+            title,
+            url,
+            author,
+            created_at,
+            state,
+            first_response_at,
+            labels,
+        };
+        evaluate(tx, subject).await;
+    }
+}
+
+/// Columns making up an `SlaEntry`, joined from `issue_slas` and `issues`.
+///
+const ENTRY_COLUMNS: &str = "
+    issues.id AS issue_id, issues.repo_owner, issues.repo_name,
+    issues.number, issues.title, issues.url, issues.author,
+    issues.created_at, issue_slas.label, issue_slas.due_at,
+    issue_slas.first_response_at, issue_slas.status
+";
+
+/// Move the open issues still awaiting a response along as time passes,
+/// notifying of those coming at risk or in breach. Issues are otherwise only
+/// looked at when synced, which they aren't unless they change.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn run(db: &DB) {
+    let waiting = match sqlx::query_as::<_, SlaEntry>(&format!(
+        "
+        SELECT {} FROM issue_slas
+        INNER JOIN issues ON issues.id = issue_slas.issue_id
+        WHERE
+            issues.state = 'open'
+            AND issue_slas.first_response_at IS NULL
+            AND issue_slas.status IN (?, ?)
+        ",
+        ENTRY_COLUMNS
+    ))
+    .bind(STATUS_PENDING)
+    .bind(STATUS_AT_RISK)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain issues awaiting a response: {}", err);
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to update slas: {}", err);
+        }
+    };
+    for mut entry in waiting {
+        let previous = Some(entry.status.clone());
+        let hours = (entry.due_at - entry.created_at) / (60 * 60);
+        entry.status =
+            status_of(entry.created_at, hours, &None, now).to_string();
+        if previous.as_deref() != Some(&entry.status) {
+            record(&mut tx, &entry, &previous, now).await;
+        }
+    }
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit sla updates: {}", err);
+    });
+}
+
+/// Obtain the open issues at risk of, or in, breach of their service level,
+/// on repository `owner/name` if provided, or on any otherwise; those due
+/// soonest first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner, if any.
+/// * `name` - The repository's name, if any.
+///
+pub async fn get_breaches(
+    db: &DB,
+    owner: &Option<String>,
+    name: &Option<String>,
+) -> Vec<SlaEntry> {
+    match sqlx::query_as::<_, SlaEntry>(&format!(
+        "
+        SELECT {} FROM issue_slas
+        INNER JOIN issues ON issues.id = issue_slas.issue_id
+        WHERE
+            issues.state = 'open'
+            AND issue_slas.status IN (?, ?)
+            AND (?3 IS NULL OR issues.repo_owner = ?3)
+            AND (?4 IS NULL OR issues.repo_name = ?4)
+        ORDER BY issue_slas.due_at
+        ",
+        ENTRY_COLUMNS
+    ))
+    .bind(STATUS_AT_RISK)
+    .bind(STATUS_BREACHED)
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain sla breaches: {}", err);
+        }
+    }
+}
+
+/// Set the service level for issues labeled `rule.label` on its repository,
+/// replacing any set for that label. The repository's issues are held to it
+/// right away.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `rule` - The service level to set.
+///
+pub async fn set_rule(db: &DB, rule: &SlaRule) -> Result<(), GHDError> {
+    if rule.label.trim().is_empty() || rule.response_hours <= 0 {
+        return Err(GHDError::BadRequest);
+    }
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to set sla: {}", err);
+        }
+    };
+
+    sqlx::query(
+        "
+        INSERT OR REPLACE INTO sla_rules (
+            repo_owner, repo_name, label, response_hours
+        ) VALUES (?, ?, ?, ?)
+        ",
+    )
+    .bind(&rule.repo_owner)
+    .bind(&rule.repo_name)
+    .bind(rule.label.trim())
+    .bind(&rule.response_hours)
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!(
+            "Unable to set sla for '{}/{}': {}",
+            rule.repo_owner, rule.repo_name, err
+        );
+    });
+
+    reevaluate(&mut tx, &rule.repo_owner, &rule.repo_name).await;
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit sla: {}", err);
+    });
+    Ok(())
+}
+
+/// Remove the service level for issues labeled `label` on repository
+/// `owner/name`, holding its issues to whichever remain.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `label` - The label the service level applies to.
+///
+pub async fn remove_rule(
+    db: &DB,
+    owner: &String,
+    name: &String,
+    label: &String,
+) {
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to remove sla: {}", err);
+        }
+    };
+
+    sqlx::query(
+        "
+        DELETE FROM sla_rules
+        WHERE repo_owner = ? AND repo_name = ? AND label = ?
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .bind(&label)
+    .execute(&mut tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to remove sla for '{}/{}': {}", owner, name, err);
+    });
+
+    reevaluate(&mut tx, &owner, &name).await;
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit sla removal: {}", err);
+    });
+}
+
+/// Obtain the service levels set, on repository `owner/name` if provided,
+/// or on every repository otherwise.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner, if any.
+/// * `name` - The repository's name, if any.
+///
+pub async fn get_rules(
+    db: &DB,
+    owner: &Option<String>,
+    name: &Option<String>,
+) -> Vec<SlaRule> {
+    match sqlx::query_as::<_, SlaRule>(
+        "
+        SELECT * FROM sla_rules
+        WHERE
+            (?1 IS NULL OR repo_owner = ?1)
+            AND (?2 IS NULL OR repo_name = ?2)
+        ORDER BY repo_owner, repo_name, label
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain slas: {}", err);
+        }
+    }
+}
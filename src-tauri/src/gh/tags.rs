@@ -0,0 +1,118 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{db::DB, errors::GHDError};
+
+use super::types::LocalTag;
+
+/// Tag issue `issueid` with local tag `tag`. Local tags never leave the
+/// database; they are shared with those applied by triage rules.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+/// * `tag` - The tag to apply.
+///
+pub async fn add_tag(
+    db: &DB,
+    issueid: &i64,
+    tag: &String,
+) -> Result<(), GHDError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(GHDError::BadRequest);
+    }
+
+    match sqlx::query(
+        "
+        INSERT OR IGNORE INTO issue_tags (issue_id, tag)
+        SELECT id, ? FROM issues WHERE id = ?
+        ",
+    )
+    .bind(&tag)
+    .bind(&issueid)
+    .execute(db.pool())
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            panic!("Unable to tag issue {}: {}", issueid, err);
+        }
+    }
+}
+
+/// Remove local tag `tag` from issue `issueid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+/// * `tag` - The tag to remove.
+///
+pub async fn remove_tag(db: &DB, issueid: &i64, tag: &String) {
+    sqlx::query("DELETE FROM issue_tags WHERE issue_id = ? AND tag = ?")
+        .bind(&issueid)
+        .bind(&tag)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to untag issue {}: {}", issueid, err);
+        });
+}
+
+/// Obtain the local tags of issue `issueid`, sorted by name.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn get_issue_tags(db: &DB, issueid: &i64) -> Vec<String> {
+    match sqlx::query_scalar::<_, String>(
+        "SELECT tag FROM issue_tags WHERE issue_id = ? ORDER BY tag",
+    )
+    .bind(&issueid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain tags of issue {}: {}", issueid, err);
+        }
+    }
+}
+
+/// Obtain every local tag in use, along with how many issues have it.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_tags(db: &DB) -> Vec<LocalTag> {
+    match sqlx::query_as::<_, LocalTag>(
+        "
+        SELECT tag, COUNT(DISTINCT issue_id) AS count FROM issue_tags
+        GROUP BY tag ORDER BY tag
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain tags: {}", err);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::Regex;
+
+use crate::{db::DB, errors::GHDError};
+
+use super::{accounts::AccountCredentials, refresh, rest, types::PullRequest};
+
+/// Seconds between refreshes of a repository's Pull Request template.
+///
+pub const TEMPLATES_REFRESH_INTERVAL: i64 = 24 * 60 * 60;
+
+/// Prefix for per-repository settings holding the newline-separated regular
+/// expressions Pull Request bodies must each match, e.g.
+/// `template.sections.owner/name` = `(?m)^## Summary`. When not set, the
+/// headings of the repository's Pull Request template are required instead.
+///
+pub const SETTING_SECTIONS_PREFIX: &str = "template.sections.";
+
+/// Where GitHub looks for a repository's Pull Request template, in order.
+///
+const TEMPLATE_PATHS: [&str; 6] = [
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+    "docs/PULL_REQUEST_TEMPLATE.md",
+];
+
+/// Columns with the template check of the `issues` row being selected, for
+/// listings.
+///
+pub const TEMPLATE_CHECK_COLUMNS: &str = "
+    COALESCE(
+        (
+            SELECT is_empty FROM pr_template_checks
+            WHERE pr_template_checks.pr_id = issues.id
+        ),
+        0
+    ) AS template_empty,
+    (
+        SELECT missing FROM pr_template_checks
+        WHERE pr_template_checks.pr_id = issues.id AND missing != ''
+    ) AS template_missing
+";
+
+/// A file's contents, as returned by the REST API.
+///
+#[derive(serde::Deserialize)]
+struct ContentReply {
+    content: String,
+    encoding: String,
+}
+
+/// A required section, by name, and the pattern a body must match to have
+/// it.
+///
+struct Section {
+    name: String,
+    re: Regex,
+}
+
+/// Obtain the sync refresh key for the template of repository `owner/name`.
+///
+pub fn refresh_key(owner: &String, name: &String) -> String {
+    format!("templates:{}/{}", owner, name)
+}
+
+/// Check whether the template of repository `owner/name` is due for a
+/// refresh.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn should_refresh(db: &DB, owner: &String, name: &String) -> bool {
+    refresh::should_refresh_sync(
+        &db,
+        &refresh_key(&owner, &name),
+        TEMPLATES_REFRESH_INTERVAL,
+    )
+    .await
+}
+
+/// Obtain the Pull Request template of a repository, if it has one.
+///
+/// # Arguments
+///
+/// * `creds` - The credentials to use when talking to the API.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn fetch_template(
+    creds: &AccountCredentials,
+    owner: &String,
+    name: &String,
+) -> Result<Option<String>, GHDError> {
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("templates");
+
+    for path in TEMPLATE_PATHS {
+        let ep = format!("/repos/{}/{}/contents/{}", owner, name, path);
+        let reply = match ghreq.send::<ContentReply>(ghreq.get(&ep)).await {
+            Ok(res) => res,
+            Err(reqwest::StatusCode::NOT_FOUND) => continue,
+            Err(err) => return Err(rest::status_to_error(err)),
+        };
+        if reply.encoding != "base64" {
+            println!("unexpected encoding for template '{}'", path);
+            continue;
+        }
+        let raw: String = reply.content.split_whitespace().collect();
+        match STANDARD.decode(&raw) {
+            Ok(res) => {
+                return Ok(Some(String::from_utf8_lossy(&res).to_string()))
+            }
+            Err(err) => {
+                println!("unable to decode template '{}': {}", path, err);
+            }
+        };
+    }
+    Ok(None)
+}
+
+/// Write the template of repository `owner/name` to the database, or drop
+/// it if the repository no longer has one. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+/// * `template` - The repository's template, if any.
+/// * `when` - When the template was obtained.
+///
+pub async fn consume_template(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+    template: &Option<String>,
+    when: &chrono::DateTime<chrono::Utc>,
+) {
+    let res = match template {
+        Some(t) => sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_templates (
+                repo_owner, repo_name, template
+            ) VALUES (?, ?, ?)
+            ",
+        )
+        .bind(&owner)
+        .bind(&name)
+        .bind(t),
+        None => sqlx::query(
+            "DELETE FROM pr_templates WHERE repo_owner = ? AND repo_name = ?",
+        )
+        .bind(&owner)
+        .bind(&name),
+    }
+    .execute(&mut *tx)
+    .await;
+    if let Err(err) = res {
+        panic!(
+            "Unable to update template for '{}/{}': {}",
+            owner, name, err
+        );
+    }
+
+    refresh::update_sync_refresh(tx, &refresh_key(&owner, &name), &when).await;
+}
+
+/// Obtain the headings of a Markdown template, as sections to require.
+///
+fn headings(template: &str) -> Vec<Section> {
+    let re = Regex::new(r"^#{1,6}\s+(.+?)\s*#*\s*$").unwrap();
+    template
+        .lines()
+        .filter_map(|line| {
+            let heading = re.captures(line)?.get(1)?.as_str().to_string();
+            let pattern = format!(
+                r"(?mi)^#{{1,6}}\s+{}\s*#*\s*$",
+                regex::escape(&heading)
+            );
+            Some(Section {
+                name: heading,
+                re: Regex::new(&pattern).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Obtain the sections required of Pull Requests to repository
+/// `owner/name`, if it has any requirements at all. This function requires
+/// a transaction.
+///
+async fn get_sections(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+) -> Option<Vec<Section>> {
+    let key = format!("{}{}/{}", SETTING_SECTIONS_PREFIX, owner, name);
+    let patterns = match sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?",
+    )
+    .bind(&key)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain setting '{}': {}", key, err);
+        }
+    };
+
+    if let Some(patterns) = patterns {
+        return Some(
+            patterns
+                .lines()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .filter_map(|p| match Regex::new(&p) {
+                    Ok(re) => Some(Section {
+                        name: p.to_string(),
+                        re,
+                    }),
+                    Err(err) => {
+                        println!("ignoring invalid section '{}': {}", p, err);
+                        None
+                    }
+                })
+                .collect(),
+        );
+    }
+
+    match sqlx::query_scalar::<_, String>(
+        "
+        SELECT template FROM pr_templates
+        WHERE repo_owner = ? AND repo_name = ?
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(res) => res.map(|t| headings(&t)),
+        Err(err) => {
+            panic!(
+                "Unable to obtain template for '{}/{}': {}",
+                owner, name, err
+            );
+        }
+    }
+}
+
+/// Check the bodies of incoming open Pull Requests to maintained
+/// repositories against their templates, recording which are empty or
+/// missing required sections. Repositories without a template or configured
+/// sections are not checked. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prs` - The newly synced Pull Requests.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prs: &Vec<PullRequest>,
+) {
+    let now = chrono::Utc::now().timestamp();
+
+    for pr in prs {
+        let issue = &pr.issue;
+        let is_checked = match sqlx::query_scalar::<_, bool>(
+            "
+            SELECT
+                EXISTS (
+                    SELECT 1 FROM repos WHERE owner = ? AND name = ?
+                )
+                AND ? NOT IN (
+                    SELECT user_id FROM tokens WHERE user_id IS NOT NULL
+                )
+            ",
+        )
+        .bind(&issue.repo_owner)
+        .bind(&issue.repo_name)
+        .bind(&issue.author_id)
+        .fetch_one(&mut *tx)
+        .await
+        {
+            Ok(res) => res && issue.state == "open",
+            Err(err) => {
+                panic!(
+                    "Unable to check whether to check {}: {}",
+                    issue.id, err
+                );
+            }
+        };
+
+        let sections = match is_checked {
+            true => get_sections(tx, &issue.repo_owner, &issue.repo_name).await,
+            false => None,
+        };
+        let sections = match sections {
+            Some(s) => s,
+            None => {
+                sqlx::query("DELETE FROM pr_template_checks WHERE pr_id = ?")
+                    .bind(&issue.id)
+                    .execute(&mut *tx)
+                    .await
+                    .unwrap_or_else(|err| {
+                        panic!(
+                            "Unable to clear template check for {}: {}",
+                            issue.id, err
+                        );
+                    });
+                continue;
+            }
+        };
+
+        let is_empty = issue.body.trim().is_empty();
+        let missing: Vec<&str> = sections
+            .iter()
+            .filter(|s| !s.re.is_match(&issue.body))
+            .map(|s| s.name.as_str())
+            .collect();
+
+        sqlx::query(
+            "
+            INSERT OR REPLACE INTO pr_template_checks (
+                pr_id, is_empty, missing, checked_at
+            ) VALUES (?, ?, ?, ?)
+            ",
+        )
+        .bind(&issue.id)
+        .bind(&is_empty)
+        .bind(&missing.join("\n"))
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to record template check for {}: {}", issue.id, err);
+        });
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::{
+    db::DB,
+    errors::GHDError,
+    notify,
+    page::{Page, PageRequest},
+};
+
+use super::{
+    annotations, bots, fixes, templates,
+    types::{Issue, TriageEntry, TriageRule},
+};
+
+/// Check whether `rule` applies to `issue`. Rules with an invalid title
+/// pattern never apply.
+///
+fn matches(rule: &TriageRule, issue: &Issue) -> bool {
+    if let Some(pattern) = &rule.title_pattern {
+        match Regex::new(&pattern) {
+            Ok(re) => {
+                if !re.is_match(&issue.title) {
+                    return false;
+                }
+            }
+            Err(err) => {
+                println!("invalid pattern on triage rule {}: {}", rule.id, err);
+                return false;
+            }
+        };
+    }
+    if let Some(label) = &rule.label {
+        if !issue.labels.contains(&label) {
+            return false;
+        }
+    }
+    if let Some(author) = &rule.author {
+        if author != &issue.author {
+            return false;
+        }
+    }
+    true
+}
+
+/// Obtain the triage rules for a repository, in evaluation order. This
+/// function requires a transaction.
+///
+async fn get_rules_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    owner: &String,
+    name: &String,
+) -> Vec<TriageRule> {
+    sqlx::query_as::<_, TriageRule>(
+        "
+        SELECT * FROM triage_rules
+        WHERE repo_owner = ? AND repo_name = ?
+        ORDER BY position, id
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to obtain triage rules: {}", err);
+    })
+}
+
+/// Classify the provided issues into triage buckets, using the first
+/// matching rule of their repository. Issues that have been classified before
+/// are left alone, as are closed issues. Matching rules may also tag the
+/// issue locally and queue a notification. This function requires a
+/// transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `issues` - The newly synced issues.
+///
+pub async fn classify(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    issues: &Vec<Issue>,
+) {
+    let mut rules_by_repo: HashMap<(String, String), Vec<TriageRule>> =
+        HashMap::new();
+    let now = chrono::Utc::now().timestamp();
+
+    for issue in issues {
+        if issue.state != "open" {
+            continue;
+        }
+
+        let triaged = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM triage WHERE issue_id = ?",
+        )
+        .bind(&issue.id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to check triage for issue {}: {}", issue.id, err);
+        });
+        if triaged > 0 {
+            continue;
+        }
+
+        let key = (issue.repo_owner.clone(), issue.repo_name.clone());
+        if !rules_by_repo.contains_key(&key) {
+            let rules = get_rules_tx(tx, &key.0, &key.1).await;
+            rules_by_repo.insert(key.clone(), rules);
+        }
+        let rule = match rules_by_repo[&key].iter().find(|r| matches(r, issue))
+        {
+            Some(r) => r,
+            None => continue,
+        };
+
+        sqlx::query(
+            "
+            INSERT INTO triage (issue_id, bucket, rule_id, triaged_at)
+            VALUES (?, ?, ?, ?)
+            ",
+        )
+        .bind(&issue.id)
+        .bind(&rule.bucket)
+        .bind(&rule.id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to triage issue {}: {}", issue.id, err);
+        });
+
+        if let Some(tag) = &rule.tag {
+            sqlx::query(
+                "INSERT OR IGNORE INTO issue_tags (issue_id, tag) VALUES (?, ?)",
+            )
+            .bind(&issue.id)
+            .bind(&tag)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to tag issue {}: {}", issue.id, err);
+            });
+        }
+
+        if rule.notify {
+            let title = format!(
+                "[{}] {}/{}#{}",
+                rule.bucket, issue.repo_owner, issue.repo_name, issue.number
+            );
+            notify::push(
+                tx,
+                "triage",
+                &Some(issue.id),
+                &title,
+                &issue.title,
+                notify::classify("triage"),
+            )
+            .await;
+        }
+    }
+}
+
+/// Add a triage rule, evaluated after all existing rules for its repository.
+/// Returns the new rule's ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `rule` - The rule to add. Its `id` and `position` are ignored.
+///
+pub async fn add_rule(db: &DB, rule: &TriageRule) -> Result<i64, GHDError> {
+    if let Some(pattern) = &rule.title_pattern {
+        if let Err(err) = Regex::new(&pattern) {
+            println!("invalid triage title pattern '{}': {}", pattern, err);
+            return Err(GHDError::BadRequest);
+        }
+    }
+
+    match sqlx::query(
+        "
+        INSERT INTO triage_rules (
+            repo_owner, repo_name, position,
+            title_pattern, label, author, bucket, tag, notify
+        ) VALUES (
+            ?, ?,
+            (
+                SELECT COALESCE(MAX(position), 0) + 1 FROM triage_rules
+                WHERE repo_owner = ? AND repo_name = ?
+            ),
+            ?, ?, ?, ?, ?, ?
+        )
+        ",
+    )
+    .bind(&rule.repo_owner)
+    .bind(&rule.repo_name)
+    .bind(&rule.repo_owner)
+    .bind(&rule.repo_name)
+    .bind(&rule.title_pattern)
+    .bind(&rule.label)
+    .bind(&rule.author)
+    .bind(&rule.bucket)
+    .bind(&rule.tag)
+    .bind(&rule.notify)
+    .execute(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res.last_insert_rowid()),
+        Err(err) => {
+            panic!("Unable to add triage rule: {}", err);
+        }
+    }
+}
+
+/// Remove triage rule `ruleid`. Issues previously classified by it remain in
+/// their bucket.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `ruleid` - The rule's ID.
+///
+pub async fn remove_rule(db: &DB, ruleid: &i64) {
+    sqlx::query("DELETE FROM triage_rules WHERE id = ?")
+        .bind(&ruleid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to remove triage rule {}: {}", ruleid, err);
+        });
+}
+
+/// Obtain the triage rules for repository `owner/name`, in evaluation order.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `owner` - The repository's owner.
+/// * `name` - The repository's name.
+///
+pub async fn get_rules(
+    db: &DB,
+    owner: &String,
+    name: &String,
+) -> Result<Vec<TriageRule>, GHDError> {
+    match sqlx::query_as::<_, TriageRule>(
+        "
+        SELECT * FROM triage_rules
+        WHERE repo_owner = ? AND repo_name = ?
+        ORDER BY position, id
+        ",
+    )
+    .bind(&owner)
+    .bind(&name)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            panic!("Unable to obtain triage rules: {}", err);
+        }
+    }
+}
+
+/// Obtain a page of the triage inbox: open issues that have been classified
+/// and not yet dismissed, optionally limited to a single bucket; most
+/// recently updated first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `bucket` - Only return issues in this bucket, if provided.
+/// * `page` - The page to obtain.
+///
+pub async fn get_inbox(
+    db: &DB,
+    bucket: &Option<String>,
+    page: &PageRequest,
+) -> Result<Page<TriageEntry>, GHDError> {
+    let query = format!(
+        "
+        SELECT
+            triage.bucket, triage.rule_id, triage.triaged_at, issues.*,
+            {}, {}, {}
+        FROM
+            triage INNER JOIN issues ON issues.id = triage.issue_id
+        WHERE
+            triage.dismissed = 0
+            AND issues.state = 'open'
+            AND (? IS NULL OR triage.bucket = ?)
+            AND {} AND {}
+        ORDER BY issues.updated_at DESC, issues.id DESC
+        LIMIT ?
+        ",
+        annotations::ANNOTATION_COLUMNS,
+        templates::TEMPLATE_CHECK_COLUMNS,
+        fixes::FIX_COLUMNS,
+        bots::filter(&db).await,
+        PageRequest::condition("issues.updated_at", "issues.id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, TriageEntry>(&query)
+        .bind(&bucket)
+        .bind(&bucket)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => {
+            Ok(page.into_page(res, |e| (e.issue.updated_at, e.issue.id)))
+        }
+        Err(err) => {
+            panic!("Unable to obtain triage inbox: {}", err);
+        }
+    }
+}
+
+/// Dismiss issue `issueid` from the triage inbox.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issueid` - The issue's database ID.
+///
+pub async fn dismiss(db: &DB, issueid: &i64) {
+    sqlx::query("UPDATE triage SET dismissed = 1 WHERE issue_id = ?")
+        .bind(&issueid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to dismiss issue {}: {}", issueid, err);
+        });
+}
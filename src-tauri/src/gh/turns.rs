@@ -0,0 +1,125 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::PullRequest;
+
+/// Column telling whether the Pull Request in the `issues` row being selected
+/// is waiting on one of our users. Pull Requests not yet looked at are not.
+///
+pub const MY_TURN_COLUMN: &str = "
+    COALESCE(
+        (SELECT my_turn FROM pr_turns WHERE pr_turns.pr_id = issues.id), 0
+    ) AS my_turn
+";
+
+/// Check whether Pull Request `pr` is waiting on any of `ours`, our users.
+/// That is, either one of them has been requested a review and hasn't
+/// reviewed since the last push, or one of them is the author, changes were
+/// requested, and nothing has been pushed since.
+///
+/// # Arguments
+///
+/// * `ours` - The database IDs of our users.
+/// * `pr` - The Pull Request to check.
+///
+pub fn is_my_turn(ours: &Vec<i64>, pr: &PullRequest) -> bool {
+    if pr.issue.state != "open" {
+        return false;
+    }
+
+    let pushed_at = pr.head_committed_at;
+    let requested = ours.iter().any(|userid| {
+        if !pr.review_requests.contains(&userid) {
+            return false;
+        }
+        let reviewed_at = pr
+            .latest_reviews
+            .iter()
+            .find(|r| r.user_id == *userid)
+            .map(|r| r.submitted_at);
+        match (reviewed_at, pushed_at) {
+            (None, _) => true,
+            (Some(reviewed), Some(pushed)) => reviewed < pushed,
+            (Some(_), None) => false,
+        }
+    });
+    if requested {
+        return true;
+    }
+
+    if !ours.contains(&pr.issue.author_id)
+        || pr.review_decision != "changes_requested"
+    {
+        return false;
+    }
+    let last_review = pr
+        .latest_reviews
+        .iter()
+        .filter(|r| r.user_id != pr.issue.author_id)
+        .map(|r| r.submitted_at)
+        .max();
+    match (last_review, pushed_at) {
+        (Some(reviewed), Some(pushed)) => pushed <= reviewed,
+        _ => true,
+    }
+}
+
+/// Write whether Pull Request `prid` is waiting on one of our users to the
+/// database. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `prid` - The Pull Request's database ID.
+/// * `my_turn` - Whether the Pull Request is waiting on one of our users.
+///
+pub async fn consume(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    prid: &i64,
+    my_turn: bool,
+) {
+    sqlx::query(
+        "INSERT OR REPLACE INTO pr_turns (pr_id, my_turn) VALUES (?, ?)",
+    )
+    .bind(&prid)
+    .bind(&my_turn)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to consume turn for pr {}: {}", prid, err);
+    });
+}
+
+/// Obtain the database IDs of our users, i.e. those we hold a token for.
+/// This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+///
+pub async fn get_our_users(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+) -> Vec<i64> {
+    match sqlx::query_scalar::<_, i64>(
+        "SELECT user_id FROM tokens WHERE user_id IS NOT NULL",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain our users: {}", err);
+        }
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod parse;
+
 // Users
 
 /// Describes a user, as it is kept in the database.
@@ -24,7 +26,7 @@ pub struct GithubUser {
     pub avatar_url: String,
 }
 
-#[derive(sqlx::FromRow)]
+#[derive(sqlx::FromRow, serde::Serialize)]
 pub struct IssueTableEntry {
     pub id: i64,
     pub number: i64,
@@ -40,6 +42,15 @@ pub struct IssueTableEntry {
     pub closed_at: Option<i64>,
     pub is_pull_request: bool,
     pub last_viewed: Option<i64>,
+    /// Local annotations; only computed by listings.
+    #[sqlx(default)]
+    pub local_priority: Option<String>,
+    #[sqlx(default)]
+    pub local_note: Option<String>,
+    /// Whether an open Pull Request will close the issue; only computed by
+    /// listings.
+    #[sqlx(default)]
+    pub fix_in_progress: bool,
 }
 
 /// A Pull Request Table Entry includes all columns in the `IssueTableEntry`
@@ -65,6 +76,181 @@ pub struct PullRequestTableEntry {
     pub is_draft: bool,
     pub review_decision: String,
     pub merged_at: Option<i64>,
+    /// Number of other open Pull Requests in the same repository touching
+    /// the same files, where either is ours. Only computed by listings.
+    #[sqlx(default)]
+    pub potential_conflicts: i64,
+    /// Whether the author is contributing to a maintained repository for the
+    /// first time. Only computed by listings.
+    #[sqlx(default)]
+    pub is_first_time_contributor: bool,
+    /// Whether the Pull Request is waiting on one of our users. Only
+    /// computed by listings.
+    #[sqlx(default)]
+    pub my_turn: bool,
+    /// Local annotations; only computed by listings.
+    #[sqlx(default)]
+    pub local_priority: Option<String>,
+    #[sqlx(default)]
+    pub local_note: Option<String>,
+    /// Size class, from `XS` to `XL`, and lines changed, if the files changed
+    /// are known. Only computed by listings.
+    #[sqlx(default)]
+    pub size_class: Option<String>,
+    #[sqlx(default)]
+    pub changed_lines: Option<i64>,
+}
+
+/// Describes a file changed by a Pull Request.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct PullRequestFile {
+    pub pr_id: i64,
+    pub path: String,
+    pub status: String,
+    pub additions: i64,
+    pub deletions: i64,
+}
+
+/// A commit of a Pull Request, along with its signature verification status.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct PullRequestCommit {
+    pub pr_id: i64,
+    pub sha: String,
+    pub position: i64,
+    pub verified: bool,
+    pub signature_state: String,
+    pub message: String,
+}
+
+/// The result of obtaining a Pull Request's commits.
+///
+pub struct PullRequestCommitsUpdate {
+    pub head_sha: String,
+    pub requires_signatures: Option<bool>,
+    pub commits: Vec<PullRequestCommit>,
+}
+
+/// Summary of the signature verification status of a Pull Request's
+/// commits. `requires_signatures` is `None` if we can't tell whether the base
+/// branch requires signed commits.
+///
+#[derive(serde::Serialize)]
+pub struct PullRequestSignatures {
+    pub requires_signatures: Option<bool>,
+    pub total_commits: i64,
+    pub verified_commits: i64,
+    pub all_verified: bool,
+    pub unverified: Vec<PullRequestCommit>,
+}
+
+/// A lint rule violated by one of our open Pull Requests. `field` is either
+/// `title` or `branch`, and `pattern` the regular expression it didn't match.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct LintWarning {
+    pub pr_id: i64,
+    pub rule: String,
+    pub field: String,
+    pub pattern: String,
+}
+
+/// The parts of a Pull Request's state that our write actions change.
+///
+#[derive(sqlx::FromRow, PartialEq)]
+pub struct PullRequestStatus {
+    pub state: String,
+    pub review_decision: String,
+    pub closed_at: Option<i64>,
+    pub merged_at: Option<i64>,
+}
+
+/// The merge requirements of a Pull Request's base branch, along with what
+/// has been reported so far for the Pull Request.
+///
+pub struct PullRequestMergeRequirements {
+    pub base_ref: String,
+    pub protection_visible: bool,
+    pub required_contexts: Vec<String>,
+    pub required_approvals: i64,
+    pub approvals: i64,
+    pub reported_contexts: Vec<String>,
+}
+
+/// What is still blocking a Pull Request from being merged, as far as the
+/// base branch's protection is concerned. If `protection_visible` is false we
+/// were unable to see the branch's protection rules, and nothing is known to
+/// be required.
+///
+#[derive(serde::Serialize)]
+pub struct PullRequestBlockers {
+    pub protection_visible: bool,
+    pub required_approvals: i64,
+    pub approvals: i64,
+    pub approvals_remaining: i64,
+    pub missing_checks: Vec<String>,
+}
+
+/// The result of comparing a Pull Request's head as of when it was last
+/// viewed against its current head.
+///
+pub struct PullRequestDeltaUpdate {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub force_pushed: bool,
+    pub commits: Vec<String>,
+    pub files: Vec<PullRequestFile>,
+}
+
+/// What changed on a Pull Request since it was last viewed. If the Pull
+/// Request has been force-pushed since, the commits and files may not be
+/// exhaustive.
+///
+#[derive(serde::Serialize)]
+pub struct PullRequestDelta {
+    pub viewed_sha: String,
+    pub viewed_at: i64,
+    pub head_sha: String,
+    pub force_pushed: bool,
+    pub new_commits: Vec<PullRequestCommit>,
+    pub changed_files: Vec<PullRequestFile>,
+}
+
+/// A Pull Request along with everything we know about it.
+///
+#[derive(serde::Serialize)]
+pub struct PullRequestDetail {
+    #[serde(flatten)]
+    pub pr: PullRequestTableEntry,
+    pub signatures: Option<PullRequestSignatures>,
+    pub blockers: Option<PullRequestBlockers>,
+    pub since_last_view: Option<PullRequestDelta>,
+    /// Lists obtained for the Pull Request that were cut short.
+    pub truncated: Vec<Truncation>,
+}
+
+/// Describes another open Pull Request touching the same files as the Pull
+/// Request being looked at, along with the overlapping paths.
+///
+#[derive(serde::Serialize)]
+pub struct PullRequestConflict {
+    pub id: i64,
+    pub number: i64,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    pub paths: Vec<String>,
+}
+
+/// A Pull Request along with the branch it was, or should be, backported to.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct BackportEntry {
+    pub branch: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub pr: PullRequestTableEntry,
 }
 
 #[derive(sqlx::FromRow)]
@@ -80,6 +266,7 @@ pub struct Issue {
     pub title: String,
     pub author: String,
     pub author_id: i64,
+    pub author_is_bot: bool,
     pub url: String,
     pub repo_owner: String,
     pub repo_name: String,
@@ -89,6 +276,91 @@ pub struct Issue {
     pub closed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub is_pull_request: bool,
     pub last_viewed: Option<chrono::DateTime<chrono::Utc>>,
+    pub labels: Vec<String>,
+    pub body: String,
+    /// Raw values that couldn't be parsed, by field, as JSON.
+    pub unknown_raw: Option<String>,
+    /// Pull Requests that will close the issue once merged; always empty for
+    /// Pull Requests.
+    #[serde(default)]
+    pub fixes: Vec<IssueFix>,
+    /// When someone other than the author first commented on the issue, if
+    /// anyone has; always `None` for Pull Requests.
+    #[serde(default)]
+    pub first_response_at: Option<i64>,
+    /// Issues and Pull Requests cross-referencing this one from elsewhere.
+    #[serde(default)]
+    pub references: Vec<IssueReference>,
+}
+
+/// A Pull Request that will close an issue once merged, as referenced from
+/// the issue's timeline.
+///
+#[derive(sqlx::FromRow, serde::Deserialize, serde::Serialize)]
+pub struct IssueFix {
+    pub issue_id: i64,
+    pub pr_id: i64,
+    pub number: i64,
+    pub title: String,
+    pub url: String,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub state: String,
+    pub linked_at: i64,
+}
+
+/// An issue or Pull Request, `source_id`, cross-referencing another,
+/// `target_id`, as seen from the latter's timeline.
+///
+#[derive(sqlx::FromRow, serde::Deserialize, serde::Serialize)]
+pub struct IssueReference {
+    pub target_id: i64,
+    pub source_id: i64,
+    pub number: i64,
+    pub title: String,
+    pub url: String,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub is_pull_request: bool,
+    pub state: String,
+    /// Whether the source will close the target once merged.
+    pub will_close: bool,
+    pub referenced_at: i64,
+    /// How many references away from the issue looked up the source is; 1
+    /// when it references the issue directly.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub depth: i64,
+}
+
+/// A repository's service level for issues with a given label: someone other
+/// than the author is to respond within `response_hours`.
+///
+#[derive(sqlx::FromRow, serde::Deserialize, serde::Serialize)]
+pub struct SlaRule {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub label: String,
+    pub response_hours: i64,
+}
+
+/// Where an issue stands against the strictest service level applying to it.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct SlaEntry {
+    pub issue_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub created_at: i64,
+    pub label: String,
+    pub due_at: i64,
+    pub first_response_at: Option<i64>,
+    /// One of `pending`, `at_risk`, `breached`, or `met`.
+    pub status: String,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -97,6 +369,47 @@ pub struct PullRequest {
     pub is_draft: bool,
     pub review_decision: String,
     pub merged_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub head_ref: String,
+    pub author_association: String,
+    pub review_requests: Vec<i64>,
+    /// Each reviewer's latest review.
+    pub latest_reviews: Vec<PullRequestReview>,
+    pub head_committed_at: Option<i64>,
+    pub ci_state: Option<String>,
+}
+
+/// A reviewer's latest review on a Pull Request.
+///
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct PullRequestReview {
+    pub user_id: i64,
+    pub login: String,
+    /// The review's state, lowercase; e.g., `approved`.
+    pub state: String,
+    pub submitted_at: i64,
+}
+
+/// The fields that changed on an existing issue or Pull Request.
+///
+#[derive(Clone, serde::Serialize)]
+pub struct IssueChange {
+    pub id: i64,
+    pub fields: Vec<String>,
+}
+
+/// What a write changed, row by row, so the frontend can update only what's
+/// affected. Refreshes never delete issues; closed ones show up as updated.
+///
+#[derive(Clone, Default, serde::Serialize)]
+pub struct IssuesDiff {
+    pub created: Vec<i64>,
+    pub updated: Vec<IssueChange>,
+}
+
+impl IssuesDiff {
+    pub fn is_empty(self: &Self) -> bool {
+        self.created.is_empty() && self.updated.is_empty()
+    }
 }
 
 pub struct UserUpdate {
@@ -104,3 +417,997 @@ pub struct UserUpdate {
     pub issues: Vec<Issue>,
     pub prs: Vec<PullRequest>,
 }
+
+// Accounts
+
+/// Describes an account, i.e., a user for which we hold an API token, and
+/// the host that token is valid for.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct Account {
+    pub id: i64,
+    pub login: String,
+    pub host: String,
+}
+
+/// A Pull Request Table Entry tagged with the account it was obtained
+/// through, formatted as `login@host`.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct AccountPullRequestEntry {
+    pub account: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub pr: PullRequestTableEntry,
+}
+
+/// Per-account badge counts.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct AccountCounts {
+    pub account: String,
+    pub authored: i64,
+    pub involved: i64,
+    pub unviewed: i64,
+}
+
+/// What the dashboard shows at once, read from a single snapshot so the
+/// badge counts always match the listed Pull Requests. Further pages of Pull
+/// Requests are obtained with `pulls.next_cursor`.
+///
+#[derive(serde::Serialize)]
+pub struct Dashboard {
+    pub pulls: crate::page::Page<AccountPullRequestEntry>,
+    pub counts: Vec<AccountCounts>,
+    pub sync_status: crate::throttle::SyncStatus,
+    /// When every tracked user was last refreshed by, if they all have been.
+    pub refreshed_at: Option<i64>,
+}
+
+// Organizations
+
+/// Describes an organization, as it is kept in the database.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct GithubOrg {
+    pub id: i64,
+    pub login: String,
+    pub avatar_url: String,
+}
+
+/// Describes a team, as it is kept in the database, along with the login of
+/// the organization it belongs to.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct GithubTeam {
+    pub id: i64,
+    pub org_id: i64,
+    pub org_login: String,
+    pub slug: String,
+    pub name: String,
+}
+
+/// Describes a member of a team.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct TeamMember {
+    pub team_id: i64,
+    pub user_id: i64,
+    pub login: String,
+}
+
+/// The result of obtaining a user's organizations and teams.
+///
+pub struct OrgUpdate {
+    pub when: chrono::DateTime<chrono::Utc>,
+    pub orgs: Vec<GithubOrg>,
+    pub teams: Vec<GithubTeam>,
+    pub members: Vec<TeamMember>,
+}
+
+/// Quick stats for an account's profile.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ProfileStats {
+    pub user_id: i64,
+    pub followers: i64,
+    pub following: i64,
+    pub public_repos: i64,
+    pub stars: i64,
+    pub updated_at: i64,
+}
+
+// Diagnostics
+
+/// The outcome of a single authentication probe.
+///
+#[derive(serde::Serialize)]
+pub struct AuthProbe {
+    pub name: String,
+    pub ok: bool,
+    pub status: Option<u16>,
+    pub detail: String,
+}
+
+/// Structured report on the health of an account's token.
+///
+#[derive(serde::Serialize)]
+pub struct AuthReport {
+    pub host: String,
+    pub login: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    pub missing_scopes: Vec<String>,
+    pub rate_limit_remaining: Option<i64>,
+    pub rate_limit_reset: Option<i64>,
+    pub probes: Vec<AuthProbe>,
+}
+
+// Activity
+
+/// An event in the activity feed. Events about a repository as a whole, such
+/// as releases, don't refer to an issue.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ActivityEvent {
+    /// Opaque ID, unique among events.
+    pub id: i64,
+    pub kind: String,
+    pub occurred_at: i64,
+    pub issue_id: Option<i64>,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: Option<i64>,
+    pub title: String,
+    pub url: String,
+}
+
+// Releases
+
+/// A draft of release notes for a repository.
+///
+#[derive(serde::Serialize)]
+pub struct ReleaseNotesDraft {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub since_tag: Option<String>,
+    pub since: Option<i64>,
+    pub num_prs: usize,
+    pub markdown: String,
+    pub release_url: Option<String>,
+}
+
+// Repositories
+
+/// Describes a maintained repository, as it is kept in the database, along
+/// with its primary language and topics as of its last branches sync.
+///
+#[derive(serde::Serialize)]
+pub struct Repository {
+    pub owner: String,
+    pub name: String,
+    pub default_branch: Option<String>,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+}
+
+/// A review request pending on one of our users for longer than its
+/// repository's reminder threshold.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct Reminder {
+    pub pr_id: i64,
+    pub user_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub url: String,
+    pub requested_at: i64,
+    pub reminded_at: Option<i64>,
+    pub snoozed_until: Option<i64>,
+}
+
+/// Describes how syncing a maintained repository is going. A repository is
+/// quarantined while `retry_at` is set, and only retried from then on.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct RepoSyncStatus {
+    pub owner: String,
+    pub name: String,
+    pub paused: bool,
+    pub failures: i64,
+    pub last_error: Option<String>,
+    pub failed_at: Option<i64>,
+    pub retry_at: Option<i64>,
+}
+
+/// Describes a repository belonging to an organization, as a candidate to
+/// be maintained.
+///
+#[derive(serde::Serialize)]
+pub struct OrgRepository {
+    pub owner: String,
+    pub name: String,
+    pub is_archived: bool,
+    pub pushed_at: Option<i64>,
+    pub open_prs: i64,
+    pub is_tracked: bool,
+}
+
+/// Describes a repository branch, along with the numbers of the open Pull
+/// Requests using it as their head.
+///
+#[derive(serde::Serialize)]
+pub struct RepoBranch {
+    pub name: String,
+    pub last_commit_at: Option<i64>,
+    pub pull_requests: Vec<i64>,
+}
+
+/// The result of obtaining a repository's branches.
+///
+pub struct RepoBranchesUpdate {
+    pub when: chrono::DateTime<chrono::Utc>,
+    pub default_branch: Option<String>,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub branches: Vec<RepoBranch>,
+}
+
+// Triage
+
+/// A triage rule, classifying newly synced issues of a repository into a
+/// bucket. All conditions that are set must match for the rule to apply.
+///
+#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct TriageRule {
+    #[serde(default)]
+    pub id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    #[serde(default)]
+    pub position: i64,
+    pub title_pattern: Option<String>,
+    pub label: Option<String>,
+    pub author: Option<String>,
+    pub bucket: String,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub notify: bool,
+}
+
+/// An action that may be run on an item, e.g. from a command palette.
+/// Actions without a `command` are carried out by the frontend itself.
+///
+#[derive(serde::Serialize)]
+pub struct Action {
+    pub id: String,
+    pub title: String,
+    pub command: Option<String>,
+    pub args: Vec<ActionArg>,
+}
+
+/// An argument to an action's command. Arguments with a `value` are already
+/// filled in; the remaining ones are asked of the user. `kind` is one of
+/// `string`, `text`, `integer`, or `enum`, the latter limited to `choices`.
+///
+#[derive(serde::Serialize)]
+pub struct ActionArg {
+    pub name: String,
+    pub kind: String,
+    pub required: bool,
+    pub choices: Vec<String>,
+    pub value: Option<serde_json::Value>,
+}
+
+/// A local tag, along with how many issues and Pull Requests have it.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct LocalTag {
+    pub tag: String,
+    pub count: i64,
+}
+
+/// An issue in the triage inbox, along with the bucket it was classified
+/// into.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct TriageEntry {
+    pub bucket: String,
+    pub rule_id: i64,
+    pub triaged_at: i64,
+    /// Whether the Pull Request's body is empty, if checked against its
+    /// repository's template.
+    #[sqlx(default)]
+    pub template_empty: bool,
+    /// The newline-separated template sections the Pull Request's body is
+    /// missing, if any.
+    #[sqlx(default)]
+    pub template_missing: Option<String>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub issue: IssueTableEntry,
+}
+
+// Releases
+
+/// A published release of a maintained repository.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct Release {
+    pub id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub url: String,
+    pub published_at: i64,
+}
+
+// Advisories
+
+/// A security advisory affecting a dependency of a tracked repository.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct AdvisoryFinding {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub ghsa_id: String,
+    pub ecosystem: String,
+    pub package: String,
+    pub version: String,
+    pub severity: String,
+    pub summary: String,
+    pub url: String,
+    pub vulnerable_range: Option<String>,
+    pub patched_version: Option<String>,
+    pub found_at: i64,
+}
+
+// Identities
+
+/// A human, along with the users they own across hosts.
+///
+#[derive(serde::Serialize)]
+pub struct Identity {
+    pub id: i64,
+    pub name: String,
+    pub users: Vec<IdentityUser>,
+}
+
+/// A user linked to an identity, by host and login. The user's details are
+/// only known if it is tracked.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct IdentityUser {
+    pub host: String,
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// A period during which we were away.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct AwayPeriod {
+    pub id: i64,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+}
+
+/// What happened while we were away.
+///
+#[derive(serde::Serialize)]
+pub struct CatchUpSummary {
+    pub period: AwayPeriod,
+    pub merged: Vec<PullRequestTableEntry>,
+    pub review_requested: Vec<PullRequestTableEntry>,
+    pub stale: Vec<PullRequestTableEntry>,
+}
+
+/// A write action journaled in the outbox. `payload` holds the action as
+/// JSON; `state` is one of `pending`, `done`, `failed`, or `discarded`.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub kind: String,
+    pub pr_id: i64,
+    pub payload: String,
+    pub state: String,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+/// API usage for a feature on behalf of an account, aggregated over a period
+/// of time.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ApiUsageEntry {
+    pub account_id: Option<i64>,
+    pub account: Option<String>,
+    pub host: String,
+    pub feature: String,
+    pub resource: Option<String>,
+    pub calls: i64,
+    pub cost: i64,
+    pub min_remaining: Option<i64>,
+    pub last_called_at: i64,
+}
+
+/// API calls made on behalf of an account during an hour.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct HourlyUsage {
+    pub account_id: Option<i64>,
+    pub account: Option<String>,
+    pub host: String,
+    /// The hour's start, as a UTC epoch timestamp.
+    pub hour: i64,
+    pub calls: i64,
+    pub cost: i64,
+}
+
+/// An hour during which an account made far more API calls than usual; e.g.,
+/// because a sync got stuck in a loop.
+///
+#[derive(Clone, sqlx::FromRow, serde::Serialize)]
+pub struct UsageAnomaly {
+    pub id: i64,
+    pub account_id: Option<i64>,
+    pub account: Option<String>,
+    pub host: String,
+    pub hour: i64,
+    pub calls: i64,
+    /// The calls usually made in an hour.
+    pub baseline: i64,
+    pub detected_at: i64,
+}
+
+/// The state of a rate limit resource for an account: the points consumed
+/// over the last hour, and those remaining as last reported by GitHub.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct RateLimitStatus {
+    pub account_id: Option<i64>,
+    pub account: Option<String>,
+    pub host: String,
+    pub resource: Option<String>,
+    pub consumed: i64,
+    pub remaining: Option<i64>,
+    pub last_called_at: i64,
+}
+
+// Dependencies
+
+/// An issue or Pull Request in a dependency graph. References to items we
+/// haven't cached only carry what the reference itself tells.
+///
+#[derive(sqlx::FromRow, serde::Serialize, Clone)]
+pub struct DependencyNode {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub id: Option<i64>,
+    pub title: Option<String>,
+    pub state: Option<String>,
+    pub url: Option<String>,
+}
+
+/// `from` depends on `to`, both being indices into the graph's nodes.
+///
+#[derive(serde::Serialize)]
+pub struct DependencyEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// The dependency graph of an item, its first node: everything it
+/// transitively depends on, along with what directly depends on it.
+///
+#[derive(serde::Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+}
+
+// Views
+
+/// A saved view on the Pull Requests of a repository touching files matching
+/// any of its globs.
+///
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PathView {
+    #[serde(default)]
+    pub id: i64,
+    pub name: String,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub globs: Vec<String>,
+}
+
+// History
+
+/// How far back a tracked user's history has been synced, and the progress
+/// of their backfill, if one was requested. Users whose history has never
+/// been limited have no `synced_from`.
+///
+#[derive(sqlx::FromRow, serde::Serialize, Clone)]
+pub struct UserHistory {
+    pub user_id: i64,
+    pub login: String,
+    pub synced_from: Option<i64>,
+    pub backfill_from: Option<i64>,
+    pub backfill_to: Option<i64>,
+    /// Everything from here up to `backfill_to` has been backfilled.
+    pub backfill_cursor: Option<i64>,
+    pub backfill_items: i64,
+    /// Chunks that hit the search results cap even at their smallest, some
+    /// of their issues having been left out.
+    pub backfill_truncated: i64,
+    pub backfill_finished_at: Option<i64>,
+}
+
+/// The cached patch of a Pull Request, decompressed, as downloaded in
+/// `format`; i.e., `diff` or `patch`.
+///
+#[derive(serde::Serialize)]
+pub struct PullRequestPatch {
+    pub pr_id: i64,
+    pub format: String,
+    pub text: String,
+    pub fetched_at: i64,
+}
+
+/// A line of a diff hunk: its kind, one of `context`, `addition` or
+/// `deletion`, and its number on either side of the diff it's on.
+///
+#[derive(serde::Serialize, Clone)]
+pub struct DiffLine {
+    pub kind: String,
+    pub content: String,
+    pub old_line: Option<i64>,
+    pub new_line: Option<i64>,
+}
+
+/// A hunk of a file's diff, with the text following its range, usually the
+/// enclosing function, as `header`.
+///
+#[derive(serde::Serialize, Clone)]
+pub struct DiffHunk {
+    pub old_start: i64,
+    pub old_lines: i64,
+    pub new_start: i64,
+    pub new_lines: i64,
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// The parsed diff of a file changed by a Pull Request. Renamed files have
+/// the path they had as `old_path`; binary files have no hunks.
+///
+#[derive(serde::Serialize, Clone)]
+pub struct FileDiff {
+    pub pr_id: i64,
+    pub path: String,
+    pub old_path: Option<String>,
+    pub is_binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A pending inline comment of a draft review, on `line` of `path` on
+/// `side` of the diff, `LEFT` or `RIGHT`.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct DraftReviewComment {
+    pub id: i64,
+    pub pr_id: i64,
+    pub path: String,
+    pub line: i64,
+    pub side: String,
+    pub body: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A review of a Pull Request being written locally, until submitted as a
+/// whole.
+///
+#[derive(serde::Serialize)]
+pub struct DraftReview {
+    pub pr_id: i64,
+    pub body: String,
+    pub updated_at: i64,
+    pub comments: Vec<DraftReviewComment>,
+}
+
+/// How many times a reaction, e.g. `+1`, was left on an issue, Pull Request
+/// or comment, and whether we're among those who did.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ReactionCount {
+    pub content: String,
+    pub count: i64,
+    pub reacted: bool,
+}
+
+/// An issue or Pull Request found by a local search, and whether it was
+/// only found as a fuzzy match.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct SearchResult {
+    pub id: i64,
+    pub number: i64,
+    pub title: String,
+    pub author: String,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub state: String,
+    pub is_pull_request: bool,
+    pub updated_at: i64,
+    #[sqlx(default)]
+    pub fuzzy: bool,
+}
+
+/// An organization whose open Pull Requests are all synced, for an admin
+/// overview, along with the user it's synced as.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct OrgScope {
+    pub org_id: i64,
+    pub org_login: String,
+    pub user_id: i64,
+    pub enabled_at: i64,
+    pub synced_at: Option<i64>,
+    /// Whether there were more open Pull Requests than the search returns.
+    pub capped: bool,
+}
+
+/// The result of obtaining an organization's open Pull Requests, along with
+/// all its teams and their rosters.
+///
+pub struct OrgScopeUpdate {
+    pub when: chrono::DateTime<chrono::Utc>,
+    pub org_id: i64,
+    pub prs: Vec<PullRequest>,
+    pub teams: Vec<GithubTeam>,
+    pub members: Vec<TeamMember>,
+    pub capped: bool,
+}
+
+/// Open Pull Request counts for a repository in an organization overview.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct OrgRepoCounts {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub open: i64,
+    pub drafts: i64,
+    pub approved: i64,
+    pub changes_requested: i64,
+    pub review_required: i64,
+    pub oldest_created_at: i64,
+}
+
+/// Open Pull Request counts for a team in an organization overview, going by
+/// the Pull Requests authored by its members.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct OrgTeamCounts {
+    pub team_id: i64,
+    pub slug: String,
+    pub name: String,
+    pub members: i64,
+    pub open: i64,
+    pub drafts: i64,
+    pub approved: i64,
+    pub changes_requested: i64,
+}
+
+/// An overview of an organization's open Pull Requests, by repository and by
+/// team.
+///
+#[derive(serde::Serialize)]
+pub struct OrgOverview {
+    pub scope: OrgScope,
+    pub open: i64,
+    pub repos: Vec<OrgRepoCounts>,
+    pub teams: Vec<OrgTeamCounts>,
+}
+
+/// An account's permission level on a repository, one of `read`, `triage`,
+/// `write`, `maintain` or `admin`, as last obtained.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct RepoPermission {
+    pub account_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub permission: String,
+    pub fetched_at: i64,
+}
+
+/// A label defined on a repository, as last obtained. `color` is a hex RGB
+/// value, without the leading `#`.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct RepoLabel {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub name: String,
+    pub color: String,
+    pub description: Option<String>,
+}
+
+/// The merge settings of a repository, as last obtained. Linear history is
+/// as required by its default branch.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct MergePolicy {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub default_branch: String,
+    pub allow_merge_commit: bool,
+    pub allow_squash_merge: bool,
+    pub allow_rebase_merge: bool,
+    pub requires_linear_history: bool,
+    pub fetched_at: i64,
+}
+
+/// A review one of our users started on a Pull Request, on GitHub, but never
+/// submitted. Pending reviews are only visible to their author, so they're
+/// easy to forget about.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct PendingReview {
+    pub review_id: i64,
+    pub user_id: i64,
+    pub pr_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub url: String,
+    pub comments: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A comment one of our users posted on an open issue or Pull Request that
+/// nobody has replied to since, so questions asked don't go unanswered.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct FollowUp {
+    pub comment_id: i64,
+    pub user_id: i64,
+    pub issue_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub url: String,
+    pub comment_url: String,
+    pub commented_at: i64,
+}
+
+/// An issue or Pull Request on a maintained repository mentioning one of our
+/// users' teams, e.g. `@org/team`.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct TeamMention {
+    pub team_id: i64,
+    pub org_login: String,
+    pub team_slug: String,
+    pub issue_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    pub is_pull_request: bool,
+    pub state: String,
+    pub updated_at: i64,
+}
+
+/// A snippet to reply to Pull Requests with, e.g. "LGTM with nits". Its body
+/// may hold placeholders, filled in for the Pull Request replied to.
+///
+#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct ReplyTemplate {
+    #[serde(default)]
+    pub id: i64,
+    pub name: String,
+    pub body: String,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+/// Time spent reviewing a Pull Request, from being started until being
+/// stopped; still running if not stopped yet.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ReviewSession {
+    pub id: i64,
+    pub pr_id: i64,
+    pub started_at: i64,
+    pub stopped_at: Option<i64>,
+}
+
+/// Time spent reviewing a Pull Request over a week, starting on Monday.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ReviewTime {
+    pub week_start: i64,
+    pub pr_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub sessions: i64,
+    pub seconds: i64,
+}
+
+/// A subscription to a label being added to or removed from an issue, e.g.
+/// `status:needs-info` being removed, checked whenever the issue is synced.
+///
+#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct LabelAlert {
+    #[serde(default)]
+    pub id: i64,
+    pub issue_id: i64,
+    pub label: String,
+    /// One of `added`, `removed`, or `any`.
+    pub on_change: String,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub triggered_at: Option<i64>,
+}
+
+/// A list obtained for an issue or Pull Request that was cut short for being
+/// longer than allowed; e.g., the files changed by a huge Pull Request.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct Truncation {
+    pub issue_id: i64,
+    pub kind: String,
+    pub kept: i64,
+    pub truncated_at: i64,
+}
+
+/// A public user or repository followed without a token, in anonymous mode.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct AnonTarget {
+    pub id: i64,
+    /// Either `user` or `repo`.
+    pub kind: String,
+    /// The user's login, or the repository's `owner/name`.
+    pub name: String,
+    pub added_at: i64,
+    pub synced_at: Option<i64>,
+}
+
+/// A Pull Request obtained in anonymous mode for one of the followed users
+/// or repositories. Only what the search API returns is known about it.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct AnonPullRequest {
+    pub target_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub author: String,
+    pub state: String,
+    pub is_draft: bool,
+    pub url: String,
+    pub updated_at: i64,
+}
+
+/// An open Pull Request opened by a dependency update bot, e.g. Dependabot
+/// or Renovate, as parsed from its title.
+///
+#[derive(serde::Serialize)]
+pub struct DependencyUpdate {
+    pub pr_id: i64,
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub number: i64,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    pub review_decision: String,
+    pub dependency: String,
+    /// Not all bots say what version is being updated from.
+    pub from_version: Option<String>,
+    pub to_version: String,
+    /// One of `major`, `minor`, `patch`, or `unknown`.
+    pub bump: String,
+}
+
+/// Dependency update Pull Requests across repositories bumping the same
+/// dependency to the same version.
+///
+#[derive(serde::Serialize)]
+pub struct DependencyUpdateGroup {
+    pub dependency: String,
+    pub to_version: String,
+    /// The largest bump among the group's Pull Requests.
+    pub bump: String,
+    pub updates: Vec<DependencyUpdate>,
+}
+
+/// The outcome of approving dependency update Pull Requests in bulk.
+///
+#[derive(serde::Serialize)]
+pub struct DependencyApproval {
+    pub approved: Vec<i64>,
+    pub failed: Vec<i64>,
+}
+
+/// Credit for contributions to maintained repositories, per contributor, in
+/// Pull Requests: authored and merged, co-authored, reviewed, and approved.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct ContributionCredit {
+    /// The contributor's login, or name if only known as a co-author.
+    pub contributor: String,
+    pub authored: i64,
+    pub co_authored: i64,
+    pub reviewed: i64,
+    pub approved: i64,
+}
+
+/// A local clone of a tracked repository, registered by the user so the Pull
+/// Request for the branch being worked on can be found.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct Workspace {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub path: String,
+    pub added_at: i64,
+}
+
+/// What a workspace currently has checked out, and the open Pull Request for
+/// it, if any.
+///
+#[derive(serde::Serialize)]
+pub struct WorkspaceHead {
+    pub workspace: Workspace,
+    /// The branch checked out, or `None` if the `HEAD` is detached.
+    pub branch: Option<String>,
+    pub pull_request: Option<PullRequestTableEntry>,
+}
+
+/// A local clone found when scanning the projects directory, proposing the
+/// repository its remote points at for tracking.
+///
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct WorkspaceCandidate {
+    pub owner: String,
+    pub name: String,
+    pub path: String,
+    /// The remote the repository was obtained from.
+    pub remote: String,
+    #[serde(default)]
+    pub is_tracked: bool,
+    #[serde(default)]
+    pub is_registered: bool,
+}
+
+/// How long Pull Requests of a size class took to be merged, in seconds since
+/// being opened.
+///
+#[derive(serde::Serialize)]
+pub struct SizeStats {
+    pub size_class: String,
+    pub merged: i64,
+    pub median_time_to_merge: Option<i64>,
+    pub mean_time_to_merge: Option<i64>,
+}
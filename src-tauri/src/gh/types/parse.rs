@@ -0,0 +1,213 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+/// Value stored for states and decisions we don't recognize.
+///
+pub const UNKNOWN: &str = "unknown";
+
+/// A timestamp as obtained from GitHub. Deserializing never fails: timestamps
+/// that can't be parsed keep their raw value, so that one odd item doesn't
+/// fail the whole response.
+///
+#[derive(Debug, Clone)]
+pub struct Timestamp {
+    pub raw: String,
+    pub value: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let raw: String = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Timestamp {
+            value: timestamp(&raw),
+            raw,
+        })
+    }
+}
+
+/// Raw values we didn't recognize while parsing an item, by field, as stored
+/// in its `unknown_raw` column.
+///
+#[derive(Default)]
+pub struct Unknowns {
+    fields: BTreeMap<String, String>,
+}
+
+impl Unknowns {
+    /// Obtain the unknown values previously stored in an `unknown_raw`
+    /// column, if any.
+    ///
+    pub fn from_column(column: &Option<String>) -> Self {
+        Unknowns {
+            fields: column
+                .as_ref()
+                .and_then(|c| serde_json::from_str(&c).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn add(self: &mut Self, field: &str, raw: &String) {
+        println!("unknown value for '{}': {}", field, raw);
+        self.fields.insert(field.to_string(), raw.clone());
+    }
+
+    /// Obtain the value for the `unknown_raw` column, if anything was unknown.
+    ///
+    pub fn to_column(self: &Self) -> Option<String> {
+        if self.fields.is_empty() {
+            return None;
+        }
+        Some(serde_json::to_string(&self.fields).unwrap())
+    }
+}
+
+/// Parse a timestamp in any of the formats GitHub has been seen using,
+/// RFC 3339 being the norm.
+///
+/// # Arguments
+///
+/// * `raw` - The timestamp to parse.
+///
+pub fn timestamp(raw: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let raw = raw.trim();
+    if let Ok(res) = chrono::DateTime::parse_from_rfc3339(&raw) {
+        return Some(res.with_timezone(&chrono::Utc));
+    }
+    for fmt in [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M:%S UTC",
+    ] {
+        if let Ok(res) = chrono::NaiveDateTime::parse_from_str(&raw, &fmt) {
+            return Some(chrono::DateTime::from_utc(res, chrono::Utc));
+        }
+    }
+    match raw.parse::<i64>() {
+        Ok(ts) => chrono::NaiveDateTime::from_timestamp_opt(ts, 0)
+            .map(|res| chrono::DateTime::from_utc(res, chrono::Utc)),
+        Err(_) => None,
+    }
+}
+
+/// Obtain the value of a required timestamp, falling back to the epoch and
+/// recording the raw value if it couldn't be parsed.
+///
+/// # Arguments
+///
+/// * `ts` - The timestamp as obtained from GitHub.
+/// * `field` - The field the timestamp is for.
+/// * `unknowns` - Where to record the raw value should it not be parsed.
+///
+pub fn required_timestamp(
+    ts: &Timestamp,
+    field: &str,
+    unknowns: &mut Unknowns,
+) -> chrono::DateTime<chrono::Utc> {
+    match ts.value {
+        Some(res) => res,
+        None => {
+            unknowns.add(&field, &ts.raw);
+            chrono::DateTime::from_utc(
+                chrono::NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+                chrono::Utc,
+            )
+        }
+    }
+}
+
+/// Obtain the value of an optional timestamp, recording the raw value if it
+/// couldn't be parsed.
+///
+/// # Arguments
+///
+/// * `ts` - The timestamp as obtained from GitHub, if any.
+/// * `field` - The field the timestamp is for.
+/// * `unknowns` - Where to record the raw value should it not be parsed.
+///
+pub fn optional_timestamp(
+    ts: &Option<Timestamp>,
+    field: &str,
+    unknowns: &mut Unknowns,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    match ts {
+        None => None,
+        Some(Timestamp { value: None, raw }) => {
+            unknowns.add(&field, &raw);
+            None
+        }
+        Some(Timestamp { value, .. }) => *value,
+    }
+}
+
+/// Obtain the lowercase representation of a GraphQL enum value, provided it
+/// is one of `known`; otherwise, `UNKNOWN`, recording the raw value.
+///
+fn enum_value<T: serde::Serialize>(
+    value: &T,
+    known: &[&str],
+    field: &str,
+    unknowns: &mut Unknowns,
+) -> String {
+    let raw = match serde_json::to_value(&value) {
+        Ok(serde_json::Value::String(res)) => res,
+        Ok(res) => res.to_string(),
+        Err(_) => String::new(),
+    };
+    let lower = raw.to_lowercase();
+    if known.contains(&lower.as_str()) {
+        return lower;
+    }
+    unknowns.add(&field, &raw);
+    String::from(UNKNOWN)
+}
+
+/// Parse an issue or Pull Request state.
+///
+/// # Arguments
+///
+/// * `state` - The state as obtained from GitHub.
+/// * `unknowns` - Where to record the raw value should it not be known.
+///
+pub fn state<T: serde::Serialize>(
+    state: &T,
+    unknowns: &mut Unknowns,
+) -> String {
+    enum_value(&state, &["open", "closed", "merged"], "state", unknowns)
+}
+
+/// Parse a Pull Request review decision, which is `none` if there's none.
+///
+/// # Arguments
+///
+/// * `decision` - The decision as obtained from GitHub, if any.
+/// * `unknowns` - Where to record the raw value should it not be known.
+///
+pub fn review_decision<T: serde::Serialize>(
+    decision: &Option<T>,
+    unknowns: &mut Unknowns,
+) -> String {
+    match decision {
+        None => String::from("none"),
+        Some(d) => enum_value(
+            &d,
+            &["approved", "changes_requested", "review_required"],
+            "review_decision",
+            unknowns,
+        ),
+    }
+}
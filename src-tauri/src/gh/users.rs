@@ -22,9 +22,14 @@ use super::{rest, types::GithubUser};
 /// # Arguments
 ///
 /// * `token` - String containing an API Token.
+/// * `host` - String containing the host the token is valid for.
 ///
-pub async fn whoami(token: &String) -> Result<GithubUser, reqwest::StatusCode> {
-    let ghreq = rest::GithubRequest::new(token);
+pub async fn whoami(
+    token: &String,
+    host: &String,
+) -> Result<GithubUser, reqwest::StatusCode> {
+    let ghreq =
+        rest::GithubRequest::new_for_host(token, host).for_feature("users");
     let req = ghreq.get("/user");
     match ghreq.send::<rest::GithubUserReply>(req).await {
         Ok(res) => Ok(user_reply_to_user(res)),
@@ -112,6 +117,74 @@ pub async fn update_user_refresh(
         });
 }
 
+/// Remove a tracked user from the GHD database, along with everything synced
+/// on their behalf. Issues and Pull Requests remain cached. This function
+/// requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `userid` - The ID of the user being removed.
+///
+pub async fn remove_user_from_db(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    userid: &i64,
+) {
+    let tables = [
+        ("user_refresh", "id"),
+        ("user_history", "user_id"),
+        ("user_issues", "user_id"),
+        ("user_issue_relations", "user_id"),
+        ("user_accounts", "user_id"),
+        ("pending_reviews", "user_id"),
+        ("follow_ups", "user_id"),
+        ("review_reminders", "user_id"),
+        ("profile_stats", "user_id"),
+        ("user_orgs", "user_id"),
+        ("user_teams", "user_id"),
+        ("org_scopes", "user_id"),
+        ("users", "id"),
+    ];
+    for (table, column) in tables {
+        sqlx::query(&format!("DELETE FROM {} WHERE {} = ?", table, column))
+            .bind(&userid)
+            .execute(&mut *tx)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Error removing user {} from '{}': {}",
+                    userid, table, err
+                );
+            });
+    }
+}
+
+/// Check whether user `userid` owns one of our accounts.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `userid` - The user's ID.
+///
+pub async fn is_account_owner(db: &DB, userid: &i64) -> bool {
+    match sqlx::query_scalar::<_, i64>(
+        "
+        SELECT COUNT(*) FROM accounts WHERE user_id = ?1
+        UNION ALL
+        SELECT COUNT(*) FROM tokens WHERE user_id = ?1
+        ",
+    )
+    .bind(&userid)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res.iter().any(|n| *n > 0),
+        Err(err) => {
+            panic!("Unable to check accounts of user {}: {}", userid, err);
+        }
+    }
+}
+
 /// Obtain GHD's main user.
 ///
 /// # Arguments
@@ -0,0 +1,228 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    db::DB,
+    errors::GHDError,
+    page::{Page, PageRequest},
+};
+
+use super::types::{PathView, PullRequestTableEntry};
+
+/// Save a new path view, returning its ID. Globs follow SQLite's `GLOB`
+/// syntax, where `*` also matches across directories; e.g.,
+/// `services/auth/**` matches everything under `services/auth/`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `view` - The view to save. Its `id` is ignored.
+///
+pub async fn add_view(db: &DB, view: &PathView) -> Result<i64, GHDError> {
+    if view.globs.iter().all(|g| g.trim().is_empty()) {
+        println!("path view '{}' has no globs", view.name);
+        return Err(GHDError::BadRequest);
+    }
+
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to add view: {}", err);
+        }
+    };
+    let id = match sqlx::query(
+        "
+        INSERT INTO path_views (name, repo_owner, repo_name)
+        VALUES (?, ?, ?)
+        ",
+    )
+    .bind(&view.name)
+    .bind(&view.repo_owner)
+    .bind(&view.repo_name)
+    .execute(&mut tx)
+    .await
+    {
+        Ok(res) => res.last_insert_rowid(),
+        Err(err) => {
+            panic!("Unable to add path view '{}': {}", view.name, err);
+        }
+    };
+
+    for glob in view
+        .globs
+        .iter()
+        .map(|g| g.trim())
+        .filter(|g| !g.is_empty())
+    {
+        sqlx::query(
+            "INSERT OR IGNORE INTO path_view_globs (view_id, glob) VALUES (?, ?)",
+        )
+        .bind(&id)
+        .bind(&glob)
+        .execute(&mut tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to add glob to path view {}: {}", id, err);
+        });
+    }
+
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit transaction to add view: {}", err);
+    });
+    Ok(id)
+}
+
+/// Remove path view `viewid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `viewid` - The view's ID.
+///
+pub async fn remove_view(db: &DB, viewid: &i64) {
+    for q in [
+        "DELETE FROM path_view_globs WHERE view_id = ?",
+        "DELETE FROM path_views WHERE id = ?",
+    ] {
+        sqlx::query(q)
+            .bind(&viewid)
+            .execute(db.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to remove path view {}: {}", viewid, err);
+            });
+    }
+}
+
+/// Obtain all saved path views, by repository and name.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_views(db: &DB) -> Result<Vec<PathView>, GHDError> {
+    let views = match sqlx::query_as::<_, (i64, String, String, String)>(
+        "
+        SELECT id, name, repo_owner, repo_name FROM path_views
+        ORDER BY repo_owner, repo_name, name
+        ",
+    )
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain path views: {}", err);
+        }
+    };
+
+    let mut res = vec![];
+    for (id, name, repo_owner, repo_name) in views {
+        let globs = match sqlx::query_scalar::<_, String>(
+            "SELECT glob FROM path_view_globs WHERE view_id = ? ORDER BY glob",
+        )
+        .bind(&id)
+        .fetch_all(db.pool())
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                panic!("Unable to obtain globs of path view {}: {}", id, err);
+            }
+        };
+        res.push(PathView {
+            id,
+            name,
+            repo_owner,
+            repo_name,
+            globs,
+        });
+    }
+    Ok(res)
+}
+
+/// Obtain a page of the Pull Requests in path view `viewid`, i.e. those to
+/// its repository touching files matching any of its globs, as far as their
+/// changed files have been cached; most recently updated first.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `viewid` - The view's ID.
+/// * `page` - The page to obtain.
+///
+pub async fn get_prs(
+    db: &DB,
+    viewid: &i64,
+    page: &PageRequest,
+) -> Result<Page<PullRequestTableEntry>, GHDError> {
+    match sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM path_views WHERE id = ?",
+    )
+    .bind(&viewid)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(0) => return Err(GHDError::NotFoundError),
+        Ok(_) => {}
+        Err(err) => {
+            panic!("Unable to obtain path view {}: {}", viewid, err);
+        }
+    };
+
+    let query = format!(
+        "
+        SELECT
+            issues.*, pull_requests.is_draft, pull_requests.review_decision,
+            pull_requests.merged_at
+        FROM
+            pull_requests
+            INNER JOIN issues ON pull_requests.id = issues.id
+            INNER JOIN path_views
+                ON path_views.repo_owner = issues.repo_owner
+                AND path_views.repo_name = issues.repo_name
+        WHERE
+            path_views.id = ?1
+            AND issues.state != 'deleted_upstream'
+            AND issues.id IN (
+                SELECT pr_files.pr_id
+                FROM pr_files JOIN path_view_globs
+                    ON pr_files.path GLOB path_view_globs.glob
+                WHERE path_view_globs.view_id = ?1
+            )
+            AND {}
+        ORDER BY issues.updated_at DESC, issues.id DESC
+        LIMIT ?4
+        ",
+        PageRequest::numbered_condition("issues.updated_at", "issues.id", 2)
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, PullRequestTableEntry>(&query)
+        .bind(&viewid)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => Ok(page.into_page(res, |pr| (pr.updated_at, pr.id))),
+        Err(err) => {
+            panic!(
+                "Unable to obtain pull requests in view {}: {}",
+                viewid, err
+            );
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    db::DB,
+    page::{Page, PageRequest},
+};
+
+/// Setting enabling the sync journal. Off by default, as it only grows.
+///
+pub const SETTING_ENABLED: &str = "journal.enabled";
+
+/// A sync, as recorded in the journal. `changes` holds a JSON object with
+/// the rows written for each part of the sync, as counted by SQLite; e.g.,
+/// `{"issues": 3}`.
+///
+#[derive(sqlx::FromRow, serde::Serialize)]
+pub struct SyncJournalEntry {
+    pub id: i64,
+    pub source: String,
+    pub started_at: i64,
+    pub duration_ms: i64,
+    pub changes: String,
+    pub error: Option<String>,
+}
+
+/// A sync being journaled, from its start until it's about to commit.
+///
+pub struct Sync {
+    source: String,
+    started_at: i64,
+    started: std::time::Instant,
+    changes: serde_json::Map<String, serde_json::Value>,
+    /// Rows written through the sync's connection as of the last count.
+    mark: i64,
+}
+
+/// Obtain how many rows have been written through `tx`'s connection since
+/// it was opened, as counted by SQLite.
+///
+async fn total_changes(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>) -> i64 {
+    match sqlx::query_scalar::<_, i64>("SELECT total_changes()")
+        .fetch_one(&mut *tx)
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain rows written: {}", err);
+        }
+    }
+}
+
+impl Sync {
+    /// Start journaling a sync of `source`; e.g., `user:login`.
+    ///
+    pub fn start(source: String) -> Self {
+        Sync {
+            source,
+            started_at: chrono::Utc::now().timestamp(),
+            started: std::time::Instant::now(),
+            changes: serde_json::Map::new(),
+            mark: 0,
+        }
+    }
+
+    /// Start counting the rows written through `tx`, the sync's
+    /// transaction, for `count_changes()` to attribute them.
+    ///
+    pub async fn begin(
+        self: &mut Self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    ) {
+        self.mark = total_changes(tx).await;
+    }
+
+    /// Record that `n` rows were written for `what`, outside the sync's
+    /// transaction.
+    ///
+    pub fn count(self: &mut Self, what: &str, n: usize) {
+        let prev = self.changes.get(what).and_then(|v| v.as_u64());
+        self.changes.insert(
+            what.to_string(),
+            serde_json::Value::from(prev.unwrap_or(0) + n as u64),
+        );
+    }
+
+    /// Record the rows written through `tx` since the last count, or since
+    /// `begin()`, as written for `what`; e.g., `issues`.
+    ///
+    pub async fn count_changes(
+        self: &mut Self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        what: &str,
+    ) {
+        let total = total_changes(tx).await;
+        self.count(what, (total - self.mark) as usize);
+        self.mark = total;
+    }
+
+    /// Append the sync to the journal, if enabled, along with the error it
+    /// failed with, if any. Recorded in the sync's own transaction, so only
+    /// syncs that made it to the database are journaled.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The sqlx transaction to piggy-back on.
+    /// * `error` - What the sync failed with, if it did.
+    ///
+    pub async fn finish(
+        self: Self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        error: Option<String>,
+    ) {
+        let enabled = match sqlx::query_scalar::<_, String>(
+            "SELECT value FROM settings WHERE key = ?",
+        )
+        .bind(SETTING_ENABLED)
+        .fetch_optional(&mut *tx)
+        .await
+        {
+            Ok(res) => res.as_deref() == Some("true"),
+            Err(err) => {
+                panic!(
+                    "Unable to obtain setting '{}': {}",
+                    SETTING_ENABLED, err
+                );
+            }
+        };
+        if !enabled {
+            return;
+        }
+
+        sqlx::query(
+            "
+            INSERT INTO sync_journal (
+                source, started_at, duration_ms, changes, error
+            ) VALUES (?, ?, ?, ?, ?)
+            ",
+        )
+        .bind(&self.source)
+        .bind(&self.started_at)
+        .bind(self.started.elapsed().as_millis() as i64)
+        .bind(serde_json::Value::Object(self.changes).to_string())
+        .bind(&error)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to journal sync of '{}': {}", self.source, err);
+        });
+    }
+}
+
+/// Obtain a page of the sync journal, most recent first, optionally limited
+/// to sources starting with `source`; e.g., `user:` or `branches:owner/`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `source` - Prefix the sources must start with, if provided.
+/// * `page` - The page to obtain.
+///
+pub async fn get_history(
+    db: &DB,
+    source: &Option<String>,
+    page: &PageRequest,
+) -> Page<SyncJournalEntry> {
+    let query = format!(
+        "
+        SELECT * FROM sync_journal
+        WHERE (?1 IS NULL OR substr(source, 1, length(?1)) = ?1) AND {}
+        ORDER BY started_at DESC, id DESC
+        LIMIT ?4
+        ",
+        PageRequest::numbered_condition("started_at", "id", 2)
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, SyncJournalEntry>(&query)
+        .bind(&source)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => page.into_page(res, |e| (e.started_at, e.id)),
+        Err(err) => {
+            panic!("Unable to obtain sync journal: {}", err);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod bg;
+pub mod common;
+pub mod config;
+pub mod db;
+pub mod errors;
+pub mod events;
+pub mod gh;
+pub mod gh_types;
+pub mod journal;
+pub mod mail;
+pub mod notify;
+pub mod page;
+pub mod paths;
+pub mod permissions;
+pub mod querylog;
+pub mod state;
+pub mod store;
+pub mod throttle;
+pub mod updates;
+pub mod vault;
+
+pub struct ManagedState {
+    pub state: std::sync::Arc<tokio::sync::Mutex<state::State>>,
+    /// What upgrading the database changes, should it need upgrading. Kept
+    /// out of the state, which the upgrade holds until it's done.
+    pub migration: std::sync::Mutex<Option<db::MigrationReport>>,
+}
+
+impl ManagedState {
+    pub async fn state(
+        self: &Self,
+    ) -> tokio::sync::MutexGuard<'_, state::State> {
+        self.state.lock().await
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::Timelike;
+use tokio::io::AsyncWriteExt;
+
+use crate::{config, db::DB, errors::GHDError, gh::report};
+
+/// Setting holding the address daily digests are mailed to. Digests are
+/// only mailed if set.
+///
+pub const SETTING_DIGEST_TO: &str = "mail.digest_to";
+
+/// Setting holding the address daily digests are mailed from, if not left
+/// to the mail system.
+///
+pub const SETTING_DIGEST_FROM: &str = "mail.digest_from";
+
+/// Setting holding the local hour, `0` to `23`, from which the daily digest
+/// is mailed.
+///
+pub const SETTING_DIGEST_HOUR: &str = "mail.digest_hour";
+
+/// Setting holding the `sendmail`-compatible command digests are handed off
+/// to; e.g., `/usr/sbin/sendmail` or `msmtp`.
+///
+pub const SETTING_SENDMAIL: &str = "mail.sendmail";
+
+/// Setting holding when the last digest was mailed.
+///
+pub const SETTING_LAST_DIGEST: &str = "mail.last_digest_at";
+
+const DEFAULT_DIGEST_HOUR: u32 = 8;
+const DEFAULT_SENDMAIL: &str = "sendmail";
+
+/// How long `sendmail` is given to accept a message before it's killed, so a
+/// stuck mail system doesn't hold the digest up forever.
+///
+const SENDMAIL_TIMEOUT_SECS: u64 = 60;
+
+/// Check whether `address` may be used in a mail header: it must look like
+/// an address, and must not smuggle in headers of its own.
+///
+fn is_valid_address(address: &str) -> bool {
+    address.contains('@') && !address.contains(['\r', '\n', ',', ';'])
+}
+
+/// Compose a plain text message, ready to be handed off to `sendmail -t`.
+///
+fn compose(
+    from: &Option<String>,
+    to: &String,
+    subject: &String,
+    body: &String,
+) -> String {
+    let mut msg = String::new();
+    if let Some(from) = from {
+        msg.push_str(&format!("From: {}\n", from));
+    }
+    msg.push_str(&format!("To: {}\n", to));
+    msg.push_str(&format!("Subject: {}\n", subject));
+    msg.push_str(&format!("Date: {}\n", chrono::Local::now().to_rfc2822()));
+    msg.push_str("MIME-Version: 1.0\n");
+    msg.push_str("Content-Type: text/markdown; charset=utf-8\n");
+    msg.push_str("Content-Transfer-Encoding: 8bit\n\n");
+    msg.push_str(&body);
+    msg
+}
+
+/// Hand `message` off to the mail system through `sendmail`, which reads
+/// the recipients from the message's headers.
+///
+async fn handoff(sendmail: &str, message: &String) -> Result<(), GHDError> {
+    let child = tokio::process::Command::new(sendmail)
+        .args(["-t", "-i"])
+        .stdin(std::process::Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(res) => res,
+        Err(err) => {
+            println!("unable to run '{}': {}", sendmail, err);
+            return Err(GHDError::NotFoundError);
+        }
+    };
+
+    let sent = tokio::time::timeout(
+        tokio::time::Duration::from_secs(SENDMAIL_TIMEOUT_SECS),
+        async {
+            if let Some(mut stdin) = child.stdin.take() {
+                if let Err(err) = stdin.write_all(message.as_bytes()).await {
+                    println!(
+                        "unable to hand off mail to '{}': {}",
+                        sendmail, err
+                    );
+                }
+            }
+            child.wait().await
+        },
+    )
+    .await;
+
+    match sent {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => {
+            println!("'{}' failed to send mail: {}", sendmail, status);
+            Err(GHDError::UnknownError)
+        }
+        Ok(Err(err)) => {
+            println!("unable to wait on '{}': {}", sendmail, err);
+            Err(GHDError::UnknownError)
+        }
+        Err(_) => {
+            println!(
+                "'{}' timed out after {} seconds sending mail",
+                sendmail, SENDMAIL_TIMEOUT_SECS
+            );
+            if let Err(err) = child.kill().await {
+                println!("unable to kill '{}': {}", sendmail, err);
+            }
+            Err(GHDError::UnknownError)
+        }
+    }
+}
+
+/// Check whether the daily digest is due: a recipient is set, we're past
+/// the configured hour, and none has been mailed today, local time.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn is_digest_due(db: &DB) -> bool {
+    if config::get_setting_or(&db, SETTING_DIGEST_TO, "")
+        .await
+        .is_empty()
+    {
+        return false;
+    }
+
+    let hour = config::get_setting_or(
+        &db,
+        SETTING_DIGEST_HOUR,
+        &DEFAULT_DIGEST_HOUR.to_string(),
+    )
+    .await
+    .parse::<u32>()
+    .unwrap_or(DEFAULT_DIGEST_HOUR);
+    let now = chrono::Local::now();
+    if now.hour() < hour {
+        return false;
+    }
+
+    let last = config::get_setting_or(&db, SETTING_LAST_DIGEST, "0")
+        .await
+        .parse::<i64>()
+        .unwrap_or(0);
+    let today = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|d| d.and_local_timezone(chrono::Local).earliest())
+        .map(|d| d.timestamp())
+        .unwrap_or(0);
+    last < today
+}
+
+/// Mail the digest of the past day to the configured recipient, rendered by
+/// the same engine as the weekly summary. The attempt is recorded whether or
+/// not the handoff succeeds, so a broken mail setup isn't retried until the
+/// next day.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn send_digest(db: &DB) -> Result<(), GHDError> {
+    let to = config::get_setting_or(&db, SETTING_DIGEST_TO, "").await;
+    let from = match config::get_setting_or(&db, SETTING_DIGEST_FROM, "").await
+    {
+        f if f.is_empty() => None,
+        f => Some(f),
+    };
+    if !is_valid_address(&to)
+        || from.as_ref().map_or(false, |f| !is_valid_address(&f))
+    {
+        return Err(GHDError::BadRequest);
+    }
+    let sendmail =
+        config::get_setting_or(&db, SETTING_SENDMAIL, DEFAULT_SENDMAIL).await;
+
+    let now = chrono::Utc::now().timestamp();
+    config::set_setting(&db, SETTING_LAST_DIGEST, &now.to_string()).await;
+
+    let body =
+        report::render(&db, "Daily summary", &(now - 24 * 3600), &now).await;
+    let subject = format!(
+        "ghd daily summary for {}",
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+    handoff(&sendmail, &compose(&from, &to, &subject, &body)).await
+}
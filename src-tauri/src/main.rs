@@ -17,45 +17,32 @@
     windows_subsystem = "windows"
 )]
 
-use tauri::Manager;
-
-mod bg;
-mod common;
-mod config;
-mod db;
-mod errors;
-mod events;
-mod gh;
-mod gh_types;
-mod paths;
-mod state;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-struct ManagedState {
-    state: tokio::sync::Mutex<state::State>,
-}
+use ghd::{
+    bg, config, db, errors, events, gh, journal, mail, notify, page, paths,
+    permissions, querylog, state, throttle, updates, ManagedState,
+};
+use tauri::Manager;
 
-impl ManagedState {
-    pub async fn state(
-        self: &Self,
-    ) -> tokio::sync::MutexGuard<'_, state::State> {
-        self.state.lock().await
-    }
-}
+use errors::CommandError;
 
 #[tauri::command]
 async fn set_token(
     token: String,
+    host: Option<String>,
     window: tauri::Window,
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<bool, ()> {
-    println!("set token to {}", token);
+) -> Result<bool, CommandError> {
+    println!("setting new token");
 
     let state = &mstate.state().await;
 
     let db = &state.db;
     let gh = &state.gh;
+    let host = host.unwrap_or(String::from(gh::accounts::DEFAULT_HOST));
     match gh
-        .set_token(&db, &token, |user| {
+        .set_token(&db, &token, &host, |user| {
             events::emit_token_set(&window);
             events::emit_user_update(&window, &user);
         })
@@ -69,7 +56,7 @@ async fn set_token(
 #[tauri::command]
 async fn get_token(
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<String, ()> {
+) -> Result<String, CommandError> {
     let state = &mstate.state().await;
     let db = &state.db;
     let gh = &state.gh;
@@ -84,24 +71,24 @@ async fn get_token(
 #[tauri::command]
 async fn get_main_user(
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<gh::types::GithubUser, ()> {
+) -> Result<gh::types::GithubUser, CommandError> {
     let state = &mstate.state().await;
     let db = &state.db;
     match gh::users::get_main_user(&db).await {
         Ok(res) => Ok(res),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[tauri::command]
 async fn get_tracked_users(
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<Vec<gh::types::GithubUser>, ()> {
+) -> Result<Vec<gh::types::GithubUser>, CommandError> {
     let state = &mstate.state().await;
     let db = &state.db;
     match gh::users::get_tracked_users(&db).await {
         Ok(res) => Ok(res),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -110,7 +97,7 @@ async fn add_tracked_user(
     username: String,
     window: tauri::Window,
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<gh::types::GithubUser, ()> {
+) -> Result<gh::types::GithubUser, CommandError> {
     println!("track new user: {}", username);
     let state = &mstate.state().await;
     let db = &state.db;
@@ -122,7 +109,28 @@ async fn add_tracked_user(
         .await
     {
         Ok(res) => Ok(res),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn remove_tracked_user(
+    username: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.untrack_user(&db, &username).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -130,14 +138,14 @@ async fn add_tracked_user(
 async fn check_user_exists(
     username: String,
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<gh::types::GithubUser, ()> {
+) -> Result<gh::types::GithubUser, CommandError> {
     println!("check user exist: {}", username);
     let state = &mstate.state().await;
     let db = &state.db;
     let gh = &state.gh;
     match gh.get_user_by_login(&db, &username).await {
         Ok(res) => Ok(res),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -145,102 +153,2923 @@ async fn check_user_exists(
 async fn pr_mark_viewed(
     prid: i64,
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<(), ()> {
+) -> Result<(), CommandError> {
     let state = &mstate.state().await;
     let db = &state.db;
     let gh = &state.gh;
     match gh.mark_pull_request_viewed(&db, &prid).await {
         Ok(_) => Ok(()),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[tauri::command]
 async fn pr_get_list_by_author(
     login: String,
+    cursor: Option<String>,
+    limit: Option<i64>,
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<Vec<gh::types::PullRequestTableEntry>, ()> {
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
     let state = &mstate.state().await;
     let db = &state.db;
     let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
 
-    match gh.get_pulls_by_author(&db, &login).await {
+    match gh.get_pulls_by_author(&db, &login, &page).await {
         Ok(res) => Ok(res),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[tauri::command]
 async fn pr_get_list_by_involved(
     login: String,
+    relation: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
     mstate: tauri::State<'_, ManagedState>,
-) -> Result<Vec<gh::types::PullRequestTableEntry>, ()> {
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
     let state = &mstate.state().await;
     let db = &state.db;
     let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
 
-    match gh.get_involved_pulls(&db, &login).await {
+    match gh.get_involved_pulls(&db, &login, &relation, &page).await {
         Ok(res) => Ok(res),
-        Err(_) => Err(()),
+        Err(err) => Err(err.into()),
     }
 }
 
-async fn setup_paths() -> paths::Paths {
-    paths::Paths::default().init().await
+#[tauri::command]
+async fn get_accounts(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::Account>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_accounts(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
 }
 
-async fn setup_db(path: &std::path::PathBuf) -> db::DB {
-    let mut handle = db::DB::new(&path).setup().await;
-    handle.connect().await;
+#[tauri::command]
+async fn pr_get_list_aggregated(
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::AccountPullRequestEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
 
-    handle
+    match gh.get_aggregated_pulls(&db, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
 }
 
-async fn setup_config() -> config::Config {
-    config::Config::default()
+#[tauri::command]
+async fn pr_get_counts_aggregated(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::AccountCounts>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_aggregated_counts(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
 }
 
-#[tokio::main]
-async fn main() {
-    let paths = setup_paths().await;
-    let db_handle = setup_db(&paths.db_path).await;
-    let cfg = setup_config().await;
+#[tauri::command]
+async fn get_dashboard(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::Dashboard, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
 
-    println!("  user data dir: {}", paths.data_dir.display());
-    println!("user config dir: {}", paths.config_dir.display());
-    println!("  database path: {}", paths.db_path.display());
+    match gh.get_dashboard(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
 
-    tauri::async_runtime::set(tokio::runtime::Handle::current());
+#[tauri::command]
+async fn get_orgs(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::GithubOrg>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
 
-    tauri::Builder::default()
-        .manage(ManagedState {
-            state: tokio::sync::Mutex::new(state::State {
-                config: cfg,
-                db: db_handle,
-                paths: paths,
-                gh: gh::Github::new(),
-            }),
-        })
-        .invoke_handler(tauri::generate_handler![
-            set_token,
-            get_token,
-            get_main_user,
-            get_tracked_users,
-            add_tracked_user,
-            check_user_exists,
-            pr_mark_viewed,
-            pr_get_list_by_author,
-            pr_get_list_by_involved,
-        ])
-        .setup(|app| {
+    match gh.get_orgs(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_teams(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::GithubTeam>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_teams(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_team_members(
+    teamid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::TeamMember>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_team_members(&db, &teamid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn org_scope_enable(
+    org: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.enable_org_scope(&db, &org).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn org_scope_disable(
+    org: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.disable_org_scope(&db, &org).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_org_scopes(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::OrgScope>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_org_scopes(&db).await)
+}
+
+#[tauri::command]
+async fn get_org_overview(
+    org: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::OrgOverview, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_org_overview(&db, &org).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn diagnose_auth(
+    account: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::AuthReport, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.diagnose_auth(&db, &account).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_files(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::PullRequestFile>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_pull_files(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_list_by_path(
+    path: String,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_pulls_by_path(&db, &path, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn path_view_add(
+    view: gh::types::PathView,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.add_path_view(&db, &view).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn path_view_remove(
+    viewid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_path_view(&db, &viewid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn path_view_get_all(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::PathView>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_path_views(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_list_by_view(
+    viewid: i64,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_pulls_by_view(&db, &viewid, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_conflicts(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::PullRequestConflict>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_pull_conflicts(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn draft_release_notes(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::ReleaseNotesDraft, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.draft_release_notes(&db, &owner, &name, &None).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn release_notes_push(
+    owner: String,
+    name: String,
+    tag: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::ReleaseNotesDraft, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.draft_release_notes(&db, &owner, &name, &Some(tag)).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_settings(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<config::ConfigEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(config::get_settings(&db).await)
+}
+
+#[tauri::command]
+async fn set_setting(
+    key: String,
+    value: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    if let Err(err) = permissions::check_setting(&db, &key).await {
+        return Err(err.into());
+    }
+    config::set_setting(&db, &key, &value).await;
+    if gh::lint::is_rule(&key) {
+        gh::lint::run(&db).await;
+    }
+    if gh::limits::is_setting(&key) {
+        gh::limits::load(&db).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn save_ui_state(
+    uistate: serde_json::Value,
+    window: tauri::Window,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    config::save_ui_state(&db, window.label(), &uistate).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn load_ui_state(
+    window: tauri::Window,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Option<serde_json::Value>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(config::load_ui_state(&db, window.label()).await)
+}
+
+#[tauri::command]
+async fn clear_ui_state(
+    window: tauri::Window,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    config::clear_ui_state(&db, window.label()).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_display_prefs(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<config::DisplayPrefs, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(config::get_display_prefs(&db).await)
+}
+
+#[tauri::command]
+async fn pr_get_backports(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::BackportEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_pull_backports(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_missing_backports(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::BackportEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_missing_backports(&db, &owner, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn add_repo(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.add_repo(&db, &owner, &name).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_repos(
+    owner: String,
+    names: Vec<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.add_repos(&db, &owner, &names).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_org_repos(
+    org: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::OrgRepository>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_org_repos(&db, &org).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn remove_repo(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_repo(&db, &owner, &name).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_repos(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::Repository>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_repos(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_repo_permissions(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::RepoPermission>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_repo_permissions(&db).await)
+}
+
+#[tauri::command]
+async fn get_repo_branches(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::RepoBranch>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_repo_branches(&db, &owner, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_stale_branches(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::RepoBranch>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_stale_branches(&db, &owner, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn triage_add_rule(
+    rule: gh::types::TriageRule,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.add_triage_rule(&db, &rule).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn triage_remove_rule(
+    ruleid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_triage_rule(&db, &ruleid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn triage_get_rules(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::TriageRule>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_triage_rules(&db, &owner, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn triage_get_inbox(
+    bucket: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::TriageEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_triage_inbox(&db, &bucket, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn label_alert_add(
+    alert: gh::types::LabelAlert,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.add_label_alert(&db, &alert).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn label_alert_remove(
+    alertid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_label_alert(&db, &alertid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_label_alerts(
+    issueid: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::LabelAlert>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_label_alerts(&db, &issueid).await)
+}
+
+#[tauri::command]
+async fn issue_get_fixes(
+    issueid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::IssueFix>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_issue_fixes(&db, &issueid).await)
+}
+
+#[tauri::command]
+async fn issue_get_references(
+    issueid: i64,
+    depth: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::IssueReference>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh
+        .get_issue_references(&db, &issueid, &depth.unwrap_or(1))
+        .await)
+}
+
+#[tauri::command]
+async fn sla_set_rule(
+    rule: gh::types::SlaRule,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.set_sla_rule(&db, &rule).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn sla_remove_rule(
+    owner: String,
+    name: String,
+    label: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_sla_rule(&db, &owner, &name, &label).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn sla_get_rules(
+    owner: Option<String>,
+    name: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::SlaRule>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_sla_rules(&db, &owner, &name).await)
+}
+
+#[tauri::command]
+async fn sla_get_breaches(
+    owner: Option<String>,
+    name: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::SlaEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_sla_breaches(&db, &owner, &name).await)
+}
+
+#[tauri::command]
+async fn workspace_set(
+    owner: String,
+    name: String,
+    path: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.set_workspace(&db, &owner, &name, &path).await {
+        Ok(()) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn workspace_remove(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_workspace(&db, &owner, &name).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_workspaces(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::Workspace>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_workspaces(&db).await)
+}
+
+#[tauri::command]
+async fn workspace_get_head(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::WorkspaceHead, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_workspace_head(&db, &owner, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn workspace_scan(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::WorkspaceCandidate>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.scan_workspaces(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn workspace_adopt(
+    candidates: Vec<gh::types::WorkspaceCandidate>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.adopt_workspaces(&db, &candidates).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn pr_get_checkout_snippet(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<String, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_checkout_snippet(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn triage_dismiss(
+    issueid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.dismiss_triage(&db, &issueid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_notifications(
+    unread_only: bool,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<notify::Notification>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(notify::get_notifications(&db, unread_only, &page).await)
+}
+
+#[tauri::command]
+async fn mark_notification_read(
+    id: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    notify::mark_read(&db, &id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn add_notification_rule(
+    rule: notify::NotificationRule,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    match notify::add_rule(&db, &rule).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn update_notification_rule(
+    rule: notify::NotificationRule,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    match notify::update_rule(&db, &rule).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn remove_notification_rule(
+    ruleid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    notify::remove_rule(&db, &ruleid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_notification_rules(
+    accountid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<notify::NotificationRule>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(notify::get_rules(&db, &accountid).await)
+}
+
+#[tauri::command]
+async fn pr_get_list_first_time(
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_first_time_pulls(&db, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_contribution_credits(
+    since: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ContributionCredit>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_contribution_credits(&db, &since).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_advisory_feed(
+    owner: Option<String>,
+    name: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::AdvisoryFinding>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_advisory_feed(&db, &owner, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_detail(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::PullRequestDetail, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_pull_detail(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn identity_create(
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.create_identity(&db, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn identity_remove(
+    identityid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_identity(&db, &identityid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn identity_link(
+    identityid: i64,
+    host: String,
+    login: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.link_identity(&db, &identityid, &host, &login).await {
+        Ok(()) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn identity_unlink(
+    host: String,
+    login: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.unlink_identity(&db, &host, &login).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_identities(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::Identity>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_identities(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_activity_feed(
+    since: Option<i64>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::ActivityEvent>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_activity_feed(&db, &since, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_list_filtered(
+    filter: gh::filters::IssueFilter,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_filtered_pulls(&db, &filter, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_list_by_size(
+    filter: gh::filters::IssueFilter,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::PullRequestTableEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.get_filtered_pulls_by_size(&db, &filter, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_size_thresholds(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<[i64; 4], CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_size_thresholds(&db).await)
+}
+
+#[tauri::command]
+async fn set_size_thresholds(
+    thresholds: Vec<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.set_size_thresholds(&db, &thresholds).await {
+        Ok(()) => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_size_stats(
+    since: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::SizeStats>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_size_stats(&db, &since).await)
+}
+
+#[tauri::command]
+async fn mark_all_viewed(
+    filter: gh::filters::IssueFilter,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<u64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.mark_all_viewed(&db, &filter).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn dismiss_all(
+    filter: gh::filters::IssueFilter,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<u64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.dismiss_all(&db, &filter).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn away_start(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.away_start(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn away_end(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Option<gh::types::AwayPeriod>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.away_end(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn away_get_status(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Option<gh::types::AwayPeriod>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_away_status(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn away_get_catch_up(
+    periodid: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::CatchUpSummary, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_away_catch_up(&db, &periodid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_api_usage(
+    since: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ApiUsageEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_api_usage(&db, &since).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_rate_limits(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::RateLimitStatus>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_rate_limits(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_hourly_usage(
+    since: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::HourlyUsage>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_hourly_usage(&db, &since).await)
+}
+
+#[tauri::command]
+async fn get_usage_anomalies(
+    since: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::UsageAnomaly>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_usage_anomalies(&db, &since).await)
+}
+
+#[tauri::command]
+async fn get_profile_stats(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Option<gh::types::ProfileStats>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_profile_stats(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_comment(
+    prid: i64,
+    body: String,
+    template: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    let body = match gh.compose_reply(&db, &prid, &template, &Some(body)).await
+    {
+        Ok(res) => res.unwrap_or_default(),
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh
+        .run_pull_action(
+            &db,
+            &prid,
+            &gh::outbox::PullRequestAction::Comment { body },
+        )
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_review(
+    prid: i64,
+    event: String,
+    body: Option<String>,
+    template: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    let body = match gh.compose_reply(&db, &prid, &template, &body).await {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh
+        .run_pull_action(
+            &db,
+            &prid,
+            &gh::outbox::PullRequestAction::Review {
+                event,
+                body,
+                comments: vec![],
+            },
+        )
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn reply_template_add(
+    template: gh::types::ReplyTemplate,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.add_reply_template(&db, &template).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn reply_template_update(
+    template: gh::types::ReplyTemplate,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.update_reply_template(&db, &template).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn reply_template_remove(
+    templateid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_reply_template(&db, &templateid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_reply_templates(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReplyTemplate>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_reply_templates(&db).await)
+}
+
+#[tauri::command]
+async fn reply_template_preview(
+    prid: i64,
+    templateid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<String, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.compose_reply(&db, &prid, &Some(templateid), &None).await {
+        Ok(res) => Ok(res.unwrap_or_default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn review_session_start(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::ReviewSession, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.start_review_session(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn review_session_stop(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::ReviewSession, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.stop_review_session(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_review_sessions(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReviewSession>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_review_sessions(&db, &prid).await)
+}
+
+#[tauri::command]
+async fn get_weekly_review_time(
+    from: i64,
+    to: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReviewTime>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    let to = to.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    Ok(gh.get_weekly_review_time(&db, &from, &to).await)
+}
+
+#[tauri::command]
+async fn draft_review_get(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::DraftReview, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_draft_review(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn draft_review_get_pulls(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<i64>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_drafted_pulls(&db).await)
+}
+
+#[tauri::command]
+async fn draft_review_set_body(
+    prid: i64,
+    body: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.set_draft_review_body(&db, &prid, &body).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn draft_review_add_comment(
+    prid: i64,
+    path: String,
+    line: i64,
+    side: String,
+    body: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<i64, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh
+        .add_draft_review_comment(&db, &prid, &path, &line, &side, &body)
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn draft_review_update_comment(
+    id: i64,
+    body: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.update_draft_review_comment(&db, &id, &body).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn draft_review_remove_comment(
+    id: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.remove_draft_review_comment(&db, &id).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn draft_review_discard(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.discard_draft_review(&db, &prid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn draft_review_submit(
+    prid: i64,
+    event: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.submit_draft_review(&db, &prid, &event).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_pending_reviews(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::PendingReview>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_pending_reviews(&db).await)
+}
+
+#[tauri::command]
+async fn get_follow_ups(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::FollowUp>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_follow_ups(&db).await)
+}
+
+#[tauri::command]
+async fn follow_up_dismiss(
+    commentid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.dismiss_follow_up(&db, &commentid).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_team_mentions(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::TeamMention>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_team_mentions(&db).await)
+}
+
+#[tauri::command]
+async fn reaction_get(
+    issueid: i64,
+    subject: gh::reactions::ReactionSubject,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReactionCount>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_reactions(&db, &issueid, &subject).await)
+}
+
+#[tauri::command]
+async fn reaction_refresh(
+    issueid: i64,
+    subject: gh::reactions::ReactionSubject,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReactionCount>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.refresh_reactions(&db, &issueid, &subject).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn reaction_add(
+    issueid: i64,
+    subject: gh::reactions::ReactionSubject,
+    content: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReactionCount>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.add_reaction(&db, &issueid, &subject, &content).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn reaction_remove(
+    issueid: i64,
+    subject: gh::reactions::ReactionSubject,
+    content: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::ReactionCount>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.remove_reaction(&db, &issueid, &subject, &content).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_weekly_report(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<String, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_weekly_report(&db).await)
+}
+
+#[tauri::command]
+async fn mail_send_digest(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    match mail::send_digest(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn pr_get_merge_methods(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<String>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_merge_methods(&db, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_merge_policy(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Option<gh::types::MergePolicy>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_merge_policy(&db, &owner, &name).await)
+}
+
+#[tauri::command]
+async fn get_repo_labels(
+    owner: Option<String>,
+    name: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::RepoLabel>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_repo_labels(&db, &owner, &name).await)
+}
+
+#[tauri::command]
+async fn label_complete(
+    prefix: String,
+    owner: Option<String>,
+    name: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<String>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.complete_label(&db, &prefix, &owner, &name).await)
+}
+
+#[tauri::command]
+async fn pr_merge(
+    prid: i64,
+    method: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Merge).await
+    {
+        return Err(err.into());
+    }
+
+    match gh
+        .run_pull_action(
+            &db,
+            &prid,
+            &gh::outbox::PullRequestAction::Merge { method },
+        )
+        .await
+    {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn outbox_get_interrupted(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::OutboxEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_interrupted_pull_actions(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn outbox_retry(
+    id: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    let kind = match gh::outbox::get_entry(&db, &id).await {
+        Ok(entry) => entry.kind,
+        Err(err) => return Err(err.into()),
+    };
+    let cap = permissions::Capability::for_pull_action(&kind);
+    if let Err(err) = permissions::check(&db, cap).await {
+        return Err(err.into());
+    }
+
+    match gh.retry_pull_action(&db, &id).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn outbox_discard(
+    id: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.discard_pull_action(&db, &id).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_lint_warnings(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::LintWarning>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_lint_warnings(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_slow_queries(
+    limit: Option<usize>,
+) -> Result<Vec<querylog::SlowQuery>, CommandError> {
+    Ok(querylog::get_slow_queries(limit.unwrap_or(20)))
+}
+
+#[tauri::command]
+async fn get_repo_sync_status(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::RepoSyncStatus>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_repo_sync_status(&db).await)
+}
+
+#[tauri::command]
+async fn retry_repo_sync(
+    owner: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.retry_repo_sync(&db, &owner, &name).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_repo_sync_paused(
+    owner: String,
+    name: String,
+    paused: bool,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.set_repo_sync_paused(&db, &owner, &name, paused).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_reminders(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::Reminder>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_reminders(&db).await)
+}
+
+#[tauri::command]
+async fn snooze_reminders(
+    prid: i64,
+    hours: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    gh.snooze_reminders(&db, &prid, hours).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_issue_priority(
+    issueid: i64,
+    priority: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.set_issue_priority(&db, &issueid, &priority).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn set_issue_note(
+    issueid: i64,
+    note: Option<String>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.set_issue_note(&db, &issueid, &note).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn add_issue_tag(
+    issueid: i64,
+    tag: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.add_issue_tag(&db, &issueid, &tag).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn remove_issue_tag(
+    issueid: i64,
+    tag: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.remove_issue_tag(&db, &issueid, &tag).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_issue_tags(
+    issueid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<String>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_issue_tags(&db, &issueid).await)
+}
+
+#[tauri::command]
+async fn get_tags(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::LocalTag>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_tags(&db).await)
+}
+
+#[tauri::command]
+async fn list_actions(
+    context: gh::actions::ActionContext,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::Action>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    let actions = match gh.list_actions(&db, &context).await {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    // leave out actions whose commands we'd refuse to run.
+    let mut res = vec![];
+    for action in actions {
+        let cap = action
+            .command
+            .as_ref()
+            .and_then(|c| permissions::Capability::for_command(c));
+        match cap {
+            Some(cap) if !permissions::is_allowed(&db, cap).await => {}
+            _ => res.push(action),
+        };
+    }
+    Ok(res)
+}
+
+#[tauri::command]
+async fn get_update_status(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<updates::UpdateStatus, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(updates::get_status(&db).await)
+}
+
+#[tauri::command]
+async fn check_for_update(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<updates::UpdateStatus, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    match updates::check(&db).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn download_update(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<String, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let dir = state.paths.data_dir.join("updates");
+
+    match updates::download(&db, &dir).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_task_health(
+) -> Result<Vec<bg::supervisor::TaskHealth>, CommandError> {
+    Ok(bg::supervisor::get_health())
+}
+
+#[tauri::command]
+async fn get_capabilities(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<permissions::CapabilityStatus>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(permissions::get_capabilities(&db).await)
+}
+
+#[tauri::command]
+async fn get_dependency_graph(
+    issueid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::DependencyGraph, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_dependency_graph(&db, &issueid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn backfill(
+    login: String,
+    from: i64,
+    to: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.request_backfill(&db, &login, &from, &to).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_user_history(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::UserHistory>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_user_history(&db).await)
+}
+
+#[tauri::command]
+async fn get_sync_history(
+    source: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<journal::SyncJournalEntry>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(journal::get_history(&db, &source, &page).await)
+}
+
+#[tauri::command]
+async fn pr_get_patch(
+    prid: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::PullRequestPatch, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let dir = state.paths.data_dir.join("patches");
+
+    match gh.get_pull_patch(&db, &dir, &prid).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_pr_diff(
+    prid: i64,
+    path: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::FileDiff, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let dir = state.paths.data_dir.join("patches");
+
+    match gh.get_pull_file_diff(&db, &dir, &prid, &path).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn search(
+    query: String,
+    cursor: Option<String>,
+    limit: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<page::Page<gh::types::SearchResult>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+    let limit = limit.or(Some(gh::search::DEFAULT_LIMIT));
+    let page = match page::PageRequest::new(&cursor, &limit) {
+        Ok(res) => res,
+        Err(err) => return Err(err.into()),
+    };
+
+    match gh.search(&db, &query, &page).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_dependency_updates(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::DependencyUpdateGroup>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_dependency_updates(&db).await)
+}
+
+#[tauri::command]
+async fn dependency_updates_approve(
+    bump: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::DependencyApproval, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Write).await
+    {
+        return Err(err.into());
+    }
+
+    match gh.approve_dependency_updates(&db, &bump).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn anon_track(
+    kind: String,
+    name: String,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<gh::types::AnonTarget, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.track_anon(&db, &kind, &name).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn anon_untrack(
+    id: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<(), CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    if let Err(err) =
+        permissions::check(&db, permissions::Capability::Delete).await
+    {
+        return Err(err.into());
+    }
+
+    gh.untrack_anon(&db, &id).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_anon_targets(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::AnonTarget>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    Ok(gh.get_anon_targets(&db).await)
+}
+
+#[tauri::command]
+async fn get_anon_pulls(
+    id: i64,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Vec<gh::types::AnonPullRequest>, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+    let gh = &state.gh;
+
+    match gh.get_anon_pulls(&db, &id).await {
+        Ok(res) => Ok(res),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[tauri::command]
+async fn get_sync_status(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<throttle::SyncStatus, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(throttle::get_status(&db).await)
+}
+
+#[tauri::command]
+async fn get_system_conditions(
+) -> Result<throttle::SystemConditions, CommandError> {
+    Ok(throttle::detect().await)
+}
+
+#[tauri::command]
+async fn pause_sync(
+    minutes: Option<i64>,
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<throttle::SyncStatus, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(throttle::pause(&db, &minutes).await)
+}
+
+#[tauri::command]
+async fn resume_sync(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<throttle::SyncStatus, CommandError> {
+    let state = &mstate.state().await;
+    let db = &state.db;
+
+    Ok(throttle::resume(&db).await)
+}
+
+#[tauri::command]
+async fn get_migration_report(
+    mstate: tauri::State<'_, ManagedState>,
+) -> Result<Option<db::MigrationReport>, CommandError> {
+    Ok(mstate.migration.lock().unwrap().clone())
+}
+
+async fn setup_paths() -> paths::Paths {
+    paths::Paths::default().init().await
+}
+
+async fn setup_db(path: &std::path::PathBuf) -> db::DB {
+    querylog::init();
+    let mut handle = db::DB::new(&path);
+    handle.prepare().await;
+    // upgrades are left for once the frontend is up, to show them.
+    if handle.migration.is_none() {
+        handle.setup().await;
+        handle.connect().await;
+    }
+
+    handle
+}
+
+/// Upgrade the database, should it need to be, holding the state meanwhile
+/// so that nothing gets to the database before it's done. The frontend is
+/// told what is being upgraded, both before and after the upgrade.
+///
+async fn upgrade_db(
+    handle: tauri::AppHandle,
+    mut state: tokio::sync::OwnedMutexGuard<state::State>,
+) {
+    let report = match &state.db.migration {
+        Some(res) => res.clone(),
+        None => return,
+    };
+    let window = handle.get_window("main").unwrap();
+    events::emit_db_migration(&window, &report);
+
+    state.db.setup().await;
+    state.db.connect().await;
+
+    if let Some(report) = &state.db.migration {
+        let mstate = handle.state::<ManagedState>();
+        *mstate.migration.lock().unwrap() = Some(report.clone());
+        events::emit_db_migration(&window, &report);
+    }
+}
+
+/// Reopen the windows that were open when we last ran, going by their saved
+/// UI state. The main window is always opened, by the config; each window
+/// restores its own UI state on load.
+///
+async fn restore_windows(handle: tauri::AppHandle) {
+    let labels = {
+        let mstate = handle.state::<ManagedState>();
+        let state = &mstate.state().await;
+        config::get_ui_windows(&state.db).await
+    };
+
+    for label in &labels {
+        if handle.get_window(&label).is_some() {
+            continue;
+        }
+        let url = tauri::WindowUrl::App("index.html".into());
+        if let Err(err) = tauri::WindowBuilder::new(&handle, label, url).build()
+        {
+            println!("unable to restore window '{}': {}", label, err);
+        }
+    }
+}
+
+/// Whether we are quitting, as opposed to windows being closed one by one.
+///
+static EXITING: AtomicBool = AtomicBool::new(false);
+
+/// Forget the saved UI state of window `label`, once closed by the user, so
+/// that it isn't restored on the next start. The main window is always
+/// opened.
+///
+async fn forget_window(handle: tauri::AppHandle, label: String) {
+    let mstate = handle.state::<ManagedState>();
+    let state = &mstate.state().await;
+    config::clear_ui_state(&state.db, &label).await;
+}
+
+async fn setup_config() -> config::Config {
+    config::Config::default()
+}
+
+#[tokio::main]
+async fn main() {
+    let paths = setup_paths().await;
+    let db_handle = setup_db(&paths.db_path).await;
+    let cfg = setup_config().await;
+
+    println!("  user data dir: {}", paths.data_dir.display());
+    println!("user config dir: {}", paths.config_dir.display());
+    println!("  database path: {}", paths.db_path.display());
+
+    tauri::async_runtime::set(tokio::runtime::Handle::current());
+
+    tauri::Builder::default()
+        .manage(ManagedState {
+            migration: std::sync::Mutex::new(db_handle.migration.clone()),
+            state: std::sync::Arc::new(tokio::sync::Mutex::new(state::State {
+                config: cfg,
+                db: db_handle,
+                paths: paths,
+                gh: gh::Github::new(),
+            })),
+        })
+        .invoke_handler(tauri::generate_handler![
+            set_token,
+            get_token,
+            get_main_user,
+            get_tracked_users,
+            add_tracked_user,
+            remove_tracked_user,
+            check_user_exists,
+            pr_mark_viewed,
+            pr_get_list_by_author,
+            pr_get_list_by_involved,
+            get_accounts,
+            pr_get_list_aggregated,
+            pr_get_counts_aggregated,
+            get_dashboard,
+            get_orgs,
+            get_teams,
+            get_team_members,
+            org_scope_enable,
+            org_scope_disable,
+            get_org_scopes,
+            get_org_overview,
+            diagnose_auth,
+            pr_get_files,
+            pr_get_list_by_path,
+            path_view_add,
+            path_view_remove,
+            path_view_get_all,
+            pr_get_list_by_view,
+            pr_get_conflicts,
+            draft_release_notes,
+            release_notes_push,
+            get_settings,
+            set_setting,
+            pr_get_backports,
+            get_missing_backports,
+            add_repo,
+            remove_repo,
+            get_repos,
+            get_repo_permissions,
+            get_repo_branches,
+            get_stale_branches,
+            triage_add_rule,
+            triage_remove_rule,
+            triage_get_rules,
+            triage_get_inbox,
+            label_alert_add,
+            label_alert_remove,
+            get_label_alerts,
+            issue_get_fixes,
+            issue_get_references,
+            sla_set_rule,
+            sla_remove_rule,
+            sla_get_rules,
+            sla_get_breaches,
+            workspace_set,
+            workspace_remove,
+            get_workspaces,
+            workspace_get_head,
+            workspace_scan,
+            workspace_adopt,
+            pr_get_checkout_snippet,
+            triage_dismiss,
+            get_notifications,
+            mark_notification_read,
+            add_notification_rule,
+            update_notification_rule,
+            remove_notification_rule,
+            get_notification_rules,
+            pr_get_list_first_time,
+            get_contribution_credits,
+            get_advisory_feed,
+            pr_get_detail,
+            identity_create,
+            identity_remove,
+            identity_link,
+            identity_unlink,
+            get_identities,
+            get_activity_feed,
+            pr_get_list_filtered,
+            pr_get_list_by_size,
+            get_size_thresholds,
+            set_size_thresholds,
+            get_size_stats,
+            mark_all_viewed,
+            dismiss_all,
+            away_start,
+            away_end,
+            away_get_status,
+            away_get_catch_up,
+            get_api_usage,
+            get_rate_limits,
+            get_hourly_usage,
+            get_usage_anomalies,
+            get_profile_stats,
+            add_repos,
+            get_org_repos,
+            pr_comment,
+            pr_review,
+            reply_template_add,
+            reply_template_update,
+            reply_template_remove,
+            get_reply_templates,
+            reply_template_preview,
+            review_session_start,
+            review_session_stop,
+            get_review_sessions,
+            get_weekly_review_time,
+            draft_review_get,
+            draft_review_get_pulls,
+            draft_review_set_body,
+            draft_review_add_comment,
+            draft_review_update_comment,
+            draft_review_remove_comment,
+            draft_review_discard,
+            get_pending_reviews,
+            get_follow_ups,
+            follow_up_dismiss,
+            get_team_mentions,
+            draft_review_submit,
+            reaction_get,
+            reaction_refresh,
+            reaction_add,
+            reaction_remove,
+            pr_merge,
+            pr_get_merge_methods,
+            get_merge_policy,
+            get_repo_labels,
+            label_complete,
+            get_weekly_report,
+            mail_send_digest,
+            outbox_get_interrupted,
+            outbox_retry,
+            outbox_discard,
+            get_lint_warnings,
+            get_slow_queries,
+            get_display_prefs,
+            save_ui_state,
+            load_ui_state,
+            clear_ui_state,
+            get_repo_sync_status,
+            retry_repo_sync,
+            set_repo_sync_paused,
+            get_reminders,
+            snooze_reminders,
+            set_issue_priority,
+            set_issue_note,
+            add_issue_tag,
+            remove_issue_tag,
+            get_issue_tags,
+            get_tags,
+            list_actions,
+            get_update_status,
+            check_for_update,
+            download_update,
+            get_task_health,
+            get_capabilities,
+            get_dependency_graph,
+            backfill,
+            get_user_history,
+            get_sync_history,
+            pr_get_patch,
+            get_pr_diff,
+            search,
+            get_dependency_updates,
+            dependency_updates_approve,
+            anon_track,
+            anon_untrack,
+            get_anon_targets,
+            get_anon_pulls,
+            get_sync_status,
+            get_system_conditions,
+            pause_sync,
+            resume_sync,
+            get_migration_report,
+        ])
+        .on_window_event(|event| {
+            // windows also go away when quitting, but are to be restored
+            // then; only those the user closes are forgotten.
+            if let tauri::WindowEvent::CloseRequested { .. } = event.event() {
+                let window = event.window();
+                if window.label() != "main" && !EXITING.load(Ordering::SeqCst) {
+                    let handle = window.app_handle();
+                    let label = window.label().to_string();
+                    tokio::spawn(async move {
+                        forget_window(handle, label).await;
+                    });
+                }
+            }
+        })
+        .setup(|app| {
+            // taken right away, so that nothing gets to the state before the
+            // database is upgraded.
+            let upgrade_state =
+                app.state::<ManagedState>().state.clone().try_lock_owned();
+            let upgrade_handle = app.app_handle();
+            tokio::spawn(async move {
+                if let Ok(state) = upgrade_state {
+                    upgrade_db(upgrade_handle, state).await;
+                }
+            });
             let handle = app.app_handle();
             // let window = app.get_window("main").unwrap();
+            tokio::spawn(bg::supervisor::supervise("sync", move || {
+                let handle = handle.clone();
+                async move {
+                    let mut bgtask = bg::BGTask::new();
+                    bgtask.run(handle).await;
+                }
+            }));
+            let restore_handle = app.app_handle();
             tokio::spawn(async move {
-                let mut bgtask = bg::BGTask::new();
-                bgtask.run(handle).await;
+                restore_windows(restore_handle).await;
             });
+            let enrich_handle = app.app_handle();
+            tokio::spawn(bg::supervisor::supervise("enrich", move || {
+                let handle = enrich_handle.clone();
+                async move {
+                    let mut enrichtask = bg::EnrichTask::new();
+                    enrichtask.run(handle).await;
+                }
+            }));
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                EXITING.store(true, Ordering::SeqCst);
+            }
+        });
 }
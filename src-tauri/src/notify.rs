@@ -0,0 +1,607 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{Datelike, Timelike};
+
+use crate::{
+    config,
+    db::DB,
+    errors::GHDError,
+    gh::bots,
+    page::{Page, PageRequest},
+    vault,
+};
+
+pub const PRIORITY_LOW: i64 = 0;
+pub const PRIORITY_NORMAL: i64 = 1;
+pub const PRIORITY_HIGH: i64 = 2;
+
+/// Default priority for each kind of notification. Kinds not listed here are
+/// low priority. Producers may still raise the priority of individual
+/// notifications, e.g. for critical advisories.
+///
+const KIND_PRIORITIES: [(&str, i64); 9] = [
+    ("ci_failed", PRIORITY_HIGH),
+    ("review_requested", PRIORITY_NORMAL),
+    ("review_reminder", PRIORITY_NORMAL),
+    ("triage", PRIORITY_NORMAL),
+    ("advisory", PRIORITY_NORMAL),
+    ("label_change", PRIORITY_NORMAL),
+    ("fix_in_progress", PRIORITY_NORMAL),
+    ("sla_at_risk", PRIORITY_NORMAL),
+    ("sla_breached", PRIORITY_HIGH),
+];
+
+/// Setting enabling digest delivery of notifications below high priority.
+///
+pub const SETTING_BATCHING: &str = "notify.batching";
+
+/// Setting holding the working hours digests are delivered in, local time;
+/// e.g., `09:00-17:00`.
+///
+pub const SETTING_WORKING_HOURS: &str = "notify.working_hours";
+
+/// Setting holding the comma-separated working days digests are delivered
+/// on; e.g., `mon,tue,wed,thu,fri`.
+///
+pub const SETTING_WORKING_DAYS: &str = "notify.working_days";
+
+/// Setting holding the minimum number of minutes between digests.
+///
+pub const SETTING_DIGEST_MINUTES: &str = "notify.digest_minutes";
+
+/// Routing rule actions: deliver immediately, regardless of batching;
+/// deliver with the next digest; or never notify at all.
+///
+pub const ROUTE_IMMEDIATE: &str = "immediate";
+pub const ROUTE_DIGEST: &str = "digest";
+pub const ROUTE_NEVER: &str = "never";
+
+const ROUTES: [&str; 3] = [ROUTE_IMMEDIATE, ROUTE_DIGEST, ROUTE_NEVER];
+
+const DEFAULT_WORKING_HOURS: &str = "09:00-17:00";
+const DEFAULT_WORKING_DAYS: &str = "mon,tue,wed,thu,fri";
+const DEFAULT_DIGEST_MINUTES: i64 = 60;
+
+/// A local notification. Notifications are queued in the database by
+/// whatever produces them, and delivered to the frontend by the background
+/// task.
+///
+#[derive(sqlx::FromRow, serde::Serialize, Clone)]
+pub struct Notification {
+    pub id: i64,
+    pub kind: String,
+    pub issue_id: Option<i64>,
+    pub title: String,
+    pub body: String,
+    pub priority: i64,
+    pub created_at: i64,
+    pub delivered_at: Option<i64>,
+    pub is_read: bool,
+}
+
+/// A routing rule, overriding the priority of an account's notifications
+/// about issues with a given label, optionally only in one repository.
+///
+#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct NotificationRule {
+    #[serde(default)]
+    pub id: i64,
+    pub account_id: i64,
+    pub repo_owner: Option<String>,
+    pub repo_name: Option<String>,
+    pub label: String,
+    /// One of the `ROUTE_*` constants.
+    pub action: String,
+}
+
+/// Notifications due for delivery. High priority notifications are always
+/// delivered immediately; the remaining ones are either delivered immediately
+/// too, or held back and delivered together as a digest if batching is
+/// enabled.
+///
+pub struct Deliveries {
+    pub immediate: Vec<Notification>,
+    pub digest: Vec<Notification>,
+}
+
+/// Obtain the default priority for notifications of kind `kind`.
+///
+pub fn classify(kind: &str) -> i64 {
+    KIND_PRIORITIES
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, p)| *p)
+        .unwrap_or(PRIORITY_LOW)
+}
+
+/// Obtain the action of the routing rule applying to a notification about
+/// issue `issue_id` for account `account`, if any. Rules for a specific
+/// repository take precedence over those for any repository; otherwise the
+/// earliest added rule wins.
+///
+async fn route(
+    conn: &mut sqlx::SqliteConnection,
+    account: &i64,
+    issue_id: &i64,
+) -> Option<String> {
+    match sqlx::query_scalar::<_, String>(
+        "
+        SELECT notification_rules.action
+        FROM
+            notification_rules
+            JOIN issues ON issues.id = ?
+            JOIN issue_labels ON issue_labels.issue_id = issues.id
+        WHERE
+            notification_rules.account_id = ?
+            AND lower(notification_rules.label) = lower(issue_labels.name)
+            AND (
+                notification_rules.repo_owner IS NULL
+                OR notification_rules.repo_owner = issues.repo_owner
+            )
+            AND (
+                notification_rules.repo_name IS NULL
+                OR notification_rules.repo_name = issues.repo_name
+            )
+        ORDER BY
+            notification_rules.repo_owner IS NULL,
+            notification_rules.repo_name IS NULL,
+            notification_rules.id
+        LIMIT 1
+        ",
+    )
+    .bind(&issue_id)
+    .bind(&account)
+    .fetch_optional(&mut *conn)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to route notification for {}: {}", issue_id, err);
+        }
+    }
+}
+
+/// Queue a new notification for delivery. The routing rules of the issue's
+/// account, if any, may raise or lower its priority, or drop it altogether.
+/// Notifications are dropped as well, rather than stored in the clear, if
+/// they are to be encrypted but the key hasn't been obtained, as per
+/// `vault::load_keys()`. This function requires a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The sqlx transaction to piggy-back on.
+/// * `kind` - What produced the notification; e.g., `triage`.
+/// * `issue_id` - The issue the notification refers to, if any.
+/// * `title` - The notification's title.
+/// * `body` - The notification's body.
+/// * `priority` - One of the `PRIORITY_*` constants.
+///
+pub async fn push(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    kind: &str,
+    issue_id: &Option<i64>,
+    title: &String,
+    body: &String,
+    priority: i64,
+) {
+    let account = vault::account_for_issue(&mut *tx, &issue_id).await;
+    let priority = match issue_id {
+        Some(id) => match route(&mut *tx, &account, &id).await.as_deref() {
+            Some(ROUTE_IMMEDIATE) => PRIORITY_HIGH,
+            Some(ROUTE_DIGEST) => std::cmp::min(priority, PRIORITY_NORMAL),
+            Some(ROUTE_NEVER) => return,
+            _ => priority,
+        },
+        None => priority,
+    };
+    let body = match vault::cached_sealer(&mut *tx, &issue_id).await {
+        Ok(sealer) => sealer.seal(&body),
+        Err(err) => {
+            log::error!("dropping notification '{}': {:?}", title, err);
+            return;
+        }
+    };
+
+    sqlx::query(
+        "
+        INSERT INTO notifications (
+            kind, issue_id, title, body, priority, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&kind)
+    .bind(&issue_id)
+    .bind(&title)
+    .bind(&body)
+    .bind(&priority)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|err| {
+        panic!("Unable to queue notification: {}", err);
+    });
+}
+
+/// Obtain a condition on the `notifications` row being selected that filters
+/// out notifications about bot-authored issues, if those are hidden. Hidden
+/// notifications are kept, and show up again if bots are no longer hidden.
+///
+async fn issue_filter(db: &DB) -> String {
+    format!(
+        "
+        (
+            notifications.issue_id IS NULL
+            OR notifications.issue_id IN (
+                SELECT issues.id FROM issues WHERE {}
+            )
+        )
+        ",
+        bots::filter(&db).await
+    )
+}
+
+/// Obtain all notifications that have not yet been delivered, oldest first.
+/// Notifications about bot-authored issues are left out if bots are hidden.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_undelivered(db: &DB) -> Vec<Notification> {
+    let query = format!(
+        "
+        SELECT * FROM notifications
+        WHERE delivered_at IS NULL AND {}
+        ORDER BY created_at ASC, id ASC
+        ",
+        issue_filter(&db).await
+    );
+
+    match sqlx::query_as::<_, Notification>(&query)
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => open_bodies(res).await,
+        Err(err) => {
+            panic!("Unable to obtain undelivered notifications: {}", err);
+        }
+    }
+}
+
+/// Decrypt the bodies of the provided notifications, if encrypted.
+///
+async fn open_bodies(
+    mut notifications: Vec<Notification>,
+) -> Vec<Notification> {
+    for n in &mut notifications {
+        n.body = vault::open(&n.body).await;
+    }
+    notifications
+}
+
+/// Mark notification `id` as having been delivered.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The notification's ID.
+///
+pub async fn mark_delivered(db: &DB, id: &i64) {
+    sqlx::query("UPDATE notifications SET delivered_at = ? WHERE id = ?")
+        .bind(chrono::Utc::now().timestamp())
+        .bind(&id)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to mark notification {} delivered: {}", id, err);
+        });
+}
+
+/// Obtain a page of delivered notifications, most recent first.
+/// Notifications about bot-authored issues are left out if bots are hidden.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `unread_only` - Whether to only return notifications not yet read.
+/// * `page` - The page to obtain.
+///
+pub async fn get_notifications(
+    db: &DB,
+    unread_only: bool,
+    page: &PageRequest,
+) -> Page<Notification> {
+    let query = format!(
+        "
+        SELECT * FROM notifications
+        WHERE
+            delivered_at IS NOT NULL AND (? = 0 OR is_read = 0) AND {} AND {}
+        ORDER BY created_at DESC, id DESC
+        LIMIT ?
+        ",
+        issue_filter(&db).await,
+        PageRequest::condition("created_at", "id")
+    );
+
+    let (key, id) = page.bind_values();
+    match sqlx::query_as::<_, Notification>(&query)
+        .bind(&unread_only)
+        .bind(&key)
+        .bind(&id)
+        .bind(page.fetch_limit())
+        .fetch_all(db.pool())
+        .await
+    {
+        Ok(res) => {
+            page.into_page(open_bodies(res).await, |n| (n.created_at, n.id))
+        }
+        Err(err) => {
+            panic!("Unable to obtain notifications: {}", err);
+        }
+    }
+}
+
+/// Mark notification `id` as having been read.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `id` - The notification's ID.
+///
+pub async fn mark_read(db: &DB, id: &i64) {
+    sqlx::query("UPDATE notifications SET is_read = 1 WHERE id = ?")
+        .bind(&id)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to mark notification {} read: {}", id, err);
+        });
+}
+
+/// Parse a `HH:MM` time into minutes since midnight.
+///
+fn parse_time(value: &str) -> Option<u32> {
+    let (h, m) = value.trim().split_once(':')?;
+    let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+    if h > 23 || m > 59 {
+        return None;
+    }
+    Some(h * 60 + m)
+}
+
+/// Check whether `now` falls within working hours `hours` (e.g.,
+/// `09:00-17:00`) on one of the working days `days` (e.g., `mon,tue`).
+/// Malformed settings are treated as always being within working hours, so
+/// digests are not held back forever.
+///
+fn is_working_time(
+    now: &chrono::DateTime<chrono::Local>,
+    hours: &str,
+    days: &str,
+) -> bool {
+    let range = hours
+        .split_once('-')
+        .and_then(|(start, end)| Some((parse_time(start)?, parse_time(end)?)));
+    let (start, end) = match range {
+        Some(r) => r,
+        None => {
+            println!("invalid working hours '{}'", hours);
+            return true;
+        }
+    };
+
+    let weekday = now.weekday().to_string().to_lowercase();
+    if !days.split(',').any(|d| d.trim().to_lowercase() == weekday) {
+        return false;
+    }
+
+    let minutes = now.hour() * 60 + now.minute();
+    if start <= end {
+        minutes >= start && minutes < end
+    } else {
+        // working hours span midnight.
+        minutes >= start || minutes < end
+    }
+}
+
+/// Obtain the notifications due for delivery. Unless batching is enabled,
+/// every undelivered notification is due immediately. With batching,
+/// notifications below high priority are held until we're within working
+/// hours and the last digest is older than the configured interval.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_deliveries(db: &DB) -> Deliveries {
+    let undelivered = get_undelivered(&db).await;
+
+    let batching = config::get_setting_or(&db, SETTING_BATCHING, "false").await;
+    if batching != "true" {
+        return Deliveries {
+            immediate: undelivered,
+            digest: vec![],
+        };
+    }
+
+    let (immediate, held): (Vec<Notification>, Vec<Notification>) = undelivered
+        .into_iter()
+        .partition(|n| n.priority >= PRIORITY_HIGH);
+    if held.is_empty() {
+        return Deliveries {
+            immediate,
+            digest: vec![],
+        };
+    }
+
+    let hours = config::get_setting_or(
+        &db,
+        SETTING_WORKING_HOURS,
+        DEFAULT_WORKING_HOURS,
+    )
+    .await;
+    let days =
+        config::get_setting_or(&db, SETTING_WORKING_DAYS, DEFAULT_WORKING_DAYS)
+            .await;
+    let interval = config::get_setting_or(
+        &db,
+        SETTING_DIGEST_MINUTES,
+        &DEFAULT_DIGEST_MINUTES.to_string(),
+    )
+    .await
+    .parse::<i64>()
+    .unwrap_or(DEFAULT_DIGEST_MINUTES);
+
+    // the last digest is the last time we delivered a held notification.
+    let last_digest = match sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(delivered_at) FROM notifications WHERE priority < ?",
+    )
+    .bind(&PRIORITY_HIGH)
+    .fetch_one(db.pool())
+    .await
+    {
+        Ok(res) => res.unwrap_or(0),
+        Err(err) => {
+            panic!("Unable to obtain last digest: {}", err);
+        }
+    };
+
+    let now = chrono::Local::now();
+    let due = is_working_time(&now, &hours, &days)
+        && now.timestamp() - last_digest >= interval * 60;
+
+    Deliveries {
+        immediate,
+        digest: if due { held } else { vec![] },
+    }
+}
+
+/// Add a routing rule, returning its ID.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `rule` - The rule to add. Its `id` is ignored.
+///
+pub async fn add_rule(
+    db: &DB,
+    rule: &NotificationRule,
+) -> Result<i64, GHDError> {
+    if !ROUTES.contains(&rule.action.as_str()) {
+        println!("invalid notification rule action '{}'", rule.action);
+        return Err(GHDError::BadRequest);
+    }
+
+    match sqlx::query(
+        "
+        INSERT INTO notification_rules (
+            account_id, repo_owner, repo_name, label, action
+        ) VALUES (?, ?, ?, ?, ?)
+        ",
+    )
+    .bind(&rule.account_id)
+    .bind(&rule.repo_owner)
+    .bind(&rule.repo_name)
+    .bind(&rule.label)
+    .bind(&rule.action)
+    .execute(db.pool())
+    .await
+    {
+        Ok(res) => Ok(res.last_insert_rowid()),
+        Err(err) => {
+            panic!("Unable to add notification rule: {}", err);
+        }
+    }
+}
+
+/// Replace routing rule `rule.id` with `rule`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `rule` - The rule's new contents.
+///
+pub async fn update_rule(
+    db: &DB,
+    rule: &NotificationRule,
+) -> Result<(), GHDError> {
+    if !ROUTES.contains(&rule.action.as_str()) {
+        println!("invalid notification rule action '{}'", rule.action);
+        return Err(GHDError::BadRequest);
+    }
+
+    match sqlx::query(
+        "
+        UPDATE notification_rules
+        SET account_id = ?, repo_owner = ?, repo_name = ?, label = ?, action = ?
+        WHERE id = ?
+        ",
+    )
+    .bind(&rule.account_id)
+    .bind(&rule.repo_owner)
+    .bind(&rule.repo_name)
+    .bind(&rule.label)
+    .bind(&rule.action)
+    .bind(&rule.id)
+    .execute(db.pool())
+    .await
+    {
+        Ok(res) if res.rows_affected() == 0 => Err(GHDError::NotFoundError),
+        Ok(_) => Ok(()),
+        Err(err) => {
+            panic!("Unable to update notification rule {}: {}", rule.id, err);
+        }
+    }
+}
+
+/// Remove routing rule `ruleid`.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `ruleid` - The rule's ID.
+///
+pub async fn remove_rule(db: &DB, ruleid: &i64) {
+    sqlx::query("DELETE FROM notification_rules WHERE id = ?")
+        .bind(&ruleid)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to remove notification rule {}: {}", ruleid, err);
+        });
+}
+
+/// Obtain the routing rules of account `account_id`, in the order they were
+/// added.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `account_id` - The account's ID.
+///
+pub async fn get_rules(db: &DB, account_id: &i64) -> Vec<NotificationRule> {
+    match sqlx::query_as::<_, NotificationRule>(
+        "SELECT * FROM notification_rules WHERE account_id = ? ORDER BY id",
+    )
+    .bind(&account_id)
+    .fetch_all(db.pool())
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain notification rules: {}", err);
+        }
+    }
+}
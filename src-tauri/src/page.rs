@@ -0,0 +1,230 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::errors::GHDError;
+
+/// Number of entries per page, if not otherwise requested.
+///
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Maximum number of entries per page, regardless of what was requested.
+///
+pub const MAX_PAGE_SIZE: i64 = 1000;
+
+/// A page of a listing, along with the cursor to obtain the next page with,
+/// if there is one.
+///
+#[derive(serde::Serialize)]
+pub struct Page<T> {
+    pub entries: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// A position in a listing sorted by a key and then by ID, both descending;
+/// for listings ranked in memory, the key is the number of entries before
+/// said position. Handed to the frontend as an opaque string.
+///
+struct Cursor {
+    key: i64,
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(self: &Self) -> String {
+        format!("{:016x}{:016x}", self.key as u64, self.id as u64)
+    }
+
+    fn decode(cursor: &String) -> Result<Cursor, GHDError> {
+        if cursor.len() != 32 || !cursor.is_ascii() {
+            return Err(GHDError::BadRequest);
+        }
+        let parse = |s: &str| {
+            u64::from_str_radix(s, 16)
+                .map(|v| v as i64)
+                .map_err(|_| GHDError::BadRequest)
+        };
+
+        Ok(Cursor {
+            key: parse(&cursor[..16])?,
+            id: parse(&cursor[16..])?,
+        })
+    }
+}
+
+/// A request for a page of a listing.
+///
+pub struct PageRequest {
+    after: Option<Cursor>,
+    limit: i64,
+}
+
+impl PageRequest {
+    /// Obtain a new PageRequest, starting after `cursor` if provided, or at
+    /// the start of the listing otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor returned along with the previous page.
+    /// * `limit` - Maximum number of entries in the page.
+    ///
+    pub fn new(
+        cursor: &Option<String>,
+        limit: &Option<i64>,
+    ) -> Result<Self, GHDError> {
+        let after = match cursor {
+            Some(c) => Some(Cursor::decode(&c)?),
+            None => None,
+        };
+
+        Ok(PageRequest {
+            after,
+            limit: limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+        })
+    }
+
+    /// Obtain the SQL condition restricting a listing sorted by the `key`
+    /// and `id` columns to this page. Must be bound with `bind_values()`,
+    /// followed by `LIMIT ?` bound with `fetch_limit()`.
+    ///
+    pub fn condition(key: &str, id: &str) -> String {
+        format!("({}, {}) < (?, ?)", key, id)
+    }
+
+    /// Same as `condition()`, for queries using numbered parameters; the
+    /// condition uses parameters `first` and `first + 1`, leaving `first + 2`
+    /// for the limit.
+    ///
+    pub fn numbered_condition(key: &str, id: &str, first: usize) -> String {
+        format!("({}, {}) < (?{}, ?{})", key, id, first, first + 1)
+    }
+
+    /// Obtain the values to bind to the page's condition, in order.
+    ///
+    pub fn bind_values(self: &Self) -> (i64, i64) {
+        match &self.after {
+            Some(c) => (c.key, c.id),
+            None => (i64::MAX, i64::MAX),
+        }
+    }
+
+    /// Obtain how many entries to fetch; one more than the page holds, to
+    /// tell whether there's a next page.
+    ///
+    pub fn fetch_limit(self: &Self) -> i64 {
+        self.limit + 1
+    }
+
+    /// Turn the fetched `entries` into a page, given a function obtaining
+    /// the sort key and ID of an entry.
+    ///
+    pub fn into_page<T, F>(
+        self: &Self,
+        mut entries: Vec<T>,
+        sort_key: F,
+    ) -> Page<T>
+    where
+        F: Fn(&T) -> (i64, i64),
+    {
+        let mut next_cursor = None;
+        if entries.len() as i64 > self.limit {
+            entries.truncate(self.limit as usize);
+            next_cursor = entries.last().map(|e| {
+                let (key, id) = sort_key(e);
+                Cursor { key, id }.encode()
+            });
+        }
+
+        Page {
+            entries,
+            next_cursor,
+        }
+    }
+
+    /// Same as `into_page()`, for listings where consecutive entries may
+    /// share a sort key and ID; e.g., one entry per account for the same
+    /// Pull Request. Those count as a single entry towards the limit, and
+    /// are never split across pages.
+    ///
+    pub fn into_grouped_page<T, F>(
+        self: &Self,
+        mut entries: Vec<T>,
+        sort_key: F,
+    ) -> Page<T>
+    where
+        F: Fn(&T) -> (i64, i64),
+    {
+        let mut groups = 0;
+        let mut last = None;
+        let mut end = None;
+        for (idx, entry) in entries.iter().enumerate() {
+            let key = sort_key(entry);
+            if last == Some(key) {
+                continue;
+            }
+            if groups == self.limit {
+                end = Some(idx);
+                break;
+            }
+            groups += 1;
+            last = Some(key);
+        }
+
+        let mut next_cursor = None;
+        if let Some(end) = end {
+            entries.truncate(end);
+            next_cursor = last.map(|(key, id)| Cursor { key, id }.encode());
+        }
+
+        Page {
+            entries,
+            next_cursor,
+        }
+    }
+
+    /// Obtain how many entries precede this page, for listings ranked in
+    /// memory rather than sorted by a column; e.g., search results.
+    ///
+    pub fn position(self: &Self) -> i64 {
+        match &self.after {
+            Some(c) => c.key.max(0),
+            None => 0,
+        }
+    }
+
+    /// Turn the first `position() + fetch_limit()` entries of a listing
+    /// ranked in memory into a page.
+    ///
+    pub fn into_ranked_page<T>(self: &Self, entries: Vec<T>) -> Page<T> {
+        let start = self.position();
+        let mut entries: Vec<T> =
+            entries.into_iter().skip(start as usize).collect();
+
+        let mut next_cursor = None;
+        if entries.len() as i64 > self.limit {
+            entries.truncate(self.limit as usize);
+            next_cursor = Some(
+                Cursor {
+                    key: start + self.limit,
+                    id: 0,
+                }
+                .encode(),
+            );
+        }
+
+        Page {
+            entries,
+            next_cursor,
+        }
+    }
+}
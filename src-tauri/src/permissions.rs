@@ -0,0 +1,193 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{config, db::DB, errors::GHDError};
+
+/// Setting disabling every capability, e.g. on shared machines.
+///
+pub const SETTING_READ_ONLY: &str = "permissions.read_only";
+
+/// Setting holding the comma-separated capabilities disabled on top of
+/// those disabled by read-only mode; e.g., `merge,delete`.
+///
+pub const SETTING_DISABLED: &str = "permissions.disabled";
+
+/// Prefixes of the settings that can't be changed through the frontend while
+/// in read-only mode: the permissions themselves, and mail settings, which
+/// name a command to run and where its output goes.
+///
+const READ_ONLY_SETTING_PREFIXES: [&str; 2] = ["permissions.", "mail."];
+
+/// What a command may do beyond reading. Commands requiring a disabled
+/// capability are refused by their handlers.
+///
+#[derive(Clone, Copy, PartialEq)]
+pub enum Capability {
+    /// Writing to GitHub, e.g. commenting on or reviewing a Pull Request.
+    Write,
+    /// Merging Pull Requests.
+    Merge,
+    /// Deleting local data, e.g. untracking a repository.
+    Delete,
+}
+
+const CAPABILITIES: [Capability; 3] =
+    [Capability::Write, Capability::Merge, Capability::Delete];
+
+/// Capability required by each gated command, for those listing commands to
+/// be run, e.g. `list_actions`.
+///
+const COMMAND_CAPABILITIES: [(&str, Capability); 24] = [
+    ("pr_comment", Capability::Write),
+    ("pr_review", Capability::Write),
+    ("draft_review_submit", Capability::Write),
+    ("reaction_add", Capability::Write),
+    ("reaction_remove", Capability::Write),
+    ("release_notes_push", Capability::Write),
+    ("pr_merge", Capability::Merge),
+    ("outbox_retry", Capability::Write),
+    ("outbox_discard", Capability::Delete),
+    ("remove_repo", Capability::Delete),
+    ("triage_remove_rule", Capability::Delete),
+    ("remove_notification_rule", Capability::Delete),
+    ("path_view_remove", Capability::Delete),
+    ("draft_review_remove_comment", Capability::Delete),
+    ("draft_review_discard", Capability::Delete),
+    ("identity_remove", Capability::Delete),
+    ("remove_tracked_user", Capability::Delete),
+    ("remove_issue_tag", Capability::Delete),
+    ("anon_untrack", Capability::Delete),
+    ("label_alert_remove", Capability::Delete),
+    ("workspace_remove", Capability::Delete),
+    ("mail_send_digest", Capability::Write),
+    ("sla_remove_rule", Capability::Delete),
+    ("reply_template_remove", Capability::Delete),
+];
+
+/// Whether a capability is currently allowed.
+///
+#[derive(serde::Serialize)]
+pub struct CapabilityStatus {
+    pub name: String,
+    pub allowed: bool,
+}
+
+impl Capability {
+    pub fn name(self: &Self) -> &'static str {
+        match self {
+            Capability::Write => "write",
+            Capability::Merge => "merge",
+            Capability::Delete => "delete",
+        }
+    }
+
+    /// Obtain the capability required to run an outbox action of `kind`.
+    ///
+    pub fn for_pull_action(kind: &str) -> Self {
+        match kind {
+            "merge" => Capability::Merge,
+            _ => Capability::Write,
+        }
+    }
+
+    /// Obtain the capability required to run `command`, if any.
+    ///
+    pub fn for_command(command: &str) -> Option<Self> {
+        COMMAND_CAPABILITIES
+            .iter()
+            .find(|(c, _)| *c == command)
+            .map(|(_, cap)| *cap)
+    }
+}
+
+/// Check whether read-only mode is enabled.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn is_read_only(db: &DB) -> bool {
+    config::get_setting_or(&db, SETTING_READ_ONLY, "false").await == "true"
+}
+
+/// Check whether capability `cap` is allowed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `cap` - The capability to check.
+///
+pub async fn is_allowed(db: &DB, cap: Capability) -> bool {
+    if is_read_only(&db).await {
+        return false;
+    }
+    let disabled = config::get_setting_or(&db, SETTING_DISABLED, "").await;
+    !disabled.split(',').any(|c| c.trim() == cap.name())
+}
+
+/// Refuse with `GHDError::PermissionDeniedError` if capability `cap` is not
+/// allowed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `cap` - The capability required.
+///
+pub async fn check(db: &DB, cap: Capability) -> Result<(), GHDError> {
+    match is_allowed(&db, cap).await {
+        true => Ok(()),
+        false => {
+            println!("refusing to run command requiring '{}'", cap.name());
+            Err(GHDError::PermissionDeniedError)
+        }
+    }
+}
+
+/// Check whether setting `key` may be changed. Permission and mail settings
+/// can't be changed through the frontend while in read-only mode, so that
+/// the mode can only be lifted, or worked around by running a different
+/// `sendmail`, by whoever configured it.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `key` - The setting's key.
+///
+pub async fn check_setting(db: &DB, key: &str) -> Result<(), GHDError> {
+    let guarded = READ_ONLY_SETTING_PREFIXES
+        .iter()
+        .any(|p| key.starts_with(p));
+    if guarded && is_read_only(&db).await {
+        println!("refusing to change '{}' in read-only mode", key);
+        return Err(GHDError::PermissionDeniedError);
+    }
+    Ok(())
+}
+
+/// Obtain whether each capability is currently allowed.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_capabilities(db: &DB) -> Vec<CapabilityStatus> {
+    let mut res = vec![];
+    for cap in CAPABILITIES {
+        res.push(CapabilityStatus {
+            name: cap.name().to_string(),
+            allowed: is_allowed(&db, cap).await,
+        });
+    }
+    res
+}
@@ -0,0 +1,216 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use regex::Regex;
+
+/// Setting holding the time, in milliseconds, past which a query is logged
+/// as slow. Read when connecting to the database.
+///
+pub const SETTING_SLOW_QUERY_MS: &str = "db.slow_query_ms";
+
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 100;
+
+/// A query that was slow at least once, with the values in it redacted.
+///
+#[derive(Clone, serde::Serialize)]
+pub struct SlowQuery {
+    pub sql: String,
+    pub count: i64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+    pub last_at: i64,
+}
+
+/// Slow queries seen since start, by redacted SQL. Kept in memory: recording
+/// them in the database would only add to its load.
+///
+static SLOW_QUERIES: Mutex<Option<HashMap<String, SlowQuery>>> =
+    Mutex::new(None);
+
+/// Environment variable holding the level past which records other than
+/// sqlx's are written to stderr, e.g. `debug`.
+///
+pub const ENV_LOG_LEVEL: &str = "GHD_LOG";
+
+const DEFAULT_LOG_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+
+/// Captures the slow statements sqlx logs, which are only ever logged at the
+/// `Warn` level as set up by `db::DB::connect()`, and writes every other
+/// record up to `level` to stderr. Other sqlx records are ignored.
+///
+struct SlowQueryLogger {
+    level: log::LevelFilter,
+}
+
+static LOGGER: OnceLock<SlowQueryLogger> = OnceLock::new();
+
+/// Patterns used on every slow statement, compiled on first use.
+///
+static ELAPSED_RE: OnceLock<Regex> = OnceLock::new();
+static STRINGS_RE: OnceLock<Regex> = OnceLock::new();
+static NUMBERS_RE: OnceLock<Regex> = OnceLock::new();
+static SPACES_RE: OnceLock<Regex> = OnceLock::new();
+
+impl log::Log for SlowQueryLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        match metadata.target() {
+            "sqlx::query" => metadata.level() <= log::Level::Warn,
+            t if t.starts_with("sqlx") => false,
+            _ => metadata.level() <= self.level,
+        }
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.target() {
+            "sqlx::query" => record_statement(&record.args().to_string()),
+            target => {
+                eprintln!("[{} {}] {}", record.level(), target, record.args())
+            }
+        };
+    }
+
+    fn flush(&self) {}
+}
+
+/// Start capturing slow queries, and logging everything else.
+///
+pub fn init() {
+    let level = std::env::var(ENV_LOG_LEVEL)
+        .ok()
+        .and_then(|v| v.parse::<log::LevelFilter>().ok())
+        .unwrap_or(DEFAULT_LOG_LEVEL);
+    let logger = LOGGER.get_or_init(|| SlowQueryLogger { level });
+    if let Err(err) = log::set_logger(logger) {
+        println!("unable to capture slow queries: {}", err);
+        return;
+    }
+    log::set_max_level(std::cmp::max(level, log::LevelFilter::Warn));
+}
+
+/// Record a statement as logged by sqlx, i.e. its summary, the rows it
+/// affected and returned, how long it took, and its full SQL if longer than
+/// the summary.
+///
+fn record_statement(msg: &String) {
+    let elapsed_re = ELAPSED_RE
+        .get_or_init(|| Regex::new(r"elapsed: ([0-9.]+)(ns|µs|ms|s)").unwrap());
+    let elapsed_ms = match elapsed_re.captures(&msg) {
+        Some(c) => {
+            let value = c[1].parse::<f64>().unwrap_or(0.0);
+            match &c[2] {
+                "ns" => value / 1_000_000.0,
+                "µs" => value / 1_000.0,
+                "ms" => value,
+                _ => value * 1_000.0,
+            }
+        }
+        None => return,
+    };
+    let sql = match msg.split_once("\n\n") {
+        Some((_, sql)) => sql,
+        None => msg.split("; rows affected").next().unwrap_or(""),
+    };
+    let sql = redact(sql);
+
+    let now = chrono::Utc::now().timestamp();
+    let mut guard = SLOW_QUERIES.lock().unwrap();
+    let entry = guard
+        .get_or_insert_with(HashMap::new)
+        .entry(sql.clone())
+        .or_insert(SlowQuery {
+            sql,
+            count: 0,
+            max_ms: 0.0,
+            total_ms: 0.0,
+            last_at: now,
+        });
+    entry.count += 1;
+    entry.total_ms += elapsed_ms;
+    entry.max_ms = entry.max_ms.max(elapsed_ms);
+    entry.last_at = now;
+}
+
+/// Redact the values in `sql`, i.e. string and numeric literals, leaving the
+/// query's shape. Bound parameters are never logged to begin with.
+///
+fn redact(sql: &str) -> String {
+    let strings =
+        STRINGS_RE.get_or_init(|| Regex::new(r"'(?:[^']|'')*'").unwrap());
+    // leave numbered placeholders, e.g. `?10`, be.
+    let numbers = NUMBERS_RE
+        .get_or_init(|| Regex::new(r"([^?\w])[0-9]+(?:\.[0-9]+)?\b").unwrap());
+    let spaces = SPACES_RE.get_or_init(|| Regex::new(r"\s+").unwrap());
+
+    let sql = strings.replace_all(&sql, "'?'");
+    let sql = numbers.replace_all(&sql, "${1}?");
+    spaces.replace_all(&sql, " ").trim().to_string()
+}
+
+/// Obtain the `limit` slowest queries seen since start, by their slowest run.
+///
+/// # Arguments
+///
+/// * `limit` - How many queries to return.
+///
+pub fn get_slow_queries(limit: usize) -> Vec<SlowQuery> {
+    let guard = SLOW_QUERIES.lock().unwrap();
+    let mut entries: Vec<SlowQuery> = match guard.as_ref() {
+        Some(res) => res.values().cloned().collect(),
+        None => vec![],
+    };
+    entries.sort_by(|a, b| b.max_ms.total_cmp(&a.max_ms));
+    entries.truncate(limit);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_cases() {
+        let cases = [
+            (
+                "SELECT * FROM users WHERE login = 'octocat'",
+                "SELECT * FROM users WHERE login = '?'",
+            ),
+            (
+                "SELECT * FROM issues WHERE title = 'it''s' AND id = 42",
+                "SELECT * FROM issues WHERE title = '?' AND id = ?",
+            ),
+            ("SELECT 1.5, -3", "SELECT ?, -?"),
+            (
+                "SELECT * FROM t WHERE a = ?1 AND b = ?10",
+                "SELECT * FROM t WHERE a = ?1 AND b = ?10",
+            ),
+            ("SELECT utf8, t2.col FROM t2", "SELECT utf8, t2.col FROM t2"),
+            (
+                "SELECT *\n    FROM issues\n    LIMIT 10  ",
+                "SELECT * FROM issues LIMIT ?",
+            ),
+        ];
+
+        for (sql, expected) in cases {
+            assert_eq!(redact(sql), expected, "{}", sql);
+        }
+    }
+}
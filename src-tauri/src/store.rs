@@ -0,0 +1,205 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::db::{Snapshot, DB};
+
+/// Read access to settings, and to when each sync job last ran. Implemented
+/// by the SQLite database, and by its read snapshots.
+///
+/// This only covers that state: everything synced from GitHub, and the
+/// bookkeeping around it, is still read and written through SQL against the
+/// database directly, so an alternative backend can't hold it yet. Errors
+/// are the backend's own to deal with, as with the database. Sync refreshes
+/// are recorded along with what was synced, in the same transaction, and
+/// aren't part of this.
+///
+// only awaited within the app, where the futures' auto traits are known.
+#[allow(async_fn_in_trait)]
+pub trait ReadStore {
+    /// Obtain the value of setting `key`, if it has been set.
+    async fn get_setting(self: &Self, key: &str) -> Option<String>;
+
+    /// Obtain the settings whose keys start with `prefix`, ordered by key.
+    async fn get_settings(self: &Self, prefix: &str) -> Vec<(String, String)>;
+
+    /// Obtain when the sync job identified by `key` last ran, if ever.
+    async fn get_sync_refresh(self: &Self, key: &str) -> Option<i64>;
+}
+
+/// Read and write access to the state covered by `ReadStore`. Only
+/// implemented by the SQLite database; read snapshots can't be written to.
+///
+#[allow(async_fn_in_trait)]
+pub trait Store: ReadStore {
+    /// Set setting `key` to `value`.
+    async fn set_setting(self: &Self, key: &str, value: &str);
+
+    /// Forget setting `key`, if set.
+    async fn remove_setting(self: &Self, key: &str);
+
+    /// Forget when the sync jobs whose keys start with `prefix` last ran, so
+    /// they run again.
+    async fn clear_sync_refresh(self: &Self, prefix: &str);
+}
+
+impl<T: ReadStore + ?Sized> ReadStore for &T {
+    async fn get_setting(self: &Self, key: &str) -> Option<String> {
+        (**self).get_setting(key).await
+    }
+
+    async fn get_settings(self: &Self, prefix: &str) -> Vec<(String, String)> {
+        (**self).get_settings(prefix).await
+    }
+
+    async fn get_sync_refresh(self: &Self, key: &str) -> Option<i64> {
+        (**self).get_sync_refresh(key).await
+    }
+}
+
+impl<T: Store + ?Sized> Store for &T {
+    async fn set_setting(self: &Self, key: &str, value: &str) {
+        (**self).set_setting(key, value).await
+    }
+
+    async fn remove_setting(self: &Self, key: &str) {
+        (**self).remove_setting(key).await
+    }
+
+    async fn clear_sync_refresh(self: &Self, prefix: &str) {
+        (**self).clear_sync_refresh(prefix).await
+    }
+}
+
+async fn select_setting<'c, E>(conn: E, key: &str) -> Option<String>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Sqlite>,
+{
+    match sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?",
+    )
+    .bind(&key)
+    .fetch_optional(conn)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain setting '{}': {}", key, err);
+        }
+    }
+}
+
+async fn select_settings<'c, E>(conn: E, prefix: &str) -> Vec<(String, String)>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Sqlite>,
+{
+    match sqlx::query_as::<_, (String, String)>(
+        "
+        SELECT key, value FROM settings
+        WHERE SUBSTR(key, 1, ?) = ?
+        ORDER BY key
+        ",
+    )
+    .bind(prefix.len() as i64)
+    .bind(&prefix)
+    .fetch_all(conn)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain settings: {}", err);
+        }
+    }
+}
+
+async fn select_sync_refresh<'c, E>(conn: E, key: &str) -> Option<i64>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Sqlite>,
+{
+    match sqlx::query_scalar::<_, i64>(
+        "SELECT refresh_at FROM sync_refresh WHERE key = ?",
+    )
+    .bind(&key)
+    .fetch_optional(conn)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain sync refresh for '{}': {}", key, err);
+        }
+    }
+}
+
+impl ReadStore for DB {
+    async fn get_setting(self: &Self, key: &str) -> Option<String> {
+        select_setting(self.pool(), key).await
+    }
+
+    async fn get_settings(self: &Self, prefix: &str) -> Vec<(String, String)> {
+        select_settings(self.pool(), prefix).await
+    }
+
+    async fn get_sync_refresh(self: &Self, key: &str) -> Option<i64> {
+        select_sync_refresh(self.pool(), key).await
+    }
+}
+
+impl Store for DB {
+    async fn set_setting(self: &Self, key: &str, value: &str) {
+        sqlx::query(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+        )
+        .bind(&key)
+        .bind(&value)
+        .execute(self.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to set setting '{}': {}", key, err);
+        });
+    }
+
+    async fn remove_setting(self: &Self, key: &str) {
+        sqlx::query("DELETE FROM settings WHERE key = ?")
+            .bind(&key)
+            .execute(self.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to remove setting '{}': {}", key, err);
+            });
+    }
+
+    async fn clear_sync_refresh(self: &Self, prefix: &str) {
+        sqlx::query("DELETE FROM sync_refresh WHERE SUBSTR(key, 1, ?) = ?")
+            .bind(prefix.len() as i64)
+            .bind(&prefix)
+            .execute(self.pool())
+            .await
+            .unwrap_or_else(|err| {
+                panic!("Unable to reset '{}' syncs: {}", prefix, err);
+            });
+    }
+}
+
+impl ReadStore for Snapshot {
+    async fn get_setting(self: &Self, key: &str) -> Option<String> {
+        select_setting(&mut *self.conn().await, key).await
+    }
+
+    async fn get_settings(self: &Self, prefix: &str) -> Vec<(String, String)> {
+        select_settings(&mut *self.conn().await, prefix).await
+    }
+
+    async fn get_sync_refresh(self: &Self, key: &str) -> Option<i64> {
+        select_sync_refresh(&mut *self.conn().await, key).await
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Mutex;
+
+use crate::{config, db::DB, store::ReadStore};
+
+/// Setting holding when a manual sync pause ends, as a UTC epoch timestamp.
+///
+pub const SETTING_PAUSED_UNTIL: &str = "sync.paused_until";
+
+/// Setting holding when a pause following anomalous API usage ends, as a UTC
+/// epoch timestamp.
+///
+pub const SETTING_ANOMALY_PAUSED_UNTIL: &str = "sync.anomaly_paused_until";
+
+/// Setting pausing sync while on a metered connection, `true` or `false`.
+/// Enabled by default.
+///
+pub const SETTING_PAUSE_ON_METERED: &str = "sync.pause_on_metered";
+
+/// Setting pausing sync while the battery is low, `true` or `false`.
+/// Enabled by default.
+///
+pub const SETTING_PAUSE_ON_LOW_BATTERY: &str = "sync.pause_on_low_battery";
+
+/// Setting holding the battery percentage at or below which it's low.
+///
+pub const SETTING_LOW_BATTERY_PERCENT: &str = "sync.low_battery_percent";
+
+/// Setting holding the seconds to wait between sync passes while on
+/// battery or in power saving mode, rather than going right at it again.
+///
+pub const SETTING_BATTERY_DELAY_SECS: &str = "sync.battery_delay_secs";
+
+pub const REASON_MANUAL: &str = "manual";
+pub const REASON_USAGE_ANOMALY: &str = "usage_anomaly";
+pub const REASON_METERED: &str = "metered";
+pub const REASON_LOW_BATTERY: &str = "low_battery";
+pub const REASON_ON_BATTERY: &str = "on_battery";
+pub const REASON_POWER_SAVER: &str = "power_saver";
+
+const DEFAULT_LOW_BATTERY_PERCENT: i64 = 20;
+const DEFAULT_BATTERY_DELAY_SECS: i64 = 5 * 60;
+
+/// Minutes a manual pause lasts, unless told otherwise.
+///
+pub const DEFAULT_PAUSE_MINUTES: i64 = 60;
+
+/// Seconds the detected system conditions are trusted for before being
+/// detected again.
+///
+const DETECT_INTERVAL_SECS: i64 = 60;
+
+/// What the OS tells us about the conditions we're running in. Anything not
+/// available on this platform is left unset.
+///
+#[derive(Clone, Default, serde::Serialize)]
+pub struct SystemConditions {
+    pub metered: Option<bool>,
+    pub on_battery: Option<bool>,
+    pub battery_percent: Option<i64>,
+    pub power_saver: Option<bool>,
+}
+
+/// Whether background sync is paused or slowed down, and why.
+///
+#[derive(Clone, PartialEq, serde::Serialize)]
+pub struct SyncStatus {
+    pub paused: bool,
+    /// Seconds to wait between sync passes, if slowed down.
+    pub delay_secs: i64,
+    /// One of the `REASON_*` constants, if paused or slowed down.
+    pub reason: Option<String>,
+    /// When a manual pause, or a pause following anomalous usage, ends.
+    pub paused_until: Option<i64>,
+}
+
+/// The last detected conditions, and when they were detected.
+///
+static DETECTED: Mutex<Option<(i64, SystemConditions)>> = Mutex::new(None);
+
+/// Run `cmd` with `args`, returning its output if it ran successfully.
+///
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+async fn run(cmd: &str, args: &[&str]) -> Option<String> {
+    match tokio::process::Command::new(cmd).args(args).output().await {
+        Ok(res) if res.status.success() => {
+            Some(String::from_utf8_lossy(&res.stdout).to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn detect_now() -> SystemConditions {
+    let mut res = SystemConditions::default();
+
+    if let Ok(mut entries) =
+        tokio::fs::read_dir("/sys/class/power_supply").await
+    {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let read = |name: &str| {
+                std::fs::read_to_string(path.join(name))
+                    .map(|v| v.trim().to_string())
+                    .ok()
+            };
+            match read("type").as_deref() {
+                Some("Mains") => {
+                    if let Some(online) = read("online") {
+                        res.on_battery = Some(online == "0");
+                    }
+                }
+                Some("Battery") => {
+                    if res.on_battery.is_none() {
+                        if let Some(status) = read("status") {
+                            res.on_battery = Some(status == "Discharging");
+                        }
+                    }
+                    if let Some(capacity) = read("capacity") {
+                        res.battery_percent = capacity.parse::<i64>().ok();
+                    }
+                }
+                _ => {}
+            };
+        }
+    }
+
+    if let Ok(profile) =
+        tokio::fs::read_to_string("/sys/firmware/acpi/platform_profile").await
+    {
+        res.power_saver = Some(profile.trim() == "low-power");
+    }
+
+    // NetworkManager reports, per device, `yes`, `no` or `unknown`, the
+    // first two possibly followed by `(guessed)`.
+    if let Some(out) =
+        run("nmcli", &["-t", "-g", "GENERAL.METERED", "device", "show"]).await
+    {
+        res.metered = Some(out.lines().any(|l| l.contains("yes")));
+    }
+
+    res
+}
+
+#[cfg(target_os = "macos")]
+async fn detect_now() -> SystemConditions {
+    let mut res = SystemConditions::default();
+
+    if let Some(out) = run("pmset", &["-g", "batt"]).await {
+        res.on_battery = Some(out.contains("'Battery Power'"));
+        res.battery_percent = out.split_whitespace().find_map(|w| {
+            w.strip_suffix("%;").and_then(|p| p.parse::<i64>().ok())
+        });
+    }
+    if let Some(out) = run("pmset", &["-g"]).await {
+        res.power_saver = Some(out.lines().any(|l| {
+            let l = l.split_whitespace().collect::<Vec<&str>>();
+            l.len() == 2 && l[0] == "lowpowermode" && l[1] == "1"
+        }));
+    }
+
+    res
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn detect_now() -> SystemConditions {
+    SystemConditions::default()
+}
+
+/// Obtain the conditions we're running in, as detected at most
+/// `DETECT_INTERVAL_SECS` ago.
+///
+pub async fn detect() -> SystemConditions {
+    let now = chrono::Utc::now().timestamp();
+    if let Some((at, conditions)) = DETECTED.lock().unwrap().as_ref() {
+        if now - at < DETECT_INTERVAL_SECS {
+            return conditions.clone();
+        }
+    }
+
+    let conditions = detect_now().await;
+    *DETECTED.lock().unwrap() = Some((now, conditions.clone()));
+    conditions
+}
+
+async fn get_int_setting<S: ReadStore>(
+    store: &S,
+    key: &str,
+    default: i64,
+) -> i64 {
+    config::get_setting_or(store, key, &default.to_string())
+        .await
+        .parse::<i64>()
+        .unwrap_or(default)
+}
+
+/// Obtain whether background sync should currently be paused or slowed
+/// down, going by a manual pause first, then by a pause following anomalous
+/// API usage, and by what the OS tells us otherwise.
+///
+/// # Arguments
+///
+/// * `store` - The store holding the settings.
+///
+pub async fn get_status<S: ReadStore>(store: &S) -> SyncStatus {
+    let now = chrono::Utc::now().timestamp();
+    let paused_until = get_int_setting(store, SETTING_PAUSED_UNTIL, 0).await;
+    if paused_until > now {
+        return SyncStatus {
+            paused: true,
+            delay_secs: 0,
+            reason: Some(REASON_MANUAL.to_string()),
+            paused_until: Some(paused_until),
+        };
+    }
+    let anomaly_until =
+        get_int_setting(store, SETTING_ANOMALY_PAUSED_UNTIL, 0).await;
+    if anomaly_until > now {
+        return SyncStatus {
+            paused: true,
+            delay_secs: 0,
+            reason: Some(REASON_USAGE_ANOMALY.to_string()),
+            paused_until: Some(anomaly_until),
+        };
+    }
+
+    let conditions = detect().await;
+    let on_metered =
+        config::get_setting_or(store, SETTING_PAUSE_ON_METERED, "true").await;
+    let on_low =
+        config::get_setting_or(store, SETTING_PAUSE_ON_LOW_BATTERY, "true")
+            .await;
+    let low_percent = get_int_setting(
+        store,
+        SETTING_LOW_BATTERY_PERCENT,
+        DEFAULT_LOW_BATTERY_PERCENT,
+    )
+    .await;
+    let on_battery = conditions.on_battery == Some(true);
+
+    let (paused, reason) =
+        if on_metered == "true" && conditions.metered == Some(true) {
+            (true, Some(REASON_METERED))
+        } else if on_low == "true"
+            && on_battery
+            && conditions
+                .battery_percent
+                .map_or(false, |p| p <= low_percent)
+        {
+            (true, Some(REASON_LOW_BATTERY))
+        } else if conditions.power_saver == Some(true) {
+            (false, Some(REASON_POWER_SAVER))
+        } else if on_battery {
+            (false, Some(REASON_ON_BATTERY))
+        } else {
+            (false, None)
+        };
+
+    let delay_secs = match (paused, reason) {
+        (false, Some(_)) => get_int_setting(
+            store,
+            SETTING_BATTERY_DELAY_SECS,
+            DEFAULT_BATTERY_DELAY_SECS,
+        )
+        .await
+        .max(0),
+        _ => 0,
+    };
+
+    SyncStatus {
+        paused,
+        delay_secs,
+        reason: reason.map(|r| r.to_string()),
+        paused_until: None,
+    }
+}
+
+/// Pause background sync for `minutes`, or `DEFAULT_PAUSE_MINUTES` if not
+/// provided. Returns the new status.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `minutes` - How long to pause for.
+///
+pub async fn pause(db: &DB, minutes: &Option<i64>) -> SyncStatus {
+    let until = chrono::Utc::now()
+        + chrono::Duration::minutes(minutes.unwrap_or(DEFAULT_PAUSE_MINUTES));
+    config::set_setting(
+        &db,
+        SETTING_PAUSED_UNTIL,
+        &until.timestamp().to_string(),
+    )
+    .await;
+    get_status(&db).await
+}
+
+/// Pause background sync for `minutes` after API usage was found anomalous,
+/// lest the account's token be blocked. Returns the new status.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `minutes` - How long to pause for.
+///
+pub async fn pause_for_anomaly(db: &DB, minutes: i64) -> SyncStatus {
+    let until = chrono::Utc::now() + chrono::Duration::minutes(minutes);
+    config::set_setting(
+        &db,
+        SETTING_ANOMALY_PAUSED_UNTIL,
+        &until.timestamp().to_string(),
+    )
+    .await;
+    get_status(&db).await
+}
+
+/// End a manual pause of background sync, or one following anomalous API
+/// usage, if any. Returns the new status, which may still be paused by the
+/// system's conditions.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn resume(db: &DB) -> SyncStatus {
+    config::set_setting(&db, SETTING_PAUSED_UNTIL, "0").await;
+    config::set_setting(&db, SETTING_ANOMALY_PAUSED_UNTIL, "0").await;
+    get_status(&db).await
+}
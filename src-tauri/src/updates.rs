@@ -0,0 +1,370 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    config,
+    db::DB,
+    errors::GHDError,
+    gh::{accounts, refresh, releases, rest},
+};
+
+/// Setting enabling checking for new versions of ghd, `true` by default.
+///
+pub const SETTING_CHECK: &str = "update.check";
+
+/// Setting holding the `owner/name` of the repository ghd is released from.
+///
+pub const SETTING_REPO: &str = "update.repo";
+
+const DEFAULT_REPO: &str = "jecluis/ghd";
+
+/// New versions are checked for once a day.
+///
+pub const CHECK_INTERVAL: i64 = 24 * 60 * 60;
+
+const REFRESH_KEY: &str = "update_check";
+
+/// Largest update we'll download, in bytes.
+///
+const MAX_DOWNLOAD_BYTES: usize = 512 * 1024 * 1024;
+
+/// Whether a newer version of ghd than the one running has been released,
+/// as of the last check.
+///
+#[derive(Clone, Default, sqlx::FromRow, serde::Serialize)]
+pub struct UpdateStatus {
+    #[sqlx(default)]
+    pub current_version: String,
+    pub latest_version: Option<String>,
+    pub url: Option<String>,
+    pub published_at: Option<i64>,
+    pub checked_at: Option<i64>,
+    pub downloaded_path: Option<String>,
+    #[sqlx(default)]
+    pub is_available: bool,
+}
+
+/// Parse a release tag or version, e.g. `v0.3.0`, into its numeric parts.
+/// Pre-releases, e.g. `v0.4.0-rc1`, are not considered.
+///
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim_start_matches('v').split('.');
+    let res = (
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+        parts.next()?.parse().ok()?,
+    );
+    match parts.next() {
+        None => Some(res),
+        Some(_) => None,
+    }
+}
+
+/// Obtain the version of ghd running.
+///
+pub fn current_version() -> String {
+    String::from(env!("CARGO_PKG_VERSION"))
+}
+
+/// Check whether new versions of ghd are due to be checked for.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn should_check(db: &DB) -> bool {
+    config::get_setting_or(&db, SETTING_CHECK, "true").await == "true"
+        && refresh::should_refresh_sync(
+            &db,
+            &String::from(REFRESH_KEY),
+            CHECK_INTERVAL,
+        )
+        .await
+}
+
+/// Obtain the result of the last check for new versions.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn get_status(db: &DB) -> UpdateStatus {
+    let mut status = match sqlx::query_as::<_, UpdateStatus>(
+        "SELECT * FROM update_status WHERE id = 1",
+    )
+    .fetch_optional(db.pool())
+    .await
+    {
+        Ok(res) => res.unwrap_or_default(),
+        Err(err) => {
+            panic!("Unable to obtain update status: {}", err);
+        }
+    };
+
+    status.current_version = current_version();
+    status.is_available = match (
+        status.latest_version.as_deref().and_then(parse_version),
+        parse_version(&status.current_version),
+    ) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    };
+    status
+}
+
+/// Check for new versions of ghd among the releases of its repository, as
+/// the release tracker sees them, recording the latest one.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn check(db: &DB) -> Result<UpdateStatus, GHDError> {
+    let repo = config::get_setting_or(&db, SETTING_REPO, DEFAULT_REPO).await;
+    let (owner, name) = match repo.split_once('/') {
+        Some((o, n)) => (o.to_string(), n.to_string()),
+        None => return Err(GHDError::BadRequest),
+    };
+
+    let creds = accounts::get_main_credentials(&db).await?;
+    let latest = releases::fetch_releases(&creds, &owner, &name)
+        .await?
+        .into_iter()
+        .filter_map(|r| Some((parse_version(&r.tag_name)?, r)))
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, r)| r);
+
+    let now = chrono::Utc::now();
+    let mut tx = match db.pool().begin().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Error starting transaction to check for updates: {}", err);
+        }
+    };
+    if let Some(r) = &latest {
+        sqlx::query(
+            "
+            INSERT INTO update_status (
+                id, latest_version, url, published_at, checked_at
+            ) VALUES (1, ?1, ?2, ?3, ?4)
+            ON CONFLICT (id) DO UPDATE SET
+                downloaded_path = CASE
+                    WHEN latest_version = excluded.latest_version
+                    THEN downloaded_path
+                    ELSE NULL
+                END,
+                latest_version = excluded.latest_version,
+                url = excluded.url,
+                published_at = excluded.published_at,
+                checked_at = excluded.checked_at
+            ",
+        )
+        .bind(&r.tag_name)
+        .bind(&r.url)
+        .bind(&r.published_at)
+        .bind(now.timestamp())
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to record update status: {}", err);
+        });
+    }
+    refresh::update_sync_refresh(&mut tx, &String::from(REFRESH_KEY), &now)
+        .await;
+    tx.commit().await.unwrap_or_else(|err| {
+        panic!("Unable to commit update status: {}", err);
+    });
+
+    Ok(get_status(&db).await)
+}
+
+/// Check whether release asset `name` is meant for the platform we're
+/// running on, going by the bundle formats ghd is released in.
+///
+fn is_for_platform(name: &str) -> bool {
+    let name = name.to_lowercase();
+    match std::env::consts::OS {
+        "linux" => name.ends_with(".appimage") || name.ends_with(".deb"),
+        "macos" => name.ends_with(".dmg"),
+        "windows" => name.ends_with(".msi"),
+        _ => false,
+    }
+}
+
+/// Stream the body of `res` into `file`, giving up as soon as it grows past
+/// `MAX_DOWNLOAD_BYTES`.
+///
+async fn write_body(
+    mut res: reqwest::Response,
+    file: &mut tokio::fs::File,
+) -> Result<(), GHDError> {
+    if res
+        .content_length()
+        .map_or(false, |l| l as usize > MAX_DOWNLOAD_BYTES)
+    {
+        return Err(GHDError::ResponseTooLargeError);
+    }
+
+    let mut written = 0;
+    loop {
+        let chunk = match res.chunk().await {
+            Ok(Some(res)) => res,
+            Ok(None) => break,
+            Err(err) => {
+                log::error!("Unable to download update: {}", err);
+                return Err(GHDError::UnknownError);
+            }
+        };
+        written += chunk.len();
+        if written > MAX_DOWNLOAD_BYTES {
+            return Err(GHDError::ResponseTooLargeError);
+        }
+        if let Err(err) = file.write_all(&chunk).await {
+            log::error!("Unable to write update: {}", err);
+            return Err(GHDError::UnknownError);
+        }
+    }
+
+    file.sync_all().await.map_err(|err| {
+        log::error!("Unable to write update: {}", err);
+        GHDError::UnknownError
+    })
+}
+
+/// Save the body of `res` to `path`. It's written to a temporary file next
+/// to it first, only renamed into place once complete, so an interrupted
+/// download is never taken for a finished one.
+///
+async fn save(res: reqwest::Response, path: &PathBuf) -> Result<(), GHDError> {
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".part");
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = match tokio::fs::File::create(&tmp).await {
+        Ok(res) => res,
+        Err(err) => {
+            log::error!("Unable to create '{}': {}", tmp.display(), err);
+            return Err(GHDError::UnknownError);
+        }
+    };
+    let mut res = write_body(res, &mut file).await;
+    drop(file);
+
+    if res.is_ok() {
+        res = tokio::fs::rename(&tmp, &path).await.map_err(|err| {
+            log::error!("Unable to move '{}': {}", tmp.display(), err);
+            GHDError::UnknownError
+        });
+    }
+    if res.is_err() {
+        let _ = tokio::fs::remove_file(&tmp).await;
+    }
+    res
+}
+
+/// Download the latest version of ghd, as of the last check, to `dir`,
+/// returning the downloaded file's path. Nothing is installed; that's up to
+/// the user.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `dir` - The directory to download to.
+///
+pub async fn download(db: &DB, dir: &PathBuf) -> Result<String, GHDError> {
+    let status = get_status(&db).await;
+    let tag = match (&status.latest_version, status.is_available) {
+        (Some(tag), true) => tag,
+        _ => return Err(GHDError::NotFoundError),
+    };
+    if let Some(path) = &status.downloaded_path {
+        if PathBuf::from(path).exists() {
+            return Ok(path.clone());
+        }
+    }
+
+    let repo = config::get_setting_or(&db, SETTING_REPO, DEFAULT_REPO).await;
+    let creds = accounts::get_main_credentials(&db).await?;
+    let ghreq = rest::GithubRequest::new_for_host(&creds.token, &creds.host)
+        .for_feature("updates");
+    let ep = format!("/repos/{}/releases/tags/{}", repo, tag);
+    let release = match ghreq
+        .send::<rest::GithubReleaseAssetsReply>(ghreq.get(&ep))
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => return Err(rest::status_to_error(err)),
+    };
+
+    let asset = match release.assets.iter().find(|a| is_for_platform(&a.name)) {
+        Some(res) => res,
+        None => return Err(GHDError::NotFoundError),
+    };
+
+    // asset names come from the release; never let one leave `dir`.
+    let name = match std::path::Path::new(&asset.name).file_name() {
+        Some(res) => res,
+        None => return Err(GHDError::NotFoundError),
+    };
+    let res = match reqwest::get(&asset.browser_download_url).await {
+        Ok(res) if res.status().is_success() => res,
+        Ok(res) => return Err(rest::status_to_error(res.status())),
+        Err(_) => return Err(GHDError::UnknownError),
+    };
+
+    let path = dir.join(&name);
+    if let Err(err) = tokio::fs::create_dir_all(&dir).await {
+        log::error!("Unable to create '{}': {}", dir.display(), err);
+        return Err(GHDError::UnknownError);
+    }
+    save(res, &path).await?;
+    let path = path.display().to_string();
+
+    sqlx::query("UPDATE update_status SET downloaded_path = ? WHERE id = 1")
+        .bind(&path)
+        .execute(db.pool())
+        .await
+        .unwrap_or_else(|err| {
+            panic!("Unable to record downloaded update: {}", err);
+        });
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_cases() {
+        let cases = [
+            ("v0.3.0", Some((0, 3, 0))),
+            ("0.3.0", Some((0, 3, 0))),
+            ("v10.20.30", Some((10, 20, 30))),
+            ("v0.4.0-rc1", None),
+            ("v0.4", None),
+            ("v0.4.0.1", None),
+            ("release-1", None),
+            ("", None),
+        ];
+
+        for (version, expected) in cases {
+            assert_eq!(parse_version(version), expected, "{}", version);
+        }
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright 2023 Joao Eduardo Luis <joao@abysmo.io>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::{db::DB, errors::GHDError};
+
+/// Setting enabling encryption of content bodies stored from then on, such as
+/// notification bodies and queued comments, when set to `true`. Keys are per
+/// account and kept in the OS keychain; everything but the bodies is left in
+/// the clear, so it can still be queried. Bodies stored before remain
+/// readable either way.
+///
+pub const SETTING_ENCRYPT_BODIES: &str = "store.encrypt_bodies";
+
+/// Prefix marking an encrypted value, followed by the account whose key it
+/// was encrypted with and the base64 encoded nonce and ciphertext.
+///
+const PREFIX: &str = "ghd-enc:v1:";
+
+/// Service name keys are stored under in the OS keychain.
+///
+const KEYCHAIN_SERVICE: &str = "ghd";
+
+/// Shown in place of a body whose key is no longer available.
+///
+pub const UNREADABLE: &str = "[encrypted]";
+
+/// Keys obtained from the keychain so far, by account.
+///
+static KEYS: Mutex<Option<HashMap<i64, Vec<u8>>>> = Mutex::new(None);
+
+/// Outcome of looking a key up in the OS keychain.
+///
+enum KeychainLookup {
+    Found(String),
+    /// The keychain definitely holds no key for the account.
+    NotFound,
+    /// The keychain could not be asked; e.g., it's locked, or the tool to
+    /// talk to it is missing.
+    Err(String),
+}
+
+/// Seals bodies for an account, with its key obtained beforehand, so that
+/// the keychain is not waited on while holding a transaction.
+///
+pub struct Sealer {
+    account_id: i64,
+    key: Option<LessSafeKey>,
+}
+
+/// Obtain the account whose key protects content about issue `issue_id`:
+/// the account through which the issue's users are tracked, or the main
+/// account otherwise.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection to use.
+/// * `issue_id` - The issue the content is about, if any.
+///
+pub async fn account_for_issue(
+    conn: &mut sqlx::SqliteConnection,
+    issue_id: &Option<i64>,
+) -> i64 {
+    match sqlx::query_scalar::<_, i64>(
+        "
+        SELECT COALESCE(
+            (
+                SELECT MAX(user_accounts.account_id) FROM user_accounts
+                JOIN user_issues
+                ON user_issues.user_id = user_accounts.user_id
+                WHERE user_issues.issue_id = ?
+            ),
+            (SELECT user_id FROM tokens WHERE id = (SELECT MAX(id) FROM tokens)),
+            0
+        )
+        ",
+    )
+    .bind(&issue_id)
+    .fetch_one(&mut *conn)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain account for {:?}: {}", issue_id, err);
+        }
+    }
+}
+
+/// Check whether body encryption is enabled.
+///
+async fn is_enabled(conn: &mut sqlx::SqliteConnection) -> bool {
+    match sqlx::query_scalar::<_, String>(
+        "SELECT value FROM settings WHERE key = ?",
+    )
+    .bind(SETTING_ENCRYPT_BODIES)
+    .fetch_optional(&mut *conn)
+    .await
+    {
+        Ok(res) => res.map_or(false, |v| v == "true"),
+        Err(err) => {
+            panic!("Unable to obtain encryption setting: {}", err);
+        }
+    }
+}
+
+/// Obtain a `Sealer` for content about issue `issue_id`, with the key of the
+/// account returned by `account_for_issue()`, generating one if there's
+/// none. Returns `KeyUnavailableError` if encryption is enabled but the key
+/// can't be obtained, rather than have the content stored in the clear.
+/// Meant to be called before starting the transaction writing the content.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+/// * `issue_id` - The issue the content is about, if any.
+///
+pub async fn sealer(
+    db: &DB,
+    issue_id: &Option<i64>,
+) -> Result<Sealer, GHDError> {
+    let mut conn = match db.pool().acquire().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain database connection: {}", err);
+        }
+    };
+    let account_id = account_for_issue(&mut conn, &issue_id).await;
+    let enabled = is_enabled(&mut conn).await;
+    drop(conn);
+
+    let key = match enabled {
+        true => Some(get_key(&account_id, true).await?),
+        false => None,
+    };
+    Ok(Sealer { account_id, key })
+}
+
+/// Obtain a `Sealer` for content about issue `issue_id` from within a
+/// transaction, using only keys obtained before, e.g. by `load_keys()`, so
+/// as not to wait on the keychain. Returns `KeyUnavailableError` if
+/// encryption is enabled but the key hasn't been obtained.
+///
+/// # Arguments
+///
+/// * `conn` - The database connection to use.
+/// * `issue_id` - The issue the content is about, if any.
+///
+pub async fn cached_sealer(
+    conn: &mut sqlx::SqliteConnection,
+    issue_id: &Option<i64>,
+) -> Result<Sealer, GHDError> {
+    let account_id = account_for_issue(&mut *conn, &issue_id).await;
+    if !is_enabled(&mut *conn).await {
+        return Ok(Sealer {
+            account_id,
+            key: None,
+        });
+    }
+
+    match get_cached_key(&account_id) {
+        Some(key) => Ok(Sealer {
+            account_id,
+            key: Some(key),
+        }),
+        None => Err(GHDError::KeyUnavailableError),
+    }
+}
+
+/// Obtain the keys of every account, generating those there are none of, if
+/// encryption is enabled. Meant to be called outside of any transaction,
+/// so that `cached_sealer()` finds them. Keys that can't be obtained are
+/// reported, and tried again on the next call.
+///
+/// # Arguments
+///
+/// * `db` - The GHD Database handle.
+///
+pub async fn load_keys(db: &DB) {
+    let mut conn = match db.pool().acquire().await {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain database connection: {}", err);
+        }
+    };
+    if !is_enabled(&mut conn).await {
+        return;
+    }
+    let accounts = match sqlx::query_scalar::<_, i64>(
+        "SELECT DISTINCT COALESCE(user_id, 0) FROM tokens",
+    )
+    .fetch_all(&mut conn)
+    .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            panic!("Unable to obtain accounts: {}", err);
+        }
+    };
+    drop(conn);
+
+    for account_id in &accounts {
+        if let Err(err) = get_key(&account_id, true).await {
+            log::error!("unable to obtain key for {}: {:?}", account_id, err);
+        }
+    }
+}
+
+impl Sealer {
+    /// Encrypt `body` with the account's key, if encryption is enabled;
+    /// otherwise `body` is returned as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The content to encrypt.
+    ///
+    pub fn seal(self: &Self, body: &String) -> String {
+        let key = match &self.key {
+            Some(res) => res,
+            None => return body.clone(),
+        };
+
+        let rng = SystemRandom::new();
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut nonce).unwrap();
+
+        let mut data = body.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut data,
+        )
+        .unwrap();
+
+        let mut sealed = nonce.to_vec();
+        sealed.append(&mut data);
+        format!("{}{}:{}", PREFIX, self.account_id, STANDARD.encode(&sealed))
+    }
+}
+
+/// Decrypt `value` if it was encrypted by `seal()`; otherwise it's returned
+/// as is. Values whose key is no longer available come out as a placeholder.
+///
+/// # Arguments
+///
+/// * `value` - The stored value.
+///
+pub async fn open(value: &String) -> String {
+    let rest = match value.strip_prefix(PREFIX) {
+        Some(res) => res,
+        None => return value.clone(),
+    };
+
+    let (account_id, data) = match rest.split_once(':') {
+        Some((account, data)) => match account.parse::<i64>() {
+            Ok(id) => (id, data),
+            Err(_) => return String::from(UNREADABLE),
+        },
+        None => return String::from(UNREADABLE),
+    };
+    let mut sealed = match STANDARD.decode(data) {
+        Ok(res) if res.len() > NONCE_LEN => res,
+        _ => return String::from(UNREADABLE),
+    };
+    let key = match get_key(&account_id, false).await {
+        Ok(res) => res,
+        Err(_) => return String::from(UNREADABLE),
+    };
+
+    let mut data = sealed.split_off(NONCE_LEN);
+    let nonce = match Nonce::try_assume_unique_for_key(&sealed) {
+        Ok(res) => res,
+        Err(_) => return String::from(UNREADABLE),
+    };
+    match key.open_in_place(nonce, Aad::empty(), &mut data) {
+        Ok(res) => String::from_utf8_lossy(res).to_string(),
+        Err(_) => String::from(UNREADABLE),
+    }
+}
+
+/// Obtain account `account_id`'s key, if obtained from the keychain before.
+///
+fn get_cached_key(account_id: &i64) -> Option<LessSafeKey> {
+    let bytes = KEYS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|keys| keys.get(&account_id).cloned())?;
+    UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+        .ok()
+        .map(LessSafeKey::new)
+}
+
+/// Obtain account `account_id`'s key, from the keychain if we haven't yet.
+/// If `create` is set, a key is generated and stored if the keychain
+/// definitely holds none. Returns `NotFoundError` if there's no key, and
+/// `KeyUnavailableError` if the keychain can't be asked or a key can't be
+/// stored; the keychain is left alone then, lest an existing key be lost.
+///
+async fn get_key(
+    account_id: &i64,
+    create: bool,
+) -> Result<LessSafeKey, GHDError> {
+    if let Some(key) = get_cached_key(&account_id) {
+        return Ok(key);
+    }
+
+    let bytes = match keychain_get(&account_id).await {
+        KeychainLookup::Found(value) => match STANDARD.decode(value.trim()) {
+            Ok(res) => res,
+            Err(err) => {
+                log::error!("bad key in keychain for {}: {}", account_id, err);
+                return Err(GHDError::KeyUnavailableError);
+            }
+        },
+        KeychainLookup::NotFound if create => {
+            let mut bytes = vec![0u8; CHACHA20_POLY1305.key_len()];
+            SystemRandom::new().fill(&mut bytes).unwrap();
+            if !keychain_set(&account_id, &STANDARD.encode(&bytes)).await {
+                return Err(GHDError::KeyUnavailableError);
+            }
+            bytes
+        }
+        KeychainLookup::NotFound => return Err(GHDError::NotFoundError),
+        KeychainLookup::Err(err) => {
+            log::error!("unable to ask keychain for {}: {}", account_id, err);
+            return Err(GHDError::KeyUnavailableError);
+        }
+    };
+
+    let key = match UnboundKey::new(&CHACHA20_POLY1305, &bytes) {
+        Ok(res) => LessSafeKey::new(res),
+        Err(_) => {
+            log::error!("bad key in keychain for {}", account_id);
+            return Err(GHDError::KeyUnavailableError);
+        }
+    };
+    KEYS.lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(*account_id, bytes);
+    Ok(key)
+}
+
+/// Obtain the secret stored in the OS keychain for account `account_id`.
+/// Only a lookup the keychain tool reports as having found nothing counts as
+/// `NotFound`; any other failure is an error.
+///
+async fn keychain_get(account_id: &i64) -> KeychainLookup {
+    let account = account_id.to_string();
+    // exit status the tool reports a missing item with.
+    let (mut cmd, not_found) = if cfg!(target_os = "macos") {
+        let mut cmd = tokio::process::Command::new("security");
+        cmd.args(["find-generic-password", "-s", KEYCHAIN_SERVICE])
+            .args(["-a", &account, "-w"]);
+        // errSecItemNotFound
+        (cmd, 44)
+    } else if cfg!(target_os = "linux") {
+        let mut cmd = tokio::process::Command::new("secret-tool");
+        cmd.args(["lookup", "service", KEYCHAIN_SERVICE])
+            .args(["account", &account]);
+        (cmd, 1)
+    } else {
+        return KeychainLookup::Err(String::from("no supported keychain"));
+    };
+
+    let out = match cmd.output().await {
+        Ok(res) => res,
+        Err(err) => return KeychainLookup::Err(err.to_string()),
+    };
+    let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    match out.status.code() {
+        Some(0) if !out.stdout.is_empty() => KeychainLookup::Found(
+            String::from_utf8_lossy(&out.stdout).to_string(),
+        ),
+        // `secret-tool` also exits with 1 on errors, but says why.
+        Some(code) if code == not_found && out.stdout.is_empty() => {
+            if cfg!(target_os = "linux") && !stderr.is_empty() {
+                KeychainLookup::Err(stderr)
+            } else {
+                KeychainLookup::NotFound
+            }
+        }
+        _ => KeychainLookup::Err(format!("{}: {}", out.status, stderr)),
+    }
+}
+
+/// Store `secret` in the OS keychain for account `account_id`, returning
+/// whether it was stored. The secret is written to the keychain tool's
+/// stdin, never passed as an argument, so it doesn't show up in `ps`.
+///
+async fn keychain_set(account_id: &i64, secret: &String) -> bool {
+    let account = account_id.to_string();
+    let (mut cmd, input) = if cfg!(target_os = "macos") {
+        // `security` only takes the password as an argument, but reads the
+        // commands to run from stdin in interactive mode.
+        let mut cmd = tokio::process::Command::new("security");
+        cmd.arg("-i");
+        let input = format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            KEYCHAIN_SERVICE, account, secret
+        );
+        (cmd, input)
+    } else if cfg!(target_os = "linux") {
+        let mut cmd = tokio::process::Command::new("secret-tool");
+        cmd.args(["store", "--label", "GHD content key"]).args([
+            "service",
+            KEYCHAIN_SERVICE,
+            "account",
+            &account,
+        ]);
+        (cmd, secret.clone())
+    } else {
+        return false;
+    };
+
+    let res = match cmd.stdin(std::process::Stdio::piped()).spawn() {
+        Ok(mut child) => {
+            use tokio::io::AsyncWriteExt;
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(input.as_bytes()).await;
+            }
+            child.wait().await
+        }
+        Err(err) => Err(err),
+    };
+
+    match res {
+        Ok(status) => status.success(),
+        Err(err) => {
+            println!("unable to store key in keychain: {}", err);
+            false
+        }
+    }
+}
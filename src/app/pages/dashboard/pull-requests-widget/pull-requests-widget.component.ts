@@ -18,7 +18,12 @@ import {
   TauriListenerEvent,
   TauriService,
 } from "src/app/shared/services/tauri.service";
-import { GithubUser, PullRequestEntry } from "src/app/shared/types";
+import {
+  DisplayPrefs,
+  GithubUser,
+  Page,
+  PullRequestEntry,
+} from "src/app/shared/types";
 import formatDistance from "date-fns/formatDistance";
 import toDate from "date-fns/toDate";
 import { interval, map, Observable } from "rxjs";
@@ -33,6 +38,7 @@ type PRTableEntry = {
   repoName: string;
   state: string;
   lastUpdate: number;
+  lastUpdateStr: string;
   lastUpdateObs: Observable<string>;
   reviewDecision: string;
 };
@@ -65,11 +71,23 @@ export class PullRequestsWidgetComponent
   public isMarkingViewed = false;
   public markingViewed?: number;
 
+  private dateFormat?: Intl.DateTimeFormat;
+
   public constructor(private zone: NgZone, private tauriSvc: TauriService) {}
 
   public ngOnInit(): void {
     this.tauriSvc.register(TauriService.events.USER_DATA_UPDATE, this);
-    this.updateUser().then(() => {});
+    this.tauriSvc
+      .getDisplayPrefs()
+      .then((prefs: DisplayPrefs) => {
+        this.dateFormat = this.getDateFormat(prefs);
+      })
+      .catch((err) => {
+        console.error("unable to obtain display preferences: ", err);
+      })
+      .finally(() => {
+        this.updateUser().then(() => {});
+      });
   }
 
   public ngOnDestroy(): void {
@@ -112,13 +130,40 @@ export class PullRequestsWidgetComponent
     return formatDistance(updatedAt, now);
   }
 
+  public formatDate(value: number): string {
+    // we get a timestamp in seconds, but we need it in milliseconds.
+    let date = toDate(value * 1000);
+    return (this.dateFormat ?? new Intl.DateTimeFormat()).format(date);
+  }
+
+  private getDateFormat(prefs: DisplayPrefs): Intl.DateTimeFormat {
+    let options: Intl.DateTimeFormatOptions = {
+      dateStyle: prefs.date_style as Intl.DateTimeFormatOptions["dateStyle"],
+      timeStyle: "short",
+    };
+    if (prefs.timezone !== "local") {
+      options.timeZone = prefs.timezone;
+    }
+    if (prefs.hour_cycle !== "") {
+      options.hourCycle = prefs.hour_cycle as "h12" | "h23";
+    }
+    try {
+      return new Intl.DateTimeFormat(prefs.locale || undefined, options);
+    } catch (err) {
+      console.error("invalid display preferences: ", err);
+      return new Intl.DateTimeFormat();
+    }
+  }
+
   private async updateUser(): Promise<void> {
     try {
-      let prs = await this.tauriSvc.getPullRequestsByAuthor(this.user.login);
+      let prs = await this.getAllPages((cursor) =>
+        this.tauriSvc.getPullRequestsByAuthor(this.user.login, cursor),
+      );
       this.ownPRs = this.processPRs(prs);
 
-      let involved = await this.tauriSvc.getInvolvedPullRequests(
-        this.user.login,
+      let involved = await this.getAllPages((cursor) =>
+        this.tauriSvc.getInvolvedPullRequests(this.user.login, cursor),
       );
       this.involved = this.processPRs(involved);
     } catch (err) {
@@ -126,6 +171,19 @@ export class PullRequestsWidgetComponent
     }
   }
 
+  private async getAllPages(
+    getPage: (cursor?: string) => Promise<Page<PullRequestEntry>>,
+  ): Promise<PullRequestEntry[]> {
+    let entries: PullRequestEntry[] = [];
+    let cursor: string | undefined = undefined;
+    do {
+      let page: Page<PullRequestEntry> = await getPage(cursor);
+      entries.push(...page.entries);
+      cursor = page.next_cursor ?? undefined;
+    } while (!!cursor);
+    return entries;
+  }
+
   private processPRs(prs: PullRequestEntry[]): TrackedPRs {
     let toView: PRTableEntry[] = [];
     let viewed: PRTableEntry[] = [];
@@ -140,6 +198,7 @@ export class PullRequestsWidgetComponent
         url: pr.url,
         state: pr.state,
         lastUpdate: pr.updated_at,
+        lastUpdateStr: this.formatDate(pr.updated_at),
         lastUpdateObs: interval(1000).pipe(
           map(() => this.getDateDiff(pr.updated_at)),
         ),
@@ -15,7 +15,12 @@
 import { Injectable } from "@angular/core";
 import { invoke } from "@tauri-apps/api";
 import { listen } from "@tauri-apps/api/event";
-import { GithubUser, PullRequestEntry } from "../types";
+import {
+  DisplayPrefs,
+  GithubUser,
+  Page,
+  PullRequestEntry,
+} from "../types";
 
 export type TauriListenerEvent = {
   name: string;
@@ -115,11 +120,26 @@ export class TauriService {
     return invoke("pr_mark_viewed", { prid: prid });
   }
 
-  public getPullRequestsByAuthor(login: string): Promise<PullRequestEntry[]> {
-    return invoke("pr_get_list_by_author", { login: login });
+  public getPullRequestsByAuthor(
+    login: string,
+    cursor?: string,
+  ): Promise<Page<PullRequestEntry>> {
+    return invoke("pr_get_list_by_author", { login: login, cursor: cursor });
   }
 
-  public getInvolvedPullRequests(login: string): Promise<PullRequestEntry[]> {
-    return invoke("pr_get_list_by_involved", { login: login });
+  public getInvolvedPullRequests(
+    login: string,
+    cursor?: string,
+    relation?: string,
+  ): Promise<Page<PullRequestEntry>> {
+    return invoke("pr_get_list_by_involved", {
+      login: login,
+      relation: relation,
+      cursor: cursor,
+    });
+  }
+
+  public getDisplayPrefs(): Promise<DisplayPrefs> {
+    return invoke("get_display_prefs");
   }
 }
@@ -38,3 +38,21 @@ export type PullRequestEntry = {
   review_decision: string;
   merged_at?: number;
 };
+
+export type Page<T> = {
+  entries: T[];
+  next_cursor?: string;
+};
+
+export type DisplayPrefs = {
+  timezone: string;
+  locale: string;
+  hour_cycle: string;
+  date_style: string;
+};
+
+export type CommandError = {
+  kind: string;
+  message: string;
+  remediation?: string;
+};